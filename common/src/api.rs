@@ -0,0 +1,88 @@
+//! Responses of the web server's API.
+
+use serde::{Deserialize, Serialize};
+
+use crate::GameVersion;
+
+/// `/{slug}/versions/`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionInfo {
+    pub latest: GameVersion,
+    pub versions: Vec<GameVersion>,
+    /// Whether icons can be read from `/{slug}/{version}/icon/{id}/`. Servers from before it
+    /// was added don't send this.
+    #[serde(default)]
+    pub icons: bool,
+}
+
+/// Query of `/{slug}/{version}/icon/{id}/`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IconQuery {
+    /// Prefers the high resolution variant, falling back to the normal one without it
+    #[serde(default)]
+    pub hires: bool,
+    #[serde(default)]
+    pub format: IconFormat,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IconFormat {
+    #[default]
+    Png,
+    Webp,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepositoryInfo {
+    pub slug: String,
+    pub name: String,
+    pub latest: GameVersion,
+}
+
+/// `/repositories/`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepositoriesResponse {
+    pub repositories: Vec<RepositoryInfo>,
+}
+
+/// `/{slug}/{version}/exists/`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExistsResponse {
+    pub exists: Vec<bool>,
+}
+
+/// Body of `/{slug}/{version}/batch/` requests. The response is described in
+/// [`crate::batch`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchRequest {
+    pub files: Vec<String>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::{GameVersion, VersionInfo};
+
+    #[test]
+    fn versions_round_trip() {
+        let info = VersionInfo {
+            latest: GameVersion::new("2025.01.01.0000.0000").unwrap(),
+            versions: vec![
+                GameVersion::new("H2017.06.06.0000.0001a").unwrap(),
+                GameVersion::new("2025.01.01.0000.0000").unwrap(),
+            ],
+            icons: true,
+        };
+        let json = serde_json::to_string(&info).unwrap();
+        assert_eq!(
+            json,
+            r#"{"latest":"2025.01.01.0000.0000","versions":["H2017.06.06.0000.0001a","2025.01.01.0000.0000"],"icons":true}"#
+        );
+        let parsed: VersionInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.versions, info.versions);
+
+        let old: VersionInfo =
+            serde_json::from_str(r#"{"latest":"2025.01.01.0000.0000","versions":[]}"#).unwrap();
+        assert!(!old.icons);
+    }
+}
@@ -0,0 +1,126 @@
+//! Body of `/{slug}/{version}/batch/` responses: a `multipart/mixed` body with a part per
+//! requested file, in the order they were asked for. Parts give their length up front, so
+//! file data never has to be searched for the boundary.
+
+use anyhow::{Context, bail};
+
+pub const BOUNDARY: &str = "exdviewer-batch";
+
+/// Most files one batch request may ask for.
+pub const MAX_FILES: usize = 256;
+
+pub fn content_type() -> String {
+    format!("multipart/mixed; boundary={BOUNDARY}")
+}
+
+/// One file of a batch. `data` is the error message if `status` isn't 200.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Part {
+    pub path: String,
+    pub status: u16,
+    pub data: Vec<u8>,
+}
+
+impl Part {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = format!(
+            "--{BOUNDARY}\r\nContent-Location: {}\r\nContent-Length: {}\r\nStatus: {}\r\n\r\n",
+            self.path,
+            self.data.len(),
+            self.status
+        )
+        .into_bytes();
+        out.extend_from_slice(&self.data);
+        out.extend_from_slice(b"\r\n");
+        out
+    }
+
+    /// Closes the body after the last part.
+    pub fn encode_end() -> Vec<u8> {
+        format!("--{BOUNDARY}--\r\n").into_bytes()
+    }
+
+    pub fn decode_all(mut body: &[u8]) -> anyhow::Result<Vec<Self>> {
+        let delimiter = format!("--{BOUNDARY}");
+        let mut parts = Vec::new();
+        loop {
+            body = body
+                .strip_prefix(delimiter.as_bytes())
+                .context("Batch part doesn't start with the boundary")?;
+            if body.starts_with(b"--") {
+                return Ok(parts);
+            }
+            body = body
+                .strip_prefix(b"\r\n")
+                .context("Malformed batch boundary")?;
+
+            let header_end = body
+                .windows(4)
+                .position(|w| w == b"\r\n\r\n")
+                .context("Batch part headers don't end")?;
+            let headers = std::str::from_utf8(&body[..header_end])?;
+            body = &body[header_end + 4..];
+
+            let (mut path, mut length, mut status) = (None, None, 200);
+            for line in headers.split("\r\n") {
+                let Some((name, value)) = line.split_once(':') else {
+                    bail!("Malformed batch part header: {line}");
+                };
+                let value = value.trim();
+                match name.trim().to_ascii_lowercase().as_str() {
+                    "content-location" => path = Some(value.to_owned()),
+                    "content-length" => length = Some(value.parse::<usize>()?),
+                    "status" => status = value.parse()?,
+                    _ => {}
+                }
+            }
+            let length = length.context("Batch part has no length")?;
+            let end = length
+                .checked_add(2)
+                .context("Batch part length is too large")?;
+            if body.len() < end {
+                bail!("Batch part is cut off");
+            }
+            parts.push(Self {
+                path: path.context("Batch part has no path")?,
+                status,
+                data: body[..length].to_vec(),
+            });
+            body = &body[end..];
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Part;
+
+    #[test]
+    fn parts_round_trip() {
+        let parts = vec![
+            Part {
+                path: "exd/Item_0_en.exd".to_owned(),
+                status: 200,
+                // Data that looks like a boundary is still read by its length
+                data: b"\r\n--exdviewer-batch--\r\n".to_vec(),
+            },
+            Part {
+                path: "exd/Item_500_en.exd".to_owned(),
+                status: 404,
+                data: b"not found".to_vec(),
+            },
+        ];
+        let mut body = parts.iter().flat_map(Part::encode).collect::<Vec<_>>();
+        body.extend(Part::encode_end());
+        assert_eq!(Part::decode_all(&body).unwrap(), parts);
+
+        assert_eq!(Part::decode_all(&Part::encode_end()).unwrap(), vec![]);
+        assert!(Part::decode_all(&body[..body.len() - 30]).is_err());
+
+        let huge = format!(
+            "--exdviewer-batch\r\nContent-Location: a\r\nContent-Length: {}\r\n\r\n",
+            usize::MAX
+        );
+        assert!(Part::decode_all(huge.as_bytes()).is_err());
+    }
+}
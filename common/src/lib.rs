@@ -0,0 +1,13 @@
+//! What the viewer and the web server both need to agree on: game versions, game file
+//! paths, and the shapes of the web API's responses. With the `tex` feature, also how game
+//! textures are decoded, since both turn icons into images. With the `xiv` feature, how the
+//! downloader's game versions convert into the shared ones.
+
+pub mod api;
+pub mod batch;
+pub mod path;
+#[cfg(feature = "tex")]
+pub mod tex_loader;
+mod version;
+
+pub use version::GameVersion;
@@ -0,0 +1,20 @@
+/// Game path of the texture of an icon.
+pub fn get_icon_path(icon_id: u32, hires: bool) -> String {
+    format!(
+        "ui/icon/{:03}000/{:06}{}.tex",
+        icon_id / 1000,
+        icon_id,
+        if hires { "_hr1" } else { "" }
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::get_icon_path;
+
+    #[test]
+    fn icon_paths() {
+        assert_eq!(get_icon_path(0, false), "ui/icon/000000/000000.tex");
+        assert_eq!(get_icon_path(60123, true), "ui/icon/060000/060123_hr1.tex");
+    }
+}
@@ -13,7 +13,11 @@ pub fn read<R: Resource>(ironworks: &Ironworks<R>, path: &str) -> Result<Dynamic
         Err(ironworks::Error::NotFound(a)) => Err(Error::NotFound(a))?,
         other => other.context("read file")?,
     };
+    decode(texture, path)
+}
 
+/// Decodes a texture that was already read. `path` is only used in errors.
+pub fn decode(texture: tex::Texture, path: &str) -> Result<DynamicImage> {
     if !matches!(texture.kind(), tex::TextureKind::D2) {
         anyhow::bail!(
             "unsupported texture dimension {:?} for path {path}",
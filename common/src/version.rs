@@ -165,3 +165,14 @@ impl Display for GameVersion {
         write!(f, "{}", self.0)
     }
 }
+
+/// The downloader's versions are formatted the same way, so they convert over their string form.
+#[cfg(feature = "xiv")]
+impl TryFrom<&xiv_core::file::version::GameVersion> for GameVersion {
+    type Error = anyhow::Error;
+
+    fn try_from(version: &xiv_core::file::version::GameVersion) -> anyhow::Result<Self> {
+        Self::new(&version.to_string())
+            .map_err(|e| anyhow::anyhow!("Invalid game version {version}: {e}"))
+    }
+}
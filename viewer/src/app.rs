@@ -1,15 +1,30 @@
-use std::{cell::OnceCell, collections::HashSet, io::Write, num::NonZero, rc::Rc, sync::Arc};
+use std::{
+    cell::OnceCell,
+    collections::{HashMap, HashSet},
+    io::Write,
+    num::NonZero,
+    rc::Rc,
+    sync::Arc,
+};
 
 #[cfg(target_arch = "wasm32")]
 use crate::utils::{PromiseKind, UnsendPromise};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{
+    control::{ControlCommand, ControlReply, ControlRequest, ControlServer},
+    settings::{CONTROL_SERVER_ENABLED, CONTROL_SERVER_PORT, WEB_CACHE_SIZE},
+    version_watch::InstallWatch,
+};
 use anyhow::Result;
 use egui::{
-    Button, CentralPanel, FontData, FontDefinitions, FontFamily, Layout, RichText, ScrollArea,
-    TextEdit, Vec2, Widget,
+    Button, CentralPanel, Color32, FontData, FontDefinitions, FontFamily, Key, Layout, Rect,
+    RichText, ScrollArea, Sense, Spinner, TextEdit, Vec2, Widget,
     containers::{menu::MenuButton, panel::Panel},
     style::ScrollStyle,
+    text::{CCursor, CCursorRange},
 };
 use egui_extras::install_image_loaders;
+use futures_util::future::{AbortHandle, Abortable};
 use ironworks::excel::Language;
 use itertools::{EitherOrBoth, Itertools};
 use lru::LruCache;
@@ -19,33 +34,105 @@ use zip::{ZipWriter, write::SimpleFileOptions};
 use crate::{
     about,
     backend::Backend,
+    bookmarks::{self, Bookmark},
+    breadcrumbs::{BreadcrumbAction, Breadcrumbs},
+    column_docs,
+    command_palette::{Command, CommandPalette, PaletteEntry},
+    compare::CompareWindow,
+    data::{memory::MemoryFileProvider, web_cache},
     editable_schema::EditableSchema,
     excel::{
         base::BaseSheet,
-        provider::{ExcelHeader, ExcelProvider},
+        provider::{EntriesStatus, ExcelHeader, ExcelProvider, ExcelSheet},
     },
+    export::{self, DiffFormat, ExportFormat, ExportOptions, SubrowShape},
+    export_job::ExportJobWindow,
+    external_links,
+    filter_builder::FilterBuilder,
+    filter_help,
+    filter_history::{self, FilterRecall},
     github::CALLBACK_PATH,
-    goto, music,
+    goto,
+    huge_operation::HugeOperation,
+    i18n::{Text, UiLanguage, tr},
+    icon_export::IconExportWindow,
+    integrity_check::IntegrityCheckWindow,
+    memory_usage::{self, SheetUsage},
+    music, operation_log,
+    outbox::{self, OutboxAction},
     pr_window::{self, PrAction, PrWindow},
+    references::ReferencesWindow,
     router::{Router, path::Path, route::RouteResponse},
+    routes::{AppQuery, RowAnchor, SheetParams, SheetQuery, cell_path, setup_path, sheet_path},
+    row_compare,
     schema::{provider::SchemaProvider, web::WebProvider},
+    schema_download::SchemaDownloadWindow,
+    schema_validation::SchemaValidationWindow,
+    scripts::ScriptsWindow,
+    sestring_inspector,
     settings::{
-        ALWAYS_HIRES, BACKEND_CONFIG, BackendConfig, CODE_SYNTAX_THEME, COLOR_THEME,
-        CURRENT_SHEET_LANGUAGES, DISPLAY_FIELD_SHOWN, EVALUATE_STRINGS, GithubSchemaBranch,
-        LANGUAGE, LOGGER_SHOWN, MISC_SHEETS_SHOWN, PR_CHANGED_ONLY, SCHEMA_EDITOR_VISIBLE,
-        SELECTED_SHEET, SHEET_FILTER_OPTIONS, SHEET_FILTERS, SHEETS_FILTER, SOLID_SCROLLBAR,
-        SORTED_BY_OFFSET, SchemaLocation, TEMP_HIGHLIGHTED_ROW, TEMP_SCROLL_TO, TEXT_MAX_LINES,
-        TEXT_USE_SCROLL, TEXT_WRAP_WIDTH,
+        ALWAYS_HIRES, BACKEND_CONFIG, BACKEND_PRESETS, BackendConfig, CODE_SYNTAX_THEME,
+        COLOR_THEME, COLUMN_DOCS_SHOWN, COLUMN_GROUP_TINTS, CURRENT_SHEET_LANGUAGES,
+        DISPLAY_FIELD_FALLBACK, DISPLAY_FIELD_SHOWN, DisplayFieldFallback, EVALUATE_STRINGS,
+        EXPORT_EVALUATE_STRINGS, EXPORT_LINK_DEPTH, EXPORT_SUBROW_SHAPE, FIRST_APPEARED_SHOWN,
+        GithubSchemaBranch, GithubSchemaLocation, HELPER_COLUMNS, ICON_AUTO_DOWNSCALE,
+        ICON_MAX_TEXTURE_SIZE, InstallLocation, LANGUAGE, LAST_VISITED_ROWS, LAYOUT_MODE,
+        LOGGER_SHOWN, MEMORY_USAGE_SHOWN, MISC_SHEETS_SHOWN, MONOSPACE_NUMBERS,
+        OPERATION_LOG_SHOWN, PAGE_BOUNDARIES_SHOWN, PR_CHANGED_ONLY, RECENT_SHEETS,
+        SCHEMA_CACHE_SIZE, SCHEMA_EDITOR_VISIBLE, SELECTED_SHEET, SHEET_CACHE_SIZE,
+        SHEET_FILTER_OPTIONS, SHEET_FILTERS, SHEET_SORTS, SHEETS_FILTER, SHOW_RAW_VALUES,
+        SOLID_SCROLLBAR, SORTED_BY_OFFSET, SchemaLocation, TABLE_FONT_SIZE, TEMP_FIND_REFERENCES,
+        TEMP_HIGHLIGHTED_ROW, TEMP_PERSIST_DISPLAY_FIELD, TEMP_RESTORE_VIEW, TEMP_SCROLL_TO,
+        TEXT_MAX_LINES, TEXT_USE_SCROLL, TEXT_WRAP_WIDTH, UI_LANGUAGE, WEB_REQUEST_LIMIT,
     },
     setup::{self, SetupWindow},
-    sheet::{CellResponse, FilterInputType, GlobalContext, MatchOptions, SheetTable, TableContext},
-    shortcuts::{GOTO_ROW, GOTO_SHEET},
+    sheet::{
+        CellResponse, FilterInputType, FirstAppearedColumn, GlobalContext, HelperFormat,
+        MatchOptions, SheetDiff, SheetTable, SheetViewState, TableContext,
+    },
+    sheet_graph::SheetGraphWindow,
+    sheet_tabs::{SheetTabs, TabAction},
+    shortcuts::{COMMAND_PALETTE, GOTO_ROW, GOTO_SHEET, PREVIOUS_SHEET},
+    telemetry,
+    timeline::RowTimeline,
+    touch_layout::{self, LayoutMode, Swipe},
     utils::{
-        CodeTheme, CollapsibleSidePanel, ColorTheme, ConvertiblePromise, FuzzyMatcher, IconManager,
-        Side, TrackedPromise, opt_slider, shortcut, tick_promises,
+        CodeTheme, CollapsibleSidePanel, ColorTheme, ConvertiblePromise, FuzzyMatcher, GameVersion,
+        IconManager, IncrementalMatcher, ProviderError, Side, TrackedPromise, error_boundary,
+        opt_slider, set_request_limit, shortcut, tick_promises, yield_to_ui,
     },
+    version_watch::{ConnectionWatch, VersionWatch},
 };
 
+const RECENT_SHEETS_LIMIT: usize = 10;
+
+/// An operation over the shown rows of a sheet, which may need confirming first.
+enum SheetOperation {
+    Export(TableContext, Vec<(u32, Option<u16>)>, ExportFormat),
+    ExportIcons(TableContext, Vec<(u32, Option<u16>)>),
+}
+
+impl SheetOperation {
+    fn table(&self) -> &TableContext {
+        match self {
+            Self::Export(table, ..) | Self::ExportIcons(table, ..) => table,
+        }
+    }
+
+    fn row_count(&self) -> usize {
+        match self {
+            Self::Export(_, rows, _) | Self::ExportIcons(_, rows) => rows.len(),
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            Self::Export(..) => "Exporting",
+            Self::ExportIcons(..) => "Exporting icons of",
+        }
+    }
+}
+
 type CachedSheetEntry = (
     Language, // language
     String,   // sheet name
@@ -59,12 +146,27 @@ type CachedSchemaEntry = String; // sheet name
 type CachedSchemaPromise = TrackedPromise<Option<Result<String>>>;
 type ConvertibleSchemaPromise = ConvertiblePromise<CachedSchemaPromise, Result<EditableSchema>>;
 
+type CachedDiffPromise = TrackedPromise<Result<SheetDiff>>;
+/// Converts to the sheet's differences against the comparison version, or the error message.
+type ConvertibleDiffPromise =
+    ConvertiblePromise<CachedDiffPromise, std::result::Result<Rc<SheetDiff>, String>>;
+
+/// The rows a "first appeared" lookup was made for: (sheet name, filter type, filter text, filter options).
+type FirstAppearedEntry = (String, FilterInputType, String, MatchOptions);
+
+type CachedFirstAppearedPromise = TrackedPromise<Result<HashMap<u32, GameVersion>>>;
+type ConvertibleFirstAppearedPromise = ConvertiblePromise<
+    CachedFirstAppearedPromise,
+    std::result::Result<Rc<HashMap<u32, GameVersion>>, String>,
+>;
+
 type CachedLanguagesPromise = TrackedPromise<Result<Vec<Language>>>;
 type ConvertibleLanguagesPromise =
     ConvertiblePromise<CachedLanguagesPromise, Result<Vec<Language>>>;
 
-/// Fuzzy-matched sheet names (name + score) cached per (filter text, show-misc) key.
-type SheetFilterData = LruCache<(String, bool), Rc<Vec<(String, i32)>>>;
+/// Fuzzy matcher over the listed sheets (name + id), keyed by (show-misc, entry count).
+/// The entry count changes while the sheet list is still being loaded.
+type SheetFilterData = Option<((bool, usize), IncrementalMatcher<(String, i32)>)>;
 
 /// Identifies which pull request a changed-schema set belongs to: (owner, repo, number).
 type ChangedSchemasKey = (String, String, u32);
@@ -146,21 +248,72 @@ pub struct App {
     setup_window: Option<setup::SetupWindow>,
     backend: Option<Backend>,
     sheet_data: LruCache<CachedSheetEntry, ConvertibleSheetPromise>,
+    /// Sheets still being fetched, to stop fetching them when cancelled
+    sheet_loads: HashMap<CachedSheetEntry, AbortHandle>,
     schema_data: LruCache<CachedSchemaEntry, ConvertibleSchemaPromise>,
     sheet_languages: LruCache<String, ConvertibleLanguagesPromise>,
+    /// A second backend on another game version that sheets are diffed against.
+    compare_backend: Option<(GameVersion, Backend)>,
+    compare_window: Option<CompareWindow>,
+    schema_download_window: Option<SchemaDownloadWindow>,
+    schema_validation_window: Option<SchemaValidationWindow>,
+    integrity_check_window: Option<IntegrityCheckWindow>,
+    sheet_graph_window: Option<SheetGraphWindow>,
+    references_window: Option<ReferencesWindow>,
+    export_job_window: Option<ExportJobWindow>,
+    icon_export_window: Option<IconExportWindow>,
+    /// Started from a menu, to run (or confirm) once the sheet isn't borrowed
+    sheet_operation: Option<SheetOperation>,
+    huge_operation: Option<HugeOperation<SheetOperation>>,
+    breadcrumbs: Breadcrumbs,
+    sheet_tabs: SheetTabs,
+    /// View of the sheet each history entry was left on, by entry id
+    view_states: LruCache<u64, SheetViewState>,
+    /// Row the open sheet was last scrolled to, when it was opened without one
+    resume_row: Option<(u32, Option<u16>)>,
+    sheet_diffs: LruCache<CachedSheetEntry, ConvertibleDiffPromise>,
+    /// `None` unless the backend has multiple versions to search through.
+    row_timeline: Option<RowTimeline>,
+    first_appeared: LruCache<FirstAppearedEntry, ConvertibleFirstAppearedPromise>,
     sheet_matcher: FuzzyMatcher,
     sheet_filter_data: SheetFilterData,
     changed_schemas: Option<(ChangedSchemasKey, ConvertibleChangedSchemasPromise)>,
     save_promise: Option<TrackedPromise<()>>,
+    export_promise: Option<TrackedPromise<()>>,
     pr_window: PrWindow,
+    /// (sheet name, text, invalid reason) of the outbox schemas in the pull request being
+    /// drafted. `None` when it's made from the modified schemas instead.
+    pr_outbox: Option<Vec<(String, String, Option<String>)>>,
+    /// (sheet name, text) of the schemas in the last submitted pull request
+    pr_files: Vec<(String, String)>,
     goto_window: Option<goto::GoToWindow>,
+    command_palette: Option<CommandPalette>,
     about_open: bool,
+    filter_help_open: bool,
+    filter_builder: Option<FilterBuilder>,
+    filter_recall: FilterRecall,
+    bookmarks_open: bool,
+    outbox_open: bool,
+    row_compare_open: bool,
+    scripts_window: Option<ScriptsWindow>,
+    version_watch: VersionWatch,
+    connection_watch: ConnectionWatch,
+    #[cfg(not(target_arch = "wasm32"))]
+    install_watch: InstallWatch,
+    /// Backend over dropped sheet files, and the name of the sheet
+    dropped_sheet: Option<TrackedPromise<Result<(Backend, String)>>>,
+    /// Set once a route is opened with `?embed=1`; hides all chrome around the sheet view.
+    embed_mode: bool,
+    /// Whether the touch layout's style is currently applied.
+    touch_layout: bool,
     music: music::MusicPlayer,
     last_system_theme: Option<egui::Theme>,
     /// `None` = Latin only
     loaded_cjk: Option<CjkFont>,
     #[cfg(target_arch = "wasm32")]
     font_promise: Option<(CjkFont, UnsendPromise<anyhow::Result<Vec<u8>>>)>,
+    #[cfg(not(target_arch = "wasm32"))]
+    control_server: Option<ControlServer>,
 }
 
 fn create_router(ctx: egui::Context) -> Result<Router<App>> {
@@ -168,8 +321,9 @@ fn create_router(ctx: egui::Context) -> Result<Router<App>> {
     builder.set_title_formatter(|title| format!("EXDViewer - {title}"));
     builder.add_route("/", App::on_setup, App::draw_setup)?;
     builder.add_route("/sheet", App::on_unnamed_sheet, App::draw_unnamed_sheet)?;
-    builder.add_route("/sheet/{*name}", App::on_named_sheet, App::draw_named_sheet)?;
+    builder.add_typed_route("/sheet/{*name}", App::on_named_sheet, App::draw_named_sheet)?;
     builder.add_route("/music", App::on_music, App::draw_music)?;
+    builder.set_entry_handler(App::on_history_entry);
     builder.add_route(
         CALLBACK_PATH,
         App::on_auth_callback,
@@ -181,23 +335,76 @@ fn create_router(ctx: egui::Context) -> Result<Router<App>> {
 impl App {
     fn draw(&mut self, ui: &mut egui::Ui) {
         let ctx = ui.ctx().clone();
-        self.router
+        let _frame_timer = telemetry::Timer::start(&ctx, "frame");
+        let router = self
+            .router
             .get_or_init(|| create_router(ctx.clone()).unwrap());
+        if !self.embed_mode && is_embed_path(&router.current_path()) {
+            self.embed_mode = true;
+        }
 
+        if !self.embed_mode && shortcut::consume(&ctx, COMMAND_PALETTE) {
+            self.command_palette = Some(CommandPalette::default());
+        }
         if shortcut::consume(&ctx, GOTO_ROW) {
             self.goto_window = Some(goto::GoToWindow::to_row());
         }
         if shortcut::consume(&ctx, GOTO_SHEET) {
             self.goto_window = Some(goto::GoToWindow::to_sheet());
         }
+        if shortcut::consume(&ctx, PREVIOUS_SHEET)
+            && let Some(sheet_name) = RECENT_SHEETS.get(&ctx).into_iter().nth(1)
+        {
+            self.navigate(sheet_path(&sheet_name, None));
+        }
+
+        let touch = touch_layout::is_touch(&ctx);
+        if touch != self.touch_layout {
+            touch_layout::apply_style(&ctx, touch);
+            self.touch_layout = touch;
+        }
 
         self.update_fonts(&ctx);
         self.update_sheet_languages(&ctx);
+        self.queue_stub_schemas(&ctx);
         self.pr_window.poll(&ctx);
-        about::draw(&ctx, &mut self.about_open);
-        self.draw_menubar(ui);
-        self.draw_logger(ui.ctx());
-        self.draw_pr_window(ui.ctx());
+        telemetry::poll(&ctx);
+        #[cfg(not(target_arch = "wasm32"))]
+        self.poll_control_server(&ctx);
+        self.poll_dropped_files(&ctx);
+        self.poll_connection_watch(&ctx);
+        if !self.embed_mode {
+            about::draw(&ctx, &mut self.about_open);
+            self.draw_filter_help(&ctx);
+            self.draw_filter_builder(&ctx);
+            sestring_inspector::draw(&ctx);
+            if let Some(path) = bookmarks::draw(&ctx, &mut self.bookmarks_open) {
+                telemetry::count(&ctx, "bookmark");
+                self.navigate(path);
+            }
+            if touch {
+                self.draw_bottom_nav(ui);
+            } else {
+                self.draw_menubar(ui);
+            }
+            self.connection_watch.draw(ui);
+            self.draw_logger(ui.ctx());
+            self.draw_operation_log(ui.ctx());
+            self.draw_memory_usage(ui.ctx());
+            self.draw_pr_window(ui.ctx());
+            self.draw_outbox(ui.ctx());
+            self.draw_compare_window(ui.ctx());
+            self.draw_schema_download_window(ui.ctx());
+            self.draw_schema_validation_window(ui.ctx());
+            self.draw_integrity_check_window(ui.ctx());
+            self.draw_sheet_graph_window(ui.ctx());
+            self.draw_references_window(ui.ctx());
+            self.draw_export_job_window(ui.ctx());
+            self.draw_icon_export_window(ui.ctx());
+            self.draw_huge_operation(ui.ctx());
+            self.draw_version_watch(ui.ctx());
+            self.draw_command_palette(ui.ctx());
+        }
 
         CentralPanel::default().show(ui, |ui| {
             self.draw_router(ui);
@@ -233,11 +440,15 @@ impl App {
     }
 
     fn navigate(&self, path: impl Into<Path>) {
-        self.router.get().unwrap().navigate(path).unwrap();
+        if let Err(e) = self.router.get().unwrap().navigate(path) {
+            log::error!("Failed to navigate: {e:?}");
+        }
     }
 
     fn navigate_replace(&self, path: impl Into<Path>) {
-        self.router.get().unwrap().replace(path).unwrap();
+        if let Err(e) = self.router.get().unwrap().replace(path) {
+            log::error!("Failed to navigate: {e:?}");
+        }
     }
 
     fn draw_goto(&mut self, ctx: &egui::Context) {
@@ -256,6 +467,7 @@ impl App {
                 }),
             ) {
                 Ok(Some(data)) => {
+                    telemetry::count(ctx, "goto");
                     let sheet = match &data {
                         EitherOrBoth::Left(sheet_name) | EitherOrBoth::Both(sheet_name, _) => {
                             Some(sheet_name.clone())
@@ -268,18 +480,7 @@ impl App {
                     };
 
                     if let Some(sheet_name) = sheet {
-                        if let Some((row, subrow)) = location {
-                            self.navigate(format!(
-                                "/sheet/{sheet_name}#R{row}{}",
-                                if let Some(subrow) = subrow {
-                                    format!(".{subrow}")
-                                } else {
-                                    String::new()
-                                }
-                            ));
-                        } else {
-                            self.navigate(format!("/sheet/{sheet_name}"));
-                        }
+                        self.navigate(sheet_path(&sheet_name, location.copied()));
                     }
                 }
                 Ok(None) => {}
@@ -299,233 +500,721 @@ impl App {
             )
             .show(ui, |ui| {
                 egui::MenuBar::new().ui(ui, |ui| {
-                    ui.menu_button("App", |ui| {
-                        if ui.button("Configure").clicked() {
-                            self.navigate("/");
-                            ui.close();
-                        }
-                        if !super::IS_WEB && ui.button("Quit").clicked() {
-                            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
-                            ui.close();
-                        }
+                    self.draw_menu_items(ui);
+                });
+            });
+    }
+
+    /// Replaces the menubar in the touch layout, with the menus tucked behind the last button.
+    fn draw_bottom_nav(&mut self, ui: &mut egui::Ui) {
+        let ctx = &ui.ctx().clone();
+        match touch_layout::swipe(ctx) {
+            Some(Swipe::Right) => CollapsibleSidePanel::set_collapsed(ctx, "sheet_list", false),
+            Some(Swipe::Left) => CollapsibleSidePanel::set_collapsed(ctx, "sheet_list", true),
+            None => {}
+        }
+
+        Panel::bottom("bottom_nav")
+            .frame(
+                egui::Frame::side_top_panel(&ctx.global_style())
+                    .fill(ctx.global_style().visuals.code_bg_color),
+            )
+            .show(ui, |ui| {
+                ui.columns(5, |columns| {
+                    let button = |ui: &mut egui::Ui, text: &str| {
+                        let size = Vec2::new(ui.available_width(), ui.spacing().interact_size.y);
+                        ui.add(Button::new(text).min_size(size))
+                    };
+
+                    if button(&mut columns[0], "☰")
+                        .on_hover_text(tr(ctx, Text::Sheets))
+                        .clicked()
+                    {
+                        let collapsed = CollapsibleSidePanel::is_collapsed(ctx, "sheet_list");
+                        CollapsibleSidePanel::set_collapsed(ctx, "sheet_list", !collapsed);
+                    }
+                    if button(&mut columns[1], "⏴")
+                        .on_hover_text(tr(ctx, Text::Back))
+                        .clicked()
+                        && let Err(e) = self.router.get().unwrap().back()
+                    {
+                        log::error!("Failed to go back: {e}");
+                    }
+                    if button(&mut columns[2], "🔍")
+                        .on_hover_text(tr(ctx, Text::GoToSheet))
+                        .clicked()
+                    {
+                        self.goto_window = Some(goto::GoToWindow::to_sheet());
+                    }
+                    if button(&mut columns[3], "🔖")
+                        .on_hover_text(tr(ctx, Text::Bookmarks))
+                        .clicked()
+                    {
+                        self.bookmarks_open = !self.bookmarks_open;
+                    }
+                    columns[4].vertical_centered_justified(|ui| {
+                        ui.menu_button("⋯", |ui| self.draw_menu_items(ui))
+                            .response
+                            .on_hover_text(tr(ctx, Text::More));
                     });
+                });
+            });
+    }
 
-                    ui.menu_button("Go", |ui| {
-                        if shortcut::button(ui, "Go to Row…", GOTO_ROW).clicked() {
-                            self.goto_window = Some(goto::GoToWindow::to_row());
-                            ui.close();
-                        }
-                        if shortcut::button(ui, "Go to Sheet…", GOTO_SHEET).clicked() {
-                            self.goto_window = Some(goto::GoToWindow::to_sheet());
+    fn draw_menu_items(&mut self, ui: &mut egui::Ui) {
+        let ctx = &ui.ctx().clone();
+        ui.menu_button(tr(ctx, Text::MenuApp), |ui| {
+            if ui.button(tr(ctx, Text::Configure)).clicked() {
+                self.navigate("/");
+                ui.close();
+            }
+            let presets = BACKEND_PRESETS.get(ctx);
+            ui.add_enabled_ui(!presets.is_empty(), |ui| {
+                ui.menu_button(tr(ctx, Text::Profiles), |ui| {
+                    let current = BACKEND_CONFIG.get(ctx);
+                    for preset in presets {
+                        let selected = current.as_ref() == Some(&preset.config);
+                        if ui.selectable_label(selected, &preset.name).clicked() {
+                            if !selected {
+                                // Setup picks up the new config and reloads straight back into the
+                                // current page
+                                BACKEND_CONFIG.set(ctx, Some(preset.config));
+                                let current = self.router.get().unwrap().current_path();
+                                self.navigate(setup_path(current));
+                            }
                             ui.close();
                         }
-                    });
+                    }
+                })
+                .response
+                .on_hover_text(tr(ctx, Text::ProfilesHover))
+                .on_disabled_hover_text("Save a profile in setup first");
+            });
+            if let Some((version, _)) = &self.compare_backend {
+                if ui
+                    .button(tr(ctx, Text::StopComparing))
+                    .on_hover_text(format!("Currently comparing against {version}"))
+                    .clicked()
+                {
+                    self.compare_backend = None;
+                    self.sheet_diffs.clear();
+                    ui.close();
+                }
+            } else if ui
+                .add_enabled(
+                    self.backend
+                        .as_ref()
+                        .is_some_and(|b| b.capabilities().supports_versions),
+                    Button::new(tr(ctx, Text::CompareVersions)),
+                )
+                .on_disabled_hover_text(
+                    "Only available with an install location that has other versions",
+                )
+                .clicked()
+            {
+                telemetry::count(ctx, "compare-versions");
+                self.compare_window = CompareWindow::new(ctx);
+                ui.close();
+            }
+            let github_source = pr_window::github_source(ctx);
+            if ui
+                .add_enabled(
+                    self.backend.is_some() && github_source.is_some(),
+                    Button::new(tr(ctx, Text::DownloadSchemas)),
+                )
+                .on_hover_text(tr(ctx, Text::DownloadSchemasHover))
+                .on_disabled_hover_text("Only available with a GitHub schema source")
+                .clicked()
+                && let Some(source) = github_source
+            {
+                self.schema_download_window = Some(SchemaDownloadWindow::new(source));
+                ui.close();
+            }
+            if ui
+                .add_enabled(
+                    self.backend.is_some(),
+                    Button::new(tr(ctx, Text::ValidateSchemas)),
+                )
+                .on_hover_text(tr(ctx, Text::ValidateSchemasHover))
+                .clicked()
+                && let Some(backend) = self.backend.clone()
+            {
+                self.schema_validation_window = Some(SchemaValidationWindow::new(backend));
+                ui.close();
+            }
+            if ui
+                .add_enabled(
+                    self.backend.is_some(),
+                    Button::new(tr(ctx, Text::CheckIntegrity)),
+                )
+                .on_hover_text(tr(ctx, Text::CheckIntegrityHover))
+                .clicked()
+                && let Some(backend) = self.backend.clone()
+            {
+                self.integrity_check_window = Some(IntegrityCheckWindow::new(backend));
+                ui.close();
+            }
+            if ui
+                .button(format!("{}…", tr(ctx, Text::SchemaOutbox)))
+                .on_hover_text(tr(ctx, Text::SchemaOutboxHover))
+                .clicked()
+            {
+                self.outbox_open = true;
+                ui.close();
+            }
+            if ui
+                .add_enabled(
+                    self.backend.is_some(),
+                    Button::new(tr(ctx, Text::SheetGraph)),
+                )
+                .on_hover_text(tr(ctx, Text::SheetGraphHover))
+                .clicked()
+                && let Some(backend) = self.backend.clone()
+            {
+                telemetry::count(ctx, "sheet-graph");
+                self.sheet_graph_window = Some(SheetGraphWindow::new(backend));
+                ui.close();
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            ui.menu_button(tr(ctx, Text::ControlServer), |ui| {
+                let mut enabled = CONTROL_SERVER_ENABLED.get(ctx);
+                if ui
+                    .checkbox(&mut enabled, tr(ctx, Text::Enabled))
+                    .on_hover_text(tr(ctx, Text::ControlServerHover))
+                    .changed()
+                {
+                    CONTROL_SERVER_ENABLED.set(ctx, enabled);
+                }
+                let mut port = CONTROL_SERVER_PORT.get(ctx);
+                ui.horizontal(|ui| {
+                    ui.label(tr(ctx, Text::Port));
+                    if ui
+                        .add(egui::DragValue::new(&mut port).range(1024..=u16::MAX))
+                        .changed()
+                    {
+                        CONTROL_SERVER_PORT.set(ctx, port);
+                    }
+                });
+            });
+            ui.menu_button(tr(ctx, Text::WebRequests), |ui| {
+                let r = opt_slider(
+                    ui,
+                    WEB_REQUEST_LIMIT.get(ctx).map(|e| e.into()),
+                    1..=64,
+                    "Max Concurrent",
+                    "No Limit",
+                    "",
+                );
+                if r.response
+                    .on_hover_text(tr(ctx, Text::WebRequestsHover))
+                    .changed()
+                {
+                    let limit = r.inner.map(|e| NonZero::new(e.get() as u8).unwrap());
+                    WEB_REQUEST_LIMIT.set(ctx, limit);
+                    set_request_limit(limit.map(|e| e.into()));
+                }
+            });
+            ui.menu_button(tr(ctx, Text::Caches), |ui| {
+                let r = opt_slider(
+                    ui,
+                    SHEET_CACHE_SIZE.get(ctx).map(|e| e.into()),
+                    1..=256,
+                    "Sheets",
+                    "No Limit",
+                    "",
+                );
+                let r2 = opt_slider(
+                    ui,
+                    SCHEMA_CACHE_SIZE.get(ctx).map(|e| e.into()),
+                    1..=1024,
+                    "Schemas",
+                    "No Limit",
+                    "",
+                );
+                if r.response
+                    .on_hover_text(tr(ctx, Text::CachesHover))
+                    .changed()
+                    || r2.response.changed()
+                {
+                    let sheets = r.inner.map(|e| NonZero::new(e.get() as u16).unwrap());
+                    SHEET_CACHE_SIZE.set(ctx, sheets);
+                    SCHEMA_CACHE_SIZE
+                        .set(ctx, r2.inner.map(|e| NonZero::new(e.get() as u16).unwrap()));
+                    self.sheet_data
+                        .resize(sheets.map_or(NonZero::<usize>::MAX, |e| e.into()));
+                }
+                #[cfg(not(target_arch = "wasm32"))]
+                {
+                    let r = opt_slider(
+                        ui,
+                        WEB_CACHE_SIZE.get(ctx).map(|e| e.into()),
+                        64..=16384,
+                        "Downloaded Data",
+                        "No Limit",
+                        " MiB",
+                    );
+                    if r.response
+                        .on_hover_text(tr(ctx, Text::DownloadedDataHover))
+                        .changed()
+                    {
+                        let size = r.inner.map(|e| NonZero::new(e.get() as u16).unwrap());
+                        WEB_CACHE_SIZE.set(ctx, size);
+                        web_cache::set_max_size(size.map(|e| u64::from(e.get()) << 20));
+                    }
+                }
+                ui.separator();
+                if ui.button(tr(ctx, Text::ClearCaches)).clicked() {
+                    self.clear_caches();
+                    ui.close();
+                }
+                if ui.button(tr(ctx, Text::ClearDownloadedData)).clicked() {
+                    clear_downloaded_data();
+                    ui.close();
+                }
+            });
+            ui.menu_button(tr(ctx, Text::ExternalLinks), external_links::draw_settings);
+            ui.menu_button(tr(ctx, Text::UsageStatistics), telemetry::draw_settings);
+            if !super::IS_WEB && ui.button(tr(ctx, Text::Quit)).clicked() {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                ui.close();
+            }
+        });
 
-                    if ui.button("Music").clicked() {
-                        self.navigate("/music");
+        ui.menu_button(tr(ctx, Text::MenuGo), |ui| {
+            if shortcut::button(ui, tr(ctx, Text::GoToRow), GOTO_ROW).clicked() {
+                self.goto_window = Some(goto::GoToWindow::to_row());
+                ui.close();
+            }
+            if shortcut::button(ui, tr(ctx, Text::GoToSheet), GOTO_SHEET).clicked() {
+                self.goto_window = Some(goto::GoToWindow::to_sheet());
+                ui.close();
+            }
+            if shortcut::button(ui, tr(ctx, Text::CommandPalette), COMMAND_PALETTE).clicked() {
+                self.command_palette = Some(CommandPalette::default());
+                ui.close();
+            }
+            let view_path = self
+                .current_sheet_table(ctx)
+                .map(|table| view_path(ctx, table));
+            if ui
+                .add_enabled(
+                    view_path.is_some(),
+                    Button::new(tr(ctx, Text::CopyViewLink)),
+                )
+                .clicked()
+                && let Some(path) = view_path
+            {
+                ctx.copy_text(self.router.get().unwrap().url_of(&path));
+                ui.close();
+            }
+            ui.menu_button(tr(ctx, Text::RecentSheets), |ui| {
+                let recent_sheets = RECENT_SHEETS.get(ctx);
+                if recent_sheets.is_empty() {
+                    ui.label(RichText::new(tr(ctx, Text::NoRecentSheets)).weak());
+                }
+                for (i, sheet_name) in recent_sheets.iter().enumerate() {
+                    let resp = if i == 1 {
+                        shortcut::button(ui, sheet_name, PREVIOUS_SHEET)
+                    } else {
+                        ui.button(sheet_name)
+                    };
+                    if resp.clicked() {
+                        self.navigate(sheet_path(&sheet_name, None));
+                        ui.close();
                     }
+                }
+            });
+        });
 
-                    ui.menu_button("Language", |ui| {
-                        let saved_lang = LANGUAGE.get(ctx);
-                        let selected_sheet = SELECTED_SHEET.get(ctx);
-                        let sheet_languages = CURRENT_SHEET_LANGUAGES
-                            .try_get(ctx)
-                            .filter(|(name, _)| Some(name.as_str()) == selected_sheet.as_deref())
-                            .map(|(_, langs)| langs);
-                        let restrict = sheet_languages
-                            .as_ref()
-                            .is_some_and(|langs| langs.iter().any(|&l| l != Language::None));
-                        for lang in Language::iter() {
-                            if lang == Language::None {
-                                continue;
-                            }
-                            let available = !restrict
-                                || sheet_languages
-                                    .as_ref()
-                                    .is_some_and(|langs| langs.contains(&lang));
-                            let response = ui.add_enabled(
-                                available,
-                                egui::Button::selectable(saved_lang == lang, lang.to_string()),
-                            );
-                            if response.clicked() {
-                                LANGUAGE.set(ctx, lang);
-                                ui.close();
-                            }
-                        }
-                    });
+        ui.menu_button(tr(ctx, Text::Bookmarks), |ui| {
+            let selected_sheet = SELECTED_SHEET.get(ctx).map(Bookmark::sheet);
+            let sheet_bookmarked = selected_sheet
+                .as_ref()
+                .is_some_and(|b| bookmarks::is_bookmarked(ctx, b));
+            if ui
+                .add_enabled(
+                    selected_sheet.is_some(),
+                    Button::new(tr(
+                        ctx,
+                        if sheet_bookmarked {
+                            Text::RemoveSheetBookmark
+                        } else {
+                            Text::BookmarkSheet
+                        },
+                    )),
+                )
+                .clicked()
+                && let Some(bookmark) = selected_sheet
+            {
+                bookmarks::toggle(ctx, bookmark);
+                ui.close();
+            }
+            if ui.button(tr(ctx, Text::ManageBookmarks)).clicked() {
+                self.bookmarks_open = true;
+                ui.close();
+            }
+            ui.separator();
+            if let Some(path) = bookmarks::draw_menu_items(ui) {
+                telemetry::count(ctx, "bookmark");
+                self.navigate(path);
+            }
+        });
 
-                    ui.menu_button("View", |ui| {
-                        ui.menu_button("Color Theme", |ui| {
-                            let mut color_theme = COLOR_THEME.get(ui.ctx());
-                            for theme in ColorTheme::themes() {
-                                if ui
-                                    .selectable_value(&mut color_theme, *theme, theme.name())
-                                    .changed()
-                                {
-                                    color_theme.apply(ui.ctx());
-                                    let solid_scrollbar = SOLID_SCROLLBAR.get(ctx);
-                                    ctx.all_styles_mut(|s| {
-                                        s.spacing.scroll = if solid_scrollbar {
-                                            ScrollStyle::solid()
-                                        } else {
-                                            ScrollStyle::default()
-                                        };
-                                    });
+        if ui.button(tr(ctx, Text::Music)).clicked() {
+            self.navigate("/music");
+        }
 
-                                    COLOR_THEME.set(ui.ctx(), color_theme);
-                                }
-                            }
-                        });
+        ui.menu_button(tr(ctx, Text::MenuLanguage), |ui| {
+            let saved_lang = LANGUAGE.get(ctx);
+            let selected_sheet = SELECTED_SHEET.get(ctx);
+            let sheet_languages = CURRENT_SHEET_LANGUAGES
+                .try_get(ctx)
+                .filter(|(name, _)| Some(name.as_str()) == selected_sheet.as_deref())
+                .map(|(_, langs)| langs);
+            let restrict = sheet_languages
+                .as_ref()
+                .is_some_and(|langs| langs.iter().any(|&l| l != Language::None));
+            for lang in Language::iter() {
+                if lang == Language::None {
+                    continue;
+                }
+                let available = !restrict
+                    || sheet_languages
+                        .as_ref()
+                        .is_some_and(|langs| langs.contains(&lang));
+                let response = ui.add_enabled(
+                    available,
+                    egui::Button::selectable(saved_lang == lang, lang.to_string()),
+                );
+                if response.clicked() {
+                    operation_log::record("Change language", [("language", lang.to_string())]);
+                    LANGUAGE.set(ctx, lang);
+                    ui.close();
+                }
+            }
+        });
 
-                        ui.menu_button("Code Theme", |ui| {
-                            let mut theme = CODE_SYNTAX_THEME.get(ui.ctx());
+        ui.menu_button(tr(ctx, Text::MenuView), |ui| {
+            ui.menu_button(tr(ctx, Text::UiLanguageMenu), |ui| {
+                let mut ui_language = UI_LANGUAGE.get(ctx);
+                for &language in UiLanguage::ALL {
+                    if ui
+                        .selectable_value(&mut ui_language, language, language.name())
+                        .changed()
+                    {
+                        UI_LANGUAGE.set(ctx, ui_language);
+                        ui.close();
+                    }
+                }
+            });
 
-                            for (id, name) in CodeTheme::themes() {
-                                if ui
-                                    .selectable_value(&mut theme.theme, id.to_string(), name)
-                                    .changed()
-                                {
-                                    CODE_SYNTAX_THEME.set(ui.ctx(), theme.clone());
-                                }
-                            }
-                        });
+            ui.menu_button(tr(ctx, Text::LayoutMode), |ui| {
+                let mut layout_mode = LAYOUT_MODE.get(ctx);
+                for &mode in LayoutMode::ALL {
+                    if ui
+                        .selectable_value(&mut layout_mode, mode, mode.name())
+                        .changed()
+                    {
+                        LAYOUT_MODE.set(ctx, layout_mode);
+                        ui.close();
+                    }
+                }
+            });
 
-                        ui.menu_button("Sort Columns by", |ui| {
-                            let mut sorted_by_offset = SORTED_BY_OFFSET.get(ctx);
-                            let r = ui.selectable_value(&mut sorted_by_offset, true, "Offset");
-                            let r =
-                                r.union(ui.selectable_value(&mut sorted_by_offset, false, "Index"));
-                            if r.changed() {
-                                ui.close();
-                                SORTED_BY_OFFSET.set(ctx, sorted_by_offset);
-                            }
+            ui.menu_button(tr(ctx, Text::ColorTheme), |ui| {
+                let mut color_theme = COLOR_THEME.get(ui.ctx());
+                for theme in ColorTheme::themes() {
+                    if ui
+                        .selectable_value(&mut color_theme, *theme, theme.name())
+                        .changed()
+                    {
+                        color_theme.apply(ui.ctx());
+                        let solid_scrollbar = SOLID_SCROLLBAR.get(ctx);
+                        ctx.all_styles_mut(|s| {
+                            s.spacing.scroll = if solid_scrollbar {
+                                ScrollStyle::solid()
+                            } else {
+                                ScrollStyle::default()
+                            };
                         });
 
-                        ui.menu_button("Text Wrapping", |ui| {
-                            let r = opt_slider(
-                                ui,
-                                TEXT_WRAP_WIDTH.get(ctx).map(|e| e.into()),
-                                50..=1000,
-                                "Max Width",
-                                "No Wrap",
-                                "px",
-                            );
-
-                            let r2 = opt_slider(
-                                ui,
-                                TEXT_MAX_LINES.get(ctx).map(|e| e.into()),
-                                1..=20,
-                                "Max Lines",
-                                "No Limit",
-                                "",
-                            );
+                        COLOR_THEME.set(ui.ctx(), color_theme);
+                    }
+                }
+            });
 
-                            if r.response.changed() || r2.response.changed() {
-                                TEXT_WRAP_WIDTH.set(
-                                    ctx,
-                                    r.inner.map(|e| NonZero::new(e.get() as u16).unwrap()),
-                                );
+            ui.menu_button(tr(ctx, Text::CodeTheme), |ui| {
+                let mut theme = CODE_SYNTAX_THEME.get(ui.ctx());
 
-                                TEXT_MAX_LINES.set(
-                                    ctx,
-                                    r2.inner.map(|e| NonZero::new(e.get() as u8).unwrap()),
-                                );
+                for (id, name) in CodeTheme::themes() {
+                    if ui
+                        .selectable_value(&mut theme.theme, id.to_string(), name)
+                        .changed()
+                    {
+                        CODE_SYNTAX_THEME.set(ui.ctx(), theme.clone());
+                    }
+                }
+            });
 
-                                for sheet in &mut self.sheet_data {
-                                    if let Ok(Ok(s)) = sheet.1.try_get_mut() {
-                                        s.invalidate_sizes(ui);
-                                    }
-                                }
-                            }
+            ui.menu_button(tr(ctx, Text::SortColumnsBy), |ui| {
+                let mut sorted_by_offset = SORTED_BY_OFFSET.get(ctx);
+                let r = ui.selectable_value(&mut sorted_by_offset, true, tr(ctx, Text::Offset));
+                let r = r.union(ui.selectable_value(
+                    &mut sorted_by_offset,
+                    false,
+                    tr(ctx, Text::Index),
+                ));
+                if r.changed() {
+                    ui.close();
+                    SORTED_BY_OFFSET.set(ctx, sorted_by_offset);
+                }
+            });
 
-                            let mut use_scroll = TEXT_USE_SCROLL.get(ctx);
-                            ui.with_layout(Layout::left_to_right(egui::Align::Center), |ui| {
-                                ui.style_mut().spacing.item_spacing.x /= 2.0;
-                                ui.set_max_width(
-                                    ui.spacing().slider_width + ui.spacing().interact_size.x,
-                                );
-                                ui.label("Show ");
-                                if ui
-                                    .selectable_label(
-                                        use_scroll,
-                                        if use_scroll { "Scrollbar" } else { "Tooltip" },
-                                    )
-                                    .clicked()
-                                {
-                                    use_scroll = !use_scroll;
-                                    TEXT_USE_SCROLL.set(ctx, use_scroll);
-                                }
-                                ui.label(" on overflow");
-                            })
-                        });
+            ui.menu_button(tr(ctx, Text::TextWrapping), |ui| {
+                let r = opt_slider(
+                    ui,
+                    TEXT_WRAP_WIDTH.get(ctx).map(|e| e.into()),
+                    50..=1000,
+                    "Max Width",
+                    "No Wrap",
+                    "px",
+                );
 
-                        {
-                            let mut solid_scrollbar = SOLID_SCROLLBAR.get(ctx);
-                            if ui
-                                .checkbox(&mut solid_scrollbar, "Solid Scrollbar")
-                                .changed()
-                            {
-                                SOLID_SCROLLBAR.set(ctx, solid_scrollbar);
-                                ctx.all_styles_mut(|s| {
-                                    s.spacing.scroll = if solid_scrollbar {
-                                        ScrollStyle::solid()
-                                    } else {
-                                        ScrollStyle::default()
-                                    };
-                                });
-                                ui.close();
-                            }
-                        }
+                let r2 = opt_slider(
+                    ui,
+                    TEXT_MAX_LINES.get(ctx).map(|e| e.into()),
+                    1..=20,
+                    "Max Lines",
+                    "No Limit",
+                    "",
+                );
 
-                        {
-                            let mut always_hires = ALWAYS_HIRES.get(ctx);
-                            if ui.checkbox(&mut always_hires, "HD Icons").changed() {
-                                ALWAYS_HIRES.set(ctx, always_hires);
-                                ui.close();
-                            }
-                        }
+                if r.response.changed() || r2.response.changed() {
+                    TEXT_WRAP_WIDTH
+                        .set(ctx, r.inner.map(|e| NonZero::new(e.get() as u16).unwrap()));
 
-                        {
-                            let mut evaluate_strings = EVALUATE_STRINGS.get(ctx);
-                            if ui
-                                .checkbox(&mut evaluate_strings, "Evaluate SeStrings")
-                                .changed()
-                            {
-                                EVALUATE_STRINGS.set(ctx, evaluate_strings);
+                    TEXT_MAX_LINES.set(ctx, r2.inner.map(|e| NonZero::new(e.get() as u8).unwrap()));
 
-                                for sheet in &mut self.sheet_data {
-                                    if let Ok(Ok(s)) = sheet.1.try_get_mut() {
-                                        s.invalidate_sizes(ui);
-                                    }
-                                }
-                            }
+                    for sheet in &mut self.sheet_data {
+                        if let Ok(Ok(s)) = sheet.1.try_get_mut() {
+                            s.invalidate_sizes(ui);
                         }
+                    }
+                }
 
-                        {
-                            let mut display_field_shown = DISPLAY_FIELD_SHOWN.get(ctx);
-                            if ui
-                                .checkbox(&mut display_field_shown, "Use Display Fields")
-                                .changed()
-                            {
-                                DISPLAY_FIELD_SHOWN.set(ctx, display_field_shown);
-                                ui.close();
-                            }
-                        }
+                let mut use_scroll = TEXT_USE_SCROLL.get(ctx);
+                ui.with_layout(Layout::left_to_right(egui::Align::Center), |ui| {
+                    ui.style_mut().spacing.item_spacing.x /= 2.0;
+                    ui.set_max_width(ui.spacing().slider_width + ui.spacing().interact_size.x);
+                    ui.label("Show ");
+                    if ui
+                        .selectable_label(
+                            use_scroll,
+                            if use_scroll { "Scrollbar" } else { "Tooltip" },
+                        )
+                        .clicked()
+                    {
+                        use_scroll = !use_scroll;
+                        TEXT_USE_SCROLL.set(ctx, use_scroll);
+                    }
+                    ui.label(" on overflow");
+                })
+            });
 
-                        {
-                            let mut logger_shown = LOGGER_SHOWN.get(ctx);
-                            if ui.checkbox(&mut logger_shown, "Show Log Window").changed() {
-                                LOGGER_SHOWN.set(ctx, logger_shown);
-                            }
+            ui.menu_button(tr(ctx, Text::TableFont), |ui| {
+                let r = opt_slider(
+                    ui,
+                    TABLE_FONT_SIZE.get(ctx).map(|e| e.into()),
+                    8..=32,
+                    "Size",
+                    "Default",
+                    "pt",
+                );
+                if r.response.changed() {
+                    TABLE_FONT_SIZE.set(ctx, r.inner.map(|e| NonZero::new(e.get() as u8).unwrap()));
+
+                    for sheet in &mut self.sheet_data {
+                        if let Ok(Ok(s)) = sheet.1.try_get_mut() {
+                            s.invalidate_sizes(ui);
                         }
-                    });
+                    }
+                }
 
-                    add_links(ui, &mut self.about_open);
-                });
+                let mut monospace_numbers = MONOSPACE_NUMBERS.get(ctx);
+                if ui
+                    .checkbox(&mut monospace_numbers, tr(ctx, Text::MonospaceNumbers))
+                    .changed()
+                {
+                    MONOSPACE_NUMBERS.set(ctx, monospace_numbers);
+                }
             });
-    }
 
-    fn draw_logger(&mut self, ctx: &egui::Context) {
+            {
+                let mut solid_scrollbar = SOLID_SCROLLBAR.get(ctx);
+                if ui
+                    .checkbox(&mut solid_scrollbar, "Solid Scrollbar")
+                    .changed()
+                {
+                    SOLID_SCROLLBAR.set(ctx, solid_scrollbar);
+                    ctx.all_styles_mut(|s| {
+                        s.spacing.scroll = if solid_scrollbar {
+                            ScrollStyle::solid()
+                        } else {
+                            ScrollStyle::default()
+                        };
+                    });
+                    ui.close();
+                }
+            }
+
+            {
+                let mut always_hires = ALWAYS_HIRES.get(ctx);
+                if ui.checkbox(&mut always_hires, "HD Icons").changed() {
+                    ALWAYS_HIRES.set(ctx, always_hires);
+                    ui.close();
+                }
+            }
+
+            ui.menu_button(tr(ctx, Text::IconTextures), |ui| {
+                let r = opt_slider(
+                    ui,
+                    ICON_MAX_TEXTURE_SIZE.get(ctx).map(|e| e.into()),
+                    64..=4096,
+                    "Max Size",
+                    "No Limit",
+                    "px",
+                );
+                let mut auto_downscale = ICON_AUTO_DOWNSCALE.get(ctx);
+                let r2 = ui
+                    .checkbox(&mut auto_downscale, tr(ctx, Text::AutoDownscale))
+                    .on_hover_text(tr(ctx, Text::AutoDownscaleHover));
+
+                if r.response.changed() || r2.changed() {
+                    ICON_MAX_TEXTURE_SIZE
+                        .set(ctx, r.inner.map(|e| NonZero::new(e.get() as u16).unwrap()));
+                    ICON_AUTO_DOWNSCALE.set(ctx, auto_downscale);
+                    // Reload icons that were loaded with the old limits
+                    self.icon_manager.clear();
+                }
+            });
+
+            {
+                let mut evaluate_strings = EVALUATE_STRINGS.get(ctx);
+                if ui
+                    .checkbox(&mut evaluate_strings, "Evaluate SeStrings")
+                    .changed()
+                {
+                    EVALUATE_STRINGS.set(ctx, evaluate_strings);
+
+                    for sheet in &mut self.sheet_data {
+                        if let Ok(Ok(s)) = sheet.1.try_get_mut() {
+                            s.invalidate_sizes(ui);
+                        }
+                    }
+                }
+            }
+
+            {
+                let mut display_field_shown = DISPLAY_FIELD_SHOWN.get(ctx);
+                if ui
+                    .checkbox(&mut display_field_shown, tr(ctx, Text::UseDisplayFields))
+                    .changed()
+                {
+                    DISPLAY_FIELD_SHOWN.set(ctx, display_field_shown);
+                    ui.close();
+                }
+            }
+
+            ui.menu_button(tr(ctx, Text::DisplayFieldFallback), |ui| {
+                let mut fallback = DISPLAY_FIELD_FALLBACK.get(ctx);
+                for &option in DisplayFieldFallback::ALL {
+                    if ui
+                        .selectable_value(&mut fallback, option, tr(ctx, option.name()))
+                        .changed()
+                    {
+                        DISPLAY_FIELD_FALLBACK.set(ctx, fallback);
+                        ui.close();
+                    }
+                }
+            });
+
+            {
+                let mut page_boundaries_shown = PAGE_BOUNDARIES_SHOWN.get(ctx);
+                if ui
+                    .checkbox(
+                        &mut page_boundaries_shown,
+                        tr(ctx, Text::ShowPageBoundaries),
+                    )
+                    .on_hover_text("Draw a line where rows from a new EXD page file begin")
+                    .changed()
+                {
+                    PAGE_BOUNDARIES_SHOWN.set(ctx, page_boundaries_shown);
+                }
+            }
+
+            {
+                let mut column_group_tints = COLUMN_GROUP_TINTS.get(ctx);
+                if ui
+                    .checkbox(&mut column_group_tints, tr(ctx, Text::TintColumnGroups))
+                    .on_hover_text("Right-click a grouped column's header to pick its color")
+                    .changed()
+                {
+                    COLUMN_GROUP_TINTS.set(ctx, column_group_tints);
+                }
+            }
+
+            {
+                let mut column_docs_shown = COLUMN_DOCS_SHOWN.get(ctx);
+                if ui
+                    .checkbox(&mut column_docs_shown, tr(ctx, Text::ShowColumnDocs))
+                    .changed()
+                {
+                    COLUMN_DOCS_SHOWN.set(ctx, column_docs_shown);
+                }
+            }
+
+            {
+                let mut logger_shown = LOGGER_SHOWN.get(ctx);
+                if ui
+                    .checkbox(&mut logger_shown, tr(ctx, Text::ShowLogWindow))
+                    .changed()
+                {
+                    LOGGER_SHOWN.set(ctx, logger_shown);
+                }
+            }
+
+            {
+                let mut operation_log_shown = OPERATION_LOG_SHOWN.get(ctx);
+                if ui
+                    .checkbox(&mut operation_log_shown, tr(ctx, Text::ShowOperationLog))
+                    .changed()
+                {
+                    OPERATION_LOG_SHOWN.set(ctx, operation_log_shown);
+                }
+            }
+
+            {
+                let mut memory_usage_shown = MEMORY_USAGE_SHOWN.get(ctx);
+                if ui
+                    .checkbox(&mut memory_usage_shown, tr(ctx, Text::ShowMemoryUsage))
+                    .changed()
+                {
+                    MEMORY_USAGE_SHOWN.set(ctx, memory_usage_shown);
+                }
+            }
+        });
+
+        ui.menu_button(tr(ctx, Text::MenuHelp), |ui| {
+            if ui.button(tr(ctx, Text::FilterSyntax)).clicked() {
+                self.filter_help_open = true;
+                ui.close();
+            }
+        });
+
+        add_links(ui, &mut self.about_open);
+    }
+
+    fn draw_logger(&mut self, ctx: &egui::Context) {
         let logger_shown = LOGGER_SHOWN.get(ctx);
         let mut logger_shown_toggle = logger_shown;
         egui::Window::new("Log")
@@ -538,6 +1227,87 @@ impl App {
         }
     }
 
+    fn draw_operation_log(&mut self, ctx: &egui::Context) {
+        if !OPERATION_LOG_SHOWN.get(ctx) {
+            return;
+        }
+        let mut open = true;
+        egui::Window::new(tr(ctx, Text::OperationLog))
+            .id(egui::Id::new("operation-log"))
+            .open(&mut open)
+            .default_size([480.0, 320.0])
+            .show(ctx, |ui| {
+                if let Some(promise) = operation_log::ui(ui) {
+                    self.export_promise = Some(promise);
+                }
+            });
+        if !open {
+            OPERATION_LOG_SHOWN.set(ctx, false);
+        }
+    }
+
+    fn draw_memory_usage(&mut self, ctx: &egui::Context) {
+        if !MEMORY_USAGE_SHOWN.get(ctx) {
+            return;
+        }
+        let sheets = self.backend.as_ref().map_or(vec![], |backend| {
+            backend
+                .excel()
+                .cached_sheets()
+                .iter()
+                .map(|(language, sheet)| SheetUsage::new(*language, sheet))
+                .collect()
+        });
+        let mut open = true;
+        let mut clear = false;
+        egui::Window::new(tr(ctx, Text::MemoryUsage))
+            .id(egui::Id::new("memory-usage"))
+            .open(&mut open)
+            .default_size([480.0, 320.0])
+            .show(ctx, |ui| {
+                clear = memory_usage::ui(ui, sheets, self.sheet_data.len(), self.schema_data.len());
+            });
+        if clear {
+            self.clear_caches();
+        }
+        if !open {
+            MEMORY_USAGE_SHOWN.set(ctx, false);
+        }
+    }
+
+    /// Drops every loaded sheet, schema and icon to be loaded again when they're next shown.
+    /// Modified schemas are kept.
+    fn clear_caches(&mut self) {
+        if let Some(backend) = &self.backend {
+            backend.excel().clear_cache();
+            backend.schema().clear_cache();
+        }
+        self.sheet_data.clear();
+        self.sheet_languages.clear();
+        self.sheet_diffs.clear();
+        self.first_appeared.clear();
+        self.retain_schemas(0);
+        self.icon_manager.clear();
+        log::info!("Caches cleared");
+    }
+
+    /// Drops the least recently used unmodified schemas until at most `limit` are left, or
+    /// only modified ones are.
+    fn retain_schemas(&mut self, limit: usize) {
+        while self.schema_data.len() > limit {
+            let unmodified = self
+                .schema_data
+                .iter()
+                .rev()
+                .find(|(_, schema)| !matches!(schema.try_get(), Ok(Ok(s)) if s.is_modified()))
+                .map(|(name, _)| name.clone());
+            let Some(name) = unmodified else {
+                break;
+            };
+            self.schema_data.pop(&name);
+        }
+    }
+
     fn poll_changed_schemas(&mut self, ctx: &egui::Context) -> PrChangedState {
         let key = match BACKEND_CONFIG.get(ctx) {
             Some(BackendConfig {
@@ -582,9 +1352,50 @@ impl App {
         }
     }
 
+    /// Names of the sheets still being fetched, in any language.
+    fn loading_sheets(&mut self) -> HashSet<String> {
+        self.sheet_loads.retain(|key, _| {
+            self.sheet_data
+                .peek(key)
+                .is_some_and(|data| data.try_get().is_err_and(|promise| !promise.ready()))
+        });
+        self.sheet_loads
+            .keys()
+            .map(|(_, sheet_name)| sheet_name.clone())
+            .collect()
+    }
+
+    /// Stops fetching a sheet and drops what was fetched of it. Leaves the sheet if it's open,
+    /// since drawing it would only start fetching it again.
+    fn cancel_sheet_load(&mut self, ctx: &egui::Context, sheet_name: &str) {
+        let keys = self
+            .sheet_loads
+            .keys()
+            .filter(|(_, name)| name == sheet_name)
+            .cloned()
+            .collect_vec();
+        for key in keys {
+            if let Some(handle) = self.sheet_loads.remove(&key) {
+                handle.abort();
+            }
+            self.sheet_data.pop(&key);
+        }
+        log::info!("Cancelled loading {sheet_name}");
+        if SELECTED_SHEET.get(ctx).as_deref() == Some(sheet_name)
+            && let Err(e) = self.router.get().unwrap().back()
+        {
+            log::error!("Failed to navigate back: {e}");
+        }
+    }
+
     fn draw_sheet_list(&mut self, ui: &mut egui::Ui) {
         let ctx = &ui.ctx().clone();
         let pr_changed = self.poll_changed_schemas(ctx);
+        let loading_sheets = self.loading_sheets();
+        let filter_pending = self
+            .sheet_filter_data
+            .as_ref()
+            .is_some_and(|(_, matcher)| matcher.is_pending());
         CollapsibleSidePanel::new("sheet_list", Side::Left).show(ui, |ui, is_open| {
             if !is_open {
                 return;
@@ -635,10 +1446,14 @@ impl App {
                         }
                     }
 
+                    if filter_pending {
+                        ui.spinner();
+                    }
                     if ui
                         .add_sized(
                             Vec2::new(ui.available_width(), 0.0),
-                            TextEdit::singleline(&mut sheets_filter).hint_text("Filter"),
+                            TextEdit::singleline(&mut sheets_filter)
+                                .hint_text(tr(ctx, Text::Filter)),
                         )
                         .changed()
                     {
@@ -691,7 +1506,7 @@ impl App {
                         self.command_save_all_schemas();
                     }
                     if open_pr {
-                        self.command_open_pr();
+                        self.command_open_pr(ctx);
                     }
                 });
             }
@@ -699,24 +1514,51 @@ impl App {
             let sheets_filter = SHEETS_FILTER.get(ctx);
             let misc_sheets_shown = MISC_SHEETS_SHOWN.get(ctx);
             let backend = self.backend.clone().unwrap();
-            let sheets = self
+            let entries_status = backend.excel().entries_status();
+            let entry_count = backend.excel().get_entries().len();
+            match &entries_status {
+                EntriesStatus::Loaded => {}
+                EntriesStatus::Loading => {
+                    Panel::bottom("sheet_list_loading").show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label(format!("{} ({entry_count})", tr(ctx, Text::LoadingSheets)));
+                        });
+                    });
+                }
+                EntriesStatus::Failed(err) => {
+                    Panel::bottom("sheet_list_loading").show(ui, |ui| {
+                        ui.colored_label(
+                            ui.visuals().error_fg_color,
+                            tr(ctx, Text::SheetListFailed),
+                        )
+                        .on_hover_text(err);
+                    });
+                }
+            }
+            let filter_key = (misc_sheets_shown, entry_count);
+            if self
                 .sheet_filter_data
-                .get_or_insert((sheets_filter.clone(), misc_sheets_shown), || {
-                    let sheets = backend
-                        .excel()
-                        .get_entries()
-                        .iter()
-                        .filter(|(_, id)| misc_sheets_shown || **id >= 0)
-                        .sorted_by_key(|(sheet, _)| *sheet)
-                        .map(|(s, &id)| (s.clone(), id));
-                    let sheets = self.sheet_matcher.match_list_indirect(
-                        (!sheets_filter.is_empty()).then_some(&sheets_filter),
-                        sheets,
-                        |s| &s.0,
-                    );
-                    Rc::new(sheets)
-                })
-                .clone();
+                .as_ref()
+                .is_none_or(|(key, _)| *key != filter_key)
+            {
+                let sheets = backend
+                    .excel()
+                    .get_entries()
+                    .iter()
+                    .filter(|(_, id)| misc_sheets_shown || **id >= 0)
+                    .sorted_by_key(|(sheet, _)| *sheet)
+                    .map(|(s, &id)| (s.clone(), id))
+                    .collect_vec();
+                self.sheet_filter_data =
+                    Some((filter_key, IncrementalMatcher::new(sheets, |s| &s.0)));
+            }
+            let (sheets, _) = self
+                .sheet_filter_data
+                .as_mut()
+                .unwrap()
+                .1
+                .matches(&sheets_filter);
 
             let sheets = match &pr_changed {
                 PrChangedState::Ready(changed) if PR_CHANGED_ONLY.get(ctx) => Rc::new(
@@ -729,8 +1571,18 @@ impl App {
                 _ => sheets,
             };
 
+            // Sheets missing from the compared version are highlighted as new
+            let compare_entries = self
+                .compare_backend
+                .as_ref()
+                .filter(|(_, b)| b.excel().entries_status() == EntriesStatus::Loaded)
+                .map(|(_, b)| b.excel().get_entries());
+
+            let mut cancelled_load = None;
             egui::CentralPanel::default().show(ui, |ui| {
-                let row_height = ui.text_style_height(&egui::TextStyle::Button);
+                let row_height = (ui.text_style_height(&egui::TextStyle::Button)
+                    + 2.0 * ui.spacing().button_padding.y)
+                    .max(ui.spacing().interact_size.y);
                 ScrollArea::both().auto_shrink(false).show_rows(
                     ui,
                     row_height,
@@ -744,27 +1596,84 @@ impl App {
                                 .take(range.end - range.start)
                             {
                                 ui.style_mut().wrap_mode = Some(egui::TextWrapMode::Truncate);
-                                let resp = Button::selectable(
-                                    current_sheet.as_ref() == Some(sheet),
-                                    sheet.as_str(),
-                                )
-                                .ui(ui)
-                                .on_hover_text(format!("{sheet}\nId: {id}"));
-                                if resp.clicked() {
+                                let is_new = compare_entries
+                                    .as_ref()
+                                    .is_some_and(|entries| !entries.contains_key(sheet));
+                                let text = if is_new {
+                                    RichText::new(sheet.as_str()).color(Color32::GREEN)
+                                } else {
+                                    RichText::new(sheet.as_str())
+                                };
+                                let resp =
+                                    Button::selectable(current_sheet.as_ref() == Some(sheet), text)
+                                        .ui(ui)
+                                        .on_hover_text(if is_new {
+                                            format!(
+                                                "{sheet}\nId: {id}\nNot in the compared version"
+                                            )
+                                        } else {
+                                            format!("{sheet}\nId: {id}")
+                                        });
+                                if loading_sheets.contains(sheet) {
+                                    // Drawn over the end of the button, so it takes the click
+                                    let size = ui.spacing().interact_size.y * 0.8;
+                                    let badge = Rect::from_center_size(
+                                        resp.rect.right_center() - Vec2::new(row_height / 2.0, 0.0),
+                                        Vec2::splat(size),
+                                    );
+                                    Spinner::new().size(size).paint_at(ui, badge);
+                                    if ui
+                                        .interact(
+                                            badge,
+                                            resp.id.with("cancel-load"),
+                                            Sense::click(),
+                                        )
+                                        .on_hover_text(tr(ctx, Text::CancelLoad))
+                                        .clicked()
+                                    {
+                                        cancelled_load = Some(sheet.clone());
+                                    }
+                                }
+                                let new_tab = resp.middle_clicked()
+                                    || (resp.clicked() && ui.input(|i| i.modifiers.command));
+                                let mut open_in_new_tab = false;
+                                resp.context_menu(|ui| {
+                                    if ui.button(tr(ctx, Text::OpenInNewTab)).clicked() {
+                                        open_in_new_tab = true;
+                                    }
+                                });
+                                if new_tab || open_in_new_tab {
+                                    self.sheet_tabs.open_in_new_tab(sheet);
+                                }
+                                if resp.clicked() || resp.middle_clicked() || open_in_new_tab {
                                     current_sheet = Some(sheet.clone());
                                     SELECTED_SHEET.set(ctx, current_sheet.clone());
-                                    self.navigate(format!("/sheet/{}", sheet.clone()));
+                                    self.navigate(sheet_path(sheet, None));
+                                    // The list covers the table on small screens
+                                    if self.touch_layout {
+                                        CollapsibleSidePanel::set_collapsed(
+                                            ctx,
+                                            "sheet_list",
+                                            true,
+                                        );
+                                    }
                                 }
                             }
                         });
                     },
                 );
             });
+            if let Some(sheet_name) = cancelled_load {
+                self.cancel_sheet_load(ctx, &sheet_name);
+            }
         });
     }
 
     fn draw_sheet_data(&mut self, ui: &mut egui::Ui) {
         let ctx = &ui.ctx().clone();
+        if let Some(limit) = SCHEMA_CACHE_SIZE.get(ctx) {
+            self.retain_schemas(limit.get().into());
+        }
         egui::CentralPanel::default()
             .frame(
                 egui::Frame::central_panel(&ctx.global_style()).inner_margin(egui::Margin {
@@ -779,14 +1688,38 @@ impl App {
                 let sheet_name = SELECTED_SHEET.get(ctx).unwrap();
                 let language = LANGUAGE.get(ctx);
 
+                // Miscellaneous-ness comes from the sheet list, so wait for the entry to appear
+                if backend.excel().entries_status() == EntriesStatus::Loading
+                    && !backend.excel().get_entries().contains_key(&sheet_name)
+                {
+                    ui.centered_and_justified(|ui| {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label(tr(ctx, Text::LoadingSheetList));
+                        });
+                    });
+                    return;
+                }
+
                 let sheet_data =
                     self.sheet_data
                         .get_or_insert_mut_ref(&(language, sheet_name.clone()), || {
                             let sheet_name = sheet_name.clone();
                             let excel = backend.excel().clone();
+                            let (abort_handle, abort_registration) = AbortHandle::new_pair();
+                            self.sheet_loads
+                                .insert((language, sheet_name.clone()), abort_handle);
 
                             ConvertiblePromise::new_promise(TrackedPromise::spawn_local(
-                                async move { excel.get_sheet(&sheet_name, language).await },
+                                async move {
+                                    Abortable::new(
+                                        excel.get_sheet(&sheet_name, language),
+                                        abort_registration,
+                                    )
+                                    .await
+                                    .map_err(|_| anyhow::anyhow!("Loading cancelled"))?
+                                    .map_err(anyhow::Error::from)
+                                },
                             ))
                         });
 
@@ -803,7 +1736,12 @@ impl App {
 
                     ConvertiblePromise::new_promise(TrackedPromise::spawn_local(async move {
                         if !is_sheet_miscellaneous {
-                            Some(schema.get_schema_text(&sheet_name).await)
+                            Some(
+                                schema
+                                    .get_schema_text(&sheet_name)
+                                    .await
+                                    .map_err(anyhow::Error::from),
+                            )
                         } else {
                             None
                         }
@@ -829,7 +1767,16 @@ impl App {
                                     },
                                 );
                                 if let Ok(column_count) = column_count {
-                                    EditableSchema::from_blank(&sheet_name, column_count)
+                                    EditableSchema::from_blank(&sheet_name, column_count).map(
+                                        |mut editor| {
+                                            if let Some(draft) =
+                                                outbox::draft(ui.ctx(), &sheet_name)
+                                            {
+                                                editor.restore_draft(draft);
+                                            }
+                                            editor
+                                        },
+                                    )
                                 } else {
                                     Err(anyhow::anyhow!(
                                         "Failed to load sheet to create blank schema"
@@ -872,44 +1819,140 @@ impl App {
 
                 let (table, editor) = match combined_result {
                     None if schema_loading && sheet_loading => {
-                        ui.label("Loading sheet and schema...");
+                        ui.label(tr(ctx, Text::LoadingSheetAndSchema));
                         return;
                     }
                     None if schema_loading => {
-                        ui.label("Loading schema...");
+                        ui.label(tr(ctx, Text::LoadingSchema));
                         return;
                     }
                     None if sheet_loading => {
-                        ui.label("Loading sheet...");
+                        ui.label(tr(ctx, Text::LoadingSheet));
                         return;
                     }
                     None => {
-                        ui.label("Preparing sheet and schema...");
+                        ui.label(tr(ctx, Text::Preparing));
                         return;
                     }
                     Some((Err(err), Err(err2))) => {
-                        ui.label("Failed to load sheet and schema");
-                        ui.label(err.to_string());
-                        ui.label(err2.to_string());
+                        ui.label(tr(ctx, Text::SheetAndSchemaFailed));
+                        let setup = draw_load_error(ui, err) | draw_load_error(ui, err2);
+                        if setup {
+                            self.navigate(setup_path(sheet_path(&sheet_name, None)));
+                        }
                         return;
                     }
                     Some((Err(err), _)) => {
-                        ui.label("Failed to load sheet");
-                        ui.label(err.to_string());
+                        ui.label(tr(ctx, Text::SheetFailed));
+                        if draw_load_error(ui, err) {
+                            self.navigate(setup_path(sheet_path(&sheet_name, None)));
+                        }
                         return;
                     }
                     Some((_, Err(err))) => {
-                        ui.label("Failed to load schema");
-                        ui.label(err.to_string());
+                        ui.label(tr(ctx, Text::SchemaFailed));
+                        if draw_load_error(ui, err) {
+                            self.navigate(setup_path(sheet_path(&sheet_name, None)));
+                        }
                         return;
                     }
                     Some((Ok(table), Ok(editor))) => (table, editor),
                 };
 
+                // `None` while the differences are still being computed
+                let diff = self.compare_backend.as_ref().map(|(_, compare_backend)| {
+                    let diff = self.sheet_diffs.get_or_insert_mut_ref(
+                        &(language, sheet_name.clone()),
+                        || {
+                            let sheet = table.context().sheet().clone();
+                            let old_excel = compare_backend.excel().clone();
+                            let sheet_name = sheet_name.clone();
+                            ConvertiblePromise::new_promise(TrackedPromise::spawn_local(
+                                async move {
+                                    while old_excel.entries_status() == EntriesStatus::Loading {
+                                        yield_to_ui().await;
+                                    }
+                                    let old = if old_excel.get_entries().contains_key(&sheet_name)
+                                    {
+                                        Some(old_excel.get_sheet(&sheet_name, language).await?)
+                                    } else {
+                                        None
+                                    };
+                                    SheetDiff::compute(&sheet, old.as_ref()).await
+                                },
+                            ))
+                        },
+                    );
+                    diff.get(|r| {
+                        r.map(Rc::new).map_err(|e| {
+                            log::error!("Failed to compare sheet: {e:?}");
+                            e.to_string()
+                        })
+                    })
+                    .cloned()
+                });
+                table.set_diff(diff.clone().flatten().and_then(|d| d.ok()));
+
+                let first_appeared = self
+                    .row_timeline
+                    .as_ref()
+                    .filter(|_| FIRST_APPEARED_SHOWN.get(ctx))
+                    .map(|timeline| {
+                        // Wait for the filter so we don't search for rows that are about to be hidden
+                        if table.is_filter_pending() {
+                            return FirstAppearedColumn::Loading;
+                        }
+                        let (filter_type, filter_text) = SHEET_FILTERS.use_with(ctx, |map| {
+                            map.get(&sheet_name).cloned().unwrap_or_default()
+                        });
+                        let key = (
+                            sheet_name.clone(),
+                            filter_type,
+                            filter_text,
+                            SHEET_FILTER_OPTIONS.get(ctx),
+                        );
+                        let promise = self.first_appeared.get_or_insert_mut_ref(&key, || {
+                            let timeline = timeline.clone();
+                            let sheet_name = sheet_name.clone();
+                            let row_ids = table
+                                .visible_rows()
+                                .into_iter()
+                                .map(|(row_id, _)| row_id)
+                                .dedup()
+                                .collect_vec();
+                            ConvertiblePromise::new_promise(TrackedPromise::spawn_local(
+                                async move { timeline.first_appeared(&sheet_name, row_ids).await },
+                            ))
+                        });
+                        match promise.get(|r| {
+                            r.map(Rc::new).map_err(|e| {
+                                log::error!("Failed to find first appearances: {e:?}");
+                                e.to_string()
+                            })
+                        }) {
+                            None => FirstAppearedColumn::Loading,
+                            Some(Ok(versions)) => FirstAppearedColumn::Loaded(versions.clone()),
+                            Some(Err(err)) => FirstAppearedColumn::Failed(err.clone()),
+                        }
+                    });
+                table.set_first_appeared(first_appeared);
+                table.set_helper_column(
+                    HELPER_COLUMNS.use_with(ctx, |map| map.get(&sheet_name).copied()),
+                );
+                table.set_script_column(
+                    self.scripts_window
+                        .as_ref()
+                        .and_then(|window| window.column(&sheet_name)),
+                );
+
+                let mut breadcrumb_action = None;
+                let mut resume_clicked = false;
                 Panel::top("sheet_data_header").show(ui, |ui| {
                     ui.add_space(4.0);
                     ui.horizontal(|ui| {
-                        if CollapsibleSidePanel::is_collapsed(ui.ctx(), "sheet_list") {
+                        if !self.embed_mode
+                            && CollapsibleSidePanel::is_collapsed(ui.ctx(), "sheet_list")
+                        {
                             ui.with_layout(Layout::left_to_right(egui::Align::Min), |ui| {
                                 CollapsibleSidePanel::draw_arrow(ui, "sheet_list");
                             });
@@ -917,6 +1960,27 @@ impl App {
 
                         ui.vertical_centered_justified(|ui| ui.heading(sheet_name.clone()));
                     });
+                    if !self.embed_mode {
+                        breadcrumb_action = self.breadcrumbs.draw(ui);
+                        if let Some((row_id, subrow_id)) = self.resume_row {
+                            ui.horizontal(|ui| {
+                                let row = match subrow_id {
+                                    Some(subrow_id) => format!("{row_id}.{subrow_id}"),
+                                    None => row_id.to_string(),
+                                };
+                                resume_clicked = ui
+                                    .button(format!("↪ {} {row}", tr(ctx, Text::ResumeAtRow)))
+                                    .clicked();
+                                if ui
+                                    .small_button("✖")
+                                    .on_hover_text(tr(ctx, Text::Dismiss))
+                                    .clicked()
+                                {
+                                    self.resume_row = None;
+                                }
+                            });
+                        }
+                    }
                     ui.add_space(4.0);
                     ui.with_layout(Layout::left_to_right(egui::Align::Min), |ui| {
                         let (mut filter_type, mut filter_text) = SHEET_FILTERS
@@ -959,11 +2023,11 @@ impl App {
 
                             let mut is_dirty = ui
                                 .toggle_value(&mut case_insensitive, "🔡")
-                                .on_hover_text("Case Insensitive")
+                                .on_hover_text(tr(ctx, Text::CaseInsensitive))
                                 .changed();
                             is_dirty |= ui
                                 .toggle_value(&mut use_display_field, "📝")
-                                .on_hover_text("Use Display Field")
+                                .on_hover_text(tr(ctx, Text::UseDisplayField))
                                 .changed();
 
                             if is_dirty {
@@ -977,6 +2041,28 @@ impl App {
                             }
                         }
 
+                        if ui
+                            .button("?")
+                            .on_hover_text(tr(ctx, Text::FilterSyntaxHover))
+                            .clicked()
+                        {
+                            self.filter_help_open = true;
+                        }
+                        if ui
+                            .button("🛠")
+                            .on_hover_text(tr(ctx, Text::FilterBuilderHover))
+                            .clicked()
+                        {
+                            let columns = table
+                                .context()
+                                .columns()
+                                .map(|columns| {
+                                    columns.iter().map(|(c, _)| c.name().to_owned()).collect()
+                                })
+                                .unwrap_or_default();
+                            self.filter_builder = Some(FilterBuilder::new(columns));
+                        }
+
                         ui.with_layout(Layout::right_to_left(egui::Align::Min), |ui| {
                             let is_miscellaneous = backend
                                 .excel()
@@ -986,11 +2072,174 @@ impl App {
                                 .unwrap_or_default()
                                 < 0;
 
+                            ui.menu_button(tr(ctx, Text::Export), |ui| {
+                                for &format in ExportFormat::ALL {
+                                    if ui.button(format.name()).clicked() {
+                                        self.sheet_operation = Some(SheetOperation::Export(
+                                            table.context().clone(),
+                                            table.visible_rows(),
+                                            format,
+                                        ));
+                                        ui.close();
+                                    }
+                                }
+                                let capabilities =
+                                    table.context().global().backend().capabilities();
+                                if ui
+                                    .add_enabled(
+                                        capabilities.supports_icons,
+                                        Button::new(tr(ctx, Text::IconsPng)),
+                                    )
+                                    .on_hover_text(tr(ctx, Text::ExportIconsHover))
+                                    .clicked()
+                                {
+                                    self.sheet_operation = Some(SheetOperation::ExportIcons(
+                                        table.context().clone(),
+                                        table.visible_rows(),
+                                    ));
+                                    ui.close();
+                                }
+                                ui.separator();
+
+                                let mut evaluate_strings = EXPORT_EVALUATE_STRINGS.get(ctx);
+                                if ui
+                                    .checkbox(&mut evaluate_strings, "Evaluate SeStrings")
+                                    .changed()
+                                {
+                                    EXPORT_EVALUATE_STRINGS.set(ctx, evaluate_strings);
+                                }
+
+                                let mut link_depth = EXPORT_LINK_DEPTH.get(ctx);
+                                ui.horizontal(|ui| {
+                                    ui.label("JSON Link Depth");
+                                    if ui
+                                        .add(egui::DragValue::new(&mut link_depth).range(0..=3))
+                                        .on_hover_text(
+                                            "How many levels of links to expand into the linked row",
+                                        )
+                                        .changed()
+                                    {
+                                        EXPORT_LINK_DEPTH.set(ctx, link_depth);
+                                    }
+                                });
+
+                                if table.context().sheet().has_subrows() {
+                                    ui.separator();
+                                    ui.label(tr(ctx, Text::SubrowsCsvJson));
+                                    let mut subrow_shape = EXPORT_SUBROW_SHAPE.get(ctx);
+                                    for &shape in SubrowShape::ALL {
+                                        if ui
+                                            .radio_value(&mut subrow_shape, shape, tr(ctx, shape.name()))
+                                            .on_hover_text(tr(ctx, shape.description()))
+                                            .changed()
+                                        {
+                                            EXPORT_SUBROW_SHAPE.set(ctx, subrow_shape);
+                                        }
+                                    }
+                                }
+                            })
+                            .response
+                            .on_hover_text(tr(ctx, Text::ExportHover));
+
+                            ui.add_enabled_ui(table.has_selection(), |ui| {
+                                ui.menu_button("📋", |ui| table.draw_selection_menu(ui))
+                                    .response
+                                    .on_hover_text(tr(ctx, Text::SelectedCellsHover));
+                            });
+
+                            if ui
+                                .toggle_value(&mut self.row_compare_open, "⚖")
+                                .on_hover_text(tr(ctx, Text::CompareRowJsonHover))
+                                .clicked()
+                            {
+                                telemetry::count(ctx, "row-compare");
+                            }
+
+                            let mut scripts_open = self.scripts_window.is_some();
+                            if ui
+                                .toggle_value(&mut scripts_open, "📜")
+                                .on_hover_text(tr(ctx, Text::ScriptsHover))
+                                .clicked()
+                            {
+                                self.scripts_window = scripts_open.then(ScriptsWindow::default);
+                                if scripts_open {
+                                    telemetry::count(ctx, "scripts");
+                                }
+                            }
+
+                            {
+                                let mut raw = SHOW_RAW_VALUES.get(ctx);
+                                if ui
+                                    .toggle_value(&mut raw, "🔢")
+                                    .on_hover_text(tr(ctx, Text::ShowRawValuesHover))
+                                    .changed()
+                                {
+                                    SHOW_RAW_VALUES.set(ctx, raw);
+                                }
+                            }
+
+                            {
+                                let mut helper_column = HELPER_COLUMNS
+                                    .use_with(ctx, |map| map.get(&sheet_name).copied());
+                                let suggested_format = HelperFormat::suggested_for(table.context());
+                                ui.menu_button(
+                                    RichText::new("🧮").color(if helper_column.is_some() {
+                                        ui.visuals().selection.stroke.color
+                                    } else {
+                                        ui.visuals().text_color()
+                                    }),
+                                    |ui| {
+                                        let mut changed = ui
+                                            .radio_value(
+                                                &mut helper_column,
+                                                None,
+                                                tr(ctx, Text::NoHelperColumn),
+                                            )
+                                            .changed();
+                                        for &format in HelperFormat::ALL {
+                                            let mut text = RichText::new(format.name());
+                                            if format == suggested_format {
+                                                text = text.strong();
+                                            }
+                                            changed |= ui
+                                                .radio_value(&mut helper_column, Some(format), text)
+                                                .on_hover_text(format.description())
+                                                .changed();
+                                        }
+                                        if changed {
+                                            HELPER_COLUMNS.use_with(ctx, |map| {
+                                                if let Some(format) = helper_column {
+                                                    map.insert(sheet_name.clone(), format);
+                                                } else {
+                                                    map.remove(&sheet_name);
+                                                }
+                                            });
+                                        }
+                                    },
+                                )
+                                .response
+                                .on_hover_text(tr(ctx, Text::HelperColumnHover));
+                            }
+
+                            if self.row_timeline.is_some() {
+                                let mut shown = FIRST_APPEARED_SHOWN.get(ctx);
+                                if ui
+                                    .toggle_value(&mut shown, "🕓")
+                                    .on_hover_text(tr(ctx, Text::FirstAppearedHover))
+                                    .changed()
+                                {
+                                    FIRST_APPEARED_SHOWN.set(ctx, shown);
+                                }
+                            }
+
                             ui.add_enabled_ui(!is_miscellaneous, |ui| {
+                                if self.embed_mode {
+                                    return;
+                                }
                                 let mut visible = SCHEMA_EDITOR_VISIBLE.get(ui.ctx());
                                 let resp = ui
-                                    .toggle_value(&mut visible, "Edit Schema")
-                                    .on_hover_text("Edit the schema for this sheet");
+                                    .toggle_value(&mut visible, tr(ctx, Text::EditSchema))
+                                    .on_hover_text(tr(ctx, Text::EditSchemaHover));
                                 if resp.changed() {
                                     SCHEMA_EDITOR_VISIBLE.set(ui.ctx(), visible);
                                 }
@@ -1001,7 +2250,7 @@ impl App {
                             let filter_resp = ui.add_sized(
                                 Vec2::new(ui.available_width(), 0.0),
                                 TextEdit::singleline(&mut filter_text)
-                                    .hint_text("Filter")
+                                    .hint_text(tr(ctx, Text::Filter))
                                     .background_color(if filter_error.is_some() {
                                         ui.visuals()
                                             .text_edit_bg_color()
@@ -1011,7 +2260,44 @@ impl App {
                                     }),
                             );
 
-                            filter_dirty |= filter_resp.changed();
+                            if filter_resp.changed() {
+                                self.filter_recall.reset();
+                                filter_dirty = true;
+                            }
+                            if filter_resp.has_focus() {
+                                let (up, down) = ui.input(|i| {
+                                    (i.key_pressed(Key::ArrowUp), i.key_pressed(Key::ArrowDown))
+                                });
+                                if (up || down)
+                                    && let Some((recalled_type, recalled_text)) =
+                                        self.filter_recall.step(
+                                            &filter_history::get(ctx, &sheet_name),
+                                            &(filter_type, filter_text.clone()),
+                                            up,
+                                        )
+                                {
+                                    filter_type = recalled_type;
+                                    filter_text = recalled_text;
+                                    filter_dirty = true;
+                                    // Up moves the cursor to the start of a single line
+                                    if let Some(mut state) =
+                                        TextEdit::load_state(ctx, filter_resp.id)
+                                    {
+                                        state.cursor.set_char_range(Some(CCursorRange::one(
+                                            CCursor::new(filter_text.chars().count()),
+                                        )));
+                                        state.store(ctx, filter_resp.id);
+                                    }
+                                }
+                            }
+                            if filter_resp.lost_focus() {
+                                self.filter_recall.reset();
+                                filter_history::record(
+                                    ctx,
+                                    &sheet_name,
+                                    (filter_type, filter_text.clone()),
+                                );
+                            }
 
                             if let Some(text) = filter_error {
                                 filter_resp.on_hover_text(RichText::new(text).monospace());
@@ -1027,51 +2313,202 @@ impl App {
                             table.update_filter(ui.ctx());
                         }
                     });
+                    if let (Some((version, _)), Some(diff)) = (&self.compare_backend, &diff) {
+                        ui.add_space(4.0);
+                        ui.horizontal(|ui| {
+                            ui.label(format!("Comparing against {version}:"));
+                            match diff {
+                                None => {
+                                    ui.spinner();
+                                    ui.label("Computing differences…");
+                                }
+                                Some(Err(err)) => {
+                                    ui.colored_label(ui.visuals().error_fg_color, err);
+                                }
+                                Some(Ok(diff)) => {
+                                    ui.colored_label(
+                                        Color32::GREEN,
+                                        format!("+{} added", diff.added_count()),
+                                    );
+                                    ui.colored_label(
+                                        Color32::YELLOW,
+                                        format!("~{} changed", diff.changed_count()),
+                                    );
+                                    let removed = diff.removed();
+                                    let resp = ui.colored_label(
+                                        Color32::LIGHT_RED,
+                                        format!("-{} removed", removed.len()),
+                                    );
+                                    if !removed.is_empty() {
+                                        resp.on_hover_text(
+                                            removed
+                                                .iter()
+                                                .take(100)
+                                                .map(|(row_id, subrow_id)| match subrow_id {
+                                                    Some(subrow_id) => {
+                                                        format!("{row_id}.{subrow_id}")
+                                                    }
+                                                    None => row_id.to_string(),
+                                                })
+                                                .join(", ")
+                                                + if removed.len() > 100 { ", …" } else { "" },
+                                        );
+                                    }
+                                    ui.menu_button(tr(ctx, Text::ExportDiff), |ui| {
+                                        for &format in DiffFormat::ALL {
+                                            if ui.button(format.name()).clicked() {
+                                                telemetry::count(ctx, "export-diff");
+                                                operation_log::record(
+                                                    "Export differences",
+                                                    [
+                                                        ("sheet", sheet_name.clone()),
+                                                        ("against", version.to_string()),
+                                                        ("format", format.name().to_owned()),
+                                                    ],
+                                                );
+                                                self.export_promise =
+                                                    Some(export::export_diff_and_save(
+                                                        table.context().clone(),
+                                                        diff.clone(),
+                                                        version.to_string(),
+                                                        format,
+                                                        export_options(ctx),
+                                                    ));
+                                                ui.close();
+                                            }
+                                        }
+                                    });
+                                }
+                            }
+                        });
+                    }
                     ui.add_space(4.0);
                 });
 
-                let resp = editor.draw(ui, backend.schema());
-                if resp.changed()
+                let resp = if self.embed_mode {
+                    ui.response()
+                } else {
+                    error_boundary(ui, ("schema_editor", &sheet_name), |ui| {
+                        let sheet_names = backend.excel().get_entries();
+                        Ok(editor.draw(ui, backend.schema(), &sheet_names))
+                    })
+                    .unwrap_or_else(|| ui.response())
+                };
+                let persisted_display_field = TEMP_PERSIST_DISPLAY_FIELD.take(ctx);
+                if let Some(field_name) = &persisted_display_field {
+                    editor.set_display_field(field_name);
+                }
+                if (resp.changed() || persisted_display_field.is_some())
                     && let Some(schema) = editor.get_schema()
                     && let Err(e) = table.context().set_schema(Some(schema))
                 {
                     log::error!("Failed to set schema: {e:?}");
                 }
 
-                let scroll_to = TEMP_SCROLL_TO.take(ctx);
+                if !self.embed_mode {
+                    let mut column_docs_shown = COLUMN_DOCS_SHOWN.get(ctx);
+                    if column_docs_shown {
+                        column_docs::draw(ctx, table.context(), &mut column_docs_shown);
+                        if !column_docs_shown {
+                            COLUMN_DOCS_SHOWN.set(ctx, false);
+                        }
+                    }
+                    if self.row_compare_open {
+                        row_compare::draw(ctx, table.context(), &mut self.row_compare_open);
+                    }
+                    if let Some(window) = &mut self.scripts_window {
+                        let mut open = true;
+                        window.draw(ctx, table, &mut open);
+                        if !open {
+                            self.scripts_window = None;
+                        }
+                    }
+                }
+
+                let restored = TEMP_RESTORE_VIEW
+                    .take(ctx)
+                    .filter(|view| view.sheet_name() == table.context().sheet().name());
+                // The view is put back as it was instead of scrolling to the row in the path
+                let scroll_to = TEMP_SCROLL_TO.take(ctx).filter(|_| restored.is_none());
+                if let Some(view) = restored {
+                    table.restore_view_state(ctx, view);
+                }
                 if let Some((row_pos, _)) = &scroll_to {
                     TEMP_HIGHLIGHTED_ROW.set(ctx, *row_pos);
+                    LAST_VISITED_ROWS.use_with(ctx, |rows| {
+                        rows.insert(sheet_name.clone(), *row_pos);
+                    });
+                    self.resume_row = None;
                 }
 
+                if !self.embed_mode {
+                    Panel::bottom("sheet_status").show(ui, |ui| table.draw_status_bar(ui));
+                }
                 let resp = table.draw(ui, scroll_to);
+                if let Some((sheet_name, (row_id, _))) = TEMP_FIND_REFERENCES.take(ctx) {
+                    telemetry::count(ctx, "find-references");
+                    operation_log::record(
+                        "Find references",
+                        [("sheet", sheet_name.clone()), ("row", row_id.to_string())],
+                    );
+                    self.references_window = Some(ReferencesWindow::new(
+                        table.context().global().clone(),
+                        sheet_name,
+                        row_id,
+                    ));
+                }
+                let link_source = table.clicked_row().map(|row| (sheet_name.clone(), row));
                 match resp {
                     CellResponse::None => {}
                     CellResponse::Icon(_) => {}
                     CellResponse::Link((sheet_name, (row_id, subrow_id))) => {
-                        self.navigate(format!(
-                            "/sheet/{sheet_name}#R{row_id}{}",
-                            if let Some(subrow_id) = subrow_id {
-                                format!(".{subrow_id}")
-                            } else {
-                                String::new()
-                            }
-                        ));
+                        if let Some(from) = link_source {
+                            self.breadcrumbs
+                                .follow(from, (sheet_name.clone(), (row_id, subrow_id)));
+                        }
+                        self.navigate(sheet_path(&sheet_name, Some((row_id, subrow_id))));
                     }
                     CellResponse::Row((sheet_name, (row_id, subrow_id))) => {
-                        self.navigate_replace(format!(
-                            "/sheet/{sheet_name}#R{row_id}{}",
-                            if let Some(subrow_id) = subrow_id {
-                                format!(".{subrow_id}")
-                            } else {
-                                String::new()
-                            }
-                        ));
+                        self.navigate_replace(sheet_path(&sheet_name, Some((row_id, subrow_id))));
                         ui.ctx().copy_text(self.router.get().unwrap().full_url());
                     }
                 }
+
+                match breadcrumb_action {
+                    Some(BreadcrumbAction::Back) => {
+                        if let Err(e) = self.router.get().unwrap().back() {
+                            log::error!("Failed to navigate back: {e}");
+                        }
+                    }
+                    Some(BreadcrumbAction::Forward) => {
+                        if let Err(e) = self.router.get().unwrap().forward() {
+                            log::error!("Failed to navigate forward: {e}");
+                        }
+                    }
+                    Some(BreadcrumbAction::Jump(path)) => self.navigate(path),
+                    None => {}
+                }
+                if resume_clicked && let Some(row) = self.resume_row.take() {
+                    self.navigate(sheet_path(&sheet_name, Some(row)));
+                }
             });
     }
 
+    /// Keeps the view of the sheet being left, and restores the one of the entry being
+    /// entered if it was left before.
+    fn on_history_entry(&mut self, ui: &egui::Ui, left: u64, entered: u64) {
+        let ctx = ui.ctx();
+        if let Some(table) = self.current_sheet_table(ctx) {
+            let view = table.view_state(ctx);
+            self.sheet_tabs.keep_view(view.clone());
+            self.view_states.put(left, view);
+        }
+        match self.view_states.pop(&entered) {
+            Some(view) => TEMP_RESTORE_VIEW.set(ctx, view),
+            None => TEMP_RESTORE_VIEW.remove(ctx),
+        }
+    }
+
     fn on_setup(
         &mut self,
         ui: &mut egui::Ui,
@@ -1080,21 +2517,52 @@ impl App {
     ) -> RouteResponse {
         self.setup_window = Some(SetupWindow::from_config(
             ui.ctx(),
-            path.query_pairs().contains_key("redirect"),
+            path.query_as::<AppQuery>()
+                .is_ok_and(|query| query.redirect.is_some()),
         ));
         RouteResponse::Title("Setup".to_string())
     }
 
     fn draw_setup(&mut self, ui: &mut egui::Ui, path: &Path, _params: &Params<'_, '_>) {
-        if let Some((backend, config)) = self.setup_window.as_mut().unwrap().draw(ui.ctx()) {
-            self.backend = Some(backend);
-            self.sheet_data.clear();
-            self.schema_data.clear();
-            self.sheet_languages.clear();
-            CURRENT_SHEET_LANGUAGES.remove(ui.ctx());
-
+        let setup_window = self.setup_window.as_mut().unwrap();
+        let result = if self.embed_mode {
+            match setup_window.poll_headless() {
+                Ok(result) => {
+                    if result.is_none() {
+                        ui.centered_and_justified(|ui| ui.spinner());
+                    }
+                    result
+                }
+                Err(err) => {
+                    ui.label(tr(ui.ctx(), Text::FailedToLoadGameData));
+                    ui.label(err.to_string());
+                    None
+                }
+            }
+        } else {
+            setup_window.draw(ui.ctx())
+        };
+        if let Some((backend, config)) = result {
+            operation_log::record(
+                "Load game data",
+                [
+                    (
+                        "location",
+                        serde_json::to_string(&config.location).unwrap_or_default(),
+                    ),
+                    (
+                        "schema",
+                        serde_json::to_string(&config.schema).unwrap_or_default(),
+                    ),
+                ],
+            );
+            self.set_backend(ui.ctx(), backend, RowTimeline::new(&config));
             BACKEND_CONFIG.set(ui.ctx(), Some(config));
-            if let Some(redirect_path) = path.query_pairs().get("redirect").map(|s| s.as_str()) {
+            if let Some(redirect_path) = path
+                .query_as::<AppQuery>()
+                .ok()
+                .and_then(|query| query.redirect)
+            {
                 self.navigate_replace(redirect_path);
             } else {
                 self.navigate("/sheet");
@@ -1102,6 +2570,93 @@ impl App {
         }
     }
 
+    /// Swaps in a newly loaded backend, dropping everything read from the old one.
+    fn set_backend(
+        &mut self,
+        ctx: &egui::Context,
+        backend: Backend,
+        row_timeline: Option<RowTimeline>,
+    ) {
+        self.backend = Some(backend);
+        self.sheet_data.clear();
+        self.schema_data.clear();
+        self.sheet_languages.clear();
+        self.compare_backend = None;
+        self.sheet_diffs.clear();
+        self.row_timeline = row_timeline;
+        self.first_appeared.clear();
+        self.icon_manager.revalidate();
+        self.version_watch.reset();
+        #[cfg(not(target_arch = "wasm32"))]
+        self.install_watch.reset();
+        CURRENT_SHEET_LANGUAGES.remove(ctx);
+    }
+
+    /// Opens an .exh and its .exd files dropped onto the window as a sheet of its own, without
+    /// setting up a backend. Schemas still come from the configured location, if there is one.
+    fn poll_dropped_files(&mut self, ctx: &egui::Context) {
+        if let Some(promise) = self.dropped_sheet.take() {
+            match promise.try_take() {
+                Ok(Ok((backend, sheet_name))) => {
+                    operation_log::record("Open dropped sheet", [("sheet", sheet_name.clone())]);
+                    self.set_backend(ctx, backend, None);
+                    self.navigate(sheet_path(&sheet_name, None));
+                }
+                Ok(Err(e)) => log::error!("Failed to open dropped files: {e:?}"),
+                Err(promise) => self.dropped_sheet = Some(promise),
+            }
+        }
+
+        let dropped = ctx.input(|i| i.raw.dropped_files.clone());
+        let files = dropped
+            .into_iter()
+            .filter_map(|file| {
+                let name = match &file.path {
+                    Some(path) => path.file_name()?.to_string_lossy().into_owned(),
+                    None => file.name.clone(),
+                };
+                let data = match (file.bytes, &file.path) {
+                    (Some(bytes), _) => bytes.to_vec(),
+                    #[cfg(not(target_arch = "wasm32"))]
+                    (None, Some(path)) => std::fs::read(path)
+                        .inspect_err(|e| log::error!("Failed to read {}: {e}", path.display()))
+                        .ok()?,
+                    _ => return None,
+                };
+                Some((name, data))
+            })
+            .collect_vec();
+        let is_sheet_file = |name: &str| {
+            let name = name.to_lowercase();
+            name.ends_with(".exh") || name.ends_with(".exd")
+        };
+        if !files.iter().any(|(name, _)| is_sheet_file(name)) {
+            return;
+        }
+
+        let (files, sheet_name) = match MemoryFileProvider::from_sheet_files(files) {
+            Ok(ret) => ret,
+            Err(e) => {
+                log::error!("Failed to open dropped files: {e}");
+                return;
+            }
+        };
+        let schema = BACKEND_CONFIG.get(ctx).map_or_else(
+            || {
+                SchemaLocation::Github(GithubSchemaLocation {
+                    owner: crate::DEFAULT_GITHUB_REPO.0.to_string(),
+                    repo: crate::DEFAULT_GITHUB_REPO.1.to_string(),
+                    branch: GithubSchemaBranch::Latest,
+                })
+            },
+            |config| config.schema,
+        );
+        self.dropped_sheet = Some(TrackedPromise::spawn_local(async move {
+            let backend = Backend::from_files(Rc::new(files), schema).await?;
+            Ok((backend, sheet_name))
+        }));
+    }
+
     fn on_auth_callback(
         &mut self,
         _ui: &mut egui::Ui,
@@ -1117,10 +2672,7 @@ impl App {
 
     fn ensure_backend(&self, path: &Path) -> Option<RouteResponse> {
         if self.backend.is_none() {
-            return Some(RouteResponse::Redirect(Path::with_params(
-                "/",
-                &[("redirect", path.to_string())],
-            )));
+            return Some(RouteResponse::Redirect(setup_path(path)));
         }
         None
     }
@@ -1136,7 +2688,7 @@ impl App {
         }
 
         if let Some(sheet) = &SELECTED_SHEET.get(ui.ctx()) {
-            return RouteResponse::Redirect(format!("/sheet/{sheet}").into());
+            return RouteResponse::Redirect(sheet_path(sheet, None));
         }
         RouteResponse::Title("Sheet List".to_string())
     }
@@ -1145,45 +2697,81 @@ impl App {
         &mut self,
         ui: &mut egui::Ui,
         path: &Path,
-        params: &Params<'_, '_>,
+        SheetParams { name: sheet }: SheetParams,
     ) -> RouteResponse {
         if let Some(r) = self.ensure_backend(path) {
             return r;
         }
         TEMP_HIGHLIGHTED_ROW.take(ui.ctx());
 
-        if let Some(sheet) = params.get("name") {
-            SELECTED_SHEET.set(ui.ctx(), Some(sheet.to_string()));
-        } else {
-            SELECTED_SHEET.set(ui.ctx(), None);
-            return RouteResponse::Redirect("/sheet".into());
+        telemetry::count(ui.ctx(), "open-sheet");
+        let tab_view = self.sheet_tabs.open(ui.ctx(), &sheet);
+        if SELECTED_SHEET.get(ui.ctx()).as_deref() != Some(&sheet) {
+            operation_log::record(
+                "Open sheet",
+                [
+                    ("sheet", sheet.clone()),
+                    ("language", LANGUAGE.get(ui.ctx()).to_string()),
+                ],
+            );
+        }
+        SELECTED_SHEET.set(ui.ctx(), Some(sheet.clone()));
+        push_recent_sheet(ui.ctx(), &sheet);
+        if let Ok(query) = path.query_as::<SheetQuery>() {
+            self.apply_sheet_query(ui.ctx(), &sheet, query);
         }
 
-        if let Some(mut fragment) = path.fragment() {
-            let mut col_nr: Option<u16> = None;
-            if let Some((rest, col_str)) = fragment.rsplit_once('C') {
-                col_nr = col_str.parse::<u16>().ok();
-                fragment = rest;
-            }
+        let anchor = path.fragment().and_then(|f| f.parse::<RowAnchor>().ok());
+        if let Some(anchor) = &anchor {
+            TEMP_SCROLL_TO.set(
+                ui.ctx(),
+                ((anchor.row_id, anchor.subrow_id), anchor.column.clone()),
+            );
+        } else if let Some(view) = tab_view
+            && TEMP_RESTORE_VIEW.try_get(ui.ctx()).is_none()
+        {
+            // Switching tabs puts back the view the tab was left on
+            TEMP_RESTORE_VIEW.set(ui.ctx(), view);
+        }
+        // Going back or forward to the sheet puts back its view instead
+        self.resume_row = if anchor.is_none() && TEMP_RESTORE_VIEW.try_get(ui.ctx()).is_none() {
+            LAST_VISITED_ROWS.use_with(ui.ctx(), |rows| rows.get(&sheet).copied())
+        } else {
+            None
+        };
+        self.breadcrumbs
+            .visit(&sheet, anchor.map(|a| (a.row_id, a.subrow_id)));
+        RouteResponse::Title(sheet)
+    }
 
-            let mut row_pos: Option<(u32, Option<u16>)> = None;
-            if let Some((_rest, row_str)) = fragment.rsplit_once('R') {
-                if let Some((row_str, subrow_str)) = row_str.split_once('.') {
-                    let row = row_str.parse::<u32>().ok();
-                    let subrow = subrow_str.parse::<u16>().ok();
-                    if let Some(row) = row {
-                        row_pos = Some((row, subrow));
+    /// Opens the sheet in the language, filter and sort of a shared link.
+    fn apply_sheet_query(&mut self, ctx: &egui::Context, sheet_name: &str, query: SheetQuery) {
+        if let Some(language) = query.lang {
+            LANGUAGE.set(ctx, language);
+        }
+        let sorts = query.sorts();
+        if let Some(filter) = query.filter() {
+            SHEET_FILTERS.use_with(ctx, |map| {
+                map.insert(sheet_name.to_owned(), filter);
+            });
+        }
+        let Some(table) = self.current_sheet_table(ctx) else {
+            // A table created later picks them up from the settings
+            if let Some(sorts) = sorts {
+                SHEET_SORTS.use_with(ctx, |map| {
+                    if sorts.is_empty() {
+                        map.remove(sheet_name);
+                    } else {
+                        map.insert(sheet_name.to_owned(), sorts);
                     }
-                } else if let Ok(row) = row_str.parse::<u32>() {
-                    row_pos = Some((row, None));
-                }
-            }
-
-            if let Some((row, subrow)) = row_pos {
-                TEMP_SCROLL_TO.set(ui.ctx(), ((row, subrow), col_nr.unwrap_or_default()));
+                });
             }
+            return;
+        };
+        table.update_filter(ctx);
+        if let Some(sorts) = sorts {
+            table.set_sort(ctx, sorts);
         }
-        RouteResponse::Title(params.get("name").unwrap().to_string())
     }
 
     fn draw_unnamed_sheet(&mut self, ui: &mut egui::Ui, _path: &Path, _params: &Params<'_, '_>) {
@@ -1192,11 +2780,46 @@ impl App {
         self.draw_sheet_list(ui);
     }
 
-    fn draw_named_sheet(&mut self, ui: &mut egui::Ui, _path: &Path, _params: &Params<'_, '_>) {
-        self.draw_goto(ui.ctx());
+    fn draw_named_sheet(&mut self, ui: &mut egui::Ui, _path: &Path, _params: SheetParams) {
+        if !self.embed_mode {
+            self.draw_goto(ui.ctx());
+            error_boundary(ui, "sheet_list", |ui| {
+                self.draw_sheet_list(ui);
+                Ok(())
+            });
+        }
+        if !self.embed_mode {
+            self.draw_sheet_tabs(ui);
+        }
+        let sheet_name = SELECTED_SHEET.get(ui.ctx());
+        error_boundary(ui, ("sheet_data", sheet_name), |ui| {
+            self.draw_sheet_data(ui);
+            Ok(())
+        });
+    }
 
-        self.draw_sheet_list(ui);
-        self.draw_sheet_data(ui);
+    fn draw_sheet_tabs(&mut self, ui: &mut egui::Ui) {
+        let ctx = ui.ctx().clone();
+        let action = Panel::top("sheet_tabs")
+            .show(ui, |ui| self.sheet_tabs.draw(ui))
+            .inner;
+        match action {
+            Some(TabAction::Select(sheet_name)) => self.navigate(sheet_path(&sheet_name, None)),
+            Some(TabAction::Close(sheet_name)) => {
+                let active = SELECTED_SHEET.get(&ctx);
+                let next = self.sheet_tabs.close(&ctx, &sheet_name);
+                if active.as_deref() == Some(sheet_name.as_str()) {
+                    match next {
+                        Some(next) => self.navigate(sheet_path(&next, None)),
+                        None => {
+                            SELECTED_SHEET.set(&ctx, None);
+                            self.navigate("/sheet");
+                        }
+                    }
+                }
+            }
+            None => {}
+        }
     }
 
     fn on_music(
@@ -1217,33 +2840,675 @@ impl App {
         }
     }
 
-    fn command_open_pr(&mut self) {
+    fn command_open_pr(&mut self, ctx: &egui::Context) {
         let names: Vec<String> = self
             .get_modified_schemas()
             .iter()
             .map(|(name, _)| (*name).clone())
             .collect();
-        self.pr_window.open(&names);
+        self.pr_outbox = None;
+        self.pr_window
+            .open(&names, pr_window::game_version(ctx).as_deref());
+    }
+
+    fn command_open_outbox_pr(&mut self, ctx: &egui::Context) {
+        let ready = outbox::ready(ctx)
+            .into_iter()
+            .map(|(name, text)| {
+                let invalid_reason = EditableSchema::new(&name, text.clone()).invalid_reason();
+                (name, text, invalid_reason)
+            })
+            .collect_vec();
+        let names = ready.iter().map(|(name, _, _)| name.clone()).collect_vec();
+        self.pr_outbox = Some(ready);
+        self.pr_window
+            .open(&names, pr_window::game_version(ctx).as_deref());
     }
 
     fn draw_pr_window(&mut self, ctx: &egui::Context) {
         let location = pr_window::github_source(ctx);
-        let modified: Vec<(String, Option<String>)> = self
-            .get_modified_schemas()
-            .iter()
-            .map(|(name, schema)| ((*name).clone(), schema.invalid_reason()))
-            .collect();
+        let modified: Vec<(String, Option<String>)> = if let Some(ready) = &self.pr_outbox {
+            ready
+                .iter()
+                .map(|(name, _, invalid_reason)| (name.clone(), invalid_reason.clone()))
+                .collect()
+        } else {
+            self.get_modified_schemas()
+                .iter()
+                .map(|(name, schema)| ((*name).clone(), schema.invalid_reason()))
+                .collect()
+        };
         if let Some(PrAction::Submit { title, body }) =
             self.pr_window.draw(ctx, location.as_ref(), &modified)
             && let Some(location) = &location
         {
-            let files: Vec<(String, String)> = self
-                .get_modified_schemas()
-                .into_iter()
-                .map(|(name, schema)| (format!("{name}.yml"), schema.get_text().clone()))
+            self.pr_files = if let Some(ready) = &self.pr_outbox {
+                ready
+                    .iter()
+                    .map(|(name, text, _)| (name.clone(), text.clone()))
+                    .collect()
+            } else {
+                self.get_modified_schemas()
+                    .into_iter()
+                    .map(|(name, schema)| (name.clone(), schema.get_text().clone()))
+                    .collect()
+            };
+            let files = self
+                .pr_files
+                .iter()
+                .map(|(name, text)| (format!("{name}.yml"), text.clone()))
                 .collect();
             self.pr_window.submit(location, title, body, files);
         }
+        if let Some(pr) = self.pr_window.take_opened() {
+            outbox::mark_submitted(ctx, &self.pr_files, &pr.html_url);
+        }
+    }
+
+    /// Keeps the outbox up to date with edits to stub schemas.
+    fn queue_stub_schemas(&self, ctx: &egui::Context) {
+        for (sheet_name, schema) in self.get_modified_schemas() {
+            if schema.is_stub() {
+                outbox::queue(ctx, sheet_name, schema.get_text());
+            }
+        }
+    }
+
+    fn draw_outbox(&mut self, ctx: &egui::Context) {
+        let can_submit = pr_window::github_source(ctx).is_some();
+        match outbox::draw(ctx, &mut self.outbox_open, can_submit) {
+            Some(OutboxAction::Open(path)) => self.navigate(path),
+            Some(OutboxAction::Submit) => self.command_open_outbox_pr(ctx),
+            None => {}
+        }
+    }
+
+    fn draw_filter_help(&mut self, ctx: &egui::Context) {
+        if let Some(example) = filter_help::draw(ctx, &mut self.filter_help_open) {
+            self.set_complex_filter(ctx, example.to_string());
+        }
+    }
+
+    fn draw_filter_builder(&mut self, ctx: &egui::Context) {
+        let Some(builder) = &mut self.filter_builder else {
+            return;
+        };
+        let mut open = true;
+        let filter = builder.draw(ctx, &mut open);
+        if !open {
+            self.filter_builder = None;
+        }
+        if let Some(filter) = filter {
+            self.set_complex_filter(ctx, filter);
+        }
+    }
+
+    /// Replaces the current sheet's filter.
+    fn set_complex_filter(&mut self, ctx: &egui::Context, filter: String) {
+        let Some(sheet_name) = SELECTED_SHEET.get(ctx) else {
+            return;
+        };
+        SHEET_FILTERS.use_with(ctx, |map| {
+            map.insert(sheet_name.clone(), (FilterInputType::Complex, filter));
+        });
+        if let Some(Ok(table)) = self
+            .sheet_data
+            .get_mut(&(LANGUAGE.get(ctx), sheet_name))
+            .and_then(|s| s.try_get_mut().ok())
+        {
+            table.update_filter(ctx);
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn poll_control_server(&mut self, ctx: &egui::Context) {
+        let port = CONTROL_SERVER_ENABLED
+            .get(ctx)
+            .then(|| CONTROL_SERVER_PORT.get(ctx));
+        if self.control_server.as_ref().map(ControlServer::port) != port {
+            self.control_server = None;
+            if let Some(port) = port {
+                match ControlServer::start(ctx.clone(), port) {
+                    Ok(server) => self.control_server = Some(server),
+                    Err(e) => {
+                        log::error!("Failed to start control server on port {port}: {e}");
+                        CONTROL_SERVER_ENABLED.set(ctx, false);
+                    }
+                }
+            }
+        }
+
+        let requests = self
+            .control_server
+            .as_ref()
+            .map(|server| server.requests().collect_vec())
+            .unwrap_or_default();
+        for request in requests {
+            self.handle_control_request(ctx, request);
+        }
+    }
+
+    /// The loaded table of the selected sheet, if any.
+    fn current_sheet_table(&mut self, ctx: &egui::Context) -> Option<&mut SheetTable> {
+        let sheet_name = SELECTED_SHEET.get(ctx)?;
+        match self
+            .sheet_data
+            .get_mut(&(LANGUAGE.get(ctx), sheet_name))?
+            .try_get_mut()
+        {
+            Ok(Ok(table)) => Some(table),
+            _ => None,
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn handle_control_request(&mut self, ctx: &egui::Context, request: ControlRequest) {
+        match &request.command {
+            ControlCommand::Status => {
+                let visible_rows = self
+                    .current_sheet_table(ctx)
+                    .map(|table| table.visible_rows().len());
+                request.respond(ControlReply::json(&serde_json::json!({
+                    "path": self.router.get().unwrap().current_path().to_string(),
+                    "sheet": SELECTED_SHEET.get(ctx),
+                    "language": LANGUAGE.get(ctx).to_string(),
+                    "visible_rows": visible_rows,
+                })));
+            }
+            ControlCommand::Open { sheet, row } => {
+                let Some(backend) = &self.backend else {
+                    request.respond(ControlReply::error(409, "No game data is loaded"));
+                    return;
+                };
+                if !backend.excel().get_entries().contains_key(sheet) {
+                    request.respond(ControlReply::error(404, format!("Unknown sheet {sheet}")));
+                    return;
+                }
+                self.navigate(sheet_path(sheet, row));
+                request.respond(ControlReply::ok());
+            }
+            ControlCommand::Filter { sheet, kind, query } => {
+                let Some(sheet_name) = sheet.clone().or_else(|| SELECTED_SHEET.get(ctx)) else {
+                    request.respond(ControlReply::error(409, "No sheet is open"));
+                    return;
+                };
+                SHEET_FILTERS.use_with(ctx, |map| {
+                    map.insert(sheet_name.clone(), (*kind, query.clone()));
+                });
+                if let Some(Ok(table)) = self
+                    .sheet_data
+                    .get_mut(&(LANGUAGE.get(ctx), sheet_name))
+                    .and_then(|s| s.try_get_mut().ok())
+                {
+                    table.update_filter(ctx);
+                }
+                request.respond(ControlReply::ok());
+            }
+            &ControlCommand::Export { format } => {
+                let Some(table) = self.current_sheet_table(ctx) else {
+                    request.respond(ControlReply::error(409, "No sheet is loaded"));
+                    return;
+                };
+                let rows = table.visible_rows();
+                let context = table.context().clone();
+                let options = export_options(ctx);
+                let _ = TrackedPromise::spawn_local(async move {
+                    request.respond(match format.export(&context, &rows, options).await {
+                        Ok(data) => ControlReply::export(format, data),
+                        Err(e) => ControlReply::error(500, format!("{e:?}")),
+                    });
+                });
+            }
+        }
+    }
+
+    fn draw_compare_window(&mut self, ctx: &egui::Context) {
+        let Some(window) = &mut self.compare_window else {
+            return;
+        };
+        let mut open = true;
+        if let Some(compare) = window.draw(ctx, &mut open) {
+            self.compare_backend = Some(compare);
+            self.sheet_diffs.clear();
+        }
+        if !open {
+            self.compare_window = None;
+        }
+    }
+
+    fn draw_schema_download_window(&mut self, ctx: &egui::Context) {
+        let Some(window) = &mut self.schema_download_window else {
+            return;
+        };
+        let mut open = true;
+        if let Some(schema) = window.draw(ctx, &mut open)
+            && let Some(config) = BACKEND_CONFIG.get(ctx)
+        {
+            // Setup picks up the new config and reloads straight back into the current page
+            BACKEND_CONFIG.set(ctx, Some(BackendConfig { schema, ..config }));
+            let current = self.router.get().unwrap().current_path();
+            self.navigate(setup_path(current));
+        }
+        if !open {
+            self.schema_download_window = None;
+        }
+    }
+
+    fn run_sheet_operation(&mut self, ctx: &egui::Context, operation: SheetOperation) {
+        match operation {
+            // Serializes the rows and prompts the user to save them
+            SheetOperation::Export(table, rows, format) => {
+                telemetry::count(ctx, "export");
+                let options = export_options(ctx);
+                operation_log::record(
+                    "Export",
+                    [
+                        ("sheet", table.sheet().name().to_owned()),
+                        ("format", format.name().to_owned()),
+                        ("rows", rows.len().to_string()),
+                        ("options", format!("{options:?}")),
+                    ],
+                );
+                self.export_job_window = Some(ExportJobWindow::new(table, rows, format, options));
+            }
+            SheetOperation::ExportIcons(table, rows) => {
+                telemetry::count(ctx, "export-icons");
+                let hires = ALWAYS_HIRES.get(ctx);
+                operation_log::record(
+                    "Export icons",
+                    [
+                        ("sheet", table.sheet().name().to_owned()),
+                        ("rows", rows.len().to_string()),
+                        ("hires", hires.to_string()),
+                    ],
+                );
+                self.icon_export_window = Some(IconExportWindow::new(ctx, table, rows, hires));
+            }
+        }
+    }
+
+    /// Runs the started sheet operation, once confirmed if it goes through a lot of rows.
+    fn draw_huge_operation(&mut self, ctx: &egui::Context) {
+        if let Some(operation) = self.sheet_operation.take() {
+            let table = operation.table().clone();
+            let (row_count, what) = (operation.row_count(), operation.description());
+            match HugeOperation::check(&table, row_count, what, operation) {
+                Ok(operation) => self.run_sheet_operation(ctx, operation),
+                Err(confirm) => self.huge_operation = Some(confirm),
+            }
+        }
+        let Some(confirm) = &self.huge_operation else {
+            return;
+        };
+        if let Some(run) = confirm.draw(ctx) {
+            let operation = self.huge_operation.take().unwrap().into_operation();
+            if run {
+                self.run_sheet_operation(ctx, operation);
+            }
+        }
+    }
+
+    fn draw_export_job_window(&mut self, ctx: &egui::Context) {
+        let Some(window) = &mut self.export_job_window else {
+            return;
+        };
+        let mut open = true;
+        window.draw(ctx, &mut open);
+        if !open {
+            self.export_job_window = None;
+        }
+    }
+
+    fn draw_icon_export_window(&mut self, ctx: &egui::Context) {
+        let Some(window) = &mut self.icon_export_window else {
+            return;
+        };
+        let mut open = true;
+        window.draw(ctx, &mut open);
+        if !open {
+            self.icon_export_window = None;
+        }
+    }
+
+    fn draw_command_palette(&mut self, ctx: &egui::Context) {
+        let Some(palette) = self.command_palette.take() else {
+            return;
+        };
+        let entries = self.palette_entries(ctx);
+        match palette.draw(ctx, &self.sheet_matcher, entries) {
+            Ok(Some(command)) => {
+                telemetry::count(ctx, "command-palette");
+                self.run_command(ctx, command);
+            }
+            Ok(None) => {}
+            Err(palette) => self.command_palette = Some(palette),
+        }
+    }
+
+    /// Everything the command palette can run right now.
+    fn palette_entries(&mut self, ctx: &egui::Context) -> Vec<PaletteEntry> {
+        let selected_sheet = SELECTED_SHEET.get(ctx);
+        let mut entries = Vec::new();
+        if self.backend.is_some() {
+            entries.push(PaletteEntry::new(
+                tr(ctx, Text::GoToSheet),
+                Command::GoToSheet,
+            ));
+            if selected_sheet.is_some() {
+                entries.push(PaletteEntry::new(tr(ctx, Text::GoToRow), Command::GoToRow));
+            }
+            for sheet in RECENT_SHEETS.get(ctx) {
+                entries.push(PaletteEntry::new(
+                    format!("{}: {sheet}", tr(ctx, Text::RecentSheets)),
+                    Command::OpenSheet(sheet),
+                ));
+            }
+        }
+
+        entries.push(PaletteEntry::new(
+            tr(ctx, Text::Configure),
+            Command::Configure,
+        ));
+        for preset in BACKEND_PRESETS.get(ctx) {
+            entries.push(PaletteEntry::new(
+                format!("{}: {}", tr(ctx, Text::Profiles), preset.name),
+                Command::Profile(preset.config),
+            ));
+        }
+        if let Some(backend) = &self.backend {
+            if backend.capabilities().supports_versions && self.compare_backend.is_none() {
+                entries.push(PaletteEntry::new(
+                    tr(ctx, Text::CompareVersions),
+                    Command::CompareVersions,
+                ));
+            }
+            if pr_window::github_source(ctx).is_some() {
+                entries.push(PaletteEntry::new(
+                    tr(ctx, Text::DownloadSchemas),
+                    Command::DownloadSchemas,
+                ));
+            }
+            entries.extend([
+                PaletteEntry::new(tr(ctx, Text::ValidateSchemas), Command::ValidateSchemas),
+                PaletteEntry::new(tr(ctx, Text::CheckIntegrity), Command::CheckIntegrity),
+                PaletteEntry::new(tr(ctx, Text::SheetGraph), Command::SheetGraph),
+            ]);
+        }
+        entries.extend([
+            PaletteEntry::new(tr(ctx, Text::SchemaOutbox), Command::SchemaOutbox),
+            PaletteEntry::new(tr(ctx, Text::ClearCaches), Command::ClearCaches),
+            PaletteEntry::new(
+                tr(ctx, Text::ClearDownloadedData),
+                Command::ClearDownloadedData,
+            ),
+        ]);
+
+        if let Some(table) = self.current_sheet_table(ctx) {
+            let export = tr(ctx, Text::Export);
+            for &format in ExportFormat::ALL {
+                entries.push(PaletteEntry::new(
+                    format!("{export}: {}", format.name()),
+                    Command::Export(format),
+                ));
+            }
+            if table
+                .context()
+                .global()
+                .backend()
+                .capabilities()
+                .supports_icons
+            {
+                entries.push(PaletteEntry::new(
+                    format!("{export}: {}", tr(ctx, Text::IconsPng)),
+                    Command::ExportIcons,
+                ));
+            }
+        }
+
+        if selected_sheet.is_some() {
+            entries.push(PaletteEntry::toggle(
+                tr(ctx, Text::EditSchema),
+                &SCHEMA_EDITOR_VISIBLE,
+                ctx,
+            ));
+        }
+        entries.extend([
+            PaletteEntry::toggle(tr(ctx, Text::ShowRawValues), &SHOW_RAW_VALUES, ctx),
+            PaletteEntry::toggle(tr(ctx, Text::UseDisplayFields), &DISPLAY_FIELD_SHOWN, ctx),
+            PaletteEntry::toggle(
+                tr(ctx, Text::ShowPageBoundaries),
+                &PAGE_BOUNDARIES_SHOWN,
+                ctx,
+            ),
+            PaletteEntry::toggle(tr(ctx, Text::TintColumnGroups), &COLUMN_GROUP_TINTS, ctx),
+            PaletteEntry::toggle(tr(ctx, Text::ShowColumnDocs), &COLUMN_DOCS_SHOWN, ctx),
+            PaletteEntry::toggle(tr(ctx, Text::MonospaceNumbers), &MONOSPACE_NUMBERS, ctx),
+            PaletteEntry::toggle(tr(ctx, Text::ShowLogWindow), &LOGGER_SHOWN, ctx),
+            PaletteEntry::toggle(tr(ctx, Text::ShowOperationLog), &OPERATION_LOG_SHOWN, ctx),
+            PaletteEntry::toggle(tr(ctx, Text::ShowMemoryUsage), &MEMORY_USAGE_SHOWN, ctx),
+        ]);
+        if self.row_timeline.is_some() {
+            entries.push(PaletteEntry::toggle(
+                tr(ctx, Text::FirstAppeared),
+                &FIRST_APPEARED_SHOWN,
+                ctx,
+            ));
+        }
+
+        let language = tr(ctx, Text::MenuLanguage);
+        for lang in Language::iter().filter(|&l| l != Language::None) {
+            entries.push(PaletteEntry::new(
+                format!("{language}: {lang}"),
+                Command::Language(lang),
+            ));
+        }
+        let ui_language = tr(ctx, Text::UiLanguageMenu);
+        for &lang in UiLanguage::ALL {
+            entries.push(PaletteEntry::new(
+                format!("{ui_language}: {}", lang.name()),
+                Command::UiLanguage(lang),
+            ));
+        }
+        let layout = tr(ctx, Text::LayoutMode);
+        for &mode in LayoutMode::ALL {
+            entries.push(PaletteEntry::new(
+                format!("{layout}: {}", mode.name()),
+                Command::LayoutMode(mode),
+            ));
+        }
+
+        if let Some(sheet_name) = selected_sheet {
+            let bookmarked = bookmarks::is_bookmarked(ctx, &Bookmark::sheet(sheet_name));
+            entries.push(PaletteEntry::new(
+                tr(
+                    ctx,
+                    if bookmarked {
+                        Text::RemoveSheetBookmark
+                    } else {
+                        Text::BookmarkSheet
+                    },
+                ),
+                Command::ToggleBookmark,
+            ));
+        }
+        entries.extend([
+            PaletteEntry::new(tr(ctx, Text::ManageBookmarks), Command::ManageBookmarks),
+            PaletteEntry::new(tr(ctx, Text::Music), Command::Music),
+            PaletteEntry::new(tr(ctx, Text::FilterSyntax), Command::FilterSyntax),
+            PaletteEntry::new(tr(ctx, Text::About), Command::About),
+        ]);
+        if !super::IS_WEB {
+            entries.push(PaletteEntry::new(tr(ctx, Text::Quit), Command::Quit));
+        }
+        entries
+    }
+
+    /// Does what the menu item a palette command stands in for does.
+    fn run_command(&mut self, ctx: &egui::Context, command: Command) {
+        match command {
+            Command::GoToSheet => self.goto_window = Some(goto::GoToWindow::to_sheet()),
+            Command::GoToRow => self.goto_window = Some(goto::GoToWindow::to_row()),
+            Command::OpenSheet(sheet_name) => self.navigate(sheet_path(&sheet_name, None)),
+            Command::Configure => self.navigate("/"),
+            Command::Profile(config) => {
+                BACKEND_CONFIG.set(ctx, Some(config));
+                let current = self.router.get().unwrap().current_path();
+                self.navigate(setup_path(current));
+            }
+            Command::CompareVersions => self.compare_window = CompareWindow::new(ctx),
+            Command::DownloadSchemas => {
+                if let Some(source) = pr_window::github_source(ctx) {
+                    self.schema_download_window = Some(SchemaDownloadWindow::new(source));
+                }
+            }
+            Command::ValidateSchemas => {
+                if let Some(backend) = self.backend.clone() {
+                    self.schema_validation_window = Some(SchemaValidationWindow::new(backend));
+                }
+            }
+            Command::CheckIntegrity => {
+                if let Some(backend) = self.backend.clone() {
+                    self.integrity_check_window = Some(IntegrityCheckWindow::new(backend));
+                }
+            }
+            Command::SheetGraph => {
+                if let Some(backend) = self.backend.clone() {
+                    self.sheet_graph_window = Some(SheetGraphWindow::new(backend));
+                }
+            }
+            Command::SchemaOutbox => self.outbox_open = true,
+            Command::ClearCaches => self.clear_caches(),
+            Command::ClearDownloadedData => clear_downloaded_data(),
+            Command::Export(format) => {
+                if let Some(table) = self.current_sheet_table(ctx) {
+                    let operation = SheetOperation::Export(
+                        table.context().clone(),
+                        table.visible_rows(),
+                        format,
+                    );
+                    self.sheet_operation = Some(operation);
+                }
+            }
+            Command::ExportIcons => {
+                if let Some(table) = self.current_sheet_table(ctx) {
+                    let operation =
+                        SheetOperation::ExportIcons(table.context().clone(), table.visible_rows());
+                    self.sheet_operation = Some(operation);
+                }
+            }
+            Command::Toggle(key) => key.set(ctx, !key.get(ctx)),
+            Command::Language(lang) => {
+                operation_log::record("Change language", [("language", lang.to_string())]);
+                LANGUAGE.set(ctx, lang);
+            }
+            Command::UiLanguage(lang) => UI_LANGUAGE.set(ctx, lang),
+            Command::LayoutMode(mode) => LAYOUT_MODE.set(ctx, mode),
+            Command::ToggleBookmark => {
+                if let Some(sheet_name) = SELECTED_SHEET.get(ctx) {
+                    bookmarks::toggle(ctx, Bookmark::sheet(sheet_name));
+                }
+            }
+            Command::ManageBookmarks => self.bookmarks_open = true,
+            Command::Music => self.navigate("/music"),
+            Command::FilterSyntax => self.filter_help_open = true,
+            Command::About => self.about_open = true,
+            Command::Quit => ctx.send_viewport_cmd(egui::ViewportCommand::Close),
+        }
+    }
+
+    /// Loads again whatever failed to while a server was unreachable, once it's back.
+    fn poll_connection_watch(&mut self, ctx: &egui::Context) {
+        if !self.connection_watch.poll(ctx) {
+            return;
+        }
+        if self.backend.is_some() {
+            // Failed loads are cached like any other
+            self.clear_caches();
+        } else if let Some(setup_window) = &mut self.setup_window {
+            setup_window.retry();
+        }
+    }
+
+    fn draw_version_watch(&mut self, ctx: &egui::Context) {
+        if self.backend.is_none() {
+            return;
+        }
+        self.version_watch.poll(ctx);
+        if let Some(version) = self.version_watch.draw(ctx)
+            && let Some(mut config) = BACKEND_CONFIG.get(ctx)
+        {
+            // Configs following the latest version pick it up on their own
+            if let InstallLocation::Web(_, _, pinned @ Some(_)) = &mut config.location {
+                *pinned = Some(version);
+            }
+            BACKEND_CONFIG.set(ctx, Some(config));
+            self.version_watch.reset();
+            let current = self.router.get().unwrap().current_path();
+            self.navigate(setup_path(current));
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.install_watch.poll(ctx);
+            if self.install_watch.draw(ctx) {
+                self.install_watch.reset();
+                let current = self.router.get().unwrap().current_path();
+                self.navigate(setup_path(current));
+            }
+        }
+    }
+
+    fn draw_schema_validation_window(&mut self, ctx: &egui::Context) {
+        let Some(window) = &mut self.schema_validation_window else {
+            return;
+        };
+        let mut open = true;
+        if let Some(sheet_name) = window.draw(ctx, &mut open) {
+            self.navigate(sheet_path(&sheet_name, None));
+        }
+        if !open {
+            self.schema_validation_window = None;
+        }
+    }
+
+    fn draw_integrity_check_window(&mut self, ctx: &egui::Context) {
+        let Some(window) = &mut self.integrity_check_window else {
+            return;
+        };
+        let mut open = true;
+        if let Some(sheet_name) = window.draw(ctx, &mut open) {
+            self.navigate(sheet_path(&sheet_name, None));
+        }
+        if !open {
+            self.integrity_check_window = None;
+        }
+    }
+
+    fn draw_sheet_graph_window(&mut self, ctx: &egui::Context) {
+        let Some(window) = &mut self.sheet_graph_window else {
+            return;
+        };
+        let mut open = true;
+        let current_sheet = SELECTED_SHEET.get(ctx);
+        if let Some(sheet_name) = window.draw(ctx, &mut open, current_sheet.as_deref()) {
+            self.navigate(sheet_path(&sheet_name, None));
+        }
+        if !open {
+            self.sheet_graph_window = None;
+        }
+    }
+
+    fn draw_references_window(&mut self, ctx: &egui::Context) {
+        let Some(window) = &mut self.references_window else {
+            return;
+        };
+        let mut open = true;
+        if let Some((sheet_name, (row_id, subrow_id))) = window.draw(ctx, &mut open) {
+            self.navigate(sheet_path(&sheet_name, Some((row_id, subrow_id))));
+        }
+        if !open {
+            self.references_window = None;
+        }
     }
 
     fn get_modified_schemas(&self) -> Vec<(&String, &EditableSchema)> {
@@ -1320,27 +3585,77 @@ impl App {
         install_image_loaders(&cc.egui_ctx);
         Self::apply_fonts(&cc.egui_ctx, None);
         Self::setup_theme(&cc.egui_ctx);
+        set_request_limit(WEB_REQUEST_LIMIT.get(&cc.egui_ctx).map(|e| e.into()));
+        #[cfg(not(target_arch = "wasm32"))]
+        web_cache::set_max_size(
+            WEB_CACHE_SIZE
+                .get(&cc.egui_ctx)
+                .map(|e| u64::from(e.get()) << 20),
+        );
 
         Self {
             router: Rc::new(OnceCell::new()),
             icon_manager: IconManager::new(),
             setup_window: None,
             backend: None,
-            sheet_data: LruCache::new(NonZero::new(32).unwrap()),
+            sheet_data: LruCache::new(
+                SHEET_CACHE_SIZE
+                    .get(&cc.egui_ctx)
+                    .map_or(NonZero::<usize>::MAX, |e| e.into()),
+            ),
+            sheet_loads: HashMap::new(),
             schema_data: LruCache::unbounded(),
             sheet_languages: LruCache::unbounded(),
+            compare_backend: None,
+            compare_window: None,
+            schema_download_window: None,
+            schema_validation_window: None,
+            integrity_check_window: None,
+            sheet_graph_window: None,
+            references_window: None,
+            export_job_window: None,
+            icon_export_window: None,
+            sheet_operation: None,
+            huge_operation: None,
+            breadcrumbs: Breadcrumbs::default(),
+            sheet_tabs: SheetTabs::default(),
+            view_states: LruCache::new(NonZero::new(64).unwrap()),
+            resume_row: None,
+            sheet_diffs: LruCache::new(NonZero::new(8).unwrap()),
+            row_timeline: None,
+            first_appeared: LruCache::new(NonZero::new(8).unwrap()),
             sheet_matcher: FuzzyMatcher::new(),
-            sheet_filter_data: LruCache::new(NonZero::new(8).unwrap()),
+            sheet_filter_data: None,
             changed_schemas: None,
             save_promise: None,
+            export_promise: None,
             pr_window: PrWindow::default(),
+            pr_outbox: None,
+            pr_files: Vec::new(),
             goto_window: None,
+            command_palette: None,
             about_open: false,
+            filter_help_open: false,
+            filter_builder: None,
+            filter_recall: FilterRecall::default(),
+            bookmarks_open: false,
+            outbox_open: false,
+            row_compare_open: false,
+            scripts_window: None,
+            version_watch: VersionWatch::default(),
+            connection_watch: ConnectionWatch::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            install_watch: InstallWatch::default(),
+            dropped_sheet: None,
+            embed_mode: false,
+            touch_layout: false,
             music: music::MusicPlayer::default(),
             last_system_theme: None,
             loaded_cjk: None,
             #[cfg(target_arch = "wasm32")]
             font_promise: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            control_server: None,
         }
     }
 
@@ -1416,7 +3731,11 @@ impl App {
         let file = font.asset_file().to_owned();
         self.font_promise = Some((
             font,
-            UnsendPromise::new(async move { crate::utils::fetch_url(file).await }),
+            UnsendPromise::new(async move {
+                crate::utils::fetch_url(file)
+                    .await
+                    .map_err(anyhow::Error::from)
+            }),
         ));
     }
 
@@ -1452,6 +3771,78 @@ impl eframe::App for App {
     }
 }
 
+fn export_options(ctx: &egui::Context) -> ExportOptions {
+    ExportOptions {
+        sorted_by_offset: SORTED_BY_OFFSET.get(ctx),
+        evaluate_strings: EXPORT_EVALUATE_STRINGS.get(ctx),
+        resolve_display_field: DISPLAY_FIELD_SHOWN.get(ctx),
+        link_depth: EXPORT_LINK_DEPTH.get(ctx),
+        subrow_shape: EXPORT_SUBROW_SHAPE.get(ctx),
+    }
+}
+
+/// Moves `sheet_name` to the front of [`RECENT_SHEETS`], keeping at most [`RECENT_SHEETS_LIMIT`].
+fn push_recent_sheet(ctx: &egui::Context, sheet_name: &str) {
+    RECENT_SHEETS.use_with(ctx, |sheets| {
+        sheets.retain(|s| s != sheet_name);
+        sheets.insert(0, sheet_name.to_owned());
+        sheets.truncate(RECENT_SHEETS_LIMIT);
+    });
+}
+
+/// Shows why a sheet or schema failed to load, with a hint on how to fix it. Returns true if
+/// the user asked to go back to setup.
+fn draw_load_error(ui: &mut egui::Ui, error: &anyhow::Error) -> bool {
+    ui.label(error.to_string());
+    let Some(error) = ProviderError::of(error) else {
+        return false;
+    };
+    ui.label(RichText::new(tr(ui.ctx(), error.hint())).weak());
+    error.needs_setup() && ui.button(tr(ui.ctx(), Text::ReturnToSetup)).clicked()
+}
+
+/// Link to a sheet as it's shown: scrolled to the same row and focused column, in the same
+/// language and with the same filter and sort.
+fn view_path(ctx: &egui::Context, table: &SheetTable) -> Path {
+    let state = table.view_state(ctx);
+    let (mode, filter) = state.filter().cloned().unzip();
+    let mut query = SheetQuery {
+        lang: Some(LANGUAGE.get(ctx)),
+        filter,
+        mode,
+        ..Default::default()
+    };
+    if !table.sort().is_empty() {
+        query.set_sorts(table.sort());
+    }
+    let path = match (state.top_row(), table.focused_column(ctx)) {
+        (Some(row), Some(column)) => cell_path(state.sheet_name(), row, column),
+        (row, _) => sheet_path(state.sheet_name(), row),
+    };
+    path.with_serialized_query(&query)
+}
+
+/// Whether the path (or the path it redirects to after setup) requests embed mode.
+fn is_embed_path(path: &Path) -> bool {
+    let Ok(query) = path.query_as::<AppQuery>() else {
+        return false;
+    };
+    query.is_embed()
+        || query
+            .redirect
+            .is_some_and(|redirect| is_embed_path(&Path::parse(&redirect)))
+}
+
+/// Removes the sheet files web providers kept, to be downloaded again.
+fn clear_downloaded_data() {
+    let _ = TrackedPromise::spawn_local(async {
+        match web_cache::clear().await {
+            Ok(()) => log::info!("Downloaded data cleared"),
+            Err(e) => log::error!("Failed to clear downloaded data: {e:?}"),
+        }
+    });
+}
+
 fn add_links(ui: &mut egui::Ui, open_about: &mut bool) {
     ui.with_layout(Layout::right_to_left(ui.layout().vertical_align()), |ui| {
         if ui
@@ -0,0 +1,117 @@
+use std::{cell::RefCell, num::NonZeroUsize, rc::Rc};
+
+use futures_util::future::LocalBoxFuture;
+
+use crate::{data::FileProvider, schema::provider::SchemaProvider};
+
+/// A kind of game file provider that's opened from a single line of user input, such as a
+/// path or URL. Registered kinds can be picked as the install location in setup next to the
+/// built-in ones.
+pub struct FileProviderKind {
+    /// Stored in the config to find the kind again, so it must never change
+    pub id: &'static str,
+    pub name: &'static str,
+    /// Describes what to enter to open the provider
+    pub hint: &'static str,
+    /// Parsed sheets kept in memory
+    pub cache_size: NonZeroUsize,
+    pub open: fn(String) -> LocalBoxFuture<'static, anyhow::Result<Rc<dyn FileProvider>>>,
+}
+
+/// Like [`FileProviderKind`], for where schemas are read from.
+pub struct SchemaProviderKind {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub hint: &'static str,
+    /// Parsed schemas kept in memory
+    pub cache_size: NonZeroUsize,
+    pub open: fn(String) -> LocalBoxFuture<'static, anyhow::Result<Box<dyn SchemaProvider>>>,
+}
+
+thread_local! {
+    static FILE_KINDS: RefCell<Vec<Rc<FileProviderKind>>> = const { RefCell::new(Vec::new()) };
+    static SCHEMA_KINDS: RefCell<Vec<Rc<SchemaProviderKind>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Registers a file provider kind, replacing any with the same id.
+pub fn register_files(kind: FileProviderKind) {
+    FILE_KINDS.with_borrow_mut(|kinds| {
+        kinds.retain(|k| k.id != kind.id);
+        kinds.push(Rc::new(kind));
+    });
+}
+
+/// Registers a schema provider kind, replacing any with the same id.
+pub fn register_schema(kind: SchemaProviderKind) {
+    SCHEMA_KINDS.with_borrow_mut(|kinds| {
+        kinds.retain(|k| k.id != kind.id);
+        kinds.push(Rc::new(kind));
+    });
+}
+
+pub fn file_kinds() -> Vec<Rc<FileProviderKind>> {
+    FILE_KINDS.with_borrow(Clone::clone)
+}
+
+pub fn schema_kinds() -> Vec<Rc<SchemaProviderKind>> {
+    SCHEMA_KINDS.with_borrow(Clone::clone)
+}
+
+pub fn file_kind(id: &str) -> anyhow::Result<Rc<FileProviderKind>> {
+    file_kinds()
+        .into_iter()
+        .find(|k| k.id == id)
+        .ok_or_else(|| anyhow::anyhow!("Unknown install location kind: {id}"))
+}
+
+pub fn schema_kind(id: &str) -> anyhow::Result<Rc<SchemaProviderKind>> {
+    schema_kinds()
+        .into_iter()
+        .find(|k| k.id == id)
+        .ok_or_else(|| anyhow::anyhow!("Unknown schema location kind: {id}"))
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroUsize;
+
+    use futures_util::FutureExt;
+
+    use super::{
+        FileProviderKind, SchemaProviderKind, file_kind, file_kinds, register_files,
+        register_schema, schema_kind,
+    };
+
+    fn file_provider_kind(name: &'static str) -> FileProviderKind {
+        FileProviderKind {
+            id: "test",
+            name,
+            hint: "Anything",
+            cache_size: NonZeroUsize::MIN,
+            open: |argument| async move { Err(anyhow::anyhow!("opened {argument}")) }.boxed_local(),
+        }
+    }
+
+    #[test]
+    fn registered_kinds_resolve_by_id() {
+        assert!(file_kind("test").is_err());
+
+        register_files(file_provider_kind("First"));
+        register_files(file_provider_kind("Second"));
+        assert_eq!(file_kinds().len(), 1);
+        let kind = file_kind("test").unwrap();
+        assert_eq!(kind.name, "Second");
+        let opened = (kind.open)("somewhere".to_owned()).now_or_never().unwrap();
+        assert_eq!(opened.err().unwrap().to_string(), "opened somewhere");
+
+        register_schema(SchemaProviderKind {
+            id: "test",
+            name: "Schemas",
+            hint: "Anything",
+            cache_size: NonZeroUsize::MIN,
+            open: |_| async { Err(anyhow::anyhow!("unavailable")) }.boxed_local(),
+        });
+        assert_eq!(schema_kind("test").unwrap().name, "Schemas");
+        assert!(schema_kind("other").is_err());
+    }
+}
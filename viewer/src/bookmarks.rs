@@ -0,0 +1,163 @@
+use egui::{Grid, RichText, TextEdit};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    i18n::{Text, tr},
+    router::path::Path,
+    routes::sheet_path,
+    settings::BOOKMARKS,
+    sheet::SheetRef,
+};
+
+/// A saved sheet, or row within a sheet, that can be jumped back to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub sheet_name: String,
+    /// Row id and subrow id. `None` bookmarks the sheet itself.
+    pub row: Option<(u32, Option<u16>)>,
+    pub label: Option<String>,
+}
+
+impl Bookmark {
+    pub fn sheet(sheet_name: String) -> Self {
+        Self {
+            sheet_name,
+            row: None,
+            label: None,
+        }
+    }
+
+    pub fn row((sheet_name, row): SheetRef) -> Self {
+        Self {
+            sheet_name,
+            row: Some(row),
+            label: None,
+        }
+    }
+
+    fn is_same_target(&self, other: &Self) -> bool {
+        self.sheet_name == other.sheet_name && self.row == other.row
+    }
+
+    /// `Sheet` or `Sheet#Row.Subrow`
+    pub fn target(&self) -> String {
+        match self.row {
+            None => self.sheet_name.clone(),
+            Some((row_id, None)) => format!("{}#{row_id}", self.sheet_name),
+            Some((row_id, Some(subrow_id))) => {
+                format!("{}#{row_id}.{subrow_id}", self.sheet_name)
+            }
+        }
+    }
+
+    pub fn name(&self) -> String {
+        self.label
+            .clone()
+            .filter(|l| !l.is_empty())
+            .unwrap_or_else(|| self.target())
+    }
+
+    /// The route to navigate to.
+    pub fn path(&self) -> Path {
+        sheet_path(&self.sheet_name, self.row)
+    }
+}
+
+pub fn is_bookmarked(ctx: &egui::Context, bookmark: &Bookmark) -> bool {
+    BOOKMARKS.use_with(ctx, |bookmarks| {
+        bookmarks.iter().any(|b| b.is_same_target(bookmark))
+    })
+}
+
+/// Adds `bookmark`, or removes it if its target is already bookmarked.
+pub fn toggle(ctx: &egui::Context, bookmark: Bookmark) {
+    BOOKMARKS.use_with(ctx, |bookmarks| {
+        if let Some(idx) = bookmarks.iter().position(|b| b.is_same_target(&bookmark)) {
+            bookmarks.remove(idx);
+        } else {
+            bookmarks.push(bookmark);
+        }
+    });
+}
+
+/// Lists the bookmarks inside the Bookmarks menu. Returns the path of the clicked one.
+pub fn draw_menu_items(ui: &mut egui::Ui) -> Option<Path> {
+    let bookmarks = BOOKMARKS.get(ui.ctx());
+    if bookmarks.is_empty() {
+        ui.label(RichText::new(tr(ui.ctx(), Text::NoBookmarks)).weak());
+        return None;
+    }
+    let mut ret = None;
+    for bookmark in &bookmarks {
+        if ui
+            .button(bookmark.name())
+            .on_hover_text(bookmark.target())
+            .clicked()
+        {
+            ret = Some(bookmark.path());
+            ui.close();
+        }
+    }
+    ret
+}
+
+/// Draws the bookmark manager. Returns the path of a bookmark to jump to, if one was clicked.
+pub fn draw(ctx: &egui::Context, open: &mut bool) -> Option<Path> {
+    let mut ret = None;
+    egui::Window::new(tr(ctx, Text::Bookmarks))
+        .id(egui::Id::new("bookmarks-window"))
+        .open(open)
+        .default_width(360.0)
+        .show(ctx, |ui| {
+            let mut bookmarks = BOOKMARKS.get(ctx);
+            if bookmarks.is_empty() {
+                ui.label(RichText::new(tr(ctx, Text::NoBookmarks)).weak());
+                return;
+            }
+
+            let mut changed = false;
+            let mut removed = None;
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                Grid::new("bookmarks_grid").striped(true).show(ui, |ui| {
+                    for (idx, bookmark) in bookmarks.iter_mut().enumerate() {
+                        if ui
+                            .link(RichText::new(bookmark.target()).monospace())
+                            .clicked()
+                        {
+                            ret = Some(bookmark.path());
+                        }
+
+                        let mut label = bookmark.label.clone().unwrap_or_default();
+                        if ui
+                            .add(
+                                TextEdit::singleline(&mut label)
+                                    .hint_text(tr(ctx, Text::Label))
+                                    .desired_width(160.0),
+                            )
+                            .changed()
+                        {
+                            bookmark.label = (!label.is_empty()).then_some(label);
+                            changed = true;
+                        }
+
+                        if ui
+                            .small_button("🗑")
+                            .on_hover_text(tr(ctx, Text::RemoveBookmark))
+                            .clicked()
+                        {
+                            removed = Some(idx);
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+            if let Some(idx) = removed {
+                bookmarks.remove(idx);
+                changed = true;
+            }
+            if changed {
+                BOOKMARKS.set(ctx, bookmarks);
+            }
+        });
+    ret
+}
@@ -0,0 +1,116 @@
+use egui::{Button, RichText};
+
+use crate::{
+    i18n::{Text, tr},
+    router::path::Path,
+    routes::sheet_path,
+    sheet::SheetRef,
+    shortcuts::{NAV_BACK, NAV_FORWARD},
+};
+
+pub enum BreadcrumbAction {
+    Back,
+    Forward,
+    /// Path of the clicked crumb
+    Jump(Path),
+}
+
+/// The chain of rows visited by following links in this session. Visiting any page moves
+/// the cursor to the matching crumb, so the router's back and forward stay in sync; visiting a
+/// sheet outside of the chain starts over.
+#[derive(Default)]
+pub struct Breadcrumbs {
+    trail: Vec<SheetRef>,
+    cursor: usize,
+}
+
+impl Breadcrumbs {
+    /// Records a link from the row `from` to `to`, dropping anything ahead of the cursor.
+    pub fn follow(&mut self, from: SheetRef, to: SheetRef) {
+        self.trail.truncate(self.cursor + 1);
+        match self.trail.last_mut() {
+            Some(last) if *last == from => {}
+            // Same sheet, another row: refine where the link was followed from
+            Some(last) if last.0 == from.0 => *last = from,
+            _ => {
+                self.trail.clear();
+                self.trail.push(from);
+            }
+        }
+        self.trail.push(to);
+        self.cursor = self.trail.len() - 1;
+    }
+
+    /// Syncs the cursor with a newly visited sheet route.
+    pub fn visit(&mut self, sheet_name: &str, row: Option<(u32, Option<u16>)>) {
+        let nearest = |pred: &dyn Fn(&SheetRef) -> bool| {
+            self.trail
+                .iter()
+                .enumerate()
+                .filter(|(_, crumb)| pred(crumb))
+                .min_by_key(|(idx, _)| idx.abs_diff(self.cursor))
+                .map(|(idx, _)| idx)
+        };
+        let exact = row.and_then(|row| nearest(&|crumb| crumb.0 == sheet_name && crumb.1 == row));
+        match exact.or_else(|| nearest(&|crumb| crumb.0 == sheet_name)) {
+            Some(idx) => self.cursor = idx,
+            None => {
+                self.trail.clear();
+                self.cursor = 0;
+            }
+        }
+    }
+
+    pub fn draw(&self, ui: &mut egui::Ui) -> Option<BreadcrumbAction> {
+        if self.trail.len() < 2 {
+            return None;
+        }
+
+        let mut ret = None;
+        ui.horizontal_wrapped(|ui| {
+            ui.spacing_mut().item_spacing.x /= 2.0;
+            if ui
+                .add_enabled(self.cursor > 0, Button::new("⏴").small())
+                .on_hover_text(format!(
+                    "{} ({})",
+                    tr(ui.ctx(), Text::Back),
+                    ui.ctx().format_shortcut(&NAV_BACK)
+                ))
+                .clicked()
+            {
+                ret = Some(BreadcrumbAction::Back);
+            }
+            if ui
+                .add_enabled(self.cursor + 1 < self.trail.len(), Button::new("⏵").small())
+                .on_hover_text(format!(
+                    "{} ({})",
+                    tr(ui.ctx(), Text::Forward),
+                    ui.ctx().format_shortcut(&NAV_FORWARD)
+                ))
+                .clicked()
+            {
+                ret = Some(BreadcrumbAction::Forward);
+            }
+            ui.separator();
+
+            for (idx, (sheet_name, (row_id, subrow_id))) in self.trail.iter().enumerate() {
+                if idx > 0 {
+                    ui.label(RichText::new("›").weak());
+                }
+                let text = match subrow_id {
+                    Some(subrow_id) => format!("{sheet_name}#{row_id}.{subrow_id}"),
+                    None => format!("{sheet_name}#{row_id}"),
+                };
+                if idx == self.cursor {
+                    ui.label(RichText::new(text).strong());
+                } else if ui.link(text).clicked() {
+                    ret = Some(BreadcrumbAction::Jump(sheet_path(
+                        sheet_name,
+                        Some((*row_id, *subrow_id)),
+                    )));
+                }
+            }
+        });
+        ret
+    }
+}
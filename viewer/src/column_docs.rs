@@ -0,0 +1,77 @@
+use egui::{RichText, TextEdit};
+
+use crate::{
+    excel::provider::ExcelSheet,
+    i18n::{Text, tr},
+    sheet::TableContext,
+    utils::markdown,
+};
+
+/// Draws the schema comments of every column in `table`, rendered as Markdown.
+pub fn draw(ctx: &egui::Context, table: &TableContext, open: &mut bool) {
+    egui::Window::new(format!(
+        "{} – {}",
+        tr(ctx, Text::ColumnDocs),
+        table.sheet().name()
+    ))
+    .id(egui::Id::new("column-docs-window"))
+    .open(open)
+    .default_width(420.0)
+    .default_height(480.0)
+    .show(ctx, |ui| {
+        let search_id = ui.id().with("search");
+        let mut search = ui.data(|d| d.get_temp::<String>(search_id).unwrap_or_default());
+        if ui
+            .add(
+                TextEdit::singleline(&mut search)
+                    .hint_text(tr(ctx, Text::Filter))
+                    .desired_width(f32::INFINITY),
+            )
+            .changed()
+        {
+            ui.data_mut(|d| d.insert_temp(search_id, search.clone()));
+        }
+        let search = search.to_lowercase();
+        ui.separator();
+
+        let mut undocumented = 0;
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for column_idx in 0..table.column_count() as u32 {
+                let Ok(((column, _), offset_idx)) = table.get_column_by_index(column_idx) else {
+                    continue;
+                };
+                let Some(comment) = column.comment() else {
+                    undocumented += 1;
+                    continue;
+                };
+                if !search.is_empty()
+                    && !column.name().to_lowercase().contains(&search)
+                    && !comment.to_lowercase().contains(&search)
+                {
+                    continue;
+                }
+
+                ui.horizontal(|ui| {
+                    ui.strong(column.name());
+                    ui.label(
+                        RichText::new(format!("{column_idx} | {offset_idx}"))
+                            .small()
+                            .weak(),
+                    );
+                });
+                ui.indent(("column_doc", column_idx), |ui| markdown(ui, comment));
+                ui.add_space(4.0);
+            }
+            if undocumented > 0 {
+                ui.separator();
+                ui.label(
+                    RichText::new(format!(
+                        "{undocumented} {}",
+                        tr(ctx, Text::UndocumentedColumns)
+                    ))
+                    .weak(),
+                );
+            }
+        });
+    });
+}
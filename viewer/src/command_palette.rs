@@ -0,0 +1,178 @@
+use egui::{Frame, Key, Layout, Modal, Modifiers, RichText, ScrollArea, TextEdit};
+use ironworks::excel::Language;
+
+use crate::{
+    export::ExportFormat,
+    i18n::{Text, UiLanguage, tr},
+    settings::{BackendConfig, DKey},
+    touch_layout::LayoutMode,
+    utils::FuzzyMatcher,
+};
+
+/// Something the palette can do, run by the app like the menu item it stands in for.
+#[derive(Clone)]
+pub enum Command {
+    GoToSheet,
+    GoToRow,
+    OpenSheet(String),
+    Configure,
+    Profile(BackendConfig),
+    CompareVersions,
+    DownloadSchemas,
+    ValidateSchemas,
+    CheckIntegrity,
+    SchemaOutbox,
+    SheetGraph,
+    ClearCaches,
+    ClearDownloadedData,
+    Export(ExportFormat),
+    ExportIcons,
+    Toggle(&'static DKey<bool>),
+    Language(Language),
+    UiLanguage(UiLanguage),
+    LayoutMode(LayoutMode),
+    ToggleBookmark,
+    ManageBookmarks,
+    Music,
+    FilterSyntax,
+    About,
+    Quit,
+}
+
+/// A command as it's listed.
+pub struct PaletteEntry {
+    pub label: String,
+    /// Whether the setting is on, for commands that toggle one. Shown as a checkbox.
+    pub checked: Option<bool>,
+    pub command: Command,
+}
+
+impl PaletteEntry {
+    pub fn new(label: impl Into<String>, command: Command) -> Self {
+        Self {
+            label: label.into(),
+            checked: None,
+            command,
+        }
+    }
+
+    pub fn toggle(label: impl Into<String>, key: &'static DKey<bool>, ctx: &egui::Context) -> Self {
+        Self {
+            label: label.into(),
+            checked: Some(key.get(ctx)),
+            command: Command::Toggle(key),
+        }
+    }
+}
+
+/// Lists every command and runs the one picked, so the whole app can be driven from the
+/// keyboard.
+#[derive(Default)]
+pub struct CommandPalette {
+    requested_focus: bool,
+    query: String,
+    selected_index: usize,
+}
+
+impl CommandPalette {
+    /// Returns `Ok(None)` once closed, or `Ok(Some)` with the command to run.
+    pub fn draw(
+        mut self,
+        ctx: &egui::Context,
+        matcher: &FuzzyMatcher,
+        entries: Vec<PaletteEntry>,
+    ) -> Result<Option<Command>, Self> {
+        const MAX_SUGGESTIONS: usize = 12;
+
+        let mut ret = None;
+        Modal::default_area("command-palette-modal".into())
+            .order(egui::Order::Middle)
+            .show(ctx, |ui| {
+                Frame::window(ui.style()).show(ui, |ui| {
+                    ui.set_width(400.0);
+
+                    let up_pressed =
+                        ui.input_mut(|input| input.consume_key(Modifiers::NONE, Key::ArrowUp));
+                    let down_pressed =
+                        ui.input_mut(|input| input.consume_key(Modifiers::NONE, Key::ArrowDown));
+                    let enter_pressed =
+                        ui.input_mut(|input| input.consume_key(Modifiers::NONE, Key::Enter));
+                    let esc_pressed =
+                        ui.input_mut(|input| input.consume_key(Modifiers::NONE, Key::Escape));
+
+                    let response = ui.add(
+                        TextEdit::singleline(&mut self.query)
+                            .hint_text(tr(ui.ctx(), Text::TypeACommand))
+                            .desired_width(f32::INFINITY)
+                            .return_key(None)
+                            .lock_focus(true),
+                    );
+                    if !self.requested_focus {
+                        response.request_focus();
+                        self.requested_focus = true;
+                    }
+                    if response.changed() {
+                        self.selected_index = 0;
+                    }
+                    if esc_pressed {
+                        ret = Some(None);
+                    }
+
+                    // Everything is listed until something's typed
+                    let pattern = Some(self.query.as_str()).filter(|q| !q.trim().is_empty());
+                    let mut matches =
+                        matcher.match_list_indirect(pattern, entries.into_iter(), |entry| {
+                            entry.label.as_str()
+                        });
+                    if matches.is_empty() {
+                        ui.label(RichText::new("No matching commands").weak());
+                        return;
+                    }
+
+                    if down_pressed {
+                        self.selected_index = (self.selected_index + 1) % matches.len();
+                    }
+                    if up_pressed {
+                        self.selected_index = self
+                            .selected_index
+                            .checked_sub(1)
+                            .unwrap_or(matches.len() - 1);
+                    }
+                    self.selected_index = self.selected_index.min(matches.len() - 1);
+
+                    let mut clicked = None;
+                    ScrollArea::vertical()
+                        .max_height(ui.spacing().interact_size.y * MAX_SUGGESTIONS as f32)
+                        .show(ui, |ui| {
+                            ui.with_layout(Layout::top_down_justified(egui::Align::LEFT), |ui| {
+                                for (i, entry) in matches.iter().enumerate() {
+                                    let selected = i == self.selected_index;
+                                    let resp = match entry.checked {
+                                        Some(checked) => ui.selectable_label(
+                                            selected,
+                                            format!(
+                                                "{} {}",
+                                                if checked { "☑" } else { "☐" },
+                                                entry.label
+                                            ),
+                                        ),
+                                        None => ui.selectable_label(selected, &entry.label),
+                                    };
+                                    if selected && (up_pressed || down_pressed) {
+                                        resp.scroll_to_me(None);
+                                    }
+                                    if resp.clicked() {
+                                        clicked = Some(i);
+                                    }
+                                }
+                            });
+                        });
+
+                    if let Some(index) = clicked.or(enter_pressed.then_some(self.selected_index)) {
+                        ret = Some(Some(matches.swap_remove(index).command));
+                    }
+                });
+            });
+        ret.ok_or(self)
+    }
+}
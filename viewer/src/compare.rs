@@ -0,0 +1,136 @@
+use crate::{
+    backend::Backend,
+    data::web::{VersionInfo, WebFileProvider},
+    settings::{BACKEND_CONFIG, BackendConfig, InstallLocation},
+    utils::{ConvertiblePromise, GameVersion, PromiseKind, TrackedPromise, UnsendPromise},
+};
+
+type VersionPromise =
+    ConvertiblePromise<TrackedPromise<anyhow::Result<VersionInfo>>, Option<VersionInfo>>;
+
+/// Picks a second game version to load alongside the current web backend, so sheets can be
+/// diffed against it.
+pub struct CompareWindow {
+    config: BackendConfig,
+    versions: VersionPromise,
+    selected: Option<GameVersion>,
+    load_promise: Option<UnsendPromise<anyhow::Result<Backend>>>,
+    error: Option<String>,
+}
+
+impl CompareWindow {
+    /// Returns `None` if the current backend isn't a web backend; other backends only ever
+    /// expose a single version.
+    pub fn new(ctx: &egui::Context) -> Option<Self> {
+        let config = BACKEND_CONFIG.get(ctx)?;
+        let InstallLocation::Web(url, region, _) = &config.location else {
+            return None;
+        };
+        let url = url.clone();
+        let slug = region.slug()?.to_string();
+        Some(Self {
+            config,
+            versions: ConvertiblePromise::new_promise(TrackedPromise::spawn_local(async move {
+                WebFileProvider::get_versions(&url, &slug).await
+            })),
+            selected: None,
+            load_promise: None,
+            error: None,
+        })
+    }
+
+    /// Returns the loaded comparison backend once it's ready. `open` is cleared when the
+    /// window is closed or the backend has loaded.
+    pub fn draw(&mut self, ctx: &egui::Context, open: &mut bool) -> Option<(GameVersion, Backend)> {
+        let mut ret = None;
+        let mut is_open = *open;
+        egui::Window::new("Compare Versions")
+            .open(&mut is_open)
+            .collapsible(false)
+            .resizable(false)
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                let current = match &self.config.location {
+                    InstallLocation::Web(_, _, version) => version.clone(),
+                    _ => None,
+                };
+
+                let versions = self.versions.get(|r| match r {
+                    Ok(v) => Some(v),
+                    Err(e) => {
+                        log::error!("Error fetching versions: {e}");
+                        None
+                    }
+                });
+                let Some(versions) = versions else {
+                    ui.label("Loading versions...");
+                    return;
+                };
+                let Some(versions) = versions else {
+                    ui.label("Failed to load versions");
+                    return;
+                };
+
+                ui.label(format!(
+                    "Current: {}",
+                    current.as_ref().unwrap_or(&versions.latest)
+                ));
+                ui.horizontal(|ui| {
+                    ui.label("Compare against:");
+                    egui::ComboBox::from_id_salt("compare_version")
+                        .selected_text(
+                            self.selected
+                                .as_ref()
+                                .map_or_else(|| "Select…".to_string(), |v| v.to_string()),
+                        )
+                        .show_ui(ui, |ui| {
+                            for entry in &versions.versions {
+                                ui.selectable_value(
+                                    &mut self.selected,
+                                    Some(entry.clone()),
+                                    entry.to_string(),
+                                );
+                            }
+                        });
+                });
+
+                if let Some(error) = &self.error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+
+                let loading = self.load_promise.is_some();
+                ui.add_enabled_ui(self.selected.is_some() && !loading, |ui| {
+                    if ui.button("Compare").clicked()
+                        && let Some(version) = self.selected.clone()
+                    {
+                        let mut config = self.config.clone();
+                        if let InstallLocation::Web(_, _, v) = &mut config.location {
+                            *v = Some(version);
+                        }
+                        self.error = None;
+                        self.load_promise = Some(UnsendPromise::new(Backend::new(config)));
+                    }
+                });
+                if loading {
+                    ui.spinner();
+                }
+            });
+
+        if let Some(promise) = self.load_promise.take() {
+            match promise.try_take() {
+                Ok(Ok(backend)) => {
+                    ret = self.selected.clone().map(|v| (v, backend));
+                    is_open = false;
+                }
+                Ok(Err(e)) => {
+                    log::error!("Failed to load comparison backend: {e:?}");
+                    self.error = Some(e.to_string());
+                }
+                Err(promise) => self.load_promise = Some(promise),
+            }
+        }
+
+        *open = is_open;
+        ret
+    }
+}
@@ -0,0 +1,403 @@
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{Ipv4Addr, TcpListener, TcpStream},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{Receiver, Sender, SyncSender, TrySendError, channel, sync_channel},
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::{export::ExportFormat, sheet::FilterInputType};
+
+/// How long a connection waits for the UI to answer, which includes running exports.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(120);
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+const MAX_HEADER_BYTES: u64 = 16 * 1024;
+/// Connections handled at once. Any more are queued up to this many again, then turned away.
+const WORKERS: usize = 4;
+
+pub enum ControlCommand {
+    /// `GET /status`
+    Status,
+    /// `POST /open?sheet=Item&row=123.1`
+    Open {
+        sheet: String,
+        row: Option<(u32, Option<u16>)>,
+    },
+    /// `POST /filter?query=...&type=contains&sheet=Item`
+    Filter {
+        sheet: Option<String>,
+        kind: FilterInputType,
+        query: String,
+    },
+    /// `GET /export?format=csv`
+    Export { format: ExportFormat },
+}
+
+pub struct ControlReply {
+    status: u16,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl ControlReply {
+    pub fn ok() -> Self {
+        Self::json(&serde_json::json!({ "ok": true }))
+    }
+
+    pub fn json(value: &serde_json::Value) -> Self {
+        Self {
+            status: 200,
+            content_type: "application/json",
+            body: value.to_string().into_bytes(),
+        }
+    }
+
+    pub fn export(format: ExportFormat, body: Vec<u8>) -> Self {
+        Self {
+            status: 200,
+            content_type: match format {
+                ExportFormat::Csv => "text/csv; charset=utf-8",
+                ExportFormat::Html { .. } => "text/html; charset=utf-8",
+                ExportFormat::Json => "application/json",
+            },
+            body,
+        }
+    }
+
+    pub fn error(status: u16, message: impl ToString) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: serde_json::json!({ "ok": false, "error": message.to_string() })
+                .to_string()
+                .into_bytes(),
+        }
+    }
+}
+
+/// A command from a client, waiting for the UI to handle it.
+pub struct ControlRequest {
+    pub command: ControlCommand,
+    reply: Sender<ControlReply>,
+}
+
+impl ControlRequest {
+    /// Answers the request. Dropping it without a reply answers with a 500 instead.
+    pub fn respond(&self, reply: ControlReply) {
+        let _ = self.reply.send(reply);
+    }
+}
+
+/// A tiny HTTP server on localhost that lets external scripts drive the viewer. Commands are
+/// handed to the UI thread through [`Self::requests`]. Requests carrying an `Origin` header, or
+/// a `Host` other than the server's own, are refused, so web pages can't reach it through the
+/// user's browser or by rebinding their domain to localhost.
+pub struct ControlServer {
+    port: u16,
+    requests: Receiver<ControlRequest>,
+    stop: Arc<AtomicBool>,
+}
+
+impl ControlServer {
+    pub fn start(ctx: egui::Context, port: u16) -> std::io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+        listener.set_nonblocking(true)?;
+        let (tx, requests) = channel();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let thread_stop = stop.clone();
+        thread::Builder::new()
+            .name("control-server".to_owned())
+            .spawn(move || accept_loop(&listener, &tx, &ctx, &thread_stop))?;
+        log::info!("Control server listening on 127.0.0.1:{port}");
+
+        Ok(Self {
+            port,
+            requests,
+            stop,
+        })
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn requests(&self) -> impl Iterator<Item = ControlRequest> + '_ {
+        self.requests.try_iter()
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+fn accept_loop(
+    listener: &TcpListener,
+    tx: &Sender<ControlRequest>,
+    ctx: &egui::Context,
+    stop: &AtomicBool,
+) {
+    let port = listener.local_addr().map_or(0, |addr| addr.port());
+    let (queue, streams) = sync_channel::<TcpStream>(WORKERS);
+    let streams = Arc::new(Mutex::new(streams));
+    for _ in 0..WORKERS {
+        let streams = streams.clone();
+        let tx = tx.clone();
+        let ctx = ctx.clone();
+        let _ = thread::Builder::new()
+            .name("control-connection".to_owned())
+            .spawn(move || work(&streams, port, &tx, &ctx));
+    }
+
+    while !stop.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, _)) => enqueue(&queue, stream),
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                thread::sleep(ACCEPT_POLL_INTERVAL);
+            }
+            Err(e) => {
+                log::error!("Control server stopped: {e}");
+                return;
+            }
+        }
+    }
+    log::info!("Control server stopped");
+}
+
+fn enqueue(queue: &SyncSender<TcpStream>, stream: TcpStream) {
+    match queue.try_send(stream) {
+        Ok(()) => {}
+        Err(TrySendError::Full(stream)) => {
+            let _ = stream.set_nonblocking(false).and_then(|()| {
+                write_reply(&stream, &ControlReply::error(503, "Too many requests"))
+            });
+        }
+        Err(TrySendError::Disconnected(_)) => {}
+    }
+}
+
+/// Handles queued connections one at a time, until the accept loop stops.
+fn work(
+    streams: &Mutex<Receiver<TcpStream>>,
+    port: u16,
+    tx: &Sender<ControlRequest>,
+    ctx: &egui::Context,
+) {
+    loop {
+        let Ok(stream) = streams.lock().unwrap().recv() else {
+            return;
+        };
+        if let Err(e) = handle_connection(stream, port, tx, ctx) {
+            log::warn!("Control connection failed: {e}");
+        }
+    }
+}
+
+fn handle_connection(
+    stream: TcpStream,
+    port: u16,
+    tx: &Sender<ControlRequest>,
+    ctx: &egui::Context,
+) -> std::io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    // Headers past the limit could hide an `Origin`, so those requests aren't answered at all
+    let mut reader = BufReader::new((&stream).take(MAX_HEADER_BYTES));
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut has_origin = false;
+    let mut host_allowed = false;
+    loop {
+        let mut line = String::new();
+        let read = reader.read_line(&mut line)?;
+        if read == 0 && reader.get_ref().limit() == 0 {
+            return write_reply(
+                &stream,
+                &ControlReply::error(431, "Request headers are too large"),
+            );
+        }
+        if read == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            let name = name.trim();
+            if name.eq_ignore_ascii_case("origin") {
+                has_origin = true;
+            } else if name.eq_ignore_ascii_case("host") {
+                host_allowed = is_local_host(value.trim(), port);
+            }
+        }
+    }
+
+    let reply = if has_origin {
+        ControlReply::error(403, "Requests from browsers are not allowed")
+    } else if !host_allowed {
+        ControlReply::error(403, "Requests must be made to localhost")
+    } else {
+        match parse_request(&request_line) {
+            Ok(command) => {
+                let (reply_tx, reply_rx) = channel();
+                if tx
+                    .send(ControlRequest {
+                        command,
+                        reply: reply_tx,
+                    })
+                    .is_err()
+                {
+                    ControlReply::error(503, "The viewer is shutting down")
+                } else {
+                    ctx.request_repaint();
+                    reply_rx
+                        .recv_timeout(REPLY_TIMEOUT)
+                        .unwrap_or_else(|_| ControlReply::error(500, "No reply from the viewer"))
+                }
+            }
+            Err(reply) => reply,
+        }
+    };
+    write_reply(&stream, &reply)
+}
+
+fn write_reply(mut stream: &TcpStream, reply: &ControlReply) -> std::io::Result<()> {
+    let reason = match reply.status {
+        200 => "OK",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        431 => "Request Header Fields Too Large",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {reason}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        reply.status,
+        reply.content_type,
+        reply.body.len()
+    )?;
+    stream.write_all(&reply.body)?;
+    stream.flush()
+}
+
+fn parse_request(request_line: &str) -> Result<ControlCommand, ControlReply> {
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(ControlReply::error(400, "Malformed request"));
+    };
+    let url = url::Url::parse(&format!("http://localhost{target}"))
+        .map_err(|e| ControlReply::error(400, e))?;
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+    };
+
+    let expect_method = |expected: &str| {
+        if method.eq_ignore_ascii_case(expected) {
+            Ok(())
+        } else {
+            Err(ControlReply::error(405, format!("Use {expected}")))
+        }
+    };
+
+    match url.path() {
+        "/status" => {
+            expect_method("GET")?;
+            Ok(ControlCommand::Status)
+        }
+        "/open" => {
+            expect_method("POST")?;
+            let sheet = param("sheet").ok_or_else(|| ControlReply::error(400, "Missing sheet"))?;
+            let row = param("row")
+                .map(|row| parse_row(&row).ok_or_else(|| ControlReply::error(400, "Bad row")))
+                .transpose()?;
+            Ok(ControlCommand::Open { sheet, row })
+        }
+        "/filter" => {
+            expect_method("POST")?;
+            let kind = match param("type").as_deref() {
+                None | Some("contains") => FilterInputType::Contains,
+                Some("equals") => FilterInputType::Equals,
+                Some("complex") => FilterInputType::Complex,
+                Some(other) => {
+                    return Err(ControlReply::error(400, format!("Unknown type {other}")));
+                }
+            };
+            Ok(ControlCommand::Filter {
+                sheet: param("sheet"),
+                kind,
+                query: param("query").unwrap_or_default(),
+            })
+        }
+        "/export" => {
+            expect_method("GET")?;
+            let format = match param("format").as_deref() {
+                None | Some("csv") => ExportFormat::Csv,
+                Some("json") => ExportFormat::Json,
+                Some("html") => ExportFormat::Html { embed_icons: false },
+                Some("html-icons") => ExportFormat::Html { embed_icons: true },
+                Some(other) => {
+                    return Err(ControlReply::error(400, format!("Unknown format {other}")));
+                }
+            };
+            Ok(ControlCommand::Export { format })
+        }
+        path => Err(ControlReply::error(404, format!("Unknown endpoint {path}"))),
+    }
+}
+
+/// Whether a `Host` header names this server, rather than some domain that resolves to it.
+fn is_local_host(host: &str, port: u16) -> bool {
+    host.rsplit_once(':').is_some_and(|(name, host_port)| {
+        (name == "127.0.0.1" || name.eq_ignore_ascii_case("localhost"))
+            && host_port.parse() == Ok(port)
+    })
+}
+
+/// Parses `123` or `123.4`.
+fn parse_row(row: &str) -> Option<(u32, Option<u16>)> {
+    match row.split_once('.') {
+        Some((row_id, subrow_id)) => Some((row_id.parse().ok()?, Some(subrow_id.parse().ok()?))),
+        None => Some((row.parse().ok()?, None)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ControlCommand, is_local_host, parse_request, parse_row};
+
+    #[test]
+    fn requests() {
+        assert!(matches!(
+            parse_request("POST /open?sheet=Item&row=5.2 HTTP/1.1\r\n"),
+            Ok(ControlCommand::Open { sheet, row: Some((5, Some(2))) }) if sheet == "Item"
+        ));
+        assert!(matches!(
+            parse_request("GET /export?format=json HTTP/1.1\r\n"),
+            Ok(ControlCommand::Export { .. })
+        ));
+        assert!(parse_request("GET /open?sheet=Item HTTP/1.1\r\n").is_err());
+        assert!(parse_request("GET /nope HTTP/1.1\r\n").is_err());
+        assert_eq!(parse_row("12"), Some((12, None)));
+        assert_eq!(parse_row("x"), None);
+    }
+
+    #[test]
+    fn hosts() {
+        assert!(is_local_host("127.0.0.1:7007", 7007));
+        assert!(is_local_host("LocalHost:7007", 7007));
+        assert!(!is_local_host("localhost:7008", 7007));
+        assert!(!is_local_host("localhost", 7007));
+        assert!(!is_local_host("evil.example:7007", 7007));
+    }
+}
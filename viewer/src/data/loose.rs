@@ -0,0 +1,139 @@
+use std::io::{self, Cursor, Read};
+
+use image::DynamicImage;
+use ironworks::{
+    file::{File, tex},
+    sqpack::Vfs,
+};
+
+use crate::utils::tex_loader;
+
+/// Game files extracted to a folder as they're named in the game, like `exd/Item.exh`, by
+/// other tools. The folder can also be the `exd` folder itself.
+pub struct LooseFiles<V: Vfs> {
+    vfs: V,
+    /// Whether the folder is the `exd` folder, so sheet paths are read without it
+    in_exd: bool,
+}
+
+impl<V: Vfs> LooseFiles<V> {
+    /// Hands the folder back if it doesn't have a sheet list, like when it's a sqpack install.
+    pub fn detect(vfs: V) -> Result<Self, V> {
+        if vfs.exists("exd/root.exl") {
+            Ok(Self { vfs, in_exd: false })
+        } else if vfs.exists("root.exl") {
+            Ok(Self { vfs, in_exd: true })
+        } else {
+            Err(vfs)
+        }
+    }
+
+    fn path<'a>(&self, path: &'a str) -> &'a str {
+        if self.in_exd {
+            path.strip_prefix("exd/").unwrap_or(path)
+        } else {
+            path
+        }
+    }
+
+    pub fn exists(&self, path: &str) -> bool {
+        self.vfs.exists(self.path(path))
+    }
+
+    pub fn read(&self, path: &str) -> io::Result<Vec<u8>> {
+        let mut data = Vec::new();
+        self.vfs.open(self.path(path))?.read_to_end(&mut data)?;
+        Ok(data)
+    }
+
+    pub fn texture(&self, path: &str) -> anyhow::Result<DynamicImage> {
+        let texture = tex::Texture::read(Cursor::new(self.read(path)?))?;
+        tex_loader::decode(texture, path)
+    }
+}
+
+/// A plain folder on disk.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct FolderVfs(pub std::path::PathBuf);
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Vfs for FolderVfs {
+    type File = io::BufReader<std::fs::File>;
+
+    fn exists(&self, path: impl AsRef<std::path::Path>) -> bool {
+        self.0.join(path).exists()
+    }
+
+    fn open(&self, path: impl AsRef<std::path::Path>) -> io::Result<Self::File> {
+        Ok(io::BufReader::new(std::fs::File::open(self.0.join(path))?))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use native::LooseFileProvider;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use async_trait::async_trait;
+    use either::Either;
+    use image::RgbaImage;
+    use url::Url;
+
+    use crate::{
+        data::{FileProvider, get_icon_path},
+        utils::ProviderResult,
+    };
+
+    use super::{FolderVfs, LooseFiles};
+
+    pub struct LooseFileProvider(LooseFiles<FolderVfs>);
+
+    impl LooseFileProvider {
+        /// `None` if the folder doesn't have loose files.
+        pub fn new(folder: &str) -> Option<Self> {
+            LooseFiles::detect(FolderVfs(folder.into())).ok().map(Self)
+        }
+    }
+
+    #[async_trait(?Send)]
+    impl FileProvider for LooseFileProvider {
+        async fn read(&self, path: &str) -> ProviderResult<Vec<u8>> {
+            Ok(self.0.read(path)?)
+        }
+
+        async fn get_icon(
+            &self,
+            icon_id: u32,
+            hires: bool,
+        ) -> anyhow::Result<Either<Url, RgbaImage>> {
+            let path = get_icon_path(icon_id, hires);
+            Ok(Either::Right(self.0.texture(&path)?.into_rgba8()))
+        }
+
+        async fn exists_many(&self, paths: &[String]) -> anyhow::Result<Vec<bool>> {
+            Ok(paths.iter().map(|path| self.0.exists(path)).collect())
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod test {
+    use super::{FolderVfs, LooseFiles};
+
+    #[test]
+    fn reads_from_either_folder() {
+        let root = std::env::temp_dir().join(format!("exdviewer-loose-{}", std::process::id()));
+        let exd = root.join("exd");
+        std::fs::create_dir_all(&exd).unwrap();
+        std::fs::write(exd.join("root.exl"), "EXLT,2\nItem,0\n").unwrap();
+
+        let files = LooseFiles::detect(FolderVfs(root.clone())).ok().unwrap();
+        assert!(files.exists("exd/root.exl"));
+        let files = LooseFiles::detect(FolderVfs(exd.clone())).ok().unwrap();
+        assert_eq!(files.read("exd/root.exl").unwrap(), b"EXLT,2\nItem,0\n");
+        assert!(!files.exists("exd/Item.exh"));
+        assert!(LooseFiles::detect(FolderVfs(root.join("sqpack"))).is_err());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
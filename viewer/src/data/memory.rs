@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use either::Either;
+use image::RgbaImage;
+use ironworks::excel::path;
+use url::Url;
+
+use crate::utils::{ProviderError, ProviderResult};
+
+use super::{FileProvider, get_icon_path, get_xivapi_asset_url};
+
+/// A single sheet's files, like ones dropped onto the window, kept in memory. The sheet list
+/// only has that sheet. Icons aren't part of it, so they're taken from XIVAPI.
+pub struct MemoryFileProvider {
+    /// By lowercased game path, since file names don't always keep the game's casing
+    files: HashMap<String, Vec<u8>>,
+}
+
+impl MemoryFileProvider {
+    /// Takes an .exh and its .exd pages by file name, returning the name of the sheet.
+    pub fn from_sheet_files(
+        files: impl IntoIterator<Item = (String, Vec<u8>)>,
+    ) -> anyhow::Result<(Self, String)> {
+        let mut sheet_name = None;
+        let mut contents = HashMap::new();
+        for (file_name, data) in files {
+            let lower = file_name.to_lowercase();
+            if lower.ends_with(".exh") {
+                let name = std::path::Path::new(&file_name)
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .unwrap_or_default();
+                if let Some(other) = sheet_name.replace(name.to_owned()) {
+                    anyhow::bail!("Drop the files of one sheet at a time, not {other} and {name}");
+                }
+                contents.insert(path::exh(name).to_lowercase(), data);
+            } else if lower.ends_with(".exd") {
+                contents.insert(format!("exd/{lower}"), data);
+            } else {
+                log::warn!("Ignoring {file_name}, which isn't an .exh or .exd file");
+            }
+        }
+        let Some(sheet_name) = sheet_name else {
+            anyhow::bail!("The sheet's .exh file is needed to open its .exd files");
+        };
+        contents.insert(
+            path::exl().to_lowercase(),
+            format!("EXLT,2\n{sheet_name},0\n").into_bytes(),
+        );
+        Ok((Self { files: contents }, sheet_name))
+    }
+}
+
+#[async_trait(?Send)]
+impl FileProvider for MemoryFileProvider {
+    async fn read(&self, path: &str) -> ProviderResult<Vec<u8>> {
+        self.files
+            .get(&path.to_lowercase())
+            .cloned()
+            .ok_or_else(|| ProviderError::NotFound(format!("{path} wasn't dropped")))
+    }
+
+    async fn get_icon(&self, icon_id: u32, hires: bool) -> anyhow::Result<Either<Url, RgbaImage>> {
+        let path = get_icon_path(icon_id, hires);
+        Ok(Either::Left(get_xivapi_asset_url(&path, Some("png"))))
+    }
+
+    async fn exists_many(&self, paths: &[String]) -> anyhow::Result<Vec<bool>> {
+        Ok(paths
+            .iter()
+            .map(|path| self.files.contains_key(&path.to_lowercase()))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use futures_util::FutureExt;
+    use ironworks::excel::{Language, path};
+
+    use super::MemoryFileProvider;
+    use crate::data::FileProvider;
+
+    #[test]
+    fn sheet_files_are_found_by_game_path() {
+        let (files, sheet_name) = MemoryFileProvider::from_sheet_files([
+            ("Item.exh".to_owned(), vec![1]),
+            ("item_0_en.exd".to_owned(), vec![2]),
+            ("readme.txt".to_owned(), vec![3]),
+        ])
+        .unwrap();
+        assert_eq!(sheet_name, "Item");
+        let read = |path: String| files.read(&path).now_or_never().unwrap().ok();
+        assert_eq!(read(path::exh("Item")), Some(vec![1]));
+        assert_eq!(read(path::exd("Item", 0, Language::English)), Some(vec![2]));
+        assert_eq!(read(path::exd("Item", 0, Language::German)), None);
+        assert!(read(path::exl().to_owned()).is_some());
+
+        assert!(MemoryFileProvider::from_sheet_files([("a_0.exd".to_owned(), vec![])]).is_err());
+    }
+}
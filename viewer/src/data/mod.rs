@@ -2,28 +2,71 @@ use std::io::Cursor;
 use std::sync::LazyLock;
 
 use async_trait::async_trait;
+pub use common::path::get_icon_path;
 use either::Either;
 use image::RgbaImage;
 use ironworks::file::File;
 use url::Url;
 
+use crate::utils::ProviderResult;
+
+pub mod loose;
+pub mod memory;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod sqpack;
 pub mod web;
+pub mod web_cache;
 #[cfg(target_arch = "wasm32")]
 pub mod worker;
 
+/// An icon read by [`FileProvider::get_icon_if_changed`].
+pub enum IconRevision {
+    /// The icon, with a tag of its content if the provider has one.
+    Changed(Either<Url, RgbaImage>, Option<String>),
+    /// Identical to the copy with the tag that was passed in.
+    Unchanged,
+}
+
 /// Reads raw game files by path from some backing store (a local sqpack install,
 /// the web API, or an in-browser worker). Higher-level readers (excel, sound, …)
 /// are layered on top of this.
 #[async_trait(?Send)]
 pub trait FileProvider {
     /// Read a file's raw bytes by path.
-    async fn read(&self, path: &str) -> anyhow::Result<Vec<u8>>;
+    async fn read(&self, path: &str) -> ProviderResult<Vec<u8>>;
 
     async fn get_icon(&self, icon_id: u32, hires: bool) -> anyhow::Result<Either<Url, RgbaImage>>;
 
+    /// [`Self::get_icon`], unless the icon is identical to the copy tagged `etag`, which may
+    /// have been read from another version. Providers that can't tell cheaply read it again.
+    async fn get_icon_if_changed(
+        &self,
+        icon_id: u32,
+        hires: bool,
+        _etag: Option<&str>,
+    ) -> anyhow::Result<IconRevision> {
+        Ok(IconRevision::Changed(
+            self.get_icon(icon_id, hires).await?,
+            None,
+        ))
+    }
+
     async fn exists_many(&self, paths: &[String]) -> anyhow::Result<Vec<bool>>;
+
+    /// Read several files at once, like all pages of a sheet. Results are in the order of
+    /// `paths`.
+    async fn read_many(&self, paths: &[String]) -> Vec<ProviderResult<Vec<u8>>> {
+        futures_util::future::join_all(paths.iter().map(|path| self.read(path))).await
+    }
+
+    /// Whether other game versions than the one being read can be listed and loaded.
+    fn supports_versions(&self) -> bool {
+        false
+    }
+
+    fn supports_icons(&self) -> bool {
+        true
+    }
 }
 
 /// Typed reads layered on [`FileProvider`]. Blanket-implemented for every
@@ -32,30 +75,35 @@ pub trait FileProvider {
 pub trait FileProviderExt: FileProvider {
     /// Read and parse a file into an ironworks [`File`] type. Pass `Vec<u8>` for
     /// raw bytes.
-    fn file<T: File>(&self, path: &str) -> impl std::future::Future<Output = anyhow::Result<T>> {
+    fn file<T: File>(&self, path: &str) -> impl std::future::Future<Output = ProviderResult<T>> {
         async move {
             let bytes = self.read(path).await?;
             Ok(T::read(Cursor::new(bytes))?)
         }
     }
+
+    /// [`Self::file`] for several files at once, through [`FileProvider::read_many`].
+    fn files<T: File>(
+        &self,
+        paths: &[String],
+    ) -> impl std::future::Future<Output = Vec<ProviderResult<T>>> {
+        async move {
+            self.read_many(paths)
+                .await
+                .into_iter()
+                .map(|bytes| Ok(T::read(Cursor::new(bytes?))?))
+                .collect()
+        }
+    }
 }
 
 impl<P: FileProvider + ?Sized> FileProviderExt for P {}
 
-pub fn get_icon_path(icon_id: u32, hires: bool) -> String {
-    format!(
-        "ui/icon/{:03}000/{:06}{}.tex",
-        icon_id / 1000,
-        icon_id,
-        if hires { "_hr1" } else { "" }
-    )
-}
-
 static XIVAPI_BASE_URL: LazyLock<Url> = LazyLock::new(|| {
     Url::parse("https://v2.xivapi.com/api/asset").expect("Failed to parse XIVAPI base URL")
 });
 
-fn get_xivapi_asset_url(path: &str, format: Option<&str>) -> Url {
+pub(crate) fn get_xivapi_asset_url(path: &str, format: Option<&str>) -> Url {
     let mut url = XIVAPI_BASE_URL.clone();
     {
         let mut pairs = url.query_pairs_mut();
@@ -1,14 +1,21 @@
-use crate::utils::tex_loader;
+use crate::utils::{ProviderResult, tex_loader};
 
 use super::{FileProvider, get_icon_path};
 use async_trait::async_trait;
+use egui::mutex::Mutex;
 use either::Either;
 use image::RgbaImage;
 use ironworks::{
     Ironworks,
     sqpack::{Install, SqPack},
 };
-use std::{path::PathBuf, str::FromStr};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher, event::ModifyKind};
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+    time::Instant,
+};
 use url::Url;
 
 pub struct SqpackFileProvider(Ironworks<SqPack<Install>>);
@@ -24,7 +31,7 @@ impl SqpackFileProvider {
 
 #[async_trait(?Send)]
 impl FileProvider for SqpackFileProvider {
-    async fn read(&self, path: &str) -> anyhow::Result<Vec<u8>> {
+    async fn read(&self, path: &str) -> ProviderResult<Vec<u8>> {
         Ok(self.0.file::<Vec<u8>>(path)?)
     }
 
@@ -42,3 +49,85 @@ impl FileProvider for SqpackFileProvider {
         Ok(result)
     }
 }
+
+/// Watches the data files of an install, to tell when the game was patched while it's open.
+pub struct SqpackWatcher {
+    _watcher: RecommendedWatcher,
+    last_change: Arc<Mutex<Option<Instant>>>,
+}
+
+impl SqpackWatcher {
+    pub fn new(install_location: &str, ctx: egui::Context) -> notify::Result<Self> {
+        let last_change = Arc::new(Mutex::new(None));
+        let mut watcher = notify::recommended_watcher({
+            let last_change = last_change.clone();
+            move |event: notify::Result<Event>| match event {
+                Ok(event) if is_data_change(&event) => {
+                    *last_change.lock() = Some(Instant::now());
+                    ctx.request_repaint();
+                }
+                Ok(_) => {}
+                Err(e) => log::warn!("Failed to watch install: {e}"),
+            }
+        })?;
+        watcher.watch(Path::new(install_location), RecursiveMode::Recursive)?;
+        Ok(Self {
+            _watcher: watcher,
+            last_change,
+        })
+    }
+
+    /// When a data file of the install last changed, if one did since it started watching.
+    pub fn last_change(&self) -> Option<Instant> {
+        *self.last_change.lock()
+    }
+}
+
+/// Whether the event writes to an index or dat file, or a loose sheet file. Reads and metadata
+/// changes are ignored.
+fn is_data_change(event: &Event) -> bool {
+    let writes = matches!(
+        event.kind,
+        EventKind::Create(_)
+            | EventKind::Remove(_)
+            | EventKind::Modify(ModifyKind::Data(_) | ModifyKind::Name(_) | ModifyKind::Any)
+    );
+    writes
+        && event.paths.iter().any(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| {
+                    ext.starts_with("index")
+                        || ext.starts_with("dat")
+                        || matches!(ext, "exl" | "exh" | "exd")
+                })
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use notify::{
+        Event, EventKind,
+        event::{AccessKind, DataChange, ModifyKind, RemoveKind},
+    };
+
+    use super::is_data_change;
+
+    #[test]
+    fn only_data_writes_count() {
+        let event = |kind, path: &str| Event::new(kind).add_path(path.into());
+        let write = EventKind::Modify(ModifyKind::Data(DataChange::Content));
+        assert!(is_data_change(&event(write, "ffxiv/0a0000.win32.dat0")));
+        assert!(is_data_change(&event(write, "ffxiv/0a0000.win32.index2")));
+        assert!(is_data_change(&event(
+            EventKind::Remove(RemoveKind::File),
+            "ffxiv/0a0000.win32.index"
+        )));
+        assert!(is_data_change(&event(write, "exd/item_0_en.exd")));
+        assert!(!is_data_change(&event(write, "ffxivgame.ver.bck")));
+        assert!(!is_data_change(&event(
+            EventKind::Access(AccessKind::Any),
+            "ffxiv/0a0000.win32.dat0"
+        )));
+    }
+}
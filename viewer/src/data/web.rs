@@ -1,35 +1,29 @@
-use crate::utils::{GameVersion, fetch_url};
+use std::cell::Cell;
 
-use super::{FileProvider, get_icon_path, get_xivapi_asset_url};
+use crate::utils::{
+    GameVersion, ProviderError, ProviderResult, fetch_url_limited, fetch_url_limited_with,
+    post_json_limited,
+};
+
+use super::{FileProvider, IconRevision, get_icon_path, get_xivapi_asset_url, web_cache::WebCache};
 use async_trait::async_trait;
+pub use common::api::{RepositoryInfo, VersionInfo};
+use common::{
+    api::{BatchRequest, ExistsResponse, RepositoriesResponse},
+    batch::{self, Part},
+};
 use either::Either;
-use image::RgbaImage;
-use serde::Deserialize;
+use image::{ImageFormat, RgbaImage};
 use url::Url;
 
-pub struct WebFileProvider(Url);
-
-#[derive(Debug, Clone, Deserialize)]
-pub struct VersionInfo {
-    pub latest: GameVersion,
-    pub versions: Vec<GameVersion>,
-}
-
-#[derive(Debug, Clone, Deserialize)]
-pub struct RepositoryInfo {
-    pub slug: String,
-    pub name: String,
-    pub latest: GameVersion,
-}
-
-#[derive(Debug, Clone, Deserialize)]
-struct RepositoriesResponse {
-    repositories: Vec<RepositoryInfo>,
-}
-
-#[derive(Debug, Clone, Deserialize)]
-struct ExistsResponse {
-    exists: Vec<bool>,
+pub struct WebFileProvider {
+    base_url: Url,
+    /// Whether the server converts icons itself, so they don't have to come from XIVAPI
+    serves_icons: bool,
+    /// Set once the server turns out not to serve batch requests, like ones from before they
+    /// could be made. Files are then read one by one.
+    batch_unsupported: Cell<bool>,
+    cache: Option<WebCache>,
 }
 
 impl WebFileProvider {
@@ -39,10 +33,14 @@ impl WebFileProvider {
         version: Option<GameVersion>,
     ) -> anyhow::Result<Self> {
         let version_info = Self::get_versions(base_url, slug).await?;
+        let serves_icons = version_info.icons;
 
         let version = if let Some(v) = version {
             if !version_info.versions.contains(&v) {
-                anyhow::bail!("Version {v} is not available");
+                return Err(ProviderError::VersionMismatch(format!(
+                    "Version {v} is not available"
+                ))
+                .into());
             }
             v
         } else {
@@ -53,6 +51,7 @@ impl WebFileProvider {
             version_info.latest
         };
 
+        let cache = WebCache::open(base_url).await;
         let mut base_url = Url::parse(base_url)?;
         base_url
             .path_segments_mut()
@@ -65,7 +64,12 @@ impl WebFileProvider {
             .push(slug)
             .push(&version.to_string());
 
-        Ok(Self(base_url))
+        Ok(Self {
+            base_url,
+            serves_icons,
+            batch_unsupported: Cell::new(false),
+            cache,
+        })
     }
 
     pub async fn get_versions(base_url: &str, slug: &str) -> anyhow::Result<VersionInfo> {
@@ -81,7 +85,19 @@ impl WebFileProvider {
             .push(slug)
             .push("versions");
 
-        let resp = fetch_url(url).await?;
+        let cache = WebCache::open(base_url).await;
+        let resp = match (fetch_url_limited(url.clone()).await, &cache) {
+            (Ok(resp), Some(cache)) => {
+                cache.put(url.as_str(), &resp).await;
+                resp
+            }
+            // Still lets versions that were opened before be picked while offline
+            (Err(ProviderError::Network(e)), Some(cache)) => cache
+                .get(url.as_str())
+                .await
+                .ok_or(ProviderError::Network(e))?,
+            (resp, _) => resp?,
+        };
 
         let mut vers: VersionInfo = serde_json::from_slice(&resp)?;
         vers.versions.sort();
@@ -101,7 +117,7 @@ impl WebFileProvider {
             })?
             .push("repositories");
 
-        let resp = fetch_url(url).await?;
+        let resp = fetch_url_limited(url).await?;
 
         let parsed: RepositoriesResponse = serde_json::from_slice(&resp)?;
         Ok(parsed.repositories)
@@ -110,9 +126,40 @@ impl WebFileProvider {
 
 #[async_trait(?Send)]
 impl FileProvider for WebFileProvider {
-    async fn read(&self, path: &str) -> anyhow::Result<Vec<u8>> {
-        let mut url = self.0.clone();
+    async fn read(&self, path: &str) -> ProviderResult<Vec<u8>> {
+        if let Some(data) = self.cached(path).await {
+            return Ok(data);
+        }
+        let data = self.fetch(path).await?;
+        self.keep(path, &data).await;
+        Ok(data)
+    }
+
+    async fn get_icon(&self, icon_id: u32, hires: bool) -> anyhow::Result<Either<Url, RgbaImage>> {
+        match self.get_icon_if_changed(icon_id, hires, None).await? {
+            IconRevision::Changed(icon, _) => Ok(icon),
+            IconRevision::Unchanged => unreachable!("no tag was sent"),
+        }
+    }
+
+    /// Icons the server converts are downloaded here rather than by egui, so they go through
+    /// the request limit and can be revalidated by their tag after switching versions. XIVAPI's
+    /// don't depend on the version, so egui's own cache of them is kept.
+    async fn get_icon_if_changed(
+        &self,
+        icon_id: u32,
+        hires: bool,
+        etag: Option<&str>,
+    ) -> anyhow::Result<IconRevision> {
+        if !self.serves_icons {
+            let path = get_icon_path(icon_id, hires);
+            return Ok(IconRevision::Changed(
+                Either::Left(get_xivapi_asset_url(&path, Some("png"))),
+                None,
+            ));
+        }
 
+        let mut url = self.base_url.clone();
         url.path_segments_mut()
             .map_err(|()| {
                 ironworks::Error::Invalid(
@@ -120,15 +167,29 @@ impl FileProvider for WebFileProvider {
                     "path parsing error".to_string(),
                 )
             })?
-            .extend(path.split('/'));
-
-        Ok(fetch_url(url).await?)
-    }
+            .push("icon")
+            .push(&icon_id.to_string());
+        url.query_pairs_mut()
+            .append_pair("hires", &hires.to_string())
+            .append_pair("format", "png");
 
-    async fn get_icon(&self, icon_id: u32, hires: bool) -> anyhow::Result<Either<Url, RgbaImage>> {
-        let path = get_icon_path(icon_id, hires);
-        let url = get_xivapi_asset_url(&path, Some("png"));
-        Ok(Either::Left(url))
+        let headers = etag.map(|etag| ("If-None-Match", etag));
+        let resp = fetch_url_limited_with(url, headers.as_slice()).await?;
+        if resp.status == 304 {
+            return Ok(IconRevision::Unchanged);
+        }
+        if !resp.ok {
+            return Err(ProviderError::from_status(
+                resp.status,
+                format!("Icon request failed ({}): {}", resp.status, resp.text()),
+            )
+            .into());
+        }
+        let image = image::load_from_memory_with_format(&resp.bytes, ImageFormat::Png)?;
+        Ok(IconRevision::Changed(
+            Either::Right(image.into_rgba8()),
+            resp.etag,
+        ))
     }
 
     async fn exists_many(&self, paths: &[String]) -> anyhow::Result<Vec<bool>> {
@@ -136,7 +197,7 @@ impl FileProvider for WebFileProvider {
             return Ok(Vec::new());
         }
 
-        let mut url = self.0.clone();
+        let mut url = self.base_url.clone();
         url.path_segments_mut()
             .map_err(|()| {
                 ironworks::Error::Invalid(
@@ -147,8 +208,184 @@ impl FileProvider for WebFileProvider {
             .push("exists");
         url.query_pairs_mut().append_pair("files", &paths.join(","));
 
-        let resp = fetch_url(url).await?;
+        let resp = match fetch_url_limited(url).await {
+            Ok(resp) => resp,
+            // Lets sheets that were opened before still list their languages while offline
+            Err(ProviderError::Network(e)) if self.cache.is_some() => {
+                log::warn!("Checking downloaded files instead: {e}");
+                let cached =
+                    futures_util::future::join_all(paths.iter().map(|path| self.cached(path)));
+                return Ok(cached.await.iter().map(Option::is_some).collect());
+            }
+            Err(e) => return Err(e.into()),
+        };
         let parsed: ExistsResponse = serde_json::from_slice(&resp)?;
         Ok(parsed.exists)
     }
+
+    async fn read_many(&self, paths: &[String]) -> Vec<ProviderResult<Vec<u8>>> {
+        let cached =
+            futures_util::future::join_all(paths.iter().map(|path| self.cached(path))).await;
+        let missing = paths
+            .iter()
+            .zip(&cached)
+            .filter(|(_, data)| data.is_none())
+            .map(|(path, _)| path.clone())
+            .collect::<Vec<_>>();
+        let mut fetched = self.fetch_many(&missing).await.into_iter();
+
+        let mut ret = Vec::with_capacity(paths.len());
+        for (path, data) in paths.iter().zip(cached) {
+            let data = match data {
+                Some(data) => Ok(data),
+                None => {
+                    let data = fetched.next().expect("a result for every missing file");
+                    if let Ok(data) = &data {
+                        self.keep(path, data).await;
+                    }
+                    data
+                }
+            };
+            ret.push(data);
+        }
+        ret
+    }
+
+    fn supports_versions(&self) -> bool {
+        true
+    }
+}
+
+/// Sheet files, which are what's kept in the [`WebCache`].
+fn is_cached_file(path: &str) -> bool {
+    [".exl", ".exh", ".exd"]
+        .iter()
+        .any(|ext| path.ends_with(ext))
+}
+
+impl WebFileProvider {
+    fn file_url(&self, path: &str) -> ProviderResult<Url> {
+        let mut url = self.base_url.clone();
+        url.path_segments_mut()
+            .map_err(|()| {
+                ironworks::Error::Invalid(
+                    ironworks::ErrorValue::Other("URL".to_string()),
+                    "path parsing error".to_string(),
+                )
+            })?
+            .extend(path.split('/'));
+        Ok(url)
+    }
+
+    async fn fetch(&self, path: &str) -> ProviderResult<Vec<u8>> {
+        fetch_url_limited(self.file_url(path)?).await
+    }
+
+    /// A previously fetched file, if it's still there.
+    async fn cached(&self, path: &str) -> Option<Vec<u8>> {
+        if let Some(cache) = &self.cache
+            && is_cached_file(path)
+        {
+            return cache.get(self.file_url(path).ok()?.as_str()).await;
+        }
+        None
+    }
+
+    /// Keeps a fetched file for next time.
+    async fn keep(&self, path: &str, data: &[u8]) {
+        if let Some(cache) = &self.cache
+            && is_cached_file(path)
+            && let Ok(url) = self.file_url(path)
+        {
+            cache.put(url.as_str(), data).await;
+        }
+    }
+
+    async fn fetch_many(&self, paths: &[String]) -> Vec<ProviderResult<Vec<u8>>> {
+        if paths.len() < 2 || self.batch_unsupported.get() {
+            return futures_util::future::join_all(paths.iter().map(|path| self.fetch(path))).await;
+        }
+
+        let batches = paths
+            .chunks(batch::MAX_FILES)
+            .map(|chunk| self.read_batch(chunk));
+        let mut ret = Vec::with_capacity(paths.len());
+        for (chunk, result) in paths
+            .chunks(batch::MAX_FILES)
+            .zip(futures_util::future::join_all(batches).await)
+        {
+            match result {
+                Ok(files) => ret.extend(files),
+                Err(e) => {
+                    log::warn!("Batch request failed, reading files one by one: {e}");
+                    if e.is::<BatchUnsupported>() {
+                        self.batch_unsupported.set(true);
+                    }
+                    let files = chunk.iter().map(|path| self.fetch(path));
+                    ret.extend(futures_util::future::join_all(files).await);
+                }
+            }
+        }
+        ret
+    }
+
+    async fn read_batch(&self, paths: &[String]) -> anyhow::Result<Vec<ProviderResult<Vec<u8>>>> {
+        let mut url = self.base_url.clone();
+        url.path_segments_mut()
+            .map_err(|()| {
+                ironworks::Error::Invalid(
+                    ironworks::ErrorValue::Other("URL".to_string()),
+                    "path parsing error".to_string(),
+                )
+            })?
+            .push("batch");
+        let body = serde_json::to_vec(&BatchRequest {
+            files: paths.to_vec(),
+        })?;
+
+        let resp = post_json_limited(url, body).await?;
+        if matches!(resp.status, 404 | 405 | 501) {
+            return Err(BatchUnsupported(format!("the server answered {}", resp.status)).into());
+        }
+        if !resp.ok {
+            return Err(ProviderError::from_status(
+                resp.status,
+                format!("Batch request failed ({}): {}", resp.status, resp.text()),
+            )
+            .into());
+        }
+        // Servers without the endpoint may answer with a page instead, like an SPA fallback
+        let content_type = resp.content_type.unwrap_or_default();
+        if !content_type.starts_with("multipart/mixed") {
+            return Err(
+                BatchUnsupported(format!("the server answered with {content_type}")).into(),
+            );
+        }
+        let parts = Part::decode_all(&resp.bytes)?;
+        if parts.len() != paths.len() || parts.iter().zip(paths).any(|(p, path)| p.path != *path) {
+            anyhow::bail!("Batch response doesn't have the files that were asked for");
+        }
+        Ok(parts
+            .into_iter()
+            .map(|part| match part.status {
+                200 => Ok(part.data),
+                status => Err(ProviderError::from_status(
+                    status,
+                    format!("{}: {}", part.path, String::from_utf8_lossy(&part.data)),
+                )),
+            })
+            .collect())
+    }
 }
+
+/// A batch request failed in a way that shows the server can't serve them at all.
+#[derive(Debug)]
+struct BatchUnsupported(String);
+
+impl std::fmt::Display for BatchUnsupported {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Batch requests aren't supported: {}", self.0)
+    }
+}
+
+impl std::error::Error for BatchUnsupported {}
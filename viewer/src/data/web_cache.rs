@@ -0,0 +1,286 @@
+#[cfg(not(target_arch = "wasm32"))]
+pub use native::{WebCache, clear, set_max_size};
+#[cfg(target_arch = "wasm32")]
+pub use web::{WebCache, clear};
+
+#[cfg(target_arch = "wasm32")]
+mod web {
+    use indexed_db::Database;
+    use web_sys::js_sys::{JsString, Uint8Array};
+
+    const DB_NAME: &str = "web_cache";
+    const STORE_FILES: &str = "files";
+
+    /// Responses from a web server kept in IndexedDB by URL, so sheets that were opened before
+    /// load instantly and without a connection. Files at a game version never change, so
+    /// they're kept until the browser clears them.
+    pub struct WebCache(Database<String>);
+
+    async fn open_db() -> Result<Database<String>, String> {
+        let factory = indexed_db::Factory::get()
+            .map_err(|e| format!("Failed to get IndexedDB factory: {e}"))?;
+        factory
+            .open(DB_NAME, 1, |evt| async move {
+                evt.database().build_object_store(STORE_FILES).create()?;
+                Ok(())
+            })
+            .await
+            .map_err(|e| format!("Failed to open IndexedDB database: {e}"))
+    }
+
+    /// Removes everything that was downloaded.
+    pub async fn clear() -> anyhow::Result<()> {
+        open_db()
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?
+            .transaction(&[STORE_FILES])
+            .rw()
+            .run(|t| async move {
+                t.object_store(STORE_FILES)?.clear().await?;
+                Ok(())
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to clear web cache: {e}"))
+    }
+
+    impl WebCache {
+        /// `None` if IndexedDB isn't available, like in some private browsing modes. Files are
+        /// keyed by their whole URL, so `_base_url` isn't needed.
+        pub async fn open(_base_url: &str) -> Option<Self> {
+            open_db()
+                .await
+                .inspect_err(|e| log::warn!("Web cache unavailable: {e}"))
+                .ok()
+                .map(Self)
+        }
+
+        pub async fn get(&self, key: &str) -> Option<Vec<u8>> {
+            let key = JsString::from(key);
+            self.0
+                .transaction(&[STORE_FILES])
+                .run(move |t| async move {
+                    let value = t.object_store(STORE_FILES)?.get(&key).await?;
+                    Ok(value.map(|value| Uint8Array::new(&value).to_vec()))
+                })
+                .await
+                .inspect_err(|e| log::warn!("Failed to read from web cache: {e}"))
+                .ok()
+                .flatten()
+        }
+
+        pub async fn put(&self, key: &str, data: &[u8]) {
+            let key = JsString::from(key);
+            let value = Uint8Array::from(data);
+            let result = self
+                .0
+                .transaction(&[STORE_FILES])
+                .rw()
+                .run(move |t| async move {
+                    t.object_store(STORE_FILES)?.put_kv(&key, &value).await?;
+                    Ok(())
+                })
+                .await;
+            // Fails once the quota is used up, in which case the file is just fetched next time
+            if let Err(e) = result {
+                log::warn!("Failed to write to web cache: {e}");
+            }
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use std::{
+        path::{Path, PathBuf},
+        sync::atomic::{AtomicBool, AtomicU64, Ordering},
+        time::SystemTime,
+    };
+
+    use url::Url;
+
+    /// In bytes, 0 for no limit
+    static MAX_SIZE: AtomicU64 = AtomicU64::new(0);
+    /// Set once the cache was trimmed this session, or is being trimmed.
+    static TRIMMED: AtomicBool = AtomicBool::new(false);
+
+    /// Sets how large [`WebCache`] may get before the versions used longest ago are removed.
+    /// `None` for no limit. The cache is trimmed to it the next time it's opened.
+    pub fn set_max_size(bytes: Option<u64>) {
+        MAX_SIZE.store(bytes.unwrap_or(0), Ordering::Relaxed);
+        TRIMMED.store(false, Ordering::Relaxed);
+    }
+
+    fn root() -> Option<PathBuf> {
+        Some(eframe::storage_dir("EXDViewer")?.join("web-cache"))
+    }
+
+    /// Removes everything that was downloaded.
+    pub async fn clear() -> anyhow::Result<()> {
+        let Some(root) = root() else {
+            return Ok(());
+        };
+        match std::fs::remove_dir_all(root) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Responses from a web server kept on disk, laid out as `{host}/{slug}/{version}/{path}`
+    /// so everything of a version can be dropped at once. Files at a game version never change,
+    /// so they're kept until the cache grows too large.
+    pub struct WebCache {
+        root: PathBuf,
+        /// Path segments of the server's API base, which come before the slug in URLs
+        base_path: Vec<String>,
+    }
+
+    impl WebCache {
+        /// `None` if there's nowhere to keep files. The first time in a session, the cache is
+        /// trimmed down to its size limit in the background.
+        pub async fn open(base_url: &str) -> Option<Self> {
+            let root = root()?;
+            let base_path = Url::parse(base_url)
+                .ok()?
+                .path_segments()?
+                .filter(|segment| !segment.is_empty())
+                .map(str::to_owned)
+                .collect();
+            if let Some(max_size) = Some(MAX_SIZE.load(Ordering::Relaxed)).filter(|&s| s != 0)
+                && !TRIMMED.swap(true, Ordering::Relaxed)
+            {
+                let root = root.clone();
+                std::thread::spawn(move || {
+                    if let Err(e) = trim(&root, max_size) {
+                        log::warn!("Failed to trim web cache: {e}");
+                    }
+                });
+            }
+            Some(Self { root, base_path })
+        }
+
+        /// `None` for URLs outside of the server's API base.
+        fn path(&self, key: &str) -> Option<PathBuf> {
+            let url = Url::parse(key).ok()?;
+            let host = match url.port() {
+                Some(port) => format!("{}_{port}", url.host_str()?),
+                None => url.host_str()?.to_owned(),
+            };
+            let mut path = self.root.join(host);
+            let mut segments = url.path_segments()?.filter(|segment| !segment.is_empty());
+            for base in &self.base_path {
+                if segments.next()? != base {
+                    return None;
+                }
+            }
+            for segment in segments {
+                if segment == "." || segment == ".." {
+                    continue;
+                }
+                path.push(segment);
+            }
+            Some(path)
+        }
+
+        pub async fn get(&self, key: &str) -> Option<Vec<u8>> {
+            std::fs::read(self.path(key)?).ok()
+        }
+
+        pub async fn put(&self, key: &str, data: &[u8]) {
+            let Some(path) = self.path(key) else {
+                return;
+            };
+            let result = path
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|()| std::fs::write(&path, data));
+            if let Err(e) = result {
+                log::warn!("Failed to write to web cache: {e}");
+            }
+        }
+    }
+
+    /// Removes the versions written to longest ago until the cache fits in `max_size`.
+    fn trim(root: &Path, max_size: u64) -> std::io::Result<()> {
+        let mut versions = Vec::new();
+        for host in read_dirs(root)? {
+            for slug in read_dirs(&host)? {
+                for version in read_dirs(&slug)? {
+                    let (size, modified) = dir_usage(&version)?;
+                    versions.push((modified, size, version));
+                }
+            }
+        }
+        let mut total = versions.iter().map(|(_, size, _)| size).sum::<u64>();
+        versions.sort();
+        for (_, size, version) in versions {
+            if total <= max_size {
+                break;
+            }
+            std::fs::remove_dir_all(version)?;
+            total -= size;
+        }
+        Ok(())
+    }
+
+    fn read_dirs(path: &Path) -> std::io::Result<Vec<PathBuf>> {
+        match std::fs::read_dir(path) {
+            Ok(entries) => Ok(entries
+                .filter_map(Result::ok)
+                .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+                .map(|entry| entry.path())
+                .collect()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Total size of the files in `path`, and when the newest was written.
+    fn dir_usage(path: &Path) -> std::io::Result<(u64, SystemTime)> {
+        let mut usage = (0, SystemTime::UNIX_EPOCH);
+        for entry in std::fs::read_dir(path)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            let (size, modified) = if metadata.is_dir() {
+                dir_usage(&entry.path())?
+            } else {
+                (metadata.len(), metadata.modified()?)
+            };
+            usage = (usage.0 + size, usage.1.max(modified));
+        }
+        Ok(usage)
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::WebCache;
+
+        #[test]
+        fn urls_map_to_version_folders() {
+            let cache = WebCache {
+                root: "cache".into(),
+                base_path: vec!["api".to_owned()],
+            };
+            let path = cache
+                .path("https://exd.camora.dev:8080/api/4e9a232b/2025.01.01.0000.0000/exd/Item.exh")
+                .unwrap();
+            assert_eq!(
+                path,
+                std::path::Path::new(
+                    "cache/exd.camora.dev_8080/4e9a232b/2025.01.01.0000.0000/exd/Item.exh"
+                )
+            );
+            let path = cache
+                .path("https://example.com/api/4e9a232b/versions")
+                .unwrap();
+            assert_eq!(
+                path,
+                std::path::Path::new("cache/example.com/4e9a232b/versions")
+            );
+            assert_eq!(cache.path("https://example.com/api/../../secret"), None);
+            assert_eq!(
+                cache.path("https://example.com/other/4e9a232b/versions"),
+                None
+            );
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{
     backend::worker,
+    utils::{ProviderError, ProviderResult},
     worker::{WorkerDirectory, WorkerRequest, WorkerResponse},
 };
 
@@ -55,17 +56,15 @@ impl WorkerFileProvider {
 
 #[async_trait(?Send)]
 impl FileProvider for WorkerFileProvider {
-    async fn read(&self, path: &str) -> anyhow::Result<Vec<u8>> {
+    async fn read(&self, path: &str) -> ProviderResult<Vec<u8>> {
         log::info!("WorkerFileProvider: requesting file {path:?}");
         if let WorkerResponse::DataRequestFile(result) =
             worker::transact(WorkerRequest::DataRequestFile(path.to_string())).await
         {
-            let file =
-                result.map_err(|e| ironworks::Error::NotFound(ironworks::ErrorValue::Other(e)))?;
-            Ok(file)
+            result.map_err(|e| ProviderError::from_worker(e, ProviderError::NotFound))
         } else {
-            Err(anyhow::anyhow!(
-                "WorkerFileProvider: invalid response from worker"
+            Err(ProviderError::Corrupt(
+                "WorkerFileProvider: invalid response from worker".to_string(),
             ))
         }
     }
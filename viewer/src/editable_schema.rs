@@ -1,20 +1,30 @@
 use crate::{
     schema::{Schema, SchemaError, boxed::BoxedSchemaProvider, provider::SchemaProvider},
+    schema_completion,
+    schema_history::History,
+    schema_tree,
     settings::{
-        CODE_SYNTAX_THEME, SCHEMA_EDITOR_ERRORS_SHOWN, SCHEMA_EDITOR_VISIBLE,
-        SCHEMA_EDITOR_WORD_WRAP,
+        CODE_SYNTAX_THEME, SCHEMA_EDITOR_ERRORS_SHOWN, SCHEMA_EDITOR_STRUCTURED,
+        SCHEMA_EDITOR_VISIBLE, SCHEMA_EDITOR_WORD_WRAP,
+    },
+    shortcuts::{
+        SCHEMA_CLEAR, SCHEMA_REDO, SCHEMA_REVERT, SCHEMA_SAVE, SCHEMA_SAVE_AS, SCHEMA_UNDO,
     },
-    shortcuts::{SCHEMA_CLEAR, SCHEMA_REVERT, SCHEMA_SAVE, SCHEMA_SAVE_AS},
     utils::{TrackedPromise, highlight, shortcut},
 };
 use egui::{
-    CentralPanel, CornerRadius, Frame, Id, Layout, Margin, MenuBar, Response, RichText, TextBuffer,
-    collapsing_header::CollapsingState, containers::panel::Panel,
+    CentralPanel, CornerRadius, Frame, Id, Key, Layout, Margin, MenuBar, Modifiers, Response,
+    RichText, TextBuffer,
+    collapsing_header::CollapsingState,
+    containers::panel::Panel,
     epaint::text::cursor::LayoutCursor,
+    text::{CCursor, CCursorRange},
+    text_edit::{TextEditOutput, TextEditState},
 };
 use itertools::Itertools;
 use std::{
     cell::{Cell, RefCell},
+    collections::HashMap,
     rc::Rc,
 };
 
@@ -23,9 +33,26 @@ pub struct EditableSchema {
     original: Rc<RefCell<String>>,
     text: String,
     is_modified: Rc<Cell<bool>>,
+    /// Generated from the sheet's columns because it had no schema
+    is_stub: bool,
     schema: anyhow::Result<Result<Schema, Vec<SchemaError>>>,
     save_promise: Cell<Option<TrackedPromise<()>>>,
     save_as_promise: Cell<Option<TrackedPromise<()>>>,
+    completion: Option<Completion>,
+    history: History,
+}
+
+/// Suggestions for the token being typed in the editor.
+struct Completion {
+    /// Char index where the token starts
+    token_start: usize,
+    /// Char index of the cursor
+    cursor: usize,
+    items: Vec<String>,
+    selected: usize,
+    /// Hidden with Escape until another token is edited
+    dismissed: bool,
+    popup_rect: Option<egui::Rect>,
 }
 
 impl EditableSchema {
@@ -34,11 +61,14 @@ impl EditableSchema {
         Self {
             sheet_name: sheet_name.into(),
             original: Rc::new(RefCell::new(schema_text.clone())),
+            history: History::new(&schema_text),
             text: schema_text,
             is_modified: Rc::new(Cell::new(false)),
+            is_stub: false,
             schema,
             save_promise: Cell::new(None),
             save_as_promise: Cell::new(None),
+            completion: None,
         }
     }
 
@@ -47,16 +77,21 @@ impl EditableSchema {
         Ok(Self {
             sheet_name: schema.name.clone(),
             original: Rc::new(RefCell::new(text.clone())),
+            history: History::new(&text),
             text,
             is_modified: Rc::new(Cell::new(false)),
+            is_stub: false,
             schema: Ok(Ok(schema)),
             save_promise: Cell::new(None),
             save_as_promise: Cell::new(None),
+            completion: None,
         })
     }
 
     pub fn from_blank(sheet_name: impl Into<String>, column_count: usize) -> anyhow::Result<Self> {
-        Self::new_unchecked(Schema::from_blank(sheet_name, column_count))
+        let mut ret = Self::new_unchecked(Schema::from_blank(sheet_name, column_count))?;
+        ret.is_stub = true;
+        Ok(ret)
     }
 
     pub fn from_miscellaneous(sheet_name: impl Into<String>) -> anyhow::Result<Self> {
@@ -71,6 +106,19 @@ impl EditableSchema {
         self.is_modified.get()
     }
 
+    pub fn is_stub(&self) -> bool {
+        self.is_stub
+    }
+
+    /// Swaps the text for a draft kept from an earlier session. The draft counts as a
+    /// modification, so it can be saved or submitted like any other edit.
+    pub fn restore_draft(&mut self, text: String) {
+        self.text = text;
+        self.history.record_step(&self.text);
+        self.schema = Schema::from_str(self.get_text());
+        self.is_modified.set(self.text != *self.original.borrow());
+    }
+
     pub fn get_schema(&self) -> Option<&Schema> {
         self.schema.as_ref().ok().and_then(|r| r.as_ref().ok())
     }
@@ -96,16 +144,51 @@ impl EditableSchema {
         }
     }
 
-    pub fn draw(&mut self, ui: &mut egui::Ui, provider: &BoxedSchemaProvider) -> Response {
-        let resp = self.draw_internal(ui, provider);
+    /// Sets the top-level `displayField` of the schema text, keeping the rest of it untouched.
+    pub fn set_display_field(&mut self, field_name: &str) {
+        let line = format!("displayField: {field_name}");
+        let mut lines = self.text.lines().map(str::to_owned).collect_vec();
+        if let Some(existing) = lines.iter_mut().find(|l| l.starts_with("displayField:")) {
+            *existing = line;
+        } else {
+            let idx = lines
+                .iter()
+                .position(|l| l.starts_with("name:"))
+                .map_or(0, |idx| idx + 1);
+            lines.insert(idx, line);
+        }
+        let mut text = lines.join("\n");
+        if self.text.ends_with('\n') {
+            text.push('\n');
+        }
+        self.text = text;
+        self.history.record_step(&self.text);
+        self.schema = Schema::from_str(self.get_text());
+        self.is_modified.set(self.text != *self.original.borrow());
+    }
+
+    /// `sheet_names` are the excel entries, offered as completions for link targets.
+    pub fn draw(
+        &mut self,
+        ui: &mut egui::Ui,
+        provider: &BoxedSchemaProvider,
+        sheet_names: &HashMap<String, i32>,
+    ) -> Response {
+        let resp = self.draw_internal(ui, provider, sheet_names);
         if resp.changed() {
+            self.history.record(&self.text);
             self.schema = Schema::from_str(self.get_text());
             self.is_modified.set(self.text != *self.original.borrow());
         }
         resp
     }
 
-    fn draw_internal(&mut self, ui: &mut egui::Ui, provider: &BoxedSchemaProvider) -> Response {
+    fn draw_internal(
+        &mut self,
+        ui: &mut egui::Ui,
+        provider: &BoxedSchemaProvider,
+        sheet_names: &HashMap<String, i32>,
+    ) -> Response {
         let mut response = ui.response();
 
         let is_shown = SCHEMA_EDITOR_VISIBLE.get(ui.ctx());
@@ -136,6 +219,16 @@ impl EditableSchema {
                 if shortcut::consume_ui(ui, SCHEMA_SAVE_AS) {
                     self.command_save_as(provider);
                 }
+                // Checked first, since the undo shortcut would also match with Shift held
+                if shortcut::consume_ui(ui, SCHEMA_REDO) {
+                    if self.command_redo(ui.ctx(), schema_editor_id) {
+                        response.mark_changed();
+                    }
+                } else if shortcut::consume_ui(ui, SCHEMA_UNDO)
+                    && self.command_undo(ui.ctx(), schema_editor_id)
+                {
+                    response.mark_changed();
+                }
 
                 Panel::top("editor-top-bar")
                     .frame(Frame::side_top_panel(ui.style()).inner_margin(Margin {
@@ -180,12 +273,40 @@ impl EditableSchema {
                                 }
                             });
 
+                            ui.menu_button("Edit", |ui| {
+                                ui.add_enabled_ui(self.history.can_undo(), |ui| {
+                                    if shortcut::button(ui, "Undo", SCHEMA_UNDO).clicked() {
+                                        self.command_undo(ui.ctx(), schema_editor_id);
+                                        response.mark_changed();
+                                        ui.close();
+                                    }
+                                });
+                                ui.add_enabled_ui(self.history.can_redo(), |ui| {
+                                    if shortcut::button(ui, "Redo", SCHEMA_REDO).clicked() {
+                                        self.command_redo(ui.ctx(), schema_editor_id);
+                                        response.mark_changed();
+                                        ui.close();
+                                    }
+                                });
+                            });
+
                             ui.menu_button("View", |ui| {
                                 let mut word_wrap = SCHEMA_EDITOR_WORD_WRAP.get(ui.ctx());
                                 if ui.toggle_value(&mut word_wrap, "Word Wrap").changed() {
                                     SCHEMA_EDITOR_WORD_WRAP.set(ui.ctx(), word_wrap);
                                     ui.close();
                                 }
+                                let mut structured = SCHEMA_EDITOR_STRUCTURED.get(ui.ctx());
+                                if ui
+                                    .toggle_value(&mut structured, "Structured")
+                                    .on_hover_text(
+                                        "Edit fields as a tree. Changes rewrite the YAML, dropping its comments and formatting.",
+                                    )
+                                    .changed()
+                                {
+                                    SCHEMA_EDITOR_STRUCTURED.set(ui.ctx(), structured);
+                                    ui.close();
+                                }
                             });
 
                             ui.with_layout(
@@ -286,6 +407,13 @@ impl EditableSchema {
                             }),
                     )
                     .show(ui, |ui| {
+                        if SCHEMA_EDITOR_STRUCTURED.get(ui.ctx()) {
+                            if self.draw_structured(ui) {
+                                response.mark_changed();
+                            }
+                            return;
+                        }
+
                         egui::ScrollArea::both().auto_shrink(false).show(ui, |ui| {
                             let theme = CODE_SYNTAX_THEME.get(ui.ctx());
 
@@ -304,6 +432,10 @@ impl EditableSchema {
                                     ui.fonts_mut(|f| f.layout_job(layout_job))
                                 };
 
+                            if self.handle_completion_keys(ui, schema_editor_id) {
+                                response.mark_changed();
+                            }
+
                             let ret = {
                                 let layout = (*ui.layout()).with_main_justify(true);
                                 ui.allocate_ui_with_layout(ui.available_size(), layout, |ui| {
@@ -328,6 +460,10 @@ impl EditableSchema {
                                 });
                             }
 
+                            if self.update_completion(ui, schema_editor_id, &ret, sheet_names) {
+                                response.mark_changed();
+                            }
+
                             if ret.response.changed() {
                                 response.mark_changed();
 
@@ -365,7 +501,7 @@ impl EditableSchema {
                                 }
                             }
                             ret.response
-                        })
+                        });
                     })
             });
 
@@ -376,12 +512,230 @@ impl EditableSchema {
         response
     }
 
+    /// Edits the parsed schema as a tree, writing changes back to the text.
+    fn draw_structured(&mut self, ui: &mut egui::Ui) -> bool {
+        let Ok(Ok(schema)) = &self.schema else {
+            ui.centered_and_justified(|ui| {
+                ui.label("Fix the schema's errors to use the structured editor");
+            });
+            return false;
+        };
+
+        let mut schema = schema.clone();
+        if !schema_tree::draw(ui, &mut schema) {
+            return false;
+        }
+        match serde_yml::to_string(&schema) {
+            Ok(text) => {
+                self.text = text;
+                true
+            }
+            Err(e) => {
+                log::error!("Failed to serialize schema: {e}");
+                false
+            }
+        }
+    }
+
+    /// Handles the keys of the open completion popup. Returns whether a suggestion was accepted.
+    fn handle_completion_keys(&mut self, ui: &egui::Ui, editor_id: Id) -> bool {
+        let Some(completion) = &mut self.completion else {
+            return false;
+        };
+        if completion.dismissed || !ui.memory(|m| m.has_focus(editor_id)) {
+            return false;
+        }
+
+        let (up, down, accept, dismiss) = ui.input_mut(|i| {
+            (
+                i.consume_key(Modifiers::NONE, Key::ArrowUp),
+                i.consume_key(Modifiers::NONE, Key::ArrowDown),
+                i.consume_key(Modifiers::NONE, Key::Tab)
+                    | i.consume_key(Modifiers::NONE, Key::Enter),
+                i.consume_key(Modifiers::NONE, Key::Escape),
+            )
+        });
+        let count = completion.items.len();
+        if up {
+            completion.selected = (completion.selected + count - 1) % count;
+        }
+        if down {
+            completion.selected = (completion.selected + 1) % count;
+        }
+        if dismiss {
+            completion.dismissed = true;
+            return false;
+        }
+        accept && self.accept_completion(ui.ctx(), editor_id)
+    }
+
+    /// Recomputes the suggestions for the token under the cursor and draws them below it.
+    /// Returns whether a suggestion was clicked.
+    fn update_completion(
+        &mut self,
+        ui: &egui::Ui,
+        editor_id: Id,
+        ret: &TextEditOutput,
+        sheet_names: &HashMap<String, i32>,
+    ) -> bool {
+        // Clicking a suggestion takes focus away from the editor before the click lands
+        let over_popup = self
+            .completion
+            .as_ref()
+            .and_then(|c| c.popup_rect)
+            .zip(ui.ctx().pointer_latest_pos())
+            .is_some_and(|(rect, pos)| rect.contains(pos));
+        if !over_popup {
+            let cursor = ret
+                .cursor_range
+                .filter(|range| range.primary.index == range.secondary.index)
+                .filter(|_| ret.response.has_focus())
+                .map(|range| range.primary.index.0);
+            let previous = self.completion.take();
+            self.completion = cursor.and_then(|cursor| {
+                let context = schema_completion::context_at(&self.text, cursor)?;
+                if context.prefix.is_empty() {
+                    return None;
+                }
+                let previous = previous.filter(|p| p.token_start == context.token_start);
+                if previous
+                    .as_ref()
+                    .is_some_and(|p| p.cursor == cursor && !ret.response.changed())
+                {
+                    return previous;
+                }
+
+                let items = schema_completion::suggestions(
+                    context.kind,
+                    context.prefix,
+                    sheet_names.keys().map(String::as_str),
+                );
+                let (selected, dismissed) =
+                    previous.map_or((0, false), |p| (p.selected, p.dismissed));
+                (!items.is_empty()).then(|| Completion {
+                    token_start: context.token_start,
+                    cursor,
+                    selected: selected.min(items.len() - 1),
+                    items,
+                    dismissed,
+                    popup_rect: None,
+                })
+            });
+        }
+
+        let Some(completion) = &mut self.completion else {
+            return false;
+        };
+        if completion.dismissed {
+            return false;
+        }
+
+        let cursor_rect = ret
+            .galley
+            .pos_from_cursor(CCursor::new(completion.cursor))
+            .translate(ret.galley_pos.to_vec2());
+        let mut clicked = None;
+        let area = egui::Area::new(editor_id.with("completion"))
+            .order(egui::Order::Foreground)
+            .fixed_pos(cursor_rect.left_bottom())
+            .show(ui.ctx(), |ui| {
+                Frame::popup(ui.style()).show(ui, |ui| {
+                    for (idx, item) in completion.items.iter().enumerate() {
+                        let label = ui.selectable_label(
+                            idx == completion.selected,
+                            RichText::new(item).monospace(),
+                        );
+                        if label.clicked() {
+                            clicked = Some(idx);
+                        }
+                    }
+                });
+            });
+        completion.popup_rect = Some(area.response.rect);
+
+        let Some(idx) = clicked else {
+            return false;
+        };
+        completion.selected = idx;
+        ui.memory_mut(|m| m.request_focus(editor_id));
+        ui.ctx()
+            .request_discard("Schema editor completion was accepted");
+        self.accept_completion(ui.ctx(), editor_id)
+    }
+
+    /// Replaces the token being typed with the selected suggestion.
+    fn accept_completion(&mut self, ctx: &egui::Context, editor_id: Id) -> bool {
+        let Some(completion) = self.completion.take() else {
+            return false;
+        };
+        let Some(item) = completion.items.get(completion.selected) else {
+            return false;
+        };
+
+        let byte_idx = |char_idx| {
+            self.text
+                .char_indices()
+                .nth(char_idx)
+                .map_or(self.text.len(), |(idx, _)| idx)
+        };
+        let range = byte_idx(completion.token_start)..byte_idx(completion.cursor);
+        self.text.replace_range(range, item);
+        self.history.record_step(&self.text);
+
+        if let Some(mut state) = TextEditState::load(ctx, editor_id) {
+            let cursor = completion.token_start + item.chars().count();
+            state
+                .cursor
+                .set_char_range(Some(CCursorRange::one(CCursor::new(cursor))));
+            state.store(ctx, editor_id);
+        }
+        true
+    }
+
     fn command_revert(&mut self) {
         self.text.replace_with(&self.original.borrow());
+        self.history.record_step(&self.text);
     }
 
     fn command_clear(&mut self) {
         TextBuffer::clear(&mut self.text);
+        self.history.record_step(&self.text);
+    }
+
+    fn command_undo(&mut self, ctx: &egui::Context, editor_id: Id) -> bool {
+        let Some(text) = self.history.undo() else {
+            return false;
+        };
+        let text = text.to_owned();
+        self.restore(ctx, editor_id, text);
+        true
+    }
+
+    fn command_redo(&mut self, ctx: &egui::Context, editor_id: Id) -> bool {
+        let Some(text) = self.history.redo() else {
+            return false;
+        };
+        let text = text.to_owned();
+        self.restore(ctx, editor_id, text);
+        true
+    }
+
+    /// Swaps in a text from the history, moving the cursor to where it first differs.
+    fn restore(&mut self, ctx: &egui::Context, editor_id: Id, text: String) {
+        let changed_at = self
+            .text
+            .chars()
+            .zip(text.chars())
+            .take_while(|(a, b)| a == b)
+            .count();
+        self.text = text;
+        self.completion = None;
+        if let Some(mut state) = TextEditState::load(ctx, editor_id) {
+            state
+                .cursor
+                .set_char_range(Some(CCursorRange::one(CCursor::new(changed_at))));
+            state.store(ctx, editor_id);
+        }
     }
 
     pub fn command_save(&self, provider: &BoxedSchemaProvider) {
@@ -1,23 +1,35 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use either::Either;
-use futures_util::{StreamExt, stream::FuturesOrdered};
 use image::RgbaImage;
 use intmap::IntMap;
 use ironworks::{
     excel::{Language, path},
     file::{
         exd::{ExcelData, RowHeader, SubrowHeader},
-        exh::{ColumnDefinition, PageDefinition, SheetKind},
+        exh::{ColumnDefinition, ColumnKind, PageDefinition, SheetKind},
     },
 };
-use std::{cell::RefCell, collections::HashMap, num::NonZeroUsize, ops::Range, rc::Rc, sync::Arc};
+use itertools::Itertools;
+use std::{
+    cell::{Ref, RefCell},
+    collections::HashMap,
+    num::NonZeroUsize,
+    ops::Range,
+    rc::Rc,
+    sync::{Arc, Weak},
+};
 use url::Url;
 
-use crate::data::{FileProvider, FileProviderExt};
-use crate::utils::{CloneableResult, KeyedCache, SharedFuture};
+use crate::data::{FileProvider, FileProviderExt, IconRevision};
+use crate::utils::{
+    KeyedCache, ProviderError, ProviderResult, SharedFuture, TrackedPromise, yield_to_ui,
+};
+
+use super::provider::{EntriesStatus, ExcelHeader, ExcelPage, ExcelProvider, ExcelRow, ExcelSheet};
 
-use super::provider::{ExcelHeader, ExcelPage, ExcelProvider, ExcelRow, ExcelSheet};
+/// Number of EXL lines parsed between yields to the UI.
+const ENTRY_CHUNK_SIZE: usize = 256;
 
 /// Excel provider that caches parsed sheets and headers on top of a shared
 /// [`FileProvider`].
@@ -31,34 +43,86 @@ impl Clone for CachedProvider {
 
 struct CachedProviderImpl {
     files: Rc<dyn FileProvider>,
-    entries: HashMap<String, i32>,
-    cache: RefCell<lru::LruCache<String, SharedFuture<CloneableResult<Rc<CacheEntry>>>>>,
+    entries: RefCell<HashMap<String, i32>>,
+    entries_status: RefCell<EntriesStatus>,
+    entries_promise: RefCell<Option<TrackedPromise<()>>>,
+    cache: RefCell<lru::LruCache<String, SharedFuture<ProviderResult<Rc<CacheEntry>>>>>,
+    /// Where string columns are read from instead when it has them, in the given language
+    string_overlay: RefCell<Option<(CachedProvider, Language)>>,
 }
 
 struct CacheEntry {
     pub header: BaseHeader,
-    pub cache: RefCell<KeyedCache<Language, SharedFuture<CloneableResult<BaseSheet>>>>,
+    pub cache: RefCell<KeyedCache<Language, SharedFuture<ProviderResult<BaseSheet>>>>,
 }
 
 impl CachedProvider {
+    /// Creates the provider immediately; the sheet list is loaded in the background so the
+    /// UI can show entries as they're parsed. See [`ExcelProvider::entries_status`].
     pub async fn new(files: Rc<dyn FileProvider>, size: NonZeroUsize) -> anyhow::Result<Self> {
-        let entries = files
-            .file::<ironworks::file::exl::ExcelList>(path::exl())
-            .await?
-            .0;
-        Ok(Self(Arc::new(CachedProviderImpl {
-            files,
-            entries,
+        let this = Self(Arc::new(CachedProviderImpl {
+            files: files.clone(),
+            entries: RefCell::new(HashMap::new()),
+            entries_status: RefCell::new(EntriesStatus::Loading),
+            entries_promise: RefCell::new(None),
             cache: RefCell::new(lru::LruCache::new(size)),
-        })))
+            string_overlay: RefCell::new(None),
+        }));
+
+        let weak = Arc::downgrade(&this.0);
+        let promise = TrackedPromise::spawn_local(async move {
+            let result = Self::load_entries(&*files, &weak).await;
+            if let Some(this) = weak.upgrade() {
+                this.entries_status.replace(match result {
+                    Ok(()) => EntriesStatus::Loaded,
+                    Err(e) => {
+                        log::error!("Failed to load sheet list: {e:?}");
+                        EntriesStatus::Failed(e.to_string())
+                    }
+                });
+            }
+        });
+        this.0.entries_promise.replace(Some(promise));
+
+        Ok(this)
+    }
+
+    async fn load_entries(
+        files: &dyn FileProvider,
+        this: &Weak<CachedProviderImpl>,
+    ) -> anyhow::Result<()> {
+        let data = files.read(path::exl()).await?;
+        let text = String::from_utf8_lossy(&data);
+        let mut lines = text.lines();
+        match lines.next() {
+            Some(magic) if magic.starts_with("EXLT") => {}
+            _ => return Err(ProviderError::Corrupt("Invalid EXL header".to_string()).into()),
+        }
+
+        for chunk in &lines.chunks(ENTRY_CHUNK_SIZE) {
+            {
+                let Some(this) = this.upgrade() else {
+                    return Ok(());
+                };
+                let mut entries = this.entries.borrow_mut();
+                for line in chunk {
+                    let Some((name, id)) = line.trim_end().rsplit_once(',') else {
+                        continue;
+                    };
+                    entries.insert(name.to_owned(), id.parse()?);
+                }
+            }
+            yield_to_ui().await;
+        }
+        Ok(())
     }
 
     async fn use_entry<R>(
         &self,
         name: &str,
         op: impl FnOnce(Rc<CacheEntry>) -> R,
-    ) -> anyhow::Result<R> {
-        let future: SharedFuture<CloneableResult<Rc<CacheEntry>>>;
+    ) -> ProviderResult<R> {
+        let future: SharedFuture<ProviderResult<Rc<CacheEntry>>>;
         {
             let mut cache = self.0.cache.borrow_mut();
 
@@ -82,7 +146,66 @@ impl CachedProvider {
                 future
             };
         }
-        future.into_shared().await.map_err(|e| e.into()).map(op)
+        future.into_shared().await.map(op)
+    }
+
+    /// Reads the string columns of sheets from `overlay` where it has them. Only applies to
+    /// sheets loaded from then on.
+    pub fn set_string_overlay(&self, overlay: CachedProvider, language: Language) {
+        self.0.string_overlay.replace(Some((overlay, language)));
+    }
+
+    /// Attaches the same sheet of the string overlay, if there is one and its string columns
+    /// are laid out the same. Sheets the overlay doesn't have are left as they are.
+    async fn overlay_strings(&self, sheet: BaseSheet) -> BaseSheet {
+        let Some((overlay, language)) = self.0.string_overlay.borrow().clone() else {
+            return sheet;
+        };
+        let overlay_sheet = match overlay.get_sheet(sheet.name(), language).await {
+            Ok(overlay_sheet) => overlay_sheet,
+            Err(e) => {
+                log::debug!("No string overlay for {}: {e}", sheet.name());
+                return sheet;
+            }
+        };
+        let is_compatible = sheet
+            .columns()
+            .iter()
+            .filter(|column| column.kind() == ColumnKind::String)
+            .all(|column| {
+                overlay_sheet.columns().iter().any(|other| {
+                    other.kind() == ColumnKind::String && other.offset() == column.offset()
+                })
+            });
+        if !is_compatible {
+            log::warn!(
+                "Not overlaying strings of {}, its columns differ in the overlay",
+                sheet.name()
+            );
+            return sheet;
+        }
+        sheet.with_string_overlay(overlay_sheet)
+    }
+
+    /// The sheets that finished loading and are still cached, by language.
+    pub fn cached_sheets(&self) -> Vec<(Language, BaseSheet)> {
+        self.0
+            .cache
+            .borrow()
+            .iter()
+            .filter_map(|(_, entry)| entry.peek()?.as_ref().ok().cloned())
+            .filter_map(|entry| {
+                let cache = entry.cache.borrow();
+                let (language, sheet) = cache.get()?;
+                Some((*language, sheet.peek()?.as_ref().ok()?.clone()))
+            })
+            .collect()
+    }
+
+    /// Drops every cached header and sheet. Sheets still in use elsewhere stay loaded until
+    /// they're dropped there.
+    pub fn clear_cache(&self) {
+        self.0.cache.borrow_mut().clear();
     }
 
     pub async fn get_available_languages(&self, name: &str) -> anyhow::Result<Vec<Language>> {
@@ -118,19 +241,32 @@ impl ExcelProvider for CachedProvider {
 
     type Sheet = BaseSheet;
 
-    fn get_entries(&self) -> &HashMap<String, i32> {
-        &self.0.entries
+    fn get_entries(&self) -> Ref<'_, HashMap<String, i32>> {
+        self.0.entries.borrow()
+    }
+
+    fn entries_status(&self) -> EntriesStatus {
+        self.0.entries_status.borrow().clone()
     }
 
     async fn get_icon(&self, icon_id: u32, hires: bool) -> Result<Either<Url, RgbaImage>> {
         self.0.files.get_icon(icon_id, hires).await
     }
 
-    async fn get_header(&self, name: &str) -> Result<BaseHeader> {
+    async fn get_icon_if_changed(
+        &self,
+        icon_id: u32,
+        hires: bool,
+        etag: Option<&str>,
+    ) -> Result<IconRevision> {
+        self.0.files.get_icon_if_changed(icon_id, hires, etag).await
+    }
+
+    async fn get_header(&self, name: &str) -> ProviderResult<BaseHeader> {
         self.use_entry(name, |a| a.header.clone()).await
     }
 
-    async fn get_sheet(&self, name: &str, language: Language) -> Result<BaseSheet> {
+    async fn get_sheet(&self, name: &str, language: Language) -> ProviderResult<BaseSheet> {
         self.use_entry(name, |a| {
             a.cache
                 .borrow_mut()
@@ -153,13 +289,13 @@ impl ExcelProvider for CachedProvider {
                                 .map(ToString::to_string)
                                 .collect::<Vec<_>>()
                                 .join(", ");
-                            return Err(anyhow::anyhow!(
+                            return Err(ProviderError::NotFound(format!(
                                 "Sheet {} has no data for {requested} (available: {available})",
                                 header.name(),
-                            )
-                            .into());
+                            )));
                         };
-                        Ok(BaseSheet::new(header, language, &*this.0.files).await?)
+                        let sheet = BaseSheet::new(header, language, &*this.0.files).await?;
+                        Ok(this.overlay_strings(sheet).await)
                     })
                 })
                 .clone()
@@ -167,7 +303,6 @@ impl ExcelProvider for CachedProvider {
         .await?
         .into_shared()
         .await
-        .map_err(|e| e.into())
     }
 }
 
@@ -243,17 +378,8 @@ struct BaseSheetImpl {
     // First row index in range -> Range of row IDs
     // This is used to map a row index to its corresponding row ID range.
     row_id_lookup: Vec<(u32, Range<u32>)>,
-}
-
-async fn read_page(
-    files: &dyn FileProvider,
-    name: &str,
-    start_id: u32,
-    language: Language,
-) -> Result<ExcelData> {
-    files
-        .file::<ExcelData>(&path::exd(name, start_id, language))
-        .await
+    /// The same sheet from the string overlay, which string columns are read from instead
+    string_overlay: Option<BaseSheet>,
 }
 
 impl BaseSheet {
@@ -283,15 +409,14 @@ impl BaseSheet {
         let mut row_id_lookup = Vec::with_capacity(header.imp.header.pages().len());
         let mut current_row_range: Option<(u32, Range<u32>)> = None;
 
-        let header_name = header.imp.name.clone();
-        let mut page_futures: FuturesOrdered<_> = header
+        let page_paths = header
             .imp
             .header
             .pages()
             .iter()
-            .map(|page_def| read_page(files, &header_name, page_def.start_id(), language))
-            .collect();
-        while let Some(data) = page_futures.next().await {
+            .map(|page_def| path::exd(&header.imp.name, page_def.start_id(), language))
+            .collect_vec();
+        for data in files.files::<ExcelData>(&page_paths).await {
             let data = data?;
             let page = ExcelPage {
                 row_size,
@@ -341,9 +466,38 @@ impl BaseSheet {
                 subrow_count,
                 row_lookup,
                 row_id_lookup,
+                string_overlay: None,
             }),
         })
     }
+
+    /// Reads string columns from `overlay` instead where it has the row and the string isn't
+    /// empty.
+    fn with_string_overlay(self, overlay: BaseSheet) -> Self {
+        match Arc::try_unwrap(self.imp) {
+            Ok(imp) => Self {
+                imp: Arc::new(BaseSheetImpl {
+                    string_overlay: Some(overlay),
+                    ..imp
+                }),
+            },
+            Err(imp) => Self { imp },
+        }
+    }
+}
+
+impl BaseSheet {
+    /// Bytes held by the sheet's page data and its row lookups.
+    pub fn memory_usage(&self) -> (usize, usize) {
+        let pages = self.imp.pages.iter().map(|page| page.data.len()).sum();
+        let lookups = self.imp.row_lookup.len() * size_of::<(u32, RowLocation)>()
+            + self.imp.row_id_lookup.len() * size_of::<(u32, Range<u32>)>();
+        (pages, lookups)
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.imp.pages.len()
+    }
 }
 
 impl ExcelHeader for BaseSheet {
@@ -449,7 +603,14 @@ impl ExcelSheet for BaseSheet {
         } else {
             (struct_offset, page.row_size as u32)
         };
-        Ok(ExcelRow::new(page, offset, struct_offset + row_size))
+        let row = ExcelRow::new(page, offset, struct_offset + row_size);
+        Ok(match &self.imp.string_overlay {
+            Some(overlay) => match overlay.get_subrow(row_id, subrow_id) {
+                Ok(overlay_row) => row.with_string_overlay(overlay_row),
+                Err(_) => row,
+            },
+            None => row,
+        })
     }
 }
 
@@ -1,2 +1,4 @@
 pub mod base;
 pub mod provider;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod zipatch;
@@ -1,4 +1,4 @@
-use std::{collections::HashMap, error::Error, io::Cursor};
+use std::{cell::Ref, collections::HashMap, error::Error, io::Cursor};
 
 use anyhow::Result;
 use async_trait::async_trait;
@@ -13,15 +13,34 @@ use ironworks::{
 use num_traits::FromBytes;
 use url::Url;
 
+use crate::{data::IconRevision, utils::ProviderResult};
+
+/// Progress of loading the list of sheets (the EXL file).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntriesStatus {
+    /// Entries are still being parsed; [`ExcelProvider::get_entries`] may be incomplete.
+    Loading,
+    Loaded,
+    Failed(String),
+}
+
 #[async_trait(?Send)]
 pub trait ExcelProvider {
     type Header: ExcelHeader;
     type Sheet: ExcelSheet;
 
-    fn get_entries(&self) -> &HashMap<String, i32>;
+    /// Sheet names and ids parsed so far. Grows while [`Self::entries_status`] is `Loading`.
+    fn get_entries(&self) -> Ref<'_, HashMap<String, i32>>;
+    fn entries_status(&self) -> EntriesStatus;
     async fn get_icon(&self, icon_id: u32, hires: bool) -> Result<Either<Url, RgbaImage>>;
-    async fn get_sheet(&self, name: &str, language: Language) -> Result<Self::Sheet>;
-    async fn get_header(&self, name: &str) -> Result<Self::Header>;
+    async fn get_icon_if_changed(
+        &self,
+        icon_id: u32,
+        hires: bool,
+        etag: Option<&str>,
+    ) -> Result<IconRevision>;
+    async fn get_sheet(&self, name: &str, language: Language) -> ProviderResult<Self::Sheet>;
+    async fn get_header(&self, name: &str) -> ProviderResult<Self::Header>;
 }
 
 pub trait ExcelHeader {
@@ -125,6 +144,8 @@ pub struct ExcelRow<'a> {
     page: &'a ExcelPage,
     offset: u32,
     string_offset: u32,
+    /// The page, offset and string offset of the same row in the string overlay
+    string_overlay: Option<(&'a ExcelPage, u32, u32)>,
 }
 
 #[binread]
@@ -137,10 +158,25 @@ impl<'a> ExcelRow<'a> {
             page,
             offset,
             string_offset,
+            string_overlay: None,
+        }
+    }
+
+    /// Reads strings from `overlay` instead where they aren't empty.
+    pub fn with_string_overlay(self, overlay: ExcelRow<'a>) -> Self {
+        Self {
+            string_overlay: Some((overlay.page, overlay.offset, overlay.string_offset)),
+            ..self
         }
     }
 
     pub fn read_string(&self, offset: u32) -> anyhow::Result<&'_ SeStr> {
+        if let Some((page, row_offset, string_offset)) = self.string_overlay
+            && let Ok(string) = page.read_string(row_offset + offset, string_offset)
+            && !string.as_bytes().is_empty()
+        {
+            return Ok(string);
+        }
         self.page
             .read_string(self.offset + offset, self.string_offset)
     }
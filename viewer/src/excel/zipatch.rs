@@ -0,0 +1,79 @@
+use std::path::Path;
+
+use async_trait::async_trait;
+use either::Either;
+use image::RgbaImage;
+use ironworks::{
+    Ironworks,
+    sqpack::SqPack,
+    zipatch::{Patch, PatchRepository, View, ZiPatch},
+};
+use url::Url;
+
+use crate::{
+    data::{FileProvider, get_icon_path, get_xivapi_asset_url},
+    utils::{ProviderResult, tex_loader},
+};
+
+/// Sheets are all in the `ffxiv` repository, so patches for expansions aren't read.
+const EXCEL_REPOSITORY: u8 = 0;
+
+/// A patch file as the launcher downloads it, read without installing it. Only the files the
+/// patch adds or changes are in it, so icons it doesn't touch are taken from XIVAPI.
+pub struct PatchFileProvider(Ironworks<SqPack<View>>);
+
+impl PatchFileProvider {
+    /// Whether `path` looks like a patch file rather than an install folder.
+    pub fn is_patch(path: &str) -> bool {
+        let path = Path::new(path);
+        path.is_file()
+            && path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("patch"))
+    }
+
+    pub fn new(path: &str) -> anyhow::Result<Self> {
+        let path = Path::new(path);
+        let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            anyhow::bail!("{} isn't the path to a patch file", path.display());
+        };
+        let view = ZiPatch::new()
+            .view()
+            .with_repository(
+                EXCEL_REPOSITORY,
+                PatchRepository {
+                    patches: vec![Patch {
+                        name: name.to_owned(),
+                        path: path.to_owned(),
+                    }],
+                },
+            )
+            .build();
+        let ironworks = Ironworks::new().with_resource(SqPack::new(view));
+        Ok(Self(ironworks))
+    }
+}
+
+#[async_trait(?Send)]
+impl FileProvider for PatchFileProvider {
+    async fn read(&self, path: &str) -> ProviderResult<Vec<u8>> {
+        Ok(self.0.file::<Vec<u8>>(path)?)
+    }
+
+    async fn get_icon(&self, icon_id: u32, hires: bool) -> anyhow::Result<Either<Url, RgbaImage>> {
+        let path = get_icon_path(icon_id, hires);
+        if !self.0.exists(&path)? {
+            return Ok(Either::Left(get_xivapi_asset_url(&path, Some("png"))));
+        }
+        let data = tex_loader::read(&self.0, &path)?;
+        Ok(Either::Right(data.into_rgba8()))
+    }
+
+    async fn exists_many(&self, paths: &[String]) -> anyhow::Result<Vec<bool>> {
+        let mut result = Vec::with_capacity(paths.len());
+        for path in paths {
+            result.push(self.0.exists(path)?);
+        }
+        Ok(result)
+    }
+}
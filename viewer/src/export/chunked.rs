@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::sheet::TableContext;
+
+use super::{
+    ExportFormat, ExportOptions,
+    csv::{csv_header, csv_records},
+    html::{HTML_TAIL, html_head, html_rows},
+    json::json_values,
+};
+
+/// Rows serialized at a time by a chunked export.
+pub const CHUNK_ROWS: usize = 512;
+
+/// Serializes an export a chunk of rows at a time, so it can be written out as it goes and
+/// picked back up at the chunk that failed. The output is the same as [`ExportFormat::export`].
+pub struct ChunkedExport {
+    format: ExportFormat,
+    options: ExportOptions,
+    /// Whether a JSON element was written yet, which the next one is separated from
+    has_elements: bool,
+    /// Embedded HTML icons by id, kept across chunks
+    icons: HashMap<u32, Option<String>>,
+}
+
+impl ChunkedExport {
+    pub fn new(format: ExportFormat, options: ExportOptions) -> Self {
+        Self {
+            format,
+            options,
+            has_elements: false,
+            icons: HashMap::new(),
+        }
+    }
+
+    pub fn format(&self) -> ExportFormat {
+        self.format
+    }
+
+    /// Everything before the first row.
+    pub fn head(&self, table: &TableContext, row_count: usize) -> anyhow::Result<String> {
+        match self.format {
+            ExportFormat::Csv => csv_header(table, self.options),
+            ExportFormat::Html { .. } => html_head(table, row_count, self.options),
+            ExportFormat::Json => Ok("[".to_owned()),
+        }
+    }
+
+    /// Serializes the next chunk of rows. A chunk that fails leaves nothing behind, so it can
+    /// be retried as is.
+    pub async fn chunk(
+        &mut self,
+        table: &TableContext,
+        rows: &[(u32, Option<u16>)],
+    ) -> anyhow::Result<String> {
+        match self.format {
+            ExportFormat::Csv => csv_records(table, rows, self.options).await,
+            ExportFormat::Html { embed_icons } => {
+                html_rows(table, rows, self.options, embed_icons, &mut self.icons).await
+            }
+            ExportFormat::Json => {
+                let mut output = String::new();
+                let mut has_elements = self.has_elements;
+                for value in json_values(table, rows, self.options).await? {
+                    push_json_element(&mut output, &value, has_elements)?;
+                    has_elements = true;
+                }
+                self.has_elements = has_elements;
+                Ok(output)
+            }
+        }
+    }
+
+    /// Everything after the last row.
+    pub fn tail(&self) -> &'static str {
+        match self.format {
+            ExportFormat::Csv => "",
+            ExportFormat::Html { .. } => HTML_TAIL,
+            ExportFormat::Json if self.has_elements => "\n]",
+            ExportFormat::Json => "]",
+        }
+    }
+}
+
+/// Appends an element of a pretty-printed JSON array.
+fn push_json_element(output: &mut String, value: &Value, separate: bool) -> anyhow::Result<()> {
+    if separate {
+        output.push(',');
+    }
+    for line in serde_json::to_string_pretty(value)?.lines() {
+        output.push_str("\n  ");
+        output.push_str(line);
+    }
+    Ok(())
+}
+
+/// The end of the chunk of about `size` rows starting at `start`. Chunks only end between
+/// rows, so the subrows of a row are always serialized together.
+pub fn chunk_end(rows: &[(u32, Option<u16>)], start: usize, size: usize) -> usize {
+    let mut end = (start + size).min(rows.len());
+    while end < rows.len() && rows[end].0 == rows[end - 1].0 {
+        end += 1;
+    }
+    end
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::{chunk_end, push_json_element};
+
+    #[test]
+    fn chunks_keep_subrows_together() {
+        let rows = [
+            (1, Some(0)),
+            (1, Some(1)),
+            (1, Some(2)),
+            (2, Some(0)),
+            (3, Some(0)),
+        ];
+        assert_eq!(chunk_end(&rows, 0, 2), 3);
+        assert_eq!(chunk_end(&rows, 3, 2), 5);
+        assert_eq!(chunk_end(&rows, 0, 10), 5);
+        assert_eq!(chunk_end(&[(1, None), (2, None)], 0, 1), 1);
+    }
+
+    #[test]
+    fn json_elements_match_pretty_array() {
+        let values = [
+            json!({ "#": 1, "Name": "a" }),
+            json!({ "#": 2, "Params": [1, 2] }),
+        ];
+        let mut output = "[".to_owned();
+        for (i, value) in values.iter().enumerate() {
+            push_json_element(&mut output, value, i != 0).unwrap();
+        }
+        output.push_str("\n]");
+        assert_eq!(output, serde_json::to_string_pretty(&values).unwrap());
+    }
+}
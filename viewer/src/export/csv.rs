@@ -0,0 +1,134 @@
+use std::fmt::Write;
+
+use compact_str::{CompactString, ToCompactString};
+use itertools::Itertools;
+
+use crate::{
+    excel::provider::{ExcelRow, ExcelSheet},
+    sheet::{SchemaColumn, TableContext},
+};
+
+use super::{
+    ExportOptions, SubrowShape, cell_text, group_subrows, iter_rows, ordered_columns, read_cell,
+};
+
+/// Serializes the given rows of a sheet as CSV, using schema column names as headers.
+/// With [`SubrowShape::Arrays`], each cell of a subrow sheet is a JSON array of its subrows' values.
+pub async fn to_csv(
+    table: &TableContext,
+    rows: &[(u32, Option<u16>)],
+    options: ExportOptions,
+) -> anyhow::Result<String> {
+    let mut output = csv_header(table, options)?;
+    output.push_str(&csv_records(table, rows, options).await?);
+    Ok(output)
+}
+
+/// The header record of a CSV export.
+pub(super) fn csv_header(table: &TableContext, options: ExportOptions) -> anyhow::Result<String> {
+    let columns = ordered_columns(table, options)?;
+    let mut output = String::new();
+    write_record(
+        &mut output,
+        std::iter::once("#")
+            .chain(table.sheet().has_subrows().then_some("#subrow"))
+            .chain(columns.iter().map(|(_, c)| c.name())),
+    );
+    Ok(output)
+}
+
+/// The records of the given rows, without the header.
+pub(super) async fn csv_records(
+    table: &TableContext,
+    rows: &[(u32, Option<u16>)],
+    options: ExportOptions,
+) -> anyhow::Result<String> {
+    let columns = ordered_columns(table, options)?;
+    let has_subrows = table.sheet().has_subrows();
+
+    let mut output = String::new();
+
+    if has_subrows && options.subrow_shape == SubrowShape::Arrays {
+        for (row_id, subrow_ids) in group_subrows(rows) {
+            let mut columns_values = vec![Vec::with_capacity(subrow_ids.len()); columns.len()];
+            for &subrow_id in &subrow_ids {
+                let row = table.sheet().get_subrow(row_id, subrow_id)?;
+                let cells = read_cells(table, &columns, row, options).await?;
+                for (values, cell) in columns_values.iter_mut().zip(cells) {
+                    values.push(cell);
+                }
+            }
+            let cells = columns_values
+                .iter()
+                .map(|values| {
+                    serde_json::to_string(&values.iter().map(CompactString::as_str).collect_vec())
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            write_record(
+                &mut output,
+                [
+                    row_id.to_string().as_str(),
+                    serde_json::to_string(&subrow_ids)?.as_str(),
+                ]
+                .into_iter()
+                .chain(cells.iter().map(String::as_str)),
+            );
+        }
+    } else {
+        for row in iter_rows(table, rows) {
+            let ((row_id, subrow_id), row) = row?;
+            let cells = read_cells(table, &columns, row, options).await?;
+            let subrow_id = subrow_id.map(|s| s.to_compact_string());
+            write_record(
+                &mut output,
+                std::iter::once(row_id.to_compact_string().as_str())
+                    .chain(subrow_id.as_deref())
+                    .chain(cells.iter().map(|c| c.as_str())),
+            );
+        }
+    }
+
+    Ok(output)
+}
+
+async fn read_cells(
+    table: &TableContext,
+    columns: &[(u32, SchemaColumn)],
+    row: ExcelRow<'_>,
+    options: ExportOptions,
+) -> anyhow::Result<Vec<CompactString>> {
+    let mut cells = Vec::with_capacity(columns.len());
+    for &(offset_idx, _) in columns {
+        cells.push(cell_text(
+            &read_cell(table, row, offset_idx, options).await?,
+            options,
+        ));
+    }
+    Ok(cells)
+}
+
+pub(super) fn write_record<'a>(output: &mut String, fields: impl Iterator<Item = &'a str>) {
+    let line = fields.map(escape_field).join(",");
+    let _ = writeln!(output, "{line}");
+}
+
+fn escape_field(field: &str) -> std::borrow::Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\"")).into()
+    } else {
+        field.into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::escape_field;
+
+    #[test]
+    fn escape() {
+        assert_eq!(escape_field("plain"), "plain");
+        assert_eq!(escape_field("a,b"), "\"a,b\"");
+        assert_eq!(escape_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(escape_field("two\nlines"), "\"two\nlines\"");
+    }
+}
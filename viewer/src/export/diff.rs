@@ -0,0 +1,310 @@
+use std::fmt::Write;
+
+use compact_str::{CompactString, ToCompactString};
+use itertools::Itertools;
+use serde_json::{Value, json};
+
+use crate::{
+    excel::provider::{ExcelRow, ExcelSheet},
+    sheet::{PathSegment, SchemaColumn, SheetDiff, TableContext},
+};
+
+use super::{
+    ExportOptions, cell_text,
+    csv::write_record,
+    format_row_id,
+    json::{cell_value, row_value},
+    ordered_columns, read_cell,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffFormat {
+    /// RFC 6902 operations against a document of rows keyed by id. Each changed cell gets a
+    /// `test` of its old value followed by a `replace` with its new one.
+    JsonPatch,
+    /// One record per changed cell, with its old and new value.
+    Csv,
+    /// A human readable summary, with a table of old and new values per changed row.
+    Markdown,
+}
+
+impl DiffFormat {
+    pub const ALL: &[Self] = &[Self::JsonPatch, Self::Csv, Self::Markdown];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::JsonPatch => "JSON Patch",
+            Self::Csv => "CSV",
+            Self::Markdown => "Markdown",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::JsonPatch => "json",
+            Self::Csv => "csv",
+            Self::Markdown => "md",
+        }
+    }
+
+    /// Serializes the differences of a sheet against `against` (the other version) in this format.
+    pub async fn export(
+        self,
+        table: &TableContext,
+        diff: &SheetDiff,
+        against: &str,
+        options: ExportOptions,
+    ) -> anyhow::Result<Vec<u8>> {
+        Ok(match self {
+            Self::JsonPatch => to_json_patch(table, diff, options).await?.into_bytes(),
+            Self::Csv => to_diff_csv(table, diff, options).await?.into_bytes(),
+            Self::Markdown => to_markdown(table, diff, against, options)
+                .await?
+                .into_bytes(),
+        })
+    }
+}
+
+/// A changed row in both versions.
+struct ChangedRow<'a> {
+    id: (u32, Option<u16>),
+    new: ExcelRow<'a>,
+    old: ExcelRow<'a>,
+    /// Offset index and column of each changed one, in export order.
+    columns: Vec<(u32, SchemaColumn)>,
+}
+
+/// Reads every changed row from both versions of the sheet, sorted by id.
+fn changed_rows<'a>(
+    table: &'a TableContext,
+    diff: &'a SheetDiff,
+    options: ExportOptions,
+) -> anyhow::Result<Vec<ChangedRow<'a>>> {
+    let Some(old_sheet) = diff.old_sheet() else {
+        return Ok(Vec::new());
+    };
+    let columns = ordered_columns(table, options)?;
+    diff.changed_rows()
+        .into_iter()
+        .map(|((row_id, subrow_id), changed)| {
+            let subrow = subrow_id.unwrap_or_default();
+            Ok(ChangedRow {
+                id: (row_id, subrow_id),
+                new: table.sheet().get_subrow(row_id, subrow)?,
+                old: old_sheet.get_subrow(row_id, subrow)?,
+                columns: columns
+                    .iter()
+                    .filter(|(offset_idx, _)| changed.contains(offset_idx))
+                    .cloned()
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+/// Serializes the differences as a JSON Patch. Rows are addressed as `/<row id>`, or
+/// `/<row id>/<subrow id>` in subrow sheets, and cells by the schema's nesting below that.
+pub async fn to_json_patch(
+    table: &TableContext,
+    diff: &SheetDiff,
+    options: ExportOptions,
+) -> anyhow::Result<String> {
+    let row_pointer = |(row_id, subrow_id): (u32, Option<u16>)| match subrow_id {
+        Some(subrow_id) => format!("/{row_id}/{subrow_id}"),
+        None => format!("/{row_id}"),
+    };
+
+    let mut ops = Vec::new();
+    for &row in diff.removed() {
+        ops.push(json!({ "op": "remove", "path": row_pointer(row) }));
+    }
+    for row in changed_rows(table, diff, options)? {
+        for (offset_idx, column) in &row.columns {
+            let path = row_pointer(row.id) + &json_pointer(&column.path());
+            let old = table.cell_by_offset(row.old, *offset_idx)?.read(false)?;
+            let new = table.cell_by_offset(row.new, *offset_idx)?.read(false)?;
+            ops.push(json!({ "op": "test", "path": path, "value": cell_value(&old, options) }));
+            ops.push(json!({ "op": "replace", "path": path, "value": cell_value(&new, options) }));
+        }
+    }
+    let columns = ordered_columns(table, options)?;
+    for (row_id, subrow_id) in diff.added_rows() {
+        let row = table
+            .sheet()
+            .get_subrow(row_id, subrow_id.unwrap_or_default())?;
+        ops.push(json!({
+            "op": "add",
+            "path": row_pointer((row_id, subrow_id)),
+            "value": row_value(table, &columns, row, options, 0).await?,
+        }));
+    }
+    Ok(serde_json::to_string_pretty(&Value::Array(ops))?)
+}
+
+/// Serializes the changed cells as CSV, one record per cell.
+pub async fn to_diff_csv(
+    table: &TableContext,
+    diff: &SheetDiff,
+    options: ExportOptions,
+) -> anyhow::Result<String> {
+    let has_subrows = table.sheet().has_subrows();
+
+    let mut output = String::new();
+    write_record(
+        &mut output,
+        std::iter::once("#")
+            .chain(has_subrows.then_some("#subrow"))
+            .chain(["Column", "Old", "New"]),
+    );
+    for row in changed_rows(table, diff, options)? {
+        let (row_id, subrow_id) = row.id;
+        let row_id = row_id.to_compact_string();
+        let subrow_id = subrow_id.map(|s| s.to_compact_string());
+        for (offset_idx, column) in &row.columns {
+            let old = cell_text(
+                &read_cell(table, row.old, *offset_idx, options).await?,
+                options,
+            );
+            let new = cell_text(
+                &read_cell(table, row.new, *offset_idx, options).await?,
+                options,
+            );
+            write_record(
+                &mut output,
+                std::iter::once(row_id.as_str())
+                    .chain(subrow_id.as_deref())
+                    .chain([column.name(), old.as_str(), new.as_str()]),
+            );
+        }
+    }
+    Ok(output)
+}
+
+/// Summarizes the differences as Markdown, for changelogs and the like.
+pub async fn to_markdown(
+    table: &TableContext,
+    diff: &SheetDiff,
+    against: &str,
+    options: ExportOptions,
+) -> anyhow::Result<String> {
+    let mut output = String::new();
+    let _ = writeln!(
+        output,
+        "# {} ({})\n\nCompared against {against}: {} added, {} changed, {} removed.",
+        table.sheet().name(),
+        table.global().language(),
+        diff.added_count(),
+        diff.changed_count(),
+        diff.removed().len()
+    );
+
+    let added = diff.added_rows();
+    if !added.is_empty() {
+        let _ = writeln!(output, "\n## Added\n");
+        let display_column = table.display_column().map(|(idx, _)| idx);
+        for (row_id, subrow_id) in added {
+            let row = table
+                .sheet()
+                .get_subrow(row_id, subrow_id.unwrap_or_default())?;
+            let label = match display_column {
+                Some(idx) => {
+                    let value = table.cell_by_offset(row, idx)?.read(false)?;
+                    cell_text(&value, options)
+                }
+                None => CompactString::default(),
+            };
+            let id = format_row_id(row_id, subrow_id);
+            if label.is_empty() {
+                let _ = writeln!(output, "- `{id}`");
+            } else {
+                let _ = writeln!(output, "- `{id}` {}", escape_markdown(&label));
+            }
+        }
+    }
+
+    let changed = changed_rows(table, diff, options)?;
+    if !changed.is_empty() {
+        let _ = writeln!(output, "\n## Changed");
+        for row in changed {
+            let _ = writeln!(
+                output,
+                "\n### {}\n\n| Column | Old | New |\n| --- | --- | --- |",
+                format_row_id(row.id.0, row.id.1)
+            );
+            for (offset_idx, column) in &row.columns {
+                let old = cell_text(
+                    &read_cell(table, row.old, *offset_idx, options).await?,
+                    options,
+                );
+                let new = cell_text(
+                    &read_cell(table, row.new, *offset_idx, options).await?,
+                    options,
+                );
+                let _ = writeln!(
+                    output,
+                    "| {} | {} | {} |",
+                    escape_markdown(column.name()),
+                    escape_markdown(&old),
+                    escape_markdown(&new)
+                );
+            }
+        }
+    }
+
+    let removed = diff.removed();
+    if !removed.is_empty() {
+        let _ = writeln!(
+            output,
+            "\n## Removed\n\n{}",
+            removed
+                .iter()
+                .map(|&(row_id, subrow_id)| format!("`{}`", format_row_id(row_id, subrow_id)))
+                .join(", ")
+        );
+    }
+    Ok(output)
+}
+
+/// Builds an RFC 6901 pointer from a schema column's path.
+fn json_pointer(path: &[PathSegment<'_>]) -> String {
+    path.iter()
+        .map(|segment| match segment {
+            PathSegment::Key(key) => format!("/{}", key.replace('~', "~0").replace('/', "~1")),
+            PathSegment::Index(index) => format!("/{index}"),
+        })
+        .collect()
+}
+
+/// Keeps a value on one line and from breaking out of its table cell.
+fn escape_markdown(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace('\n', "<br>")
+}
+
+#[cfg(test)]
+mod test {
+    use super::{escape_markdown, json_pointer};
+    use crate::sheet::PathSegment;
+
+    #[test]
+    fn pointers() {
+        assert_eq!(json_pointer(&[PathSegment::Key("Name")]), "/Name");
+        assert_eq!(
+            json_pointer(&[
+                PathSegment::Key("Params"),
+                PathSegment::Index(2),
+                PathSegment::Key("Value")
+            ]),
+            "/Params/2/Value"
+        );
+        assert_eq!(json_pointer(&[PathSegment::Key("a/b~c")]), "/a~1b~0c");
+    }
+
+    #[test]
+    fn markdown_cells() {
+        assert_eq!(escape_markdown("a|b"), "a\\|b");
+        assert_eq!(escape_markdown("two\nlines"), "two<br>lines");
+    }
+}
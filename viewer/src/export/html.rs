@@ -0,0 +1,152 @@
+use std::{collections::HashMap, fmt::Write, io::Cursor};
+
+use base64::{Engine, prelude::BASE64_STANDARD};
+use either::Either;
+use image::ImageFormat;
+
+use crate::{
+    excel::provider::{ExcelHeader, ExcelProvider},
+    sheet::{CellValue, TableContext},
+};
+
+use super::{ExportOptions, cell_text, format_row_id, iter_rows, ordered_columns, read_cell};
+
+const STYLE: &str = "body{font-family:sans-serif}\
+table{border-collapse:collapse}\
+th,td{border:1px solid #8884;padding:2px 6px;vertical-align:top;white-space:pre-wrap}\
+th{position:sticky;top:0;background:#eee}\
+tr:nth-child(even) td{background:#8881}\
+img{height:32px}";
+
+/// Everything after the last row of an HTML export.
+pub(super) const HTML_TAIL: &str = "</tbody>\n</table>\n</body>\n</html>\n";
+
+/// Renders the given rows of a sheet as a self-contained, static HTML table.
+/// Links are written as their resolved text; icons are optionally embedded as images.
+pub async fn to_html(
+    table: &TableContext,
+    rows: &[(u32, Option<u16>)],
+    options: ExportOptions,
+    embed_icons: bool,
+) -> anyhow::Result<String> {
+    let mut output = html_head(table, rows.len(), options)?;
+    output.push_str(&html_rows(table, rows, options, embed_icons, &mut HashMap::new()).await?);
+    output.push_str(HTML_TAIL);
+    Ok(output)
+}
+
+/// Everything up to the first row of an HTML export.
+pub(super) fn html_head(
+    table: &TableContext,
+    row_count: usize,
+    options: ExportOptions,
+) -> anyhow::Result<String> {
+    let columns = ordered_columns(table, options)?;
+    let sheet_name = escape(table.sheet().name());
+
+    let mut output = String::new();
+    write!(
+        output,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+        <title>{sheet_name}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n\
+        <h1>{sheet_name}</h1>\n<p>{row_count} rows, exported from EXDViewer v{}</p>\n\
+        <table>\n<thead><tr><th>#</th>",
+        crate::build::PKG_VERSION
+    )?;
+    for (_, column) in &columns {
+        write!(output, "<th>{}</th>", escape(column.name()))?;
+    }
+    output.push_str("</tr></thead>\n<tbody>\n");
+    Ok(output)
+}
+
+/// The table rows of the given rows. `icons` caches embedded icons by id.
+pub(super) async fn html_rows(
+    table: &TableContext,
+    rows: &[(u32, Option<u16>)],
+    options: ExportOptions,
+    embed_icons: bool,
+    icons: &mut HashMap<u32, Option<String>>,
+) -> anyhow::Result<String> {
+    let columns = ordered_columns(table, options)?;
+
+    let mut output = String::new();
+    for row in iter_rows(table, rows) {
+        let ((row_id, subrow_id), row) = row?;
+        write!(output, "<tr><td>{}</td>", format_row_id(row_id, subrow_id))?;
+        for &(offset_idx, _) in &columns {
+            let value = read_cell(table, row, offset_idx, options).await?;
+            output.push_str("<td>");
+            match value {
+                CellValue::Icon(icon_id) if embed_icons => {
+                    if let Ok(icon_id) = u32::try_from(icon_id)
+                        && let Some(src) = icon_source(table, icons, icon_id).await
+                    {
+                        write!(output, "<img src=\"{src}\" title=\"{icon_id}\">")?;
+                    } else {
+                        output.push_str(&escape(&icon_id.to_string()));
+                    }
+                }
+                value => output.push_str(&escape(&cell_text(&value, options))),
+            }
+            output.push_str("</td>");
+        }
+        output.push_str("</tr>\n");
+    }
+    Ok(output)
+}
+
+/// Returns an `<img>` source for the icon, caching the result (including failures) by id.
+async fn icon_source(
+    table: &TableContext,
+    cache: &mut HashMap<u32, Option<String>>,
+    icon_id: u32,
+) -> Option<String> {
+    if let Some(src) = cache.get(&icon_id) {
+        return src.clone();
+    }
+
+    let src = match table
+        .global()
+        .backend()
+        .excel()
+        .get_icon(icon_id, false)
+        .await
+    {
+        Ok(Either::Left(url)) => Some(escape(url.as_str())),
+        Ok(Either::Right(image)) => {
+            let mut png = Cursor::new(Vec::new());
+            match image.write_to(&mut png, ImageFormat::Png) {
+                Ok(()) => Some(format!(
+                    "data:image/png;base64,{}",
+                    BASE64_STANDARD.encode(png.into_inner())
+                )),
+                Err(e) => {
+                    log::error!("Failed to encode icon {icon_id}: {e}");
+                    None
+                }
+            }
+        }
+        Err(e) => {
+            log::warn!("Failed to load icon {icon_id} for export: {e}");
+            None
+        }
+    };
+    cache.insert(icon_id, src.clone());
+    src
+}
+
+fn escape(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            '\'' => output.push_str("&#39;"),
+            c => output.push(c),
+        }
+    }
+    output
+}
@@ -0,0 +1,244 @@
+use compact_str::ToCompactString;
+use egui::ecolor::HexColor;
+use either::Either;
+use serde_json::{Map, Value};
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+use crate::{
+    excel::provider::{ExcelRow, ExcelSheet},
+    sheet::{CellValue, LinkTarget, PathSegment, SchemaColumn, TableContext},
+    utils::sleep,
+};
+
+use super::{
+    ExportOptions, LINK_POLL_INTERVAL, LINK_TIMEOUT, SubrowShape, group_subrows, iter_rows,
+    ordered_columns,
+};
+
+/// Serializes the given rows of a sheet as a JSON array of objects. Arrays and nested
+/// fields are rebuilt from the schema's field definitions, and links are expanded
+/// into the linked row up to `options.link_depth` levels deep. With [`SubrowShape::Arrays`],
+/// each row of a subrow sheet holds its subrows in a `subrows` array.
+pub async fn to_json(
+    table: &TableContext,
+    rows: &[(u32, Option<u16>)],
+    options: ExportOptions,
+) -> anyhow::Result<String> {
+    Ok(serde_json::to_string_pretty(
+        &json_values(table, rows, options).await?,
+    )?)
+}
+
+/// The elements of the JSON array for the given rows.
+pub(super) async fn json_values(
+    table: &TableContext,
+    rows: &[(u32, Option<u16>)],
+    options: ExportOptions,
+) -> anyhow::Result<Vec<Value>> {
+    let columns = ordered_columns(table, options)?;
+    if table.sheet().has_subrows() && options.subrow_shape == SubrowShape::Arrays {
+        let mut output = Vec::new();
+        for (row_id, subrow_ids) in group_subrows(rows) {
+            let mut subrows = Vec::with_capacity(subrow_ids.len());
+            for subrow_id in subrow_ids {
+                let row = table.sheet().get_subrow(row_id, subrow_id)?;
+                let mut value = Map::new();
+                value.insert("#subrow".to_owned(), subrow_id.into());
+                if let Value::Object(fields) =
+                    row_value(table, &columns, row, options, options.link_depth).await?
+                {
+                    value.extend(fields);
+                }
+                subrows.push(Value::Object(value));
+            }
+            output.push(serde_json::json!({
+                "#": row_id,
+                "subrows": subrows,
+            }));
+        }
+        return Ok(output);
+    }
+
+    let mut output = Vec::with_capacity(rows.len());
+    for row in iter_rows(table, rows) {
+        let ((row_id, subrow_id), row) = row?;
+        let mut value = Map::new();
+        value.insert("#".to_owned(), row_id.into());
+        if let Some(subrow_id) = subrow_id {
+            value.insert("#subrow".to_owned(), subrow_id.into());
+        }
+        if let Value::Object(fields) =
+            row_value(table, &columns, row, options, options.link_depth).await?
+        {
+            value.extend(fields);
+        }
+        output.push(Value::Object(value));
+    }
+    Ok(output)
+}
+
+/// Builds a row's object from `columns`, as given by [`ordered_columns`] for `table`.
+pub(super) async fn row_value(
+    table: &TableContext,
+    columns: &[(u32, SchemaColumn)],
+    row: ExcelRow<'_>,
+    options: ExportOptions,
+    link_depth: u8,
+) -> anyhow::Result<Value> {
+    let mut root = Value::Object(Map::new());
+    for (offset_idx, column) in columns {
+        let cell = table.cell_by_offset(row, *offset_idx)?;
+
+        let value = if link_depth == 0 {
+            None
+        } else {
+            let deadline = Instant::now() + LINK_TIMEOUT;
+            let mut target = cell.link_target()?;
+            while matches!(target, Some(LinkTarget::InProgress(_))) && Instant::now() < deadline {
+                sleep(LINK_POLL_INTERVAL).await;
+                target = cell.link_target()?;
+            }
+            match target {
+                Some(LinkTarget::Found {
+                    sheet_name,
+                    row_id,
+                    table,
+                }) => {
+                    let linked_row = table.sheet().get_row(row_id)?;
+                    let linked_columns = ordered_columns(&table, options)?;
+                    let fields = Box::pin(row_value(
+                        &table,
+                        &linked_columns,
+                        linked_row,
+                        options,
+                        link_depth - 1,
+                    ))
+                    .await?;
+                    Some(serde_json::json!({
+                        "sheet": sheet_name,
+                        "row_id": row_id,
+                        "fields": fields,
+                    }))
+                }
+                Some(LinkTarget::Invalid(row_id) | LinkTarget::InProgress(row_id)) => {
+                    Some(integer_value(row_id))
+                }
+                None => None,
+            }
+        };
+        let value = match value {
+            Some(value) => value,
+            None => cell_value(&cell.read(false)?, options),
+        };
+
+        insert_at(&mut root, &column.path(), value);
+    }
+    Ok(root)
+}
+
+pub(super) fn cell_value(value: &CellValue, options: ExportOptions) -> Value {
+    match value {
+        CellValue::String(s) => {
+            let text = if options.evaluate_strings {
+                s.format().try_to_compact_string()
+            } else {
+                s.macro_string().try_to_compact_string()
+            };
+            Value::String(text.unwrap_or_default().into())
+        }
+        CellValue::Integer(i)
+        | CellValue::Icon(i)
+        | CellValue::InvalidLink(i)
+        | CellValue::InProgressLink(i) => integer_value(*i),
+        CellValue::Float(f) => {
+            serde_json::Number::from_f64((*f).into()).map_or(Value::Null, Value::Number)
+        }
+        CellValue::Boolean(b) => Value::Bool(*b),
+        CellValue::ModelId(Either::Left(id)) => (*id).into(),
+        CellValue::ModelId(Either::Right(id)) => (*id).into(),
+        CellValue::Color(color) => Value::String(HexColor::Hex8(*color).to_string()),
+        CellValue::ValidLink { row_id, .. } => (*row_id).into(),
+    }
+}
+
+fn integer_value(value: i128) -> Value {
+    if let Ok(value) = i64::try_from(value) {
+        value.into()
+    } else if let Ok(value) = u64::try_from(value) {
+        value.into()
+    } else {
+        Value::String(value.to_string())
+    }
+}
+
+fn insert_at(root: &mut Value, path: &[PathSegment<'_>], value: Value) {
+    let Some((segment, rest)) = path.split_first() else {
+        *root = value;
+        return;
+    };
+
+    let child = match segment {
+        PathSegment::Key(key) => {
+            if !root.is_object() {
+                *root = Value::Object(Map::new());
+            }
+            root.as_object_mut()
+                .unwrap()
+                .entry(key.to_string())
+                .or_insert(Value::Null)
+        }
+        PathSegment::Index(index) => {
+            if !root.is_array() {
+                *root = Value::Array(Vec::new());
+            }
+            let array = root.as_array_mut().unwrap();
+            if array.len() <= *index {
+                array.resize(*index + 1, Value::Null);
+            }
+            &mut array[*index]
+        }
+    };
+    insert_at(child, rest, value);
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::insert_at;
+    use crate::sheet::PathSegment::{Index, Key};
+
+    #[test]
+    fn nesting() {
+        let mut root = json!({});
+        insert_at(&mut root, &[Key("Name.Text")], json!("a"));
+        insert_at(
+            &mut root,
+            &[Key("Params"), Index(0), Key("Value")],
+            json!(1),
+        );
+        insert_at(
+            &mut root,
+            &[Key("Params"), Index(1), Key("Value")],
+            json!(2),
+        );
+        insert_at(
+            &mut root,
+            &[Key("Params"), Index(1), Key("Flag")],
+            json!(true),
+        );
+        insert_at(&mut root, &[Key("Grid"), Index(1), Index(0)], json!(3));
+        assert_eq!(
+            root,
+            json!({
+                "Name.Text": "a",
+                "Params": [{ "Value": 1 }, { "Value": 2, "Flag": true }],
+                "Grid": [null, [3]],
+            })
+        );
+    }
+}
@@ -0,0 +1,326 @@
+mod chunked;
+mod csv;
+mod diff;
+mod html;
+mod json;
+
+use std::{io::Cursor, rc::Rc};
+
+use compact_str::{CompactString, ToCompactString};
+use either::Either;
+use image::ImageFormat;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Duration, Instant};
+#[cfg(target_arch = "wasm32")]
+use web_time::{Duration, Instant};
+
+pub use chunked::{CHUNK_ROWS, ChunkedExport, chunk_end};
+pub use csv::to_csv;
+pub use diff::DiffFormat;
+pub use html::to_html;
+pub use json::to_json;
+
+use crate::{
+    excel::{
+        base::CachedProvider,
+        provider::{ExcelHeader, ExcelProvider, ExcelRow, ExcelSheet},
+    },
+    i18n::Text,
+    sheet::{CellValue, SchemaColumn, SheetDiff, TableContext},
+    utils::{TrackedPromise, fetch_url_limited, sleep},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    /// A static, read-only HTML table. Icons are optionally embedded as images.
+    Html {
+        embed_icons: bool,
+    },
+    /// An array of row objects, nested according to the schema's arrays and fields.
+    Json,
+}
+
+impl ExportFormat {
+    pub const ALL: &[Self] = &[
+        Self::Csv,
+        Self::Html { embed_icons: false },
+        Self::Html { embed_icons: true },
+        Self::Json,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Csv => "CSV",
+            Self::Html { embed_icons: false } => "HTML",
+            Self::Html { embed_icons: true } => "HTML (with icons)",
+            Self::Json => "JSON",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Html { .. } => "html",
+            Self::Json => "json",
+        }
+    }
+
+    /// Serializes the given rows of a sheet in this format.
+    pub async fn export(
+        self,
+        table: &TableContext,
+        rows: &[(u32, Option<u16>)],
+        options: ExportOptions,
+    ) -> anyhow::Result<Vec<u8>> {
+        Ok(match self {
+            Self::Csv => to_csv(table, rows, options).await?.into_bytes(),
+            Self::Html { embed_icons } => to_html(table, rows, options, embed_icons)
+                .await?
+                .into_bytes(),
+            Self::Json => to_json(table, rows, options).await?.into_bytes(),
+        })
+    }
+}
+
+/// How the subrows of a subrow sheet are laid out in CSV and JSON exports.
+/// HTML exports always list each subrow as its own table row.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SubrowShape {
+    /// One record per subrow, with a separate subrow index column.
+    #[default]
+    Records,
+    /// One record per row, with each row's subrows aggregated into arrays.
+    Arrays,
+}
+
+impl SubrowShape {
+    pub const ALL: &[Self] = &[Self::Records, Self::Arrays];
+
+    pub fn name(self) -> Text {
+        match self {
+            Self::Records => Text::SubrowRecords,
+            Self::Arrays => Text::SubrowArrays,
+        }
+    }
+
+    pub fn description(self) -> Text {
+        match self {
+            Self::Records => Text::SubrowRecordsHover,
+            Self::Arrays => Text::SubrowArraysHover,
+        }
+    }
+}
+
+/// How cells are turned into text when exporting a sheet.
+#[derive(Debug, Clone, Copy)]
+pub struct ExportOptions {
+    /// Order columns by offset (`true`) or by column index (`false`), matching the table view.
+    pub sorted_by_offset: bool,
+    /// Evaluate SeStrings instead of exporting their macro representation.
+    pub evaluate_strings: bool,
+    /// Replace links with the display field of the linked row when one is available.
+    pub resolve_display_field: bool,
+    /// How many levels of links to expand into the linked row's fields (JSON only).
+    pub link_depth: u8,
+    /// Layout of subrows; ignored for sheets without subrows.
+    pub subrow_shape: SubrowShape,
+}
+
+/// How long to wait for a linked sheet to load before giving up on it.
+const LINK_TIMEOUT: Duration = Duration::from_secs(10);
+/// How often a linked sheet that's still loading is checked on.
+const LINK_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Reads a cell for a text export. With display fields resolved, links into sheets that are
+/// still loading are waited on, so the output doesn't depend on which sheets happened to be
+/// loaded already. Fails if the linked sheet doesn't load in time.
+async fn read_cell(
+    table: &TableContext,
+    row: ExcelRow<'_>,
+    offset_idx: u32,
+    options: ExportOptions,
+) -> anyhow::Result<CellValue> {
+    let cell = table.cell_by_offset(row, offset_idx)?;
+    let deadline = Instant::now() + LINK_TIMEOUT;
+    loop {
+        let value = cell.read(options.resolve_display_field)?;
+        if !value.is_in_progress() {
+            return Ok(value);
+        }
+        if Instant::now() >= deadline {
+            let (column, _) = table.get_column_by_offset(offset_idx)?;
+            anyhow::bail!(
+                "{} links to a sheet that didn't load in time",
+                column.name()
+            );
+        }
+        sleep(LINK_POLL_INTERVAL).await;
+    }
+}
+
+/// Returns the schema columns in export order, alongside their offset index.
+fn ordered_columns(
+    table: &TableContext,
+    options: ExportOptions,
+) -> anyhow::Result<Vec<(u32, SchemaColumn)>> {
+    (0..table.column_count() as u32)
+        .map(|i| {
+            if options.sorted_by_offset {
+                Ok((i, table.get_column_by_offset(i)?.0))
+            } else {
+                let ((schema_column, _), offset_idx) = table.get_column_by_index(i)?;
+                Ok((offset_idx, schema_column))
+            }
+        })
+        .collect()
+}
+
+/// Iterates the given (row id, subrow id) pairs, yielding the row data for each.
+fn iter_rows<'a>(
+    table: &'a TableContext,
+    rows: &'a [(u32, Option<u16>)],
+) -> impl Iterator<Item = anyhow::Result<((u32, Option<u16>), ExcelRow<'a>)>> + 'a {
+    rows.iter().map(|&(row_id, subrow_id)| {
+        let row = table
+            .sheet()
+            .get_subrow(row_id, subrow_id.unwrap_or_default())?;
+        Ok(((row_id, subrow_id), row))
+    })
+}
+
+/// Groups the (row id, subrow id) pairs by row, keeping their order. Assumes the subrows of
+/// a row are adjacent, as they are in the table.
+fn group_subrows(rows: &[(u32, Option<u16>)]) -> Vec<(u32, Vec<u16>)> {
+    rows.iter()
+        .chunk_by(|(row_id, _)| *row_id)
+        .into_iter()
+        .map(|(row_id, subrows)| {
+            (
+                row_id,
+                subrows.map(|(_, s)| s.unwrap_or_default()).collect(),
+            )
+        })
+        .collect()
+}
+
+pub fn format_row_id(row_id: u32, subrow_id: Option<u16>) -> CompactString {
+    match subrow_id {
+        Some(subrow_id) => compact_str::format_compact!("{row_id}.{subrow_id}"),
+        None => row_id.to_compact_string(),
+    }
+}
+
+/// Formats a cell as plain text, the same way it's presented in the table.
+pub fn cell_text(value: &CellValue, options: ExportOptions) -> CompactString {
+    match value {
+        CellValue::String(s) if options.evaluate_strings => {
+            s.format().try_to_compact_string().unwrap_or_default()
+        }
+        CellValue::ValidLink {
+            sheet_name,
+            row_id,
+            value: None,
+        } => compact_str::format_compact!("{sheet_name}#{row_id}"),
+        CellValue::ValidLink {
+            value: Some(value), ..
+        } => cell_text(value, options),
+        CellValue::InProgressLink(row_id) => compact_str::format_compact!("...#{row_id}"),
+        CellValue::InvalidLink(row_id) => compact_str::format_compact!("???#{row_id}"),
+        value => value.coerce_string(),
+    }
+}
+
+/// Serializes a sheet's differences against another version in the background, then prompts
+/// the user for a save location.
+pub fn export_diff_and_save(
+    table: TableContext,
+    diff: Rc<SheetDiff>,
+    against: String,
+    format: DiffFormat,
+    options: ExportOptions,
+) -> TrackedPromise<()> {
+    let file_name = format!(
+        "{}.{}.{against}.diff.{}",
+        table.sheet().name(),
+        table.global().language(),
+        format.extension()
+    );
+    let dialog = rfd::AsyncFileDialog::new()
+        .set_title(format!("Export Differences as {}", format.name()))
+        .set_file_name(file_name);
+    TrackedPromise::spawn_local(async move {
+        let data = match format.export(&table, &diff, &against, options).await {
+            Ok(data) => data,
+            Err(e) => {
+                log::error!("Failed to export differences: {e:?}");
+                return;
+            }
+        };
+        if let Some(file) = dialog.save_file().await {
+            if let Err(e) = file.write(&data).await {
+                log::error!("Failed to save differences: {e}");
+            } else {
+                log::info!("Differences saved successfully");
+            }
+        }
+    })
+}
+
+/// Fetches an icon as a PNG. Icons only available as a URL, like XIVAPI's, are downloaded
+/// under the same request limit as the rest of a web backend's files.
+pub async fn icon_png(
+    excel: &CachedProvider,
+    icon_id: u32,
+    hires: bool,
+) -> anyhow::Result<Vec<u8>> {
+    match excel.get_icon(icon_id, hires).await? {
+        Either::Left(url) => Ok(fetch_url_limited(url).await?),
+        Either::Right(image) => {
+            let mut png = Cursor::new(Vec::new());
+            image.write_to(&mut png, ImageFormat::Png)?;
+            Ok(png.into_inner())
+        }
+    }
+}
+
+/// Fetches an icon as a PNG, then prompts the user for a save location.
+pub fn save_icon(excel: CachedProvider, icon_id: u32, hires: bool) -> TrackedPromise<()> {
+    let file_name = format!("{icon_id:06}{}.png", if hires { "_hr1" } else { "" });
+    let dialog = rfd::AsyncFileDialog::new()
+        .set_title("Save Icon")
+        .set_file_name(file_name);
+    TrackedPromise::spawn_local(async move {
+        let data = match icon_png(&excel, icon_id, hires).await {
+            Ok(data) => data,
+            Err(e) => {
+                log::error!("Failed to load icon {icon_id}: {e:?}");
+                return;
+            }
+        };
+        if let Some(file) = dialog.save_file().await {
+            if let Err(e) = file.write(&data).await {
+                log::error!("Failed to save icon: {e}");
+            } else {
+                log::info!("Icon {icon_id} saved successfully");
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::group_subrows;
+
+    #[test]
+    fn subrow_groups() {
+        assert_eq!(
+            group_subrows(&[(1, Some(0)), (1, Some(1)), (3, Some(0)), (4, Some(2))]),
+            vec![(1, vec![0, 1]), (3, vec![0]), (4, vec![2])]
+        );
+        assert_eq!(group_subrows(&[]), vec![]);
+    }
+}
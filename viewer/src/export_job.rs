@@ -0,0 +1,350 @@
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use egui::{ProgressBar, RichText};
+
+use crate::{
+    excel::provider::ExcelHeader,
+    export::{self, CHUNK_ROWS, ChunkedExport, ExportFormat, ExportOptions},
+    i18n::{Text, tr},
+    sheet::TableContext,
+    utils::{PromiseKind, TrackedPromise, yield_to_ui},
+};
+
+/// A chunk that fails is tried this many more times before the export pauses.
+const CHUNK_RETRIES: u32 = 3;
+
+/// Where an export is written. Native writes every chunk to the file as soon as it's done;
+/// browsers can only download whole files, so web keeps the export until it's finished.
+struct Destination {
+    #[cfg(not(target_arch = "wasm32"))]
+    path: std::path::PathBuf,
+    #[cfg(not(target_arch = "wasm32"))]
+    file: std::fs::File,
+    #[cfg(target_arch = "wasm32")]
+    file: rfd::FileHandle,
+    #[cfg(target_arch = "wasm32")]
+    data: Vec<u8>,
+}
+
+impl Destination {
+    /// Prompts the user for a save location. `None` if they cancelled.
+    async fn pick(table: &TableContext, format: ExportFormat) -> anyhow::Result<Option<Self>> {
+        let file_name = format!(
+            "{}.{}.{}",
+            table.sheet().name(),
+            table.global().language(),
+            format.extension()
+        );
+        let Some(file) = rfd::AsyncFileDialog::new()
+            .set_title(format!("Export {}", format.name()))
+            .set_file_name(file_name)
+            .save_file()
+            .await
+        else {
+            return Ok(None);
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let destination = Self {
+            path: file.path().to_owned(),
+            file: std::fs::File::create(file.path())?,
+        };
+        #[cfg(target_arch = "wasm32")]
+        let destination = Self {
+            file,
+            data: Vec::new(),
+        };
+        Ok(Some(destination))
+    }
+
+    fn write(&mut self, data: &str) -> anyhow::Result<()> {
+        #[cfg(not(target_arch = "wasm32"))]
+        std::io::Write::write_all(&mut self.file, data.as_bytes())?;
+        #[cfg(target_arch = "wasm32")]
+        self.data.extend_from_slice(data.as_bytes());
+        Ok(())
+    }
+
+    async fn finish(self) -> anyhow::Result<()> {
+        #[cfg(not(target_arch = "wasm32"))]
+        self.file.sync_all()?;
+        #[cfg(target_arch = "wasm32")]
+        self.file.write(&self.data).await?;
+        Ok(())
+    }
+
+    /// Removes what was written of an export that won't be finished.
+    fn discard(self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            drop(self.file);
+            if let Err(e) = std::fs::remove_file(&self.path) {
+                log::warn!("Failed to remove unfinished export: {e}");
+            }
+        }
+    }
+}
+
+/// Everything needed to pick an export back up where it stopped.
+struct Checkpoint {
+    table: TableContext,
+    rows: Vec<(u32, Option<u16>)>,
+    export: ChunkedExport,
+    destination: Destination,
+    /// Index of the first row in `rows` that isn't written yet
+    next: usize,
+}
+
+/// Shared between the window and the running export.
+#[derive(Clone, Default)]
+struct Control {
+    /// (written, total) rows
+    progress: Rc<Cell<(usize, usize)>>,
+    pause: Rc<Cell<bool>>,
+    cancel: Rc<Cell<bool>>,
+    /// Where a paused or failed export is set aside until it's resumed
+    checkpoint: Rc<RefCell<Option<Checkpoint>>>,
+}
+
+enum Stopped {
+    Finished,
+    Paused,
+    /// Paused after a chunk kept failing
+    Failed(String),
+    Cancelled,
+}
+
+enum Status {
+    Running,
+    /// With the error that paused it, if any
+    Paused(Option<String>),
+    Done(Result<(), String>),
+}
+
+/// Exports the shown rows of a sheet a chunk at a time, writing each to the destination as it
+/// goes. The export can be paused and resumed, and pauses by itself when the backend keeps
+/// failing partway through, to be resumed from the chunk that failed.
+pub struct ExportJobWindow {
+    sheet_name: String,
+    format: ExportFormat,
+    control: Control,
+    promise: Option<TrackedPromise<anyhow::Result<Stopped>>>,
+    status: Status,
+}
+
+impl ExportJobWindow {
+    pub fn new(
+        table: TableContext,
+        rows: Vec<(u32, Option<u16>)>,
+        format: ExportFormat,
+        options: ExportOptions,
+    ) -> Self {
+        let control = Control::default();
+        control.progress.set((0, rows.len()));
+        let sheet_name = table.sheet().name().to_owned();
+        let promise = TrackedPromise::spawn_local(start(
+            table,
+            rows,
+            ChunkedExport::new(format, options),
+            control.clone(),
+        ));
+        Self {
+            sheet_name,
+            format,
+            control,
+            promise: Some(promise),
+            status: Status::Running,
+        }
+    }
+
+    /// `open` is cleared when the window is closed, which cancels an unfinished export, or
+    /// when the save dialog was cancelled.
+    pub fn draw(&mut self, ctx: &egui::Context, open: &mut bool) {
+        if let Some(promise) = self.promise.take() {
+            match promise.try_take() {
+                Ok(Ok(Stopped::Finished)) => {
+                    log::info!("Export of {} saved successfully", self.sheet_name);
+                    self.status = Status::Done(Ok(()));
+                }
+                Ok(Ok(Stopped::Paused)) => self.status = Status::Paused(None),
+                Ok(Ok(Stopped::Failed(e))) => self.status = Status::Paused(Some(e)),
+                Ok(Ok(Stopped::Cancelled)) => {
+                    *open = false;
+                    return;
+                }
+                Ok(Err(e)) => {
+                    log::error!("Failed to export sheet: {e:?}");
+                    self.status = Status::Done(Err(e.to_string()));
+                }
+                Err(promise) => self.promise = Some(promise),
+            }
+        }
+
+        let mut cancelled = false;
+        egui::Window::new(format!("{} {}", tr(ctx, Text::Export), self.format.name()))
+            .id(egui::Id::new("export-job-window"))
+            .open(open)
+            .collapsible(false)
+            .resizable(false)
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                ui.label(RichText::new(&self.sheet_name).monospace());
+                ui.add_space(4.0);
+
+                let (written, total) = self.control.progress.get();
+                if let Status::Done(result) = &self.status {
+                    match result {
+                        Ok(()) => {
+                            ui.label(tr(ctx, Text::SavedRows).replace("{n}", &total.to_string()))
+                        }
+                        Err(e) => ui.colored_label(ui.visuals().error_fg_color, e),
+                    };
+                    return;
+                }
+
+                ui.add(
+                    ProgressBar::new(if total == 0 {
+                        0.0
+                    } else {
+                        written as f32 / total as f32
+                    })
+                    .text(format!("{written} / {total} {}", tr(ctx, Text::Rows))),
+                );
+                if let Status::Paused(Some(e)) = &self.status {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        format!("⚠ {}", tr(ctx, Text::PausedAfterFailure)),
+                    )
+                    .on_hover_text(e);
+                }
+                ui.horizontal(|ui| {
+                    match self.status {
+                        Status::Running => {
+                            let pausing = self.control.pause.get();
+                            if ui
+                                .add_enabled(!pausing, egui::Button::new(tr(ctx, Text::Pause)))
+                                .clicked()
+                            {
+                                self.control.pause.set(true);
+                            }
+                        }
+                        Status::Paused(_) => {
+                            if ui.button(tr(ctx, Text::Resume)).clicked() {
+                                self.resume();
+                            }
+                        }
+                        Status::Done(_) => unreachable!(),
+                    }
+                    cancelled = ui.button(tr(ctx, Text::Cancel)).clicked();
+                });
+            });
+
+        if cancelled {
+            *open = false;
+        }
+        if !*open && !matches!(self.status, Status::Done(_)) {
+            self.cancel();
+        }
+    }
+
+    fn resume(&mut self) {
+        let Some(checkpoint) = self.control.checkpoint.take() else {
+            return;
+        };
+        self.control.pause.set(false);
+        self.promise = Some(TrackedPromise::spawn_local(run(
+            checkpoint,
+            self.control.clone(),
+        )));
+        self.status = Status::Running;
+    }
+
+    /// Stops the export and removes what was written of it. A running export stops once the
+    /// chunk it's on is done.
+    fn cancel(&mut self) {
+        self.control.cancel.set(true);
+        if let Some(checkpoint) = self.control.checkpoint.take() {
+            checkpoint.destination.discard();
+        }
+    }
+}
+
+async fn start(
+    table: TableContext,
+    rows: Vec<(u32, Option<u16>)>,
+    export: ChunkedExport,
+    control: Control,
+) -> anyhow::Result<Stopped> {
+    let Some(mut destination) = Destination::pick(&table, export.format()).await? else {
+        return Ok(Stopped::Cancelled);
+    };
+    destination.write(&export.head(&table, rows.len())?)?;
+    run(
+        Checkpoint {
+            table,
+            rows,
+            export,
+            destination,
+            next: 0,
+        },
+        control,
+    )
+    .await
+}
+
+async fn run(mut checkpoint: Checkpoint, control: Control) -> anyhow::Result<Stopped> {
+    let total = checkpoint.rows.len();
+    let mut retries = 0;
+    while checkpoint.next < total {
+        if control.cancel.get() {
+            checkpoint.destination.discard();
+            return Ok(Stopped::Cancelled);
+        }
+        if control.pause.get() {
+            control.checkpoint.replace(Some(checkpoint));
+            return Ok(Stopped::Paused);
+        }
+
+        let end = export::chunk_end(&checkpoint.rows, checkpoint.next, CHUNK_ROWS);
+        let chunk = checkpoint
+            .export
+            .chunk(&checkpoint.table, &checkpoint.rows[checkpoint.next..end])
+            .await;
+        match chunk {
+            Ok(data) => {
+                if let Err(e) = checkpoint.destination.write(&data) {
+                    checkpoint.destination.discard();
+                    return Err(e);
+                }
+                checkpoint.next = end;
+                retries = 0;
+                control.progress.set((end, total));
+            }
+            Err(e) if retries < CHUNK_RETRIES => {
+                retries += 1;
+                log::warn!(
+                    "Export chunk at row {} failed, retrying ({retries}/{CHUNK_RETRIES}): {e}",
+                    checkpoint.next
+                );
+            }
+            Err(e) => {
+                log::error!("Export chunk at row {} failed: {e:?}", checkpoint.next);
+                control.checkpoint.replace(Some(checkpoint));
+                return Ok(Stopped::Failed(e.to_string()));
+            }
+        }
+        yield_to_ui().await;
+    }
+
+    let Checkpoint {
+        export,
+        mut destination,
+        ..
+    } = checkpoint;
+    destination.write(export.tail())?;
+    destination.finish().await?;
+    Ok(Stopped::Finished)
+}
@@ -0,0 +1,178 @@
+use egui::{Grid, RichText, TextEdit};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    i18n::{Text, tr},
+    settings::EXTERNAL_LINKS,
+    sheet::SheetRef,
+};
+
+/// A site a row can be looked up on, offered in row context menus.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExternalLink {
+    pub name: String,
+    /// `{sheet}`, `{row}` and `{subrow}` are replaced with the row's. The subrow is 0 on sheets
+    /// without subrows.
+    pub url: String,
+    /// Only offered on this sheet, for sites that only know of one kind of row, like items.
+    pub sheet: Option<String>,
+}
+
+impl ExternalLink {
+    fn new(name: &str, url: &str, sheet: Option<&str>) -> Self {
+        Self {
+            name: name.to_owned(),
+            url: url.to_owned(),
+            sheet: sheet.map(str::to_owned),
+        }
+    }
+
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self::new(
+                "XIVAPI",
+                "https://v2.xivapi.com/api/sheet/{sheet}/{row}",
+                None,
+            ),
+            Self::new(
+                "Garland Tools",
+                "https://www.garlandtools.org/db/#item/{row}",
+                Some("Item"),
+            ),
+            Self::new(
+                "Teamcraft",
+                "https://ffxivteamcraft.com/db/en/item/{row}",
+                Some("Item"),
+            ),
+        ]
+    }
+
+    pub fn applies_to(&self, sheet_name: &str) -> bool {
+        self.sheet
+            .as_deref()
+            .is_none_or(|sheet| sheet.eq_ignore_ascii_case(sheet_name))
+    }
+
+    pub fn url_for(&self, (sheet_name, (row_id, subrow_id)): &SheetRef) -> String {
+        self.url
+            .replace("{sheet}", sheet_name)
+            .replace("{row}", &row_id.to_string())
+            .replace("{subrow}", &subrow_id.unwrap_or_default().to_string())
+    }
+}
+
+/// Lists the links that apply to `row` inside a row's context menu, under a submenu so they
+/// don't crowd it. Nothing is drawn if none do.
+pub fn draw_menu_items(ui: &mut egui::Ui, row: &SheetRef) {
+    let links = EXTERNAL_LINKS.get(ui.ctx());
+    let links = links
+        .iter()
+        .filter(|link| link.applies_to(&row.0))
+        .collect::<Vec<_>>();
+    if links.is_empty() {
+        return;
+    }
+    ui.menu_button(tr(ui.ctx(), Text::OpenExternally), |ui| {
+        for link in links {
+            let url = link.url_for(row);
+            if ui.button(&link.name).on_hover_text(&url).clicked() {
+                ui.ctx().open_url(egui::OpenUrl::new_tab(url));
+                ui.close();
+            }
+        }
+    });
+}
+
+/// Edits the link templates, inside the menu they're configured from.
+pub fn draw_settings(ui: &mut egui::Ui) {
+    let ctx = &ui.ctx().clone();
+    ui.set_max_width(560.0);
+    ui.label(RichText::new(tr(ctx, Text::ExternalLinksHint)).weak());
+    ui.separator();
+
+    let mut links = EXTERNAL_LINKS.get(ctx);
+    let mut changed = false;
+    let mut removed = None;
+    Grid::new("external_links_grid")
+        .striped(true)
+        .show(ui, |ui| {
+            for (idx, link) in links.iter_mut().enumerate() {
+                changed |= ui
+                    .add(
+                        TextEdit::singleline(&mut link.name)
+                            .hint_text(tr(ctx, Text::Label))
+                            .desired_width(100.0),
+                    )
+                    .changed();
+                changed |= ui
+                    .add(
+                        TextEdit::singleline(&mut link.url)
+                            .hint_text("https://…/{sheet}/{row}")
+                            .desired_width(280.0),
+                    )
+                    .changed();
+
+                let mut sheet = link.sheet.clone().unwrap_or_default();
+                if ui
+                    .add(
+                        TextEdit::singleline(&mut sheet)
+                            .hint_text(tr(ctx, Text::AnySheet))
+                            .desired_width(100.0),
+                    )
+                    .changed()
+                {
+                    link.sheet = (!sheet.trim().is_empty()).then_some(sheet);
+                    changed = true;
+                }
+
+                if ui.small_button("🗑").clicked() {
+                    removed = Some(idx);
+                }
+                ui.end_row();
+            }
+        });
+    if let Some(idx) = removed {
+        links.remove(idx);
+        changed = true;
+    }
+
+    ui.horizontal(|ui| {
+        if ui.button(tr(ctx, Text::AddLink)).clicked() {
+            links.push(ExternalLink::new("", "", None));
+            changed = true;
+        }
+        if ui.button(tr(ctx, Text::ResetToDefaults)).clicked() {
+            links = ExternalLink::defaults();
+            changed = true;
+        }
+    });
+    if changed {
+        EXTERNAL_LINKS.set(ctx, links);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ExternalLink;
+
+    #[test]
+    fn templates_take_the_row() {
+        let [xivapi, garland, _] = &ExternalLink::defaults()[..] else {
+            panic!("expected three default links");
+        };
+        assert_eq!(
+            xivapi.url_for(&("Action".to_owned(), (7, None))),
+            "https://v2.xivapi.com/api/sheet/Action/7"
+        );
+        assert!(xivapi.applies_to("Action"));
+        assert!(garland.applies_to("item"));
+        assert!(!garland.applies_to("Action"));
+
+        let subrows = ExternalLink::new("", "{sheet}/{row}:{subrow}", None);
+        assert_eq!(
+            subrows.url_for(&("QuestLinkMarker".to_owned(), (3, Some(2)))),
+            "QuestLinkMarker/3:2"
+        );
+        assert_eq!(subrows.url_for(&("Item".to_owned(), (3, None))), "Item/3:0");
+    }
+}
@@ -0,0 +1,436 @@
+use std::str::FromStr;
+
+use egui::{ComboBox, RichText, TextEdit};
+use itertools::Itertools;
+
+use crate::{
+    i18n::{Text, tr},
+    sheet::ComplexFilter,
+};
+
+/// Comparators of the complex filter grammar (see `sheet/filter/filter.pest`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Equals,
+    StartsWith,
+    EndsWith,
+    Contains,
+    Fuzzy,
+    Wildcard,
+    Regex,
+    Range,
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+    HasBit,
+}
+
+impl Operator {
+    const ALL: &[Self] = &[
+        Self::Equals,
+        Self::StartsWith,
+        Self::EndsWith,
+        Self::Contains,
+        Self::Fuzzy,
+        Self::Wildcard,
+        Self::Regex,
+        Self::Range,
+        Self::Greater,
+        Self::GreaterEq,
+        Self::Less,
+        Self::LessEq,
+        Self::HasBit,
+    ];
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Self::Equals => "=",
+            Self::StartsWith => "^=",
+            Self::EndsWith => "$=",
+            Self::Contains => "*=",
+            Self::Fuzzy => "~=",
+            Self::Wildcard => "?=",
+            Self::Regex => "/=",
+            Self::Range => "|=",
+            Self::Greater => ">",
+            Self::GreaterEq => ">=",
+            Self::Less => "<",
+            Self::LessEq => "<=",
+            Self::HasBit => "&=",
+        }
+    }
+
+    fn name(self, ctx: &egui::Context) -> &'static str {
+        tr(
+            ctx,
+            match self {
+                Self::Equals => Text::OpEquals,
+                Self::StartsWith => Text::OpStartsWith,
+                Self::EndsWith => Text::OpEndsWith,
+                Self::Contains => Text::OpContains,
+                Self::Fuzzy => Text::OpFuzzy,
+                Self::Wildcard => Text::OpWildcard,
+                Self::Regex => Text::OpRegex,
+                Self::Range => Text::OpRange,
+                Self::Greater => Text::OpGreater,
+                Self::GreaterEq => Text::OpGreaterEq,
+                Self::Less => Text::OpLess,
+                Self::LessEq => Text::OpLessEq,
+                Self::HasBit => Text::OpHasBit,
+            },
+        )
+    }
+
+    fn hint(self, ctx: &egui::Context) -> &'static str {
+        match self {
+            Self::Regex => "/pattern/flags",
+            Self::Range => "10..20",
+            Self::Wildcard => "*Sword?",
+            Self::Greater | Self::GreaterEq | Self::Less | Self::LessEq => {
+                tr(ctx, Text::FilterNumber)
+            }
+            Self::HasBit => tr(ctx, Text::FilterBitPosition),
+            _ => tr(ctx, Text::FilterValue),
+        }
+    }
+
+    /// Writes `value` the way the comparator expects it. Numbers, ranges and regexes are
+    /// passed through, everything else is quoted unless it's a plain word.
+    fn format_value(self, value: &str) -> String {
+        let value = value.trim();
+        match self {
+            Self::Range
+            | Self::Greater
+            | Self::GreaterEq
+            | Self::Less
+            | Self::LessEq
+            | Self::HasBit => value.to_owned(),
+            Self::Regex if value.starts_with('/') => value.to_owned(),
+            _ => quote(value),
+        }
+    }
+}
+
+/// Leaves words the grammar reads as bare strings alone, and quotes anything else.
+fn quote(value: &str) -> String {
+    let is_bare = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '/'));
+    if is_bare {
+        value.to_owned()
+    } else {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Join {
+    All,
+    Any,
+}
+
+impl Join {
+    fn keyword(self) -> &'static str {
+        match self {
+            Self::All => " and ",
+            Self::Any => " or ",
+        }
+    }
+
+    fn name(self, ctx: &egui::Context) -> &'static str {
+        match self {
+            Self::All => tr(ctx, Text::JoinAll),
+            Self::Any => tr(ctx, Text::JoinAny),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Condition {
+    /// `#` for the row id, `*` for any column
+    column: String,
+    negate: bool,
+    operator: Operator,
+    value: String,
+}
+
+impl Default for Condition {
+    fn default() -> Self {
+        Self {
+            column: "*".to_owned(),
+            negate: false,
+            operator: Operator::Contains,
+            value: String::new(),
+        }
+    }
+}
+
+impl Condition {
+    fn to_filter_string(&self) -> String {
+        format!(
+            "{} {}{} {}",
+            self.column,
+            if self.negate { "!" } else { "" },
+            self.operator.symbol(),
+            self.operator.format_value(&self.value)
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Group {
+    join: Join,
+    conditions: Vec<Condition>,
+}
+
+impl Default for Group {
+    fn default() -> Self {
+        Self {
+            join: Join::All,
+            conditions: vec![Condition::default()],
+        }
+    }
+}
+
+/// A dialog that puts a complex filter together from conditions picked from dropdowns, for
+/// those who'd rather not learn the filter syntax.
+pub struct FilterBuilder {
+    /// Column names of the sheet being filtered
+    columns: Vec<String>,
+    /// How the groups are combined
+    join: Join,
+    groups: Vec<Group>,
+}
+
+impl FilterBuilder {
+    pub fn new(columns: Vec<String>) -> Self {
+        Self {
+            columns,
+            join: Join::Any,
+            groups: vec![Group::default()],
+        }
+    }
+
+    /// The filter in the complex filter syntax.
+    fn to_filter_string(&self) -> String {
+        let wrap = self.groups.len() > 1;
+        self.groups
+            .iter()
+            .filter(|g| !g.conditions.is_empty())
+            .map(|group| {
+                let text = group
+                    .conditions
+                    .iter()
+                    .map(Condition::to_filter_string)
+                    .join(group.join.keyword());
+                if wrap && group.conditions.len() > 1 {
+                    format!("({text})")
+                } else {
+                    text
+                }
+            })
+            .join(self.join.keyword())
+    }
+
+    /// Returns the filter to apply once the user is done. `open` is cleared when the window is
+    /// closed.
+    pub fn draw(&mut self, ctx: &egui::Context, open: &mut bool) -> Option<String> {
+        let mut ret = None;
+        egui::Window::new(tr(ctx, Text::FilterBuilder))
+            .id(egui::Id::new("filter-builder"))
+            .open(open)
+            .default_width(560.0)
+            .show(ctx, |ui| {
+                let multiple_groups = self.groups.len() > 1;
+                if multiple_groups {
+                    join_picker(
+                        ui,
+                        "filter-builder-join",
+                        &mut self.join,
+                        Text::OfTheseGroups,
+                    );
+                }
+
+                let mut removed_group = None;
+                egui::ScrollArea::vertical()
+                    .max_height(360.0)
+                    .show(ui, |ui| {
+                        for (group_idx, group) in self.groups.iter_mut().enumerate() {
+                            egui::Frame::group(ui.style()).show(ui, |ui| {
+                                ui.set_min_width(ui.available_width());
+                                ui.horizontal(|ui| {
+                                    join_picker(
+                                        ui,
+                                        ("filter-builder-group-join", group_idx),
+                                        &mut group.join,
+                                        Text::OfTheseConditions,
+                                    );
+                                    if multiple_groups
+                                        && ui
+                                            .small_button("🗑")
+                                            .on_hover_text(tr(ctx, Text::RemoveGroup))
+                                            .clicked()
+                                    {
+                                        removed_group = Some(group_idx);
+                                    }
+                                });
+                                draw_conditions(ui, group_idx, group, &self.columns);
+                            });
+                        }
+                    });
+                if let Some(idx) = removed_group {
+                    self.groups.remove(idx);
+                }
+                if ui
+                    .button(format!("➕ {}", tr(ctx, Text::AddGroup)))
+                    .clicked()
+                {
+                    self.groups.push(Group::default());
+                }
+
+                ui.separator();
+                let filter = self.to_filter_string();
+                ui.label(RichText::new(&filter).monospace());
+                let error = ComplexFilter::from_str(&filter).err();
+                if let Some(error) = &error {
+                    ui.colored_label(ui.visuals().error_fg_color, error.to_string());
+                }
+                if ui
+                    .add_enabled(error.is_none(), egui::Button::new(tr(ctx, Text::Apply)))
+                    .clicked()
+                {
+                    ret = Some(filter);
+                }
+            });
+        ret
+    }
+}
+
+fn join_picker(ui: &mut egui::Ui, id: impl std::hash::Hash, join: &mut Join, what: Text) {
+    let ctx = &ui.ctx().clone();
+    ui.horizontal(|ui| {
+        ui.label(tr(ctx, Text::Match));
+        ComboBox::from_id_salt(id)
+            .width(60.0)
+            .selected_text(join.name(ctx))
+            .show_ui(ui, |ui| {
+                for value in [Join::All, Join::Any] {
+                    ui.selectable_value(join, value, value.name(ctx));
+                }
+            });
+        ui.label(tr(ctx, what));
+    });
+}
+
+fn draw_conditions(ui: &mut egui::Ui, group_idx: usize, group: &mut Group, columns: &[String]) {
+    let ctx = &ui.ctx().clone();
+    let mut removed = None;
+    egui::Grid::new(("filter-builder-conditions", group_idx))
+        .num_columns(5)
+        .show(ui, |ui| {
+            for (idx, condition) in group.conditions.iter_mut().enumerate() {
+                ComboBox::from_id_salt(("filter-builder-column", group_idx, idx))
+                    .width(140.0)
+                    .selected_text(column_name(ctx, &condition.column))
+                    .show_ui(ui, |ui| {
+                        for column in ["#", "*"]
+                            .into_iter()
+                            .chain(columns.iter().map(String::as_str))
+                        {
+                            ui.selectable_value(
+                                &mut condition.column,
+                                column.to_owned(),
+                                column_name(ctx, column),
+                            );
+                        }
+                    });
+                ui.checkbox(&mut condition.negate, tr(ctx, Text::Not));
+                ComboBox::from_id_salt(("filter-builder-operator", group_idx, idx))
+                    .width(120.0)
+                    .selected_text(condition.operator.name(ctx))
+                    .show_ui(ui, |ui| {
+                        for &operator in Operator::ALL {
+                            ui.selectable_value(
+                                &mut condition.operator,
+                                operator,
+                                operator.name(ctx),
+                            )
+                            .on_hover_text(operator.symbol());
+                        }
+                    });
+                ui.add(
+                    TextEdit::singleline(&mut condition.value)
+                        .hint_text(condition.operator.hint(ctx))
+                        .desired_width(160.0),
+                );
+                if ui
+                    .small_button("🗑")
+                    .on_hover_text(tr(ctx, Text::RemoveCondition))
+                    .clicked()
+                {
+                    removed = Some(idx);
+                }
+                ui.end_row();
+            }
+        });
+    if let Some(idx) = removed {
+        group.conditions.remove(idx);
+    }
+    if ui
+        .small_button(format!("➕ {}", tr(ctx, Text::AddCondition)))
+        .clicked()
+    {
+        group.conditions.push(Condition::default());
+    }
+}
+
+fn column_name<'a>(ctx: &egui::Context, column: &'a str) -> &'a str {
+    match column {
+        "#" => tr(ctx, Text::RowIdColumn),
+        "*" => tr(ctx, Text::AnyColumn),
+        column => column,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::{Condition, FilterBuilder, Group, Join, Operator};
+    use crate::sheet::ComplexFilter;
+
+    fn condition(column: &str, negate: bool, operator: Operator, value: &str) -> Condition {
+        Condition {
+            column: column.to_owned(),
+            negate,
+            operator,
+            value: value.to_owned(),
+        }
+    }
+
+    #[test]
+    fn builds_parseable_filters() {
+        let mut builder = FilterBuilder::new(Vec::new());
+        builder.groups = vec![
+            Group {
+                join: Join::All,
+                conditions: vec![
+                    condition("Name", false, Operator::StartsWith, "Iron Ore"),
+                    condition("#", true, Operator::Range, "100..200"),
+                ],
+            },
+            Group {
+                join: Join::Any,
+                conditions: vec![condition("*", false, Operator::Regex, r#"say "hi""#)],
+            },
+        ];
+        let filter = builder.to_filter_string();
+        assert_eq!(
+            filter,
+            r#"(Name ^= "Iron Ore" and # !|= 100..200) or * /= "say \"hi\"""#
+        );
+        assert!(ComplexFilter::from_str(&filter).is_ok(), "{filter:?}");
+    }
+}
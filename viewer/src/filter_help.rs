@@ -0,0 +1,143 @@
+use egui::{Grid, RichText};
+
+/// Operators of the complex filter grammar (see `sheet/filter/filter.pest`).
+const COMPARATORS: &[(&str, &str)] = &[
+    ("=", "Equals (string or number)"),
+    ("^=", "Starts with"),
+    ("$=", "Ends with"),
+    ("*=", "Contains"),
+    ("~=", "Fuzzy match"),
+    ("?=", "Wildcard match (* and ?)"),
+    ("/=", "Regex match"),
+    ("|=", "In range (a..b, ..b, a..)"),
+    (">  >=  <  <=", "Numeric comparison"),
+    ("&=", "Has bit set (by position, 0 is the lowest)"),
+];
+
+const LOGIC: &[(&str, &str)] = &[
+    ("!  not", "Negate a filter or a comparator"),
+    ("&&  and", "Both filters must match"),
+    ("||  or", "Either filter must match"),
+    ("( … )", "Group filters"),
+];
+
+const KEYS: &[(&str, &str)] = &[
+    ("#", "The row id"),
+    ("Name", "A column by name"),
+    ("Foo.Bar[0]", "Nested and array columns"),
+    (
+        "*  Icon?",
+        "Wildcards match multiple columns; any match passes",
+    ),
+];
+
+const REGEX_FLAGS: &[(&str, &str)] = &[
+    ("i", "Case insensitive"),
+    ("m", "Multi-line (^ and $ match at line breaks)"),
+    ("s", "Dot matches newlines"),
+    ("U", "Swap greediness"),
+    ("R", "CRLF mode"),
+    ("x", "Ignore whitespace in the pattern"),
+];
+
+const EXAMPLES: &[(&str, &str)] = &[
+    ("# = 42", "Row 42"),
+    ("# |= 100..200", "Rows 100 to 200"),
+    (r#"Name ^= "Iron""#, "Names starting with \"Iron\""),
+    ("* *= Potion", "Any column containing \"Potion\""),
+    (
+        "not * *= Potion",
+        "Rows where no column contains \"Potion\"",
+    ),
+    ("Name /= /^the\\b/i", "Regex with the case-insensitive flag"),
+    ("Level >= 50 and Level < 60", "Combined numeric comparisons"),
+    ("Flags &= 6", "Bit 6 (0x40) of Flags is set"),
+    (
+        r#"(Name ?= "*Sword*" or Name ?= "*Blade*") and not # < 100"#,
+        "Grouping and negation",
+    ),
+    ("Icon? >== 1", "Every column matching Icon? is at least 1"),
+];
+
+fn draw_table(ui: &mut egui::Ui, id: &str, rows: &[(&str, &str)]) {
+    Grid::new(id).striped(true).show(ui, |ui| {
+        for (syntax, description) in rows {
+            ui.label(RichText::new(*syntax).monospace());
+            ui.label(*description);
+            ui.end_row();
+        }
+    });
+}
+
+/// Draws the filter syntax reference. Returns an example query if one was clicked.
+pub fn draw(ctx: &egui::Context, open: &mut bool) -> Option<&'static str> {
+    let mut ret = None;
+    egui::Window::new("Filter Syntax")
+        .open(open)
+        .default_width(460.0)
+        .show(ctx, |ui| {
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                ui.label(
+                    "Complex filters (＊) are written as `key comparator value`, combined with \
+                     logical operators. Values can be bare words, numbers, or quoted strings.",
+                );
+
+                ui.heading("Keys");
+                draw_table(ui, "filter_help_keys", KEYS);
+
+                ui.heading("Comparators");
+                draw_table(ui, "filter_help_comparators", COMPARATORS);
+                ui.label(
+                    "Prefix a comparator with ! or not to negate it, e.g. `Name !^= A`. Add a \
+                     trailing = (strict key, e.g. `*=`→`*==`) to require every matched column \
+                     to pass instead of any.",
+                );
+
+                ui.heading("Logic");
+                draw_table(ui, "filter_help_logic", LOGIC);
+                ui.label("Precedence is not, then and, then or.");
+
+                ui.heading("Regex Flags");
+                ui.label("Written after the closing slash, e.g. `/pattern/im`.");
+                draw_table(ui, "filter_help_regex", REGEX_FLAGS);
+
+                ui.heading("Examples");
+                ui.label("Click an example to use it as the current sheet's filter.");
+                Grid::new("filter_help_examples")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (example, description) in EXAMPLES {
+                            if ui
+                                .link(RichText::new(*example).monospace())
+                                .on_hover_text("Use this filter")
+                                .clicked()
+                            {
+                                ret = Some(*example);
+                            }
+                            ui.label(*description);
+                            ui.end_row();
+                        }
+                    });
+            });
+        });
+    ret
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use crate::sheet::ComplexFilter;
+
+    #[test]
+    fn examples_parse() {
+        for (example, _) in super::EXAMPLES {
+            let filter = ComplexFilter::from_str(example);
+            assert!(
+                filter.is_ok(),
+                "{example:?} failed: {}",
+                filter.unwrap_err()
+            );
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use crate::{settings::SHEET_FILTER_HISTORY, sheet::FilterInputType};
+
+/// Filters kept per sheet, oldest first.
+const MAX_ENTRIES: usize = 50;
+
+pub type FilterEntry = (FilterInputType, String);
+
+/// The sheet's past filters, oldest first.
+pub fn get(ctx: &egui::Context, sheet_name: &str) -> Vec<FilterEntry> {
+    SHEET_FILTER_HISTORY.use_with(ctx, |map| map.get(sheet_name).cloned().unwrap_or_default())
+}
+
+/// Adds a filter to the sheet's history, moving it to the end if it was used before.
+pub fn record(ctx: &egui::Context, sheet_name: &str, entry: FilterEntry) {
+    if entry.1.trim().is_empty() {
+        return;
+    }
+    SHEET_FILTER_HISTORY.use_with(ctx, |map| {
+        let history = map.entry(sheet_name.to_owned()).or_default();
+        if history.last() == Some(&entry) {
+            return;
+        }
+        history.retain(|e| *e != entry);
+        history.push(entry);
+        if history.len() > MAX_ENTRIES {
+            history.drain(..history.len() - MAX_ENTRIES);
+        }
+    });
+}
+
+/// Browses a sheet's filter history with Up and Down, like a shell.
+#[derive(Default)]
+pub struct FilterRecall {
+    /// Index of the shown history entry, `None` while editing a new filter
+    position: Option<usize>,
+    /// What was typed before browsing, shown again after stepping past the newest entry
+    stash: Option<FilterEntry>,
+}
+
+impl FilterRecall {
+    /// Steps to an `older` or newer entry of `history`. Returns the filter to show, if it
+    /// changes. The first step back skips entries that match the `current` filter, which is
+    /// usually the newest one.
+    pub fn step(
+        &mut self,
+        history: &[FilterEntry],
+        current: &FilterEntry,
+        older: bool,
+    ) -> Option<FilterEntry> {
+        let idx = match (self.position, older) {
+            (None, true) => {
+                let idx = history.iter().rposition(|e| e != current)?;
+                self.stash = Some(current.clone());
+                idx
+            }
+            (None, false) => return None,
+            (Some(idx), true) => idx.checked_sub(1)?,
+            (Some(idx), false) if idx + 1 < history.len() => idx + 1,
+            (Some(_), false) => {
+                self.position = None;
+                return self.stash.take();
+            }
+        };
+        let entry = history.get(idx)?.clone();
+        self.position = Some(idx);
+        Some(entry)
+    }
+
+    /// Goes back to editing a new filter, like after typing.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{FilterEntry, FilterRecall};
+    use crate::sheet::FilterInputType;
+
+    fn entry(text: &str) -> FilterEntry {
+        (FilterInputType::default(), text.to_owned())
+    }
+
+    #[test]
+    fn steps_like_a_shell() {
+        let history = [entry("a"), entry("b"), entry("c")];
+        let mut recall = FilterRecall::default();
+
+        assert_eq!(recall.step(&history, &entry("c"), false), None);
+        assert_eq!(recall.step(&history, &entry("c"), true), Some(entry("b")));
+        assert_eq!(recall.step(&history, &entry("b"), true), Some(entry("a")));
+        assert_eq!(recall.step(&history, &entry("a"), true), None);
+        assert_eq!(recall.step(&history, &entry("a"), false), Some(entry("b")));
+        assert_eq!(recall.step(&history, &entry("b"), false), Some(entry("c")));
+        assert_eq!(recall.step(&history, &entry("c"), false), Some(entry("c")));
+        assert_eq!(recall.step(&history, &entry("c"), false), None);
+    }
+}
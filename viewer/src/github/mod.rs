@@ -5,4 +5,6 @@ pub use oauth::{
     CALLBACK_PATH, GithubAuth, RelayResult, build_auth_start, exchange_code, fetch_client_id,
     relay_and_close, take_relayed_result,
 };
+#[cfg(not(target_arch = "wasm32"))]
+pub use oauth::{DeviceCode, poll_device_token, request_device_code};
 pub use pr::{GithubClient, PrDraft, PrResult};
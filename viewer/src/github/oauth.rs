@@ -20,9 +20,39 @@ pub enum RelayResult {
     Error(String),
 }
 
+/// A pending sign-in of the OAuth device flow, which native builds use since they can't
+/// receive the redirect of the web flow.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Debug, serde::Deserialize)]
+pub struct DeviceCode {
+    pub device_code: String,
+    /// Entered by the user at [`Self::verification_uri`]
+    pub user_code: String,
+    pub verification_uri: String,
+    /// Seconds
+    pub expires_in: u64,
+    /// Minimum seconds between polls
+    pub interval: u64,
+}
+
 pub use imp::{
     build_auth_start, exchange_code, fetch_client_id, relay_and_close, take_relayed_result,
 };
+#[cfg(not(target_arch = "wasm32"))]
+pub use imp::{poll_device_token, request_device_code};
+
+fn parse_client_id(config: &[u8]) -> anyhow::Result<String> {
+    let config: serde_json::Value = serde_json::from_slice(config)?;
+    let client_id = config
+        .get("client_id")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string();
+    if client_id.is_empty() {
+        anyhow::bail!("GitHub sign-in is not configured on the server");
+    }
+    Ok(client_id)
+}
 
 #[cfg(target_arch = "wasm32")]
 mod imp {
@@ -76,16 +106,7 @@ mod imp {
 
     pub async fn fetch_client_id() -> Result<String> {
         let config = fetch_url(format!("{}/github/oauth/config/", api_base()?)).await?;
-        let config: serde_json::Value = serde_json::from_slice(&config)?;
-        let client_id = config
-            .get("client_id")
-            .and_then(|v| v.as_str())
-            .unwrap_or_default()
-            .to_string();
-        if client_id.is_empty() {
-            bail!("GitHub sign-in is not configured on the server");
-        }
-        Ok(client_id)
+        super::parse_client_id(&config)
     }
 
     pub fn build_auth_start(client_id: &str) -> Result<AuthStart> {
@@ -201,16 +222,111 @@ mod imp {
 
 #[cfg(not(target_arch = "wasm32"))]
 mod imp {
-    use anyhow::{Result, bail};
+    use std::time::{Duration, Instant};
 
-    use super::{AuthStart, GithubAuth, RelayResult};
+    use anyhow::{Result, anyhow, bail};
+    use serde_json::{Value, json};
 
+    use super::{AuthStart, DeviceCode, GithubAuth, RelayResult};
+    use crate::{
+        github::GithubClient,
+        utils::{fetch_url, request, sleep},
+    };
+
+    const SCOPE: &str = "public_repo";
+    const DEVICE_CODE_URL: &str = "https://github.com/login/device/code";
+    const TOKEN_URL: &str = "https://github.com/login/oauth/access_token";
+    const DEVICE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+    /// Added to the poll interval whenever GitHub asks us to slow down
+    const SLOW_DOWN_SECONDS: u64 = 5;
+
+    /// The OAuth app is shared with the web version, so its id comes from the default server.
     pub async fn fetch_client_id() -> Result<String> {
-        bail!("GitHub sign-in is only available in the web version")
+        let config = fetch_url(format!("{}/github/oauth/config/", crate::DEFAULT_API_URL)).await?;
+        super::parse_client_id(&config)
+    }
+
+    async fn post_json(url: &str, body: Value) -> Result<Value> {
+        let resp = request(
+            "POST",
+            url,
+            &[
+                ("Accept", "application/json"),
+                ("Content-Type", "application/json"),
+                ("User-Agent", "EXDViewer"),
+            ],
+            Some(serde_json::to_vec(&body)?),
+        )
+        .await?;
+        if !resp.ok {
+            bail!("GitHub sign-in failed ({}): {}", resp.status, resp.text());
+        }
+        Ok(serde_json::from_slice(&resp.bytes)?)
+    }
+
+    pub async fn request_device_code(client_id: String) -> Result<DeviceCode> {
+        let json = post_json(
+            DEVICE_CODE_URL,
+            json!({ "client_id": client_id, "scope": SCOPE }),
+        )
+        .await?;
+        if let Some(error) = json.get("error").and_then(|v| v.as_str()) {
+            let desc = json
+                .get("error_description")
+                .and_then(|v| v.as_str())
+                .unwrap_or(error);
+            bail!("Sign-in failed: {desc}");
+        }
+        Ok(serde_json::from_value(json)?)
+    }
+
+    /// Waits for the user to enter the code on GitHub, then returns the resulting token.
+    pub async fn poll_device_token(client_id: String, device: DeviceCode) -> Result<GithubAuth> {
+        let deadline = Instant::now() + Duration::from_secs(device.expires_in);
+        let mut interval = Duration::from_secs(device.interval);
+        loop {
+            sleep(interval).await;
+            if Instant::now() >= deadline {
+                bail!("Sign-in timed out; please try again");
+            }
+
+            let json = post_json(
+                TOKEN_URL,
+                json!({
+                    "client_id": client_id,
+                    "device_code": device.device_code,
+                    "grant_type": DEVICE_GRANT_TYPE,
+                }),
+            )
+            .await?;
+            match json.get("error").and_then(|v| v.as_str()) {
+                None => {}
+                Some("authorization_pending") => continue,
+                Some("slow_down") => {
+                    interval += Duration::from_secs(SLOW_DOWN_SECONDS);
+                    continue;
+                }
+                Some(error) => {
+                    let desc = json
+                        .get("error_description")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or(error);
+                    bail!("Sign-in failed: {desc}");
+                }
+            }
+
+            let token = json
+                .get("access_token")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| anyhow!("no access token in response"))?
+                .to_string();
+            let login = GithubClient::new(token.clone()).current_login().await?;
+            return Ok(GithubAuth { token, login });
+        }
     }
 
     pub fn build_auth_start(_client_id: &str) -> Result<AuthStart> {
-        bail!("GitHub sign-in is only available in the web version")
+        bail!("Native builds sign in with a device code")
     }
 
     pub fn take_relayed_result() -> Option<RelayResult> {
@@ -218,7 +334,7 @@ mod imp {
     }
 
     pub async fn exchange_code(_code: String, _verifier: String) -> Result<GithubAuth> {
-        bail!("GitHub sign-in is only available in the web version")
+        bail!("Native builds sign in with a device code")
     }
 
     pub fn relay_and_close() {}
@@ -0,0 +1,119 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Duration, Instant};
+#[cfg(target_arch = "wasm32")]
+use web_time::{Duration, Instant};
+
+use egui::{Modal, RichText};
+
+use crate::{
+    excel::provider::{ExcelHeader, ExcelSheet},
+    i18n::{Text, tr},
+    sheet::TableContext,
+};
+
+/// Operations over more rows than this ask before they start.
+pub const HUGE_ROW_COUNT: usize = 100_000;
+/// Rows read to estimate how long an operation takes.
+const SAMPLE_ROWS: u32 = 256;
+
+/// An operation over every row of a huge sheet, held back until the user confirms it with an
+/// estimate of how long it'll take in front of them.
+pub struct HugeOperation<T> {
+    /// What the operation does, e.g. "Exporting"
+    what: String,
+    sheet_name: String,
+    row_count: usize,
+    estimate: Duration,
+    operation: T,
+}
+
+impl<T> HugeOperation<T> {
+    /// Hands `operation` straight back when it runs over few enough rows to go ahead.
+    pub fn check(
+        table: &TableContext,
+        row_count: usize,
+        what: impl Into<String>,
+        operation: T,
+    ) -> Result<T, Self> {
+        if row_count <= HUGE_ROW_COUNT {
+            return Ok(operation);
+        }
+        Err(Self {
+            what: what.into(),
+            sheet_name: table.sheet().name().to_owned(),
+            row_count,
+            estimate: estimate(table, row_count),
+            operation,
+        })
+    }
+
+    /// Returns whether to run the operation once the user decided, `None` until then.
+    pub fn draw(&self, ctx: &egui::Context) -> Option<bool> {
+        let mut ret = None;
+        let resp = Modal::new(egui::Id::new("huge-operation")).show(ctx, |ui| {
+            ui.set_max_width(360.0);
+            ui.heading(format!("{} {}?", self.what, self.sheet_name));
+            ui.add_space(4.0);
+            ui.label(
+                tr(ctx, Text::HugeOperationEstimate)
+                    .replace("{rows}", &self.row_count.to_string())
+                    .replace("{estimate}", &format_estimate(ctx, self.estimate)),
+            );
+            ui.label(RichText::new(tr(ctx, Text::HugeOperationHint)).weak());
+            ui.add_space(4.0);
+            ui.horizontal(|ui| {
+                if ui.button(tr(ctx, Text::Continue)).clicked() {
+                    ret = Some(true);
+                }
+                if ui.button(tr(ctx, Text::Cancel)).clicked() {
+                    ret = Some(false);
+                }
+            });
+        });
+        if resp.should_close() {
+            ret.get_or_insert(false);
+        }
+        ret
+    }
+
+    pub fn into_operation(self) -> T {
+        self.operation
+    }
+}
+
+/// Times reading every cell of rows spread evenly over the sheet, scaled up to `row_count`.
+fn estimate(table: &TableContext, row_count: usize) -> Duration {
+    let sheet = table.sheet();
+    let subrow_ids = sheet.get_subrow_ids().collect::<Vec<_>>();
+    let step = (subrow_ids.len() / SAMPLE_ROWS as usize).max(1);
+    let column_count = table.column_count() as u32;
+
+    let start = Instant::now();
+    let mut sampled = 0u32;
+    for &(row_id, subrow_id) in subrow_ids.iter().step_by(step).take(SAMPLE_ROWS as usize) {
+        let Ok(row) = sheet.get_subrow(row_id, subrow_id) else {
+            continue;
+        };
+        for offset_idx in 0..column_count {
+            if let Ok(cell) = table.cell_by_offset(row, offset_idx) {
+                let _ = cell.read(true);
+            }
+        }
+        sampled += 1;
+    }
+    if sampled == 0 {
+        return Duration::ZERO;
+    }
+    start
+        .elapsed()
+        .mul_f64(row_count as f64 / f64::from(sampled))
+}
+
+fn format_estimate(ctx: &egui::Context, estimate: Duration) -> String {
+    let secs = estimate.as_secs();
+    match secs {
+        0 => tr(ctx, Text::UnderASecond).to_owned(),
+        1..60 => tr(ctx, Text::AboutSeconds).replace("{n}", &secs.to_string()),
+        _ => tr(ctx, Text::AboutMinutes).replace("{n}", &secs.div_ceil(60).to_string()),
+    }
+}
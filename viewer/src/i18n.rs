@@ -0,0 +1,344 @@
+use serde::{Deserialize, Serialize};
+
+use crate::settings::UI_LANGUAGE;
+
+/// Language of the viewer's own interface. Independent of the game data [`LANGUAGE`](crate::settings::LANGUAGE).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum UiLanguage {
+    English,
+    Japanese,
+}
+
+impl UiLanguage {
+    pub const ALL: &[Self] = &[Self::English, Self::Japanese];
+
+    /// The language's name in itself, so it can be found regardless of the current language.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::English => "English",
+            Self::Japanese => "日本語",
+        }
+    }
+}
+
+macro_rules! ui_strings {
+    ($($key:ident => $en:literal, $ja:literal;)*) => {
+        /// A translatable UI string. Look it up with [`tr`].
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum Text {
+            $($key,)*
+        }
+
+        impl Text {
+            pub fn get(self, language: UiLanguage) -> &'static str {
+                match language {
+                    UiLanguage::English => match self {
+                        $(Self::$key => $en,)*
+                    },
+                    UiLanguage::Japanese => match self {
+                        $(Self::$key => $ja,)*
+                    },
+                }
+            }
+        }
+    };
+}
+
+ui_strings! {
+    MenuApp => "App", "アプリ";
+    Configure => "Configure", "設定";
+    Profiles => "Profiles", "プロファイル";
+    ProfilesHover => "Switch to a location and schema saved in setup", "セットアップで保存した場所とスキーマに切り替える";
+    ProfileLabel => "Profile:", "プロファイル:";
+    SelectProfile => "Select...", "選択...";
+    ProfileName => "Name", "名前";
+    SaveProfileHover => "Save the location and schema below under this name", "下の場所とスキーマをこの名前で保存";
+    Save => "Save", "保存";
+    Delete => "Delete", "削除";
+    StringOverlay => "String Overlay", "文字列オーバーレイ";
+    StringOverlayHint => "Read strings from another install where it has them", "別のインストールにある文字列を読み込む";
+    LocalInstall => "Local", "ローカル";
+    WebInstall => "Web", "Web";
+    PathLabel => "Path:", "パス:";
+    Browse => "Browse", "参照";
+    PatchFile => "Patch File", "パッチファイル";
+    LocalInstallHint => "The game's sqpack folder, or a folder of loose exd files extracted from it", "ゲームのsqpackフォルダー、またはそこから抽出したexdファイルのフォルダー";
+    LocalInstallPatchHint => "The game's sqpack folder, a folder of loose exd files extracted from it, or a patch file to see the sheets it changes without installing it", "ゲームのsqpackフォルダー、そこから抽出したexdファイルのフォルダー、またはインストールせずに変更されるシートを確認するためのパッチファイル";
+    UrlLabel => "URL:", "URL:";
+    RegionLabel => "Region:", "地域:";
+    LanguageLabel => "Language:", "言語:";
+    Quit => "Quit", "終了";
+    CompareVersions => "Compare Versions…", "バージョン比較…";
+    DownloadSchemas => "Download All Schemas…", "すべてのスキーマをダウンロード…";
+    DownloadSchemasHover => "Save a copy of the GitHub schemas for offline use", "オフラインで使えるようにGitHubのスキーマを保存";
+    ValidateSchemas => "Validate All Schemas…", "すべてのスキーマを検証…";
+    CheckIntegrity => "Check Install Integrity…", "インストールの整合性を確認…";
+    CheckIntegrityHover => "Check that every sheet in the sheet list has readable files", "シート一覧のすべてのシートのファイルが読み込めるか確認";
+    SheetGraph => "Sheet Graph…", "シート関係図…";
+    SheetGraphHover => "See how sheets link to each other around the open sheet", "開いているシートを中心に、シート間のリンクを表示";
+    ValidateSchemasHover => "Check every schema against the loaded game data", "読み込んだゲームデータとすべてのスキーマを照合";
+    SchemaOutbox => "Schema Outbox", "スキーマ送信トレイ";
+    SchemaOutboxHover => "Schemas written from blank stubs, waiting to be contributed", "空のスタブから作成し、提供待ちのスキーマ";
+    SchemaOutboxEmpty => "Edit a sheet's blank schema to queue it here", "シートの空のスキーマを編集するとここに追加されます";
+    RemoveFromOutbox => "Remove from Outbox", "送信トレイから削除";
+    OutboxDraft => "Draft", "下書き";
+    OutboxReady => "Ready", "準備完了";
+    OutboxSubmitted => "Submitted", "送信済み";
+    CreatePr => "Create PR ({n} ready)", "PRを作成（{n}件準備完了）";
+    CreatePrDisabledHover => "Needs a GitHub schema source and a ready schema", "GitHubのスキーマソースと準備完了のスキーマが必要です";
+    ClearSubmitted => "Clear Submitted", "送信済みをクリア";
+    ListingSchemas => "Listing schemas…", "スキーマを一覧表示中…";
+    SchemasDownloaded => "Schemas downloaded:", "ダウンロードしたスキーマ:";
+    SwitchToDownloadedSchemas => "Switch the schema source to the downloaded copy?", "スキーマのソースをダウンロードしたコピーに切り替えますか？";
+    Switch => "Switch", "切り替え";
+    KeepCurrent => "Keep Current", "現在のまま";
+    StopComparing => "Stop Comparing", "比較を終了";
+    MenuGo => "Go", "移動";
+    GoToRow => "Go to Row…", "行へ移動…";
+    GoToSheet => "Go to Sheet…", "シートへ移動…";
+    CommandPalette => "Command Palette…", "コマンドパレット…";
+    TypeACommand => "Type a command", "コマンドを入力";
+    ShowRawValues => "Show Raw Values", "生の値を表示";
+    About => "About", "情報";
+    CopyViewLink => "Copy Link to This View", "この表示へのリンクをコピー";
+    Back => "Back", "戻る";
+    Forward => "Forward", "進む";
+    RecentSheets => "Recent", "最近のシート";
+    NoRecentSheets => "No recent sheets", "最近のシートはありません";
+    Music => "Music", "音楽";
+    MenuLanguage => "Language", "データ言語";
+    MenuView => "View", "表示";
+    UiLanguageMenu => "UI Language", "表示言語";
+    ColorTheme => "Color Theme", "カラーテーマ";
+    CodeTheme => "Code Theme", "コードテーマ";
+    SortColumnsBy => "Sort Columns by", "列の並び順";
+    Offset => "Offset", "オフセット";
+    Index => "Index", "インデックス";
+    TextWrapping => "Text Wrapping", "テキストの折り返し";
+    TableFont => "Table Font", "テーブルのフォント";
+    MonospaceNumbers => "Monospace Numbers", "数値を等幅で表示";
+    UseDisplayFields => "Use Display Fields", "表示フィールドを使用";
+    DisplayFieldFallback => "Display Field Fallback", "表示フィールドの代替";
+    FallbackOff => "Off", "オフ";
+    FallbackString => "String Column", "文字列の列";
+    FallbackStringOrName => "String or Name Column", "文字列または名前の列";
+    DisplayField => "Display Field", "表示フィールド";
+    NoDisplayField => "None", "なし";
+    Fields => "Fields", "フィールド";
+    AddField => "Add Field", "フィールドを追加";
+    AddFieldBelow => "Add Field Below", "下にフィールドを追加";
+    RemoveField => "Remove Field", "フィールドを削除";
+    MoveUp => "Move Up", "上に移動";
+    MoveDown => "Move Down", "下に移動";
+    UnnamedField => "Unnamed", "名前なし";
+    TargetSheets => "Target Sheets", "リンク先のシート";
+    ShowLogWindow => "Show Log Window", "ログウィンドウを表示";
+    ShowOperationLog => "Show Operation Log", "操作ログを表示";
+    OperationLog => "Operation Log", "操作ログ";
+    MenuHelp => "Help", "ヘルプ";
+    FilterSyntax => "Filter Syntax…", "フィルター構文…";
+    Export => "Export", "エクスポート";
+    ExportHover => "Export the currently shown rows", "表示中の行をエクスポート";
+    SubrowsCsvJson => "Subrows (CSV/JSON)", "サブ行 (CSV/JSON)";
+    SubrowRecords => "Separate Records", "個別のレコード";
+    SubrowRecordsHover => "One record per subrow, with a #subrow index column", "サブ行ごとに1レコード、#subrowインデックス列付き";
+    SubrowArrays => "Arrays per Row", "行ごとの配列";
+    SubrowArraysHover => "One record per row, with its subrows' values collected into arrays", "行ごとに1レコード、サブ行の値を配列にまとめる";
+    SelectedCellsHover => "Copy or act on the selected cells", "選択中のセルをコピー・操作";
+    EditSchema => "Edit Schema", "スキーマを編集";
+    EditSchemaHover => "Edit the schema for this sheet", "このシートのスキーマを編集";
+    Filter => "Filter", "フィルター";
+    FilterSyntaxHover => "Filter syntax reference", "フィルター構文リファレンス";
+    FilterBuilder => "Filter Builder", "フィルタービルダー";
+    FilterBuilderHover => "Put a complex filter together from conditions", "条件を組み合わせて複合フィルターを作成";
+    Filtering => "Filtering…", "フィルター中…";
+    OpEquals => "equals", "と等しい";
+    OpStartsWith => "starts with", "で始まる";
+    OpEndsWith => "ends with", "で終わる";
+    OpContains => "contains", "を含む";
+    OpFuzzy => "fuzzy matches", "にあいまい一致";
+    OpWildcard => "matches wildcard", "にワイルドカードで一致";
+    OpRegex => "matches regex", "に正規表現で一致";
+    OpRange => "is in range", "が範囲内";
+    OpGreater => "is greater than", "より大きい";
+    OpGreaterEq => "is at least", "以上";
+    OpLess => "is less than", "より小さい";
+    OpLessEq => "is at most", "以下";
+    OpHasBit => "has bit set", "のビットが立っている";
+    FilterNumber => "Number", "数値";
+    FilterBitPosition => "Bit position", "ビット位置";
+    BitN => "Bit {n}", "ビット{n}";
+    FilterValue => "Value", "値";
+    Match => "Match", "一致:";
+    JoinAll => "all", "すべて";
+    JoinAny => "any", "いずれか";
+    OfTheseGroups => "of these groups", "のグループ";
+    OfTheseConditions => "of these conditions", "の条件";
+    AddGroup => "Group", "グループ";
+    RemoveGroup => "Remove group", "グループを削除";
+    AddCondition => "Condition", "条件";
+    RemoveCondition => "Remove condition", "条件を削除";
+    Not => "Not", "否定";
+    RowIdColumn => "Row Id", "行ID";
+    AnyColumn => "Any Column", "すべての列";
+    Apply => "Apply", "適用";
+    CaseInsensitive => "Case Insensitive", "大文字と小文字を区別しない";
+    UseDisplayField => "Use Display Field", "表示フィールドを使用";
+    LoadingSheets => "Loading sheets…", "シートを読み込み中…";
+    LoadingSheetList => "Loading sheet list…", "シート一覧を読み込み中…";
+    SheetListFailed => "Failed to load sheet list", "シート一覧の読み込みに失敗しました";
+    LoadingSheetAndSchema => "Loading sheet and schema...", "シートとスキーマを読み込み中...";
+    LoadingSchema => "Loading schema...", "スキーマを読み込み中...";
+    LoadingSheet => "Loading sheet...", "シートを読み込み中...";
+    Preparing => "Preparing sheet and schema...", "シートとスキーマを準備中...";
+    SheetAndSchemaFailed => "Failed to load sheet and schema", "シートとスキーマの読み込みに失敗しました";
+    SheetFailed => "Failed to load sheet", "シートの読み込みに失敗しました";
+    SchemaFailed => "Failed to load schema", "スキーマの読み込みに失敗しました";
+    SomethingWentWrong => "⚠ Something went wrong", "⚠ エラーが発生しました";
+    Retry => "Retry", "再試行";
+    CopyError => "Copy Error", "エラーをコピー";
+    Failed => "Failed", "失敗";
+    FirstAppeared => "First Appeared", "初出";
+    FirstAppearedHover => "Show the version each row first appeared in", "各行が初めて登場したバージョンを表示";
+    SearchingVersions => "Searching versions…", "バージョンを検索中…";
+    Bookmarks => "Bookmarks", "ブックマーク";
+    BookmarkSheet => "Bookmark This Sheet", "このシートをブックマーク";
+    RemoveSheetBookmark => "Remove Sheet Bookmark", "シートのブックマークを削除";
+    ManageBookmarks => "Manage Bookmarks…", "ブックマークを管理…";
+    BookmarkRow => "Bookmark This Row", "この行をブックマーク";
+    RemoveBookmark => "Remove Bookmark", "ブックマークを削除";
+    NoBookmarks => "No bookmarks", "ブックマークはありません";
+    Label => "Label", "ラベル";
+    ColumnDocs => "Column Docs", "列のドキュメント";
+    ShowPageBoundaries => "Show Page Boundaries", "ページ境界を表示";
+    TintColumnGroups => "Tint Column Groups", "列グループを色分け";
+    GroupColor => "Group Color", "グループの色";
+    ResetGroupColorHover => "Reset to the automatic color", "自動の色に戻す";
+    FitColumn => "Fit Column to Content", "列幅を内容に合わせる";
+    FitAllColumns => "Fit All Columns", "すべての列幅を合わせる";
+    AutoDisplayField => "Display Field (auto-detected). Click to save it to the schema.", "表示フィールド（自動検出）。クリックでスキーマに保存します。";
+    ShowColumnDocs => "Show Column Docs", "列のドキュメントを表示";
+    UndocumentedColumns => "columns without comments", "件のコメントのない列";
+    ShowRawValuesHover => "Show raw values, ignoring the schema's icons, links, colors, and display fields", "スキーマのアイコン・リンク・色・表示フィールドを無視して生の値を表示";
+    CompareRowJson => "Compare Row With JSON", "行をJSONと比較";
+    CompareRowJsonHover => "Check a pasted JSON object against the selected row", "貼り付けたJSONオブジェクトを選択中の行と照合";
+    Scripts => "Scripts", "スクリプト";
+    ScriptsHover => "Run a Rhai script over the shown rows", "表示中の行に対してRhaiスクリプトを実行";
+    ScriptsHint => "Run the script to see what it prints and returns.", "スクリプトを実行すると、出力と戻り値が表示されます。";
+    Script => "Script", "スクリプト";
+    ScriptColumnHint => "Derived by the script", "スクリプトで算出";
+    ScriptColumnValues => "values in the script column", "件の値がスクリプト列にあります";
+    StopScript => "Stop", "停止";
+    Run => "Run", "実行";
+    ClearColumn => "Clear Column", "列を消去";
+    PasteJsonHint => "Paste a JSON object here", "ここにJSONオブジェクトを貼り付け";
+    OnlyMismatches => "Only Show Mismatches", "不一致のみ表示";
+    JsonNotAnObject => "Expected a JSON object or an array of objects", "JSONオブジェクトまたはオブジェクトの配列が必要です";
+    SelectRowToCompare => "Click a row number to pick the row to compare", "比較する行の行番号をクリックしてください";
+    MissingKeys => "missing", "件欠落";
+    UnknownKeys => "Unknown Keys", "不明なキー";
+    Column => "Column", "列";
+    Pasted => "Pasted", "貼り付け";
+    Sheet => "Sheet", "シート";
+    ControlServer => "Control Server", "コントロールサーバー";
+    ControlServerHover => "Serve a local HTTP API on 127.0.0.1 so scripts can open sheets, set filters, and export the current view", "スクリプトからシートを開いたり、フィルターを設定したり、表示中のデータをエクスポートしたりできるローカルHTTP APIを127.0.0.1で提供";
+    Enabled => "Enabled", "有効";
+    Port => "Port", "ポート";
+    HelperColumnHover => "Show a helper column with identifiers derived from each row", "各行から算出した識別子の補助列を表示";
+    NoHelperColumn => "None", "なし";
+    IconTextures => "Icon Textures", "アイコンテクスチャ";
+    AutoDownscale => "Automatic Downscaling", "自動縮小";
+    AutoDownscaleHover => "Shrink icons that are too large for the GPU, and high resolution icons on low-memory devices", "GPUに収まらないアイコンと、メモリの少ない端末での高解像度アイコンを縮小";
+    IconLoadFailed => "Failed to load icon", "アイコンの読み込みに失敗しました";
+    LayoutMode => "Layout", "レイアウト";
+    Sheets => "Sheets", "シート一覧";
+    More => "More", "その他";
+    UsageStatistics => "Usage Statistics", "利用統計";
+    ExternalLinks => "External Links", "外部リンク";
+    ExternalLinksHint => "Sites offered in row context menus. {sheet}, {row} and {subrow} in URLs are replaced with the row's.", "行のコンテキストメニューに表示されるサイトです。URL内の{sheet}、{row}、{subrow}は行の値に置き換えられます。";
+    OpenExternally => "Open in", "外部サイトで開く";
+    AnySheet => "Any sheet", "すべてのシート";
+    AddLink => "Add Link", "リンクを追加";
+    ResetToDefaults => "Reset to Defaults", "デフォルトに戻す";
+    TelemetryDisclosure => "When enabled, anonymous counts of which features are used and how long the interface takes to draw are sent to the endpoint below a minute after starting, then every 15 minutes. Sheet names, row data, filters, file paths and identifiers are never included.", "有効にすると、使用した機能の回数と画面の描画時間の匿名の集計が起動の1分後、その後は15分ごとに下記のエンドポイントへ送信されます。シート名、行データ、フィルター、ファイルパス、識別子は一切含まれません。";
+    Endpoint => "Endpoint", "エンドポイント";
+    PendingReport => "Pending Report", "送信待ちのレポート";
+    ExportDiff => "Export Diff", "差分をエクスポート";
+    ExportIcons => "Export Icons", "アイコンをエクスポート";
+    IconsPng => "Icons (PNG)…", "アイコン (PNG)…";
+    ExportIconsHover => "Save every icon of the shown rows' icon columns", "表示中の行のアイコン列にあるすべてのアイコンを保存";
+    ExportIconsTo => "Export Icons To", "アイコンのエクスポート先";
+    SavedIcons => "Saved {n} icons", "{n}個のアイコンを保存しました";
+    IconsFailedToLoad => "{n} icons failed to load, see the log", "{n}個のアイコンを読み込めませんでした。ログを確認してください";
+    NoSchemaIssues => "No issues found.", "問題は見つかりませんでした。";
+    NoIntegrityIssues => "Every sheet's files are present and readable.", "すべてのシートのファイルが揃っていて読み込めます。";
+    ValueDistribution => "Value Distribution", "値の分布";
+    SheetHasNoRows => "The sheet has no rows.", "このシートには行がありません。";
+    HistogramValues => "Values", "値の数";
+    HistogramDistinct => "Distinct", "種類";
+    HistogramMin => "Min", "最小";
+    HistogramMax => "Max", "最大";
+    LogarithmicScale => "Logarithmic Scale", "対数スケール";
+    MostCommon => "Most Common", "最頻値";
+    NumberFormat => "Number Format", "数値の表示形式";
+    InterpretAs => "Interpret as", "解釈";
+    RawVsSchema => "Raw vs. Schema", "生データとスキーマの比較";
+    InspectorRaw => "Raw", "生データ";
+    InspectorSchema => "Schema", "スキーマ";
+    SeStringInspector => "SeString Inspector", "SeStringインスペクター";
+    Formatted => "Formatted", "整形済み";
+    Inspect => "Inspect…", "検査…";
+    PopOut => "Pop Out", "別ウィンドウで開く";
+    SaveAsPng => "Save as PNG…", "PNGとして保存…";
+    SaveIconAsPng => "Save Icon as PNG…", "アイコンをPNGとして保存…";
+    PopOutIconHover => "Open this icon in a separate window", "このアイコンを別のウィンドウで開く";
+    PopOutRowHover => "Open this row in a separate window", "この行を別のウィンドウで開く";
+    NewGameVersion => "New Game Version", "新しいゲームバージョン";
+    SwitchToNewVersion => "Switch to New Version", "新しいバージョンに切り替え";
+    GameFilesChanged => "Game Files Changed", "ゲームファイルが変更されました";
+    ReloadGameData => "Reload Game Data", "ゲームデータを再読み込み";
+    ServerUnreachable => "Server unreachable:", "サーバーに接続できません:";
+    RetryNow => "Retry Now", "今すぐ再試行";
+    FindReferences => "Find References", "参照を検索";
+    NotFoundHint => "It doesn't exist in this game version or folder.", "このゲームバージョンまたはフォルダーには存在しません。";
+    NotFound => "Not Found", "見つかりません";
+    PageNotFound => "The requested page was not found.", "要求されたページが見つかりませんでした。";
+    CheckTheUrl => "Please check the URL and try again.", "URLを確認して、もう一度お試しください。";
+    PermissionDeniedHint => "Access to the folder was lost. Re-grant it to keep browsing.", "フォルダーへのアクセス許可が失われました。再度許可してください。";
+    NetworkHint => "Check your connection and retry.", "接続を確認して再試行してください。";
+    CorruptHint => "The data couldn't be read. It may be damaged or from an unsupported version.", "データを読み込めませんでした。破損しているか、未対応のバージョンの可能性があります。";
+    VersionMismatchHint => "It isn't available for the selected game version. Pick another version.", "選択したゲームバージョンでは利用できません。別のバージョンを選択してください。";
+    ReturnToSetup => "Return to Setup", "セットアップに戻る";
+    FailedToLoadGameData => "Failed to load game data", "ゲームデータを読み込めませんでした";
+    WebRequests => "Web Requests", "Webリクエスト";
+    WebRequestsHover => "Limit how many files and schemas are fetched from web sources at once. Lower it if the server rate-limits you.", "Webソースから同時に取得するファイルとスキーマの数を制限します。サーバーにレート制限される場合は下げてください。";
+    Caches => "Caches", "キャッシュ";
+    CachesHover => "How many opened sheets and schemas stay loaded. Lower it to use less memory.", "読み込んだままにするシートとスキーマの数。メモリ使用量を減らすには下げてください。";
+    ClearCaches => "Clear Caches", "キャッシュをクリア";
+    DownloadedDataHover => "How much of the sheet data downloaded from web servers is kept on disk. Versions used longest ago are removed first.", "ウェブサーバーからダウンロードしたシートデータをディスクに保存する量。最も長く使われていないバージョンから削除されます。";
+    ClearDownloadedData => "Clear Downloaded Data", "ダウンロードしたデータをクリア";
+    ShowMemoryUsage => "Show Memory Usage", "メモリ使用量を表示";
+    MemoryUsage => "Memory Usage", "メモリ使用量";
+    ResumeAtRow => "Resume at row", "前回の行に戻る:";
+    Dismiss => "Dismiss", "閉じる";
+    CancelLoad => "Loading, click to cancel", "読み込み中（クリックでキャンセル）";
+    HugeOperationEstimate => "This goes through {rows} rows, which takes {estimate} going by a sample of them.", "{rows}行を処理します。一部の行から見積もると{estimate}かかります。";
+    HugeOperationHint => "Linked sheets and icons loaded along the way can take longer.", "途中で読み込むリンク先のシートやアイコンにより、さらに時間がかかる場合があります。";
+    UnderASecond => "under a second", "1秒未満";
+    AboutSeconds => "about {n} seconds", "約{n}秒";
+    AboutMinutes => "about {n} minutes", "約{n}分";
+    Continue => "Continue", "続行";
+    Cancel => "Cancel", "キャンセル";
+    SavedRows => "Saved {n} rows", "{n}行を保存しました";
+    Rows => "rows", "行";
+    PausedAfterFailure => "Paused after a failure", "失敗したため一時停止しました";
+    Pause => "Pause", "一時停止";
+    Resume => "Resume", "再開";
+    OpenInNewTab => "Open in New Tab", "新しいタブで開く";
+    CloseTab => "Close Tab", "タブを閉じる";
+}
+
+/// Looks up `text` in the current [`UI_LANGUAGE`].
+pub fn tr(ctx: &egui::Context, text: Text) -> &'static str {
+    text.get(UI_LANGUAGE.get(ctx))
+}
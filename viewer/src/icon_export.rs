@@ -0,0 +1,234 @@
+use std::{cell::Cell, collections::HashMap, rc::Rc};
+
+use egui::{ProgressBar, RichText};
+use futures_util::StreamExt;
+use itertools::Itertools;
+
+use crate::{
+    excel::provider::{ExcelHeader, ExcelSheet},
+    export,
+    i18n::{Text, tr},
+    sheet::{CellValue, SchemaColumnMeta, TableContext},
+    utils::{PromiseKind, TrackedPromise},
+};
+
+const CONCURRENT_ICONS: usize = 8;
+
+/// (saved, failed)
+type ExportPromise = TrackedPromise<anyhow::Result<Option<(usize, usize)>>>;
+
+/// Saves every icon referenced by the icon columns of a sheet's shown rows as PNGs, named
+/// after the row and column they're in. Native saves into a folder, web downloads a ZIP.
+pub struct IconExportWindow {
+    sheet_name: String,
+    /// (loaded, total) icons
+    progress: Rc<Cell<(usize, usize)>>,
+    promise: Option<ExportPromise>,
+    result: Option<Result<(usize, usize), String>>,
+}
+
+impl IconExportWindow {
+    pub fn new(
+        ctx: &egui::Context,
+        table: TableContext,
+        rows: Vec<(u32, Option<u16>)>,
+        hires: bool,
+    ) -> Self {
+        let progress = Rc::new(Cell::new((0, 0)));
+        let sheet_name = table.sheet().name().to_owned();
+        // Native picks a folder, web saves a ZIP
+        let dialog_title = tr(
+            ctx,
+            if cfg!(target_arch = "wasm32") {
+                Text::ExportIcons
+            } else {
+                Text::ExportIconsTo
+            },
+        );
+        let promise = TrackedPromise::spawn_local(export_icons(
+            table,
+            rows,
+            hires,
+            dialog_title,
+            progress.clone(),
+        ));
+        Self {
+            sheet_name,
+            progress,
+            promise: Some(promise),
+            result: None,
+        }
+    }
+
+    /// `open` is cleared when the window is closed or the folder picker was cancelled.
+    pub fn draw(&mut self, ctx: &egui::Context, open: &mut bool) {
+        if let Some(promise) = self.promise.take() {
+            match promise.try_take() {
+                Ok(Ok(Some(result))) => self.result = Some(Ok(result)),
+                Ok(Ok(None)) => {
+                    *open = false;
+                    return;
+                }
+                Ok(Err(e)) => {
+                    log::error!("Failed to export icons: {e:?}");
+                    self.result = Some(Err(e.to_string()));
+                }
+                Err(promise) => self.promise = Some(promise),
+            }
+        }
+
+        egui::Window::new(tr(ctx, Text::ExportIcons))
+            .id(egui::Id::new("icon-export-window"))
+            .open(open)
+            .collapsible(false)
+            .resizable(false)
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                ui.label(RichText::new(&self.sheet_name).monospace());
+                ui.add_space(4.0);
+
+                match &self.result {
+                    None => {
+                        let (done, total) = self.progress.get();
+                        if total == 0 {
+                            ui.spinner();
+                        } else {
+                            ui.add(
+                                ProgressBar::new(done as f32 / total as f32)
+                                    .text(format!("{done} / {total}")),
+                            );
+                        }
+                    }
+                    Some(Err(e)) => {
+                        ui.colored_label(ui.visuals().error_fg_color, e);
+                    }
+                    Some(Ok((saved, failed))) => {
+                        ui.label(tr(ctx, Text::SavedIcons).replace("{n}", &saved.to_string()));
+                        if *failed != 0 {
+                            ui.colored_label(
+                                ui.visuals().warn_fg_color,
+                                tr(ctx, Text::IconsFailedToLoad)
+                                    .replace("{n}", &failed.to_string()),
+                            );
+                        }
+                    }
+                }
+            });
+    }
+}
+
+/// (file name, icon id) of every icon in the icon columns of the given rows.
+fn collect_icons(
+    table: &TableContext,
+    rows: &[(u32, Option<u16>)],
+) -> anyhow::Result<Vec<(String, u32)>> {
+    let columns: Vec<_> = (0..table.column_count() as u32)
+        .map(|i| anyhow::Ok((i, table.get_column_by_offset(i)?.0)))
+        .filter_ok(|(_, column)| matches!(column.meta(), SchemaColumnMeta::Icon))
+        .try_collect()?;
+
+    let mut icons = Vec::new();
+    if columns.is_empty() {
+        return Ok(icons);
+    }
+    for &(row_id, subrow_id) in rows {
+        let row = table
+            .sheet()
+            .get_subrow(row_id, subrow_id.unwrap_or_default())?;
+        for (offset_idx, column) in &columns {
+            if let CellValue::Icon(icon_id) = table.cell_by_offset(row, *offset_idx)?.read(false)?
+                && let Ok(icon_id) = u32::try_from(icon_id)
+                && icon_id != 0
+            {
+                let column_name = column.name().replace(['/', '\\', ':'], "_");
+                icons.push((
+                    format!(
+                        "{}_{column_name}.png",
+                        export::format_row_id(row_id, subrow_id)
+                    ),
+                    icon_id,
+                ));
+            }
+        }
+    }
+    Ok(icons)
+}
+
+async fn export_icons(
+    table: TableContext,
+    rows: Vec<(u32, Option<u16>)>,
+    hires: bool,
+    dialog_title: &'static str,
+    progress: Rc<Cell<(usize, usize)>>,
+) -> anyhow::Result<Option<(usize, usize)>> {
+    #[cfg(not(target_arch = "wasm32"))]
+    let Some(folder) = rfd::AsyncFileDialog::new()
+        .set_title(dialog_title)
+        .pick_folder()
+        .await
+    else {
+        return Ok(None);
+    };
+
+    let icons = collect_icons(&table, &rows)?;
+    let icon_ids = icons.iter().map(|(_, id)| *id).unique().collect_vec();
+    let total = icon_ids.len();
+    progress.set((0, total));
+
+    // Icons used by several rows are only loaded once
+    let excel = table.global().backend().excel().clone();
+    let pngs: HashMap<u32, Vec<u8>> = futures_util::stream::iter(icon_ids)
+        .map(|icon_id| {
+            let excel = excel.clone();
+            async move { (icon_id, export::icon_png(&excel, icon_id, hires).await) }
+        })
+        .buffer_unordered(CONCURRENT_ICONS)
+        .inspect(|_| progress.set((progress.get().0 + 1, total)))
+        .filter_map(|(icon_id, png)| async move {
+            png.inspect_err(|e| log::warn!("Failed to load icon {icon_id} for export: {e}"))
+                .ok()
+                .map(|png| (icon_id, png))
+        })
+        .collect()
+        .await;
+    let failed = total - pngs.len();
+    let files = icons
+        .into_iter()
+        .filter_map(|(name, icon_id)| Some((name, pngs.get(&icon_id)?)))
+        .collect_vec();
+    let saved = files.len();
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let folder = folder.path();
+        for (name, png) in files {
+            std::fs::write(folder.join(name), png)?;
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        use std::io::Write;
+        use zip::{ZipWriter, write::SimpleFileOptions};
+
+        let mut archive = ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for (name, png) in files {
+            archive.start_file(name, SimpleFileOptions::default())?;
+            archive.write_all(png)?;
+        }
+        let archive = archive.finish()?.into_inner();
+
+        let Some(file) = rfd::AsyncFileDialog::new()
+            .set_title(dialog_title)
+            .set_file_name(format!("{}.icons.zip", table.sheet().name()))
+            .save_file()
+            .await
+        else {
+            return Ok(None);
+        };
+        file.write(&archive).await?;
+    }
+
+    log::info!("Exported {saved} icons of {}", table.sheet().name());
+    Ok(Some((saved, failed)))
+}
@@ -0,0 +1,196 @@
+use std::{cell::Cell, rc::Rc};
+
+use egui::{ProgressBar, RichText};
+use egui_extras::{Column, TableBuilder};
+use futures_util::StreamExt;
+use ironworks::{excel::path, file::exd::ExcelData};
+use itertools::Itertools;
+
+use crate::{
+    backend::Backend,
+    data::FileProviderExt,
+    excel::provider::{EntriesStatus, ExcelHeader, ExcelProvider},
+    i18n::{Text, tr},
+    utils::{ProviderError, TrackedPromise, yield_to_ui},
+};
+
+const CONCURRENT_CHECKS: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum IssueKind {
+    /// The sheet is in the EXL, but its EXH or one of its EXD pages isn't in the install.
+    Missing,
+    /// The file is there, but couldn't be read or parsed.
+    Unreadable,
+}
+
+impl IssueKind {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Missing => "Missing",
+            Self::Unreadable => "Unreadable",
+        }
+    }
+
+    fn of(error: &ProviderError) -> Self {
+        match error {
+            ProviderError::NotFound(_) => Self::Missing,
+            _ => Self::Unreadable,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Issue {
+    sheet: String,
+    kind: IssueKind,
+    /// The file and what went wrong reading it
+    message: String,
+}
+
+/// Checks that every sheet in the EXL has its header and every page in every language it
+/// declares, and that they can all be read. Broken or partially downloaded installs otherwise
+/// only show up as load errors here and there.
+pub struct IntegrityCheckWindow {
+    /// (checked, total)
+    progress: Rc<Cell<(usize, usize)>>,
+    promise: Option<TrackedPromise<Vec<Issue>>>,
+    issues: Option<Vec<Issue>>,
+}
+
+impl IntegrityCheckWindow {
+    pub fn new(backend: Backend) -> Self {
+        let progress = Rc::new(Cell::new((0, 0)));
+        let promise = TrackedPromise::spawn_local(check(backend, progress.clone()));
+        Self {
+            progress,
+            promise: Some(promise),
+            issues: None,
+        }
+    }
+
+    /// Returns the name of a sheet the user clicked on. `open` is cleared when the window is
+    /// closed.
+    pub fn draw(&mut self, ctx: &egui::Context, open: &mut bool) -> Option<String> {
+        if let Some(promise) = self.promise.take() {
+            match promise.try_take() {
+                Ok(issues) => self.issues = Some(issues),
+                Err(promise) => self.promise = Some(promise),
+            }
+        }
+
+        let mut ret = None;
+        egui::Window::new(tr(ctx, Text::CheckIntegrity))
+            .id(egui::Id::new("integrity-check-window"))
+            .open(open)
+            .default_size([640.0, 400.0])
+            .show(ctx, |ui| {
+                let (done, total) = self.progress.get();
+                let Some(issues) = &self.issues else {
+                    if total == 0 {
+                        ui.spinner();
+                    } else {
+                        ui.add(
+                            ProgressBar::new(done as f32 / total as f32)
+                                .text(format!("{done} / {total}")),
+                        );
+                    }
+                    return;
+                };
+
+                let sheet_count = issues.iter().map(|i| &i.sheet).unique().count();
+                ui.label(format!(
+                    "{sheet_count} of {total} sheets have issues ({} files)",
+                    issues.len()
+                ));
+                ui.separator();
+
+                if issues.is_empty() {
+                    ui.label(tr(ctx, Text::NoIntegrityIssues));
+                    return;
+                }
+
+                let row_height = ui.text_style_height(&egui::TextStyle::Body);
+                TableBuilder::new(ui)
+                    .striped(true)
+                    .resizable(true)
+                    .column(Column::initial(160.0))
+                    .column(Column::initial(90.0))
+                    .column(Column::remainder())
+                    .header(row_height + 4.0, |mut header| {
+                        for label in ["Sheet", "Issue", "Details"] {
+                            header.col(|ui| {
+                                ui.label(RichText::new(label).strong());
+                            });
+                        }
+                    })
+                    .body(|body| {
+                        body.rows(row_height, issues.len(), |mut row| {
+                            let issue = &issues[row.index()];
+                            row.col(|ui| {
+                                if ui.link(&issue.sheet).clicked() {
+                                    ret = Some(issue.sheet.clone());
+                                }
+                            });
+                            row.col(|ui| {
+                                ui.label(issue.kind.name());
+                            });
+                            row.col(|ui| {
+                                ui.label(&issue.message).on_hover_text(&issue.message);
+                            });
+                        });
+                    });
+            });
+        ret
+    }
+}
+
+async fn check(backend: Backend, progress: Rc<Cell<(usize, usize)>>) -> Vec<Issue> {
+    while backend.excel().entries_status() == EntriesStatus::Loading {
+        yield_to_ui().await;
+    }
+    let names = backend
+        .excel()
+        .get_entries()
+        .keys()
+        .cloned()
+        .sorted()
+        .collect_vec();
+    let total = names.len();
+    progress.set((0, total));
+
+    let mut issues = futures_util::stream::iter(names)
+        .map(|name| {
+            let backend = backend.clone();
+            async move { check_sheet(&backend, name).await }
+        })
+        .buffer_unordered(CONCURRENT_CHECKS)
+        .inspect(|_| progress.set((progress.get().0 + 1, total)))
+        .concat()
+        .await;
+    issues.sort_by(|a, b| a.sheet.cmp(&b.sheet).then(a.kind.cmp(&b.kind)));
+    issues
+}
+
+async fn check_sheet(backend: &Backend, name: String) -> Vec<Issue> {
+    let issue = |error: &ProviderError, path: &str| Issue {
+        sheet: name.clone(),
+        kind: IssueKind::of(error),
+        message: format!("{path}: {error}"),
+    };
+
+    let header = match backend.excel().get_header(&name).await {
+        Ok(header) => header,
+        Err(e) => return vec![issue(&e, &path::exh(&name))],
+    };
+    let mut issues = Vec::new();
+    for page in header.row_intervals() {
+        for &language in header.languages() {
+            let path = path::exd(&name, page.start_id(), language);
+            if let Err(e) = backend.files().file::<ExcelData>(&path).await {
+                issues.push(issue(&e, &path));
+            }
+        }
+    }
+    issues
+}
@@ -25,21 +25,57 @@ mod about;
 mod app;
 pub mod audio;
 mod backend;
+mod bookmarks;
+mod breadcrumbs;
+mod column_docs;
+mod command_palette;
+mod compare;
+#[cfg(not(target_arch = "wasm32"))]
+mod control;
 mod data;
 mod editable_schema;
 mod excel;
+mod export;
+mod export_job;
+mod external_links;
+mod filter_builder;
+mod filter_help;
+mod filter_history;
 mod github;
 mod goto;
+mod huge_operation;
+mod i18n;
+mod icon_export;
+mod integrity_check;
+mod memory_usage;
 mod music;
+mod operation_log;
+mod outbox;
 mod pr_window;
+mod references;
 mod router;
+mod routes;
+mod row_compare;
 mod schema;
+mod schema_completion;
+mod schema_download;
+mod schema_history;
+mod schema_tree;
+mod schema_validation;
+mod scripts;
+mod sestring_inspector;
 mod settings;
 mod setup;
 mod sheet;
+mod sheet_graph;
+mod sheet_tabs;
 mod shortcuts;
 pub mod stopwatch;
+mod telemetry;
+mod timeline;
+mod touch_layout;
 mod utils;
+mod version_watch;
 #[cfg(target_arch = "wasm32")]
 pub mod worker;
 
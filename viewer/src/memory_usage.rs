@@ -0,0 +1,108 @@
+use egui::{Grid, RichText, ScrollArea};
+use ironworks::excel::Language;
+
+use crate::excel::{base::BaseSheet, provider::ExcelHeader};
+
+/// Memory held by a cached sheet.
+pub struct SheetUsage {
+    name: String,
+    language: Language,
+    pages: usize,
+    page_bytes: usize,
+    lookup_bytes: usize,
+}
+
+impl SheetUsage {
+    pub fn new(language: Language, sheet: &BaseSheet) -> Self {
+        let (page_bytes, lookup_bytes) = sheet.memory_usage();
+        Self {
+            name: sheet.name().to_owned(),
+            language,
+            pages: sheet.page_count(),
+            page_bytes,
+            lookup_bytes,
+        }
+    }
+
+    fn total(&self) -> usize {
+        self.page_bytes + self.lookup_bytes
+    }
+}
+
+/// Lists the memory held by every cached sheet, largest first, along with how many opened
+/// sheets and schemas are kept. Returns whether the caches should be cleared.
+pub fn ui(
+    ui: &mut egui::Ui,
+    mut sheets: Vec<SheetUsage>,
+    open_sheets: usize,
+    schemas: usize,
+) -> bool {
+    sheets.sort_by_key(|s| std::cmp::Reverse(s.total()));
+    let total = sheets.iter().map(SheetUsage::total).sum();
+
+    let mut clear = false;
+    ui.horizontal(|ui| {
+        ui.label(format!(
+            "{} in {} sheets ({open_sheets} opened), {schemas} schemas",
+            format_bytes(total),
+            sheets.len()
+        ));
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            clear = ui.button("🗑 Clear Caches").clicked();
+        });
+    });
+    ui.separator();
+
+    if sheets.is_empty() {
+        ui.label(RichText::new("No sheets loaded").weak());
+        return clear;
+    }
+    ScrollArea::vertical().auto_shrink(false).show(ui, |ui| {
+        Grid::new("memory-usage")
+            .num_columns(5)
+            .striped(true)
+            .show(ui, |ui| {
+                for header in ["Sheet", "Language", "Pages", "Page Data", "Row Lookups"] {
+                    ui.label(RichText::new(header).strong());
+                }
+                ui.end_row();
+                for sheet in &sheets {
+                    ui.label(RichText::new(&sheet.name).monospace());
+                    ui.label(sheet.language.to_string());
+                    ui.label(sheet.pages.to_string());
+                    ui.label(format_bytes(sheet.page_bytes));
+                    ui.label(format_bytes(sheet.lookup_bytes));
+                    ui.end_row();
+                }
+            });
+    });
+    clear
+}
+
+fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::format_bytes;
+
+    #[test]
+    fn formats_bytes() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(5 * 1024 * 1024), "5.0 MiB");
+    }
+}
@@ -0,0 +1,222 @@
+use std::{collections::VecDeque, fmt::Write, sync::LazyLock};
+
+use egui::{RichText, ScrollArea, mutex::Mutex};
+use serde_json::{Map, Value, json};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+#[cfg(target_arch = "wasm32")]
+use web_time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::utils::TrackedPromise;
+
+/// Operations kept; older ones are dropped.
+const MAX_OPERATIONS: usize = 1000;
+/// Repeats of an operation on the same thing within this long replace the previous one, so
+/// typing out a filter is one entry instead of one per key.
+const COALESCE_WINDOW: Duration = Duration::from_secs(3);
+
+static LOG: LazyLock<Mutex<OperationLog>> = LazyLock::new(|| Mutex::new(OperationLog::new()));
+
+/// High-level actions of the user during the session, such as opening a sheet, filtering it
+/// or exporting it, with the parameters they were done with. Unlike the debug log, it's meant
+/// to be attached to bug reports and notes to tell exactly what was done.
+struct OperationLog {
+    started: Instant,
+    operations: VecDeque<Operation>,
+}
+
+struct Operation {
+    at: Instant,
+    /// Milliseconds since the Unix epoch
+    time: u64,
+    action: &'static str,
+    /// The first one is what the action was done on, e.g. the sheet
+    details: Vec<(&'static str, String)>,
+}
+
+impl OperationLog {
+    fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            operations: VecDeque::new(),
+        }
+    }
+
+    fn push(&mut self, operation: Operation) {
+        if let Some(last) = self.operations.back_mut()
+            && last.action == operation.action
+            && last.details.first() == operation.details.first()
+            && operation.at - last.at < COALESCE_WINDOW
+        {
+            *last = operation;
+            return;
+        }
+        if self.operations.len() == MAX_OPERATIONS {
+            self.operations.pop_front();
+        }
+        self.operations.push_back(operation);
+    }
+
+    fn to_text(&self) -> String {
+        let mut output = String::new();
+        for operation in &self.operations {
+            let _ = write!(
+                output,
+                "[{}] {}",
+                format_elapsed(operation.at - self.started),
+                operation.action
+            );
+            for (key, value) in &operation.details {
+                let _ = write!(output, " {key}={value:?}");
+            }
+            output.push('\n');
+        }
+        output
+    }
+
+    fn to_json(&self) -> Value {
+        let operations = self
+            .operations
+            .iter()
+            .map(|operation| {
+                let mut value = Map::new();
+                value.insert("time".to_owned(), operation.time.into());
+                value.insert("action".to_owned(), operation.action.into());
+                for (key, detail) in &operation.details {
+                    value.insert((*key).to_owned(), detail.clone().into());
+                }
+                Value::Object(value)
+            })
+            .collect::<Vec<_>>();
+        json!({
+            "version": crate::build::PKG_VERSION,
+            "commit": crate::build::SHORT_COMMIT,
+            "operations": operations,
+        })
+    }
+}
+
+/// Records an operation. The first detail is what it was done on, which repeats of the same
+/// operation in quick succession are coalesced by.
+pub fn record(action: &'static str, details: impl IntoIterator<Item = (&'static str, String)>) {
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64);
+    let details = details.into_iter().collect::<Vec<_>>();
+    log::debug!("Operation: {action} {details:?}");
+    LOG.lock().push(Operation {
+        at: Instant::now(),
+        time,
+        action,
+        details,
+    });
+}
+
+/// Lists the operations, with buttons to copy, export or clear them. Returns the export once
+/// one was started.
+pub fn ui(ui: &mut egui::Ui) -> Option<TrackedPromise<()>> {
+    let mut ret = None;
+    ui.horizontal(|ui| {
+        if ui.button("📋 Copy").clicked() {
+            ui.ctx().copy_text(LOG.lock().to_text());
+        }
+        if ui.button("💾 Export…").clicked() {
+            ret = Some(save());
+        }
+        if ui.button("🗑 Clear").clicked() {
+            LOG.lock().operations.clear();
+        }
+    });
+    ui.separator();
+
+    let log = LOG.lock();
+    if log.operations.is_empty() {
+        ui.label(RichText::new("Nothing done yet").weak());
+        return ret;
+    }
+    ScrollArea::vertical()
+        .auto_shrink(false)
+        .stick_to_bottom(true)
+        .show(ui, |ui| {
+            for operation in &log.operations {
+                ui.horizontal_wrapped(|ui| {
+                    ui.label(
+                        RichText::new(format_elapsed(operation.at - log.started))
+                            .monospace()
+                            .weak(),
+                    );
+                    ui.label(RichText::new(operation.action).strong());
+                    for (key, value) in &operation.details {
+                        ui.label(RichText::new(format!("{key}:")).weak());
+                        ui.label(value);
+                    }
+                });
+            }
+        });
+    ret
+}
+
+/// Prompts the user for where to save the operations as JSON.
+fn save() -> TrackedPromise<()> {
+    let data = serde_json::to_vec_pretty(&LOG.lock().to_json()).unwrap_or_default();
+    let dialog = rfd::AsyncFileDialog::new()
+        .set_title("Export Operation Log")
+        .set_file_name("operations.json");
+    TrackedPromise::spawn_local(async move {
+        if let Some(file) = dialog.save_file().await {
+            if let Err(e) = file.write(&data).await {
+                log::error!("Failed to save operation log: {e}");
+            } else {
+                log::info!("Operation log saved successfully");
+            }
+        }
+    })
+}
+
+/// `h:mm:ss` since the session started.
+fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{COALESCE_WINDOW, Duration, Instant, Operation, OperationLog};
+
+    #[test]
+    fn coalesces_quick_repeats() {
+        let mut log = OperationLog::new();
+        let start = Instant::now();
+        let operation = |secs, action, sheet: &str, filter: &str| Operation {
+            at: start + Duration::from_secs(secs),
+            time: 0,
+            action,
+            details: vec![("sheet", sheet.to_owned()), ("filter", filter.to_owned())],
+        };
+        log.push(operation(0, "Filter", "Item", "a"));
+        log.push(operation(1, "Filter", "Item", "ab"));
+        log.push(operation(1, "Filter", "Action", "ab"));
+        log.push(operation(1, "Sort", "Action", ""));
+        log.push(operation(
+            1 + COALESCE_WINDOW.as_secs(),
+            "Sort",
+            "Action",
+            "",
+        ));
+        let entries = log
+            .operations
+            .iter()
+            .map(|o| (o.action, o.details[1].1.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            [
+                ("Filter", "ab"),
+                ("Filter", "ab"),
+                ("Sort", ""),
+                ("Sort", "")
+            ]
+        );
+        assert_eq!(log.operations[0].details[0].1, "Item");
+    }
+}
@@ -0,0 +1,208 @@
+use egui::{Button, Grid, RichText};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    i18n::{Text, tr},
+    settings::SCHEMA_OUTBOX,
+};
+
+/// How far along a queued schema is.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OutboxStatus {
+    /// Still being worked on
+    #[default]
+    Draft,
+    /// Included in the next pull request made from the outbox
+    Ready,
+    Submitted,
+}
+
+impl OutboxStatus {
+    pub const ALL: &[Self] = &[Self::Draft, Self::Ready, Self::Submitted];
+
+    pub fn name(self, ctx: &egui::Context) -> &'static str {
+        tr(
+            ctx,
+            match self {
+                Self::Draft => Text::OutboxDraft,
+                Self::Ready => Text::OutboxReady,
+                Self::Submitted => Text::OutboxSubmitted,
+            },
+        )
+    }
+}
+
+/// A schema written from a blank stub, kept until it's contributed upstream.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutboxEntry {
+    pub text: String,
+    pub status: OutboxStatus,
+    /// Pull request the schema was submitted in
+    pub pull_request: Option<String>,
+}
+
+pub enum OutboxAction {
+    /// Go to the sheet's route
+    Open(String),
+    /// Open a pull request with the ready schemas
+    Submit,
+}
+
+/// Queues the edited text of a stub schema. Editing a submitted schema drafts it again, since
+/// the changes aren't part of the pull request.
+pub fn queue(ctx: &egui::Context, sheet_name: &str, text: &str) {
+    SCHEMA_OUTBOX.use_with(ctx, |outbox| {
+        if let Some(entry) = outbox.get_mut(sheet_name) {
+            if entry.text != text {
+                entry.text = text.to_owned();
+                if entry.status == OutboxStatus::Submitted {
+                    entry.status = OutboxStatus::Draft;
+                    entry.pull_request = None;
+                }
+            }
+        } else {
+            outbox.insert(
+                sheet_name.to_owned(),
+                OutboxEntry {
+                    text: text.to_owned(),
+                    status: OutboxStatus::default(),
+                    pull_request: None,
+                },
+            );
+        }
+    });
+}
+
+/// The queued text of a sheet's schema, unless it was already submitted.
+pub fn draft(ctx: &egui::Context, sheet_name: &str) -> Option<String> {
+    SCHEMA_OUTBOX.use_with(ctx, |outbox| {
+        outbox
+            .get(sheet_name)
+            .filter(|e| e.status != OutboxStatus::Submitted)
+            .map(|e| e.text.clone())
+    })
+}
+
+/// (sheet name, text) of every schema marked as ready.
+pub fn ready(ctx: &egui::Context) -> Vec<(String, String)> {
+    SCHEMA_OUTBOX.use_with(ctx, |outbox| {
+        outbox
+            .iter()
+            .filter(|(_, e)| e.status == OutboxStatus::Ready)
+            .map(|(name, e)| (name.clone(), e.text.clone()))
+            .collect()
+    })
+}
+
+/// Marks the queued schemas among `files` as submitted, if the pull request has the queued
+/// text.
+pub fn mark_submitted(ctx: &egui::Context, files: &[(String, String)], pull_request: &str) {
+    SCHEMA_OUTBOX.use_with(ctx, |outbox| {
+        for (sheet_name, text) in files {
+            if let Some(entry) = outbox.get_mut(sheet_name)
+                && entry.text == *text
+            {
+                entry.status = OutboxStatus::Submitted;
+                entry.pull_request = Some(pull_request.to_owned());
+            }
+        }
+    });
+}
+
+/// Draws the contribution outbox. `can_submit` is whether pull requests can be made with the
+/// current schema source.
+pub fn draw(ctx: &egui::Context, open: &mut bool, can_submit: bool) -> Option<OutboxAction> {
+    let mut ret = None;
+    egui::Window::new(tr(ctx, Text::SchemaOutbox))
+        .id(egui::Id::new("schema-outbox-window"))
+        .open(open)
+        .default_width(420.0)
+        .show(ctx, |ui| {
+            let mut outbox = SCHEMA_OUTBOX.get(ctx);
+            if outbox.is_empty() {
+                ui.label(RichText::new(tr(ctx, Text::SchemaOutboxEmpty)).weak());
+                return;
+            }
+
+            let mut changed = false;
+            let mut removed = None;
+            egui::ScrollArea::vertical()
+                .max_height(320.0)
+                .show(ui, |ui| {
+                    Grid::new("schema_outbox_grid")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for (sheet_name, entry) in &mut outbox {
+                                if ui.link(RichText::new(sheet_name).monospace()).clicked() {
+                                    ret = Some(OutboxAction::Open(format!("/sheet/{sheet_name}")));
+                                }
+
+                                egui::ComboBox::from_id_salt(("outbox-status", &*sheet_name))
+                                    .selected_text(entry.status.name(ctx))
+                                    .show_ui(ui, |ui| {
+                                        for &status in OutboxStatus::ALL {
+                                            changed |= ui
+                                                .selectable_value(
+                                                    &mut entry.status,
+                                                    status,
+                                                    status.name(ctx),
+                                                )
+                                                .changed();
+                                        }
+                                    });
+
+                                match &entry.pull_request {
+                                    Some(url) if entry.status == OutboxStatus::Submitted => {
+                                        ui.hyperlink_to("PR", url);
+                                    }
+                                    _ => {
+                                        ui.label("");
+                                    }
+                                }
+
+                                if ui
+                                    .small_button("🗑")
+                                    .on_hover_text(tr(ctx, Text::RemoveFromOutbox))
+                                    .clicked()
+                                {
+                                    removed = Some(sheet_name.clone());
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+            if let Some(sheet_name) = removed {
+                outbox.remove(&sheet_name);
+                changed = true;
+            }
+
+            ui.separator();
+            let ready_count = outbox
+                .values()
+                .filter(|e| e.status == OutboxStatus::Ready)
+                .count();
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(
+                        can_submit && ready_count > 0,
+                        Button::new(
+                            tr(ctx, Text::CreatePr).replace("{n}", &ready_count.to_string()),
+                        ),
+                    )
+                    .on_disabled_hover_text(tr(ctx, Text::CreatePrDisabledHover))
+                    .clicked()
+                {
+                    ret = Some(OutboxAction::Submit);
+                }
+                if ui.button(tr(ctx, Text::ClearSubmitted)).clicked() {
+                    outbox.retain(|_, e| e.status != OutboxStatus::Submitted);
+                    changed = true;
+                }
+            });
+
+            if changed {
+                SCHEMA_OUTBOX.set(ctx, outbox);
+            }
+        });
+    ret
+}
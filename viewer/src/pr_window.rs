@@ -4,10 +4,16 @@ use itertools::Itertools;
 
 use crate::{
     about::centered_inline, github::{
-        GithubAuth, GithubClient, PrDraft, PrResult, RelayResult, build_auth_start, exchange_code,
-        fetch_client_id, relay_and_close, take_relayed_result,
-    }, settings::{BACKEND_CONFIG, BackendConfig, GithubSchemaLocation, SchemaLocation}, utils::{PromiseKind, TrackedPromise},
+        GithubAuth, GithubClient, PrDraft, PrResult, RelayResult, exchange_code, fetch_client_id,
+        relay_and_close, take_relayed_result,
+    }, settings::{
+        BACKEND_CONFIG, BackendConfig, GithubSchemaLocation, InstallLocation, SchemaLocation,
+    }, utils::{PromiseKind, TrackedPromise},
 };
+#[cfg(target_arch = "wasm32")]
+use crate::github::build_auth_start;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::github::{DeviceCode, poll_device_token, request_device_code};
 
 pub type PrOutcome = std::result::Result<PrResult, String>;
 
@@ -31,6 +37,24 @@ pub fn github_source(ctx: &egui::Context) -> Option<GithubSchemaLocation> {
     }
 }
 
+/// Version of the game files being viewed, for the pull request description. `None` if it
+/// can't be determined, e.g. when viewing the latest version from a web install location.
+pub fn game_version(ctx: &egui::Context) -> Option<String> {
+    match BACKEND_CONFIG.get(ctx)?.location {
+        InstallLocation::Web(_, _, version) => version.map(|v| v.to_string()),
+        #[cfg(not(target_arch = "wasm32"))]
+        InstallLocation::Sqpack(path) => {
+            // The sqpack folder sits next to the version file
+            let path = std::path::Path::new(&path).parent()?.join("ffxivgame.ver");
+            let version = std::fs::read_to_string(path).ok()?;
+            Some(version.trim().to_string())
+        }
+        #[cfg(target_arch = "wasm32")]
+        InstallLocation::Worker(_) => None,
+        InstallLocation::Registered(..) => None,
+    }
+}
+
 pub fn draw_auth_callback(ui: &mut egui::Ui) {
     relay_and_close();
     ui.vertical_centered(|ui| {
@@ -50,10 +74,17 @@ pub struct PrWindow {
     /// (verifier, state)
     oauth_pending: Option<(String, String)>,
     oauth_exchange: Option<TrackedPromise<Result<GithubAuth>>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    device_request: Option<TrackedPromise<Result<DeviceCode>>>,
+    /// Shown to the user while waiting for them to enter it on GitHub
+    #[cfg(not(target_arch = "wasm32"))]
+    device_code: Option<DeviceCode>,
     oauth_error: Option<String>,
     draft: Option<Draft>,
     pr_promise: Option<TrackedPromise<Result<PrResult>>>,
     pr_outcome: Option<PrOutcome>,
+    /// Set when a pull request was just opened, until taken
+    opened: Option<PrResult>,
 }
 
 impl PrWindow {
@@ -96,11 +127,37 @@ impl PrWindow {
             }
         }
 
+        #[cfg(not(target_arch = "wasm32"))]
+        if self
+            .device_request
+            .as_ref()
+            .is_some_and(|p| p.try_get().is_some())
+        {
+            match self.device_request.take().unwrap().block_and_take() {
+                Ok(device) => {
+                    ctx.open_url(egui::OpenUrl::new_tab(&device.verification_uri));
+                    let client_id = self.oauth_client_id.clone().unwrap_or_default();
+                    self.device_code = Some(device.clone());
+                    self.oauth_exchange = Some(TrackedPromise::spawn_local(async move {
+                        poll_device_token(client_id, device).await
+                    }));
+                }
+                Err(e) => {
+                    log::error!("Failed to start GitHub sign-in: {e}");
+                    self.oauth_error = Some(e.to_string());
+                }
+            }
+        }
+
         if self
             .oauth_exchange
             .as_ref()
             .is_some_and(|p| p.try_get().is_some())
         {
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                self.device_code = None;
+            }
             match self.oauth_exchange.take().unwrap().block_and_take() {
                 Ok(auth) => {
                     log::info!("Signed in to GitHub as {}", auth.login);
@@ -123,15 +180,12 @@ impl PrWindow {
         }
     }
 
-    pub fn open(&mut self, modified_names: &[String]) {
+    pub fn open(&mut self, modified_names: &[String], game_version: Option<&str>) {
         let title = match modified_names {
             [one] => format!("Update {one} schema"),
             many => format!("Update {} schemas", many.len()),
         };
-        let body = format!(
-            "Updated schemas:\n{}",
-            modified_names.iter().map(|n| format!("- {n}")).join("\n")
-        );
+        let body = pr_template(modified_names, game_version);
         self.pr_outcome = None;
         // Prefetch client id
         if self.github_auth.is_none() {
@@ -151,6 +205,11 @@ impl PrWindow {
             self.oauth_error = Some("Preparing sign-in… try again in a moment".to_string());
             return;
         };
+        self.start_login(ctx, client_id);
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn start_login(&mut self, ctx: &egui::Context, client_id: String) {
         match build_auth_start(&client_id) {
             Ok(start) => {
                 self.oauth_pending = Some((start.verifier, start.state));
@@ -163,6 +222,15 @@ impl PrWindow {
         }
     }
 
+    /// Native builds can't receive the OAuth redirect, so they use the device flow instead.
+    /// The verification page is opened once the code is ready.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn start_login(&mut self, _ctx: &egui::Context, client_id: String) {
+        self.device_request = Some(TrackedPromise::spawn_local(async move {
+            request_device_code(client_id).await
+        }));
+    }
+
     pub fn submit(
         &mut self,
         location: &GithubSchemaLocation,
@@ -193,6 +261,11 @@ impl PrWindow {
         }));
     }
 
+    /// The pull request that was opened since the last call, if any.
+    pub fn take_opened(&mut self) -> Option<PrResult> {
+        self.opened.take()
+    }
+
     pub fn draw(
         &mut self,
         ctx: &egui::Context,
@@ -206,6 +279,7 @@ impl PrWindow {
             .is_some_and(|p| p.try_get().is_some())
         {
             let result = self.pr_promise.take().unwrap().block_and_take();
+            self.opened = result.as_ref().ok().cloned();
             self.pr_outcome = Some(result.map_err(|e| e.to_string()));
         }
 
@@ -219,6 +293,10 @@ impl PrWindow {
         let submitting = self.pr_promise.is_some();
         let signed_in_as = self.github_auth.as_ref().map(|a| a.login.clone());
         let signing_in = self.oauth_pending.is_some() || self.oauth_exchange.is_some();
+        #[cfg(not(target_arch = "wasm32"))]
+        let signing_in = signing_in || self.device_request.is_some();
+        #[cfg(not(target_arch = "wasm32"))]
+        let device_code = self.device_code.clone();
         let client_id_ready = self.oauth_client_id.is_some();
         let oauth_error = self.oauth_error.clone();
         let mut open = true;
@@ -319,6 +397,18 @@ impl PrWindow {
                         ui.spinner();
                         ui.label("Waiting for GitHub. Finish in the new tab.");
                     });
+                    #[cfg(not(target_arch = "wasm32"))]
+                    if let Some(device) = &device_code {
+                        ui.horizontal(|ui| {
+                            ui.label("Enter");
+                            ui.label(RichText::new(&device.user_code).monospace().strong());
+                            if ui.small_button("📋").on_hover_text("Copy").clicked() {
+                                ui.ctx().copy_text(device.user_code.clone());
+                            }
+                            ui.label("at");
+                            ui.hyperlink(&device.verification_uri);
+                        });
+                    }
                     if let Some(err) = &oauth_error {
                         ui.colored_label(ui.visuals().error_fg_color, err);
                     }
@@ -452,3 +542,13 @@ impl PrWindow {
         action
     }
 }
+
+/// The default pull request description, listing the changed schemas and the game version
+/// they were checked against.
+fn pr_template(modified_names: &[String], game_version: Option<&str>) -> String {
+    format!(
+        "### Updated schemas\n{}\n\n### Game version\n{}\n\n### Notes\n\n",
+        modified_names.iter().map(|n| format!("- {n}")).join("\n"),
+        game_version.unwrap_or("Unknown")
+    )
+}
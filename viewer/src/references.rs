@@ -0,0 +1,324 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+
+use egui::{ProgressBar, RichText};
+use egui_extras::{Column, TableBuilder};
+use futures_util::StreamExt;
+use itertools::Itertools;
+
+use crate::{
+    excel::provider::{EntriesStatus, ExcelHeader, ExcelProvider, ExcelSheet},
+    i18n::{Text, tr},
+    schema::{Schema, provider::SchemaProvider},
+    sheet::{GlobalContext, SchemaColumn, SchemaColumnMeta, SheetRef, TableContext},
+    utils::{PromiseKind, TrackedPromise, yield_to_ui},
+};
+
+const CONCURRENT_SCANS: usize = 8;
+/// Rows read between yields to the UI.
+const SCAN_CHUNK_SIZE: usize = 0x2000;
+
+/// A row that links to the row being looked up.
+#[derive(Debug, Clone)]
+pub struct Reference {
+    pub sheet: String,
+    pub row: (u32, Option<u16>),
+    /// Name of the linking column
+    pub column: String,
+}
+
+/// Lists the rows of other sheets that link to a row. Every sheet whose schema has a link
+/// targeting the row's sheet is scanned for the row's id.
+pub struct ReferencesWindow {
+    sheet_name: String,
+    row_id: u32,
+    /// (searched, total) sheets
+    progress: Rc<Cell<(usize, usize)>>,
+    promise: Option<TrackedPromise<Vec<Reference>>>,
+    references: Option<Vec<Reference>>,
+}
+
+impl ReferencesWindow {
+    pub fn new(global: GlobalContext, sheet_name: String, row_id: u32) -> Self {
+        let progress = Rc::new(Cell::new((0, 0)));
+        let promise = TrackedPromise::spawn_local(find_references(
+            global,
+            sheet_name.clone(),
+            row_id,
+            progress.clone(),
+        ));
+        Self {
+            sheet_name,
+            row_id,
+            progress,
+            promise: Some(promise),
+            references: None,
+        }
+    }
+
+    /// Returns the row the user clicked on. `open` is cleared when the window is closed.
+    pub fn draw(&mut self, ctx: &egui::Context, open: &mut bool) -> Option<SheetRef> {
+        if let Some(promise) = self.promise.take() {
+            match promise.try_take() {
+                Ok(references) => self.references = Some(references),
+                Err(promise) => self.promise = Some(promise),
+            }
+        }
+
+        let mut ret = None;
+        egui::Window::new(format!(
+            "{} - {}#{}",
+            tr(ctx, Text::FindReferences),
+            self.sheet_name,
+            self.row_id
+        ))
+        .id(egui::Id::new("references-window"))
+        .open(open)
+        .default_size([480.0, 360.0])
+        .show(ctx, |ui| {
+            let Some(references) = &self.references else {
+                let (done, total) = self.progress.get();
+                if total == 0 {
+                    ui.spinner();
+                } else {
+                    ui.add(
+                        ProgressBar::new(done as f32 / total as f32)
+                            .text(format!("{done} / {total}")),
+                    );
+                }
+                return;
+            };
+
+            let sheet_count = references.iter().map(|r| &r.sheet).unique().count();
+            ui.label(format!(
+                "{} references in {sheet_count} sheets",
+                references.len()
+            ));
+            ui.separator();
+            if references.is_empty() {
+                return;
+            }
+
+            let row_height = ui.text_style_height(&egui::TextStyle::Body);
+            TableBuilder::new(ui)
+                .striped(true)
+                .resizable(true)
+                .column(Column::initial(160.0))
+                .column(Column::initial(80.0))
+                .column(Column::remainder())
+                .header(row_height + 4.0, |mut header| {
+                    for label in ["Sheet", "Row", "Column"] {
+                        header.col(|ui| {
+                            ui.label(RichText::new(label).strong());
+                        });
+                    }
+                })
+                .body(|body| {
+                    body.rows(row_height, references.len(), |mut row| {
+                        let reference = &references[row.index()];
+                        row.col(|ui| {
+                            ui.label(&reference.sheet);
+                        });
+                        row.col(|ui| {
+                            let text = match reference.row {
+                                (row_id, Some(subrow_id)) => format!("{row_id}.{subrow_id}"),
+                                (row_id, None) => row_id.to_string(),
+                            };
+                            if ui.link(text).clicked() {
+                                ret = Some((reference.sheet.clone(), reference.row));
+                            }
+                        });
+                        row.col(|ui| {
+                            ui.label(&reference.column);
+                        });
+                    });
+                });
+        });
+        ret
+    }
+}
+
+/// Whether sheets contain a row, shared between the scans.
+type RowLookup = Rc<RefCell<HashMap<String, bool>>>;
+
+async fn find_references(
+    global: GlobalContext,
+    target: String,
+    row_id: u32,
+    progress: Rc<Cell<(usize, usize)>>,
+) -> Vec<Reference> {
+    let backend = global.backend().clone();
+    while backend.excel().entries_status() == EntriesStatus::Loading {
+        yield_to_ui().await;
+    }
+    // Miscellaneous sheets have no schemas
+    let names = backend
+        .excel()
+        .get_entries()
+        .iter()
+        .filter(|(_, id)| **id >= 0)
+        .map(|(name, _)| name.clone())
+        .sorted()
+        .collect_vec();
+    let total = names.len();
+    progress.set((0, total));
+
+    let lookup = RowLookup::default();
+    let mut references = futures_util::stream::iter(names)
+        .map(|name| scan_sheet(&global, name, &target, row_id, &lookup))
+        .buffer_unordered(CONCURRENT_SCANS)
+        .inspect(|_| progress.set((progress.get().0 + 1, total)))
+        .concat()
+        .await;
+    references.sort_by(|a, b| (&a.sheet, a.row, &a.column).cmp(&(&b.sheet, b.row, &b.column)));
+    references
+}
+
+/// A column that can link to the target sheet.
+struct Candidate {
+    column_idx: u32,
+    name: String,
+    /// Column switched on and the cases linking to the target, for conditional links
+    switch: Option<(u32, HashSet<i32>)>,
+}
+
+async fn scan_sheet(
+    global: &GlobalContext,
+    name: String,
+    target: &str,
+    row_id: u32,
+    lookup: &RowLookup,
+) -> Vec<Reference> {
+    match scan_sheet_inner(global, &name, target, row_id, lookup).await {
+        Ok(references) => references,
+        Err(e) => {
+            log::warn!("Failed to search {name} for references: {e:?}");
+            Vec::new()
+        }
+    }
+}
+
+async fn scan_sheet_inner(
+    global: &GlobalContext,
+    name: &str,
+    target: &str,
+    row_id: u32,
+    lookup: &RowLookup,
+) -> anyhow::Result<Vec<Reference>> {
+    // Sheets without a valid schema have no links to follow
+    let Ok(text) = global.backend().schema().get_schema_text(name).await else {
+        return Ok(Vec::new());
+    };
+    let Ok(Ok(schema)) = Schema::from_str(&text) else {
+        return Ok(Vec::new());
+    };
+    let (columns, _) = SchemaColumn::from_schema(&schema)?;
+
+    let mut candidates = Vec::new();
+    for (column_idx, column) in columns.iter().enumerate() {
+        let switch = match column.meta() {
+            SchemaColumnMeta::Link(link) => {
+                if !reaches(global, link.targets(), target, row_id, lookup).await {
+                    continue;
+                }
+                None
+            }
+            SchemaColumnMeta::ConditionalLink { column_idx, links } => {
+                let mut cases = HashSet::new();
+                for (case, link) in links {
+                    if reaches(global, link.targets(), target, row_id, lookup).await {
+                        cases.insert(*case);
+                    }
+                }
+                if cases.is_empty() {
+                    continue;
+                }
+                Some((*column_idx, cases))
+            }
+            _ => continue,
+        };
+        candidates.push(Candidate {
+            column_idx: column_idx as u32,
+            name: column.name().to_owned(),
+            switch,
+        });
+    }
+    if candidates.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let sheet = global
+        .backend()
+        .excel()
+        .get_sheet(name, global.language())
+        .await?;
+    let table = TableContext::new(global.clone(), sheet, Some(&schema));
+    let has_subrows = table.sheet().has_subrows();
+    let needle = Some(i128::from(row_id));
+
+    let mut references = Vec::new();
+    for chunk in &table.sheet().get_subrow_ids().chunks(SCAN_CHUNK_SIZE) {
+        for (linking_row_id, subrow_id) in chunk {
+            let row = table.sheet().get_subrow(linking_row_id, subrow_id)?;
+            for candidate in &candidates {
+                let cell = table.cell_by_index(row, candidate.column_idx)?;
+                if cell.read_raw()?.coerce_integer() != needle {
+                    continue;
+                }
+                if let Some((switch_idx, cases)) = &candidate.switch {
+                    let switch = table
+                        .cell_by_index(row, *switch_idx)?
+                        .read_raw()?
+                        .coerce_integer();
+                    if !switch.is_some_and(|s| cases.contains(&(s as i32))) {
+                        continue;
+                    }
+                }
+                references.push(Reference {
+                    sheet: name.to_owned(),
+                    row: (linking_row_id, has_subrows.then_some(subrow_id)),
+                    column: candidate.name.clone(),
+                });
+            }
+        }
+        yield_to_ui().await;
+    }
+    Ok(references)
+}
+
+/// Whether a link with `targets` resolves `row_id` to the target sheet. Links go to the first
+/// of their targets that has the row, so an earlier target having it shadows the target.
+async fn reaches(
+    global: &GlobalContext,
+    targets: &[String],
+    target: &str,
+    row_id: u32,
+    lookup: &RowLookup,
+) -> bool {
+    for sheet in targets {
+        if sheet == target {
+            return true;
+        }
+        if has_row(global, sheet, row_id, lookup).await {
+            return false;
+        }
+    }
+    false
+}
+
+async fn has_row(global: &GlobalContext, sheet: &str, row_id: u32, lookup: &RowLookup) -> bool {
+    if let Some(&has_row) = lookup.borrow().get(sheet) {
+        return has_row;
+    }
+    let has_row = global
+        .backend()
+        .excel()
+        .get_sheet(sheet, global.language())
+        .await
+        .is_ok_and(|s| s.get_row(row_id).is_ok());
+    lookup.borrow_mut().insert(sheet.to_owned(), has_row);
+    has_row
+}
@@ -1,6 +1,7 @@
 use crate::router::path::Path;
 use anyhow::{anyhow, bail};
 use egui::{Id, util::IdTypeMap};
+use serde::{Deserialize, Serialize};
 
 use super::History;
 
@@ -8,31 +9,34 @@ pub struct MemoryHistory {
     ctx: egui::Context,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+struct Entry {
+    path: Path,
+    id: u64,
+}
+
 impl MemoryHistory {
-    fn history(d: &mut IdTypeMap) -> &mut Vec<Path> {
-        d.get_persisted_mut_or_insert_with(Id::new("memory_history"), || vec!["/".into()])
+    fn history(d: &mut IdTypeMap) -> &mut Vec<Entry> {
+        d.get_persisted_mut_or_insert_with(Id::new("memory_history_entries"), || {
+            vec![Entry {
+                path: "/".into(),
+                id: 0,
+            }]
+        })
     }
 
     fn position(d: &mut IdTypeMap) -> &mut usize {
         d.get_persisted_mut_or_insert_with(Id::new("memory_history_position"), || 0)
     }
-}
 
-impl History for MemoryHistory {
-    fn new(ctx: egui::Context) -> Self {
-        Self { ctx }
+    fn next_id(d: &mut IdTypeMap) -> u64 {
+        let next_id = d.get_persisted_mut_or_insert_with(Id::new("memory_history_next_id"), || 1);
+        let id = *next_id;
+        *next_id += 1;
+        id
     }
 
-    fn set_title(&mut self, title: String) {
-        self.ctx
-            .send_viewport_cmd(egui::ViewportCommand::Title(title));
-    }
-
-    fn base_url(&self) -> String {
-        String::new()
-    }
-
-    fn active_route(&self) -> Path {
+    fn active_entry(&self) -> Entry {
         self.ctx
             .data_mut(|d| {
                 let position = {
@@ -50,13 +54,37 @@ impl History for MemoryHistory {
             })
             .unwrap()
     }
+}
+
+impl History for MemoryHistory {
+    fn new(ctx: egui::Context) -> Self {
+        Self { ctx }
+    }
+
+    fn set_title(&mut self, title: String) {
+        self.ctx
+            .send_viewport_cmd(egui::ViewportCommand::Title(title));
+    }
+
+    fn base_url(&self) -> String {
+        String::new()
+    }
+
+    fn active_route(&self) -> Path {
+        self.active_entry().path
+    }
+
+    fn entry_id(&self) -> u64 {
+        self.active_entry().id
+    }
 
     fn push(&mut self, location: Path) -> anyhow::Result<()> {
         self.ctx.data_mut(|d| {
             let position = *Self::position(d);
+            let id = Self::next_id(d);
             let history = Self::history(d);
             history.drain(position + 1..);
-            history.push(location);
+            history.push(Entry { path: location, id });
             *Self::position(d) += 1;
         });
         Ok(())
@@ -65,9 +93,10 @@ impl History for MemoryHistory {
     fn replace(&mut self, location: Path) -> anyhow::Result<()> {
         self.ctx.data_mut(|d| {
             let position = *Self::position(d);
-            *Self::history(d)
+            Self::history(d)
                 .get_mut(position)
-                .ok_or_else(|| anyhow!("Invalid history position"))? = location;
+                .ok_or_else(|| anyhow!("Invalid history position"))?
+                .path = location;
             Ok(())
         })
     }
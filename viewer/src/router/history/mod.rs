@@ -14,6 +14,9 @@ pub trait History {
     fn new(ctx: egui::Context) -> Self;
     fn base_url(&self) -> String;
     fn active_route(&self) -> Path;
+    /// Identifies the active history entry. It stays the same while the entry is replaced and
+    /// when going back or forward to it, and no other entry of the session shares it.
+    fn entry_id(&self) -> u64;
     fn set_title(&mut self, title: String);
     fn push(&mut self, location: Path) -> anyhow::Result<()>;
     fn replace(&mut self, location: Path) -> anyhow::Result<()>;
@@ -1,5 +1,8 @@
+use std::cell::Cell;
+
 use eframe::wasm_bindgen::{JsCast, JsValue, prelude::Closure};
 use web_sys::window;
+use web_time::{SystemTime, UNIX_EPOCH};
 
 use crate::{router::path::Path, utils::JsErr};
 
@@ -50,6 +53,20 @@ impl WebHistory {
     }
 }
 
+/// A new entry id, kept in the entry's state. Entries outlive the page when it's reloaded, so
+/// ids are made from the time to not reuse any of the previous page's.
+fn new_entry_id() -> u64 {
+    thread_local! {
+        static COUNTER: Cell<u64> = const { Cell::new(0) };
+    }
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64);
+    let counter = COUNTER.replace(COUNTER.get().wrapping_add(1));
+    // Stays within the integers an f64 can hold exactly
+    millis * 1000 + counter % 1000
+}
+
 impl Drop for WebHistory {
     fn drop(&mut self) {
         window()
@@ -90,16 +107,40 @@ impl History for WebHistory {
         path.into()
     }
 
+    fn entry_id(&self) -> u64 {
+        if let Some(id) = self.history.state().ok().and_then(|state| state.as_f64()) {
+            return id as u64;
+        }
+        // The entry the page was loaded on doesn't have one yet
+        let id = new_entry_id();
+        if let Err(e) = self
+            .history
+            .replace_state(&JsValue::from_f64(id as f64), "")
+            .map_err(JsErr::from)
+        {
+            log::warn!("Failed to store history entry id: {e}");
+        }
+        id
+    }
+
     fn push(&mut self, location: Path) -> anyhow::Result<()> {
         self.history
-            .push_state_with_url(&JsValue::null(), "", Some(&self.prefix_path(&location)))
+            .push_state_with_url(
+                &JsValue::from_f64(new_entry_id() as f64),
+                "",
+                Some(&self.prefix_path(&location)),
+            )
             .map_err(JsErr::from)?;
         Ok(())
     }
 
     fn replace(&mut self, location: Path) -> anyhow::Result<()> {
         self.history
-            .replace_state_with_url(&JsValue::null(), "", Some(&self.prefix_path(&location)))
+            .replace_state_with_url(
+                &JsValue::from_f64(self.entry_id() as f64),
+                "",
+                Some(&self.prefix_path(&location)),
+            )
             .map_err(JsErr::from)?;
         Ok(())
     }
@@ -110,7 +151,7 @@ impl History for WebHistory {
     }
 
     fn forward(&mut self) -> anyhow::Result<()> {
-        self.history.back().map_err(JsErr::from)?;
+        self.history.forward().map_err(JsErr::from)?;
         Ok(())
     }
 }
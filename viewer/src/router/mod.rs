@@ -4,6 +4,7 @@ use history::History;
 use matchit::{InsertError, Match, Params};
 use path::Path;
 use route::RouteResponse;
+use serde::de::DeserializeOwned;
 
 use crate::{
     shortcuts::{NAV_BACK, NAV_FORWARD},
@@ -19,9 +20,13 @@ pub struct Router<T, H: History = history::DefaultHistory> {
     matcher: matchit::Router<route::Route<T>>,
     unmatched: route::Route<T>,
     title_formatter: Box<dyn Fn(String) -> String>,
+    entry_handler: Option<Box<EntryHandlerFn<T>>>,
     last_path: RefCell<Option<Path>>,
+    last_entry: RefCell<Option<u64>>,
 }
 
+type EntryHandlerFn<T> = dyn Fn(&mut T, &egui::Ui, u64, u64);
+
 impl<T, H: History> Router<T, H> {
     pub fn new(ctx: egui::Context) -> Self {
         Self::from_history(H::new(ctx))
@@ -33,7 +38,9 @@ impl<T, H: History> Router<T, H> {
             matcher: matchit::Router::new(),
             unmatched: route::Route::unmatched(),
             title_formatter: Box::new(|title| title),
+            entry_handler: None,
             last_path: RefCell::new(None),
+            last_entry: RefCell::new(None),
         }
     }
 
@@ -47,10 +54,28 @@ impl<T, H: History> Router<T, H> {
         self.matcher.insert(path, route)
     }
 
+    /// Like [`Self::add_route`], with the route's parameters deserialized into `P`.
+    pub fn add_typed_route<P: DeserializeOwned + 'static>(
+        &mut self,
+        path: &str,
+        on_start: impl Fn(&mut T, &mut egui::Ui, &Path, P) -> RouteResponse + 'static,
+        on_render: impl Fn(&mut T, &mut egui::Ui, &Path, P) + 'static,
+    ) -> Result<(), InsertError> {
+        self.matcher
+            .insert(path, route::Route::typed(on_start, on_render))
+    }
+
     pub fn set_title_formatter(&mut self, formatter: impl Fn(String) -> String + 'static) {
         self.title_formatter = Box::new(formatter);
     }
 
+    /// Called with the id of the history entry being left and the one being entered whenever
+    /// the active entry changes, before the entered route starts. Pages keep their view state
+    /// by entry with it, to restore it when going back or forward to them.
+    pub fn set_entry_handler(&mut self, handler: impl Fn(&mut T, &egui::Ui, u64, u64) + 'static) {
+        self.entry_handler = Some(Box::new(handler));
+    }
+
     pub fn navigate(&self, path: impl Into<path::Path>) -> anyhow::Result<()> {
         self.history.borrow_mut().push(path.into())
     }
@@ -72,7 +97,12 @@ impl<T, H: History> Router<T, H> {
     }
 
     pub fn full_url(&self) -> String {
-        format!("{}{}", self.base_url(), self.current_path())
+        self.url_of(&self.current_path())
+    }
+
+    /// The URL that opens `path`, to share it.
+    pub fn url_of(&self, path: &path::Path) -> String {
+        format!("{}{}", self.base_url(), path)
     }
 
     pub fn current_path(&self) -> Path {
@@ -91,6 +121,14 @@ impl<T, H: History> Router<T, H> {
             log::error!("Failed to navigate forward: {e}");
         }
 
+        let entry = self.history.borrow().entry_id();
+        if let Some(left) = self.last_entry.replace(Some(entry))
+            && left != entry
+            && let Some(handler) = &self.entry_handler
+        {
+            handler(state, ui, left, entry);
+        }
+
         let path = self.current_path();
         let is_new_path = self.last_path.borrow().as_ref() != Some(&path);
         if is_new_path {
@@ -5,7 +5,7 @@ use std::{
     str::{FromStr, Split},
 };
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use url::form_urlencoded::{self, Parse};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -36,6 +36,36 @@ impl Path {
         path.parse().unwrap()
     }
 
+    /// `path` with `query` serialized as its query string, e.g. a struct with a field per
+    /// parameter. Fields that serialize to nothing, like `None`, are left out.
+    pub fn with_query(path: &str, query: &impl Serialize) -> Self {
+        let mut path = path.to_string();
+        match serde_urlencoded::to_string(query) {
+            Ok(query) if !query.is_empty() => {
+                path.push('?');
+                path.push_str(&query);
+            }
+            Ok(_) => {}
+            Err(e) => log::error!("Failed to serialize query of {path}: {e}"),
+        }
+        path.parse().unwrap()
+    }
+
+    /// Replaces the query string with `query` serialized, as in [`Self::with_query`].
+    #[must_use]
+    pub fn with_serialized_query(self, query: &impl Serialize) -> Self {
+        Self {
+            fragment: self.fragment,
+            ..Self::with_query(&self.path, query)
+        }
+    }
+
+    #[must_use]
+    pub fn with_fragment(mut self, fragment: impl ToString) -> Self {
+        self.fragment = Some(fragment.to_string());
+        self
+    }
+
     pub fn path(&self) -> &str {
         &self.path
     }
@@ -63,6 +93,11 @@ impl Path {
             .map(|(k, v)| (k.to_string(), v.to_string()))
             .collect()
     }
+
+    /// Deserializes the query string into `Q`, e.g. a struct with a field per parameter.
+    pub fn query_as<Q: DeserializeOwned>(&self) -> Result<Q, serde_urlencoded::de::Error> {
+        serde_urlencoded::from_str(self.query().unwrap_or_default())
+    }
 }
 
 impl FromStr for Path {
@@ -119,3 +154,43 @@ impl std::fmt::Display for Path {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use serde::{Deserialize, Serialize};
+
+    use super::Path;
+
+    #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+    #[serde(default)]
+    struct Query {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        redirect: Option<String>,
+        row: u32,
+    }
+
+    #[test]
+    fn query_round_trip() {
+        let query = Query {
+            redirect: Some("/sheet/Item#R5".to_string()),
+            row: 12,
+        };
+        let path = Path::with_query("/", &query).with_fragment("top");
+        assert_eq!(
+            path.to_string(),
+            "/?redirect=%2Fsheet%2FItem%23R5&row=12#top"
+        );
+        assert_eq!(
+            Path::parse(&path.to_string()).query_as::<Query>().unwrap(),
+            query
+        );
+
+        let path = Path::with_query("/sheet", &Query::default());
+        assert_eq!(path.to_string(), "/sheet?row=0");
+        assert_eq!(
+            Path::parse("/sheet").query_as::<Query>().unwrap(),
+            Query::default()
+        );
+        assert!(Path::parse("/?row=x").query_as::<Query>().is_err());
+    }
+}
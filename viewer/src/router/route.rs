@@ -1,10 +1,24 @@
 use matchit::Params;
+use serde::de::DeserializeOwned;
+use url::form_urlencoded;
 
 use super::path::Path;
+use crate::i18n::{Text, tr};
 
 type RouteStartFn<T> = dyn Fn(&mut T, &mut egui::Ui, &Path, &Params<'_, '_>) -> RouteResponse;
 type RouteRenderFn<T> = dyn Fn(&mut T, &mut egui::Ui, &Path, &Params<'_, '_>);
 
+/// Deserializes the parameters of a matched route into `P`, a struct with a field per
+/// parameter.
+pub fn typed_params<P: DeserializeOwned>(
+    params: &Params<'_, '_>,
+) -> Result<P, serde_urlencoded::de::Error> {
+    let encoded = form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(params.iter())
+        .finish();
+    serde_urlencoded::from_str(&encoded)
+}
+
 pub enum RouteResponse {
     Title(String),
     Redirect(Path),
@@ -26,16 +40,31 @@ impl<T> Route<T> {
         }
     }
 
-    pub fn unmatched() -> Self {
+    /// A route whose handlers take its parameters deserialized into `P`. Paths with
+    /// parameters that don't fit `P` are treated as not found.
+    pub fn typed<P: DeserializeOwned + 'static>(
+        on_start: impl Fn(&mut T, &mut egui::Ui, &Path, P) -> RouteResponse + 'static,
+        on_render: impl Fn(&mut T, &mut egui::Ui, &Path, P) + 'static,
+    ) -> Self {
         Self::new(
-            |_, _, _, _| RouteResponse::Title("Not Found".to_string()),
-            |_, ui, _, _| {
-                ui.vertical_centered_justified(|ui| {
-                    ui.heading("Not Found");
-                    ui.label("The requested page was not found.");
-                    ui.label("Please check the URL and try again.");
-                });
+            move |state, ui, path, params| match typed_params(params) {
+                Ok(params) => on_start(state, ui, path, params),
+                Err(e) => {
+                    log::error!("Invalid parameters for {path}: {e}");
+                    not_found_title(ui.ctx())
+                }
             },
+            move |state, ui, path, params| match typed_params(params) {
+                Ok(params) => on_render(state, ui, path, params),
+                Err(_) => not_found_ui(ui),
+            },
+        )
+    }
+
+    pub fn unmatched() -> Self {
+        Self::new(
+            |_, ui, _, _| not_found_title(ui.ctx()),
+            |_, ui, _, _| not_found_ui(ui),
         )
     }
 
@@ -53,3 +82,15 @@ impl<T> Route<T> {
         (self.on_render)(state, ui, path, params);
     }
 }
+
+fn not_found_title(ctx: &egui::Context) -> RouteResponse {
+    RouteResponse::Title(tr(ctx, Text::NotFound).to_string())
+}
+
+fn not_found_ui(ui: &mut egui::Ui) {
+    ui.vertical_centered_justified(|ui| {
+        ui.heading(tr(ui.ctx(), Text::NotFound));
+        ui.label(tr(ui.ctx(), Text::PageNotFound));
+        ui.label(tr(ui.ctx(), Text::CheckTheUrl));
+    });
+}
@@ -0,0 +1,356 @@
+use std::{fmt::Display, str::FromStr};
+
+use ironworks::excel::Language;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    router::path::Path,
+    sheet::{ColumnSort, FilterInputType, SortDirection},
+};
+
+/// Parameters of `/sheet/{*name}`.
+#[derive(Debug, Deserialize)]
+pub struct SheetParams {
+    pub name: String,
+}
+
+/// Query parameters understood on any route.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppQuery {
+    /// Where to go once setup is done
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redirect: Option<String>,
+    /// Shows only the sheet, for embedding it in other pages
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embed: Option<String>,
+}
+
+impl AppQuery {
+    pub fn is_embed(&self) -> bool {
+        self.embed
+            .as_deref()
+            .is_some_and(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+    }
+}
+
+/// The view a sheet route opens with, so a shared link shows what it was copied from. Sheets
+/// keep their last filter and sort for what's left out.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SheetQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lang: Option<Language>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<FilterInputType>,
+    /// Offset indices of the columns sorted by, highest priority first, e.g. `3,-5`. Descending
+    /// columns are negated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<String>,
+    /// Ids of the rows shown, as `10..20`, `..20`, `10..` or `10`, e.g. to embed part of a sheet.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rows: Option<String>,
+}
+
+impl SheetQuery {
+    pub fn set_sorts(&mut self, sorts: &[ColumnSort]) {
+        self.sort = Some(
+            sorts
+                .iter()
+                .map(|sort| match sort.direction {
+                    SortDirection::Ascending => sort.offset_idx.to_string(),
+                    SortDirection::Descending => format!("-{}", sort.offset_idx),
+                })
+                .collect::<Vec<_>>()
+                .join(","),
+        );
+    }
+
+    /// `None` if there's no sort or it doesn't parse. An empty one is unsorted.
+    pub fn sorts(&self) -> Option<Vec<ColumnSort>> {
+        let sort = self.sort.as_deref()?;
+        if sort.is_empty() {
+            return Some(Vec::new());
+        }
+        sort.split(',')
+            .map(|level| {
+                let (direction, offset_idx) = match level.strip_prefix('-') {
+                    Some(offset_idx) => (SortDirection::Descending, offset_idx),
+                    None => (SortDirection::Ascending, level),
+                };
+                Some(ColumnSort {
+                    offset_idx: offset_idx.parse().ok()?,
+                    direction,
+                })
+            })
+            .collect()
+    }
+
+    /// The filter the sheet opens with. `rows` narrows it down with a complex filter on the row
+    /// id; rows that don't parse are left out.
+    pub fn filter(&self) -> Option<(FilterInputType, String)> {
+        let mode = self.mode.unwrap_or_default();
+        let Some(rows) = self.rows.as_deref().filter(|rows| is_row_range(rows)) else {
+            return self.filter.clone().map(|filter| (mode, filter));
+        };
+        let rows = format!("# |= {rows}");
+        let quoted =
+            |value: &str| format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""));
+        let filter = match (mode, self.filter.as_deref()) {
+            (_, None | Some("")) => rows,
+            (FilterInputType::Equals, Some(filter)) => format!("{rows} && * = {}", quoted(filter)),
+            (FilterInputType::Contains, Some(filter)) => {
+                format!("{rows} && * *= {}", quoted(filter))
+            }
+            (FilterInputType::Complex, Some(filter)) => format!("{rows} && ({filter})"),
+        };
+        Some((FilterInputType::Complex, filter))
+    }
+}
+
+/// Whether `rows` is a range of row ids the complex filter reads.
+fn is_row_range(rows: &str) -> bool {
+    let (start, end) = rows.split_once("..").unwrap_or((rows, rows));
+    // Written the way the grammar reads numbers, without a sign or leading zeros
+    let id = |id: &str| {
+        id.parse::<u32>()
+            .ok()
+            .filter(|parsed| parsed.to_string() == id)
+    };
+    match (start, end) {
+        ("", "") => false,
+        ("", end) => id(end).is_some(),
+        (start, "") => id(start).is_some(),
+        (start, end) => id(start)
+            .zip(id(end))
+            .is_some_and(|(start, end)| start <= end),
+    }
+}
+
+/// The row (and column) a sheet route scrolls to, in its fragment:
+/// `R{row}[.{subrow}][C{column}]`, or `R{row}[.{subrow}]/{column name}` to still point at the
+/// same column once the schema adds or removes others.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowAnchor {
+    pub row_id: u32,
+    pub subrow_id: Option<u16>,
+    pub column: Option<ColumnRef>,
+}
+
+/// A column of a sheet, as addressed by a link.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColumnRef {
+    /// Index of the column, ordered by id
+    Index(u16),
+    /// Name of the column in the schema. Names that aren't found are read as an index.
+    Name(String),
+}
+
+impl FromStr for RowAnchor {
+    type Err = ();
+
+    fn from_str(mut fragment: &str) -> Result<Self, Self::Err> {
+        let mut column = None;
+        if let Some((rest, name)) = fragment.split_once('/') {
+            column = (!name.is_empty()).then(|| ColumnRef::Name(name.to_owned()));
+            fragment = rest;
+        } else if let Some((rest, column_str)) = fragment.rsplit_once('C') {
+            column = column_str.parse().ok().map(ColumnRef::Index);
+            fragment = rest;
+        }
+        let (_, row_str) = fragment.rsplit_once('R').ok_or(())?;
+        let (row_id, subrow_id) = match row_str.split_once('.') {
+            Some((row_str, subrow_str)) => (row_str.parse(), subrow_str.parse().ok()),
+            None => (row_str.parse(), None),
+        };
+        Ok(Self {
+            row_id: row_id.map_err(|_| ())?,
+            subrow_id,
+            column,
+        })
+    }
+}
+
+impl Display for RowAnchor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "R{}", self.row_id)?;
+        if let Some(subrow_id) = self.subrow_id {
+            write!(f, ".{subrow_id}")?;
+        }
+        match &self.column {
+            Some(ColumnRef::Index(column)) => write!(f, "C{column}")?,
+            Some(ColumnRef::Name(name)) => write!(f, "/{name}")?,
+            None => {}
+        }
+        Ok(())
+    }
+}
+
+/// `/sheet/{sheet_name}`, scrolled to `row` if given.
+pub fn sheet_path(sheet_name: &str, row: Option<(u32, Option<u16>)>) -> Path {
+    let path = Path::parse(&format!("/sheet/{sheet_name}"));
+    match row {
+        Some((row_id, subrow_id)) => path.with_fragment(RowAnchor {
+            row_id,
+            subrow_id,
+            column: None,
+        }),
+        None => path,
+    }
+}
+
+/// `/sheet/{sheet_name}`, scrolled to `column` of `row`.
+pub fn cell_path(
+    sheet_name: &str,
+    (row_id, subrow_id): (u32, Option<u16>),
+    column: ColumnRef,
+) -> Path {
+    Path::parse(&format!("/sheet/{sheet_name}")).with_fragment(RowAnchor {
+        row_id,
+        subrow_id,
+        column: Some(column),
+    })
+}
+
+/// Setup, going to `redirect` once it's done.
+pub fn setup_path(redirect: impl ToString) -> Path {
+    Path::with_query(
+        "/",
+        &AppQuery {
+            redirect: Some(redirect.to_string()),
+            ..Default::default()
+        },
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use crate::sheet::{ColumnSort, ComplexFilter, FilterInputType, SortDirection};
+
+    use super::{ColumnRef, RowAnchor, SheetQuery, cell_path, sheet_path};
+
+    #[test]
+    fn row_anchors() {
+        let anchor = |row_id, subrow_id, column| RowAnchor {
+            row_id,
+            subrow_id,
+            column,
+        };
+        assert_eq!("R12".parse(), Ok(anchor(12, None, None)));
+        assert_eq!("R12.3".parse(), Ok(anchor(12, Some(3), None)));
+        assert_eq!(
+            "R12.3C4".parse(),
+            Ok(anchor(12, Some(3), Some(ColumnRef::Index(4))))
+        );
+        assert_eq!(
+            "R12C4".parse(),
+            Ok(anchor(12, None, Some(ColumnRef::Index(4))))
+        );
+        assert_eq!("C4".parse::<RowAnchor>(), Err(()));
+        assert_eq!(
+            anchor(12, Some(3), Some(ColumnRef::Index(4))).to_string(),
+            "R12.3C4"
+        );
+        let name = || Some(ColumnRef::Name("ClassJobCategory".to_owned()));
+        assert_eq!("R42/ClassJobCategory".parse(), Ok(anchor(42, None, name())));
+        assert_eq!(
+            "R42.1/ClassJobCategory".parse(),
+            Ok(anchor(42, Some(1), name()))
+        );
+        assert_eq!("R42/".parse(), Ok(anchor(42, None, None)));
+        assert_eq!(
+            cell_path("Item", (42, None), name().unwrap()).to_string(),
+            "/sheet/Item#R42/ClassJobCategory"
+        );
+        assert_eq!(
+            sheet_path("quest/000/ClsArc000_00001", Some((5, Some(1)))).to_string(),
+            "/sheet/quest/000/ClsArc000_00001#R5.1"
+        );
+        assert_eq!(sheet_path("Item", None).to_string(), "/sheet/Item");
+    }
+
+    #[test]
+    fn sheet_queries() {
+        let sorts = [
+            ColumnSort {
+                offset_idx: 3,
+                direction: SortDirection::Ascending,
+            },
+            ColumnSort {
+                offset_idx: 5,
+                direction: SortDirection::Descending,
+            },
+        ];
+        let mut query = SheetQuery {
+            filter: Some("Name ~ \"Potion\"".to_owned()),
+            mode: Some(FilterInputType::Complex),
+            ..Default::default()
+        };
+        query.set_sorts(&sorts);
+        assert_eq!(query.sort.as_deref(), Some("3,-5"));
+
+        let path = sheet_path("Item", Some((5, None))).with_serialized_query(&query);
+        let parsed = path.query_as::<SheetQuery>().unwrap();
+        assert_eq!(parsed.filter, query.filter);
+        assert_eq!(parsed.mode, Some(FilterInputType::Complex));
+        assert_eq!(parsed.sorts(), Some(sorts.to_vec()));
+        assert_eq!(path.fragment(), Some("R5"));
+
+        let unsorted = SheetQuery {
+            sort: Some(String::new()),
+            ..Default::default()
+        };
+        assert_eq!(unsorted.sorts(), Some(vec![]));
+        let invalid = SheetQuery {
+            sort: Some("3,x".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(invalid.sorts(), None);
+        assert_eq!(SheetQuery::default().sorts(), None);
+    }
+
+    #[test]
+    fn sheet_query_rows() {
+        let query = |rows: &str, filter: Option<&str>, mode| SheetQuery {
+            rows: Some(rows.to_owned()),
+            filter: filter.map(str::to_owned),
+            mode,
+            ..Default::default()
+        };
+        let complex = |filter: &str| Some((FilterInputType::Complex, filter.to_owned()));
+
+        assert_eq!(query("10..20", None, None).filter(), complex("# |= 10..20"));
+        assert_eq!(query("..20", None, None).filter(), complex("# |= ..20"));
+        assert_eq!(query("10", None, None).filter(), complex("# |= 10"));
+        assert_eq!(
+            query("10..", Some("Level > 5"), Some(FilterInputType::Complex)).filter(),
+            complex("# |= 10.. && (Level > 5)")
+        );
+        assert_eq!(
+            query("10..20", Some("Po\"tion"), None).filter(),
+            complex("# |= 10..20 && * *= \"Po\\\"tion\"")
+        );
+        assert_eq!(
+            query("10..20", Some("Potion"), Some(FilterInputType::Equals)).filter(),
+            complex("# |= 10..20 && * = \"Potion\"")
+        );
+        for filter in [
+            "# |= 10..20",
+            "# |= 10.. && (Level > 5)",
+            "# |= 10..20 && * *= \"Po\\\"tion\"",
+        ] {
+            assert!(filter.parse::<ComplexFilter>().is_ok(), "{filter}");
+        }
+
+        for rows in ["", "..", "20..10", "010", "-5", "a..b"] {
+            assert_eq!(
+                query(rows, Some("Potion"), None).filter(),
+                Some((FilterInputType::Contains, "Potion".to_owned())),
+                "{rows}"
+            );
+        }
+        assert_eq!(SheetQuery::default().filter(), None);
+    }
+}
@@ -0,0 +1,362 @@
+use compact_str::ToCompactString;
+use egui::{Color32, Grid, RichText, TextEdit};
+use serde_json::{Map, Value};
+
+use crate::{
+    excel::provider::ExcelSheet,
+    i18n::{Text, tr},
+    settings::TEMP_HIGHLIGHTED_ROW,
+    sheet::{CellValue, PathSegment, TableContext},
+};
+
+/// Relative tolerance when comparing floats, since exports rarely keep every digit.
+const FLOAT_TOLERANCE: f64 = 1e-5;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Match,
+    Mismatch,
+    /// The pasted object has no key for this column.
+    Missing,
+}
+
+struct ColumnResult {
+    name: String,
+    expected: Option<String>,
+    actual: String,
+    status: Status,
+}
+
+/// Matches the keys of a pasted JSON object against the schema columns of a row and
+/// highlights the values that differ. Arrays and nested fields are looked up with the same
+/// `Foo[2].Bar` paths the JSON export writes.
+pub fn draw(ctx: &egui::Context, table: &TableContext, open: &mut bool) {
+    egui::Window::new(format!(
+        "{} – {}",
+        tr(ctx, Text::CompareRowJson),
+        table.sheet().name()
+    ))
+    .id(egui::Id::new("row-compare-window"))
+    .open(open)
+    .default_width(480.0)
+    .default_height(520.0)
+    .show(ctx, |ui| {
+        let text_id = ui.id().with("text");
+        let mismatches_id = ui.id().with("mismatches-only");
+        let mut text = ui.data(|d| d.get_temp::<String>(text_id).unwrap_or_default());
+        let mut mismatches_only =
+            ui.data(|d| d.get_temp::<bool>(mismatches_id).unwrap_or_default());
+
+        egui::ScrollArea::vertical()
+            .id_salt("row-compare-input")
+            .max_height(160.0)
+            .show(ui, |ui| {
+                if ui
+                    .add(
+                        TextEdit::multiline(&mut text)
+                            .code_editor()
+                            .hint_text(tr(ctx, Text::PasteJsonHint))
+                            .desired_rows(6)
+                            .desired_width(f32::INFINITY),
+                    )
+                    .changed()
+                {
+                    ui.data_mut(|d| d.insert_temp(text_id, text.clone()));
+                }
+            });
+        if ui
+            .checkbox(&mut mismatches_only, tr(ctx, Text::OnlyMismatches))
+            .changed()
+        {
+            ui.data_mut(|d| d.insert_temp(mismatches_id, mismatches_only));
+        }
+        ui.separator();
+
+        if text.trim().is_empty() {
+            return;
+        }
+        let root: Value = match serde_json::from_str(&text) {
+            Ok(root) => root,
+            Err(e) => {
+                ui.colored_label(ui.visuals().error_fg_color, e.to_string());
+                return;
+            }
+        };
+
+        let selected = TEMP_HIGHLIGHTED_ROW.try_get(ctx);
+        let Some(object) = pick_object(&root, selected) else {
+            ui.colored_label(ui.visuals().error_fg_color, tr(ctx, Text::JsonNotAnObject));
+            return;
+        };
+        let Some((row_id, subrow_id)) = selected.or_else(|| object_row(object)) else {
+            ui.label(RichText::new(tr(ctx, Text::SelectRowToCompare)).weak());
+            return;
+        };
+
+        let row = match subrow_id {
+            Some(subrow_id) => table.sheet().get_subrow(row_id, subrow_id),
+            None => table.sheet().get_row(row_id),
+        };
+        let row = match row {
+            Ok(row) => row,
+            Err(e) => {
+                ui.colored_label(ui.visuals().error_fg_color, e.to_string());
+                return;
+            }
+        };
+
+        let mut results = Vec::with_capacity(table.column_count());
+        let mut consumed = Vec::new();
+        for column_idx in 0..table.column_count() as u32 {
+            let Ok(((column, _), offset_idx)) = table.get_column_by_index(column_idx) else {
+                continue;
+            };
+            let actual = table
+                .cell_by_offset(row, offset_idx)
+                .and_then(|cell| cell.read(false));
+            let (expected, path) = lookup(object, &column.path()).unzip();
+
+            let (actual, status) = match (&actual, expected) {
+                (Err(e), _) => (e.to_string(), Status::Mismatch),
+                (Ok(value), None) => (value.coerce_string().into(), Status::Missing),
+                (Ok(value), Some(expected)) => (
+                    value.coerce_string().into(),
+                    if matches(value, expected) {
+                        Status::Match
+                    } else {
+                        Status::Mismatch
+                    },
+                ),
+            };
+            results.push(ColumnResult {
+                name: column.name().to_owned(),
+                expected: expected.map(|expected| match expected {
+                    Value::String(s) => s.clone(),
+                    value => value.to_string(),
+                }),
+                actual,
+                status,
+            });
+            consumed.extend(path);
+        }
+
+        let mut leaves = Vec::new();
+        collect_leaves(object, String::new(), &mut leaves);
+        let unknown = leaves
+            .into_iter()
+            .filter(|leaf| !consumed.iter().any(|path| is_within(leaf, path)))
+            .collect::<Vec<_>>();
+
+        let count = |status| results.iter().filter(|r| r.status == status).count();
+        ui.label(format!(
+            "{}{} – {} ✔  {} ✘  {} {}  {} {}",
+            row_id,
+            subrow_id.map(|s| format!(".{s}")).unwrap_or_default(),
+            count(Status::Match),
+            count(Status::Mismatch),
+            count(Status::Missing),
+            tr(ctx, Text::MissingKeys),
+            unknown.len(),
+            tr(ctx, Text::UnknownKeys),
+        ));
+
+        egui::ScrollArea::vertical()
+            .id_salt("row-compare-results")
+            .show(ui, |ui| {
+                Grid::new("row-compare-grid").striped(true).show(ui, |ui| {
+                    ui.strong(tr(ctx, Text::Column));
+                    ui.strong(tr(ctx, Text::Pasted));
+                    ui.strong(tr(ctx, Text::Sheet));
+                    ui.end_row();
+
+                    for result in &results {
+                        if mismatches_only && result.status == Status::Match {
+                            continue;
+                        }
+                        let color = match result.status {
+                            Status::Match => ui.visuals().text_color(),
+                            Status::Mismatch => ui.visuals().error_fg_color,
+                            Status::Missing => ui.visuals().weak_text_color(),
+                        };
+                        ui.label(RichText::new(&result.name).color(color));
+                        match &result.expected {
+                            Some(expected) => ui.label(RichText::new(expected).color(color)),
+                            None => ui.label(RichText::new("—").weak()),
+                        };
+                        ui.label(RichText::new(&result.actual).color(color));
+                        ui.end_row();
+                    }
+                });
+
+                if !unknown.is_empty() {
+                    ui.separator();
+                    ui.label(RichText::new(tr(ctx, Text::UnknownKeys)).strong());
+                    for key in &unknown {
+                        ui.label(RichText::new(key).monospace().color(Color32::ORANGE));
+                    }
+                }
+            });
+    });
+}
+
+/// Picks the object to compare: the object itself, or the array element whose `#` (and
+/// `#subrow`) matches the selected row, falling back to the first element.
+fn pick_object(root: &Value, selected: Option<(u32, Option<u16>)>) -> Option<&Map<String, Value>> {
+    match root {
+        Value::Object(object) => Some(object),
+        Value::Array(items) => {
+            let mut objects = items.iter().filter_map(Value::as_object);
+            let first = objects.clone().next();
+            selected
+                .and_then(|selected| objects.find(|o| object_row(o) == Some(selected)))
+                .or(first)
+        }
+        _ => None,
+    }
+}
+
+/// The row an object was exported from, as written by the JSON export.
+fn object_row(object: &Map<String, Value>) -> Option<(u32, Option<u16>)> {
+    let row_id = object.get("#")?.as_u64()?.try_into().ok()?;
+    let subrow_id = object
+        .get("#subrow")
+        .and_then(Value::as_u64)
+        .and_then(|s| s.try_into().ok());
+    Some((row_id, subrow_id))
+}
+
+/// Finds the value at a column's path, returning it along with the path as spelled in the
+/// pasted object. Keys are matched case-insensitively if there's no exact match.
+fn lookup<'a>(
+    object: &'a Map<String, Value>,
+    path: &[PathSegment<'_>],
+) -> Option<(&'a Value, String)> {
+    let mut current = None::<&Value>;
+    let mut spelled = String::new();
+    for segment in path {
+        match segment {
+            PathSegment::Key(key) => {
+                let object = match current {
+                    None => object,
+                    Some(value) => value.as_object()?,
+                };
+                let (key, value) = object
+                    .get_key_value(*key)
+                    .or_else(|| object.iter().find(|(k, _)| k.eq_ignore_ascii_case(key)))?;
+                if !spelled.is_empty() {
+                    spelled.push('.');
+                }
+                spelled.push_str(key);
+                current = Some(value);
+            }
+            PathSegment::Index(index) => {
+                current = Some(current?.as_array()?.get(*index)?);
+                spelled.push_str(&format!("[{index}]"));
+            }
+        }
+    }
+    current.map(|value| (value, spelled))
+}
+
+fn collect_leaves(object: &Map<String, Value>, prefix: String, leaves: &mut Vec<String>) {
+    for (key, value) in object {
+        if prefix.is_empty() && (key == "#" || key == "#subrow") {
+            continue;
+        }
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        collect_value_leaves(value, path, leaves);
+    }
+}
+
+fn collect_value_leaves(value: &Value, path: String, leaves: &mut Vec<String>) {
+    match value {
+        // Expanded links are compared by their row id
+        Value::Object(object) if object.contains_key("row_id") => leaves.push(path),
+        Value::Object(object) => collect_leaves(object, path, leaves),
+        Value::Array(items) => {
+            for (idx, item) in items.iter().enumerate() {
+                collect_value_leaves(item, format!("{path}[{idx}]"), leaves);
+            }
+        }
+        _ => leaves.push(path),
+    }
+}
+
+/// Whether `leaf` is `path` itself or nested inside of it.
+fn is_within(leaf: &str, path: &str) -> bool {
+    leaf.strip_prefix(path)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(['.', '[']))
+}
+
+fn matches(value: &CellValue, expected: &Value) -> bool {
+    match expected {
+        Value::Null => value.is_empty(),
+        Value::Bool(b) => value.coerce_integer() == Some(i128::from(*b)),
+        Value::Number(n) => match value {
+            CellValue::Float(f) => n
+                .as_f64()
+                .is_some_and(|n| (f64::from(*f) - n).abs() <= FLOAT_TOLERANCE * n.abs().max(1.0)),
+            _ => {
+                let expected = n
+                    .as_i64()
+                    .map(i128::from)
+                    .or_else(|| n.as_u64().map(i128::from))
+                    .or_else(|| n.as_f64().filter(|f| f.fract() == 0.0).map(|f| f as i128));
+                expected.is_some() && expected == value.coerce_integer()
+            }
+        },
+        Value::String(s) => match value {
+            CellValue::String(text) => {
+                text.macro_string()
+                    .try_to_compact_string()
+                    .is_ok_and(|t| t == s.as_str())
+                    || text
+                        .format()
+                        .try_to_compact_string()
+                        .is_ok_and(|t| t == s.as_str())
+            }
+            _ => {
+                value.coerce_string() == s.as_str()
+                    || s.parse::<i128>()
+                        .is_ok_and(|i| value.coerce_integer() == Some(i))
+            }
+        },
+        // Expanded links, either from this viewer's export or other tools
+        Value::Object(object) => object
+            .get("row_id")
+            .or_else(|| object.get("value"))
+            .is_some_and(|v| matches(value, v)),
+        Value::Array(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::{is_within, lookup};
+    use crate::sheet::PathSegment::{Index, Key};
+
+    #[test]
+    fn lookup_paths() {
+        let value = json!({
+            "Name": "a",
+            "params": [{ "Value": 1 }, { "Value": 2 }],
+        });
+        let object = value.as_object().unwrap();
+        assert_eq!(
+            lookup(object, &[Key("Params"), Index(1), Key("Value")]),
+            Some((&json!(2), "params[1].Value".to_owned()))
+        );
+        assert_eq!(
+            lookup(object, &[Key("Params"), Index(2), Key("Value")]),
+            None
+        );
+        assert!(is_within("params[1].Value", "params[1]"));
+        assert!(!is_within("params10", "params1"));
+    }
+}
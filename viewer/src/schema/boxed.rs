@@ -19,6 +19,10 @@ impl BoxedSchemaProvider {
         )
     }
 
+    pub fn new_boxed(value: Box<dyn SchemaProvider>, size: std::num::NonZeroUsize) -> Self {
+        CachedProvider::new(value, size)
+    }
+
     pub fn new_web(value: super::web::WebProvider) -> Self {
         CachedProvider::new(
             Box::new(value) as Box<dyn SchemaProvider>,
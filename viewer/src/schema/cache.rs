@@ -2,7 +2,7 @@ use std::{cell::RefCell, num::NonZeroUsize, sync::Arc};
 
 use async_trait::async_trait;
 
-use crate::utils::{CloneableResult, SharedFuture};
+use crate::utils::{ProviderResult, SharedFuture};
 
 use super::provider::SchemaProvider;
 
@@ -16,7 +16,7 @@ impl<T: SchemaProvider + 'static> Clone for CachedProvider<T> {
 
 pub struct CachedProviderImpl<T: SchemaProvider + 'static> {
     provider: T,
-    cache: RefCell<lru::LruCache<String, SharedFuture<CloneableResult<String>>>>,
+    cache: RefCell<lru::LruCache<String, SharedFuture<ProviderResult<String>>>>,
 }
 
 impl<T: SchemaProvider + 'static> CachedProvider<T> {
@@ -26,12 +26,16 @@ impl<T: SchemaProvider + 'static> CachedProvider<T> {
             cache: RefCell::new(lru::LruCache::new(size)),
         }))
     }
+
+    pub fn clear_cache(&self) {
+        self.0.cache.borrow_mut().clear();
+    }
 }
 
 #[async_trait(?Send)]
 impl<T: SchemaProvider + 'static> SchemaProvider for CachedProvider<T> {
-    async fn get_schema_text(&self, name: &str) -> anyhow::Result<String> {
-        let future: SharedFuture<CloneableResult<String>>;
+    async fn get_schema_text(&self, name: &str) -> ProviderResult<String> {
+        let future: SharedFuture<ProviderResult<String>>;
         {
             let mut cache = self.0.cache.borrow_mut();
             future = if let Some(future) = cache.get(name) {
@@ -40,13 +44,13 @@ impl<T: SchemaProvider + 'static> SchemaProvider for CachedProvider<T> {
                 let this = self.clone();
                 let future_name = name.to_owned();
                 let future = SharedFuture::new(async move {
-                    Ok(this.0.provider.get_schema_text(&future_name).await?)
+                    this.0.provider.get_schema_text(&future_name).await
                 });
                 cache.put(name.to_string(), future.clone());
                 future
             };
         }
-        future.into_shared().await.map_err(|e| e.into())
+        future.into_shared().await
     }
 
     fn can_save_schemas(&self) -> bool {
@@ -57,7 +61,7 @@ impl<T: SchemaProvider + 'static> SchemaProvider for CachedProvider<T> {
         self.0.provider.save_schema_start_dir()
     }
 
-    async fn save_schema(&self, name: &str, text: &str) -> anyhow::Result<()> {
+    async fn save_schema(&self, name: &str, text: &str) -> ProviderResult<()> {
         self.0.provider.save_schema(name, text).await?;
         self.0.cache.borrow_mut().pop(name);
         Ok(())
@@ -66,7 +70,7 @@ impl<T: SchemaProvider + 'static> SchemaProvider for CachedProvider<T> {
 
 #[async_trait(?Send)]
 impl SchemaProvider for Box<dyn SchemaProvider> {
-    async fn get_schema_text(&self, name: &str) -> anyhow::Result<String> {
+    async fn get_schema_text(&self, name: &str) -> ProviderResult<String> {
         self.as_ref().get_schema_text(name).await
     }
 
@@ -78,7 +82,7 @@ impl SchemaProvider for Box<dyn SchemaProvider> {
         self.as_ref().save_schema_start_dir()
     }
 
-    async fn save_schema(&self, name: &str, text: &str) -> anyhow::Result<()> {
+    async fn save_schema(&self, name: &str, text: &str) -> ProviderResult<()> {
         self.as_ref().save_schema(name, text).await
     }
 }
@@ -18,6 +18,23 @@ pub struct Schema {
     pub fields: Vec<Field>,
     #[serde(skip_serializing_if = "is_default")]
     pub relations: Option<HashMap<String, Vec<String>>>,
+    /// Columns holding world positions, shown as the coordinates on the game's maps.
+    #[serde(skip_serializing_if = "is_default")]
+    pub coordinates: Option<Vec<MapCoordinates>>,
+}
+
+/// A pair of columns holding a world position.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MapCoordinates {
+    pub x: String,
+    pub y: String,
+    /// Column linking to the Map sheet, whose size factor and offsets are used.
+    #[serde(skip_serializing_if = "is_default")]
+    pub map: Option<String>,
+    /// Size factor to use without a map column. 100 if unset.
+    #[serde(skip_serializing_if = "is_default")]
+    pub size_factor: Option<u16>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
@@ -54,6 +71,29 @@ pub enum FieldType {
     Color,
 }
 
+impl FieldType {
+    pub const ALL: &[Self] = &[
+        Self::Scalar,
+        Self::Link,
+        Self::Array,
+        Self::Icon,
+        Self::ModelId,
+        Self::Color,
+    ];
+
+    /// The type's name as written in schema files.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Scalar => "scalar",
+            Self::Link => "link",
+            Self::Array => "array",
+            Self::Icon => "icon",
+            Self::ModelId => "modelId",
+            Self::Color => "color",
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Condition {
@@ -2,6 +2,8 @@ use std::{path::PathBuf, str::FromStr};
 
 use async_trait::async_trait;
 
+use crate::utils::ProviderResult;
+
 use super::provider::SchemaProvider;
 
 pub struct LocalProvider {
@@ -18,7 +20,7 @@ impl LocalProvider {
 
 #[async_trait(?Send)]
 impl SchemaProvider for LocalProvider {
-    async fn get_schema_text(&self, name: &str) -> anyhow::Result<String> {
+    async fn get_schema_text(&self, name: &str) -> ProviderResult<String> {
         Ok(std::fs::read_to_string(
             self.base_path.join(format!("{name}.yml")),
         )?)
@@ -32,7 +34,7 @@ impl SchemaProvider for LocalProvider {
         Some(self.base_path.clone())
     }
 
-    async fn save_schema(&self, name: &str, text: &str) -> anyhow::Result<()> {
+    async fn save_schema(&self, name: &str, text: &str) -> ProviderResult<()> {
         std::fs::write(self.base_path.join(format!("{name}.yml")), text)?;
         Ok(())
     }
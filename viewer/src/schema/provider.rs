@@ -2,13 +2,15 @@ use std::path::PathBuf;
 
 use async_trait::async_trait;
 
+use crate::utils::ProviderResult;
+
 #[async_trait(?Send)]
 pub trait SchemaProvider {
-    async fn get_schema_text(&self, name: &str) -> anyhow::Result<String>;
+    async fn get_schema_text(&self, name: &str) -> ProviderResult<String>;
 
     fn can_save_schemas(&self) -> bool;
 
     fn save_schema_start_dir(&self) -> Option<PathBuf>;
 
-    async fn save_schema(&self, name: &str, text: &str) -> anyhow::Result<()>;
+    async fn save_schema(&self, name: &str, text: &str) -> ProviderResult<()>;
 }
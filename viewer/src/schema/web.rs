@@ -6,7 +6,7 @@ use serde::Deserialize;
 
 use crate::{
     settings::{GithubSchemaBranch, GithubSchemaLocation},
-    utils::{GameVersion, fetch_url, fetch_url_str},
+    utils::{GameVersion, ProviderError, ProviderResult, fetch_url, fetch_url_limited},
 };
 
 use super::provider::SchemaProvider;
@@ -58,6 +58,18 @@ pub struct GithubPullRequestFile {
     pub filename: String,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub struct GithubTree {
+    pub tree: Vec<GithubTreeEntry>,
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GithubTreeEntry {
+    pub path: String,
+    pub r#type: String,
+}
+
 impl WebProvider {
     pub fn new(base_url: String) -> Self {
         WebProvider { base_url }
@@ -170,12 +182,44 @@ impl WebProvider {
 
         Ok(ret)
     }
+
+    /// Lists the names of every schema in the location's branch, without the `.yml` suffix.
+    pub async fn fetch_github_schema_names(
+        location: &GithubSchemaLocation,
+    ) -> anyhow::Result<Vec<String>> {
+        let (full_name, branch) = location.repository();
+        if !full_name.split('/').all(Self::is_valid_github_name) {
+            return Err(anyhow::anyhow!("Invalid GitHub repository format"));
+        }
+
+        // The trees endpoint can't resolve branch names with slashes (e.g. `ver/7.0`)
+        let url = format!("https://api.github.com/repos/{full_name}/branches/{branch}");
+        let branch: GithubBranch = serde_json::from_slice(&fetch_url(url).await?)?;
+
+        let url = format!(
+            "https://api.github.com/repos/{full_name}/git/trees/{}?recursive=1",
+            branch.commit.sha
+        );
+        let tree: GithubTree = serde_json::from_slice(&fetch_url(url).await?)?;
+        if tree.truncated {
+            log::warn!("Schema listing for {full_name} was truncated; some schemas are missing");
+        }
+
+        Ok(tree
+            .tree
+            .into_iter()
+            .filter(|entry| entry.r#type == "blob")
+            .filter(|entry| !entry.path.split('/').any(|c| c.starts_with('.')))
+            .filter_map(|entry| entry.path.strip_suffix(".yml").map(str::to_string))
+            .collect_vec())
+    }
 }
 
 #[async_trait(?Send)]
 impl SchemaProvider for WebProvider {
-    async fn get_schema_text(&self, name: &str) -> anyhow::Result<String> {
-        fetch_url_str(format!("{}/{name}.yml", self.base_url)).await
+    async fn get_schema_text(&self, name: &str) -> ProviderResult<String> {
+        let bytes = fetch_url_limited(format!("{}/{name}.yml", self.base_url)).await?;
+        String::from_utf8(bytes).map_err(|e| ProviderError::Corrupt(e.to_string()))
     }
 
     fn can_save_schemas(&self) -> bool {
@@ -186,7 +230,7 @@ impl SchemaProvider for WebProvider {
         None
     }
 
-    async fn save_schema(&self, _name: &str, _text: &str) -> anyhow::Result<()> {
+    async fn save_schema(&self, _name: &str, _text: &str) -> ProviderResult<()> {
         unreachable!("Saving schemas is not supported by this provider");
     }
 }
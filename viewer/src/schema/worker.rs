@@ -2,6 +2,7 @@ use async_trait::async_trait;
 
 use crate::{
     backend::worker,
+    utils::{ProviderError, ProviderResult},
     worker::{WorkerDirectory, WorkerRequest, WorkerResponse},
 };
 
@@ -40,6 +41,21 @@ impl WorkerProvider {
         }
     }
 
+    /// Writes `files` into a fresh `folder` in the origin private file system and registers
+    /// it as a schema folder.
+    pub async fn store_snapshot(
+        folder: String,
+        files: Vec<(String, String)>,
+    ) -> anyhow::Result<()> {
+        match worker::transact(WorkerRequest::SchemaSnapshot((folder, files))).await {
+            WorkerResponse::SchemaSnapshot(Ok(())) => Ok(()),
+            WorkerResponse::SchemaSnapshot(Err(e)) => Err(anyhow::anyhow!(
+                "WorkerProvider: failed to store schema snapshot: {e}"
+            )),
+            _ => Err(anyhow::anyhow!("WorkerProvider: invalid schema response")),
+        }
+    }
+
     pub async fn verify_folder(handle: WorkerDirectory) -> anyhow::Result<()> {
         match worker::transact(WorkerRequest::VerifyFolder((handle, true))).await {
             WorkerResponse::VerifyFolder(Ok(())) => Ok(()),
@@ -53,14 +69,21 @@ impl WorkerProvider {
 
 #[async_trait(?Send)]
 impl SchemaProvider for WorkerProvider {
-    async fn get_schema_text(&self, name: &str) -> anyhow::Result<String> {
+    async fn get_schema_text(&self, name: &str) -> ProviderResult<String> {
         log::info!("WorkerProvider: requesting schema {name:?}");
         if let WorkerResponse::SchemaRequestGet(result) =
             worker::transact(WorkerRequest::SchemaRequestGet(format!("{name}.yml"))).await
         {
-            result.map_err(|e| anyhow::anyhow!("WorkerProvider: failed to get schema: {e}"))
+            result.map_err(|e| {
+                ProviderError::from_worker(
+                    format!("WorkerProvider: failed to get schema: {e}"),
+                    ProviderError::NotFound,
+                )
+            })
         } else {
-            return Err(anyhow::anyhow!("WorkerProvider: invalid schema response"));
+            return Err(ProviderError::Corrupt(
+                "WorkerProvider: invalid schema response".to_string(),
+            ));
         }
     }
 
@@ -72,16 +95,23 @@ impl SchemaProvider for WorkerProvider {
         None
     }
 
-    async fn save_schema(&self, name: &str, text: &str) -> anyhow::Result<()> {
+    async fn save_schema(&self, name: &str, text: &str) -> ProviderResult<()> {
         log::info!("WorkerProvider: saving schema {name:?}");
         if let WorkerResponse::SchemaRequestStore(result) = worker::transact(
             WorkerRequest::SchemaRequestStore((format!("{name}.yml"), text.to_string())),
         )
         .await
         {
-            result.map_err(|e| anyhow::anyhow!("WorkerProvider: failed to save schema: {e}"))
+            result.map_err(|e| {
+                ProviderError::from_worker(
+                    format!("WorkerProvider: failed to save schema: {e}"),
+                    ProviderError::Corrupt,
+                )
+            })
         } else {
-            return Err(anyhow::anyhow!("WorkerProvider: invalid schema response"));
+            return Err(ProviderError::Corrupt(
+                "WorkerProvider: invalid schema response".to_string(),
+            ));
         }
     }
 }
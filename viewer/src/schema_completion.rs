@@ -0,0 +1,208 @@
+use itertools::Itertools;
+
+use crate::schema::FieldType;
+
+const MAX_SUGGESTIONS: usize = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionKind {
+    /// The value of a `type:` key.
+    FieldType,
+    /// An entry of a `targets:` list or of a condition's `cases:`.
+    SheetName,
+}
+
+/// What the token under the cursor of a schema's YAML text could be completed with.
+#[derive(Debug, PartialEq, Eq)]
+pub struct CompletionContext<'a> {
+    pub kind: CompletionKind,
+    /// Char index where the partially typed token starts.
+    pub token_start: usize,
+    pub prefix: &'a str,
+}
+
+/// Finds what can be completed at the char index `cursor` of `text`, if anything.
+pub fn context_at(text: &str, cursor: usize) -> Option<CompletionContext<'_>> {
+    let cursor_byte = text
+        .char_indices()
+        .nth(cursor)
+        .map_or(text.len(), |(idx, _)| idx);
+    let line_start = text[..cursor_byte].rfind('\n').map_or(0, |idx| idx + 1);
+    let line = &text[line_start..cursor_byte];
+
+    let prefix_start = line
+        .char_indices()
+        .rev()
+        .take_while(|&(_, c)| is_token_char(c))
+        .last()
+        .map_or(line.len(), |(idx, _)| idx);
+    let prefix = &line[prefix_start..];
+    let before = &line[..prefix_start];
+    let token_start = cursor - prefix.chars().count();
+
+    let trimmed = before.trim_start();
+    let kind = if trimmed.strip_prefix("- ").unwrap_or(trimmed).trim_end() == "type:" {
+        CompletionKind::FieldType
+    } else if is_inline_list_item(trimmed) || is_block_list_item(text, line_start, before) {
+        CompletionKind::SheetName
+    } else {
+        return None;
+    };
+
+    Some(CompletionContext {
+        kind,
+        token_start,
+        prefix,
+    })
+}
+
+/// Candidates matching `prefix`, starting with those that begin with it. Nothing is suggested
+/// if the token already is the only match.
+pub fn suggestions<'a>(
+    kind: CompletionKind,
+    prefix: &str,
+    sheet_names: impl Iterator<Item = &'a str>,
+) -> Vec<String> {
+    let candidates = match kind {
+        CompletionKind::FieldType => FieldType::ALL.iter().map(FieldType::name).collect_vec(),
+        CompletionKind::SheetName => sheet_names.collect_vec(),
+    };
+    let lower_prefix = prefix.to_lowercase();
+    let mut ranked = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let lower = candidate.to_lowercase();
+            let rank = if lower.starts_with(&lower_prefix) {
+                0
+            } else if lower.contains(&lower_prefix) {
+                1
+            } else {
+                return None;
+            };
+            Some((rank, candidate.len(), candidate))
+        })
+        .collect_vec();
+    ranked.sort_unstable();
+
+    let items = ranked
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, _, candidate)| candidate.to_owned())
+        .collect_vec();
+    if items.len() == 1 && items[0] == prefix {
+        return Vec::new();
+    }
+    items
+}
+
+fn is_token_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '/'
+}
+
+/// `targets: [Item, Ev` or `1: [Quest, `
+fn is_inline_list_item(trimmed: &str) -> bool {
+    let trimmed = trimmed.strip_prefix("- ").unwrap_or(trimmed);
+    let Some((key, value)) = trimmed.split_once(':') else {
+        return false;
+    };
+    let value = value.trim();
+    is_sheet_list_key(key)
+        && value.starts_with('[')
+        && !value.contains(']')
+        && (value.ends_with('[') || value.ends_with(','))
+}
+
+/// A `- ` entry below a `targets:` or case key.
+fn is_block_list_item(text: &str, line_start: usize, before: &str) -> bool {
+    if before.trim() != "-" {
+        return false;
+    }
+    let indent = before.len() - before.trim_start().len();
+    text[..line_start]
+        .lines()
+        .rev()
+        .filter(|line| !line.trim().is_empty())
+        .find(|line| {
+            // Skip earlier items of the same list
+            let line_indent = line.len() - line.trim_start().len();
+            !(line_indent == indent && line.trim_start().starts_with('-'))
+        })
+        .and_then(|line| {
+            let line = line.trim();
+            line.strip_prefix("- ").unwrap_or(line).strip_suffix(':')
+        })
+        .is_some_and(is_sheet_list_key)
+}
+
+fn is_sheet_list_key(key: &str) -> bool {
+    let key = key.trim();
+    key == "targets" || key.parse::<i32>().is_ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CompletionContext, CompletionKind, context_at, suggestions};
+
+    fn kind_at_end(text: &str) -> Option<(CompletionKind, &str)> {
+        context_at(text, text.chars().count()).map(|c| (c.kind, c.prefix))
+    }
+
+    #[test]
+    fn contexts() {
+        assert_eq!(
+            kind_at_end("  - name: Foo\n    type: li"),
+            Some((CompletionKind::FieldType, "li"))
+        );
+        assert_eq!(
+            kind_at_end("  - type: "),
+            Some((CompletionKind::FieldType, ""))
+        );
+        assert_eq!(
+            kind_at_end("    targets: [Item, Ev"),
+            Some((CompletionKind::SheetName, "Ev"))
+        );
+        assert_eq!(
+            kind_at_end("    targets:\n      - Item\n      - Qu"),
+            Some((CompletionKind::SheetName, "Qu"))
+        );
+        assert_eq!(
+            kind_at_end("      cases:\n        1: [Quest, Cus"),
+            Some((CompletionKind::SheetName, "Cus"))
+        );
+        assert_eq!(kind_at_end("    targets: [Item]"), None);
+        assert_eq!(kind_at_end("  - name: Ty"), None);
+        assert_eq!(kind_at_end("fields:\n  - Fo"), None);
+        assert_eq!(
+            context_at("type: ico\n", 9),
+            Some(CompletionContext {
+                kind: CompletionKind::FieldType,
+                token_start: 6,
+                prefix: "ico"
+            })
+        );
+    }
+
+    #[test]
+    fn ranking() {
+        let sheets = ["ItemAction", "Item", "EventItem", "Quest"];
+        assert_eq!(
+            suggestions(CompletionKind::SheetName, "item", sheets.into_iter()),
+            vec!["Item", "ItemAction", "EventItem"]
+        );
+        assert!(suggestions(CompletionKind::SheetName, "Quest", sheets.into_iter()).is_empty());
+        assert_eq!(
+            suggestions(CompletionKind::FieldType, "m", std::iter::empty()),
+            vec!["modelId"]
+        );
+    }
+
+    #[test]
+    fn field_type_names() {
+        for field_type in crate::schema::FieldType::ALL {
+            assert_eq!(
+                serde_yml::from_str::<crate::schema::FieldType>(field_type.name()).ok(),
+                Some(field_type.clone())
+            );
+        }
+    }
+}
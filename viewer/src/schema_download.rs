@@ -0,0 +1,160 @@
+use std::{cell::Cell, rc::Rc};
+
+use egui::{ProgressBar, RichText};
+use futures_util::{StreamExt, TryStreamExt};
+
+use crate::{
+    i18n::{Text, tr},
+    schema::web::WebProvider,
+    settings::{GithubSchemaLocation, SchemaLocation},
+    utils::{PromiseKind, TrackedPromise, fetch_url_str},
+};
+
+const CONCURRENT_DOWNLOADS: usize = 16;
+
+type DownloadPromise = TrackedPromise<anyhow::Result<Option<(SchemaLocation, usize)>>>;
+
+/// Snapshots every schema of a GitHub schema source into a local folder (native) or the
+/// origin private file system (web), so later sessions can read schemas without a network.
+pub struct SchemaDownloadWindow {
+    source: GithubSchemaLocation,
+    /// (downloaded, total)
+    progress: Rc<Cell<(usize, usize)>>,
+    promise: Option<DownloadPromise>,
+    result: Option<Result<(SchemaLocation, usize), String>>,
+}
+
+impl SchemaDownloadWindow {
+    pub fn new(source: GithubSchemaLocation) -> Self {
+        let progress = Rc::new(Cell::new((0, 0)));
+        let promise = TrackedPromise::spawn_local(download(source.clone(), progress.clone()));
+        Self {
+            source,
+            progress,
+            promise: Some(promise),
+            result: None,
+        }
+    }
+
+    /// Returns the downloaded copy's location if the user chose to switch to it. `open` is
+    /// cleared when the window is closed or the folder picker was cancelled.
+    pub fn draw(&mut self, ctx: &egui::Context, open: &mut bool) -> Option<SchemaLocation> {
+        if let Some(promise) = self.promise.take() {
+            match promise.try_take() {
+                Ok(Ok(Some(result))) => self.result = Some(Ok(result)),
+                Ok(Ok(None)) => {
+                    *open = false;
+                    return None;
+                }
+                Ok(Err(e)) => {
+                    log::error!("Failed to download schemas: {e:?}");
+                    self.result = Some(Err(e.to_string()));
+                }
+                Err(promise) => self.promise = Some(promise),
+            }
+        }
+
+        let mut ret = None;
+        let mut is_open = *open;
+        egui::Window::new(tr(ctx, Text::DownloadSchemas))
+            .id(egui::Id::new("schema-download-window"))
+            .open(&mut is_open)
+            .collapsible(false)
+            .resizable(false)
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                let (full_name, branch) = self.source.repository();
+                ui.label(RichText::new(format!("{full_name} @ {branch}")).monospace());
+                ui.add_space(4.0);
+
+                match &self.result {
+                    None => {
+                        let (done, total) = self.progress.get();
+                        if total == 0 {
+                            ui.horizontal(|ui| {
+                                ui.spinner();
+                                ui.label(tr(ctx, Text::ListingSchemas));
+                            });
+                        } else {
+                            ui.add(
+                                ProgressBar::new(done as f32 / total as f32)
+                                    .text(format!("{done} / {total}")),
+                            );
+                        }
+                    }
+                    Some(Err(e)) => {
+                        ui.colored_label(ui.visuals().error_fg_color, e);
+                    }
+                    Some(Ok((location, count))) => {
+                        ui.label(format!("{} {count}", tr(ctx, Text::SchemasDownloaded)));
+                        ui.label(tr(ctx, Text::SwitchToDownloadedSchemas));
+                        ui.horizontal(|ui| {
+                            if ui.button(tr(ctx, Text::Switch)).clicked() {
+                                ret = Some(location.clone());
+                            }
+                            if ui.button(tr(ctx, Text::KeepCurrent)).clicked() {
+                                *open = false;
+                            }
+                        });
+                    }
+                }
+            });
+
+        *open &= is_open && ret.is_none();
+        ret
+    }
+}
+
+async fn download(
+    source: GithubSchemaLocation,
+    progress: Rc<Cell<(usize, usize)>>,
+) -> anyhow::Result<Option<(SchemaLocation, usize)>> {
+    #[cfg(not(target_arch = "wasm32"))]
+    let Some(folder) = rfd::AsyncFileDialog::new()
+        .set_title("Download Schemas To")
+        .pick_folder()
+        .await
+    else {
+        return Ok(None);
+    };
+
+    let names = WebProvider::fetch_github_schema_names(&source).await?;
+    let total = names.len();
+    progress.set((0, total));
+
+    let base_url = source.base_url();
+    let files: Vec<(String, String)> = futures_util::stream::iter(names)
+        .map(|name| {
+            let url = format!("{base_url}/{name}.yml");
+            async move { anyhow::Ok((format!("{name}.yml"), fetch_url_str(url).await?)) }
+        })
+        .buffer_unordered(CONCURRENT_DOWNLOADS)
+        .inspect_ok(|_| progress.set((progress.get().0 + 1, total)))
+        .try_collect::<Vec<_>>()
+        .await?;
+    let count = files.len();
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let folder = folder.path();
+        for (path, text) in files {
+            let path = folder.join(path);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, text)?;
+        }
+        Ok(Some((
+            SchemaLocation::Local(folder.to_string_lossy().into_owned()),
+            count,
+        )))
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        let (full_name, branch) = source.repository();
+        let folder = format!("{full_name}@{branch}").replace('/', "-");
+        crate::schema::worker::WorkerProvider::store_snapshot(folder.clone(), files).await?;
+        Ok(Some((SchemaLocation::Worker(folder), count)))
+    }
+}
@@ -0,0 +1,112 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Duration, Instant};
+#[cfg(target_arch = "wasm32")]
+use web_time::{Duration, Instant};
+
+/// Edits made this soon after the previous one are undone together with it.
+const GROUP_INTERVAL: Duration = Duration::from_millis(750);
+/// Oldest steps are dropped past this many.
+const MAX_STEPS: usize = 256;
+
+/// Undo/redo stack of a schema's text. Snapshots of the whole text are kept, since schemas are
+/// small and edits can come from the text editor, the structured editor or completions alike.
+pub struct History {
+    undo: Vec<String>,
+    redo: Vec<String>,
+    /// The text as of the last recorded edit
+    current: String,
+    /// When the current group of edits was last extended, `None` if it's closed
+    last_edit: Option<Instant>,
+}
+
+impl History {
+    pub fn new(text: &str) -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            current: text.to_owned(),
+            last_edit: None,
+        }
+    }
+
+    /// Records `text` if it differs from the last recorded text. Rapid edits, like typing a
+    /// word, are merged into a single step.
+    pub fn record(&mut self, text: &str) {
+        self.record_at(text, Instant::now());
+    }
+
+    /// Records `text` as a step of its own, for edits that replace a lot of text at once.
+    pub fn record_step(&mut self, text: &str) {
+        self.last_edit = None;
+        self.record(text);
+        self.last_edit = None;
+    }
+
+    fn record_at(&mut self, text: &str, now: Instant) {
+        if text == self.current {
+            return;
+        }
+        let grouped = self
+            .last_edit
+            .is_some_and(|last| now.duration_since(last) < GROUP_INTERVAL);
+        let previous = std::mem::replace(&mut self.current, text.to_owned());
+        if !grouped {
+            self.undo.push(previous);
+            if self.undo.len() > MAX_STEPS {
+                self.undo.remove(0);
+            }
+        }
+        self.redo.clear();
+        self.last_edit = Some(now);
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Steps back, returning the text to restore.
+    pub fn undo(&mut self) -> Option<&str> {
+        let previous = self.undo.pop()?;
+        self.redo
+            .push(std::mem::replace(&mut self.current, previous));
+        self.last_edit = None;
+        Some(&self.current)
+    }
+
+    /// Steps forward again after an undo, returning the text to restore.
+    pub fn redo(&mut self) -> Option<&str> {
+        let next = self.redo.pop()?;
+        self.undo.push(std::mem::replace(&mut self.current, next));
+        self.last_edit = None;
+        Some(&self.current)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{GROUP_INTERVAL, History, Instant};
+
+    #[test]
+    fn grouping() {
+        let start = Instant::now();
+        let mut history = History::new("");
+        history.record_at("a", start);
+        history.record_at("ab", start + GROUP_INTERVAL / 2);
+        history.record_at("ab c", start + GROUP_INTERVAL * 3);
+        history.record_step("");
+
+        assert_eq!(history.undo(), Some("ab c"));
+        assert_eq!(history.undo(), Some("ab"));
+        assert_eq!(history.undo(), Some(""));
+        assert_eq!(history.undo(), None);
+        assert_eq!(history.redo(), Some("ab"));
+
+        history.record_at("abd", start + GROUP_INTERVAL * 4);
+        assert!(!history.can_redo());
+        assert_eq!(history.undo(), Some("ab"));
+    }
+}
@@ -0,0 +1,232 @@
+use egui::{Button, CollapsingHeader, ComboBox, DragValue, RichText, TextEdit};
+use itertools::Itertools;
+
+use crate::{
+    i18n::{Text, tr},
+    schema::{Field, FieldType, Schema},
+};
+
+enum FieldAction {
+    MoveUp(usize),
+    MoveDown(usize),
+    Remove(usize),
+    InsertAfter(usize),
+    Push,
+}
+
+/// Draws a schema's fields as an editable tree. Returns whether the schema was changed.
+pub fn draw(ui: &mut egui::Ui, schema: &mut Schema) -> bool {
+    let ctx = &ui.ctx().clone();
+    let mut changed = false;
+    egui::ScrollArea::both().auto_shrink(false).show(ui, |ui| {
+        ui.horizontal(|ui| {
+            ui.label(tr(ctx, Text::DisplayField));
+            let mut display_field = schema.display_field.clone();
+            ComboBox::from_id_salt("display-field")
+                .selected_text(
+                    display_field
+                        .as_deref()
+                        .unwrap_or(tr(ctx, Text::NoDisplayField)),
+                )
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut display_field, None, tr(ctx, Text::NoDisplayField));
+                    for name in schema.fields.iter().filter_map(|f| f.name.as_ref()) {
+                        ui.selectable_value(&mut display_field, Some(name.clone()), name);
+                    }
+                });
+            if display_field != schema.display_field {
+                schema.display_field = display_field;
+                changed = true;
+            }
+        });
+        ui.separator();
+
+        changed |= draw_fields(ui, &mut schema.fields);
+    });
+    changed
+}
+
+fn draw_fields(ui: &mut egui::Ui, fields: &mut Vec<Field>) -> bool {
+    let ctx = &ui.ctx().clone();
+    let mut changed = false;
+    let mut action = None;
+    let len = fields.len();
+    for (idx, field) in fields.iter_mut().enumerate() {
+        ui.push_id(idx, |ui| {
+            ui.horizontal(|ui| {
+                ui.spacing_mut().item_spacing.x /= 2.0;
+                if ui
+                    .add_enabled(idx > 0, Button::new("⏶").small())
+                    .on_hover_text(tr(ctx, Text::MoveUp))
+                    .clicked()
+                {
+                    action = Some(FieldAction::MoveUp(idx));
+                }
+                if ui
+                    .add_enabled(idx + 1 < len, Button::new("⏷").small())
+                    .on_hover_text(tr(ctx, Text::MoveDown))
+                    .clicked()
+                {
+                    action = Some(FieldAction::MoveDown(idx));
+                }
+                ui.label(RichText::new(format!("{idx:>3}")).monospace().weak());
+
+                changed |= draw_field(ui, field);
+
+                if ui
+                    .small_button("➕")
+                    .on_hover_text(tr(ctx, Text::AddFieldBelow))
+                    .clicked()
+                {
+                    action = Some(FieldAction::InsertAfter(idx));
+                }
+                if ui
+                    .small_button("🗑")
+                    .on_hover_text(tr(ctx, Text::RemoveField))
+                    .clicked()
+                {
+                    action = Some(FieldAction::Remove(idx));
+                }
+            });
+
+            if field.r#type == FieldType::Array {
+                let mut nested = field.fields.take().unwrap_or_default();
+                CollapsingHeader::new(format!("{} ({})", tr(ctx, Text::Fields), nested.len()))
+                    .id_salt("fields")
+                    .show(ui, |ui| {
+                        changed |= draw_fields(ui, &mut nested);
+                    });
+                field.fields = (!nested.is_empty()).then_some(nested);
+            }
+        });
+    }
+
+    if ui
+        .small_button(format!("➕ {}", tr(ctx, Text::AddField)))
+        .clicked()
+    {
+        action = Some(FieldAction::Push);
+    }
+
+    match action {
+        Some(FieldAction::MoveUp(idx)) => fields.swap(idx - 1, idx),
+        Some(FieldAction::MoveDown(idx)) => fields.swap(idx, idx + 1),
+        Some(FieldAction::Remove(idx)) => {
+            fields.remove(idx);
+        }
+        Some(FieldAction::InsertAfter(idx)) => {
+            fields.insert(idx + 1, Field::default());
+        }
+        Some(FieldAction::Push) => fields.push(Field::default()),
+        None => return changed,
+    }
+    true
+}
+
+/// Draws the name, type, count and targets of a single field.
+fn draw_field(ui: &mut egui::Ui, field: &mut Field) -> bool {
+    let mut changed = false;
+
+    let mut name = field.name.clone().unwrap_or_default();
+    if ui
+        .add(
+            TextEdit::singleline(&mut name)
+                .hint_text(tr(ui.ctx(), Text::UnnamedField))
+                .desired_width(180.0),
+        )
+        .changed()
+    {
+        field.name = (!name.is_empty()).then_some(name);
+        changed = true;
+    }
+
+    let mut field_type = field.r#type.clone();
+    ComboBox::from_id_salt("type")
+        .selected_text(field_type.name())
+        .width(80.0)
+        .show_ui(ui, |ui| {
+            for option in FieldType::ALL {
+                ui.selectable_value(&mut field_type, option.clone(), option.name());
+            }
+        });
+    if field_type != field.r#type {
+        set_type(field, field_type);
+        changed = true;
+    }
+
+    match field.r#type {
+        FieldType::Array => {
+            let mut count = field.count.unwrap_or(1);
+            if ui
+                .add(DragValue::new(&mut count).range(1..=u32::MAX).prefix("× "))
+                .changed()
+            {
+                field.count = Some(count);
+                changed = true;
+            }
+        }
+        FieldType::Link => match &field.condition {
+            Some(condition) => {
+                ui.label(RichText::new(format!("switch: {}", condition.switch)).weak())
+                    .on_hover_ui(|ui| {
+                        for (case, targets) in condition.cases.iter().sorted_by_key(|c| c.0) {
+                            ui.label(format!("{case}: {}", targets.join(", ")));
+                        }
+                    });
+            }
+            None => changed |= draw_targets(ui, &mut field.targets),
+        },
+        _ => {}
+    }
+
+    if let Some(comment) = &field.comment {
+        ui.label("💬").on_hover_text(comment);
+    }
+    changed
+}
+
+/// Edits link targets as a comma separated list. The text is kept while it's being typed in,
+/// so separators aren't swallowed by the round trip through the list.
+fn draw_targets(ui: &mut egui::Ui, targets: &mut Option<Vec<String>>) -> bool {
+    let id = ui.id().with("targets");
+    let mut text = ui
+        .data(|d| d.get_temp::<String>(id))
+        .unwrap_or_else(|| targets.as_deref().unwrap_or_default().join(", "));
+    let resp = ui.add(
+        TextEdit::singleline(&mut text)
+            .hint_text(tr(ui.ctx(), Text::TargetSheets))
+            .desired_width(240.0),
+    );
+
+    let changed = resp.changed();
+    if changed {
+        let parsed = text
+            .split(',')
+            .map(str::trim)
+            .filter(|target| !target.is_empty())
+            .map(str::to_owned)
+            .collect_vec();
+        *targets = (!parsed.is_empty()).then_some(parsed);
+    }
+    if resp.has_focus() {
+        ui.data_mut(|d| d.insert_temp(id, text));
+    } else if resp.lost_focus() {
+        ui.data_mut(|d| d.remove::<String>(id));
+    }
+    changed
+}
+
+/// Changes a field's type, dropping the properties the new type doesn't use.
+fn set_type(field: &mut Field, field_type: FieldType) {
+    if field_type == FieldType::Array {
+        field.count.get_or_insert(1);
+    } else {
+        field.count = None;
+        field.fields = None;
+    }
+    if field_type != FieldType::Link {
+        field.targets = None;
+        field.condition = None;
+    }
+    field.r#type = field_type;
+}
@@ -0,0 +1,325 @@
+use std::{cell::Cell, rc::Rc};
+
+use egui::{ProgressBar, RichText, Sense};
+use egui_extras::{Column, TableBuilder};
+use futures_util::StreamExt;
+use itertools::Itertools;
+use serde::Serialize;
+
+use crate::{
+    backend::Backend,
+    excel::provider::{EntriesStatus, ExcelHeader, ExcelProvider},
+    i18n::{Text, tr},
+    schema::{Schema, provider::SchemaProvider},
+    sheet::SchemaColumn,
+    utils::{PromiseKind, TrackedPromise, yield_to_ui},
+};
+
+const CONCURRENT_CHECKS: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum IssueKind {
+    /// The sheet's header couldn't be read.
+    SheetUnavailable,
+    /// The sheet has no schema, or it couldn't be fetched.
+    SchemaUnavailable,
+    /// The schema isn't valid YAML or doesn't match the schema format.
+    ParseFailure,
+    /// The schema describes a different number of columns than the sheet has.
+    ColumnCount,
+    /// A conditional link switches on a field that doesn't exist.
+    BrokenSwitch,
+}
+
+impl IssueKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::SheetUnavailable => "Sheet Unavailable",
+            Self::SchemaUnavailable => "Schema Unavailable",
+            Self::ParseFailure => "Parse Failure",
+            Self::ColumnCount => "Column Count",
+            Self::BrokenSwitch => "Broken Switch",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Issue {
+    pub sheet: String,
+    pub kind: IssueKind,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortColumn {
+    Sheet,
+    Kind,
+    Message,
+}
+
+/// Checks the schema of every sheet against the loaded game data, listing every problem found.
+pub struct SchemaValidationWindow {
+    /// (checked, total)
+    progress: Rc<Cell<(usize, usize)>>,
+    promise: Option<TrackedPromise<Vec<Issue>>>,
+    issues: Option<Vec<Issue>>,
+    /// Column and whether it's descending
+    sort: (SortColumn, bool),
+    export_promise: Option<TrackedPromise<()>>,
+}
+
+impl SchemaValidationWindow {
+    pub fn new(backend: Backend) -> Self {
+        let progress = Rc::new(Cell::new((0, 0)));
+        let promise = TrackedPromise::spawn_local(validate(backend, progress.clone()));
+        Self {
+            progress,
+            promise: Some(promise),
+            issues: None,
+            sort: (SortColumn::Sheet, false),
+            export_promise: None,
+        }
+    }
+
+    /// Returns the name of a sheet the user clicked on. `open` is cleared when the window is
+    /// closed.
+    pub fn draw(&mut self, ctx: &egui::Context, open: &mut bool) -> Option<String> {
+        if let Some(promise) = self.promise.take() {
+            match promise.try_take() {
+                Ok(issues) => {
+                    self.issues = Some(issues);
+                    self.sort_issues();
+                }
+                Err(promise) => self.promise = Some(promise),
+            }
+        }
+        if self
+            .export_promise
+            .as_ref()
+            .is_some_and(|p| p.try_get().is_some())
+        {
+            self.export_promise = None;
+        }
+
+        let mut ret = None;
+        egui::Window::new(tr(ctx, Text::ValidateSchemas))
+            .id(egui::Id::new("schema-validation-window"))
+            .open(open)
+            .default_size([640.0, 400.0])
+            .show(ctx, |ui| {
+                let Some(issues) = &self.issues else {
+                    let (done, total) = self.progress.get();
+                    if total == 0 {
+                        ui.spinner();
+                    } else {
+                        ui.add(
+                            ProgressBar::new(done as f32 / total as f32)
+                                .text(format!("{done} / {total}")),
+                        );
+                    }
+                    return;
+                };
+
+                ui.horizontal(|ui| {
+                    let sheet_count = issues.iter().map(|i| &i.sheet).unique().count();
+                    ui.label(format!("{} issues in {sheet_count} sheets", issues.len()));
+                    if ui
+                        .add_enabled(
+                            !issues.is_empty() && self.export_promise.is_none(),
+                            egui::Button::new(tr(ctx, Text::Export)),
+                        )
+                        .clicked()
+                    {
+                        self.export_promise = Some(export(issues.clone()));
+                    }
+                });
+                ui.separator();
+
+                if issues.is_empty() {
+                    ui.label(tr(ctx, Text::NoSchemaIssues));
+                    return;
+                }
+
+                let mut clicked_sort = None;
+                let row_height = ui.text_style_height(&egui::TextStyle::Body);
+                TableBuilder::new(ui)
+                    .striped(true)
+                    .resizable(true)
+                    .column(Column::initial(160.0))
+                    .column(Column::initial(120.0))
+                    .column(Column::remainder())
+                    .header(row_height + 4.0, |mut header| {
+                        for (column, label) in [
+                            (SortColumn::Sheet, "Sheet"),
+                            (SortColumn::Kind, "Issue"),
+                            (SortColumn::Message, "Details"),
+                        ] {
+                            header.col(|ui| {
+                                let arrow = match self.sort {
+                                    (sorted, false) if sorted == column => " ⏶",
+                                    (sorted, true) if sorted == column => " ⏷",
+                                    _ => "",
+                                };
+                                if ui
+                                    .add(
+                                        egui::Label::new(
+                                            RichText::new(format!("{label}{arrow}")).strong(),
+                                        )
+                                        .sense(Sense::click()),
+                                    )
+                                    .clicked()
+                                {
+                                    clicked_sort = Some(column);
+                                }
+                            });
+                        }
+                    })
+                    .body(|body| {
+                        body.rows(row_height, issues.len(), |mut row| {
+                            let issue = &issues[row.index()];
+                            row.col(|ui| {
+                                if ui.link(&issue.sheet).clicked() {
+                                    ret = Some(issue.sheet.clone());
+                                }
+                            });
+                            row.col(|ui| {
+                                ui.label(issue.kind.name());
+                            });
+                            row.col(|ui| {
+                                ui.label(&issue.message).on_hover_text(&issue.message);
+                            });
+                        });
+                    });
+
+                if let Some(column) = clicked_sort {
+                    self.sort = (column, self.sort.0 == column && !self.sort.1);
+                    self.sort_issues();
+                }
+            });
+        ret
+    }
+
+    fn sort_issues(&mut self) {
+        let Some(issues) = &mut self.issues else {
+            return;
+        };
+        let (column, descending) = self.sort;
+        issues.sort_by(|a, b| {
+            let ordering = match column {
+                SortColumn::Sheet => a.sheet.cmp(&b.sheet).then(a.kind.cmp(&b.kind)),
+                SortColumn::Kind => a.kind.cmp(&b.kind).then_with(|| a.sheet.cmp(&b.sheet)),
+                SortColumn::Message => a.message.cmp(&b.message),
+            };
+            if descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+    }
+}
+
+async fn validate(backend: Backend, progress: Rc<Cell<(usize, usize)>>) -> Vec<Issue> {
+    while backend.excel().entries_status() == EntriesStatus::Loading {
+        yield_to_ui().await;
+    }
+    // Miscellaneous sheets have no schemas
+    let names = backend
+        .excel()
+        .get_entries()
+        .iter()
+        .filter(|(_, id)| **id >= 0)
+        .map(|(name, _)| name.clone())
+        .sorted()
+        .collect_vec();
+    let total = names.len();
+    progress.set((0, total));
+
+    futures_util::stream::iter(names)
+        .map(|name| {
+            let backend = backend.clone();
+            async move { validate_sheet(&backend, name).await }
+        })
+        .buffer_unordered(CONCURRENT_CHECKS)
+        .inspect(|_| progress.set((progress.get().0 + 1, total)))
+        .concat()
+        .await
+}
+
+async fn validate_sheet(backend: &Backend, name: String) -> Vec<Issue> {
+    let issue = |kind, message: String| Issue {
+        sheet: name.clone(),
+        kind,
+        message,
+    };
+
+    let column_count = match backend.excel().get_header(&name).await {
+        Ok(header) => header.columns().len(),
+        Err(e) => return vec![issue(IssueKind::SheetUnavailable, e.to_string())],
+    };
+    let text = match backend.schema().get_schema_text(&name).await {
+        Ok(text) => text,
+        Err(e) => return vec![issue(IssueKind::SchemaUnavailable, e.to_string())],
+    };
+    let schema = match Schema::from_str(&text) {
+        Ok(Ok(schema)) => schema,
+        Ok(Err(errors)) => {
+            return errors
+                .into_iter()
+                .map(|e| {
+                    let message = if e.location.is_empty() {
+                        e.description
+                    } else {
+                        format!("{} at {}", e.description, e.location)
+                    };
+                    issue(IssueKind::ParseFailure, message)
+                })
+                .collect();
+        }
+        Err(e) => return vec![issue(IssueKind::ParseFailure, e.to_string())],
+    };
+
+    let (schema_column_count, broken_switches) = match SchemaColumn::check_switches(&schema) {
+        Ok(result) => result,
+        Err(e) => return vec![issue(IssueKind::ParseFailure, e.to_string())],
+    };
+    let mut issues = broken_switches
+        .into_iter()
+        .map(|(column, switch)| {
+            issue(
+                IssueKind::BrokenSwitch,
+                format!("{column} switches on {switch}, which doesn't exist"),
+            )
+        })
+        .collect_vec();
+    if schema_column_count != column_count {
+        issues.push(issue(
+            IssueKind::ColumnCount,
+            format!("Schema has {schema_column_count} columns, but the sheet has {column_count}"),
+        ));
+    }
+    issues
+}
+
+/// Saves the results as JSON, so they can be fed into CI or an issue tracker.
+fn export(issues: Vec<Issue>) -> TrackedPromise<()> {
+    let dialog = rfd::AsyncFileDialog::new()
+        .set_title("Export Schema Issues")
+        .set_file_name("schema-issues.json");
+    TrackedPromise::spawn_local(async move {
+        let data = match serde_json::to_vec_pretty(&issues) {
+            Ok(data) => data,
+            Err(e) => {
+                log::error!("Failed to serialize schema issues: {e}");
+                return;
+            }
+        };
+        if let Some(file) = dialog.save_file().await {
+            if let Err(e) = file.write(&data).await {
+                log::error!("Failed to save schema issues: {e}");
+            } else {
+                log::info!("Schema issues saved successfully");
+            }
+        }
+    })
+}
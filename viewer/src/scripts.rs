@@ -0,0 +1,461 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Duration, Instant};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+};
+#[cfg(target_arch = "wasm32")]
+use web_time::{Duration, Instant};
+
+use egui::{RichText, TextBuffer, TextEdit};
+use itertools::Itertools;
+use rhai::{AST, Array, CallFnOptions, Dynamic, Engine, EvalAltResult, INT, Scope};
+
+use crate::{
+    excel::provider::ExcelSheet,
+    export,
+    i18n::{Text, tr},
+    settings::{CODE_SYNTAX_THEME, SCRIPT_SOURCE},
+    sheet::{CellValue, ScriptColumn, SheetTable, TableContext},
+    utils::highlight,
+};
+
+/// Operations a run may take in all, across the script and every `column` call, before it's
+/// stopped.
+const MAX_OPERATIONS: u64 = 50_000_000;
+/// How long a single call into the script may block the viewer before it's stopped.
+const MAX_CALL_DURATION: Duration = Duration::from_secs(2);
+/// How long `column` calls may take each frame. The rest are called on the frames after.
+const FRAME_BUDGET: Duration = Duration::from_millis(8);
+const MAX_STRING_SIZE: usize = 1 << 20;
+const MAX_ARRAY_SIZE: usize = 1 << 20;
+const MAX_MAP_SIZE: usize = 1 << 16;
+/// Lines of `print` output kept from a run.
+const MAX_PRINTED: usize = 1000;
+
+pub const EXAMPLE_SCRIPT: &str = r#"// `sheet` is the sheet's name, `columns` its column names and `rows` the rows shown,
+// in order. Cells are read with `row["Column"]`, and `row.id` and `row.subrow` are the
+// row's ids.
+//
+// Defining `column(row)` adds a column of what it returns to the table.
+fn column(row) {
+    row.id * 2
+}
+
+let total = 0;
+for row in rows {
+    total += row.id;
+}
+print(`${rows.len()} rows in ${sheet}`);
+total
+"#;
+
+/// A row as scripts see it. Cells are read as they're indexed.
+#[derive(Clone)]
+struct ScriptRow {
+    table: TableContext,
+    /// Offset index of each column, by name
+    columns: Rc<HashMap<String, u32>>,
+    row_id: u32,
+    subrow_id: Option<u16>,
+}
+
+impl ScriptRow {
+    fn cell(&mut self, name: &str) -> Result<Dynamic, Box<EvalAltResult>> {
+        let offset_idx = *self
+            .columns
+            .get(name)
+            .ok_or_else(|| format!("No column named {name}"))?;
+        let row = self
+            .table
+            .sheet()
+            .get_subrow(self.row_id, self.subrow_id.unwrap_or_default())
+            .map_err(|e| e.to_string())?;
+        let value = self
+            .table
+            .cell_by_offset(row, offset_idx)
+            .and_then(|cell| cell.read(false))
+            .map_err(|e| e.to_string())?;
+        Ok(to_dynamic(&value))
+    }
+}
+
+/// Numbers and links become integers or floats so they can be added up; everything else is
+/// read as the text shown in the table.
+fn to_dynamic(value: &CellValue) -> Dynamic {
+    match value {
+        CellValue::Float(f) => Dynamic::from_float(f64::from(*f)),
+        CellValue::Boolean(b) => Dynamic::from_bool(*b),
+        CellValue::Integer(_)
+        | CellValue::Icon(_)
+        | CellValue::InvalidLink(_)
+        | CellValue::InProgressLink(_)
+        | CellValue::ValidLink { .. } => value
+            .coerce_integer()
+            .and_then(|i| INT::try_from(i).ok())
+            .map_or_else(
+                || value.coerce_string().to_string().into(),
+                Dynamic::from_int,
+            ),
+        value => value.coerce_string().to_string().into(),
+    }
+}
+
+/// What a run left behind.
+struct ScriptOutput {
+    printed: Vec<String>,
+    /// The script's final value, or the error it stopped at.
+    result: Result<Option<String>, String>,
+    column: Option<ScriptColumn>,
+    sheet_name: String,
+}
+
+/// A run whose `column` calls are spread over frames, so the table stays responsive while
+/// they're made.
+struct ScriptRun {
+    engine: Engine,
+    scope: Scope<'static>,
+    printed: Rc<RefCell<Vec<String>>>,
+    /// When the call into the script being made is stopped
+    deadline: Rc<Cell<Instant>>,
+    sheet_name: String,
+    column_name: String,
+    /// The script's final value, or the error it stopped at.
+    value: Result<Dynamic, String>,
+    /// The script and the rows `column` is yet to be called on, if the script defines it
+    column: Option<(AST, std::vec::IntoIter<ScriptRow>)>,
+    values: HashMap<(u32, Option<u16>), String>,
+    row_count: usize,
+}
+
+impl ScriptRun {
+    /// Runs the script itself. `column` is called by [`Self::step`].
+    fn new(source: &str, table: &TableContext, rows: &[(u32, Option<u16>)]) -> Self {
+        let printed = Rc::new(RefCell::new(Vec::new()));
+        let deadline = Rc::new(Cell::new(Instant::now() + MAX_CALL_DURATION));
+        let mut engine = Engine::new();
+        engine
+            .set_max_string_size(MAX_STRING_SIZE)
+            .set_max_array_size(MAX_ARRAY_SIZE.max(rows.len()))
+            .set_max_map_size(MAX_MAP_SIZE);
+        engine.on_progress({
+            // Rhai's own operation limit starts over with every call, which `column` makes one
+            // of per row
+            let operations = Cell::new(0u64);
+            let deadline = deadline.clone();
+            move |_| {
+                let count = operations.get() + 1;
+                operations.set(count);
+                if count > MAX_OPERATIONS {
+                    Some("too many operations".into())
+                } else if count % 4096 == 0 && Instant::now() > deadline.get() {
+                    Some("took too long".into())
+                } else {
+                    None
+                }
+            }
+        });
+        engine.on_print({
+            let printed = printed.clone();
+            move |text| {
+                let mut printed = printed.borrow_mut();
+                if printed.len() < MAX_PRINTED {
+                    printed.push(text.to_owned());
+                }
+            }
+        });
+        engine
+            .register_type_with_name::<ScriptRow>("Row")
+            .register_get("id", |row: &mut ScriptRow| INT::from(row.row_id))
+            .register_get("subrow", |row: &mut ScriptRow| {
+                row.subrow_id.map_or(Dynamic::UNIT, |s| INT::from(s).into())
+            })
+            .register_indexer_get(ScriptRow::cell)
+            .register_fn("to_string", |row: &mut ScriptRow| {
+                format!(
+                    "{}#{}",
+                    row.table.sheet().name(),
+                    export::format_row_id(row.row_id, row.subrow_id)
+                )
+            });
+
+        let columns = (0..table.column_count() as u32)
+            .filter_map(|offset_idx| {
+                let (column, _) = table.get_column_by_offset(offset_idx).ok()?;
+                Some((column.name().to_owned(), offset_idx))
+            })
+            .collect::<HashMap<_, _>>();
+        let column_names = (0..table.column_count() as u32)
+            .filter_map(|column_idx| table.get_column_by_index(column_idx).ok())
+            .map(|((column, _), _)| Dynamic::from(column.name().to_owned()))
+            .collect::<Array>();
+        let columns = Rc::new(columns);
+        let script_rows = rows
+            .iter()
+            .map(|&(row_id, subrow_id)| ScriptRow {
+                table: table.clone(),
+                columns: columns.clone(),
+                row_id,
+                subrow_id,
+            })
+            .collect_vec();
+
+        let mut scope = Scope::new();
+        scope.push_constant("sheet", table.sheet().name().to_owned());
+        scope.push_constant("columns", column_names);
+        scope.push(
+            "rows",
+            script_rows
+                .iter()
+                .cloned()
+                .map(Dynamic::from)
+                .collect::<Array>(),
+        );
+
+        let result = engine
+            .compile(source)
+            .map_err(|e| e.to_string())
+            .and_then(|ast| {
+                let value = engine
+                    .eval_ast_with_scope::<Dynamic>(&mut scope, &ast)
+                    .map_err(|e| e.to_string())?;
+                Ok((value, ast))
+            });
+        let (value, column) = match result {
+            Ok((value, ast)) => {
+                let has_column = ast
+                    .iter_functions()
+                    .any(|f| f.name == "column" && f.params.len() == 1);
+                (
+                    Ok(value),
+                    has_column.then(|| (ast, script_rows.into_iter())),
+                )
+            }
+            Err(e) => (Err(e), None),
+        };
+
+        Self {
+            engine,
+            scope,
+            printed,
+            deadline,
+            sheet_name: table.sheet().name().to_owned(),
+            column_name: tr(table.global().ctx(), Text::Script).to_owned(),
+            value,
+            column,
+            values: HashMap::with_capacity(rows.len()),
+            row_count: rows.len(),
+        }
+    }
+
+    /// Rows `column` was called on so far.
+    fn rows_done(&self) -> usize {
+        self.column
+            .as_ref()
+            .map_or(self.row_count, |(_, rows)| self.row_count - rows.len())
+    }
+
+    /// Calls `column` on rows until `until`, handing the run back if there are rows left.
+    fn step(mut self, until: Instant) -> Result<ScriptOutput, Self> {
+        if let Some((ast, rows)) = &mut self.column
+            && self.value.is_ok()
+        {
+            while Instant::now() < until {
+                let Some(row) = rows.next() else {
+                    break;
+                };
+                let key = (row.row_id, row.subrow_id);
+                self.deadline.set(Instant::now() + MAX_CALL_DURATION);
+                match self.engine.call_fn_with_options::<Dynamic>(
+                    CallFnOptions::new().eval_ast(false),
+                    &mut self.scope,
+                    ast,
+                    "column",
+                    (row,),
+                ) {
+                    Ok(value) if value.is_unit() => {}
+                    Ok(value) => {
+                        self.values.insert(key, value.to_string());
+                    }
+                    Err(e) => {
+                        self.value = Err(e.to_string());
+                        break;
+                    }
+                }
+            }
+            if self.value.is_ok() && rows.len() != 0 {
+                return Err(self);
+            }
+        }
+
+        let column = (self.column.is_some() && self.value.is_ok()).then(|| ScriptColumn {
+            name: self.column_name,
+            values: Rc::new(self.values),
+        });
+        Ok(ScriptOutput {
+            printed: self.printed.take(),
+            result: self
+                .value
+                .map(|value| Some(value).filter(|v| !v.is_unit()).map(|v| v.to_string())),
+            column,
+            sheet_name: self.sheet_name,
+        })
+    }
+}
+
+/// Edits and runs scripts over the shown rows of a sheet, showing what they print and return.
+#[derive(Default)]
+pub struct ScriptsWindow {
+    output: Option<ScriptOutput>,
+    running: Option<ScriptRun>,
+}
+
+impl ScriptsWindow {
+    /// The column the last run derived, if it was run on `sheet_name`.
+    pub fn column(&self, sheet_name: &str) -> Option<ScriptColumn> {
+        self.output
+            .as_ref()
+            .filter(|output| output.sheet_name == sheet_name)?
+            .column
+            .clone()
+    }
+
+    pub fn draw(&mut self, ctx: &egui::Context, table: &mut SheetTable, open: &mut bool) {
+        egui::Window::new(tr(ctx, Text::Scripts))
+            .id(egui::Id::new("scripts-window"))
+            .open(open)
+            .default_width(520.0)
+            .default_height(560.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button(format!("▶ {}", tr(ctx, Text::Run))).clicked() {
+                        let rows = table.visible_rows();
+                        self.running = Some(ScriptRun::new(
+                            &SCRIPT_SOURCE.get(ctx),
+                            table.context(),
+                            &rows,
+                        ));
+                    }
+                    if ui
+                        .add_enabled(
+                            self.output.as_ref().is_some_and(|o| o.column.is_some()),
+                            egui::Button::new(tr(ctx, Text::ClearColumn)),
+                        )
+                        .clicked()
+                        && let Some(output) = &mut self.output
+                    {
+                        output.column = None;
+                    }
+                    if ui.button(tr(ctx, Text::ResetToDefaults)).clicked() {
+                        SCRIPT_SOURCE.set(ctx, EXAMPLE_SCRIPT.to_owned());
+                    }
+                    if let Some(run) = &self.running {
+                        ui.spinner();
+                        ui.label(format!("{} / {}", run.rows_done(), run.row_count));
+                        if ui.button(tr(ctx, Text::StopScript)).clicked() {
+                            self.running = None;
+                        }
+                    }
+                });
+                ui.separator();
+
+                if let Some(run) = self.running.take() {
+                    match run.step(Instant::now() + FRAME_BUDGET) {
+                        Ok(output) => self.output = Some(output),
+                        Err(run) => self.running = Some(run),
+                    }
+                    // The table picks up the new column on the next frame
+                    ctx.request_repaint();
+                }
+
+                egui::ScrollArea::vertical()
+                    .id_salt("scripts-source")
+                    .max_height(ui.available_height() * 0.6)
+                    .show(ui, |ui| {
+                        let theme = CODE_SYNTAX_THEME.get(ctx);
+                        let mut layouter =
+                            |ui: &egui::Ui, buf: &dyn TextBuffer, wrap_width: f32| {
+                                let mut layout_job =
+                                    highlight(ui.ctx(), ui.style(), &theme, buf.as_str(), "rs");
+                                layout_job.wrap.max_width = wrap_width;
+                                ui.fonts_mut(|f| f.layout_job(layout_job))
+                            };
+                        let mut source = SCRIPT_SOURCE.get(ctx);
+                        if ui
+                            .add(
+                                TextEdit::multiline(&mut source)
+                                    .code_editor()
+                                    .desired_rows(16)
+                                    .desired_width(f32::INFINITY)
+                                    .layouter(&mut layouter),
+                            )
+                            .changed()
+                        {
+                            SCRIPT_SOURCE.set(ctx, source);
+                        }
+                    });
+                ui.separator();
+
+                let Some(output) = &self.output else {
+                    ui.label(RichText::new(tr(ctx, Text::ScriptsHint)).weak());
+                    return;
+                };
+                egui::ScrollArea::vertical()
+                    .id_salt("scripts-output")
+                    .show(ui, |ui| {
+                        for line in &output.printed {
+                            ui.label(RichText::new(line).monospace());
+                        }
+                        match &output.result {
+                            Ok(Some(value)) => {
+                                ui.label(RichText::new(format!("= {value}")).monospace().strong());
+                            }
+                            Ok(None) => {}
+                            Err(e) => {
+                                ui.colored_label(ui.visuals().error_fg_color, e);
+                            }
+                        }
+                        if let Some(column) = &output.column {
+                            ui.label(
+                                RichText::new(format!(
+                                    "{} {}",
+                                    column.values.len(),
+                                    tr(ctx, Text::ScriptColumnValues)
+                                ))
+                                .weak(),
+                            );
+                        }
+                    });
+            });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use either::Either;
+
+    use super::to_dynamic;
+    use crate::sheet::CellValue;
+
+    #[test]
+    fn cells_convert_to_script_values() {
+        assert_eq!(to_dynamic(&CellValue::Integer(-3)).as_int(), Ok(-3));
+        assert_eq!(
+            to_dynamic(&CellValue::ValidLink {
+                sheet_name: "Item".into(),
+                row_id: 12,
+                value: None,
+            })
+            .as_int(),
+            Ok(12)
+        );
+        assert_eq!(to_dynamic(&CellValue::Float(0.5)).as_float(), Ok(0.5));
+        assert_eq!(
+            to_dynamic(&CellValue::ModelId(Either::Left(0x0102_0003)))
+                .into_string()
+                .unwrap(),
+            "3, 2, 1"
+        );
+    }
+}
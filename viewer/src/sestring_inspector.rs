@@ -0,0 +1,548 @@
+use std::ops::Range;
+
+use compact_str::ToCompactString;
+use egui::{CollapsingHeader, Color32, FontId, RichText, TextFormat, TextStyle, text::LayoutJob};
+use ironworks::sestring::SeStr;
+
+use crate::{
+    i18n::{Text, tr},
+    settings::TEMP_INSPECTED_STRING,
+};
+
+const HEX_LINE_WIDTH: usize = 16;
+
+/// A piece of an SeString, with the byte range it was parsed from.
+#[derive(Debug, PartialEq, Eq)]
+enum Token {
+    Text(String, Range<usize>),
+    Macro {
+        code: u8,
+        args: Vec<Expr>,
+        range: Range<usize>,
+    },
+}
+
+/// A macro parameter, with the byte range it was parsed from.
+#[derive(Debug, PartialEq, Eq)]
+struct Expr {
+    kind: ExprKind,
+    range: Range<usize>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum ExprKind {
+    Integer(u32),
+    /// Time or color values filled in at runtime
+    Placeholder(u8),
+    /// Comparisons between two expressions
+    Binary(u8, Box<Expr>, Box<Expr>),
+    /// Parameter lookups (`lnum`, `gnum`, `lstr`, `gstr`)
+    Unary(u8, Box<Expr>),
+    String(Vec<Token>),
+    Unknown(u8),
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    // Offset of `data` within the whole string
+    base: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8], base: usize) -> Self {
+        Self { data, pos: 0, base }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    fn offset(&self) -> usize {
+        self.base + self.pos
+    }
+
+    fn byte(&mut self) -> anyhow::Result<u8> {
+        let b = *self
+            .data
+            .get(self.pos)
+            .ok_or_else(|| anyhow::anyhow!("Unexpected end of string at {}", self.offset()))?;
+        self.pos += 1;
+        Ok(b)
+    }
+
+    fn bytes(&mut self, len: usize) -> anyhow::Result<&'a [u8]> {
+        let bytes = self
+            .data
+            .get(self.pos..self.pos + len)
+            .ok_or_else(|| anyhow::anyhow!("Unexpected end of string at {}", self.offset()))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn packed_int_from(&mut self, b: u8) -> anyhow::Result<u32> {
+        Ok(match b {
+            0x01..=0xCF => u32::from(b - 1),
+            0xF0..=0xFE => {
+                let flags = (b + 1) & 0xF;
+                let mut ret = 0;
+                for (flag, shift) in [(8, 24), (4, 16), (2, 8), (1, 0)] {
+                    if flags & flag != 0 {
+                        ret |= u32::from(self.byte()?) << shift;
+                    }
+                }
+                ret
+            }
+            _ => anyhow::bail!("Invalid packed integer 0x{b:02X} at {}", self.offset() - 1),
+        })
+    }
+
+    fn packed_int(&mut self) -> anyhow::Result<u32> {
+        let b = self.byte()?;
+        self.packed_int_from(b)
+    }
+
+    fn tokens(&mut self) -> anyhow::Result<Vec<Token>> {
+        let mut tokens = Vec::new();
+        while !self.is_empty() {
+            let start = self.offset();
+            if self.data[self.pos] == 0x02 {
+                self.pos += 1;
+                let code = self.byte()?;
+                let len = self.packed_int()? as usize;
+                let body_start = self.offset();
+                let body = self.bytes(len)?;
+                if self.byte()? != 0x03 {
+                    anyhow::bail!("Macro at {start} is missing its end marker");
+                }
+                let mut body = Reader::new(body, body_start);
+                let mut args = Vec::new();
+                while !body.is_empty() {
+                    args.push(body.expr()?);
+                }
+                tokens.push(Token::Macro {
+                    code,
+                    args,
+                    range: start..self.offset(),
+                });
+            } else {
+                let len = self.data[self.pos..]
+                    .iter()
+                    .position(|&b| b == 0x02)
+                    .unwrap_or(self.data.len() - self.pos);
+                let text = String::from_utf8_lossy(self.bytes(len)?).into_owned();
+                tokens.push(Token::Text(text, start..self.offset()));
+            }
+        }
+        Ok(tokens)
+    }
+
+    fn expr(&mut self) -> anyhow::Result<Expr> {
+        let start = self.offset();
+        let b = self.byte()?;
+        let kind = match b {
+            0x01..=0xCF | 0xF0..=0xFE => ExprKind::Integer(self.packed_int_from(b)?),
+            0xD0..=0xDF | 0xEC => ExprKind::Placeholder(b),
+            0xE0..=0xE5 => ExprKind::Binary(b, Box::new(self.expr()?), Box::new(self.expr()?)),
+            0xE8..=0xEB => ExprKind::Unary(b, Box::new(self.expr()?)),
+            0xFF => {
+                let len = self.packed_int()? as usize;
+                let string_start = self.offset();
+                let data = self.bytes(len)?;
+                ExprKind::String(Reader::new(data, string_start).tokens()?)
+            }
+            _ => ExprKind::Unknown(b),
+        };
+        Ok(Expr {
+            kind,
+            range: start..self.offset(),
+        })
+    }
+}
+
+fn parse(data: &[u8]) -> anyhow::Result<Vec<Token>> {
+    Reader::new(data, 0).tokens()
+}
+
+fn macro_name(code: u8) -> String {
+    match code {
+        0x08 => "if",
+        0x09 => "switch",
+        0x0A => "pcname",
+        0x0B => "ifpcgender",
+        0x0C => "ifpcname",
+        0x0D => "josa",
+        0x0E => "josaro",
+        0x0F => "ifself",
+        0x10 => "br",
+        0x11 => "wait",
+        0x12 => "icon",
+        0x13 => "color",
+        0x14 => "edgecolor",
+        0x15 => "shadowcolor",
+        0x16 => "softhyphen",
+        0x17 => "key",
+        0x18 => "scale",
+        0x19 => "bold",
+        0x1A => "italic",
+        0x1B => "edge",
+        0x1C => "shadow",
+        0x1D => "nbsp",
+        0x1E => "icon2",
+        0x1F => "hyphen",
+        0x20 => "num",
+        0x21 => "hex",
+        0x22 => "kilo",
+        0x23 => "byte",
+        0x24 => "sec",
+        0x25 => "time",
+        0x26 => "float",
+        0x27 => "link",
+        0x28 => "sheet",
+        0x29 => "string",
+        0x2A => "caps",
+        0x2B => "head",
+        0x2C => "split",
+        0x2D => "headall",
+        0x2E => "fixed",
+        0x2F => "lower",
+        0x30 => "jpnoun",
+        0x31 => "ennoun",
+        0x32 => "denoun",
+        0x33 => "frnoun",
+        0x34 => "chnoun",
+        0x40 => "lowerhead",
+        0x48 => "colortype",
+        0x49 => "edgecolortype",
+        0x4A => "ruby",
+        0x50 => "digit",
+        0x51 => "ordinal",
+        0x60 => "sound",
+        0x61 => "levelpos",
+        _ => return format!("macro_{code:02X}"),
+    }
+    .to_owned()
+}
+
+fn operator_name(op: u8) -> String {
+    match op {
+        0xD8 => "t_msec",
+        0xD9 => "t_sec",
+        0xDA => "t_min",
+        0xDB => "t_hour",
+        0xDC => "t_day",
+        0xDD => "t_wday",
+        0xDE => "t_mon",
+        0xDF => "t_year",
+        0xEC => "stackcolor",
+        0xE0 => ">=",
+        0xE1 => ">",
+        0xE2 => "<=",
+        0xE3 => "<",
+        0xE4 => "==",
+        0xE5 => "!=",
+        0xE8 => "lnum",
+        0xE9 => "gnum",
+        0xEA => "lstr",
+        0xEB => "gstr",
+        _ => return format!("0x{op:02X}"),
+    }
+    .to_owned()
+}
+
+struct Palette {
+    text: Color32,
+    macro_name: Color32,
+    number: Color32,
+    operator: Color32,
+    highlight: Color32,
+}
+
+impl Palette {
+    fn new(visuals: &egui::Visuals) -> Self {
+        if visuals.dark_mode {
+            Self {
+                text: Color32::from_rgb(0xA6, 0xE2, 0x2E),
+                macro_name: Color32::from_rgb(0x66, 0xD9, 0xEF),
+                number: Color32::from_rgb(0xFD, 0x97, 0x1F),
+                operator: Color32::from_rgb(0xF9, 0x26, 0x72),
+                highlight: Color32::from_rgba_unmultiplied(0x66, 0xD9, 0xEF, 0x40),
+            }
+        } else {
+            Self {
+                text: Color32::from_rgb(0x3A, 0x7D, 0x10),
+                macro_name: Color32::from_rgb(0x00, 0x6A, 0x9E),
+                number: Color32::from_rgb(0xB3, 0x5C, 0x00),
+                operator: Color32::from_rgb(0xB0, 0x10, 0x50),
+                highlight: Color32::from_rgba_unmultiplied(0x00, 0x6A, 0x9E, 0x30),
+            }
+        }
+    }
+}
+
+fn track_hover(resp: &egui::Response, range: &Range<usize>, hovered: &mut Option<Range<usize>>) {
+    if resp.hovered() {
+        *hovered = Some(range.clone());
+    }
+}
+
+fn draw_tokens(
+    ui: &mut egui::Ui,
+    palette: &Palette,
+    tokens: &[Token],
+    hovered: &mut Option<Range<usize>>,
+) {
+    for token in tokens {
+        match token {
+            Token::Text(text, range) => {
+                let resp = ui.label(
+                    RichText::new(format!("{text:?}"))
+                        .monospace()
+                        .color(palette.text),
+                );
+                track_hover(&resp, range, hovered);
+            }
+            Token::Macro { code, args, range } => {
+                let name = RichText::new(format!("<{}>", macro_name(*code)))
+                    .monospace()
+                    .color(palette.macro_name);
+                if args.is_empty() {
+                    let resp = ui.label(name);
+                    track_hover(&resp, range, hovered);
+                } else {
+                    let resp = CollapsingHeader::new(name)
+                        .id_salt(("sestring_macro", range.start))
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            for arg in args {
+                                draw_expr(ui, palette, arg, hovered);
+                            }
+                        });
+                    track_hover(&resp.header_response, range, hovered);
+                }
+            }
+        }
+    }
+}
+
+fn draw_expr(
+    ui: &mut egui::Ui,
+    palette: &Palette,
+    expr: &Expr,
+    hovered: &mut Option<Range<usize>>,
+) {
+    let header = |text: String, color| RichText::new(text).monospace().color(color);
+    let resp = match &expr.kind {
+        ExprKind::Integer(value) => ui.label(header(value.to_string(), palette.number)),
+        ExprKind::Placeholder(op) => ui.label(header(operator_name(*op), palette.operator)),
+        ExprKind::Unknown(b) => {
+            ui.label(header(format!("?? 0x{b:02X}"), ui.visuals().error_fg_color))
+        }
+        ExprKind::Binary(op, lhs, rhs) => {
+            CollapsingHeader::new(header(operator_name(*op), palette.operator))
+                .id_salt(("sestring_expr", expr.range.start))
+                .default_open(true)
+                .show(ui, |ui| {
+                    draw_expr(ui, palette, lhs, hovered);
+                    draw_expr(ui, palette, rhs, hovered);
+                })
+                .header_response
+        }
+        ExprKind::Unary(op, operand) => {
+            CollapsingHeader::new(header(operator_name(*op), palette.operator))
+                .id_salt(("sestring_expr", expr.range.start))
+                .default_open(true)
+                .show(ui, |ui| draw_expr(ui, palette, operand, hovered))
+                .header_response
+        }
+        ExprKind::String(tokens) => {
+            CollapsingHeader::new(header("string".to_owned(), palette.text))
+                .id_salt(("sestring_expr", expr.range.start))
+                .default_open(true)
+                .show(ui, |ui| draw_tokens(ui, palette, tokens, hovered))
+                .header_response
+        }
+    };
+    track_hover(&resp, &expr.range, hovered);
+}
+
+fn draw_hex(ui: &mut egui::Ui, palette: &Palette, data: &[u8], hovered: Option<&Range<usize>>) {
+    let font_id = TextStyle::Monospace.resolve(ui.style());
+    let text_color = ui.visuals().text_color();
+    let weak_color = ui.visuals().weak_text_color();
+    let append = |job: &mut LayoutJob, text: &str, color: Color32, background: Color32| {
+        job.append(
+            text,
+            0.0,
+            TextFormat {
+                font_id: font_id.clone(),
+                color,
+                background,
+                ..Default::default()
+            },
+        );
+    };
+    for (line_idx, line) in data.chunks(HEX_LINE_WIDTH).enumerate() {
+        let mut job = LayoutJob::default();
+        append(
+            &mut job,
+            &format!("{:04X}  ", line_idx * HEX_LINE_WIDTH),
+            weak_color,
+            Color32::TRANSPARENT,
+        );
+        for (i, b) in line.iter().enumerate() {
+            let offset = line_idx * HEX_LINE_WIDTH + i;
+            let background = if hovered.is_some_and(|r| r.contains(&offset)) {
+                palette.highlight
+            } else {
+                Color32::TRANSPARENT
+            };
+            let color = match b {
+                0x02 | 0x03 => palette.macro_name,
+                _ => text_color,
+            };
+            append(&mut job, &format!("{b:02X}"), color, background);
+            append(&mut job, " ", text_color, Color32::TRANSPARENT);
+        }
+        for _ in line.len()..HEX_LINE_WIDTH {
+            append(&mut job, "   ", text_color, Color32::TRANSPARENT);
+        }
+        let ascii = line
+            .iter()
+            .map(|&b| {
+                if b.is_ascii_graphic() || b == b' ' {
+                    b as char
+                } else {
+                    '.'
+                }
+            })
+            .collect::<String>();
+        append(
+            &mut job,
+            &format!(" {ascii}"),
+            weak_color,
+            Color32::TRANSPARENT,
+        );
+        ui.label(job);
+    }
+}
+
+/// Opens the inspector for `value`.
+pub fn inspect(ctx: &egui::Context, value: &SeStr) {
+    TEMP_INSPECTED_STRING.set(ctx, value.as_bytes().to_vec());
+}
+
+/// Draws the SeString inspector if a string is being inspected: the string as a tree of text and
+/// macros with decoded parameters, next to its raw bytes.
+pub fn draw(ctx: &egui::Context) {
+    let Some(data) = TEMP_INSPECTED_STRING.try_get(ctx) else {
+        return;
+    };
+
+    let mut open = true;
+    egui::Window::new(tr(ctx, Text::SeStringInspector))
+        .open(&mut open)
+        .default_size([720.0, 420.0])
+        .show(ctx, |ui| {
+            let palette = Palette::new(ui.visuals());
+            let value: &SeStr = data.as_slice().into();
+
+            CollapsingHeader::new(tr(ctx, Text::Formatted))
+                .default_open(true)
+                .show(ui, |ui| {
+                    if let Ok(text) = value.format().try_to_compact_string() {
+                        ui.label(text.as_str());
+                    }
+                    if let Ok(text) = value.macro_string().try_to_compact_string() {
+                        ui.label(
+                            RichText::new(text.as_str())
+                                .font(FontId::monospace(ui.text_style_height(&TextStyle::Small))),
+                        );
+                    }
+                });
+            ui.separator();
+
+            let hovered_id = ui.id().with("hovered");
+            let mut hovered = None;
+            ui.columns(2, |columns| {
+                egui::ScrollArea::vertical()
+                    .id_salt("sestring_tree")
+                    .show(&mut columns[0], |ui| match parse(&data) {
+                        Ok(tokens) => draw_tokens(ui, &palette, &tokens, &mut hovered),
+                        Err(e) => {
+                            ui.colored_label(ui.visuals().error_fg_color, e.to_string());
+                        }
+                    });
+                // Use last frame's hover if the tree wasn't hovered yet this frame
+                let highlighted = hovered
+                    .clone()
+                    .or_else(|| columns[1].data(|d| d.get_temp::<Range<usize>>(hovered_id)));
+                egui::ScrollArea::both()
+                    .id_salt("sestring_hex")
+                    .show(&mut columns[1], |ui| {
+                        draw_hex(ui, &palette, &data, highlighted.as_ref());
+                    });
+            });
+            ui.data_mut(|d| match &hovered {
+                Some(range) => d.insert_temp(hovered_id, range.clone()),
+                None => d.remove::<Range<usize>>(hovered_id),
+            });
+        });
+
+    if !open {
+        TEMP_INSPECTED_STRING.remove(ctx);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Expr, ExprKind, Reader, Token, parse};
+
+    #[test]
+    fn packed_int() {
+        assert_eq!(Reader::new(&[0x0C], 0).packed_int().unwrap(), 11);
+        assert_eq!(
+            Reader::new(&[0xF2, 0x01, 0x00], 0).packed_int().unwrap(),
+            0x100
+        );
+        assert_eq!(
+            Reader::new(&[0xFE, 0x12, 0x34, 0x56, 0x78], 0)
+                .packed_int()
+                .unwrap(),
+            0x1234_5678
+        );
+    }
+
+    #[test]
+    fn tokens() {
+        // "Hi<color(gnum(11))>!<br>"
+        let data = b"Hi\x02\x13\x03\xE9\x0C\x03!\x02\x10\x01\x03";
+        assert_eq!(
+            parse(data).unwrap(),
+            vec![
+                Token::Text("Hi".to_owned(), 0..2),
+                Token::Macro {
+                    code: 0x13,
+                    args: vec![Expr {
+                        kind: ExprKind::Unary(
+                            0xE9,
+                            Box::new(Expr {
+                                kind: ExprKind::Integer(11),
+                                range: 6..7,
+                            })
+                        ),
+                        range: 5..7,
+                    }],
+                    range: 2..8,
+                },
+                Token::Text("!".to_owned(), 8..9),
+                Token::Macro {
+                    code: 0x10,
+                    args: vec![],
+                    range: 9..13,
+                },
+            ]
+        );
+        assert!(parse(b"\x02\x10\x05\x03").is_err());
+    }
+}
@@ -1,11 +1,27 @@
-use std::{cmp::Reverse, collections::HashMap, fmt::Display, num::NonZero, sync::Arc};
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    num::NonZero,
+    sync::Arc,
+};
 
 use egui::ThemePreference;
 use ironworks::excel::Language;
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
 
 use crate::{
-    sheet::{FilterInputType, MatchOptions},
+    bookmarks::Bookmark,
+    export::SubrowShape,
+    external_links::ExternalLink,
+    i18n::{Text, UiLanguage},
+    outbox::OutboxEntry,
+    routes::ColumnRef,
+    scripts::EXAMPLE_SCRIPT,
+    sheet::{
+        ColumnSort, FilterInputType, HelperFormat, MatchOptions, NumberFormat, SheetViewState,
+    },
+    touch_layout::LayoutMode,
     utils::{CodeTheme, ColorTheme, GameVersion},
 };
 
@@ -218,20 +234,74 @@ pub type TempFKey<K, P = ()> = FuncKey<K, true, P>;
 pub type TempDKey<K> = DefaultedKey<K, true>;
 
 pub const LOGGER_SHOWN: DKey<bool> = DKey::new("logger-shown", false);
+pub const OPERATION_LOG_SHOWN: DKey<bool> = DKey::new("operation-log-shown", false);
+pub const MEMORY_USAGE_SHOWN: DKey<bool> = DKey::new("memory-usage-shown", false);
 pub const SORTED_BY_OFFSET: DKey<bool> = DKey::new("sorted-by-offset", false);
+/// Whether copied cells start with a line of their column names.
+pub const COPY_HEADER_ROW: DKey<bool> = DKey::new("copy-header-row", false);
 pub const SOLID_SCROLLBAR: DKey<bool> = DKey::new("solid-scrollbar", true);
+pub const LAYOUT_MODE: DKey<LayoutMode> = DKey::new("layout-mode", LayoutMode::Auto);
 pub const ALWAYS_HIRES: DKey<bool> = DKey::new("always-hires", false);
+pub const ICON_MAX_TEXTURE_SIZE: DKey<Option<NonZero<u16>>> =
+    DKey::new("icon-max-texture-size", None);
+pub const ICON_AUTO_DOWNSCALE: DKey<bool> = DKey::new("icon-auto-downscale", true);
 pub const DISPLAY_FIELD_SHOWN: DKey<bool> = DKey::new("display-field-shown", true);
+/// Columns tried in place of a display field on sheets whose schema doesn't define one.
+pub const DISPLAY_FIELD_FALLBACK: DKey<DisplayFieldFallback> =
+    DKey::new("display-field-fallback", DisplayFieldFallback::StringOrName);
 pub const EVALUATE_STRINGS: DKey<bool> = DKey::new("evaluate-strings", false);
 pub const TEXT_WRAP_WIDTH: DKey<Option<NonZero<u16>>> =
     DKey::new("text-wrap-width", NonZero::new(600));
 pub const TEXT_MAX_LINES: DKey<Option<NonZero<u8>>> = DKey::new("text-max-lines", NonZero::new(5));
 pub const TEXT_USE_SCROLL: DKey<bool> = DKey::new("text-use-scroll", false);
+pub const TABLE_FONT_SIZE: DKey<Option<NonZero<u8>>> = DKey::new("table-font-size", None);
+pub const MONOSPACE_NUMBERS: DKey<bool> = DKey::new("monospace-numbers", false);
+/// Most requests web providers run at once; see [`crate::utils::set_request_limit`].
+pub const WEB_REQUEST_LIMIT: DKey<Option<NonZero<u8>>> =
+    DKey::new("web-request-limit", NonZero::new(16));
+/// Opened sheets kept loaded, with their layout and filters. `None` keeps every one.
+pub const SHEET_CACHE_SIZE: DKey<Option<NonZero<u16>>> =
+    DKey::new("sheet-cache-size", NonZero::new(32));
+/// Schemas kept loaded. Modified ones are kept regardless. `None` keeps every one.
+pub const SCHEMA_CACHE_SIZE: DKey<Option<NonZero<u16>>> = DKey::new("schema-cache-size", None);
+/// MiB of downloaded sheet files kept on disk by web providers. `None` keeps every one.
+pub const WEB_CACHE_SIZE: DKey<Option<NonZero<u16>>> =
+    DKey::new("web-cache-size", NonZero::new(1024));
 pub const BACKEND_CONFIG: DKey<Option<BackendConfig>> = DKey::new("backend-config", None);
+/// Saved backend configs to switch between, in the order they were saved.
+pub const BACKEND_PRESETS: DKey<Vec<BackendPreset>> = DKey::new("backend-presets", Vec::new());
 pub const LANGUAGE: DKey<Language> = DKey::new("language", Language::English);
+pub const UI_LANGUAGE: DKey<UiLanguage> = DKey::new("ui-language", UiLanguage::English);
 pub const SHEETS_FILTER: DKey<String> = DKey::new("sheets-filter", String::new());
 pub const SHEET_FILTERS: FKey<HashMap<String, (FilterInputType, String)>> =
     FKey::new("sheet-filters", |_, ()| HashMap::new());
+/// Past filters of each sheet, oldest first.
+pub const SHEET_FILTER_HISTORY: FKey<HashMap<String, Vec<(FilterInputType, String)>>> =
+    FKey::new("sheet-filter-history", |_, ()| HashMap::new());
+/// The helper column shown for each sheet, if any.
+pub const HELPER_COLUMNS: FKey<HashMap<String, HelperFormat>> =
+    FKey::new("helper-columns", |_, ()| HashMap::new());
+/// The row each sheet was last scrolled to, offered to resume at when it's opened again.
+pub const LAST_VISITED_ROWS: FKey<HashMap<String, (u32, Option<u16>)>> =
+    FKey::new("last-visited-rows", |_, ()| HashMap::new());
+/// The columns each sheet is sorted by, highest priority first.
+pub const SHEET_SORTS: FKey<HashMap<String, Vec<ColumnSort>>> =
+    FKey::new("sheet-sorts", |_, ()| HashMap::new());
+/// Tints the columns of each array field alike, to visually chunk wide sheets.
+pub const COLUMN_GROUP_TINTS: DKey<bool> = DKey::new("column-group-tints", true);
+/// Colors picked for column groups (see [`crate::sheet::SchemaColumn::group`]) in place of the
+/// automatic ones, by sheet and group name.
+pub const COLUMN_GROUP_COLORS: FKey<HashMap<String, HashMap<String, [u8; 3]>>> =
+    FKey::new("column-group-colors", |_, ()| HashMap::new());
+/// Column widths fitted to their contents, by sheet and column offset index.
+pub const COLUMN_WIDTHS: FKey<HashMap<String, HashMap<u32, f32>>> =
+    FKey::new("column-widths", |_, ()| HashMap::new());
+/// Formats picked for integer columns other than decimal, by sheet and column offset index.
+pub const COLUMN_NUMBER_FORMATS: FKey<HashMap<String, HashMap<u32, NumberFormat>>> =
+    FKey::new("column-number-formats", |_, ()| HashMap::new());
+/// Schemas written from blank stubs, by sheet name.
+pub const SCHEMA_OUTBOX: FKey<BTreeMap<String, OutboxEntry>> =
+    FKey::new("schema-outbox", |_, ()| BTreeMap::new());
 pub const SHEET_FILTER_OPTIONS: DKey<MatchOptions> = DKey::new(
     "sheet-filter-options",
     MatchOptions {
@@ -240,11 +310,37 @@ pub const SHEET_FILTER_OPTIONS: DKey<MatchOptions> = DKey::new(
     },
 );
 pub const SELECTED_SHEET: DKey<Option<String>> = DKey::new("selected-sheet", None);
+/// Most recently visited first.
+pub const RECENT_SHEETS: DKey<Vec<String>> = DKey::new("recent-sheets", Vec::new());
+/// Sheets open in tabs, in the order they're shown.
+pub const SHEET_TABS: DKey<Vec<String>> = DKey::new("sheet-tabs", Vec::new());
 pub const MISC_SHEETS_SHOWN: DKey<bool> = DKey::new("misc-sheets-shown", false);
 pub const PR_CHANGED_ONLY: DKey<bool> = DKey::new("pr-changed-only", true);
 pub const SCHEMA_EDITOR_VISIBLE: DKey<bool> = DKey::new("schema-editor-visible", false);
 pub const SCHEMA_EDITOR_WORD_WRAP: DKey<bool> = DKey::new("schema-editor-word-wrap", false);
+pub const SCHEMA_EDITOR_STRUCTURED: DKey<bool> = DKey::new("schema-editor-structured", false);
+pub const EXPORT_EVALUATE_STRINGS: DKey<bool> = DKey::new("export-evaluate-strings", false);
+pub const EXPORT_LINK_DEPTH: DKey<u8> = DKey::new("export-link-depth", 0);
+pub const EXPORT_SUBROW_SHAPE: DKey<SubrowShape> =
+    DKey::new("export-subrow-shape", SubrowShape::Records);
+pub const BOOKMARKS: DKey<Vec<Bookmark>> = DKey::new("bookmarks", Vec::new());
+/// Sites rows can be opened on from their context menus.
+pub const EXTERNAL_LINKS: FKey<Vec<ExternalLink>> =
+    FKey::new("external-links", |_, ()| ExternalLink::defaults());
+/// The script in the Scripts window.
+pub const SCRIPT_SOURCE: FKey<String> =
+    FKey::new("script-source", |_, ()| EXAMPLE_SCRIPT.to_owned());
+pub const COLUMN_DOCS_SHOWN: DKey<bool> = DKey::new("column-docs-shown", false);
+pub const PAGE_BOUNDARIES_SHOWN: DKey<bool> = DKey::new("page-boundaries-shown", false);
+pub const SHOW_RAW_VALUES: DKey<bool> = DKey::new("show-raw-values", false);
+pub const FIRST_APPEARED_SHOWN: DKey<bool> = DKey::new("first-appeared-shown", false);
 pub const SCHEMA_EDITOR_ERRORS_SHOWN: DKey<bool> = DKey::new("schema-editor-errors-shown", false);
+/// Native only; see [`crate::control::ControlServer`].
+pub const CONTROL_SERVER_ENABLED: DKey<bool> = DKey::new("control-server-enabled", false);
+pub const CONTROL_SERVER_PORT: DKey<u16> = DKey::new("control-server-port", 7980);
+/// Opt-in; see [`crate::telemetry`].
+pub const TELEMETRY_ENABLED: DKey<bool> = DKey::new("telemetry-enabled", false);
+pub const TELEMETRY_ENDPOINT: DKey<Option<String>> = DKey::new("telemetry-endpoint", None);
 
 pub const COLOR_THEME: FKey<ColorTheme, ThemePreference> = FKey::new_with_preflight(
     "color-theme",
@@ -267,8 +363,42 @@ pub const CODE_SYNTAX_THEME: FKey<CodeTheme, Arc<egui::Style>> = FKey::new_with_
 
 pub const CURRENT_SHEET_LANGUAGES: TempKey<(String, Vec<Language>)> =
     TempKey::new("current-sheet-languages");
-pub const TEMP_SCROLL_TO: TempKey<((u32, Option<u16>), u16)> = TempKey::new("temp-scroll-to");
+pub const TEMP_SCROLL_TO: TempKey<((u32, Option<u16>), Option<ColumnRef>)> =
+    TempKey::new("temp-scroll-to");
+/// View of the sheet to put back after going back or forward to it, in place of scrolling to
+/// the row in the path.
+pub const TEMP_RESTORE_VIEW: TempKey<SheetViewState> = TempKey::new("temp-restore-view");
 pub const TEMP_HIGHLIGHTED_ROW: TempKey<(u32, Option<u16>)> = TempKey::new("temp-highlighted-row");
+/// Raw bytes of the SeString shown in the inspector window.
+pub const TEMP_PERSIST_DISPLAY_FIELD: TempKey<String> = TempKey::new("temp-persist-display-field");
+pub const TEMP_INSPECTED_STRING: TempKey<Vec<u8>> = TempKey::new("temp-inspected-string");
+/// Row whose references were requested from the table, as (sheet name, (row id, subrow id)).
+pub const TEMP_FIND_REFERENCES: TempKey<(String, (u32, Option<u16>))> =
+    TempKey::new("temp-find-references");
+
+/// How far [`crate::sheet::TableContext::display_field_cell`] goes looking for a column to show
+/// for linked rows when their sheet has no display field.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DisplayFieldFallback {
+    /// Links are shown as `Sheet#id`.
+    Off,
+    /// The first string column whose values are mostly filled in and unique.
+    String,
+    /// As above, then the first column named like a name, such as `Name` or `Singular`.
+    StringOrName,
+}
+
+impl DisplayFieldFallback {
+    pub const ALL: &[Self] = &[Self::Off, Self::String, Self::StringOrName];
+
+    pub fn name(self) -> Text {
+        match self {
+            Self::Off => Text::FallbackOff,
+            Self::String => Text::FallbackString,
+            Self::StringOrName => Text::FallbackStringOrName,
+        }
+    }
+}
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum Region {
@@ -316,6 +446,8 @@ pub enum InstallLocation {
     #[cfg(target_arch = "wasm32")]
     Worker(String),
     Web(String, Region, Option<GameVersion>),
+    /// A [registered](crate::backend::registry) kind by id, and what it's opened from
+    Registered(String, String),
 }
 
 #[derive(Clone, Serialize, Deserialize, PartialEq)]
@@ -327,23 +459,20 @@ pub struct GithubSchemaLocation {
 
 impl GithubSchemaLocation {
     pub fn base_url(&self) -> String {
+        let (full_name, branch) = self.repository();
+        format!("https://raw.githubusercontent.com/{full_name}/refs/heads/{branch}")
+    }
+
+    /// The `owner/repo` and branch the schemas are read from. Pull requests are read from
+    /// their head repository.
+    pub fn repository(&self) -> (String, String) {
         if let GithubSchemaBranch::PullRequest {
             full_name, branch, ..
         } = &self.branch
         {
-            format!("https://raw.githubusercontent.com/{full_name}/refs/heads/{branch}")
+            (full_name.clone(), branch.clone())
         } else {
-            format!(
-                "https://raw.githubusercontent.com/{}/{}/refs/heads/{}",
-                self.owner,
-                self.repo,
-                match &self.branch {
-                    GithubSchemaBranch::Latest => "latest".to_string(),
-                    GithubSchemaBranch::Other(name) => name.clone(),
-                    GithubSchemaBranch::Version(v) => format!("ver/{}", v.0),
-                    GithubSchemaBranch::PullRequest { .. } => unreachable!(),
-                }
-            )
+            (format!("{}/{}", self.owner, self.repo), self.base_branch())
         }
     }
 
@@ -396,10 +525,30 @@ pub enum SchemaLocation {
     Worker(String),
     Github(GithubSchemaLocation),
     Web(String),
+    /// A [registered](crate::backend::registry) kind by id, and what it's opened from
+    Registered(String, String),
 }
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, PartialEq)]
 pub struct BackendConfig {
     pub location: InstallLocation,
     pub schema: SchemaLocation,
+    #[serde(default)]
+    pub overlay: Option<StringOverlay>,
+}
+
+/// A [`BackendConfig`] saved under a name, like "Live" or "Benchmark". Pinned web versions are
+/// part of the location, so they're kept too.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BackendPreset {
+    pub name: String,
+    pub config: BackendConfig,
+}
+
+/// A second install that string columns are read from where it has them, e.g. an
+/// international client for names while browsing a partially translated CN/KR one.
+#[derive(Clone, Serialize, Deserialize, PartialEq)]
+pub struct StringOverlay {
+    pub location: InstallLocation,
+    pub language: Language,
 }
@@ -1,15 +1,23 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
 use egui::{Frame, Layout, Modal, Sense, TextEdit, UiBuilder, Vec2, WidgetText};
+use ironworks::excel::Language;
 
 use crate::{
     DEFAULT_API_URL,
-    backend::Backend,
+    backend::{Backend, registry},
     data::web::{RepositoryInfo, VersionInfo, WebFileProvider},
+    i18n::{Text, tr},
     schema::web::WebProvider,
     settings::{
-        BACKEND_CONFIG, BackendConfig, GithubSchemaBranch, GithubSchemaLocation, InstallLocation,
-        Region, SchemaLocation,
+        BACKEND_CONFIG, BACKEND_PRESETS, BackendConfig, BackendPreset, GithubSchemaBranch,
+        GithubSchemaLocation, InstallLocation, Region, SchemaLocation, StringOverlay,
     },
     utils::{ConvertiblePromise, PromiseKind, TrackedPromise, UnsendPromise},
+    version_watch::REVALIDATE_INTERVAL,
 };
 
 #[cfg(target_arch = "wasm32")]
@@ -21,6 +29,9 @@ type VersionPromiseHolder<K, T> = Option<(K, VersionPromise<T>)>;
 pub struct SetupWindow {
     location: InstallLocation,
     schema: SchemaLocation,
+    overlay: Option<StringOverlay>,
+    /// Name to save the config under, or of the preset it was loaded from
+    preset_name: String,
     is_startup: bool,
     #[cfg(target_arch = "wasm32")]
     location_promises: SetupPromises,
@@ -30,6 +41,8 @@ pub struct SetupWindow {
     display_error: Option<anyhow::Error>,
 
     web_version_promise: VersionPromiseHolder<(String, Region), VersionInfo>,
+    /// When the web versions were last fetched, to revalidate them while setup is open
+    web_version_fetched: Option<Instant>,
     web_repositories_promise: VersionPromiseHolder<String, Vec<RepositoryInfo>>,
     github_branch_promise: VersionPromiseHolder<(String, String), Vec<GithubSchemaBranch>>,
 }
@@ -56,6 +69,8 @@ impl SetupWindow {
                 repo: super::DEFAULT_GITHUB_REPO.1.to_string(),
                 branch: GithubSchemaBranch::Latest,
             }),
+            overlay: None,
+            preset_name: String::new(),
             is_startup,
             #[cfg(target_arch = "wasm32")]
             location_promises: Default::default(),
@@ -64,6 +79,7 @@ impl SetupWindow {
             setup_promise: None,
             display_error: None,
             web_version_promise: None,
+            web_version_fetched: None,
             web_repositories_promise: None,
             github_branch_promise: None,
         }
@@ -71,9 +87,17 @@ impl SetupWindow {
 
     pub fn from_config(ctx: &egui::Context, is_startup: bool) -> Self {
         if let Some(Some(config)) = BACKEND_CONFIG.try_get(ctx) {
+            let preset_name = BACKEND_PRESETS
+                .get(ctx)
+                .into_iter()
+                .find(|preset| preset.config == config)
+                .map(|preset| preset.name)
+                .unwrap_or_default();
             Self {
                 location: config.location,
                 schema: config.schema,
+                overlay: config.overlay,
+                preset_name,
                 is_startup,
                 #[cfg(target_arch = "wasm32")]
                 location_promises: Default::default(),
@@ -82,6 +106,7 @@ impl SetupWindow {
                 setup_promise: None,
                 display_error: None,
                 web_version_promise: None,
+                web_version_fetched: None,
                 web_repositories_promise: None,
                 github_branch_promise: None,
             }
@@ -134,6 +159,8 @@ impl SetupWindow {
                 ui.label("Please select the location of the game files and schema.");
             }
 
+            ui.add_enabled_ui(enabled, |ui| self.draw_presets(ui));
+
             let is_go_clicked = ui
                 .add_enabled_ui(enabled, |ui| {
                     Frame::group(ui.style()).show(ui, |ui| {
@@ -178,6 +205,16 @@ impl SetupWindow {
                                 }
                             });
                         });
+                        for kind in registry::file_kinds() {
+                            let selected = matches!(
+                                &self.location,
+                                InstallLocation::Registered(id, _) if id == kind.id
+                            );
+                            if radio(ui, selected, kind.name) {
+                                self.location =
+                                    InstallLocation::Registered(kind.id.to_owned(), String::new());
+                            }
+                        }
 
                         match &mut self.location {
                             #[cfg(not(target_arch = "wasm32"))]
@@ -192,12 +229,21 @@ impl SetupWindow {
                                         {
                                             *path = picked_path;
                                         }
+                                        if ui.button(tr(ctx, Text::PatchFile)).clicked()
+                                            && let Some(picked_path) = rfd::FileDialog::new()
+                                                .add_filter("Patch", &["patch"])
+                                                .pick_file()
+                                                .and_then(|d| d.to_str().map(|s| s.to_owned()))
+                                        {
+                                            *path = picked_path;
+                                        }
                                         ui.add(
                                             egui::TextEdit::singleline(path)
                                                 .desired_width(ui.available_width()),
                                         );
                                     });
                                 });
+                                draw_local_hint(ui);
                             }
 
                             #[cfg(target_arch = "wasm32")]
@@ -254,6 +300,7 @@ impl SetupWindow {
                                             },
                                         );
                                     });
+                                    draw_local_hint(ui);
                                 }
                             }
 
@@ -346,26 +393,33 @@ impl SetupWindow {
                                         });
                                 });
 
-                                // (Re)fetch versions whenever the URL or region changes. On an
-                                // actual change (not the initial load of a persisted config),
-                                // reset the selected version so it can't dangle across regions.
+                                // (Re)fetch versions whenever the URL or region changes, or once
+                                // they're stale. On an actual change (not the initial load of a
+                                // persisted config), reset the selected version so it can't
+                                // dangle across regions.
                                 let version_key = (url.clone(), *region);
                                 let key_changed = self
                                     .web_version_promise
                                     .as_ref()
                                     .is_some_and(|v| v.0 != version_key);
+                                let is_stale = self
+                                    .web_version_fetched
+                                    .is_some_and(|t| t.elapsed() >= REVALIDATE_INTERVAL);
                                 if !url.is_empty()
                                     && region.is_available()
-                                    && self
-                                        .web_version_promise
-                                        .as_ref()
-                                        .is_none_or(|v| v.0 != version_key)
+                                    && (is_stale
+                                        || self
+                                            .web_version_promise
+                                            .as_ref()
+                                            .is_none_or(|v| v.0 != version_key))
                                 {
                                     if key_changed {
                                         *version = None;
                                     }
                                     let ver_url = url.clone();
                                     let slug = region.slug().unwrap_or_default().to_string();
+                                    self.web_version_fetched = Some(Instant::now());
+                                    ui.ctx().request_repaint_after(REVALIDATE_INTERVAL);
                                     self.web_version_promise = Some((
                                         version_key,
                                         ConvertiblePromise::new_promise(
@@ -423,9 +477,18 @@ impl SetupWindow {
                                     }
                                 });
                             }
+
+                            InstallLocation::Registered(kind, argument) => {
+                                let hint = registry::file_kind(kind).map(|k| k.hint);
+                                registered_argument(ui, hint, argument);
+                            }
                         }
                     });
 
+                    Frame::group(ui.style()).show(ui, |ui| {
+                        draw_overlay(ui, &mut self.overlay);
+                    });
+
                     Frame::group(ui.style()).show(ui, |ui| {
                         ui.vertical_centered(|ui| {
                             ui.heading("Schema");
@@ -475,6 +538,16 @@ impl SetupWindow {
                                 }
                             });
                         });
+                        for kind in registry::schema_kinds() {
+                            let selected = matches!(
+                                &self.schema,
+                                SchemaLocation::Registered(id, _) if id == kind.id
+                            );
+                            if radio(ui, selected, kind.name) {
+                                self.schema =
+                                    SchemaLocation::Registered(kind.id.to_owned(), String::new());
+                            }
+                        }
 
                         match &mut self.schema {
                             #[cfg(not(target_arch = "wasm32"))]
@@ -716,6 +789,11 @@ impl SetupWindow {
                                     );
                                 });
                             }
+
+                            SchemaLocation::Registered(kind, argument) => {
+                                let hint = registry::schema_kind(kind).map(|k| k.hint);
+                                registered_argument(ui, hint, argument);
+                            }
                         }
                     });
 
@@ -733,10 +811,8 @@ impl SetupWindow {
             if is_go_clicked || self.is_startup {
                 self.is_startup = false;
                 if self.setup_promise.is_none() {
-                    let location = self.location.clone();
-                    let schema = self.schema.clone();
+                    let config = self.config();
                     self.setup_promise = Some(UnsendPromise::new(async move {
-                        let config = BackendConfig { location, schema };
                         Backend::new(config.clone())
                             .await
                             .map(|backend| (backend, config))
@@ -759,6 +835,117 @@ impl SetupWindow {
             .inner
     }
 
+    fn config(&self) -> BackendConfig {
+        BackendConfig {
+            location: self.location.clone(),
+            schema: self.schema.clone(),
+            overlay: self.overlay.clone(),
+        }
+    }
+
+    /// Picks a saved preset to fill in the config from, or saves the current one under a name.
+    fn draw_presets(&mut self, ui: &mut egui::Ui) {
+        let ctx = ui.ctx().clone();
+        let mut presets = BACKEND_PRESETS.get(&ctx);
+        let existing = presets.iter().position(|p| p.name == self.preset_name);
+        ui.horizontal(|ui| {
+            ui.label(tr(&ctx, Text::ProfileLabel));
+            ui.add_enabled_ui(!presets.is_empty(), |ui| {
+                egui::ComboBox::from_id_salt("setup-preset")
+                    .selected_text(match existing {
+                        Some(idx) => presets[idx].name.as_str(),
+                        None => tr(&ctx, Text::SelectProfile),
+                    })
+                    .show_ui(ui, |ui| {
+                        for (idx, preset) in presets.iter().enumerate() {
+                            if ui
+                                .selectable_label(existing == Some(idx), &preset.name)
+                                .clicked()
+                            {
+                                self.preset_name.clone_from(&preset.name);
+                                self.location = preset.config.location.clone();
+                                self.schema = preset.config.schema.clone();
+                                self.overlay = preset.config.overlay.clone();
+                                self.display_error = None;
+                            }
+                        }
+                    });
+            });
+            ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui
+                    .add_enabled(
+                        existing.is_some(),
+                        egui::Button::new(tr(&ctx, Text::Delete)),
+                    )
+                    .clicked()
+                    && let Some(idx) = existing
+                {
+                    presets.remove(idx);
+                    BACKEND_PRESETS.set(&ctx, presets.clone());
+                }
+                let name = self.preset_name.trim().to_owned();
+                if ui
+                    .add_enabled(!name.is_empty(), egui::Button::new(tr(&ctx, Text::Save)))
+                    .on_hover_text(tr(&ctx, Text::SaveProfileHover))
+                    .clicked()
+                {
+                    let preset = BackendPreset {
+                        name: name.clone(),
+                        config: self.config(),
+                    };
+                    match presets.iter_mut().find(|p| p.name == name) {
+                        Some(saved) => *saved = preset,
+                        None => presets.push(preset),
+                    }
+                    self.preset_name = name;
+                    BACKEND_PRESETS.set(&ctx, presets.clone());
+                }
+                ui.add(
+                    TextEdit::singleline(&mut self.preset_name)
+                        .hint_text(tr(&ctx, Text::ProfileName))
+                        .desired_width(ui.available_width()),
+                );
+            });
+        });
+    }
+
+    /// Tries the same configuration again if it failed, like once an unreachable server is back.
+    pub fn retry(&mut self) {
+        if self.display_error.take().is_some() {
+            self.is_startup = true;
+        }
+    }
+
+    /// Starts (or continues) setup with the current configuration without drawing anything.
+    /// Used by embed mode, where the setup modal is hidden.
+    pub fn poll_headless(&mut self) -> Result<Option<(Backend, BackendConfig)>, &anyhow::Error> {
+        if self.display_error.is_some() {
+            return Err(self.display_error.as_ref().unwrap());
+        }
+
+        match self.setup_promise.take().map(PromiseKind::try_take) {
+            None => {
+                let config = self.config();
+                self.is_startup = false;
+                self.setup_promise = Some(UnsendPromise::new(async move {
+                    Backend::new(config.clone())
+                        .await
+                        .map(|backend| (backend, config))
+                }));
+                Ok(None)
+            }
+            Some(Err(promise)) => {
+                self.setup_promise = Some(promise);
+                Ok(None)
+            }
+            Some(Ok(Ok(backend))) => Ok(Some(backend)),
+            Some(Ok(Err(err))) => {
+                log::error!("Setup Error: {err}");
+                Err(self.display_error.insert(err))
+            }
+        }
+    }
+
     fn can_go(&self) -> bool {
         #[cfg(target_arch = "wasm32")]
         if !*IS_DIRECTORY_PICKER_SUPPORTED
@@ -789,6 +976,21 @@ impl SetupWindow {
     }
 }
 
+fn draw_local_hint(ui: &mut egui::Ui) {
+    ui.label(
+        egui::RichText::new(tr(
+            ui.ctx(),
+            if cfg!(target_arch = "wasm32") {
+                Text::LocalInstallHint
+            } else {
+                Text::LocalInstallPatchHint
+            },
+        ))
+        .small()
+        .weak(),
+    );
+}
+
 fn radio(ui: &mut egui::Ui, selected: bool, text: impl Into<WidgetText>) -> bool {
     let mut resp = ui
         .vertical_centered_justified(|ui| ui.radio(selected, text))
@@ -801,6 +1003,118 @@ fn radio(ui: &mut egui::Ui, selected: bool, text: impl Into<WidgetText>) -> bool
     }
 }
 
+/// Picks the install that string columns are substituted from, if any. Only the latest
+/// version of a web install can be picked, since it's only read for names and text.
+fn draw_overlay(ui: &mut egui::Ui, overlay: &mut Option<StringOverlay>) {
+    let ctx = &ui.ctx().clone();
+    ui.vertical_centered(|ui| {
+        ui.heading(tr(ctx, Text::StringOverlay));
+    });
+    let mut enabled = overlay.is_some();
+    if ui
+        .checkbox(&mut enabled, tr(ctx, Text::StringOverlayHint))
+        .changed()
+    {
+        *overlay = enabled.then(|| StringOverlay {
+            location: InstallLocation::Web(DEFAULT_API_URL.to_string(), Region::Global, None),
+            language: Language::English,
+        });
+    }
+    let Some(overlay) = overlay else {
+        return;
+    };
+
+    ui.horizontal(|ui| {
+        ui.columns_const(|[col_0, col_1]| {
+            #[cfg(not(target_arch = "wasm32"))]
+            if radio(
+                col_0,
+                matches!(overlay.location, InstallLocation::Sqpack(_)),
+                tr(ctx, Text::LocalInstall),
+            ) {
+                overlay.location = InstallLocation::Sqpack(String::new());
+            }
+            #[cfg(target_arch = "wasm32")]
+            let _ = col_0;
+            if radio(
+                col_1,
+                matches!(overlay.location, InstallLocation::Web(_, _, _)),
+                tr(ctx, Text::WebInstall),
+            ) {
+                overlay.location =
+                    InstallLocation::Web(DEFAULT_API_URL.to_string(), Region::Global, None);
+            }
+        });
+    });
+    match &mut overlay.location {
+        #[cfg(not(target_arch = "wasm32"))]
+        InstallLocation::Sqpack(path) => {
+            ui.horizontal(|ui| {
+                ui.label(tr(ctx, Text::PathLabel));
+                ui.with_layout(Layout::right_to_left(egui::Align::Min), |ui| {
+                    if ui.button(tr(ctx, Text::Browse)).clicked()
+                        && let Some(picked_path) = rfd::FileDialog::new()
+                            .pick_folder()
+                            .and_then(|d| d.to_str().map(|s| s.to_owned()))
+                    {
+                        *path = picked_path;
+                    }
+                    ui.add(TextEdit::singleline(path).desired_width(ui.available_width()));
+                });
+            });
+        }
+        InstallLocation::Web(url, region, _) => {
+            ui.horizontal(|ui| {
+                ui.label(tr(ctx, Text::UrlLabel));
+                ui.add(TextEdit::singleline(url).desired_width(ui.available_width()));
+            });
+            ui.horizontal(|ui| {
+                ui.label(tr(ctx, Text::RegionLabel));
+                egui::ComboBox::from_id_salt("setup_overlay_region")
+                    .selected_text(region.name())
+                    .width(ui.available_width())
+                    .show_ui(ui, |ui| {
+                        for r in [Region::Global, Region::Korea, Region::China] {
+                            ui.selectable_value(region, r, r.name());
+                        }
+                    });
+            });
+        }
+        _ => {}
+    }
+    ui.horizontal(|ui| {
+        ui.label(tr(ctx, Text::LanguageLabel));
+        egui::ComboBox::from_id_salt("setup_overlay_language")
+            .selected_text(overlay.language.to_string())
+            .width(ui.available_width())
+            .show_ui(ui, |ui| {
+                for language in Language::iter().filter(|&l| l != Language::None) {
+                    ui.selectable_value(&mut overlay.language, language, language.to_string());
+                }
+            });
+    });
+}
+
+/// Input for what a registered provider kind is opened from, or why it can't be.
+fn registered_argument(
+    ui: &mut egui::Ui,
+    hint: anyhow::Result<&'static str>,
+    argument: &mut String,
+) {
+    match hint {
+        Ok(hint) => {
+            ui.add(
+                TextEdit::singleline(argument)
+                    .hint_text(hint)
+                    .desired_width(ui.available_width()),
+            );
+        }
+        Err(e) => {
+            ui.colored_label(ui.visuals().error_fg_color, e.to_string());
+        }
+    }
+}
+
 #[cfg(target_arch = "wasm32")]
 type SelectedPickerPromise = UnsendPromise<anyhow::Result<WorkerDirectory>>;
 
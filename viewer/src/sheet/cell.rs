@@ -13,7 +13,12 @@ use serde::{Deserialize, Serialize};
 use crate::{
     data::get_icon_path,
     excel::provider::{ExcelProvider, ExcelRow, ExcelSheet},
-    settings::{ALWAYS_HIRES, DISPLAY_FIELD_SHOWN, EVALUATE_STRINGS, TEXT_MAX_LINES},
+    export,
+    i18n::{Text, tr},
+    settings::{
+        ALWAYS_HIRES, COLUMN_NUMBER_FORMATS, DISPLAY_FIELD_SHOWN, EVALUATE_STRINGS,
+        SHOW_RAW_VALUES, TEXT_MAX_LINES,
+    },
     sheet::{
         compact_sestring::CompactSeString,
         schema_column::{ResolvedTableContext, SheetLink},
@@ -24,12 +29,18 @@ use crate::{
 };
 
 use super::{
-    GlobalContext, copyable_label,
+    GlobalContext, NumberFormat, copyable_label,
+    map_coordinate::{MapAxis, MapScale},
+    numeric_label,
     schema_column::{SchemaColumn, SchemaColumnMeta},
     sheet_column::SheetColumnDefinition,
     table_context::TableContext,
 };
 
+/// How many columns of the linked row are shown when hovering a link cell.
+const LINK_PREVIEW_FIELD_COUNT: usize = 8;
+const LINK_PREVIEW_MAX_WIDTH: f32 = 360.0;
+
 pub struct Cell<'a> {
     row: ExcelRow<'a>,
     // This can be either a SchemaColumn or a SchemaColumnMeta::Link to a sheet link (and None if no sheets are linked) (as a reference)
@@ -52,6 +63,19 @@ pub enum CellResponse {
     Row(SheetRef),
 }
 
+/// The row a link cell points to, as resolved by [`Cell::link_target`].
+pub enum LinkTarget {
+    /// The raw value doesn't point to a row in any of the target sheets.
+    Invalid(i128),
+    /// One of the target sheets is still loading.
+    InProgress(i128),
+    Found {
+        sheet_name: String,
+        row_id: u32,
+        table: TableContext,
+    },
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct MatchOptions {
     pub case_insensitive: bool,
@@ -163,8 +187,113 @@ impl<'a> Cell<'a> {
     }
 
     fn draw(self, ui: &mut egui::Ui) -> anyhow::Result<InnerResponse<CellResponse>> {
-        self.read(DISPLAY_FIELD_SHOWN.get(ui.ctx()))
-            .map(|value| value.show(ui, self.table_context.global()))
+        let show_raw = SHOW_RAW_VALUES.get(ui.ctx());
+        let value = if show_raw {
+            self.read_raw()
+        } else {
+            self.read(DISPLAY_FIELD_SHOWN.get(ui.ctx()))
+        }?;
+        if !show_raw
+            && let Either::Left(schema_column) = &self.schema_column
+            && let Some(axis) = schema_column.map_axis()
+            && let Some(raw) = position(&value)
+            && let Ok(Some((x, y))) = self.map_position(axis, raw)
+        {
+            let coordinate = if axis.is_y { y } else { x };
+            let text = format!(
+                "{} ({} {coordinate:.1})",
+                value.coerce_string(),
+                axis.name()
+            );
+            let resp = numeric_label(ui, &text).on_hover_text(format!("X {x:.1}, Y {y:.1}"));
+            return Ok(InnerResponse::new(CellResponse::None, resp));
+        }
+        if let CellValue::Integer(value) = value
+            && let Some(format) = self.number_format(ui.ctx())
+        {
+            let kind = self.sheet_column.kind();
+            let mut resp = numeric_label(ui, &format.format(value, kind));
+            if format == NumberFormat::Flags && value != 0 {
+                resp = resp.on_hover_ui(|ui| draw_flag_breakdown(ui, value, kind));
+            } else if format.is_interpretation() {
+                resp = resp.on_hover_text(value.to_string());
+            }
+            return Ok(InnerResponse::new(CellResponse::None, resp));
+        }
+        let is_valid_link = matches!(value, CellValue::ValidLink { .. });
+        let mut resp = value.show(ui, self.table_context.global());
+        if is_valid_link {
+            resp.response = resp.response.on_hover_ui(|ui| match self.link_target() {
+                Ok(Some(LinkTarget::Found {
+                    sheet_name,
+                    row_id,
+                    table,
+                })) => draw_link_preview(ui, &sheet_name, row_id, &table),
+                Ok(_) => {}
+                Err(err) => {
+                    ui.colored_label(Color32::LIGHT_RED, err.to_string());
+                }
+            });
+        }
+        Ok(resp)
+    }
+
+    /// The map coordinates of the position the column is an axis of, `None` while the map it's
+    /// on is still loading.
+    fn map_position(&self, axis: &MapAxis, raw: f64) -> anyhow::Result<Option<(f64, f64)>> {
+        let other = self
+            .table_context
+            .cell_by_offset(self.row, axis.other_idx)?
+            .read_raw()?;
+        let Some(other) = position(&other) else {
+            return Ok(None);
+        };
+        let map_target = match axis.map_idx {
+            Some(map_idx) => self
+                .table_context
+                .cell_by_offset(self.row, map_idx)?
+                .link_target()?,
+            None => None,
+        };
+        let scale = match map_target {
+            Some(LinkTarget::Found { row_id, table, .. }) => {
+                let row = table.sheet().get_row(row_id)?;
+                let read = |name: &str| {
+                    table
+                        .column_index_by_name(name)
+                        .and_then(|idx| table.cell_by_index(row, idx).ok()?.read_raw().ok())
+                        .as_ref()
+                        .and_then(position)
+                        .ok_or_else(|| anyhow::anyhow!("Map sheet has no {name} column"))
+                };
+                MapScale {
+                    size_factor: read("SizeFactor")?,
+                    offset_x: read("OffsetX")?,
+                    offset_y: read("OffsetY")?,
+                }
+            }
+            Some(LinkTarget::InProgress(_)) => return Ok(None),
+            _ => MapScale::new(axis.size_factor),
+        };
+        let (x, y) = if axis.is_y {
+            (other, raw)
+        } else {
+            (raw, other)
+        };
+        Ok(Some((scale.to_map(x, false), scale.to_map(y, true))))
+    }
+
+    /// The format picked for the column, if it isn't the default.
+    fn number_format(&self, ctx: &egui::Context) -> Option<NumberFormat> {
+        let offset_idx = self
+            .table_context
+            .convert_column_index_to_offset_index(self.sheet_column.id)
+            .ok()?;
+        COLUMN_NUMBER_FORMATS.use_with(ctx, |map| {
+            map.get(self.table_context.sheet().name())?
+                .get(&offset_idx)
+                .copied()
+        })
     }
 
     fn size_text(&self, ui: &mut egui::Ui) -> f32 {
@@ -211,22 +340,47 @@ impl<'a> Cell<'a> {
         )
     }
 
+    fn size_scalar(&self, ui: &mut egui::Ui) -> anyhow::Result<f32> {
+        Ok(if self.sheet_column.kind() == ColumnKind::String {
+            let text = read_string(
+                self.row,
+                self.sheet_column.offset() as u32,
+                self.sheet_column.kind(),
+                ui,
+            )?;
+            self.size_text_multiline(ui, &text)
+        } else {
+            self.size_text(ui)
+        })
+    }
+
+    /// The height of every cell of a column, or `None` when it depends on the row. Matches
+    /// what the cells are sized as.
+    pub fn fixed_size(
+        ui: &egui::Ui,
+        schema_column: &SchemaColumn,
+        sheet_column: &SheetColumnDefinition,
+    ) -> Option<f32> {
+        let text = ui.text_style_height(&egui::TextStyle::Body);
+        let scalar = (sheet_column.kind() != ColumnKind::String).then_some(text);
+        if SHOW_RAW_VALUES.get(ui.ctx()) {
+            return scalar;
+        }
+        match schema_column.meta() {
+            SchemaColumnMeta::Scalar => scalar,
+            SchemaColumnMeta::Icon => Some(32.0),
+            SchemaColumnMeta::ModelId | SchemaColumnMeta::Color => Some(text),
+            SchemaColumnMeta::Link(_) | SchemaColumnMeta::ConditionalLink { .. } => None,
+        }
+    }
+
     fn size_internal(&self, ui: &mut egui::Ui) -> anyhow::Result<f32> {
+        if SHOW_RAW_VALUES.get(ui.ctx()) {
+            return self.size_scalar(ui);
+        }
         Ok(match &self.schema_column {
             Either::Left(schema_column) => match schema_column.meta() {
-                SchemaColumnMeta::Scalar => {
-                    if self.sheet_column.kind() == ColumnKind::String {
-                        let text = read_string(
-                            self.row,
-                            self.sheet_column.offset() as u32,
-                            self.sheet_column.kind(),
-                            ui,
-                        )?;
-                        self.size_text_multiline(ui, &text)
-                    } else {
-                        self.size_text(ui)
-                    }
-                }
+                SchemaColumnMeta::Scalar => self.size_scalar(ui)?,
                 SchemaColumnMeta::Icon => 32.0,
                 SchemaColumnMeta::ModelId => self.size_text(ui),
                 SchemaColumnMeta::Color => self.size_text(ui),
@@ -273,6 +427,16 @@ impl<'a> Cell<'a> {
         Ok(size_ui.min_rect().size().y)
     }
 
+    /// How wide the cell is when drawn without wrapping or truncation.
+    pub fn width(self, ui: &mut egui::Ui) -> f32 {
+        let mut size_ui = ui.new_child(egui::UiBuilder::new().sizing_pass().max_rect(
+            egui::Rect::from_min_size(ui.max_rect().min, egui::Vec2::INFINITY),
+        ));
+        size_ui.style_mut().wrap_mode = Some(egui::TextWrapMode::Extend);
+        self.show(&mut size_ui);
+        size_ui.min_rect().width()
+    }
+
     pub fn show(self, ui: &mut egui::Ui) -> InnerResponse<CellResponse> {
         match self.draw(ui) {
             Ok(resp) => resp,
@@ -325,14 +489,64 @@ impl<'a> Cell<'a> {
         )
     }
 
+    /// Resolves the row this cell links to, or `None` if the cell isn't a link.
+    pub fn link_target(&self) -> anyhow::Result<Option<LinkTarget>> {
+        let sheets = match &self.schema_column {
+            Either::Left(schema_column) => match schema_column.meta() {
+                SchemaColumnMeta::Link(sheets) => Some(sheets),
+                SchemaColumnMeta::ConditionalLink { column_idx, links } => {
+                    let (_, switch_column) =
+                        self.table_context.get_column_by_offset(*column_idx)?;
+                    let switch_data: i32 = read_integer(
+                        self.row,
+                        switch_column.offset() as u32,
+                        switch_column.kind(),
+                    )?;
+                    links.get(&switch_data)
+                }
+                _ => return Ok(None),
+            },
+            Either::Right(sheets) => *sheets,
+        };
+
+        let row_id: i128 = read_integer(
+            self.row,
+            self.sheet_column.offset() as u32,
+            self.sheet_column.kind(),
+        )?;
+
+        Ok(Some(
+            match row_id
+                .try_into()
+                .ok()
+                .and_then(|id| sheets.map(|s| (s.resolve(self.table_context, id), id)))
+            {
+                Some((ResolvedTableContext::Found { sheet_name, table }, id)) => {
+                    LinkTarget::Found {
+                        sheet_name: sheet_name.clone(),
+                        row_id: id,
+                        table,
+                    }
+                }
+                Some((ResolvedTableContext::InProgress, _)) => LinkTarget::InProgress(row_id),
+                _ => LinkTarget::Invalid(row_id),
+            },
+        ))
+    }
+
+    /// Reads the underlying value, ignoring the schema's icons, links, and colors.
+    pub fn read_raw(&self) -> anyhow::Result<CellValue> {
+        read_scalar(
+            self.row,
+            self.sheet_column.offset() as u32,
+            self.sheet_column.kind(),
+        )
+    }
+
     pub fn read(&self, resolve_display_field: bool) -> anyhow::Result<CellValue> {
         Ok(match &self.schema_column {
             Either::Left(schema_column) => match schema_column.meta() {
-                SchemaColumnMeta::Scalar => read_scalar(
-                    self.row,
-                    self.sheet_column.offset() as u32,
-                    self.sheet_column.kind(),
-                )?,
+                SchemaColumnMeta::Scalar => self.read_raw()?,
                 SchemaColumnMeta::Icon => {
                     let icon_id: i128 = read_integer(
                         self.row,
@@ -463,13 +677,16 @@ impl CellValue {
     pub fn show(self, ui: &mut egui::Ui, ctx: &GlobalContext) -> InnerResponse<CellResponse> {
         let resp = match self {
             CellValue::String(value) => string_label_wrapped(ui, &value),
-            CellValue::Integer(value) => copyable_label(ui, &value),
-            CellValue::Float(value) => copyable_label(ui, &value),
+            CellValue::Integer(value) => numeric_label(ui, &value),
+            CellValue::Float(value) => numeric_label(ui, &value),
             CellValue::Boolean(value) => copyable_label(ui, &value),
             CellValue::Icon(icon_id) => {
-                let Ok(icon_id) = icon_id.try_into() else {
-                    return InnerResponse::new(CellResponse::None, copyable_label(ui, &icon_id));
+                let Ok(icon_id) = u32::try_from(icon_id) else {
+                    return InnerResponse::new(CellResponse::None, numeric_label(ui, &icon_id));
                 };
+                if !ctx.backend().capabilities().supports_icons {
+                    return InnerResponse::new(CellResponse::None, numeric_label(ui, &icon_id));
+                }
 
                 let resp = draw_icon(ctx, ui, icon_id).on_hover_cursor(CursorIcon::PointingHand);
                 if resp.clicked() && !should_ignore_clicks(ui) {
@@ -504,10 +721,7 @@ impl CellValue {
                 value,
             } => {
                 let resp = if let Some(cell) = value {
-                    let mut resp = cell.show(ui, ctx);
-                    resp.response = resp
-                        .response
-                        .on_hover_text(format!("{sheet_name}#{row_id}"));
+                    let resp = cell.show(ui, ctx);
                     if !matches!(resp.inner, CellResponse::None) {
                         return resp;
                     }
@@ -530,12 +744,105 @@ impl CellValue {
     }
 }
 
+/// A raw world position read from a cell.
+fn position(value: &CellValue) -> Option<f64> {
+    match value {
+        CellValue::Float(value) => Some(f64::from(*value)),
+        CellValue::Integer(value) => Some(*value as f64),
+        _ => None,
+    }
+}
+
+/// Lists the bits set in a bitfield cell, shown when hovering it.
+fn draw_flag_breakdown(ui: &mut egui::Ui, value: i128, kind: ColumnKind) {
+    egui::Grid::new("flag-breakdown")
+        .striped(true)
+        .show(ui, |ui| {
+            for bit in NumberFormat::set_bits(value, kind) {
+                ui.label(tr(ui.ctx(), Text::BitN).replace("{n}", &bit.to_string()));
+                ui.monospace(format!("0x{:X}", 1u128 << bit));
+                ui.monospace((1u128 << bit).to_string());
+                ui.end_row();
+            }
+        });
+}
+
+/// Draws a card with the display field and first few columns of a linked row, shown when
+/// hovering a link cell.
+fn draw_link_preview(ui: &mut egui::Ui, sheet_name: &str, row_id: u32, table: &TableContext) {
+    ui.set_max_width(LINK_PREVIEW_MAX_WIDTH);
+    ui.strong(format!("{sheet_name}#{row_id}"));
+
+    let row = match table.sheet().get_row(row_id) {
+        Ok(row) => row,
+        Err(err) => {
+            ui.colored_label(Color32::LIGHT_RED, err.to_string());
+            return;
+        }
+    };
+
+    let display_column = table.display_column().map(|(offset_idx, _)| offset_idx);
+    let columns = (0..table.column_count() as u32)
+        .filter_map(|column_idx| {
+            table
+                .get_column_by_index(column_idx)
+                .ok()
+                .map(|(_, offset_idx)| offset_idx)
+        })
+        .filter(|&offset_idx| Some(offset_idx) != display_column);
+    let shown = display_column
+        .into_iter()
+        .chain(columns)
+        .take(LINK_PREVIEW_FIELD_COUNT);
+
+    ui.separator();
+    egui::Grid::new(("link-preview", sheet_name, row_id))
+        .striped(true)
+        .show(ui, |ui| {
+            for offset_idx in shown {
+                let Ok(cell) = table.cell_by_offset(row, offset_idx) else {
+                    continue;
+                };
+                let name = table
+                    .get_column_by_offset(offset_idx)
+                    .map(|(column, _)| column.name().to_owned())
+                    .unwrap_or_default();
+                if Some(offset_idx) == display_column {
+                    ui.label(egui::RichText::new(name).strong());
+                } else {
+                    ui.label(egui::RichText::new(name).weak());
+                }
+                match cell.read(true) {
+                    Ok(value) => {
+                        value.show(ui, table.global());
+                    }
+                    Err(err) => {
+                        ui.colored_label(Color32::LIGHT_RED, "⚠")
+                            .on_hover_text(err.to_string());
+                    }
+                }
+                ui.end_row();
+            }
+        });
+
+    let hidden = table
+        .column_count()
+        .saturating_sub(LINK_PREVIEW_FIELD_COUNT);
+    if hidden > 0 {
+        ui.label(egui::RichText::new(format!("+{hidden}…")).weak());
+    }
+}
+
 fn draw_icon(ctx: &GlobalContext, ui: &mut egui::Ui, icon_id: u32) -> egui::Response {
     let (excel, icon_mgr) = (ctx.backend().excel().clone(), &ctx.icon_manager());
     let hires = ALWAYS_HIRES.get(ui.ctx());
-    let image_source = icon_mgr.get_or_insert_icon(icon_id, hires, ui.ctx(), move || {
+    let image_source = icon_mgr.get_or_insert_icon(icon_id, hires, ui.ctx(), move |etag| {
         log::debug!("Icon not found in cache: {icon_id}");
-        TrackedPromise::spawn_local(async move { excel.get_icon(icon_id, hires).await })
+        TrackedPromise::spawn_local(async move {
+            excel
+                .get_icon_if_changed(icon_id, hires, etag.as_deref())
+                .await
+        })
     });
     let resp = match image_source {
         ManagedIcon::Loaded(source) => {
@@ -572,11 +879,11 @@ fn draw_icon(ctx: &GlobalContext, ui: &mut egui::Ui, icon_id: u32) -> egui::Resp
             ui.ctx().copy_text(icon_id.to_string());
             ui.close();
         }
-        // ui.add_enabled_ui(image_source.is_some(), |ui| {
-        //     if ui.button("Save").clicked() {
-        //         image_source.unwrap().load(ctx, texture_options, size_hint)
-        //     }
-        // });
+        if ui.button(tr(ui.ctx(), Text::SaveAsPng)).clicked() {
+            // Runs detached; the promise only reports back through the log
+            let _ = export::save_icon(ctx.backend().excel().clone(), icon_id, hires);
+            ui.close();
+        }
     });
     resp
 }
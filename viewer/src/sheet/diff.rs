@@ -0,0 +1,199 @@
+use std::collections::{HashMap, HashSet};
+
+use ironworks::file::exh::{ColumnDefinition, ColumnKind};
+
+use crate::{
+    excel::{
+        base::BaseSheet,
+        provider::{ExcelHeader, ExcelRow, ExcelSheet},
+    },
+    utils::yield_to_ui,
+};
+
+use super::sheet_column::SheetColumnDefinition;
+
+/// Number of rows compared between yields to the UI.
+const DIFF_CHUNK_SIZE: usize = 512;
+
+/// Row and cell level differences of a sheet against the same sheet in another game version.
+/// Rows are keyed by (row id, subrow id); sheets without subrows use subrow 0.
+#[derive(Debug, Default)]
+pub struct SheetDiff {
+    added: HashSet<(u32, u16)>,
+    // Offset indices of changed columns
+    changed: HashMap<(u32, u16), Vec<u32>>,
+    removed: Vec<(u32, Option<u16>)>,
+    // Offset indices of columns that don't exist in the other version
+    added_columns: HashSet<u32>,
+    has_subrows: bool,
+    // Kept around to read the previous values of changed cells
+    old: Option<BaseSheet>,
+}
+
+impl SheetDiff {
+    /// Compares `new` against `old`. If `old` is `None` (the sheet doesn't exist in the other
+    /// version), every row is considered added.
+    pub async fn compute(new: &BaseSheet, old: Option<&BaseSheet>) -> anyhow::Result<Self> {
+        let mut ret = Self::default();
+
+        let new_columns = SheetColumnDefinition::from_sheet(new);
+        let old_columns = old.map(SheetColumnDefinition::from_sheet);
+        let column_pairs = new_columns
+            .iter()
+            .map(|c| {
+                old_columns.as_ref().and_then(|old| {
+                    old.iter()
+                        .find(|o| o.offset() == c.offset() && o.kind() == c.kind())
+                })
+            })
+            .collect::<Vec<_>>();
+        if old.is_some() {
+            ret.added_columns = column_pairs
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| c.is_none())
+                .map(|(i, _)| i as u32)
+                .collect();
+        }
+
+        let has_subrows = new.has_subrows();
+        ret.has_subrows = has_subrows;
+        let mut seen = HashSet::new();
+        for (i, (row_id, subrow_id)) in new.get_subrow_ids().enumerate() {
+            if i % DIFF_CHUNK_SIZE == DIFF_CHUNK_SIZE - 1 {
+                yield_to_ui().await;
+            }
+            seen.insert((row_id, subrow_id));
+
+            let old_row = old.and_then(|old| old.get_subrow(row_id, subrow_id).ok());
+            let Some(old_row) = old_row else {
+                ret.added.insert((row_id, subrow_id));
+                continue;
+            };
+            let new_row = new.get_subrow(row_id, subrow_id)?;
+
+            let mut changed = vec![];
+            for (offset_idx, (new_column, old_column)) in
+                new_columns.iter().zip(&column_pairs).enumerate()
+            {
+                let Some(old_column) = old_column else {
+                    continue;
+                };
+                if !values_equal(new_row, new_column, old_row, old_column)? {
+                    changed.push(offset_idx as u32);
+                }
+            }
+            if !changed.is_empty() {
+                ret.changed.insert((row_id, subrow_id), changed);
+            }
+        }
+
+        if let Some(old) = old {
+            for (row_id, subrow_id) in old.get_subrow_ids() {
+                if !seen.contains(&(row_id, subrow_id)) {
+                    ret.removed.push((row_id, has_subrows.then_some(subrow_id)));
+                }
+            }
+        }
+
+        ret.old = old.cloned();
+        Ok(ret)
+    }
+
+    pub fn is_added(&self, row_id: u32, subrow_id: Option<u16>) -> bool {
+        self.added
+            .contains(&(row_id, subrow_id.unwrap_or_default()))
+    }
+
+    pub fn changed_columns(&self, row_id: u32, subrow_id: Option<u16>) -> Option<&[u32]> {
+        self.changed
+            .get(&(row_id, subrow_id.unwrap_or_default()))
+            .map(Vec::as_slice)
+    }
+
+    pub fn is_changed(&self, row_id: u32, subrow_id: Option<u16>, offset_idx: u32) -> bool {
+        self.changed_columns(row_id, subrow_id)
+            .is_some_and(|c| c.contains(&offset_idx))
+    }
+
+    pub fn is_added_column(&self, offset_idx: u32) -> bool {
+        self.added_columns.contains(&offset_idx)
+    }
+
+    pub fn added_count(&self) -> usize {
+        self.added.len()
+    }
+
+    pub fn changed_count(&self) -> usize {
+        self.changed.len()
+    }
+
+    pub fn removed(&self) -> &[(u32, Option<u16>)] {
+        &self.removed
+    }
+
+    /// The sheet in the other version, if it exists there.
+    pub fn old_sheet(&self) -> Option<&BaseSheet> {
+        self.old.as_ref()
+    }
+
+    /// Added rows, sorted by id.
+    pub fn added_rows(&self) -> Vec<(u32, Option<u16>)> {
+        let mut rows = self
+            .added
+            .iter()
+            .map(|&(row_id, subrow_id)| (row_id, self.has_subrows.then_some(subrow_id)))
+            .collect::<Vec<_>>();
+        rows.sort_unstable();
+        rows
+    }
+
+    /// Changed rows and the offset indices of their changed columns, sorted by id.
+    pub fn changed_rows(&self) -> Vec<((u32, Option<u16>), &[u32])> {
+        let mut rows = self
+            .changed
+            .iter()
+            .map(|(&(row_id, subrow_id), columns)| {
+                (
+                    (row_id, self.has_subrows.then_some(subrow_id)),
+                    columns.as_slice(),
+                )
+            })
+            .collect::<Vec<_>>();
+        rows.sort_unstable_by_key(|(row, _)| *row);
+        rows
+    }
+}
+
+fn values_equal(
+    new_row: ExcelRow<'_>,
+    new_column: &ColumnDefinition,
+    old_row: ExcelRow<'_>,
+    old_column: &ColumnDefinition,
+) -> anyhow::Result<bool> {
+    let (new_offset, old_offset) = (new_column.offset() as u32, old_column.offset() as u32);
+    Ok(match new_column.kind() {
+        // String offsets can shift around between versions, so compare the contents
+        ColumnKind::String => {
+            new_row.read_string(new_offset)?.as_bytes()
+                == old_row.read_string(old_offset)?.as_bytes()
+        }
+        ColumnKind::Bool | ColumnKind::Int8 | ColumnKind::UInt8 => {
+            new_row.read::<u8>(new_offset)? == old_row.read::<u8>(old_offset)?
+        }
+        ColumnKind::Int16 | ColumnKind::UInt16 => {
+            new_row.read::<u16>(new_offset)? == old_row.read::<u16>(old_offset)?
+        }
+        ColumnKind::Int32 | ColumnKind::UInt32 | ColumnKind::Float32 => {
+            new_row.read::<u32>(new_offset)? == old_row.read::<u32>(old_offset)?
+        }
+        ColumnKind::Int64 | ColumnKind::UInt64 => {
+            new_row.read::<u64>(new_offset)? == old_row.read::<u64>(old_offset)?
+        }
+        kind => {
+            let packed_index = (u16::from(kind) - u16::from(ColumnKind::PackedBool0)) as u8;
+            new_row.read_packed_bool(new_offset, packed_index)?
+                == old_row.read_packed_bool(old_offset, packed_index)?
+        }
+    })
+}
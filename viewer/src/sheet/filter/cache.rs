@@ -197,6 +197,7 @@ impl FilterCache {
             FilterValue::Wildcard(v) => v.matches(&cell.coerce_string()),
             FilterValue::Regex(v) => v.is_match(&cell.coerce_string()),
             FilterValue::Range(v) => cell.coerce_integer().is_some_and(|i| v.contains(i)),
+            FilterValue::HasBit(bit) => cell.coerce_integer().is_some_and(|i| (i >> bit) & 1 == 1),
         }
     }
 
@@ -50,6 +50,7 @@ pub enum FilterKey {
 /// - `?="H*o W?rld"` (wildcard match "H*o W?rld")
 /// - `/="^Hello.*World$"` (regex match "^Hello.*World$")
 /// - `=10..20` (range between 10 and 20, inclusive)
+/// - `&=6` (bit 6 is set)
 /// - `!$=Test` (not ends with "Test")
 /// - `!/= "^Test.*"` (not regex match "^Test.*")
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -85,6 +86,10 @@ pub enum FilterValue {
     /// Check if the value is within a range (inclusive) with optional bounds (only for numeric values)
     /// Uses '|=' with '..' for the range
     Range(FilterRange),
+
+    /// Check if the bit at a position is set (only for numeric values)
+    /// Uses '&=' with the position, 0 being the lowest bit
+    HasBit(u8),
 }
 
 #[derive(Debug, Clone)]
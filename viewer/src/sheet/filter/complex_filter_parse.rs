@@ -211,6 +211,15 @@ fn parse_comparator(pair: Pair<'_, Rule>) -> Result<(FilterValue, bool), String>
         Rule::LESSER => FilterValue::Range(FilterRange::AtMost(
             parse_number_value(value)?.saturating_sub(1),
         )),
+        Rule::HAS_BIT => {
+            let bit = parse_number_value(value)?;
+            FilterValue::HasBit(
+                u8::try_from(bit)
+                    .ok()
+                    .filter(|&bit| bit < 64)
+                    .ok_or_else(|| format!("Invalid bit position: {bit} is not within 0..63"))?,
+            )
+        }
         _ => unreachable!("Unexpected operator in comparator: {:?}", op.as_rule()),
     };
     Ok((value, is_strict))
@@ -554,6 +563,18 @@ mod tests {
         test_filter(filter_str);
     }
 
+    #[test]
+    fn test_has_bit() {
+        let filter_str = r#"Flags &= 6 and Flags !&= 0"#;
+        test_filter(filter_str);
+    }
+
+    #[test]
+    fn test_zero() {
+        let filter_str = r#"Column1 > 0 and Column2 <= -0 and Column3 |= 0..10"#;
+        test_filter(filter_str);
+    }
+
     #[test]
     fn test_strict_greq() {
         let filter_str = r#"Colum?1 >== 10"#;
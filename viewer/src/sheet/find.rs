@@ -0,0 +1,164 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+use super::selection::CellPos;
+
+/// Cells of the shown rows that contain the find bar's query. Unlike a filter, rows without
+/// matches stay shown, and the matches are stepped through in place. Rows are searched a few
+/// at a time across frames, so big sheets don't stall the UI.
+pub struct Find {
+    query: String,
+    /// Lowercased `query`
+    needle: String,
+    /// In reading order
+    matches: Vec<CellPos>,
+    /// Rows searched so far, by row_nr
+    searched_rows: u64,
+    /// Index of the match last stepped to
+    current: Option<usize>,
+    /// Whether the query field should take focus
+    focus_requested: bool,
+}
+
+impl Find {
+    pub fn new() -> Self {
+        Self {
+            query: String::new(),
+            needle: String::new(),
+            matches: Vec::new(),
+            searched_rows: 0,
+            current: None,
+            focus_requested: true,
+        }
+    }
+
+    pub fn query_mut(&mut self) -> &mut String {
+        &mut self.query
+    }
+
+    pub fn request_focus(&mut self) {
+        self.focus_requested = true;
+    }
+
+    pub fn take_focus_request(&mut self) -> bool {
+        std::mem::take(&mut self.focus_requested)
+    }
+
+    /// Searches again from the first row, once the query or the shown rows changed.
+    pub fn restart(&mut self) {
+        self.needle = self.query.to_lowercase();
+        self.matches.clear();
+        self.searched_rows = 0;
+        self.current = None;
+    }
+
+    /// Whether every one of `row_count` rows has been searched.
+    pub fn is_done(&self, row_count: u64) -> bool {
+        self.needle.is_empty() || self.searched_rows >= row_count
+    }
+
+    /// Searches rows until `deadline`, with `cells` giving the text of each cell of a row.
+    pub fn search(
+        &mut self,
+        row_count: u64,
+        deadline: Instant,
+        mut cells: impl FnMut(u64) -> Vec<String>,
+    ) {
+        while !self.is_done(row_count) {
+            let row_nr = self.searched_rows;
+            for (col_nr, text) in cells(row_nr).iter().enumerate() {
+                if text.to_lowercase().contains(&self.needle) {
+                    self.matches.push(CellPos { row_nr, col_nr });
+                }
+            }
+            self.searched_rows += 1;
+            if Instant::now() >= deadline {
+                break;
+            }
+        }
+    }
+
+    /// Steps to the next match (or the previous one, if `backwards`), wrapping around. The first
+    /// step goes to the match nearest `from` in that direction.
+    pub fn step(&mut self, from: Option<CellPos>, backwards: bool) -> Option<CellPos> {
+        let len = self.matches.len();
+        if len == 0 {
+            return None;
+        }
+        let idx = match (self.current, from) {
+            (Some(current), _) if backwards => (current + len - 1) % len,
+            (Some(current), _) => (current + 1) % len,
+            (None, Some(from)) if backwards => {
+                (self.matches.partition_point(|m| *m < from) + len - 1) % len
+            }
+            (None, Some(from)) => self.matches.partition_point(|m| *m <= from) % len,
+            (None, None) if backwards => len - 1,
+            (None, None) => 0,
+        };
+        self.current = Some(idx);
+        Some(self.matches[idx])
+    }
+
+    pub fn is_match(&self, pos: CellPos) -> bool {
+        self.matches.binary_search(&pos).is_ok()
+    }
+
+    pub fn is_current(&self, pos: CellPos) -> bool {
+        self.current.is_some_and(|idx| self.matches[idx] == pos)
+    }
+
+    /// e.g. "3 / 12", with an ellipsis while rows are still being searched.
+    pub fn status(&self, row_count: u64) -> String {
+        let searching = if self.is_done(row_count) { "" } else { "…" };
+        match self.current {
+            Some(idx) => format!("{} / {}{searching}", idx + 1, self.matches.len()),
+            None => format!("{}{searching}", self.matches.len()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CellPos, Find, Instant};
+
+    fn pos(row_nr: u64, col_nr: usize) -> CellPos {
+        CellPos { row_nr, col_nr }
+    }
+
+    #[test]
+    fn steps_through_matches() {
+        let rows = [
+            ["0", "Potion", "x"],
+            ["1", "Ether", "potion"],
+            ["2", "Elixir", "y"],
+        ];
+        let mut find = Find::new();
+        find.query_mut().push_str("POTION");
+        find.restart();
+        find.search(3, Instant::now(), |row_nr| {
+            rows[row_nr as usize].map(str::to_owned).to_vec()
+        });
+        // Stops at the deadline, a row at a time
+        assert!(!find.is_done(3));
+        assert_eq!(find.status(3), "1…");
+        while !find.is_done(3) {
+            find.search(3, Instant::now(), |row_nr| {
+                rows[row_nr as usize].map(str::to_owned).to_vec()
+            });
+        }
+        assert!(find.is_match(pos(0, 1)) && find.is_match(pos(1, 2)));
+        assert!(!find.is_match(pos(1, 1)));
+
+        assert_eq!(find.step(Some(pos(0, 2)), false), Some(pos(1, 2)));
+        assert_eq!(find.status(3), "2 / 2");
+        assert_eq!(find.step(None, false), Some(pos(0, 1)));
+        assert_eq!(find.step(None, true), Some(pos(1, 2)));
+        assert!(find.is_current(pos(1, 2)));
+
+        find.restart();
+        find.search(3, Instant::now(), |_| Vec::new());
+        assert_eq!(find.step(Some(pos(2, 0)), true), None);
+    }
+}
@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{data::get_icon_path, excel::provider::ExcelRow};
+
+use super::{schema_column::SchemaColumnMeta, table_context::TableContext};
+
+/// A built-in formatter for the virtual helper column, which shows an identifier derived
+/// from each row so it doesn't have to be worked out by hand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HelperFormat {
+    /// The id the game uses for high quality copies of an item.
+    HqItemId,
+    /// The id the game uses for collectable copies of an item.
+    CollectableItemId,
+    HexRowId,
+    /// Game path of the row's first icon column.
+    IconPath,
+    /// Game path of the row's first icon column, in high resolution.
+    HiresIconPath,
+}
+
+impl HelperFormat {
+    pub const ALL: &[Self] = &[
+        Self::HqItemId,
+        Self::CollectableItemId,
+        Self::HexRowId,
+        Self::IconPath,
+        Self::HiresIconPath,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::HqItemId => "HQ Item ID",
+            Self::CollectableItemId => "Collectable Item ID",
+            Self::HexRowId => "Hex Row ID",
+            Self::IconPath => "Icon Path",
+            Self::HiresIconPath => "Icon Path (HR)",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::HqItemId => "Row ID + 1,000,000",
+            Self::CollectableItemId => "Row ID + 500,000",
+            Self::HexRowId => "Row ID in hexadecimal",
+            Self::IconPath => "ui/icon path of the first icon column",
+            Self::HiresIconPath => "ui/icon path of the first icon column, with the _hr1 suffix",
+        }
+    }
+
+    /// The formatter most useful for a sheet, highlighted in the picker.
+    pub fn suggested_for(table: &TableContext) -> Self {
+        match table.sheet().name() {
+            "Item" => Self::HqItemId,
+            _ if first_icon_column(table).is_some() => Self::IconPath,
+            _ => Self::HexRowId,
+        }
+    }
+
+    pub fn format(self, table: &TableContext, row_id: u32, row: ExcelRow<'_>) -> Option<String> {
+        Some(match self {
+            Self::HqItemId => (u64::from(row_id) + 1_000_000).to_string(),
+            Self::CollectableItemId => (u64::from(row_id) + 500_000).to_string(),
+            Self::HexRowId => format!("0x{row_id:X}"),
+            Self::IconPath | Self::HiresIconPath => {
+                let icon_id = table
+                    .cell_by_offset(row, first_icon_column(table)?)
+                    .ok()?
+                    .read_raw()
+                    .ok()?
+                    .coerce_integer()?;
+                get_icon_path(
+                    u32::try_from(icon_id).ok().filter(|&id| id != 0)?,
+                    self == Self::HiresIconPath,
+                )
+            }
+        })
+    }
+}
+
+/// Offset index of the first icon column, in schema order.
+fn first_icon_column(table: &TableContext) -> Option<u32> {
+    (0..table.column_count() as u32).find_map(|column_idx| {
+        let ((column, _), offset_idx) = table.get_column_by_index(column_idx).ok()?;
+        matches!(column.meta(), SchemaColumnMeta::Icon).then_some(offset_idx)
+    })
+}
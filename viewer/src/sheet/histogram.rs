@@ -0,0 +1,317 @@
+use std::{cell::Cell, rc::Rc};
+
+use egui::{Color32, ProgressBar, Rect, RichText, Sense, pos2, vec2};
+use ironworks::file::exh::ColumnKind;
+use itertools::Itertools;
+
+use crate::{
+    excel::provider::ExcelSheet,
+    i18n::{Text, tr},
+    utils::{PromiseKind, TrackedPromise, yield_to_ui},
+};
+
+use super::{CellValue, sheet_column::SheetColumnDefinition, table_context::TableContext};
+
+/// Most bars a histogram is split into.
+const BIN_COUNT: usize = 32;
+/// Rows read between yields to the UI.
+const SCAN_CHUNK_SIZE: usize = 0x2000;
+/// Most common values listed under the histogram.
+const MOST_COMMON_COUNT: usize = 8;
+const HISTOGRAM_HEIGHT: f32 = 120.0;
+
+/// Summary of every value in a column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnStats {
+    pub count: usize,
+    pub distinct: usize,
+    pub min: f64,
+    pub max: f64,
+    /// (lowest value, highest value, count) of each bar. Small integer ranges get a bar per
+    /// value, where both bounds are the same.
+    pub bins: Vec<(f64, f64, usize)>,
+    /// Most common values and how often they appear, most common first
+    pub most_common: Vec<(f64, usize)>,
+}
+
+impl ColumnStats {
+    /// Returns `None` if there are no values.
+    pub fn from_values(mut values: Vec<f64>) -> Option<Self> {
+        values.sort_by(f64::total_cmp);
+        let (min, max) = (*values.first()?, *values.last()?);
+        let counts = values
+            .iter()
+            .chunk_by(|v| **v)
+            .into_iter()
+            .map(|(value, group)| (value, group.count()))
+            .collect_vec();
+
+        let is_integral = values.iter().all(|v| v.fract() == 0.0);
+        let bins = if is_integral && max - min < BIN_COUNT as f64 {
+            (min as i64..=max as i64)
+                .map(|value| {
+                    let value = value as f64;
+                    let count = counts
+                        .iter()
+                        .find(|(v, _)| *v == value)
+                        .map_or(0, |(_, count)| *count);
+                    (value, value, count)
+                })
+                .collect()
+        } else {
+            let width = (max - min) / BIN_COUNT as f64;
+            let mut bins = (0..BIN_COUNT)
+                .map(|i| {
+                    let low = min + width * i as f64;
+                    (low, low + width, 0)
+                })
+                .collect_vec();
+            for value in &values {
+                let idx = (((value - min) / width) as usize).min(BIN_COUNT - 1);
+                bins[idx].2 += 1;
+            }
+            bins
+        };
+
+        let most_common = counts
+            .iter()
+            .sorted_by(|(_, a), (_, b)| b.cmp(a))
+            .take(MOST_COMMON_COUNT)
+            .copied()
+            .collect();
+
+        Some(Self {
+            count: values.len(),
+            distinct: counts.len(),
+            min,
+            max,
+            bins,
+            most_common,
+        })
+    }
+}
+
+/// Whether the column holds numbers worth plotting.
+pub fn is_numeric(column: &SheetColumnDefinition) -> bool {
+    matches!(
+        column.kind(),
+        ColumnKind::Int8
+            | ColumnKind::UInt8
+            | ColumnKind::Int16
+            | ColumnKind::UInt16
+            | ColumnKind::Int32
+            | ColumnKind::UInt32
+            | ColumnKind::Float32
+            | ColumnKind::Int64
+            | ColumnKind::UInt64
+    )
+}
+
+/// A window plotting the distribution of a numeric column's raw values, to tell flags, ids,
+/// percentages and the like apart at a glance.
+pub struct ColumnHistogram {
+    column_name: String,
+    /// (scanned, total)
+    progress: Rc<Cell<(usize, usize)>>,
+    promise: Option<TrackedPromise<anyhow::Result<Option<ColumnStats>>>>,
+    stats: Option<Result<Option<ColumnStats>, String>>,
+    log_scale: bool,
+}
+
+impl ColumnHistogram {
+    pub fn new(table: TableContext, offset_idx: u32, column_name: String) -> Self {
+        let progress = Rc::new(Cell::new((0, 0)));
+        let promise = TrackedPromise::spawn_local(scan(table, offset_idx, progress.clone()));
+        Self {
+            column_name,
+            progress,
+            promise: Some(promise),
+            stats: None,
+            log_scale: false,
+        }
+    }
+
+    /// `open` is cleared when the window is closed.
+    pub fn draw(&mut self, ctx: &egui::Context, open: &mut bool) {
+        if let Some(promise) = self.promise.take() {
+            match promise.try_take() {
+                Ok(result) => self.stats = Some(result.map_err(|e| e.to_string())),
+                Err(promise) => self.promise = Some(promise),
+            }
+        }
+
+        egui::Window::new(format!(
+            "{} - {}",
+            tr(ctx, Text::ValueDistribution),
+            self.column_name
+        ))
+        .id(egui::Id::new("column-histogram"))
+        .open(open)
+        .default_width(420.0)
+        .show(ctx, |ui| match &self.stats {
+            None => {
+                let (done, total) = self.progress.get();
+                if total == 0 {
+                    ui.spinner();
+                } else {
+                    ui.add(
+                        ProgressBar::new(done as f32 / total as f32)
+                            .text(format!("{done} / {total}")),
+                    );
+                }
+            }
+            Some(Err(e)) => {
+                ui.colored_label(ui.visuals().error_fg_color, e);
+            }
+            Some(Ok(None)) => {
+                ui.label(tr(ctx, Text::SheetHasNoRows));
+            }
+            Some(Ok(Some(stats))) => {
+                egui::Grid::new("column-histogram-stats")
+                    .num_columns(4)
+                    .show(ui, |ui| {
+                        ui.label(tr(ctx, Text::HistogramValues));
+                        ui.label(stats.count.to_string());
+                        ui.label(tr(ctx, Text::HistogramDistinct));
+                        ui.label(stats.distinct.to_string());
+                        ui.end_row();
+                        ui.label(tr(ctx, Text::HistogramMin));
+                        ui.label(format_value(stats.min));
+                        ui.label(tr(ctx, Text::HistogramMax));
+                        ui.label(format_value(stats.max));
+                        ui.end_row();
+                    });
+                ui.checkbox(&mut self.log_scale, tr(ctx, Text::LogarithmicScale));
+                draw_bars(ui, stats, self.log_scale);
+
+                ui.separator();
+                ui.label(RichText::new(tr(ctx, Text::MostCommon)).strong());
+                egui::Grid::new("column-histogram-common")
+                    .num_columns(2)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (value, count) in &stats.most_common {
+                            ui.label(format_value(*value));
+                            ui.label(format!(
+                                "{count} ({:.1}%)",
+                                *count as f64 * 100.0 / stats.count as f64
+                            ));
+                            ui.end_row();
+                        }
+                    });
+            }
+        });
+    }
+}
+
+fn draw_bars(ui: &mut egui::Ui, stats: &ColumnStats, log_scale: bool) {
+    let (rect, resp) =
+        ui.allocate_exact_size(vec2(ui.available_width(), HISTOGRAM_HEIGHT), Sense::hover());
+    let scale = |count: usize| {
+        if log_scale {
+            (count as f32).ln_1p()
+        } else {
+            count as f32
+        }
+    };
+    let peak = scale(
+        stats
+            .bins
+            .iter()
+            .map(|(_, _, c)| *c)
+            .max()
+            .unwrap_or_default(),
+    )
+    .max(1.0);
+    let bar_width = rect.width() / stats.bins.len() as f32;
+    let hovered = resp
+        .hover_pos()
+        .map(|pos| ((pos.x - rect.left()) / bar_width) as usize)
+        .filter(|idx| *idx < stats.bins.len());
+
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+    for (idx, &(_, _, count)) in stats.bins.iter().enumerate() {
+        let height = rect.height() * scale(count) / peak;
+        let left = rect.left() + bar_width * idx as f32;
+        let bar = Rect::from_min_max(
+            pos2(left + 1.0, rect.bottom() - height),
+            pos2(left + bar_width - 1.0, rect.bottom()),
+        );
+        let color = if hovered == Some(idx) {
+            ui.visuals().selection.bg_fill
+        } else {
+            Color32::LIGHT_BLUE.gamma_multiply(0.6)
+        };
+        painter.rect_filled(bar, 1.0, color);
+    }
+
+    if let Some(idx) = hovered {
+        let (low, high, count) = stats.bins[idx];
+        let range = if low == high {
+            format_value(low)
+        } else {
+            format!("{} to {}", format_value(low), format_value(high))
+        };
+        resp.on_hover_text_at_pointer(format!("{range}: {count}"));
+    }
+}
+
+/// Integers without a trailing `.0`, since most columns hold them.
+fn format_value(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        (value as i64).to_string()
+    } else {
+        format!("{value:.3}")
+    }
+}
+
+/// Reads the column from every row of the sheet.
+async fn scan(
+    table: TableContext,
+    offset_idx: u32,
+    progress: Rc<Cell<(usize, usize)>>,
+) -> anyhow::Result<Option<ColumnStats>> {
+    let total = table.sheet().subrow_count() as usize;
+    progress.set((0, total));
+
+    let mut values = Vec::with_capacity(total);
+    for chunk in &table.sheet().get_subrow_ids().chunks(SCAN_CHUNK_SIZE) {
+        for (row_id, subrow_id) in chunk {
+            let row = table.sheet().get_subrow(row_id, subrow_id)?;
+            let value = match table.cell_by_offset(row, offset_idx)?.read_raw()? {
+                CellValue::Float(value) => f64::from(value),
+                value => value.coerce_integer().unwrap_or_default() as f64,
+            };
+            values.push(value);
+        }
+        progress.set((values.len(), total));
+        yield_to_ui().await;
+    }
+    Ok(ColumnStats::from_values(values))
+}
+
+#[cfg(test)]
+mod test {
+    use super::ColumnStats;
+
+    #[test]
+    fn small_integer_ranges() {
+        let stats = ColumnStats::from_values(vec![1.0, 4.0, 1.0, 2.0, 1.0]).unwrap();
+        assert_eq!((stats.count, stats.distinct), (5, 3));
+        assert_eq!(
+            stats.bins,
+            [(1.0, 1.0, 3), (2.0, 2.0, 1), (3.0, 3.0, 0), (4.0, 4.0, 1)]
+        );
+        assert_eq!(stats.most_common[0], (1.0, 3));
+    }
+
+    #[test]
+    fn wide_ranges() {
+        let stats = ColumnStats::from_values((0..=1000).map(f64::from).collect()).unwrap();
+        assert_eq!(stats.bins.len(), super::BIN_COUNT);
+        assert_eq!(stats.bins.iter().map(|(_, _, c)| c).sum::<usize>(), 1001);
+        assert_eq!(stats.bins.last().unwrap().1, 1000.0);
+        assert_eq!(ColumnStats::from_values(Vec::new()), None);
+    }
+}
@@ -0,0 +1,66 @@
+/// A column named in a schema's `coordinates` block, whose raw positions are shown as the
+/// coordinates the game shows on its maps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapAxis {
+    /// `true` for the Y axis, which uses the map's Y offset.
+    pub is_y: bool,
+    /// Offset index of the other axis' column.
+    pub other_idx: u32,
+    /// Offset index of the column linking to the Map sheet, to read its size factor and offsets
+    /// from.
+    pub map_idx: Option<u32>,
+    /// Used when there's no map column.
+    pub size_factor: u16,
+}
+
+impl MapAxis {
+    pub fn name(&self) -> &'static str {
+        if self.is_y { "Y" } else { "X" }
+    }
+}
+
+/// The scale and offsets of a row of the Map sheet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MapScale {
+    pub size_factor: f64,
+    pub offset_x: f64,
+    pub offset_y: f64,
+}
+
+impl MapScale {
+    pub fn new(size_factor: u16) -> Self {
+        Self {
+            size_factor: f64::from(size_factor),
+            offset_x: 0.0,
+            offset_y: 0.0,
+        }
+    }
+
+    /// Converts a raw world position to the coordinate shown on the map, which runs from 1 to
+    /// about 42 on a map with a size factor of 100.
+    pub fn to_map(self, raw: f64, is_y: bool) -> f64 {
+        let scale = self.size_factor / 100.0;
+        let offset = if is_y { self.offset_y } else { self.offset_x };
+        (41.0 / scale) * (((raw + offset) * scale + 1024.0) / 2048.0) + 1.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::MapScale;
+
+    #[test]
+    fn raw_positions_convert_to_map_coordinates() {
+        let scale = MapScale::new(100);
+        assert!((scale.to_map(0.0, false) - 21.5).abs() < 0.01);
+        assert!((scale.to_map(-1024.0, true) - 1.0).abs() < 0.01);
+
+        let scale = MapScale {
+            size_factor: 200.0,
+            offset_x: -448.0,
+            offset_y: 0.0,
+        };
+        assert!((scale.to_map(448.0, false) - 11.25).abs() < 0.01);
+        assert!((scale.to_map(100.0, true) - 13.25).abs() < 0.01);
+    }
+}
@@ -1,40 +1,73 @@
 mod cell;
 mod cell_iter;
 mod compact_sestring;
+mod diff;
 mod filter;
+mod find;
 mod global_context;
+mod helper_column;
+mod histogram;
+mod map_coordinate;
+mod number_format;
+mod row_inspector;
+mod row_size_cache;
 mod schema_column;
+mod selection;
 mod sheet_column;
 mod sheet_table;
+mod sort;
 mod table_context;
 
 use std::{fmt::Write, sync::Arc};
 
 use base64::{Engine, prelude::BASE64_STANDARD};
-pub use cell::{CellResponse, MatchOptions};
+pub use cell::{CellResponse, CellValue, LinkTarget, MatchOptions, SheetRef};
 use compact_str::ToCompactString;
+pub use diff::SheetDiff;
 use egui::{
     Align, Color32, Direction, FontSelection, Galley, Label, Layout, Response, RichText, Sense,
     text::LayoutJob,
 };
 pub use filter::{ComplexFilter, FilterInput, FilterInputType};
 pub use global_context::GlobalContext;
+pub use helper_column::HelperFormat;
 use intmap::IntMap;
 use ironworks::sestring::SeStr;
-pub use sheet_table::SheetTable;
+pub use number_format::NumberFormat;
+pub use schema_column::{PathSegment, SchemaColumn, SchemaColumnMeta};
+pub use sheet_table::{FirstAppearedColumn, ScriptColumn, SheetTable, SheetViewState};
+pub use sort::{ColumnSort, SortDirection};
 pub use table_context::TableContext;
 
 use crate::{
-    settings::{EVALUATE_STRINGS, TEXT_MAX_LINES, TEXT_USE_SCROLL, TEXT_WRAP_WIDTH},
+    sestring_inspector,
+    settings::{
+        EVALUATE_STRINGS, MONOSPACE_NUMBERS, TABLE_FONT_SIZE, TEXT_MAX_LINES, TEXT_USE_SCROLL,
+        TEXT_WRAP_WIDTH,
+    },
     stopwatch::stopwatches::{MULTILINE3_STOPWATCH, MULTILINE4_STOPWATCH},
 };
 
 fn copyable_label(ui: &mut egui::Ui, text: &impl ToString) -> Response {
+    copyable_label_styled(ui, text.to_string(), false)
+}
+
+/// Like [`copyable_label`], but monospaced when [`MONOSPACE_NUMBERS`] is set so digits line up
+/// across rows.
+fn numeric_label(ui: &mut egui::Ui, value: &impl ToString) -> Response {
+    let monospace = MONOSPACE_NUMBERS.get(ui.ctx());
+    copyable_label_styled(ui, value.to_string(), monospace)
+}
+
+fn copyable_label_styled(ui: &mut egui::Ui, text: String, monospace: bool) -> Response {
     ui.with_layout(
         Layout::centered_and_justified(Direction::LeftToRight).with_main_align(Align::Min),
         |ui| {
-            let text = text.to_string();
-            let resp = ui.add(Label::new(&text).sense(Sense::click()));
+            let mut rich_text = RichText::new(&text);
+            if monospace {
+                rich_text = rich_text.monospace();
+            }
+            let resp = ui.add(Label::new(rich_text).sense(Sense::click()));
             resp.context_menu(|ui| {
                 if ui.button("Copy").clicked() {
                     ui.ctx().copy_text(text);
@@ -47,6 +80,20 @@ fn copyable_label(ui: &mut egui::Ui, text: &impl ToString) -> Response {
     .inner
 }
 
+/// Resizes the body and monospace fonts to [`TABLE_FONT_SIZE`], if set. Applied to both the
+/// table cells and the pass that sizes their rows.
+fn apply_table_font(ui: &mut egui::Ui) {
+    let Some(size) = TABLE_FONT_SIZE.get(ui.ctx()) else {
+        return;
+    };
+    let text_styles = &mut ui.style_mut().text_styles;
+    for style in [egui::TextStyle::Body, egui::TextStyle::Monospace] {
+        if let Some(font) = text_styles.get_mut(&style) {
+            font.size = f32::from(size.get());
+        }
+    }
+}
+
 fn string_label_wrapped(ui: &mut egui::Ui, value: &SeStr) -> Response {
     let text = if EVALUATE_STRINGS.get(ui.ctx()) {
         value
@@ -98,25 +145,25 @@ fn string_label_wrapped(ui: &mut egui::Ui, value: &SeStr) -> Response {
         })
         .inner;
 
+    if resp.double_clicked() {
+        sestring_inspector::inspect(ui.ctx(), value);
+    }
+
     resp.context_menu(|ui| {
         if ui.button("Copy").clicked() {
             ui.ctx().copy_text(text.into());
             ui.close();
         }
+        if ui.button("Inspect…").clicked() {
+            sestring_inspector::inspect(ui.ctx(), value);
+            ui.close();
+        }
         if ui.button("Copy Raw (base64)").clicked() {
-            ui.ctx().copy_text(BASE64_STANDARD.encode(value.as_bytes()));
+            ui.ctx().copy_text(raw_base64(value));
             ui.close();
         }
         if ui.button("Copy Raw (hex)").clicked() {
-            ui.ctx().copy_text(
-                value
-                    .as_bytes()
-                    .iter()
-                    .fold(String::new(), |mut output, b| {
-                        let _ = write!(output, "{b:02X}");
-                        output
-                    }),
-            );
+            ui.ctx().copy_text(raw_hex(value));
             ui.close();
         }
     });
@@ -124,6 +171,21 @@ fn string_label_wrapped(ui: &mut egui::Ui, value: &SeStr) -> Response {
     resp
 }
 
+/// A string's bytes as stored in the sheet, before any macros are evaluated.
+fn raw_base64(value: &SeStr) -> String {
+    BASE64_STANDARD.encode(value.as_bytes())
+}
+
+fn raw_hex(value: &SeStr) -> String {
+    value
+        .as_bytes()
+        .iter()
+        .fold(String::new(), |mut output, b| {
+            let _ = write!(output, "{b:02X}");
+            output
+        })
+}
+
 fn create_galley(ui: &egui::Ui, text: String, try_elide: bool) -> Arc<Galley> {
     let max_width = TEXT_WRAP_WIDTH
         .get(ui.ctx())
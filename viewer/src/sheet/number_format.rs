@@ -0,0 +1,220 @@
+use ironworks::file::exh::ColumnKind;
+use serde::{Deserialize, Serialize};
+
+/// How an integer column's values are written out, picked per column so bitfields, times and
+/// durations can be read at a glance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum NumberFormat {
+    #[default]
+    Decimal,
+    Hex,
+    Binary,
+    /// The set bits of a bitfield, one by one.
+    Flags,
+    /// Seconds since the Unix epoch, as a UTC date and time.
+    Timestamp,
+    /// A duration in seconds.
+    Seconds,
+    /// A duration in milliseconds.
+    Milliseconds,
+}
+
+impl NumberFormat {
+    pub const ALL: &[Self] = &[
+        Self::Decimal,
+        Self::Hex,
+        Self::Binary,
+        Self::Flags,
+        Self::Timestamp,
+        Self::Seconds,
+        Self::Milliseconds,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Decimal => "Decimal",
+            Self::Hex => "Hexadecimal",
+            Self::Binary => "Binary",
+            Self::Flags => "Bit Flags",
+            Self::Timestamp => "Timestamp",
+            Self::Seconds => "Seconds",
+            Self::Milliseconds => "Milliseconds",
+        }
+    }
+
+    /// Whether values are read as something else than a plain number, in which case the raw
+    /// number is shown on hover.
+    pub fn is_interpretation(self) -> bool {
+        matches!(self, Self::Timestamp | Self::Seconds | Self::Milliseconds)
+    }
+
+    /// Width in bits of integer columns of `kind`, `None` if it isn't one.
+    pub fn bits(kind: ColumnKind) -> Option<u32> {
+        match kind {
+            ColumnKind::Int8 | ColumnKind::UInt8 => Some(8),
+            ColumnKind::Int16 | ColumnKind::UInt16 => Some(16),
+            ColumnKind::Int32 | ColumnKind::UInt32 => Some(32),
+            ColumnKind::Int64 | ColumnKind::UInt64 => Some(64),
+            _ => None,
+        }
+    }
+
+    /// Negative values are shown as their two's complement at the column's width, like they're
+    /// stored. Binary is padded to whole nibbles and grouped by them.
+    pub fn format(self, value: i128, kind: ColumnKind) -> String {
+        let unsigned = Self::unsigned(value, kind);
+        match self {
+            Self::Decimal => value.to_string(),
+            Self::Hex => format!("0x{unsigned:X}"),
+            Self::Binary => {
+                let digits = format!("{unsigned:b}");
+                let padded = format!("{digits:0>width$}", width = digits.len().div_ceil(4) * 4);
+                let mut text = String::from("0b");
+                for (i, digit) in padded.chars().enumerate() {
+                    if i != 0 && i % 4 == 0 {
+                        text.push('_');
+                    }
+                    text.push(digit);
+                }
+                text
+            }
+            Self::Flags if unsigned == 0 => "0".to_owned(),
+            Self::Flags => Self::set_bits(value, kind)
+                .map(|bit| format!("0x{:X}", 1u128 << bit))
+                .collect::<Vec<_>>()
+                .join(" | "),
+            Self::Timestamp => format_timestamp(value).unwrap_or_else(|| value.to_string()),
+            Self::Seconds => format_duration(value, false),
+            Self::Milliseconds => format_duration(value, true),
+        }
+    }
+
+    /// Positions of the bits set in `value`, lowest first.
+    pub fn set_bits(value: i128, kind: ColumnKind) -> impl Iterator<Item = u32> {
+        let unsigned = Self::unsigned(value, kind);
+        (0..u128::BITS).filter(move |bit| (unsigned >> bit) & 1 == 1)
+    }
+
+    fn unsigned(value: i128, kind: ColumnKind) -> u128 {
+        let bits = Self::bits(kind).unwrap_or(64);
+        value as u128 & ((1u128 << bits) - 1)
+    }
+}
+
+/// `YYYY-MM-DD hh:mm:ss UTC`, or `None` past the year 9999.
+fn format_timestamp(secs: i128) -> Option<String> {
+    const MAX_SECS: i128 = 253_402_300_799; // 9999-12-31 23:59:59
+
+    if !(-MAX_SECS..=MAX_SECS).contains(&secs) {
+        return None;
+    }
+    let days = secs.div_euclid(86400) as i64;
+    let time = secs.rem_euclid(86400);
+    let (year, month, day) = civil_from_days(days);
+    Some(format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02} UTC",
+        time / 3600,
+        time / 60 % 60,
+        time % 60
+    ))
+}
+
+/// The date `days` after 1970-01-01, in the proleptic Gregorian calendar.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    // Counts from 0000-03-01 so leap days fall at the end of each year
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// `[-][Nd ]h:mm:ss`, with `.mmm` if `value` is in milliseconds.
+fn format_duration(value: i128, in_millis: bool) -> String {
+    let sign = if value < 0 { "-" } else { "" };
+    let value = value.unsigned_abs();
+    let (secs, millis) = if in_millis {
+        (value / 1000, format!(".{:03}", value % 1000))
+    } else {
+        (value, String::new())
+    };
+    let days = match secs / 86400 {
+        0 => String::new(),
+        days => format!("{days}d "),
+    };
+    format!(
+        "{sign}{days}{}:{:02}:{:02}{millis}",
+        secs / 3600 % 24,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use ironworks::file::exh::ColumnKind;
+
+    use super::NumberFormat;
+
+    #[test]
+    fn integers_format_at_column_width() {
+        assert_eq!(NumberFormat::Decimal.format(-1, ColumnKind::Int8), "-1");
+        assert_eq!(NumberFormat::Hex.format(-1, ColumnKind::Int8), "0xFF");
+        assert_eq!(
+            NumberFormat::Hex.format(-2, ColumnKind::Int32),
+            "0xFFFFFFFE"
+        );
+        assert_eq!(NumberFormat::Hex.format(0x1F, ColumnKind::UInt16), "0x1F");
+        assert_eq!(NumberFormat::Binary.format(0, ColumnKind::UInt8), "0b0000");
+        assert_eq!(
+            NumberFormat::Binary.format(0b1_0010_1101, ColumnKind::UInt16),
+            "0b0001_0010_1101"
+        );
+        assert_eq!(
+            NumberFormat::Binary.format(-128, ColumnKind::Int8),
+            "0b1000_0000"
+        );
+        assert_eq!(
+            NumberFormat::Flags.format(0x41, ColumnKind::UInt32),
+            "0x1 | 0x40"
+        );
+        assert_eq!(NumberFormat::Flags.format(-128, ColumnKind::Int8), "0x80");
+        assert_eq!(NumberFormat::Flags.format(0, ColumnKind::UInt8), "0");
+    }
+
+    #[test]
+    fn times_and_durations() {
+        let format = |format: NumberFormat, value| format.format(value, ColumnKind::Int64);
+        assert_eq!(
+            format(NumberFormat::Timestamp, 0),
+            "1970-01-01 00:00:00 UTC"
+        );
+        assert_eq!(
+            format(NumberFormat::Timestamp, 1_700_000_000),
+            "2023-11-14 22:13:20 UTC"
+        );
+        assert_eq!(
+            format(NumberFormat::Timestamp, 951_782_400),
+            "2000-02-29 00:00:00 UTC"
+        );
+        assert_eq!(
+            format(NumberFormat::Timestamp, -1),
+            "1969-12-31 23:59:59 UTC"
+        );
+        assert_eq!(format(NumberFormat::Seconds, 3725), "1:02:05");
+        assert_eq!(format(NumberFormat::Seconds, 90061), "1d 1:01:01");
+        assert_eq!(format(NumberFormat::Seconds, -5), "-0:00:05");
+        assert_eq!(format(NumberFormat::Milliseconds, 1500), "0:00:01.500");
+        assert_eq!(format(NumberFormat::Milliseconds, -250), "-0:00:00.250");
+    }
+}
@@ -0,0 +1,216 @@
+#[cfg(not(target_arch = "wasm32"))]
+use egui::{CentralPanel, ViewportBuilder, ViewportId};
+use egui::{RichText, Sense};
+use egui_extras::{Column, TableBuilder};
+
+use crate::{
+    excel::provider::{ExcelRow, ExcelSheet},
+    export,
+    i18n::{Text, tr},
+    settings::DISPLAY_FIELD_SHOWN,
+};
+
+use super::table_context::TableContext;
+
+/// Shows one row twice, side by side: as the raw columns in the order of the sheet's header,
+/// and as the schema's fields. Hovering a column in either pane highlights it in the other, to
+/// check that the schema lines up with the bytes.
+pub struct RowInspector {
+    table: TableContext,
+    row: (u32, Option<u16>),
+    /// Offset index of the hovered column
+    hovered: Option<u32>,
+    /// Shown in its own OS window instead of inside the viewer's
+    #[cfg(not(target_arch = "wasm32"))]
+    detached: bool,
+}
+
+impl RowInspector {
+    pub fn new(table: TableContext, row: (u32, Option<u16>)) -> Self {
+        Self {
+            table,
+            row,
+            hovered: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            detached: false,
+        }
+    }
+
+    /// `open` is cleared when the window is closed.
+    pub fn draw(&mut self, ctx: &egui::Context, open: &mut bool) {
+        let (row_id, subrow_id) = self.row;
+        let title = format!(
+            "{} - {}#{}",
+            tr(ctx, Text::RawVsSchema),
+            self.table.sheet().name(),
+            export::format_row_id(row_id, subrow_id)
+        );
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.detached {
+            let builder = ViewportBuilder::default()
+                .with_title(title)
+                .with_inner_size([720.0, 480.0]);
+            ctx.show_viewport_immediate(
+                ViewportId::from_hash_of("row-inspector-viewport"),
+                builder,
+                |ui, _class| {
+                    CentralPanel::default().show(ui, |ui| self.draw_contents(ui));
+                    if ui.input(|i| i.viewport().close_requested()) {
+                        *open = false;
+                    }
+                },
+            );
+            return;
+        }
+
+        egui::Window::new(title)
+            .id(egui::Id::new("row-inspector"))
+            .open(open)
+            .default_size([720.0, 480.0])
+            .show(ctx, |ui| {
+                #[cfg(not(target_arch = "wasm32"))]
+                if ui
+                    .button(format!("⏏ {}", tr(ctx, Text::PopOut)))
+                    .on_hover_text(tr(ctx, Text::PopOutRowHover))
+                    .clicked()
+                {
+                    self.detached = true;
+                }
+                self.draw_contents(ui);
+            });
+    }
+
+    fn draw_contents(&mut self, ui: &mut egui::Ui) {
+        let (row_id, subrow_id) = self.row;
+        let row = match self
+            .table
+            .sheet()
+            .get_subrow(row_id, subrow_id.unwrap_or_default())
+        {
+            Ok(row) => row,
+            Err(e) => {
+                ui.colored_label(ui.visuals().error_fg_color, e.to_string());
+                return;
+            }
+        };
+
+        let mut hovered = None;
+        ui.columns_const(|[raw_ui, schema_ui]| {
+            raw_ui.push_id("raw", |ui| {
+                ui.label(RichText::new(tr(ui.ctx(), Text::InspectorRaw)).strong());
+                hovered = hovered.or(self.draw_raw(ui, row));
+            });
+            schema_ui.push_id("schema", |ui| {
+                ui.label(RichText::new(tr(ui.ctx(), Text::InspectorSchema)).strong());
+                hovered = hovered.or(self.draw_schema(ui, row));
+            });
+        });
+        self.hovered = hovered;
+    }
+
+    /// Columns in the order of the sheet's header. Returns the offset index of the hovered one.
+    fn draw_raw(&self, ui: &mut egui::Ui, row: ExcelRow<'_>) -> Option<u32> {
+        let row_height = ui.text_style_height(&egui::TextStyle::Body);
+        let mut hovered = None;
+        TableBuilder::new(ui)
+            .striped(true)
+            .sense(Sense::hover())
+            .column(Column::auto())
+            .column(Column::auto())
+            .column(Column::auto())
+            .column(Column::remainder())
+            .header(row_height + 4.0, |mut header| {
+                for label in ["#", "Offset", "Kind", "Value"] {
+                    header.col(|ui| {
+                        ui.label(RichText::new(label).strong());
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(row_height, self.table.column_count(), |mut table_row| {
+                    let column_idx = table_row.index() as u32;
+                    let Ok(((_, sheet_column), offset_idx)) =
+                        self.table.get_column_by_index(column_idx)
+                    else {
+                        return;
+                    };
+                    table_row.set_selected(self.hovered == Some(offset_idx));
+                    table_row.col(|ui| {
+                        ui.label(column_idx.to_string());
+                    });
+                    table_row.col(|ui| {
+                        ui.monospace(format!("0x{:X}", sheet_column.offset()));
+                    });
+                    table_row.col(|ui| {
+                        ui.label(format!("{:?}", sheet_column.kind()));
+                    });
+                    table_row.col(|ui| {
+                        let value = self
+                            .table
+                            .cell_by_index(row, column_idx)
+                            .and_then(|cell| cell.read_raw());
+                        value_label(ui, value.map(|v| v.coerce_string().into()));
+                    });
+                    if table_row.response().hovered() {
+                        hovered = Some(offset_idx);
+                    }
+                });
+            });
+        hovered
+    }
+
+    /// Fields in the order of the schema. Returns the offset index of the hovered one.
+    fn draw_schema(&self, ui: &mut egui::Ui, row: ExcelRow<'_>) -> Option<u32> {
+        let row_height = ui.text_style_height(&egui::TextStyle::Body);
+        let resolve_display_field = DISPLAY_FIELD_SHOWN.get(ui.ctx());
+        let mut hovered = None;
+        TableBuilder::new(ui)
+            .striped(true)
+            .sense(Sense::hover())
+            .column(Column::auto())
+            .column(Column::remainder())
+            .header(row_height + 4.0, |mut header| {
+                for label in ["Field", "Value"] {
+                    header.col(|ui| {
+                        ui.label(RichText::new(label).strong());
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(row_height, self.table.column_count(), |mut table_row| {
+                    let offset_idx = table_row.index() as u32;
+                    let Ok((schema_column, _)) = self.table.get_column_by_offset(offset_idx) else {
+                        return;
+                    };
+                    table_row.set_selected(self.hovered == Some(offset_idx));
+                    table_row.col(|ui| {
+                        ui.label(schema_column.name());
+                    });
+                    table_row.col(|ui| {
+                        let value = self
+                            .table
+                            .cell_by_offset(row, offset_idx)
+                            .and_then(|cell| cell.read(resolve_display_field));
+                        value_label(ui, value.map(|v| v.coerce_string().into()));
+                    });
+                    if table_row.response().hovered() {
+                        hovered = Some(offset_idx);
+                    }
+                });
+            });
+        hovered
+    }
+}
+
+fn value_label(ui: &mut egui::Ui, value: anyhow::Result<String>) {
+    match value {
+        Ok(value) => {
+            ui.add(egui::Label::new(value).truncate());
+        }
+        Err(e) => {
+            ui.colored_label(ui.visuals().error_fg_color, "⚠")
+                .on_hover_text(e.to_string());
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    excel::provider::{ExcelHeader, ExcelSheet},
+    pr_window,
+    settings::{
+        BACKEND_CONFIG, EVALUATE_STRINGS, SHOW_RAW_VALUES, TABLE_FONT_SIZE, TEXT_MAX_LINES,
+        TEXT_WRAP_WIDTH,
+    },
+};
+
+use super::table_context::TableContext;
+
+/// Everything the measured row heights of a sheet depend on. Heights are only reused when
+/// all of it is the same.
+#[derive(Serialize)]
+pub struct RowSizeKey<'a> {
+    sheet: &'a str,
+    language: String,
+    /// The install location and string overlay and, when known, the game version of it
+    source: String,
+    raw_values: bool,
+    evaluate_strings: bool,
+    wrap_width: Option<u16>,
+    max_lines: Option<u8>,
+    font_size: Option<u8>,
+    /// Offset indices of the columns measured per row, and the height of the rest, which
+    /// change with the schema
+    sized_columns: &'a [u32],
+    fixed_size: f32,
+    row_count: u32,
+}
+
+impl<'a> RowSizeKey<'a> {
+    pub fn new(
+        ctx: &egui::Context,
+        context: &'a TableContext,
+        (sized_columns, fixed_size): (&'a [u32], f32),
+    ) -> Self {
+        let location = BACKEND_CONFIG
+            .get(ctx)
+            .and_then(|config| serde_json::to_string(&(config.location, config.overlay)).ok())
+            .unwrap_or_default();
+        let version = pr_window::game_version(ctx).unwrap_or_default();
+        Self {
+            sheet: context.sheet().name(),
+            language: context.global().language().to_string(),
+            source: format!("{location}@{version}"),
+            raw_values: SHOW_RAW_VALUES.get(ctx),
+            evaluate_strings: EVALUATE_STRINGS.get(ctx),
+            wrap_width: TEXT_WRAP_WIDTH.get(ctx).map(|w| w.get()),
+            max_lines: TEXT_MAX_LINES.get(ctx).map(|l| l.get()),
+            font_size: TABLE_FONT_SIZE.get(ctx).map(|s| s.get()),
+            sized_columns,
+            fixed_size,
+            row_count: context.sheet().subrow_count(),
+        }
+    }
+
+    fn id(&self) -> String {
+        let key = serde_json::to_vec(self).unwrap_or_default();
+        BASE64_URL_SAFE_NO_PAD.encode(Sha256::digest(key))
+    }
+}
+
+/// The row heights measured the last time the sheet was opened the same way, if any.
+pub fn load(key: &RowSizeKey<'_>) -> Option<Vec<f32>> {
+    let sizes = decode(&read(&key.id())?)?;
+    (sizes.len() == key.row_count as usize).then_some(sizes)
+}
+
+pub fn store(key: &RowSizeKey<'_>, sizes: &[f32]) {
+    write(&key.id(), &encode(sizes));
+}
+
+/// Most rows of a sheet are the same height, so heights are stored as runs of
+/// (height, count).
+fn encode(sizes: &[f32]) -> Vec<u8> {
+    let mut output = Vec::new();
+    for run in sizes.chunk_by(|a, b| a.to_bits() == b.to_bits()) {
+        output.extend_from_slice(&run[0].to_le_bytes());
+        output.extend_from_slice(&(run.len() as u32).to_le_bytes());
+    }
+    output
+}
+
+fn decode(data: &[u8]) -> Option<Vec<f32>> {
+    if data.len() % 8 != 0 {
+        return None;
+    }
+    let mut sizes = Vec::new();
+    for run in data.chunks_exact(8) {
+        let size = f32::from_le_bytes(run[..4].try_into().ok()?);
+        let count = u32::from_le_bytes(run[4..].try_into().ok()?);
+        sizes.extend(std::iter::repeat_n(size, count as usize));
+    }
+    Some(sizes)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn path(id: &str) -> Option<std::path::PathBuf> {
+    Some(
+        eframe::storage_dir("EXDViewer")?
+            .join("row-sizes")
+            .join(format!("{id}.bin")),
+    )
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read(id: &str) -> Option<Vec<u8>> {
+    std::fs::read(path(id)?).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write(id: &str, data: &[u8]) {
+    let Some(path) = path(id) else {
+        return;
+    };
+    let result = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|()| std::fs::write(&path, data));
+    if let Err(e) = result {
+        log::warn!("Failed to cache row sizes: {e}");
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok().flatten()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read(id: &str) -> Option<Vec<u8>> {
+    let data = storage()?.get_item(&format!("row-sizes-{id}")).ok()??;
+    BASE64_URL_SAFE_NO_PAD.decode(data).ok()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write(id: &str, data: &[u8]) {
+    let Some(storage) = storage() else {
+        return;
+    };
+    // Fails once localStorage is full, in which case the sheet is just measured next time
+    if let Err(e) = storage.set_item(
+        &format!("row-sizes-{id}"),
+        &BASE64_URL_SAFE_NO_PAD.encode(data),
+    ) {
+        log::warn!("Failed to cache row sizes: {e:?}");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{decode, encode};
+
+    #[test]
+    fn runs_round_trip() {
+        let sizes = [20.0, 20.0, 20.0, 36.0, 20.0, 52.5, 52.5];
+        let encoded = encode(&sizes);
+        assert_eq!(encoded.len(), 4 * 8);
+        assert_eq!(decode(&encoded).unwrap(), sizes);
+        assert_eq!(decode(&encode(&[])).unwrap(), Vec::<f32>::new());
+        assert_eq!(decode(&encoded[..5]), None);
+    }
+}
@@ -1,22 +1,43 @@
-use std::{cell::OnceCell, collections::HashMap, rc::Rc};
+use std::{cell::OnceCell, collections::HashMap, ops::Range, rc::Rc};
 
 use anyhow::bail;
 use itertools::Itertools;
 
 use crate::{
     excel::provider::ExcelSheet,
-    schema::{Field, FieldType, Schema},
-    sheet::{GlobalContext, TableContext, table_context::SharedConvertibleSheetPromise},
+    schema::{Field, FieldType, MapCoordinates, Schema},
+    sheet::{
+        GlobalContext, TableContext, map_coordinate::MapAxis,
+        table_context::SharedConvertibleSheetPromise,
+    },
 };
 
+/// A step from a row to a column's value, nested like the schema's fields. `Foo[2].Bar` is
+/// `[Key("Foo"), Index(2), Key("Bar")]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathSegment<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+/// A [`PathSegment`] with its key as the range of the column's name it was written to.
+#[derive(Debug, Clone)]
+enum PathStep {
+    Key(Range<usize>),
+    Index(usize),
+}
+
 #[derive(Debug, Clone)]
 pub struct SchemaColumn(Rc<SchemaColumnImpl>);
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct SchemaColumnImpl {
     name: String,
+    path: Vec<PathStep>,
     meta: SchemaColumnMeta,
     comment: Option<String>,
+    map_axis: Option<MapAxis>,
+    relation: Option<String>,
 }
 
 impl SchemaColumn {
@@ -28,27 +49,63 @@ impl SchemaColumn {
         &self.0.meta
     }
 
+    /// Where the column's field is in the schema, which names can't be parsed back into when
+    /// fields have `.` or `[` in theirs.
+    pub fn path(&self) -> Vec<PathSegment<'_>> {
+        self.0
+            .path
+            .iter()
+            .map(|step| match step {
+                PathStep::Key(range) => PathSegment::Key(&self.0.name[range.clone()]),
+                PathStep::Index(index) => PathSegment::Index(*index),
+            })
+            .collect()
+    }
+
     pub fn comment(&self) -> Option<&str> {
         self.0.comment.as_deref()
     }
 
+    /// Set if the schema's `coordinates` block names the column.
+    pub fn map_axis(&self) -> Option<&MapAxis> {
+        self.0.map_axis.as_ref()
+    }
+
+    /// The top level array field the column belongs to, if any. Its columns are tinted alike.
+    pub fn group(&self) -> Option<&str> {
+        self.name().split_once('[').map(|(group, _)| group)
+    }
+
+    /// The schema relation the column's array field is part of, if any. Its columns are shown
+    /// under a shared header. Relations of nested fields are named after the array element
+    /// they're in, like `Entries[0].Pair`, so each element gets a header of its own.
+    pub fn relation(&self) -> Option<&str> {
+        self.0.relation.as_deref()
+    }
+
     fn get_columns_inner(
         ret: &mut Vec<Self>,
         column_placeholder: &mut u32,
         column_lookups: &mut Vec<String>,
         scope: String,
+        path: &[PathStep],
         fields: &[Field],
         is_array: bool,
     ) -> anyhow::Result<()> {
         for field in fields {
             let mut scope = scope.clone();
+            let mut path = path.to_vec();
             if is_array {
                 if let Some(name) = &field.name {
                     scope.push('.');
+                    let start = scope.len();
                     scope.push_str(name);
+                    path.push(PathStep::Key(start..scope.len()));
                 }
             } else {
+                let start = scope.len();
                 scope.push_str(field.name.as_deref().unwrap_or("Unk"));
+                path.push(PathStep::Key(start..scope.len()));
             }
 
             if field.r#type == FieldType::Array {
@@ -58,11 +115,14 @@ impl SchemaColumn {
                     None => &[Field::default()],
                 };
                 for i in 0..(field.count.unwrap_or(1)) {
+                    let mut path = path.clone();
+                    path.push(PathStep::Index(i as usize));
                     Self::get_columns_inner(
                         ret,
                         column_placeholder,
                         column_lookups,
                         scope.clone() + &format!("[{i}]"),
+                        &path,
                         subfields,
                         true,
                     )?;
@@ -97,7 +157,7 @@ impl SchemaColumn {
                     FieldType::Array => unreachable!(),
                 };
 
-                ret.push(Self::new(name, meta, field.comment.clone()));
+                ret.push(Self::new(name, path, meta, field.comment.clone()));
             }
         }
 
@@ -132,13 +192,14 @@ impl SchemaColumn {
 
                 if matches!(ret[i].meta(), SchemaColumnMeta::ConditionalLink { .. }) {
                     let name = ret[i].0.name.clone();
+                    let path = ret[i].0.path.clone();
                     let mut meta = ret[i].0.meta.clone();
                     if let SchemaColumnMeta::ConditionalLink { column_idx, .. } = &mut meta {
                         *column_idx = resolved_column_idx;
                     } else {
                         unreachable!();
                     }
-                    ret[i] = Self::new(name, meta, None);
+                    ret[i] = Self::new(name, path, meta, None);
                 } else {
                     unreachable!();
                 }
@@ -158,10 +219,13 @@ impl SchemaColumn {
             &mut column_placeholder,
             &mut column_lookups,
             String::new(),
+            &[],
             fields,
             false,
         )?;
         Self::resolve_placeholders(&mut ret, &column_lookups)?;
+        Self::resolve_coordinates(&mut ret, schema.coordinates.as_deref().unwrap_or_default())?;
+        Self::resolve_relations(&mut ret, schema);
 
         let display_idx = if let Some(display_field) = &schema.display_field {
             ret.iter()
@@ -174,17 +238,145 @@ impl SchemaColumn {
         Ok((ret, display_idx))
     }
 
+    fn resolve_coordinates(ret: &mut [Self], coordinates: &[MapCoordinates]) -> anyhow::Result<()> {
+        let find = |name: &str| {
+            ret.iter()
+                .position(|c| c.name() == name)
+                .map(|i| i as u32)
+                .ok_or_else(|| anyhow::anyhow!("Failed to find coordinate column: {name}"))
+        };
+        let mut axes = Vec::new();
+        for coordinates in coordinates {
+            let x_idx = find(&coordinates.x)?;
+            let y_idx = find(&coordinates.y)?;
+            let map_idx = coordinates.map.as_deref().map(find).transpose()?;
+            let size_factor = coordinates.size_factor.unwrap_or(100);
+            for (idx, other_idx, is_y) in [(x_idx, y_idx, false), (y_idx, x_idx, true)] {
+                axes.push((
+                    idx,
+                    MapAxis {
+                        is_y,
+                        other_idx,
+                        map_idx,
+                        size_factor,
+                    },
+                ));
+            }
+        }
+        for (idx, axis) in axes {
+            Rc::make_mut(&mut ret[idx as usize].0).map_axis = Some(axis);
+        }
+        Ok(())
+    }
+
+    fn resolve_relations(ret: &mut [Self], schema: &Schema) {
+        for column in ret {
+            let relation = column.find_relation(schema);
+            if relation.is_some() {
+                Rc::make_mut(&mut column.0).relation = relation;
+            }
+        }
+    }
+
+    /// Follows the column's path through the schema's fields, checking the relations of each
+    /// level it passes. The innermost relation the column is part of wins.
+    fn find_relation(&self, schema: &Schema) -> Option<String> {
+        let name = self.name();
+        let mut fields = schema.fields.as_slice();
+        let mut relations = schema.relations.as_ref();
+        let mut field = None;
+        let mut relation = None;
+        // End of the part of the name walked through so far
+        let mut end = 0;
+        let mut scope = "";
+        for step in &self.0.path {
+            match step {
+                PathStep::Key(range) => {
+                    let key = &name[range.clone()];
+                    field = fields.iter().find(|f| f.name.as_deref() == Some(key));
+                    if let Some((found, _)) = relations.and_then(|relations| {
+                        relations
+                            .iter()
+                            .find(|(_, members)| members.iter().any(|member| member == key))
+                    }) {
+                        relation = Some(if scope.is_empty() {
+                            found.clone()
+                        } else {
+                            format!("{scope}.{found}")
+                        });
+                    }
+                    end = range.end;
+                }
+                PathStep::Index(index) => {
+                    let Some(array) = field else {
+                        break;
+                    };
+                    end += format!("[{index}]").len();
+                    scope = &name[..end];
+                    fields = array.fields.as_deref().unwrap_or_default();
+                    relations = array.relations.as_ref();
+                    // Elements of arrays with a single unnamed field have no key of their own
+                    field = fields
+                        .iter()
+                        .exactly_one()
+                        .ok()
+                        .filter(|f| f.name.is_none());
+                }
+            }
+        }
+        relation
+    }
+
+    /// Flattens a schema like [`Self::from_schema`], but instead of failing on conditional links
+    /// whose switch field doesn't exist, returns them as (column name, switch name) pairs
+    /// alongside the number of columns.
+    pub fn check_switches(schema: &Schema) -> anyhow::Result<(usize, Vec<(String, String)>)> {
+        let mut ret = vec![];
+        let mut column_placeholder = 0;
+        let mut column_lookups = vec![];
+        Self::get_columns_inner(
+            &mut ret,
+            &mut column_placeholder,
+            &mut column_lookups,
+            String::new(),
+            &[],
+            &schema.fields,
+            false,
+        )?;
+
+        let broken = ret
+            .iter()
+            .filter_map(|column| {
+                let SchemaColumnMeta::ConditionalLink { column_idx, .. } = column.meta() else {
+                    return None;
+                };
+                let switch_name = column_lookups.get(*column_idx as usize)?;
+                (!ret.iter().any(|c| c.name() == *switch_name))
+                    .then(|| (column.name().to_owned(), switch_name.clone()))
+            })
+            .collect();
+        Ok((ret.len(), broken))
+    }
+
     pub fn from_blank(column_count: usize) -> Vec<Self> {
         Self::from_schema(&Schema::from_blank("Blank", column_count))
             .unwrap()
             .0
     }
 
-    pub fn new(name: String, meta: SchemaColumnMeta, comment: Option<String>) -> Self {
+    fn new(
+        name: String,
+        path: Vec<PathStep>,
+        meta: SchemaColumnMeta,
+        comment: Option<String>,
+    ) -> Self {
         Self(Rc::new(SchemaColumnImpl {
             name,
+            path,
             meta,
             comment,
+            map_axis: None,
+            relation: None,
         }))
     }
 }
@@ -278,3 +470,137 @@ impl std::fmt::Debug for SheetLink {
             .finish_non_exhaustive()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{PathSegment, SchemaColumn};
+    use crate::schema::Schema;
+
+    #[test]
+    fn paths_follow_fields() {
+        let schema = Schema::from_str(
+            "name: Quest
+fields:
+  - name: Name.Text
+  - name: Params
+    type: array
+    count: 2
+    fields:
+      - name: Value[0]
+      - type: array
+        count: 1
+",
+        )
+        .unwrap()
+        .unwrap();
+        let (columns, _) = SchemaColumn::from_schema(&schema).unwrap();
+        assert_eq!(columns[0].path(), [PathSegment::Key("Name.Text")]);
+        assert_eq!(
+            columns[3].path(),
+            [
+                PathSegment::Key("Params"),
+                PathSegment::Index(1),
+                PathSegment::Key("Value[0]")
+            ]
+        );
+        assert_eq!(
+            columns[4].path(),
+            [
+                PathSegment::Key("Params"),
+                PathSegment::Index(1),
+                PathSegment::Index(0)
+            ]
+        );
+    }
+
+    #[test]
+    fn relation_columns_are_grouped() {
+        let schema = Schema::from_str(
+            "name: ItemFood
+fields:
+  - name: Exp
+  - name: BaseParam
+    type: array
+    count: 2
+  - name: Value
+    type: array
+    count: 2
+relations:
+  Params: [BaseParam, Value]
+",
+        )
+        .unwrap()
+        .unwrap();
+        let (columns, _) = SchemaColumn::from_schema(&schema).unwrap();
+        let relations = columns
+            .iter()
+            .map(SchemaColumn::relation)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            relations,
+            [
+                None,
+                Some("Params"),
+                Some("Params"),
+                Some("Params"),
+                Some("Params")
+            ]
+        );
+    }
+
+    #[test]
+    fn nested_relations_are_grouped_per_element() {
+        let schema = Schema::from_str(
+            "name: Recipe
+fields:
+  - name: Entries
+    type: array
+    count: 2
+    fields:
+      - name: Id
+      - name: Item
+        type: array
+        count: 2
+      - name: Amount
+        type: array
+        count: 2
+    relations:
+      Ingredients: [Item, Amount]
+  - name: Extra
+    type: array
+    count: 2
+relations:
+  Misc: [Entries, Extra]
+",
+        )
+        .unwrap()
+        .unwrap();
+        let (columns, _) = SchemaColumn::from_schema(&schema).unwrap();
+        let relations = columns
+            .iter()
+            .map(|column| (column.name(), column.relation()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            relations[..6],
+            [
+                ("Entries[0].Id", Some("Misc")),
+                ("Entries[0].Item[0]", Some("Entries[0].Ingredients")),
+                ("Entries[0].Item[1]", Some("Entries[0].Ingredients")),
+                ("Entries[0].Amount[0]", Some("Entries[0].Ingredients")),
+                ("Entries[0].Amount[1]", Some("Entries[0].Ingredients")),
+                ("Entries[1].Id", Some("Misc")),
+            ]
+        );
+        assert_eq!(
+            relations[6..],
+            [
+                ("Entries[1].Item[0]", Some("Entries[1].Ingredients")),
+                ("Entries[1].Item[1]", Some("Entries[1].Ingredients")),
+                ("Entries[1].Amount[0]", Some("Entries[1].Ingredients")),
+                ("Entries[1].Amount[1]", Some("Entries[1].Ingredients")),
+                ("Extra[0]", Some("Misc")),
+                ("Extra[1]", Some("Misc")),
+            ]
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use std::{
+    borrow::Cow,
+    ops::{Range, RangeInclusive},
+};
+
+use egui::Key;
+
+/// A cell of the table, by its position among the shown rows and the table's columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CellPos {
+    pub row_nr: u64,
+    pub col_nr: usize,
+}
+
+impl CellPos {
+    /// The cell `key` moves to from this one, in a table of `row_count` rows and
+    /// `column_count` columns with `page_rows` rows in view. `None` if `key` doesn't move.
+    pub fn moved(
+        self,
+        key: Key,
+        page_rows: u64,
+        row_count: u64,
+        column_count: usize,
+    ) -> Option<Self> {
+        if row_count == 0 || column_count == 0 {
+            return None;
+        }
+        let (row_nr, col_nr) = match key {
+            Key::ArrowUp => (self.row_nr.saturating_sub(1), self.col_nr),
+            Key::ArrowDown => (self.row_nr.saturating_add(1), self.col_nr),
+            Key::ArrowLeft => (self.row_nr, self.col_nr.saturating_sub(1)),
+            Key::ArrowRight => (self.row_nr, self.col_nr.saturating_add(1)),
+            Key::PageUp => (self.row_nr.saturating_sub(page_rows), self.col_nr),
+            Key::PageDown => (self.row_nr.saturating_add(page_rows), self.col_nr),
+            Key::Home => (self.row_nr, 0),
+            Key::End => (self.row_nr, column_count - 1),
+            _ => return None,
+        };
+        Some(Self {
+            row_nr: row_nr.min(row_count - 1),
+            col_nr: col_nr.min(column_count - 1),
+        })
+    }
+}
+
+/// Cells selected by dragging across the table or Shift-clicking, as the rectangle between
+/// the cell it started from and the one it was extended to. Positions are kept instead of
+/// labels, so rows scrolled out of view are still part of the selection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellSelection {
+    anchor: CellPos,
+    cursor: CellPos,
+}
+
+impl CellSelection {
+    pub fn new(pos: CellPos) -> Self {
+        Self {
+            anchor: pos,
+            cursor: pos,
+        }
+    }
+
+    pub fn extend_to(&mut self, pos: CellPos) {
+        self.cursor = pos;
+    }
+
+    /// The cell the selection was extended to last.
+    pub fn cursor(&self) -> CellPos {
+        self.cursor
+    }
+
+    /// The selected columns.
+    pub fn columns(&self) -> Range<usize> {
+        let first = self.anchor.col_nr.min(self.cursor.col_nr);
+        let last = self.anchor.col_nr.max(self.cursor.col_nr);
+        first..last + 1
+    }
+
+    /// The selected rows.
+    pub fn rows(&self) -> RangeInclusive<u64> {
+        self.anchor.row_nr.min(self.cursor.row_nr)..=self.anchor.row_nr.max(self.cursor.row_nr)
+    }
+
+    pub fn contains(&self, pos: CellPos) -> bool {
+        self.rows().contains(&pos.row_nr) && self.columns().contains(&pos.col_nr)
+    }
+}
+
+/// `text` as a field of tab-separated values, quoted like spreadsheets do when it has tabs,
+/// line breaks or quotes of its own.
+pub fn tsv_field(text: &str) -> Cow<'_, str> {
+    if text.contains(['\t', '\n', '\r', '"']) {
+        Cow::Owned(format!("\"{}\"", text.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use egui::Key;
+
+    use super::{CellPos, CellSelection, tsv_field};
+
+    fn pos(row_nr: u64, col_nr: usize) -> CellPos {
+        CellPos { row_nr, col_nr }
+    }
+
+    #[test]
+    fn rectangle() {
+        let mut selection = CellSelection::new(pos(3, 1));
+        selection.extend_to(pos(1, 2));
+
+        assert!(selection.contains(pos(1, 1)));
+        assert!(selection.contains(pos(2, 2)));
+        assert!(selection.contains(pos(3, 1)));
+        assert!(!selection.contains(pos(1, 3)));
+        assert!(!selection.contains(pos(2, 0)));
+        assert!(!selection.contains(pos(4, 1)));
+        assert_eq!(selection.rows(), 1..=3);
+        assert_eq!(selection.columns(), 1..3);
+    }
+
+    #[test]
+    fn tsv_fields() {
+        assert_eq!(tsv_field("Potion"), "Potion");
+        assert_eq!(tsv_field("a\tb"), "\"a\tb\"");
+        assert_eq!(tsv_field("say \"hi\"\n"), "\"say \"\"hi\"\"\n\"");
+    }
+
+    #[test]
+    fn moves_within_table() {
+        assert_eq!(pos(0, 0).moved(Key::ArrowUp, 10, 5, 3), Some(pos(0, 0)));
+        assert_eq!(pos(0, 0).moved(Key::ArrowRight, 10, 5, 3), Some(pos(0, 1)));
+        assert_eq!(pos(4, 2).moved(Key::ArrowDown, 10, 5, 3), Some(pos(4, 2)));
+        assert_eq!(pos(4, 2).moved(Key::ArrowRight, 10, 5, 3), Some(pos(4, 2)));
+        assert_eq!(pos(1, 1).moved(Key::PageDown, 10, 5, 3), Some(pos(4, 1)));
+        assert_eq!(pos(4, 1).moved(Key::PageUp, 2, 5, 3), Some(pos(2, 1)));
+        assert_eq!(pos(2, 1).moved(Key::End, 10, 5, 3), Some(pos(2, 2)));
+        assert_eq!(pos(2, 1).moved(Key::Home, 10, 5, 3), Some(pos(2, 0)));
+        // Rows filtered out from under the cell
+        assert_eq!(pos(9, 1).moved(Key::ArrowUp, 10, 5, 3), Some(pos(4, 1)));
+        assert_eq!(pos(0, 0).moved(Key::ArrowDown, 10, 0, 3), None);
+        assert_eq!(pos(0, 0).moved(Key::A, 10, 5, 3), None);
+    }
+}
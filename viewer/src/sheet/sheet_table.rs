@@ -1,14 +1,21 @@
 use egui::{
-    Align, Color32, Id, InnerResponse, Layout, Margin, Modal, RichText, Spinner, UiBuilder,
+    Align, Color32, Id, InnerResponse, Key, Layout, Margin, Modal, Modifiers, PointerButton, Rect,
+    RichText, Sense, Spinner, TextEdit, UiBuilder,
 };
+#[cfg(not(target_arch = "wasm32"))]
+use egui::{CentralPanel, ViewportBuilder, ViewportId};
 use egui_table::TableDelegate;
+use ironworks::sestring::SeStr;
 use itertools::Itertools;
 use lru::LruCache;
+use serde::{Deserialize, Serialize};
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::{Duration, Instant};
 use std::{
     cell::{Cell, RefCell},
+    collections::HashMap,
     num::NonZero,
+    ops::Range,
     rc::Rc,
     str::FromStr,
 };
@@ -16,12 +23,26 @@ use std::{
 use web_time::{Duration, Instant};
 
 use crate::{
+    bookmarks::{self, Bookmark},
     excel::provider::{ExcelHeader, ExcelProvider, ExcelRow, ExcelSheet},
-    settings::{SHEET_FILTER_OPTIONS, SHEET_FILTERS, SORTED_BY_OFFSET, TEMP_HIGHLIGHTED_ROW},
+    export::{self, ExportOptions, SubrowShape},
+    external_links,
+    huge_operation::HugeOperation,
+    i18n::{Text, tr},
+    operation_log,
+    routes::ColumnRef,
+    sestring_inspector,
+    settings::{
+        ALWAYS_HIRES, COLUMN_GROUP_COLORS, COLUMN_GROUP_TINTS, COLUMN_NUMBER_FORMATS,
+        COLUMN_WIDTHS, COPY_HEADER_ROW, DISPLAY_FIELD_SHOWN, EVALUATE_STRINGS, MONOSPACE_NUMBERS,
+        PAGE_BOUNDARIES_SHOWN, SHEET_FILTER_OPTIONS, SHEET_FILTERS, SHEET_SORTS, SHOW_RAW_VALUES,
+        SORTED_BY_OFFSET, TEMP_FIND_REFERENCES, TEMP_HIGHLIGHTED_ROW, TEMP_PERSIST_DISPLAY_FIELD,
+    },
     sheet::{
-        ComplexFilter, FilterInput, FilterInputType, filter::CompiledFilterInput,
-        should_ignore_clicks,
+        ComplexFilter, FilterInput, FilterInputType, HelperFormat, NumberFormat, SheetDiff,
+        apply_table_font, copyable_label, filter::CompiledFilterInput, raw_base64, raw_hex,
     },
+    shortcuts,
     stopwatch::{
         Stopwatch,
         stopwatches::{
@@ -31,10 +52,23 @@ use crate::{
             MULTILINE2_STOPWATCH, MULTILINE3_STOPWATCH, MULTILINE4_STOPWATCH,
         },
     },
-    utils::{ManagedIcon, PromiseKind, TrackedPromise, yield_to_ui},
+    touch_layout,
+    utils::{
+        ConvertiblePromise, GameVersion, ManagedIcon, PromiseKind, TrackedPromise, markdown,
+        shortcut, yield_to_ui,
+    },
 };
 
-use super::{cell::CellResponse, table_context::TableContext};
+use super::{
+    cell::{CellResponse, CellValue},
+    find::Find,
+    histogram::{self, ColumnHistogram},
+    row_inspector::RowInspector,
+    row_size_cache::{self, RowSizeKey},
+    selection::{CellPos, CellSelection, tsv_field},
+    sort::{ColumnSort, SortValue, click_column, sort_rows},
+    table_context::TableContext,
+};
 
 type FilterPromise = TrackedPromise<anyhow::Result<FilterOutput>>;
 struct FilterOutput {
@@ -44,10 +78,78 @@ struct FilterOutput {
 }
 struct FilterValue {
     filter_result: anyhow::Result<FilterOutput>,
+    /// How long the filter took to run
+    elapsed: Duration,
     // Cached row offsets, indexed by row_nr
     row_offsets: Rc<RefCell<Vec<f32>>>,
 }
 
+const MIN_COLUMN_WIDTH: f32 = 32.0;
+/// Time spent searching for the find bar each frame
+const FIND_BUDGET: Duration = Duration::from_millis(5);
+/// Fitting leaves long text truncated past this width
+const MAX_FITTED_COLUMN_WIDTH: f32 = 600.0;
+
+type SortKeysPromise = TrackedPromise<anyhow::Result<Vec<SortValue>>>;
+type ConvertibleSortKeysPromise =
+    ConvertiblePromise<SortKeysPromise, Result<Rc<Vec<SortValue>>, String>>;
+
+/// A column width fitted to the column's contents.
+#[derive(Clone, Copy)]
+struct FittedWidth {
+    width: f32,
+    /// Bumped on every fit, so the table takes the width over the one it remembers
+    generation: u32,
+}
+
+struct SortedRows {
+    sort: Vec<ColumnSort>,
+    /// Filter whose rows were sorted, `None` for all rows
+    filter: Option<CompiledFilterInput>,
+    // Sorted rows (by row_nr)
+    rows: Vec<u32>,
+    // Cached row offsets, indexed by position in `rows`
+    row_offsets: Rc<RefCell<Vec<f32>>>,
+}
+
+/// State of the virtual "First Appeared" column.
+pub enum FirstAppearedColumn {
+    Loading,
+    Failed(String),
+    Loaded(Rc<HashMap<u32, GameVersion>>),
+}
+
+/// Values of the virtual column a script derived from the rows it was run on.
+#[derive(Clone)]
+pub struct ScriptColumn {
+    pub name: String,
+    pub values: Rc<HashMap<(u32, Option<u16>), String>>,
+}
+
+/// Where a sheet was scrolled to and what it was filtered by, kept by history entry to be
+/// restored when going back or forward to it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SheetViewState {
+    sheet_name: String,
+    filter: Option<(FilterInputType, String)>,
+    /// Row at the top of the view
+    top_row: Option<(u32, Option<u16>)>,
+}
+
+impl SheetViewState {
+    pub fn sheet_name(&self) -> &str {
+        &self.sheet_name
+    }
+
+    pub fn filter(&self) -> Option<&(FilterInputType, String)> {
+        self.filter.as_ref()
+    }
+
+    pub fn top_row(&self) -> Option<(u32, Option<u16>)> {
+        self.top_row
+    }
+}
+
 pub struct SheetTable {
     context: TableContext,
     // Accumulated subrow count (row_nr), indexed by row index (not ID)
@@ -55,17 +157,70 @@ pub struct SheetTable {
     subrow_lookup: Option<Vec<u32>>,
     // Precomputed row sizes, indexed by row_nr
     row_sizes: Vec<f32>,
+    // Whether row_sizes were measured with SHOW_RAW_VALUES on
+    sized_raw_values: bool,
 
     modal_image: Option<u32>,
+    #[cfg(not(target_arch = "wasm32"))]
+    detached_images: Vec<u32>,
+    histogram: Option<ColumnHistogram>,
+    /// A value distribution of a huge sheet's column, waiting to be confirmed
+    histogram_confirm: Option<HugeOperation<(u32, String)>>,
+    row_inspector: Option<RowInspector>,
+    /// Tint of each column (by offset index) from its group, empty when tints are off
+    group_tints: Vec<Option<Color32>>,
+    /// The picked group colors `group_tints` was made with, `None` when tints are off
+    tinted_groups: Option<HashMap<String, [u8; 3]>>,
+    /// Runs of columns (by col_nr) under the same header band, and the relation they're part
+    /// of. Empty when the schema has no relations, in which case there's no band.
+    relation_bands: Vec<(Range<usize>, Option<String>)>,
+    /// Widths fitted to the columns' contents, by offset index
+    fitted_widths: HashMap<u32, FittedWidth>,
+    /// Generation of the next fit. Widths loaded from the settings are generation 0.
+    fit_generation: u32,
+    /// Columns handed to the table each frame
+    columns: Vec<egui_table::Column>,
+    /// (sorted by offset, leading column count, fit generation) `columns` was built for
+    columns_for: Option<(bool, usize, u32)>,
+    /// First and last row_nr drawn this frame, and in the frame before
+    drawn_rows: Option<(u64, u64)>,
+    shown_rows: Option<(u64, u64)>,
 
     clicked_cell: Option<CellResponse>,
+    /// Row of the most recently clicked cell
+    clicked_row: Option<(u32, Option<u16>)>,
+
+    /// Cells selected by dragging across them
+    selection: Option<CellSelection>,
+    /// Cell moved around with the keyboard, outlined in the table
+    focused: Option<CellPos>,
+    /// Cells matching the find bar, while it's open
+    find: Option<Find>,
+    /// Row to scroll to the top of the view once the filter is done, when restoring a view
+    restored_top_row: Option<(u32, Option<u16>)>,
+    /// Whether the selection is still being dragged out
+    selecting: bool,
+
+    // Differences against another game version, if comparing
+    diff: Option<Rc<SheetDiff>>,
+    first_appeared: Option<FirstAppearedColumn>,
+    helper_column: Option<HelperFormat>,
+    script_column: Option<ScriptColumn>,
 
     filtered_rows: RefCell<LruCache<CompiledFilterInput, FilterValue>>,
     unfiltered_row_offsets: Rc<RefCell<Vec<f32>>>,
     last_filter: Option<CompiledFilterInput>,
     current_filter: Result<Option<CompiledFilterInput>, String>,
     current_filter_promise: Option<FilterPromise>,
+    current_filter_started: Option<Instant>,
     current_filter_cancel_token: Option<Rc<Cell<bool>>>,
+
+    /// Columns the rows are ordered by, highest priority first. Empty for row id order.
+    sort: Vec<ColumnSort>,
+    /// Sort keys of every row (by row_nr), by the offset index of their column
+    sort_keys: HashMap<u32, ConvertibleSortKeysPromise>,
+    /// The shown rows in sorted order, until the sort keys of a newly sorted column are loaded
+    sorted_rows: Option<SortedRows>,
 }
 
 impl SheetTable {
@@ -93,19 +248,64 @@ impl SheetTable {
             context,
             subrow_lookup,
             row_sizes: Vec::new(),
+            sized_raw_values: false,
             modal_image: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            detached_images: Vec::new(),
+            histogram: None,
+            histogram_confirm: None,
+            row_inspector: None,
+            group_tints: Vec::new(),
+            relation_bands: Vec::new(),
+            tinted_groups: None,
+            fitted_widths: HashMap::new(),
+            fit_generation: 1,
+            columns: Vec::new(),
+            columns_for: None,
+            drawn_rows: None,
+            shown_rows: None,
             clicked_cell: None,
+            clicked_row: None,
+            selection: None,
+            focused: None,
+            find: None,
+            restored_top_row: None,
+            selecting: false,
+            diff: None,
+            first_appeared: None,
+            helper_column: None,
+            script_column: None,
             filtered_rows,
             unfiltered_row_offsets,
             last_filter: None,
             current_filter: Ok(None),
             current_filter_promise: None,
+            current_filter_started: None,
             current_filter_cancel_token: None,
+            sort: Vec::new(),
+            sort_keys: HashMap::new(),
+            sorted_rows: None,
         };
 
         ret.size_all_rows(ui);
 
         ret.update_filter(ui.ctx());
+        ret.apply_sort(ret.retrieve_sort(ui.ctx()));
+        ret.fitted_widths = COLUMN_WIDTHS.use_with(ui.ctx(), |map| {
+            map.get(ret.context.sheet().name())
+                .into_iter()
+                .flatten()
+                .map(|(&offset_idx, &width)| {
+                    (
+                        offset_idx,
+                        FittedWidth {
+                            width,
+                            generation: 0,
+                        },
+                    )
+                })
+                .collect()
+        });
 
         ret
     }
@@ -113,50 +313,118 @@ impl SheetTable {
     pub fn draw(
         &mut self,
         ui: &mut egui::Ui,
-        scroll_to: Option<((u32, Option<u16>), u16)>,
+        scroll_to: Option<((u32, Option<u16>), Option<ColumnRef>)>,
     ) -> CellResponse {
         self.tick_filter();
+        self.tick_sort();
+        self.update_group_tints(ui.ctx());
+        self.shown_rows = self.drawn_rows.take();
+        if SHOW_RAW_VALUES.get(ui.ctx()) != self.sized_raw_values {
+            self.invalidate_sizes(ui);
+        }
+
+        let sorted_by_offset = SORTED_BY_OFFSET.get(ui.ctx());
+        let columns = self.table_columns(sorted_by_offset);
+        self.update_relation_bands(sorted_by_offset);
+        let found = self.draw_find_bar(ui);
+        let focus_moved = self.navigate_cells(ui).or(found);
 
         let id = Id::new(self.context.sheet().name());
         ui.push_id(id, |ui| {
+            let mut headers = vec![egui_table::HeaderRow::new(
+                ui.text_style_height(&egui::TextStyle::Heading)
+                    + ui.spacing().item_spacing.y
+                    + ui.text_style_height(&egui::TextStyle::Small)
+                    + 4.0,
+            )];
+            if !self.relation_bands.is_empty() {
+                headers.insert(
+                    0,
+                    egui_table::HeaderRow {
+                        height: ui.text_style_height(&egui::TextStyle::Body) + 4.0,
+                        groups: self
+                            .relation_bands
+                            .iter()
+                            .map(|(range, _)| range.clone())
+                            .collect(),
+                    },
+                );
+            }
             let mut table = egui_table::Table::new()
                 .num_rows(self.get_filtered_row_count() as u64)
-                .columns(vec![
-                    egui_table::Column::new(100.0)
-                        .range(50.0..=10000.0)
-                        .resizable(true);
-                    self.context.sheet().columns().len() + 1
-                ])
-                .num_sticky_cols(1)
-                .headers([egui_table::HeaderRow::new(
-                    ui.text_style_height(&egui::TextStyle::Heading)
-                        + ui.spacing().item_spacing.y
-                        + ui.text_style_height(&egui::TextStyle::Small)
-                        + 4.0,
-                )]);
-            if let Some(((row_id, subrow_id), column_id)) = scroll_to {
+                .columns(columns)
+                .num_sticky_cols(self.leading_column_count())
+                .headers(headers);
+            if let Some(((row_id, subrow_id), column)) = scroll_to {
                 if let Some(row_nr) = self.search_filtered_row_nr(row_id, subrow_id) {
                     table = table.scroll_to_row(row_nr, Some(Align::Center));
                 }
-                let sorted_by_offset = SORTED_BY_OFFSET.get(ui.ctx());
+                let column_id = column.map_or(Some(0), |column| self.resolve_column(&column));
                 let column_nr = if sorted_by_offset {
-                    self.context
-                        .convert_column_index_to_offset_index(column_id.into())
-                        .ok()
+                    column_id.and_then(|column_id| {
+                        self.context
+                            .convert_column_index_to_offset_index(column_id)
+                            .ok()
+                    })
                 } else {
-                    Some(column_id.into())
+                    column_id
                 };
                 if let Some(col_nr) = column_nr {
-                    table = table.scroll_to_column(col_nr as usize, Some(Align::Center));
+                    table = table.scroll_to_column(
+                        col_nr as usize + self.leading_column_count() - 1,
+                        Some(Align::Center),
+                    );
                 }
             }
+            if let Some((row_id, subrow_id)) = self.restored_top_row
+                && !self.is_filter_pending()
+            {
+                self.restored_top_row = None;
+                if let Some(row_nr) = self.search_filtered_row_nr(row_id, subrow_id) {
+                    table = table.scroll_to_row(row_nr, Some(Align::Min));
+                }
+            }
+            if let Some(pos) = focus_moved {
+                table = table
+                    .scroll_to_row(pos.row_nr, None)
+                    .scroll_to_column(pos.col_nr, None);
+            }
 
-            if should_ignore_clicks(ui) {
-                ui.style_mut().interaction.selectable_labels = false;
+            // Text is selected across cells by the table instead of label by label
+            ui.style_mut().interaction.selectable_labels = false;
+            if ui.input(|i| i.pointer.primary_pressed() && !i.modifiers.shift)
+                && ui.rect_contains_pointer(ui.available_rect_before_wrap())
+            {
+                self.selection = None;
             }
             table.show(ui, self);
         });
 
+        if self.selecting && !ui.input(|i| i.pointer.primary_down()) {
+            self.selecting = false;
+        }
+        if self.has_selection() && !ui.ctx().wants_keyboard_input() {
+            let (copied, cleared) = ui.input(|i| {
+                (
+                    i.events.iter().any(|e| matches!(e, egui::Event::Copy)),
+                    i.key_pressed(Key::Escape),
+                )
+            });
+            if copied {
+                ui.ctx().copy_text(self.selection_text(ui.ctx()));
+            }
+            if shortcut::consume_ui(ui, shortcuts::COPY_RAW_BASE64) {
+                self.copy_selected_raw(ui.ctx(), raw_base64);
+            }
+            if shortcut::consume_ui(ui, shortcuts::COPY_RAW_HEX) {
+                self.copy_selected_raw(ui.ctx(), raw_hex);
+            }
+            if cleared {
+                self.selection = None;
+                self.focused = None;
+            }
+        }
+
         if let Some(icon_id) = &self.modal_image {
             let icon_id = *icon_id;
             let resp = Modal::new(Id::new("icon-modal"))
@@ -164,46 +432,50 @@ impl SheetTable {
                     "icon-modal-{icon_id}"
                 ))))
                 .show(ui.ctx(), |ui| {
-                    let (excel, icon_mgr) = (
-                        self.context.global().backend().excel().clone(),
-                        &self.context.global().icon_manager(),
-                    );
-                    let resp = icon_mgr.get_or_insert_icon(icon_id, true, ui.ctx(), move || {
-                        log::debug!("Hires icon not found in cache: {icon_id}");
-                        TrackedPromise::spawn_local(
-                            async move { excel.get_icon(icon_id, true).await },
-                        )
-                    });
-                    match resp {
-                        ManagedIcon::Loaded(icon) => {
-                            ui.add(egui::Image::new(icon).fit_to_exact_size(ui.available_size()))
-                        }
-                        ManagedIcon::Failed(e) => {
-                            ui.label("Failed to load icon").on_hover_text(e.to_string())
-                        }
-                        ManagedIcon::Loading => {
-                            let (rect, _) =
-                                ui.allocate_exact_size(ui.available_size(), egui::Sense::hover());
-                            ui.scope_builder(
-                                UiBuilder::new()
-                                    .max_rect(rect)
-                                    .layout(Layout::centered_and_justified(ui.layout().main_dir())),
-                                |ui| {
-                                    ui.add(Spinner::new().size(
-                                        ui.text_style_height(&egui::TextStyle::Heading) * 3.0,
-                                    ))
-                                },
-                            )
-                            .inner
+                    #[cfg(not(target_arch = "wasm32"))]
+                    if ui
+                        .button(format!("⏏ {}", tr(ui.ctx(), Text::PopOut)))
+                        .on_hover_text(tr(ui.ctx(), Text::PopOutIconHover))
+                        .clicked()
+                    {
+                        if !self.detached_images.contains(&icon_id) {
+                            self.detached_images.push(icon_id);
                         }
-                        ManagedIcon::NotLoaded => ui.label("Icon not loaded"),
+                        ui.close();
                     }
+                    self.draw_icon_image(ui, icon_id)
                 });
             if resp.should_close() {
                 self.modal_image = None;
             }
         }
 
+        #[cfg(not(target_arch = "wasm32"))]
+        self.draw_detached_images(ui.ctx());
+
+        if let Some(confirm) = &self.histogram_confirm
+            && let Some(run) = confirm.draw(ui.ctx())
+        {
+            let (offset_idx, column_name) = self.histogram_confirm.take().unwrap().into_operation();
+            if run {
+                self.open_histogram(offset_idx, column_name);
+            }
+        }
+        if let Some(histogram) = &mut self.histogram {
+            let mut open = true;
+            histogram.draw(ui.ctx(), &mut open);
+            if !open {
+                self.histogram = None;
+            }
+        }
+        if let Some(row_inspector) = &mut self.row_inspector {
+            let mut open = true;
+            row_inspector.draw(ui.ctx(), &mut open);
+            if !open {
+                self.row_inspector = None;
+            }
+        }
+
         self.clicked_cell.take().unwrap_or_default()
     }
 
@@ -211,7 +483,165 @@ impl SheetTable {
         &self.context
     }
 
+    pub fn view_state(&self, ctx: &egui::Context) -> SheetViewState {
+        let sheet_name = self.context.sheet().name().to_owned();
+        let filter = SHEET_FILTERS.use_with(ctx, |map| map.get(&sheet_name).cloned());
+        let top_row = self
+            .drawn_rows
+            .or(self.shown_rows)
+            .and_then(|(first, _)| self.get_row_id(self.get_filtered_row_nr(first)).ok());
+        SheetViewState {
+            sheet_name,
+            filter,
+            top_row,
+        }
+    }
+
+    /// Puts back the filter of a view, and scrolls back to where it was once it's applied.
+    pub fn restore_view_state(&mut self, ctx: &egui::Context, state: SheetViewState) {
+        SHEET_FILTERS.use_with(ctx, |map| {
+            if let Some(filter) = state.filter {
+                map.insert(state.sheet_name, filter);
+            } else {
+                map.remove(&state.sheet_name);
+            }
+        });
+        self.update_filter(ctx);
+        self.restored_top_row = state.top_row;
+    }
+
+    /// Row of the cell behind the last non-empty [`CellResponse`].
+    pub fn clicked_row(&self) -> Option<(u32, Option<u16>)> {
+        self.clicked_row
+    }
+
+    pub fn set_diff(&mut self, diff: Option<Rc<SheetDiff>>) {
+        self.diff = diff;
+    }
+
+    pub fn set_first_appeared(&mut self, first_appeared: Option<FirstAppearedColumn>) {
+        self.first_appeared = first_appeared;
+    }
+
+    pub fn set_helper_column(&mut self, helper_column: Option<HelperFormat>) {
+        self.helper_column = helper_column;
+    }
+
+    pub fn set_script_column(&mut self, script_column: Option<ScriptColumn>) {
+        self.script_column = script_column;
+    }
+
+    /// Number of table columns before the sheet's own columns (row id and virtual columns).
+    fn leading_column_count(&self) -> usize {
+        1 + usize::from(self.first_appeared.is_some())
+            + usize::from(self.helper_column.is_some())
+            + usize::from(self.script_column.is_some())
+    }
+
+    fn is_first_appeared_column(&self, col_nr: usize) -> bool {
+        self.first_appeared.is_some() && col_nr == 1
+    }
+
+    fn is_helper_column(&self, col_nr: usize) -> bool {
+        self.helper_column.is_some() && col_nr == 1 + usize::from(self.first_appeared.is_some())
+    }
+
+    fn is_script_column(&self, col_nr: usize) -> bool {
+        self.script_column.is_some() && col_nr == self.leading_column_count() - 1
+    }
+
+    fn draw_icon_image(&self, ui: &mut egui::Ui, icon_id: u32) -> egui::Response {
+        let (excel, icon_mgr) = (
+            self.context.global().backend().excel().clone(),
+            &self.context.global().icon_manager(),
+        );
+        let resp = icon_mgr.get_or_insert_icon(icon_id, true, ui.ctx(), move |etag| {
+            log::debug!("Hires icon not found in cache: {icon_id}");
+            TrackedPromise::spawn_local(async move {
+                excel
+                    .get_icon_if_changed(icon_id, true, etag.as_deref())
+                    .await
+            })
+        });
+        match resp {
+            ManagedIcon::Loaded(icon) => {
+                let resp = ui.add(
+                    egui::Image::new(icon)
+                        .sense(egui::Sense::click())
+                        .fit_to_exact_size(ui.available_size()),
+                );
+                resp.context_menu(|ui| {
+                    if ui.button(tr(ui.ctx(), Text::SaveAsPng)).clicked() {
+                        let excel = self.context.global().backend().excel().clone();
+                        let _ = export::save_icon(excel, icon_id, true);
+                        ui.close();
+                    }
+                });
+                resp
+            }
+            ManagedIcon::Failed(e) => {
+                ui.label(format!("⚠ {}", tr(ui.ctx(), Text::IconLoadFailed)));
+                ui.colored_label(ui.visuals().warn_fg_color, e.to_string())
+            }
+            ManagedIcon::Loading => {
+                let (rect, _) = ui.allocate_exact_size(ui.available_size(), egui::Sense::hover());
+                ui.scope_builder(
+                    UiBuilder::new()
+                        .max_rect(rect)
+                        .layout(Layout::centered_and_justified(ui.layout().main_dir())),
+                    |ui| {
+                        ui.add(
+                            Spinner::new()
+                                .size(ui.text_style_height(&egui::TextStyle::Heading) * 3.0),
+                        )
+                    },
+                )
+                .inner
+            }
+            ManagedIcon::NotLoaded => ui.label("Icon not loaded"),
+        }
+    }
+
+    // Popped out icons are shown as immediate viewports so they can share the table's
+    // icon cache and textures; closing the OS window removes it from the list.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn draw_detached_images(&mut self, ctx: &egui::Context) {
+        let mut closed = Vec::new();
+        for &icon_id in &self.detached_images {
+            let viewport_id = ViewportId::from_hash_of(("icon-viewport", icon_id));
+            let builder = ViewportBuilder::default()
+                .with_title(format!("Icon {icon_id}"))
+                .with_inner_size([512.0, 512.0]);
+            ctx.show_viewport_immediate(viewport_id, builder, |ui, _class| {
+                CentralPanel::default().show(ui, |ui| {
+                    self.draw_icon_image(ui, icon_id);
+                });
+                if ui.input(|i| i.viewport().close_requested()) {
+                    closed.push(icon_id);
+                }
+            });
+        }
+        self.detached_images.retain(|id| !closed.contains(id));
+    }
+
+    /// Returns the (row id, subrow id) of every row currently shown, in display order.
+    pub fn visible_rows(&mut self) -> Vec<(u32, Option<u16>)> {
+        (0..self.get_filtered_row_count() as u64)
+            .filter_map(|i| self.get_row_id(self.get_filtered_row_nr(i)).ok())
+            .collect()
+    }
+
     fn search_filtered_row_nr(&mut self, row_id: u32, subrow_id: Option<u16>) -> Option<u64> {
+        if let Some(sorted) = &self.sorted_rows {
+            return sorted
+                .rows
+                .iter()
+                .position(|&row_nr| {
+                    self.get_row_id(row_nr.into())
+                        .is_ok_and(|id| id == (row_id, subrow_id))
+                })
+                .map(|i| i as u64);
+        }
         let max = self.get_filtered_row_count() as u64;
         let result = (0..max).collect_vec().binary_search_by(|i| {
             let (i_row, i_subrow) = self.get_row_id(self.get_filtered_row_nr(*i)).unwrap();
@@ -240,53 +670,831 @@ impl SheetTable {
         if let Some(offset) = row_offsets.get(filtered_row_nr as usize) {
             return *offset;
         }
-
-        let (len, mut last_size) = (
-            row_offsets.len() as u64,
-            row_offsets.last().copied().unwrap_or_default(),
+
+        let (len, mut last_size) = (
+            row_offsets.len() as u64,
+            row_offsets.last().copied().unwrap_or_default(),
+        );
+
+        row_offsets.reserve((filtered_row_nr - len) as usize);
+        for i in len..filtered_row_nr {
+            row_offsets.push(last_size);
+            last_size += self.row_sizes[self.get_filtered_row_nr(i) as usize];
+        }
+        row_offsets.push(last_size);
+        row_offsets[filtered_row_nr as usize]
+    }
+
+    /// `Some(is_auto)` if the column is the sheet's display field.
+    fn display_column_state(
+        &self,
+        column_idx: Option<usize>,
+        sorted_by_offset: bool,
+    ) -> Option<bool> {
+        let (column_idx, (display_idx, is_auto)) = (column_idx?, self.context.display_column()?);
+        let is_display_column = if sorted_by_offset {
+            column_idx as u32 == display_idx
+        } else {
+            self.context
+                .convert_column_index_to_offset_index(column_idx as u32)
+                .is_ok_and(|idx| idx == display_idx)
+        };
+        is_display_column.then_some(is_auto)
+    }
+
+    /// Index of the EXD page `row_id` is stored in.
+    fn page_of(&self, row_id: u32) -> Option<usize> {
+        self.context
+            .sheet()
+            .row_intervals()
+            .partition_point(|page| page.start_id() <= row_id)
+            .checked_sub(1)
+    }
+
+    /// Returns the page index if the shown row is the first one from a different page than the
+    /// row shown above it.
+    fn page_boundary(&self, row_nr: u64, row_id: u32) -> Option<usize> {
+        let prev_row_nr = row_nr.checked_sub(1)?;
+        let page = self.page_of(row_id)?;
+        let (prev_row_id, _) = self
+            .get_row_id(self.get_filtered_row_nr(prev_row_nr))
+            .ok()?;
+        (self.page_of(prev_row_id) != Some(page)).then_some(page)
+    }
+
+    fn paint_cell_background(ui: &mut egui::Ui, color: Color32) {
+        ui.painter().rect_filled(ui.max_rect(), 0.0, color);
+    }
+
+    /// Tints each column group with the color picked for it, or a hue of its own, in the order
+    /// the groups first appear.
+    fn update_group_tints(&mut self, ctx: &egui::Context) {
+        if !COLUMN_GROUP_TINTS.get(ctx) {
+            self.group_tints.clear();
+            self.tinted_groups = None;
+            return;
+        }
+        let picked = COLUMN_GROUP_COLORS.use_with(ctx, |map| {
+            map.get(self.context.sheet().name())
+                .cloned()
+                .unwrap_or_default()
+        });
+        // Going through every column of a wide sheet each frame adds up
+        if self.tinted_groups.as_ref() == Some(&picked) {
+            return;
+        }
+        self.group_tints.clear();
+        let mut groups = HashMap::new();
+        for offset_idx in 0..self.context.column_count() as u32 {
+            let tint =
+                self.context
+                    .get_column_by_offset(offset_idx)
+                    .ok()
+                    .and_then(|(column, _)| {
+                        let group = column.group()?;
+                        let group_idx = groups.len();
+                        Some(*groups.entry(group.to_owned()).or_insert_with(|| {
+                            picked.get(group).map_or_else(
+                                || group_color(group_idx),
+                                |&[r, g, b]| Color32::from_rgb(r, g, b),
+                            )
+                        }))
+                    });
+            self.group_tints.push(tint);
+        }
+        self.tinted_groups = Some(picked);
+    }
+
+    fn open_histogram(&mut self, offset_idx: u32, column_name: String) {
+        self.histogram = Some(ColumnHistogram::new(
+            self.context.clone(),
+            offset_idx,
+            column_name,
+        ));
+    }
+
+    /// Bands columns of the same schema relation together, in the order they're shown.
+    fn update_relation_bands(&mut self, sorted_by_offset: bool) {
+        let leading = self.leading_column_count();
+        // Leading columns are sticky, so they get bands of their own
+        let mut bands = (0..leading)
+            .map(|col_nr| (col_nr..col_nr + 1, None))
+            .collect_vec();
+        let mut has_relations = false;
+        for column_idx in 0..self.context.column_count() {
+            let relation = self
+                .offset_idx(column_idx, sorted_by_offset)
+                .and_then(|offset_idx| self.context.get_column_by_offset(offset_idx).ok())
+                .and_then(|(column, _)| column.relation().map(str::to_owned));
+            has_relations |= relation.is_some();
+            let col_nr = leading + column_idx;
+            match bands.last_mut() {
+                Some((range, last)) if column_idx != 0 && *last == relation => {
+                    range.end = col_nr + 1;
+                }
+                _ => bands.push((col_nr..col_nr + 1, relation)),
+            }
+        }
+        if !has_relations {
+            bands.clear();
+        }
+        self.relation_bands = bands;
+    }
+
+    fn draw_relation_band(&self, ui: &mut egui::Ui, col_range: &Range<usize>) {
+        let Some((_, Some(relation))) = self
+            .relation_bands
+            .iter()
+            .find(|(range, _)| range == col_range)
+        else {
+            return;
+        };
+        let background = ui.visuals().faint_bg_color;
+        Self::paint_cell_background(ui, background);
+        ui.painter().line_segment(
+            [ui.max_rect().left_bottom(), ui.max_rect().right_bottom()],
+            ui.visuals().widgets.noninteractive.bg_stroke,
+        );
+        egui::Frame::NONE
+            .inner_margin(Margin::symmetric(4, 2))
+            .show(ui, |ui| {
+                ui.label(RichText::new(relation).strong());
+            });
+    }
+
+    fn group_tint(&self, column_idx: Option<usize>, sorted_by_offset: bool) -> Option<Color32> {
+        let offset_idx = self.offset_idx(column_idx?, sorted_by_offset)?;
+        self.group_tints.get(offset_idx as usize).copied().flatten()
+    }
+
+    fn offset_idx(&self, column_idx: usize, sorted_by_offset: bool) -> Option<u32> {
+        if sorted_by_offset {
+            Some(column_idx as u32)
+        } else {
+            self.context
+                .convert_column_index_to_offset_index(column_idx as u32)
+                .ok()
+        }
+    }
+
+    /// The table's columns, rebuilt only when they change since wide sheets have hundreds.
+    fn table_columns(&mut self, sorted_by_offset: bool) -> Vec<egui_table::Column> {
+        let key = (
+            sorted_by_offset,
+            self.leading_column_count(),
+            self.fit_generation,
+        );
+        if self.columns_for == Some(key) {
+            return self.columns.clone();
+        }
+        self.columns = (0..self.context.sheet().columns().len() + self.leading_column_count())
+            .map(|col_nr| {
+                let fitted = col_nr
+                    .checked_sub(self.leading_column_count())
+                    .and_then(|column_idx| self.offset_idx(column_idx, sorted_by_offset))
+                    .and_then(|offset_idx| {
+                        Some((offset_idx, *self.fitted_widths.get(&offset_idx)?))
+                    });
+                let column = match fitted {
+                    Some((offset_idx, fitted)) => {
+                        egui_table::Column::new(fitted.width).id(Id::new((
+                            "fitted-column",
+                            offset_idx,
+                            fitted.width.to_bits(),
+                            fitted.generation,
+                        )))
+                    }
+                    None => egui_table::Column::new(100.0),
+                };
+                column.range(MIN_COLUMN_WIDTH..=10000.0).resizable(true)
+            })
+            .collect_vec();
+        self.columns_for = Some(key);
+        self.columns.clone()
+    }
+
+    /// Widens or narrows the columns to fit their header and the cells of the rows on screen,
+    /// and saves the widths for the sheet.
+    fn fit_columns(&mut self, ui: &mut egui::Ui, offsets: impl IntoIterator<Item = u32>) {
+        let row_ids = self
+            .shown_rows
+            .into_iter()
+            .flat_map(|(first, last)| first..=last)
+            .filter_map(|row_nr| self.get_row_id(self.get_filtered_row_nr(row_nr)).ok())
+            .collect_vec();
+        let sheet = self.context.sheet();
+        let rows = row_ids
+            .into_iter()
+            .filter_map(|(row_id, subrow_id)| {
+                sheet.get_subrow(row_id, subrow_id.unwrap_or_default()).ok()
+            })
+            .collect_vec();
+        let heading_height = ui.text_style_height(&egui::TextStyle::Heading);
+
+        let mut sizing_ui = ui.new_child(
+            UiBuilder::new()
+                .sizing_pass()
+                .max_rect(Rect::from_min_size(ui.max_rect().min, egui::Vec2::INFINITY)),
+        );
+        sizing_ui.style_mut().wrap_mode = Some(egui::TextWrapMode::Extend);
+        apply_table_font(&mut sizing_ui);
+        for offset_idx in offsets {
+            let Ok((schema_column, _)) = self.context.get_column_by_offset(offset_idx) else {
+                continue;
+            };
+            let header_width = {
+                let mut header_ui = sizing_ui.new_child(UiBuilder::new());
+                header_ui.heading(schema_column.name());
+                // Room for the sort arrow and the header's icons
+                let icon_count = usize::from(self.sort.iter().any(|s| s.offset_idx == offset_idx))
+                    + usize::from(schema_column.comment().is_some())
+                    + usize::from(
+                        self.display_column_state(Some(offset_idx as usize), true)
+                            .is_some(),
+                    );
+                header_ui.min_rect().width() + heading_height * icon_count as f32
+            };
+            let cells_width = rows
+                .iter()
+                .filter_map(|row| self.context.cell_by_offset(*row, offset_idx).ok())
+                .map(|cell| cell.width(&mut sizing_ui))
+                .fold(0.0, f32::max);
+            // Cells have 4px of margin on either side
+            let width = header_width.max(cells_width) + 8.0;
+            self.fitted_widths.insert(
+                offset_idx,
+                FittedWidth {
+                    width: width.clamp(MIN_COLUMN_WIDTH, MAX_FITTED_COLUMN_WIDTH),
+                    generation: self.fit_generation,
+                },
+            );
+        }
+        self.fit_generation += 1;
+
+        let widths = self
+            .fitted_widths
+            .iter()
+            .map(|(&offset_idx, fitted)| (offset_idx, fitted.width))
+            .collect();
+        let sheet_name = self.context.sheet().name().to_owned();
+        COLUMN_WIDTHS.use_with(ui.ctx(), |map| {
+            map.insert(sheet_name, widths);
+        });
+    }
+
+    /// Moves the focused cell with the arrow keys, Page Up/Down and Home/End, extending the
+    /// selection while Shift is held, and activates it with Enter like clicking it would.
+    /// Returns where the focus moved to, to scroll it into view.
+    fn navigate_cells(&mut self, ui: &egui::Ui) -> Option<CellPos> {
+        let focused = self.focused?;
+        if ui.ctx().wants_keyboard_input() || self.modal_image.is_some() {
+            return None;
+        }
+        let (key, shift, activate) = ui.input_mut(|i| {
+            let shift = i.modifiers.shift;
+            let activate = i.consume_key(Modifiers::NONE, Key::Enter);
+            // Alt+Left/Right navigate back and forward
+            if i.modifiers.alt {
+                return (None, shift, activate);
+            }
+            // Consumed so the scroll area doesn't also scroll by them
+            let key = [
+                Key::ArrowUp,
+                Key::ArrowDown,
+                Key::ArrowLeft,
+                Key::ArrowRight,
+                Key::PageUp,
+                Key::PageDown,
+                Key::Home,
+                Key::End,
+            ]
+            .into_iter()
+            .find(|&key| i.consume_key(Modifiers::NONE, key));
+            (key, shift, activate)
+        });
+
+        if activate {
+            self.activate_cell(ui.ctx(), focused);
+        }
+
+        let page_rows = self
+            .shown_rows
+            .map_or(1, |(first, last)| (last - first).max(1));
+        let row_count = self.get_filtered_row_count() as u64;
+        let pos = focused.moved(key?, page_rows, row_count, self.table_column_count())?;
+        self.focused = Some(pos);
+        if shift {
+            self.selection
+                .get_or_insert(CellSelection::new(focused))
+                .extend_to(pos);
+        } else {
+            self.selection = None;
+        }
+        Some(pos)
+    }
+
+    /// Does what clicking the cell at `pos` would: opens its icon or follows its link, or
+    /// links to the row from the row id column.
+    fn activate_cell(&mut self, ctx: &egui::Context, pos: CellPos) {
+        let Some(((row_id, subrow_id), value)) = self.cell_at(ctx, pos) else {
+            return;
+        };
+        let resp = match value {
+            _ if pos.col_nr == 0 => {
+                CellResponse::Row((self.context.sheet().name().to_string(), (row_id, subrow_id)))
+            }
+            Some(CellValue::Icon(icon_id))
+                if self
+                    .context
+                    .global()
+                    .backend()
+                    .capabilities()
+                    .supports_icons =>
+            {
+                match u32::try_from(icon_id) {
+                    Ok(icon_id) => {
+                        self.modal_image = Some(icon_id);
+                        CellResponse::Icon(icon_id)
+                    }
+                    Err(_) => CellResponse::None,
+                }
+            }
+            Some(CellValue::ValidLink {
+                sheet_name, row_id, ..
+            }) => CellResponse::Link((sheet_name.into(), (row_id, None))),
+            _ => CellResponse::None,
+        };
+        if !matches!(resp, CellResponse::None) {
+            self.clicked_cell = Some(resp);
+            self.clicked_row = Some((row_id, subrow_id));
+        }
+    }
+
+    /// Drops what refers to rows by their position, which would point at different rows once
+    /// the shown rows or their order change.
+    fn forget_row_positions(&mut self) {
+        self.selection = None;
+        if let Some(find) = &mut self.find {
+            find.restart();
+        }
+    }
+
+    /// Opens the find bar with Ctrl+F and draws it while open, searching a few more rows each
+    /// frame. Returns the match stepped to with Enter or F3, to focus and scroll to.
+    fn draw_find_bar(&mut self, ui: &mut egui::Ui) -> Option<CellPos> {
+        if shortcut::consume_ui(ui, shortcuts::FIND) {
+            self.find.get_or_insert_with(Find::new).request_focus();
+        }
+        let mut find = self.find.take()?;
+        let row_count = self.get_filtered_row_count() as u64;
+
+        let mut step = None;
+        let mut open = true;
+        ui.horizontal(|ui| {
+            let resp = ui.add(
+                TextEdit::singleline(find.query_mut())
+                    .hint_text("Find in sheet")
+                    .desired_width(240.0),
+            );
+            if find.take_focus_request() {
+                resp.request_focus();
+            }
+            if resp.changed() {
+                find.restart();
+            }
+            if resp.lost_focus() {
+                let (enter, escape, shift) = ui.input(|i| {
+                    (
+                        i.key_pressed(Key::Enter),
+                        i.key_pressed(Key::Escape),
+                        i.modifiers.shift,
+                    )
+                });
+                if enter {
+                    step = Some(shift);
+                    resp.request_focus();
+                }
+                open &= !escape;
+            }
+            ui.label(RichText::new(find.status(row_count)).weak());
+            if ui
+                .small_button("⏶")
+                .on_hover_text("Previous (Shift+F3)")
+                .clicked()
+            {
+                step = Some(true);
+            }
+            if ui.small_button("⏷").on_hover_text("Next (F3)").clicked() {
+                step = Some(false);
+            }
+            open &= !ui.small_button("✖").clicked();
+        });
+        if shortcut::consume_ui(ui, shortcuts::FIND_PREVIOUS) {
+            step = Some(true);
+        } else if shortcut::consume_ui(ui, shortcuts::FIND_NEXT) {
+            step = Some(false);
+        }
+
+        // The rows are about to change, which restarts the search
+        if !self.is_filter_pending() && !find.is_done(row_count) {
+            let options = self.copy_options(ui.ctx());
+            let column_count = self.table_column_count();
+            find.search(row_count, Instant::now() + FIND_BUDGET, |row_nr| {
+                (0..column_count)
+                    .map(|col_nr| self.cell_text(row_nr, col_nr, options).unwrap_or_default())
+                    .collect()
+            });
+            ui.ctx().request_repaint();
+        }
+
+        let found = step.and_then(|backwards| find.step(self.focused, backwards));
+        if open {
+            self.find = Some(find);
+        }
+        if found.is_some() {
+            self.focused = found;
+        }
+        found
+    }
+
+    /// Columns of the table, including the leading ones.
+    fn table_column_count(&self) -> usize {
+        self.context.sheet().columns().len() + self.leading_column_count()
+    }
+
+    /// Starts the selection when a drag starts on the cell at `pos`, or extends it while the
+    /// pointer is over the cell. Disabled in the touch layout, where dragging scrolls instead.
+    fn update_selection(&mut self, ui: &egui::Ui, pos: CellPos) {
+        if touch_layout::is_touch(ui.ctx()) {
+            return;
+        }
+        let resp = ui.interact(ui.max_rect(), ui.id().with("selection"), Sense::drag());
+        if resp.drag_started() {
+            // Shift-dragging extends the selection instead of starting another
+            if !ui.input(|i| i.modifiers.shift) || self.selection.is_none() {
+                self.selection = Some(CellSelection::new(pos));
+            }
+            self.selecting = true;
+        } else if self.selecting
+            && ui.rect_contains_pointer(ui.max_rect())
+            && let Some(selection) = &mut self.selection
+        {
+            selection.extend_to(pos);
+        }
+    }
+
+    /// The selected cells as tab-separated values, with a line per row and the names of their
+    /// columns first if [`COPY_HEADER_ROW`] is set. Just the focused cell when none are
+    /// selected.
+    fn selection_text(&self, ctx: &egui::Context) -> String {
+        let Some(selection) = self.selection.or(self.focused.map(CellSelection::new)) else {
+            return String::new();
+        };
+        let options = self.copy_options(ctx);
+        let header = COPY_HEADER_ROW.get(ctx).then(|| {
+            selection
+                .columns()
+                .map(|col_nr| tsv_field(&self.column_name(ctx, col_nr)).into_owned())
+                .join("\t")
+        });
+        let rows = selection.rows().map(|row_nr| {
+            selection
+                .columns()
+                .map(|col_nr| {
+                    let text = self.cell_text(row_nr, col_nr, options).unwrap_or_default();
+                    tsv_field(&text).into_owned()
+                })
+                .join("\t")
+        });
+        header.into_iter().chain(rows).join("\n")
+    }
+
+    /// How cells are turned into text when copying or finding them, matching how they're shown.
+    fn copy_options(&self, ctx: &egui::Context) -> ExportOptions {
+        ExportOptions {
+            sorted_by_offset: SORTED_BY_OFFSET.get(ctx),
+            evaluate_strings: EVALUATE_STRINGS.get(ctx),
+            resolve_display_field: DISPLAY_FIELD_SHOWN.get(ctx),
+            link_depth: 0,
+            subrow_shape: SubrowShape::default(),
+        }
+    }
+
+    /// Index of the column a link points to, which like `C` links counts the row id column.
+    /// Names the schema doesn't have are read as an index instead.
+    fn resolve_column(&self, column: &ColumnRef) -> Option<u32> {
+        let column_id = match column {
+            ColumnRef::Index(column_id) => Some((*column_id).into()),
+            ColumnRef::Name(name) => self
+                .context
+                .column_index_by_name(name)
+                .map(|column_idx| column_idx + 1)
+                .or_else(|| name.parse().ok()),
+        };
+        if column_id.is_none() {
+            log::warn!(
+                "Column {column:?} not found in {}",
+                self.context.sheet().name()
+            );
+        }
+        column_id
+    }
+
+    /// The schema column of the focused cell, to link to it by name.
+    pub fn focused_column(&self, ctx: &egui::Context) -> Option<ColumnRef> {
+        let col_nr = self.focused?.col_nr;
+        let column_idx = col_nr.checked_sub(self.leading_column_count())? as u32;
+        let (column, _) = if SORTED_BY_OFFSET.get(ctx) {
+            self.context.get_column_by_offset(column_idx).ok()?
+        } else {
+            self.context.get_column_by_index(column_idx).ok()?.0
+        };
+        Some(ColumnRef::Name(column.name().to_owned()))
+    }
+
+    /// The name in the header of a column, as exports name it for the sheet's own columns.
+    fn column_name(&self, ctx: &egui::Context, col_nr: usize) -> String {
+        if col_nr == 0 {
+            return "#".to_owned();
+        }
+        if self.is_first_appeared_column(col_nr) {
+            return tr(ctx, Text::FirstAppeared).to_owned();
+        }
+        if let Some(helper_column) = self.helper_column
+            && self.is_helper_column(col_nr)
+        {
+            return helper_column.name().to_owned();
+        }
+        if let Some(script_column) = &self.script_column
+            && self.is_script_column(col_nr)
+        {
+            return script_column.name.clone();
+        }
+        let column_idx = (col_nr - self.leading_column_count()) as u32;
+        let column = if SORTED_BY_OFFSET.get(ctx) {
+            self.context.get_column_by_offset(column_idx)
+        } else {
+            self.context
+                .get_column_by_index(column_idx)
+                .map(|(column, _)| column)
+        };
+        column.map_or_else(|_| String::new(), |(column, _)| column.name().to_owned())
+    }
+
+    /// The cell the selection ends on, or the focused cell when none are selected.
+    fn selected_cell(
+        &self,
+        ctx: &egui::Context,
+    ) -> Option<((u32, Option<u16>), Option<CellValue>)> {
+        let pos = self.selection.map(|s| s.cursor()).or(self.focused)?;
+        self.cell_at(ctx, pos)
+    }
+
+    /// The row of the cell at `pos`, and the cell's value if it's one of the sheet's own
+    /// columns.
+    fn cell_at(
+        &self,
+        ctx: &egui::Context,
+        pos: CellPos,
+    ) -> Option<((u32, Option<u16>), Option<CellValue>)> {
+        let (row_id, subrow_id) = self.get_row_id(self.get_filtered_row_nr(pos.row_nr)).ok()?;
+        let Some(column_idx) = pos.col_nr.checked_sub(self.leading_column_count()) else {
+            return Some(((row_id, subrow_id), None));
+        };
+        let row = self
+            .context
+            .sheet()
+            .get_subrow(row_id, subrow_id.unwrap_or_default())
+            .ok()?;
+        let cell = if SORTED_BY_OFFSET.get(ctx) {
+            self.context.cell_by_offset(row, column_idx as u32)
+        } else {
+            self.context.cell_by_index(row, column_idx as u32)
+        };
+        let value = cell.and_then(|cell| cell.read(false)).ok();
+        Some(((row_id, subrow_id), value))
+    }
+
+    /// Copies the bytes of the selected string cell, if it is one.
+    fn copy_selected_raw(&self, ctx: &egui::Context, encode: fn(&SeStr) -> String) {
+        if let Some((_, Some(CellValue::String(value)))) = self.selected_cell(ctx) {
+            ctx.copy_text(encode(&value));
+        }
+    }
+
+    /// Menu entries for the selected cells, matching what the cells' context menus offer so
+    /// they can be reached without a pointer.
+    pub fn draw_selection_menu(&mut self, ui: &mut egui::Ui) {
+        let ctx = ui.ctx().clone();
+        let Some(((row_id, subrow_id), value)) = self.selected_cell(&ctx) else {
+            ui.label(RichText::new("No cells are selected").weak());
+            return;
+        };
+
+        if shortcut::button(ui, "Copy", shortcuts::COPY).clicked() {
+            ctx.copy_text(self.selection_text(&ctx));
+            ui.close();
+        }
+        let mut header_row = COPY_HEADER_ROW.get(&ctx);
+        if ui
+            .checkbox(&mut header_row, "Copy Column Names")
+            .on_hover_text("Copy the names of the selected columns as the first line")
+            .changed()
+        {
+            COPY_HEADER_ROW.set(&ctx, header_row);
+        }
+        match value {
+            Some(CellValue::String(value)) => {
+                if ui.button(tr(&ctx, Text::Inspect)).clicked() {
+                    sestring_inspector::inspect(&ctx, &value);
+                    ui.close();
+                }
+                if shortcut::button(ui, "Copy Raw (base64)", shortcuts::COPY_RAW_BASE64).clicked() {
+                    ctx.copy_text(raw_base64(&value));
+                    ui.close();
+                }
+                if shortcut::button(ui, "Copy Raw (hex)", shortcuts::COPY_RAW_HEX).clicked() {
+                    ctx.copy_text(raw_hex(&value));
+                    ui.close();
+                }
+            }
+            Some(CellValue::Icon(icon_id)) => {
+                if let Ok(icon_id) = u32::try_from(icon_id)
+                    && ui.button(tr(&ctx, Text::SaveIconAsPng)).clicked()
+                {
+                    // Runs detached; the promise only reports back through the log
+                    let _ = export::save_icon(
+                        self.context.global().backend().excel().clone(),
+                        icon_id,
+                        ALWAYS_HIRES.get(&ctx),
+                    );
+                    ui.close();
+                }
+            }
+            _ => {}
+        }
+
+        ui.separator();
+        let bookmark =
+            Bookmark::row((self.context.sheet().name().to_string(), (row_id, subrow_id)));
+        let text = if bookmarks::is_bookmarked(&ctx, &bookmark) {
+            Text::RemoveBookmark
+        } else {
+            Text::BookmarkRow
+        };
+        if ui.button(tr(&ctx, text)).clicked() {
+            bookmarks::toggle(&ctx, bookmark);
+            ui.close();
+        }
+        if ui
+            .button(format!("{}…", tr(&ctx, Text::FindReferences)))
+            .clicked()
+        {
+            TEMP_FIND_REFERENCES.set(
+                &ctx,
+                (self.context.sheet().name().to_string(), (row_id, subrow_id)),
+            );
+            ui.close();
+        }
+        if ui
+            .button(format!("{}…", tr(&ctx, Text::RawVsSchema)))
+            .clicked()
+        {
+            self.row_inspector = Some(RowInspector::new(self.context.clone(), (row_id, subrow_id)));
+            ui.close();
+        }
+        external_links::draw_menu_items(
+            ui,
+            &(self.context.sheet().name().to_string(), (row_id, subrow_id)),
         );
+    }
 
-        row_offsets.reserve((filtered_row_nr - len) as usize);
-        for i in len..filtered_row_nr {
-            row_offsets.push(last_size);
-            last_size += self.row_sizes[self.get_filtered_row_nr(i) as usize];
-        }
-        row_offsets.push(last_size);
-        row_offsets[filtered_row_nr as usize]
+    pub fn has_selection(&self) -> bool {
+        self.selection.is_some() || self.focused.is_some()
     }
 
-    fn is_display_column(&self, column_idx: Option<usize>, sorted_by_offset: bool) -> bool {
-        let mut is_display_column = false;
-        if let (Some(column_idx), Some(display_idx)) =
-            (column_idx, self.context.display_column_idx())
-        {
-            is_display_column = if sorted_by_offset {
-                column_idx as u32 == display_idx
-            } else {
-                self.context
-                    .convert_column_index_to_offset_index(column_idx as u32)
-                    .is_ok_and(|idx| idx == display_idx)
+    /// A cell's text as it's shown in the table, regardless of whether it's scrolled into view.
+    fn cell_text(&self, row_nr: u64, col_nr: usize, options: ExportOptions) -> Option<String> {
+        let (row_id, subrow_id) = self.get_row_id(self.get_filtered_row_nr(row_nr)).ok()?;
+        if col_nr == 0 {
+            return Some(export::format_row_id(row_id, subrow_id).into());
+        }
+        if self.is_first_appeared_column(col_nr) {
+            return match &self.first_appeared {
+                Some(FirstAppearedColumn::Loaded(versions)) => {
+                    versions.get(&row_id).map(ToString::to_string)
+                }
+                _ => None,
             };
         }
-        is_display_column
-    }
+        if let Some(script_column) = &self.script_column
+            && self.is_script_column(col_nr)
+        {
+            return script_column.values.get(&(row_id, subrow_id)).cloned();
+        }
 
-    fn paint_cell_background(ui: &mut egui::Ui, color: Color32) {
-        ui.painter().rect_filled(ui.max_rect(), 0.0, color);
+        let row = self
+            .context
+            .sheet()
+            .get_subrow(row_id, subrow_id.unwrap_or_default())
+            .ok()?;
+        if let Some(helper_column) = self.helper_column
+            && self.is_helper_column(col_nr)
+        {
+            return helper_column.format(&self.context, row_id, row);
+        }
+
+        let column_idx = (col_nr - self.leading_column_count()) as u32;
+        let cell = if options.sorted_by_offset {
+            self.context.cell_by_offset(row, column_idx)
+        } else {
+            self.context.cell_by_index(row, column_idx)
+        }
+        .ok()?;
+        let value = cell.read(options.resolve_display_field).ok()?;
+        Some(export::cell_text(&value, options).into())
     }
 
     pub fn has_filter(&self) -> bool {
         matches!(self.current_filter, Ok(Some(..)))
     }
 
+    /// Whether the shown rows are about to change once the current filter finishes.
+    pub fn is_filter_pending(&self) -> bool {
+        self.current_filter_promise.is_some()
+    }
+
     pub fn get_filter_error(&self) -> Option<&str> {
         self.current_filter.as_ref().err().map(|e| e.as_str())
     }
 
+    /// Row counts and how the filter is doing, for a status bar under the table.
+    pub fn draw_status_bar(&mut self, ui: &mut egui::Ui) {
+        let shown = self.get_filtered_row_count();
+        let pending = self.is_filter_pending();
+        // `Ok(None)` while the filter has yet to finish for the first time
+        let filter = match &self.current_filter {
+            Err(e) => Some(Err(e.clone())),
+            Ok(None) => None,
+            Ok(Some(filter)) => Some(match self.filtered_rows.get_mut().peek(filter) {
+                Some(FilterValue {
+                    filter_result: Ok(output),
+                    elapsed,
+                    ..
+                }) => Ok(Some((*elapsed, output.is_in_progress))),
+                Some(FilterValue {
+                    filter_result: Err(e),
+                    ..
+                }) => Err(e.to_string()),
+                None => Ok(None),
+            }),
+        };
+
+        ui.horizontal(|ui| {
+            let sheet = self.context.sheet();
+            let total = sheet.subrow_count();
+            ui.label(if sheet.has_subrows() {
+                format!("{} rows, {total} subrows", sheet.row_count())
+            } else {
+                format!("{total} rows")
+            });
+
+            let Some(filter) = filter else {
+                return;
+            };
+            ui.separator();
+            match filter {
+                Err(e) => {
+                    ui.colored_label(ui.visuals().error_fg_color, "⚠ Filter failed")
+                        .on_hover_text(e);
+                }
+                Ok(result) => {
+                    if result.is_some() || !pending {
+                        ui.label(format!("{shown} shown"));
+                    }
+                    if pending {
+                        ui.spinner();
+                        ui.label(tr(ui.ctx(), Text::Filtering));
+                    } else if let Some((elapsed, is_partial)) = result {
+                        ui.label(RichText::new(format!("in {elapsed:.1?}")).weak());
+                        if is_partial {
+                            ui.colored_label(ui.visuals().warn_fg_color, "⚠ Incomplete")
+                                .on_hover_text(
+                                    "Some linked sheets were still loading, so matches in them \
+                                    may be missing",
+                                );
+                        }
+                    }
+                }
+            }
+        });
+    }
+
     fn set_compiled_filter(&mut self, filter: Result<Option<CompiledFilterInput>, String>) {
         if self.current_filter == filter {
             return;
         }
+        self.forget_row_positions();
 
         if self
             .current_filter
@@ -314,73 +1522,11 @@ impl SheetTable {
         let ctx = self.context().clone();
         let promise_token = token.clone();
         let promise = TrackedPromise::spawn_local(async move {
-            #[inline]
-            async fn filter_core(
-                ctx: TableContext,
-                promise_token: Rc<Cell<bool>>,
-                mut inspector: impl FnMut(
-                    &TableContext,
-                    u32,
-                    u32,
-                    Option<u16>,
-                    &ExcelRow<'_>,
-                ) -> anyhow::Result<()>,
-            ) -> anyhow::Result<()> {
-                let batch_count = 0x4000usize.div_euclid(ctx.column_count().max(1)).max(1);
-
-                let iter: Box<
-                    dyn Iterator<Item = (u32, Option<u16>, anyhow::Result<ExcelRow<'_>>)>,
-                > = if ctx.sheet().has_subrows() {
-                    Box::new(ctx.sheet().get_row_ids().flat_map(|row_id| {
-                        let subrow_count = ctx
-                            .sheet()
-                            .get_row_subrow_count(row_id)
-                            .expect("Row should exist");
-                        let sheet = ctx.sheet();
-                        (0..subrow_count).map(move |subrow_id| {
-                            (row_id, Some(subrow_id), sheet.get_subrow(row_id, subrow_id))
-                        })
-                    }))
-                } else {
-                    Box::new(
-                        ctx.sheet()
-                            .get_row_ids()
-                            .map(|row_id| (row_id, None, ctx.sheet().get_row(row_id))),
-                    )
-                };
-
-                let mut last_now = Instant::now();
-                let mut iters = 0;
-                const MAX_FRAME_TIME: Duration = Duration::from_millis(250);
-
-                for chunk in &iter.enumerate().chunks(batch_count) {
-                    for (row_nr, (row_id, subrow_id, row)) in chunk {
-                        inspector(&ctx, row_nr as u32, row_id, subrow_id, &row?)?;
-                    }
-
-                    if promise_token.get() {
-                        log::info!("Filter cancelled");
-                        return Err(anyhow::anyhow!("Filter cancelled"));
-                    }
-
-                    let now = Instant::now();
-                    if now.duration_since(last_now) >= MAX_FRAME_TIME {
-                        iters += 1;
-                        last_now = now;
-                        yield_to_ui().await;
-                    }
-                }
-
-                log::info!("Filter completed after {iters} yields");
-
-                Ok(())
-            }
-
             let mut filtered_rows: Vec<u32>;
             let mut is_in_progress = false;
             if filter.input().unwrap().has_fuzzy {
                 let mut scored_rows = Vec::new();
-                filter_core(ctx, promise_token, |ctx, row_nr, row_id, subrow_id, row| {
+                scan_rows(ctx, promise_token, |ctx, row_nr, row_id, subrow_id, row| {
                     let (score, row_in_progress) =
                         ctx.score_row(row_id, subrow_id, row, &filter)?;
                     if row_in_progress {
@@ -405,7 +1551,7 @@ impl SheetTable {
                 FILTER_CELL_READ_STOPWATCH.reset();
                 FILTER_KEY_STOPWATCH.reset();
                 FILTER_MATCH_STOPWATCH.reset();
-                filter_core(ctx, promise_token, |ctx, row_nr, row_id, subrow_id, row| {
+                scan_rows(ctx, promise_token, |ctx, row_nr, row_id, subrow_id, row| {
                     let _sw = FILTER_TOTAL_STOPWATCH.start();
                     let (matches, row_in_progress) =
                         ctx.filter_row(row_id, subrow_id, row, &filter)?;
@@ -436,9 +1582,13 @@ impl SheetTable {
 
         self.current_filter_cancel_token = Some(token);
         self.current_filter_promise = Some(promise);
+        self.current_filter_started = Some(Instant::now());
     }
 
     fn get_filtered_row_count(&mut self) -> usize {
+        if let Some(sorted) = &self.sorted_rows {
+            return sorted.rows.len();
+        }
         if let Ok(Some(current_filter)) = &self.current_filter {
             if let Some(filter_value) = self.filtered_rows.get_mut().get(current_filter)
                 && let Ok(filter_output) = &filter_value.filter_result
@@ -456,6 +1606,12 @@ impl SheetTable {
     }
 
     fn get_filtered_row_nr(&self, filtered_row_nr: u64) -> u64 {
+        if let Some(sorted) = &self.sorted_rows {
+            return sorted
+                .rows
+                .get(filtered_row_nr as usize)
+                .map_or(filtered_row_nr, |&row_nr| row_nr.into());
+        }
         if let Ok(Some(current_filter)) = &self.current_filter {
             if let Some(filter_value) = self.filtered_rows.borrow_mut().get(current_filter)
                 && let Ok(filter_output) = &filter_value.filter_result
@@ -478,6 +1634,9 @@ impl SheetTable {
     }
 
     fn get_row_offsets(&self) -> Rc<RefCell<Vec<f32>>> {
+        if let Some(sorted) = &self.sorted_rows {
+            return sorted.row_offsets.clone();
+        }
         self.current_filter
             .as_ref()
             .unwrap_or(&None)
@@ -500,27 +1659,167 @@ impl SheetTable {
                 self.current_filter.clone().unwrap().unwrap(),
                 FilterValue {
                     filter_result: result,
+                    elapsed: self
+                        .current_filter_started
+                        .take()
+                        .map(|started| started.elapsed())
+                        .unwrap_or_default(),
                     row_offsets: Rc::new(RefCell::new(Vec::new())),
                 },
             );
         }
     }
 
+    /// Filter whose rows are shown, which is the previous one while the current one runs.
+    fn shown_filter(&mut self) -> Option<CompiledFilterInput> {
+        let current_filter = self.current_filter.as_ref().ok()?.as_ref()?;
+        let rows = self.filtered_rows.get_mut();
+        [Some(current_filter), self.last_filter.as_ref()]
+            .into_iter()
+            .flatten()
+            .find(|f| rows.get(*f).is_some_and(|v| v.filter_result.is_ok()))
+            .cloned()
+    }
+
+    /// The sort saved for this sheet, without columns the sheet no longer has.
+    fn retrieve_sort(&self, ctx: &egui::Context) -> Vec<ColumnSort> {
+        let column_count = self.context.column_count() as u32;
+        SHEET_SORTS.use_with(ctx, |map| {
+            map.get(self.context.sheet().name())
+                .map(|sorts| {
+                    sorts
+                        .iter()
+                        .filter(|s| s.offset_idx < column_count)
+                        .copied()
+                        .collect()
+                })
+                .unwrap_or_default()
+        })
+    }
+
+    /// The columns the rows are sorted by, highest priority first.
+    pub fn sort(&self) -> &[ColumnSort] {
+        &self.sort
+    }
+
+    /// Sorts the rows by `sort` and saves it for the sheet.
+    pub fn set_sort(&mut self, ctx: &egui::Context, sort: Vec<ColumnSort>) {
+        if self.sort == sort {
+            return;
+        }
+        let sheet_name = self.context.sheet().name().to_owned();
+        let levels = sort
+            .iter()
+            .map(|level| {
+                let name = self
+                    .context
+                    .get_column_by_offset(level.offset_idx)
+                    .map_or_else(
+                        |_| level.offset_idx.to_string(),
+                        |(c, _)| c.name().to_owned(),
+                    );
+                format!("{name} {}", level.direction.arrow())
+            })
+            .join(", ");
+        operation_log::record("Sort", [("sheet", sheet_name.clone()), ("by", levels)]);
+        SHEET_SORTS.use_with(ctx, |map| {
+            if sort.is_empty() {
+                map.remove(&sheet_name);
+            } else {
+                map.insert(sheet_name, sort.clone());
+            }
+        });
+        self.apply_sort(sort);
+    }
+
+    fn apply_sort(&mut self, sort: Vec<ColumnSort>) {
+        self.forget_row_positions();
+        for column in &sort {
+            self.sort_keys
+                .entry(column.offset_idx)
+                .or_insert_with(|| load_sort_keys(self.context.clone(), column.offset_idx));
+        }
+        self.sort = sort;
+    }
+
+    /// Re-sorts the shown rows when the sort or the filter changed.
+    fn tick_sort(&mut self) {
+        if self.sort.is_empty() {
+            self.sorted_rows = None;
+            return;
+        }
+        let mut levels = Vec::with_capacity(self.sort.len());
+        for column in &self.sort {
+            match self
+                .sort_keys
+                .get_mut(&column.offset_idx)
+                .and_then(|keys| keys.get(|r| r.map(Rc::new).map_err(|e| e.to_string())))
+            {
+                Some(Ok(keys)) => levels.push((keys.clone(), column.direction)),
+                Some(Err(_)) => {
+                    self.sorted_rows = None;
+                    return;
+                }
+                None => return,
+            }
+        }
+
+        let filter = self.shown_filter();
+        if self
+            .sorted_rows
+            .as_ref()
+            .is_some_and(|s| s.sort == self.sort && s.filter == filter)
+        {
+            return;
+        }
+        let rows = match &filter {
+            Some(filter) => self
+                .filtered_rows
+                .get_mut()
+                .get(filter)
+                .and_then(|v| v.filter_result.as_ref().ok())
+                .map(|output| output.filtered_rows.clone())
+                .unwrap_or_default(),
+            None => (0..self.context.sheet().subrow_count() as u32).collect(),
+        };
+        self.forget_row_positions();
+        let levels = levels
+            .iter()
+            .map(|(keys, direction)| (keys.as_slice(), *direction))
+            .collect_vec();
+        self.sorted_rows = Some(SortedRows {
+            sort: self.sort.clone(),
+            filter,
+            rows: sort_rows(rows, &levels),
+            row_offsets: Rc::default(),
+        });
+    }
+
     fn size_all_rows(&mut self, ui: &mut egui::Ui) {
         let sheet = self.context.sheet();
 
+        self.sized_raw_values = SHOW_RAW_VALUES.get(ui.ctx());
         self.row_sizes.clear();
         self.row_sizes.reserve(sheet.subrow_count() as usize);
         {
             let _stop = Stopwatch::new(format!("Sizing - {}", sheet.name()));
             let mut sizing_ui = ui.new_child(UiBuilder::new().sizing_pass());
+            apply_table_font(&mut sizing_ui);
+            let (sized_columns, fixed_size) = self.context.sized_columns(&sizing_ui);
+            let key = RowSizeKey::new(ui.ctx(), &self.context, (&sized_columns, fixed_size));
+            if let Some(sizes) = row_size_cache::load(&key) {
+                self.row_sizes = sizes;
+                return;
+            }
             for (row_id, subrow_id) in sheet.get_subrow_ids() {
                 self.row_sizes.push(self.context.size_row(
                     sheet.get_subrow(row_id, subrow_id).unwrap(),
                     &mut sizing_ui,
                     (row_id, sheet.has_subrows().then_some(subrow_id)),
+                    (&sized_columns, fixed_size),
                 ));
             }
+            row_size_cache::store(&key, &self.row_sizes);
             drop(_stop);
             MULTILINE_STOPWATCH.report();
             MULTILINE2_STOPWATCH.report();
@@ -531,6 +1830,9 @@ impl SheetTable {
 
     fn clear_offsets(&mut self) {
         self.unfiltered_row_offsets.borrow_mut().clear();
+        if let Some(sorted) = &self.sorted_rows {
+            sorted.row_offsets.borrow_mut().clear();
+        }
         for filter_value in self.filtered_rows.get_mut().iter_mut() {
             filter_value.1.row_offsets.borrow_mut().clear();
         }
@@ -569,20 +1871,199 @@ impl SheetTable {
     }
 
     pub fn update_filter(&mut self, ctx: &egui::Context) {
-        self.set_compiled_filter(self.retrieve_filter(ctx));
+        let filter = self.retrieve_filter(ctx);
+        if filter != self.current_filter {
+            let sheet_name = self.context.sheet().name();
+            let (filter_type, filter_text) = SHEET_FILTERS
+                .get(ctx)
+                .get(sheet_name)
+                .cloned()
+                .unwrap_or_default();
+            let mut details = vec![
+                ("sheet", sheet_name.to_owned()),
+                ("type", format!("{filter_type:?}")),
+                ("filter", filter_text),
+            ];
+            if let Err(e) = &filter {
+                details.push(("error", e.clone()));
+            }
+            operation_log::record("Filter", details);
+        }
+        self.set_compiled_filter(filter);
     }
 }
 
-impl TableDelegate for SheetTable {
-    fn header_cell_ui(&mut self, ui: &mut egui::Ui, cell_inf: &egui_table::HeaderCellInfo) {
-        let egui_table::HeaderCellInfo { col_range, .. } = cell_inf;
+/// The automatic tint of the `group_idx`th group. Stepping the hue by the golden ratio keeps
+/// neighbouring groups apart.
+fn group_color(group_idx: usize) -> Color32 {
+    egui::ecolor::Hsva::new((group_idx as f32 * 0.618_034).fract(), 0.6, 0.9, 1.0).into()
+}
 
-        let column_idx = if col_range.start == 0 {
-            None
+/// Reads the sort keys of a column in the background.
+fn load_sort_keys(context: TableContext, offset_idx: u32) -> ConvertibleSortKeysPromise {
+    ConvertiblePromise::new_promise(TrackedPromise::spawn_local(async move {
+        let mut keys = Vec::with_capacity(context.sheet().subrow_count() as usize);
+        scan_rows(context, Rc::default(), |ctx, _, _, _, row| {
+            keys.push(SortValue::from_cell(
+                ctx.cell_by_offset(*row, offset_idx)
+                    .and_then(|cell| cell.read_raw()),
+            ));
+            Ok(())
+        })
+        .await?;
+        Ok(keys)
+    }))
+}
+
+/// Calls `inspector` with every (sub)row of the sheet in row number order, yielding to the UI
+/// every so often. Stops early once `promise_token` is set.
+async fn scan_rows(
+    ctx: TableContext,
+    promise_token: Rc<Cell<bool>>,
+    mut inspector: impl FnMut(&TableContext, u32, u32, Option<u16>, &ExcelRow<'_>) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    let batch_count = 0x4000usize.div_euclid(ctx.column_count().max(1)).max(1);
+
+    let iter: Box<dyn Iterator<Item = (u32, Option<u16>, anyhow::Result<ExcelRow<'_>>)>> =
+        if ctx.sheet().has_subrows() {
+            Box::new(ctx.sheet().get_row_ids().flat_map(|row_id| {
+                let subrow_count = ctx
+                    .sheet()
+                    .get_row_subrow_count(row_id)
+                    .expect("Row should exist");
+                let sheet = ctx.sheet();
+                (0..subrow_count).map(move |subrow_id| {
+                    (row_id, Some(subrow_id), sheet.get_subrow(row_id, subrow_id))
+                })
+            }))
         } else {
-            Some(col_range.start - 1)
+            Box::new(
+                ctx.sheet()
+                    .get_row_ids()
+                    .map(|row_id| (row_id, None, ctx.sheet().get_row(row_id))),
+            )
         };
 
+    let mut last_now = Instant::now();
+    let mut iters = 0;
+    const MAX_FRAME_TIME: Duration = Duration::from_millis(250);
+
+    for chunk in &iter.enumerate().chunks(batch_count) {
+        for (row_nr, (row_id, subrow_id, row)) in chunk {
+            inspector(&ctx, row_nr as u32, row_id, subrow_id, &row?)?;
+        }
+
+        if promise_token.get() {
+            log::info!("Row scan cancelled");
+            return Err(anyhow::anyhow!("Row scan cancelled"));
+        }
+
+        let now = Instant::now();
+        if now.duration_since(last_now) >= MAX_FRAME_TIME {
+            iters += 1;
+            last_now = now;
+            yield_to_ui().await;
+        }
+    }
+
+    log::info!("Row scan completed after {iters} yields");
+
+    Ok(())
+}
+
+impl TableDelegate for SheetTable {
+    fn prepare(&mut self, info: &egui_table::PrefetchInfo) {
+        let sorted_by_offset = SORTED_BY_OFFSET.get(self.context.global().ctx());
+        let leading = self.leading_column_count();
+        let visible = info
+            .visible_columns
+            .clone()
+            .filter_map(|col_nr| self.offset_idx(col_nr.checked_sub(leading)?, sorted_by_offset));
+        self.context.prefetch_links(visible);
+    }
+
+    fn header_cell_ui(&mut self, ui: &mut egui::Ui, cell_inf: &egui_table::HeaderCellInfo) {
+        let egui_table::HeaderCellInfo {
+            col_range, row_nr, ..
+        } = cell_inf;
+
+        if *row_nr == 0 && !self.relation_bands.is_empty() {
+            self.draw_relation_band(ui, col_range);
+            return;
+        }
+
+        if self.is_first_appeared_column(col_range.start) {
+            egui::Frame::NONE
+                .inner_margin(Margin::symmetric(4, 2))
+                .show(ui, |ui| {
+                    ui.vertical(|ui| {
+                        ui.heading(tr(ui.ctx(), Text::FirstAppeared));
+                        match &self.first_appeared {
+                            Some(FirstAppearedColumn::Loading) => {
+                                ui.horizontal(|ui| {
+                                    ui.add(
+                                        Spinner::new()
+                                            .size(ui.text_style_height(&egui::TextStyle::Small)),
+                                    );
+                                    ui.label(
+                                        RichText::new(tr(ui.ctx(), Text::SearchingVersions))
+                                            .small(),
+                                    );
+                                });
+                            }
+                            Some(FirstAppearedColumn::Failed(err)) => {
+                                ui.label(
+                                    RichText::new(tr(ui.ctx(), Text::Failed))
+                                        .small()
+                                        .color(ui.visuals().error_fg_color),
+                                )
+                                .on_hover_text(err);
+                            }
+                            _ => {}
+                        }
+                    });
+                });
+            return;
+        }
+
+        if let Some(helper_column) = self.helper_column
+            && self.is_helper_column(col_range.start)
+        {
+            egui::Frame::NONE
+                .inner_margin(Margin::symmetric(4, 2))
+                .show(ui, |ui| {
+                    ui.vertical(|ui| {
+                        ui.heading(helper_column.name());
+                        ui.label(
+                            RichText::new(helper_column.description())
+                                .small()
+                                .color(Color32::GRAY),
+                        );
+                    });
+                });
+            return;
+        }
+
+        if let Some(script_column) = &self.script_column
+            && self.is_script_column(col_range.start)
+        {
+            egui::Frame::NONE
+                .inner_margin(Margin::symmetric(4, 2))
+                .show(ui, |ui| {
+                    ui.vertical(|ui| {
+                        ui.heading(&script_column.name);
+                        ui.label(
+                            RichText::new(tr(ui.ctx(), Text::ScriptColumnHint))
+                                .small()
+                                .color(Color32::GRAY),
+                        );
+                    });
+                });
+            return;
+        }
+
+        let column_idx = col_range.start.checked_sub(self.leading_column_count());
+
         let sorted_by_offset = SORTED_BY_OFFSET.get(ui.ctx());
 
         let column = column_idx.and_then(|c| {
@@ -598,19 +2079,171 @@ impl TableDelegate for SheetTable {
             .ok()
         });
 
-        let is_display_column = self.is_display_column(column_idx, sorted_by_offset);
+        let display_column_state = self.display_column_state(column_idx, sorted_by_offset);
+        let is_display_column = display_column_state.is_some();
+
+        if let Some(tint) = self.group_tint(column_idx, sorted_by_offset) {
+            Self::paint_cell_background(ui, tint.gamma_multiply(0.15));
+        }
 
         if is_display_column {
             Self::paint_cell_background(ui, Color32::LIGHT_BLUE.gamma_multiply(0.05));
         }
 
+        if let (Some(diff), Some(((offset_idx, _), _))) = (&self.diff, &column)
+            && diff.is_added_column(*offset_idx)
+        {
+            Self::paint_cell_background(ui, Color32::GREEN.gamma_multiply(0.1));
+        }
+
+        // Registered before the header's contents so they keep their own clicks
+        let menu_resp = ui.interact(ui.max_rect(), ui.id().with("header-menu"), Sense::click());
+        // Columns to fit to their contents
+        let mut fit_columns = None;
+        // Double-clicking the header's right edge, where the column is resized, fits it
+        let rect = ui.max_rect();
+        let on_edge = ui.input(|i| i.pointer.interact_pos()).is_some_and(|pos| {
+            (pos.x - rect.right()).abs() <= ui.style().interaction.resize_grab_radius_side
+                && rect.y_range().contains(pos.y)
+        });
+        if on_edge
+            && ui.input(|i| i.pointer.button_double_clicked(PointerButton::Primary))
+            && let Some(((offset_idx, _), _)) = &column
+        {
+            fit_columns = Some(vec![*offset_idx]);
+        }
+        // Clicking the row id column goes back to row id order. Shift-clicking adds the column
+        // as another sort level.
+        let sort_clicked = (menu_resp.clicked() && !on_edge).then(|| {
+            (
+                column.as_ref().map(|((offset_idx, _), _)| *offset_idx),
+                ui.input(|i| i.modifiers.shift),
+            )
+        });
+        // (priority, direction) of the column's sort level
+        let sort_state = column.as_ref().and_then(|((offset_idx, _), _)| {
+            let priority = self.sort.iter().position(|s| s.offset_idx == *offset_idx)?;
+            let keys = self.sort_keys.get(offset_idx).map(|keys| keys.try_get());
+            Some(match keys {
+                Some(Ok(Err(e))) => Err(Some(e.clone())),
+                Some(Ok(Ok(_))) => Ok((priority, self.sort[priority].direction)),
+                _ => Err(None),
+            })
+        });
+        let show_sort_priority = self.sort.len() > 1;
+        let mut open_histogram = None;
+        // (group, picked color), `None` to go back to the automatic one
+        let mut picked_group_color = None;
+        let mut picked_number_format = None;
+        if let Some(((offset_idx, _), (schema_column, sheet_column))) = &column {
+            let is_numeric = histogram::is_numeric(sheet_column);
+            let is_integer = NumberFormat::bits(sheet_column.kind()).is_some();
+            let group_tint = schema_column.group().zip(
+                self.group_tints
+                    .get(*offset_idx as usize)
+                    .copied()
+                    .flatten(),
+            );
+            menu_resp.context_menu(|ui| {
+                if ui.button(tr(ui.ctx(), Text::FitColumn)).clicked() {
+                    fit_columns = Some(vec![*offset_idx]);
+                    ui.close();
+                }
+                if ui.button(tr(ui.ctx(), Text::FitAllColumns)).clicked() {
+                    fit_columns = Some((0..self.context.column_count() as u32).collect());
+                    ui.close();
+                }
+                if is_numeric {
+                    ui.separator();
+                    if ui
+                        .button(format!("{}…", tr(ui.ctx(), Text::ValueDistribution)))
+                        .clicked()
+                    {
+                        open_histogram = Some((*offset_idx, schema_column.name().to_owned()));
+                        ui.close();
+                    }
+                }
+                if is_integer {
+                    let current = COLUMN_NUMBER_FORMATS
+                        .use_with(ui.ctx(), |map| {
+                            map.get(self.context.sheet().name())?
+                                .get(offset_idx)
+                                .copied()
+                        })
+                        .unwrap_or_default();
+                    ui.menu_button(tr(ui.ctx(), Text::NumberFormat), |ui| {
+                        for &format in NumberFormat::ALL {
+                            if format == NumberFormat::Timestamp {
+                                ui.separator();
+                                ui.weak(tr(ui.ctx(), Text::InterpretAs));
+                            }
+                            if ui.radio(current == format, format.name()).clicked() {
+                                picked_number_format = Some((*offset_idx, format));
+                                ui.close();
+                            }
+                        }
+                    });
+                }
+                if let Some((group, tint)) = group_tint {
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        let mut rgb = [tint.r(), tint.g(), tint.b()];
+                        if ui.color_edit_button_srgb(&mut rgb).changed() {
+                            picked_group_color = Some((group.to_owned(), Some(rgb)));
+                        }
+                        ui.label(format!("{} ({group})", tr(ui.ctx(), Text::GroupColor)));
+                        if ui
+                            .small_button("⟲")
+                            .on_hover_text(tr(ui.ctx(), Text::ResetGroupColorHover))
+                            .clicked()
+                        {
+                            picked_group_color = Some((group.to_owned(), None));
+                        }
+                    });
+                }
+            });
+        }
+
         egui::Frame::NONE
             .inner_margin(Margin::symmetric(4, 2))
             .show(ui, |ui| {
                 if let Some(((offset_idx, column_idx), (schema_column, sheet_column))) = column {
                     ui.horizontal_top(|ui| {
                         ui.vertical(|ui| {
-                            ui.heading(schema_column.name());
+                            ui.horizontal(|ui| {
+                                ui.heading(schema_column.name());
+                                match &sort_state {
+                                    Some(Ok((priority, direction))) => {
+                                        ui.heading(direction.arrow());
+                                        if show_sort_priority {
+                                            ui.label(
+                                                RichText::new((priority + 1).to_string())
+                                                    .small()
+                                                    .strong()
+                                                    .background_color(
+                                                        ui.visuals().widgets.inactive.bg_fill,
+                                                    ),
+                                            )
+                                            .on_hover_text(format!(
+                                                "Sort priority {}",
+                                                priority + 1
+                                            ));
+                                        }
+                                    }
+                                    Some(Err(None)) => {
+                                        ui.spinner();
+                                    }
+                                    Some(Err(Some(e))) => {
+                                        ui.label(
+                                            RichText::new("⚠")
+                                                .heading()
+                                                .color(ui.visuals().error_fg_color),
+                                        )
+                                        .on_hover_text(e);
+                                    }
+                                    None => {}
+                                }
+                            });
 
                             ui.label(
                                 RichText::new(format!(
@@ -632,15 +2265,37 @@ impl TableDelegate for SheetTable {
                             }
                             ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
                                 ui.style_mut().interaction.selectable_labels = false;
-                                if is_display_column {
-                                    ui.label(RichText::new("★").heading().color(Color32::GOLD))
-                                        .on_hover_text("Display Field");
+                                match display_column_state {
+                                    Some(false) => {
+                                        ui.label(RichText::new("★").heading().color(Color32::GOLD))
+                                            .on_hover_text("Display Field");
+                                    }
+                                    Some(true) => {
+                                        let resp = ui
+                                            .add(
+                                                egui::Label::new(
+                                                    RichText::new("☆")
+                                                        .heading()
+                                                        .color(Color32::GOLD),
+                                                )
+                                                .sense(egui::Sense::click()),
+                                            )
+                                            .on_hover_text(tr(ui.ctx(), Text::AutoDisplayField));
+                                        if resp.clicked() {
+                                            TEMP_PERSIST_DISPLAY_FIELD
+                                                .set(ui.ctx(), schema_column.name().to_owned());
+                                        }
+                                    }
+                                    None => {}
                                 }
                                 if let Some(comment) = schema_column.comment() {
                                     ui.label(
                                         RichText::new("🔖").heading().color(Color32::LIGHT_BLUE),
                                     )
-                                    .on_hover_text(format!("Comment: {comment}"));
+                                    .on_hover_ui(|ui| {
+                                        ui.set_max_width(400.0);
+                                        markdown(ui, comment);
+                                    });
                                 }
                             });
                         }
@@ -649,12 +2304,66 @@ impl TableDelegate for SheetTable {
                     ui.centered_and_justified(|ui| ui.heading("Row"));
                 }
             });
+
+        if let Some((offset_idx, add)) = sort_clicked {
+            let sort = offset_idx
+                .map(|idx| click_column(&self.sort, idx, add))
+                .unwrap_or_default();
+            self.set_sort(ui.ctx(), sort);
+        }
+        if let Some(offsets) = fit_columns {
+            self.fit_columns(ui, offsets);
+        }
+        if let Some((group, color)) = picked_group_color {
+            let sheet_name = self.context.sheet().name().to_owned();
+            COLUMN_GROUP_COLORS.use_with(ui.ctx(), |map| {
+                let colors = map.entry(sheet_name.clone()).or_default();
+                if let Some(color) = color {
+                    colors.insert(group, color);
+                } else {
+                    colors.remove(&group);
+                    if colors.is_empty() {
+                        map.remove(&sheet_name);
+                    }
+                }
+            });
+        }
+        if let Some((offset_idx, format)) = picked_number_format {
+            let sheet_name = self.context.sheet().name().to_owned();
+            COLUMN_NUMBER_FORMATS.use_with(ui.ctx(), |map| {
+                let formats = map.entry(sheet_name.clone()).or_default();
+                if format == NumberFormat::Decimal {
+                    formats.remove(&offset_idx);
+                    if formats.is_empty() {
+                        map.remove(&sheet_name);
+                    }
+                } else {
+                    formats.insert(offset_idx, format);
+                }
+            });
+        }
+        if let Some((offset_idx, column_name)) = open_histogram {
+            let row_count = self.context.sheet().subrow_count() as usize;
+            match HugeOperation::check(
+                &self.context,
+                row_count,
+                "Value distribution of",
+                (offset_idx, column_name),
+            ) {
+                Ok((offset_idx, column_name)) => self.open_histogram(offset_idx, column_name),
+                Err(confirm) => self.histogram_confirm = Some(confirm),
+            }
+        }
     }
 
     fn cell_ui(&mut self, ui: &mut egui::Ui, cell_info: &egui_table::CellInfo) {
         let egui_table::CellInfo { row_nr, col_nr, .. } = *cell_info;
+        apply_table_font(ui);
+        self.drawn_rows = Some(self.drawn_rows.map_or((row_nr, row_nr), |(first, last)| {
+            (first.min(row_nr), last.max(row_nr))
+        }));
 
-        let column_idx = if col_nr == 0 { None } else { Some(col_nr - 1) };
+        let column_idx = col_nr.checked_sub(self.leading_column_count());
 
         let row_data = self
             .get_row_id(self.get_filtered_row_nr(row_nr))
@@ -676,17 +2385,131 @@ impl TableDelegate for SheetTable {
         let sorted_by_offset = SORTED_BY_OFFSET.get(ui.ctx());
 
         if row_nr % 2 == 1 {
-            Self::paint_cell_background(ui, ui.visuals().faint_bg_color);
+            let background = ui.visuals().faint_bg_color;
+            Self::paint_cell_background(ui, background);
+        }
+
+        if let Some(tint) = self.group_tint(column_idx, sorted_by_offset) {
+            Self::paint_cell_background(ui, tint.gamma_multiply(0.06));
         }
 
         if TEMP_HIGHLIGHTED_ROW.try_get(ui.ctx()) == Some((row_id, subrow_id)) {
             Self::paint_cell_background(ui, Color32::GOLD.gamma_multiply(0.2));
         }
 
-        if self.is_display_column(column_idx, sorted_by_offset) {
+        if self
+            .display_column_state(column_idx, sorted_by_offset)
+            .is_some()
+        {
             Self::paint_cell_background(ui, Color32::LIGHT_BLUE.gamma_multiply(0.05));
         }
 
+        if let Some(diff) = &self.diff {
+            if diff.is_added(row_id, subrow_id) {
+                Self::paint_cell_background(ui, Color32::GREEN.gamma_multiply(0.1));
+            } else if let Some(column_idx) = column_idx {
+                let offset_idx = if sorted_by_offset {
+                    Some(column_idx as u32)
+                } else {
+                    self.context
+                        .convert_column_index_to_offset_index(column_idx as u32)
+                        .ok()
+                };
+                if offset_idx.is_some_and(|c| diff.is_changed(row_id, subrow_id, c)) {
+                    Self::paint_cell_background(ui, Color32::YELLOW.gamma_multiply(0.15));
+                }
+            } else if diff.changed_columns(row_id, subrow_id).is_some() {
+                Self::paint_cell_background(ui, Color32::YELLOW.gamma_multiply(0.05));
+            }
+        }
+
+        let page_boundary = PAGE_BOUNDARIES_SHOWN
+            .get(ui.ctx())
+            .then(|| self.page_boundary(row_nr, row_id))
+            .flatten();
+        if page_boundary.is_some() {
+            let rect = ui.max_rect();
+            ui.painter().hline(
+                rect.x_range(),
+                rect.top(),
+                egui::Stroke::new(1.0, ui.visuals().weak_text_color().gamma_multiply(0.6)),
+            );
+        }
+
+        let pos = CellPos { row_nr, col_nr };
+        let (pressed, shift) = ui.input(|i| (i.pointer.primary_pressed(), i.modifiers.shift));
+        if pressed && ui.rect_contains_pointer(ui.max_rect()) {
+            // Shift-clicking selects up to the cell from the focused one
+            if shift && let Some(focused) = self.focused {
+                self.selection
+                    .get_or_insert(CellSelection::new(focused))
+                    .extend_to(pos);
+            }
+            self.focused = Some(pos);
+        }
+        self.update_selection(ui, pos);
+        if self.selection.is_some_and(|s| s.contains(pos)) {
+            Self::paint_cell_background(ui, ui.visuals().selection.bg_fill.gamma_multiply(0.4));
+        }
+        if let Some(find) = &self.find
+            && find.is_match(pos)
+        {
+            let opacity = if find.is_current(pos) { 0.45 } else { 0.2 };
+            Self::paint_cell_background(ui, Color32::ORANGE.gamma_multiply(opacity));
+        }
+        if self.focused == Some(pos) {
+            ui.painter().rect_stroke(
+                ui.max_rect().shrink(1.0),
+                0.0,
+                ui.visuals().selection.stroke,
+                egui::StrokeKind::Inside,
+            );
+        }
+
+        if self.is_first_appeared_column(col_nr) {
+            egui::Frame::NONE
+                .inner_margin(Margin::symmetric(4, 2))
+                .show(ui, |ui| {
+                    if let Some(FirstAppearedColumn::Loaded(versions)) = &self.first_appeared
+                        && let Some(version) = versions.get(&row_id)
+                    {
+                        ui.label(version.to_string());
+                    } else {
+                        ui.label(RichText::new("…").color(Color32::GRAY));
+                    }
+                });
+            return;
+        }
+
+        if let Some(helper_column) = self.helper_column
+            && self.is_helper_column(col_nr)
+        {
+            egui::Frame::NONE
+                .inner_margin(Margin::symmetric(4, 2))
+                .show(ui, |ui| {
+                    match helper_column.format(&self.context, row_id, row_data) {
+                        Some(text) => copyable_label(ui, &text),
+                        None => ui.label(RichText::new("—").color(Color32::GRAY)),
+                    }
+                });
+            return;
+        }
+
+        if let Some(script_column) = &self.script_column
+            && self.is_script_column(col_nr)
+        {
+            egui::Frame::NONE
+                .inner_margin(Margin::symmetric(4, 2))
+                .show(ui, |ui| {
+                    match script_column.values.get(&(row_id, subrow_id)) {
+                        Some(text) => copyable_label(ui, text),
+                        None => ui.label(RichText::new("—").color(Color32::GRAY)),
+                    }
+                });
+            return;
+        }
+
+        let mut inspected_row = None;
         let resp = egui::Frame::NONE
             .inner_margin(Margin::symmetric(4, 2))
             .show(ui, |ui| {
@@ -709,17 +2532,71 @@ impl TableDelegate for SheetTable {
                             egui::Layout::centered_and_justified(egui::Direction::LeftToRight)
                                 .with_main_align(egui::Align::Center),
                             |ui| {
-                                if let Some(subrow_id) = subrow_id {
-                                    ui.label(format!("{row_id}.{subrow_id}"))
-                                        .on_hover_text(format!("Row {row_id}, Subrow {subrow_id}"))
+                                let mut hover_text = if let Some(subrow_id) = subrow_id {
+                                    format!("Row {row_id}, Subrow {subrow_id}")
+                                } else {
+                                    format!("Row {row_id}")
+                                };
+                                if let Some(page) = page_boundary
+                                    && let Some(page_def) =
+                                        self.context.sheet().row_intervals().get(page)
+                                {
+                                    hover_text += &format!(
+                                        "\nStart of page {page}: start_id {}, row_count {}",
+                                        page_def.start_id(),
+                                        page_def.row_count()
+                                    );
+                                }
+                                let text = if let Some(subrow_id) = subrow_id {
+                                    format!("{row_id}.{subrow_id}")
                                 } else {
-                                    ui.label(row_id.to_string())
-                                        .on_hover_text(format!("Row {row_id}"))
+                                    row_id.to_string()
+                                };
+                                let mut text = RichText::new(text);
+                                if MONOSPACE_NUMBERS.get(ui.ctx()) {
+                                    text = text.monospace();
                                 }
+                                ui.label(text).on_hover_text(hover_text)
                             },
                         )
                         .inner
                         .on_hover_cursor(egui::CursorIcon::Copy);
+                    resp.context_menu(|ui| {
+                        let bookmark = Bookmark::row((
+                            self.context.sheet().name().to_string(),
+                            (row_id, subrow_id),
+                        ));
+                        let text = if bookmarks::is_bookmarked(ui.ctx(), &bookmark) {
+                            Text::RemoveBookmark
+                        } else {
+                            Text::BookmarkRow
+                        };
+                        if ui.button(tr(ui.ctx(), text)).clicked() {
+                            bookmarks::toggle(ui.ctx(), bookmark);
+                            ui.close();
+                        }
+                        if ui
+                            .button(format!("{}…", tr(ui.ctx(), Text::FindReferences)))
+                            .clicked()
+                        {
+                            TEMP_FIND_REFERENCES.set(
+                                ui.ctx(),
+                                (self.context.sheet().name().to_string(), (row_id, subrow_id)),
+                            );
+                            ui.close();
+                        }
+                        if ui
+                            .button(format!("{}…", tr(ui.ctx(), Text::RawVsSchema)))
+                            .clicked()
+                        {
+                            inspected_row = Some((row_id, subrow_id));
+                            ui.close();
+                        }
+                        external_links::draw_menu_items(
+                            ui,
+                            &(self.context.sheet().name().to_string(), (row_id, subrow_id)),
+                        );
+                    });
                     let cell_resp = if resp.clicked() {
                         CellResponse::Row((
                             self.context.sheet().name().to_string(),
@@ -734,6 +2611,10 @@ impl TableDelegate for SheetTable {
             .inner
             .inner;
 
+        if let Some(row) = inspected_row {
+            self.row_inspector = Some(RowInspector::new(self.context.clone(), row));
+        }
+
         match resp {
             CellResponse::None => {}
             CellResponse::Icon(icon_id) => {
@@ -744,6 +2625,7 @@ impl TableDelegate for SheetTable {
 
         if !matches!(resp, CellResponse::None) {
             self.clicked_cell = Some(resp);
+            self.clicked_row = Some((row_id, subrow_id));
         }
     }
 
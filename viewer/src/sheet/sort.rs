@@ -0,0 +1,192 @@
+use std::cmp::Ordering;
+
+use compact_str::{CompactString, ToCompactString};
+use serde::{Deserialize, Serialize};
+
+use super::cell::CellValue;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+impl SortDirection {
+    pub fn arrow(self) -> &'static str {
+        match self {
+            Self::Ascending => "⏶",
+            Self::Descending => "⏷",
+        }
+    }
+}
+
+/// Rows ordered by the values of a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnSort {
+    pub offset_idx: u32,
+    pub direction: SortDirection,
+}
+
+/// The sort levels after clicking a column's header. A click sorts by the column alone, cycling
+/// it through ascending, descending and unsorted. With `add` (Shift), the column is cycled the
+/// same way while keeping the other levels, and joins as the lowest priority.
+pub fn click_column(sorts: &[ColumnSort], offset_idx: u32, add: bool) -> Vec<ColumnSort> {
+    let mut sorts = sorts
+        .iter()
+        .filter(|s| add || s.offset_idx == offset_idx)
+        .copied()
+        .collect::<Vec<_>>();
+    match sorts.iter().position(|s| s.offset_idx == offset_idx) {
+        Some(idx) => match sorts[idx].direction {
+            SortDirection::Ascending => sorts[idx].direction = SortDirection::Descending,
+            SortDirection::Descending => {
+                sorts.remove(idx);
+            }
+        },
+        None => sorts.push(ColumnSort {
+            offset_idx,
+            direction: SortDirection::Ascending,
+        }),
+    }
+    sorts
+}
+
+/// What a cell is sorted by.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SortValue {
+    Integer(i128),
+    Float(f32),
+    /// Lowercased, so case doesn't split up otherwise equal strings
+    Text(CompactString),
+    /// Empty strings and unreadable cells, which go last in either direction
+    Missing,
+}
+
+impl SortValue {
+    pub fn from_cell(value: anyhow::Result<CellValue>) -> Self {
+        match value {
+            Ok(CellValue::Float(value)) => Self::Float(value),
+            Ok(CellValue::String(value)) => match value.extract_text(false).try_to_compact_string()
+            {
+                Ok(text) if !text.is_empty() => Self::Text(text.to_lowercase().into()),
+                _ => Self::Missing,
+            },
+            Ok(value) => value.coerce_integer().map_or(Self::Missing, Self::Integer),
+            Err(_) => Self::Missing,
+        }
+    }
+
+    fn cmp_present(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Integer(a), Self::Integer(b)) => a.cmp(b),
+            (Self::Float(a), Self::Float(b)) => a.total_cmp(b),
+            (Self::Integer(a), Self::Float(b)) => (*a as f64).total_cmp(&f64::from(*b)),
+            (Self::Float(a), Self::Integer(b)) => f64::from(*a).total_cmp(&(*b as f64)),
+            (Self::Text(a), Self::Text(b)) => a.cmp(b),
+            // Numbers before text
+            (Self::Text(_), _) => Ordering::Greater,
+            (_, Self::Text(_)) => Ordering::Less,
+            (Self::Missing, _) | (_, Self::Missing) => Ordering::Equal,
+        }
+    }
+}
+
+fn compare(a: &SortValue, b: &SortValue, direction: SortDirection) -> Ordering {
+    match (a, b) {
+        (SortValue::Missing, SortValue::Missing) => Ordering::Equal,
+        (SortValue::Missing, _) => Ordering::Greater,
+        (_, SortValue::Missing) => Ordering::Less,
+        _ => match direction {
+            SortDirection::Ascending => a.cmp_present(b),
+            SortDirection::Descending => b.cmp_present(a),
+        },
+    }
+}
+
+/// Orders `rows` (row numbers) by the sort keys of each level, which are indexed by row number.
+/// Later levels only break ties of earlier ones. The sort is stable, so rows with equal values
+/// keep their order.
+pub fn sort_rows(mut rows: Vec<u32>, levels: &[(&[SortValue], SortDirection)]) -> Vec<u32> {
+    fn key(keys: &[SortValue], row_nr: u32) -> &SortValue {
+        keys.get(row_nr as usize).unwrap_or(&SortValue::Missing)
+    }
+    rows.sort_by(|&a, &b| {
+        levels
+            .iter()
+            .map(|&(keys, direction)| compare(key(keys, a), key(keys, b), direction))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    });
+    rows
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ColumnSort, SortDirection, SortValue, click_column, sort_rows};
+
+    #[test]
+    fn sorts_stably_with_missing_last() {
+        let keys = [
+            SortValue::Integer(3),
+            SortValue::Missing,
+            SortValue::Float(1.5),
+            SortValue::Integer(3),
+            SortValue::Text("b".into()),
+            SortValue::Integer(-2),
+        ];
+        let rows = (0..keys.len() as u32).collect::<Vec<_>>();
+        assert_eq!(
+            sort_rows(rows.clone(), &[(&keys[..], SortDirection::Ascending)]),
+            [5, 2, 0, 3, 4, 1]
+        );
+        assert_eq!(
+            sort_rows(rows.clone(), &[(&keys[..], SortDirection::Descending)]),
+            [4, 0, 3, 2, 5, 1]
+        );
+
+        let tiebreaker = (0..keys.len() as i128)
+            .map(SortValue::Integer)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sort_rows(
+                rows,
+                &[
+                    (&keys[..], SortDirection::Ascending),
+                    (tiebreaker.as_slice(), SortDirection::Descending)
+                ]
+            ),
+            [5, 2, 3, 0, 4, 1]
+        );
+    }
+
+    #[test]
+    fn shift_click_adds_levels() {
+        let sort = |offset_idx, direction| ColumnSort {
+            offset_idx,
+            direction,
+        };
+        let sorts = click_column(&[], 3, true);
+        let sorts = click_column(&sorts, 1, true);
+        assert_eq!(
+            sorts,
+            [
+                sort(3, SortDirection::Ascending),
+                sort(1, SortDirection::Ascending)
+            ]
+        );
+        let sorts = click_column(&sorts, 3, true);
+        assert_eq!(sorts[0], sort(3, SortDirection::Descending));
+        assert_eq!(
+            click_column(&sorts, 3, true),
+            [sort(1, SortDirection::Ascending)]
+        );
+        assert_eq!(
+            click_column(&sorts, 1, false),
+            [sort(1, SortDirection::Descending)]
+        );
+        assert_eq!(
+            click_column(&sorts, 2, false),
+            [sort(2, SortDirection::Ascending)]
+        );
+    }
+}
@@ -1,24 +1,34 @@
-use std::{borrow::Cow, cell::RefCell, collections::HashMap, num::NonZeroU32, rc::Rc};
+use std::{
+    borrow::Cow,
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    num::NonZeroU32,
+    rc::Rc,
+};
 
 use anyhow::bail;
+use ironworks::file::exh::ColumnKind;
 use itertools::Itertools;
 
 use crate::{
     excel::{
         base::BaseSheet,
-        provider::{ExcelHeader, ExcelProvider, ExcelRow},
+        provider::{ExcelHeader, ExcelProvider, ExcelRow, ExcelSheet},
     },
     schema::{Schema, provider::SchemaProvider},
+    settings::{DISPLAY_FIELD_FALLBACK, DisplayFieldFallback},
     sheet::{
         cell::MatchOptions,
         filter::{CompiledFilterInput, CompiledFilterKey, FilterCache, FilterInput, KeyCellIter},
     },
     stopwatch::stopwatches::{FILTER_CELL_GRAB_STOPWATCH, FILTER_ROW_STOPWATCH},
-    utils::{CloneableResult, ConvertiblePromise, TrackedPromise},
+    utils::{CloneableResult, ConvertiblePromise, ProviderResult, TrackedPromise},
 };
 
 use super::{
-    cell::Cell, global_context::GlobalContext, schema_column::SchemaColumn,
+    cell::Cell,
+    global_context::GlobalContext,
+    schema_column::{SchemaColumn, SchemaColumnMeta},
     sheet_column::SheetColumnDefinition,
 };
 
@@ -40,8 +50,13 @@ pub struct TableContextImpl {
     schema_columns: RefCell<Vec<SchemaColumn>>,
     // Offset index of the displayField column
     display_column_idx: std::cell::Cell<Option<u32>>,
+    // Offset index of the heuristically picked displayField column, computed lazily for the
+    // fallback it was picked with
+    auto_display_column_idx: std::cell::Cell<Option<(DisplayFieldFallback, Option<u32>)>>,
 
     referenced_sheets: RefCell<HashMap<String, SharedConvertibleSheetPromise>>,
+    // Whether the sheets linked by the current schema started loading
+    links_prefetched: std::cell::Cell<bool>,
 
     filter_cache: FilterCache,
 }
@@ -68,7 +83,9 @@ impl TableContext {
             sheet_columns,
             schema_columns: RefCell::new(schema_columns),
             display_column_idx: std::cell::Cell::new(display_column_idx),
+            auto_display_column_idx: std::cell::Cell::new(None),
             referenced_sheets: RefCell::new(HashMap::new()),
+            links_prefetched: std::cell::Cell::new(false),
             filter_cache,
         }))
     }
@@ -133,6 +150,14 @@ impl TableContext {
         Ok((self.get_column_by_offset(offset_idx)?, offset_idx))
     }
 
+    /// Index of the column with the given name in the schema.
+    pub fn column_index_by_name(&self, name: &str) -> Option<u32> {
+        (0..self.column_count() as u32).find(|&column_idx| {
+            self.get_column_by_index(column_idx)
+                .is_ok_and(|((column, _), _)| column.name() == name)
+        })
+    }
+
     pub fn set_schema(&self, schema: Option<&Schema>) -> anyhow::Result<()> {
         let schema = schema.map_or_else(
             || {
@@ -155,9 +180,47 @@ impl TableContext {
         })?;
         self.0.schema_columns.replace(columns);
         self.0.display_column_idx.replace(display_column_idx);
+        self.0.auto_display_column_idx.take();
+        self.0.links_prefetched.set(false);
         Ok(())
     }
 
+    /// Starts loading every sheet the columns link to, so links resolve as soon as they're
+    /// scrolled to instead of showing ids until then. Sheets linked from the `visible` offset
+    /// indices are requested first. Does nothing once done for the current schema.
+    pub fn prefetch_links(&self, visible: impl IntoIterator<Item = u32>) {
+        if self.0.links_prefetched.replace(true) {
+            return;
+        }
+        let targets = {
+            let columns = self.0.schema_columns.borrow();
+            visible
+                .into_iter()
+                .filter_map(|offset_idx| columns.get(offset_idx as usize))
+                .chain(columns.iter())
+                .flat_map(|column| match column.meta() {
+                    SchemaColumnMeta::Link(link) => link.targets().to_vec(),
+                    SchemaColumnMeta::ConditionalLink { links, .. } => links
+                        .iter()
+                        .sorted_by_key(|(value, _)| **value)
+                        .flat_map(|(_, link)| link.targets().to_vec())
+                        .collect(),
+                    _ => vec![],
+                })
+                .filter(|name| name != self.0.sheet.name())
+                .unique()
+                .collect_vec()
+        };
+        if !targets.is_empty() {
+            log::debug!(
+                "Prefetching {} sheets linked from {}",
+                targets.len(),
+                self.0.sheet.name()
+            );
+            self.load_sheets(&targets);
+        }
+    }
+
     pub fn load_sheets(&self, names: &[String]) -> Vec<SharedConvertibleSheetPromise> {
         let mut sheets = self.0.referenced_sheets.borrow_mut();
         names
@@ -174,12 +237,13 @@ impl TableContext {
                                     ctx.backend().excel().get_sheet(&name, ctx.language());
                                 let schema_future = ctx.backend().schema().get_schema_text(&name);
                                 Ok(futures_util::try_join!(sheet_future, async move {
-                                    Ok(schema_future
-                                        .await
-                                        .and_then(|s| Schema::from_str(&s))
-                                        .map(|a| a.ok())
-                                        .ok()
-                                        .flatten())
+                                    ProviderResult::Ok(
+                                        schema_future
+                                            .await
+                                            .ok()
+                                            .and_then(|s| Schema::from_str(&s).ok())
+                                            .and_then(Result::ok),
+                                    )
                                 })?)
                             },
                         ));
@@ -233,21 +297,126 @@ impl TableContext {
         self.0.display_column_idx.get()
     }
 
+    /// The schema's display field, or a provisional one picked by [`Self::auto_display_column_idx`].
+    /// The second value is `true` if the column was picked automatically.
+    pub fn display_column(&self) -> Option<(u32, bool)> {
+        match self.display_column_idx() {
+            Some(idx) => Some((idx, false)),
+            None => self.auto_display_column_idx().map(|idx| (idx, true)),
+        }
+    }
+
+    /// Offset index of the column used in place of a display field when the schema doesn't
+    /// define one, picked as set by [`DISPLAY_FIELD_FALLBACK`]: the first string column whose
+    /// values are mostly filled in and unique, then the first column named like a name.
+    pub fn auto_display_column_idx(&self) -> Option<u32> {
+        if self.display_column_idx().is_some() {
+            return None;
+        }
+        let fallback = DISPLAY_FIELD_FALLBACK.get(self.global().ctx());
+        if let Some((cached_fallback, idx)) = self.0.auto_display_column_idx.get()
+            && cached_fallback == fallback
+        {
+            return idx;
+        }
+        let idx = match fallback {
+            DisplayFieldFallback::Off => None,
+            DisplayFieldFallback::String => self.detect_display_column(),
+            DisplayFieldFallback::StringOrName => self
+                .detect_display_column()
+                .or_else(|| self.name_like_column()),
+        };
+        self.0.auto_display_column_idx.set(Some((fallback, idx)));
+        idx
+    }
+
+    /// Offset index of the first column named like a name, with the names tried in order.
+    /// Nested columns are matched by their last part.
+    fn name_like_column(&self) -> Option<u32> {
+        const NAME_LIKE_COLUMNS: &[&str] = &["Name", "Singular", "Title", "Text", "Label"];
+
+        let schema_columns = self.0.schema_columns.borrow();
+        NAME_LIKE_COLUMNS.iter().find_map(|&name| {
+            schema_columns
+                .iter()
+                .position(|column| column.name().rsplit('.').next() == Some(name))
+                .map(|idx| idx as u32)
+        })
+    }
+
+    fn detect_display_column(&self) -> Option<u32> {
+        const SAMPLE_SIZE: usize = 512;
+        const MIN_FILLED_RATIO: f32 = 0.5;
+        const MIN_UNIQUE_RATIO: f32 = 0.8;
+
+        let sheet = self.sheet();
+        let rows = sheet
+            .get_row_ids()
+            .take(SAMPLE_SIZE)
+            .filter_map(|row_id| sheet.get_subrow(row_id, 0).ok())
+            .collect_vec();
+        if rows.len() < 2 {
+            return None;
+        }
+
+        self.0
+            .sheet_columns
+            .iter()
+            .position(|column| {
+                if column.kind() != ColumnKind::String {
+                    return false;
+                }
+                let values = rows
+                    .iter()
+                    .filter_map(|row| row.read_string(column.offset() as u32).ok())
+                    .map(|s| s.as_bytes())
+                    .filter(|s| !s.is_empty())
+                    .collect_vec();
+                let unique = values.iter().collect::<HashSet<_>>().len();
+                values.len() as f32 >= rows.len() as f32 * MIN_FILLED_RATIO
+                    && unique as f32 >= values.len() as f32 * MIN_UNIQUE_RATIO
+            })
+            .map(|idx| idx as u32)
+    }
+
+    /// The cell shown for `row` where it's linked to: the display field's, or that of the
+    /// column picked by [`Self::auto_display_column_idx`].
     pub fn display_field_cell<'a>(&'a self, row: ExcelRow<'a>) -> Option<anyhow::Result<Cell<'a>>> {
-        Some(self.cell_by_offset(row, self.0.display_column_idx.get()?))
+        Some(self.cell_by_offset(row, self.display_column()?.0))
     }
 
+    /// Splits the columns by whether the height of their cells depends on the row. Returns the
+    /// offset indices of those that do, and the tallest height of those that don't. Only the
+    /// former need measuring for each row, which on wide sheets of mostly numbers is a few.
+    pub fn sized_columns(&self, ui: &egui::Ui) -> (Vec<u32>, f32) {
+        let mut sized = Vec::new();
+        let mut fixed_size = 0f32;
+        for column_idx in 0..self.column_count() as u32 {
+            let Ok((schema_column, sheet_column)) = self.get_column_by_offset(column_idx) else {
+                continue;
+            };
+            match Cell::fixed_size(ui, &schema_column, sheet_column) {
+                Some(size) => fixed_size = fixed_size.max(size),
+                None => sized.push(column_idx),
+            }
+        }
+        (sized, fixed_size)
+    }
+
+    /// `sized_columns` is what [`Self::sized_columns`] returned.
     pub fn size_row(
         &self,
         row: ExcelRow<'_>,
         ui: &mut egui::Ui,
         row_location: (u32, Option<u16>),
+        (sized_columns, fixed_size): (&[u32], f32),
     ) -> f32 {
-        let size = (0..self.sheet().columns().len())
-            .filter_map(|column_idx| self.cell_by_offset(row, column_idx as u32).ok())
+        let size = sized_columns
+            .iter()
+            .filter_map(|&column_idx| self.cell_by_offset(row, column_idx).ok())
             .map(|c| c.size(ui, row_location))
-            .reduce(f32::max);
-        size.unwrap_or_default() + 4.0
+            .fold(fixed_size, f32::max);
+        size + 4.0
     }
 
     pub fn filter_row(
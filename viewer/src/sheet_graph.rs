@@ -0,0 +1,453 @@
+use std::{
+    cell::Cell,
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    f32::consts::TAU,
+    rc::Rc,
+};
+
+use egui::{Pos2, ProgressBar, Rect, Shape, Stroke, Vec2, vec2};
+use futures_util::StreamExt;
+use itertools::Itertools;
+
+use crate::{
+    backend::Backend,
+    excel::provider::{EntriesStatus, ExcelProvider},
+    i18n::{Text, tr},
+    schema::{Schema, provider::SchemaProvider},
+    sheet::{SchemaColumn, SchemaColumnMeta},
+    utils::{PromiseKind, TrackedPromise, yield_to_ui},
+};
+
+const CONCURRENT_READS: usize = 16;
+/// Most sheets shown at once, so the layout stays readable and cheap to compute.
+const MAX_NODES: usize = 120;
+const MAX_DEPTH: usize = 3;
+const NODE_SIZE: Vec2 = vec2(150.0, 24.0);
+/// Ideal distance between linked sheets.
+const SPRING_LENGTH: f32 = 220.0;
+const LAYOUT_ITERATIONS: usize = 200;
+
+/// Which sheets link to which, according to their schemas.
+#[derive(Debug, Default)]
+pub struct SheetGraph {
+    /// Sheet -> sheets it links to
+    links: BTreeMap<String, BTreeSet<String>>,
+    /// Sheet -> sheets linking to it
+    backlinks: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl SheetGraph {
+    fn add_link(&mut self, from: &str, to: &str) {
+        self.links
+            .entry(from.to_owned())
+            .or_default()
+            .insert(to.to_owned());
+        self.backlinks
+            .entry(to.to_owned())
+            .or_default()
+            .insert(from.to_owned());
+    }
+
+    fn neighbors<'a>(&'a self, sheet: &str) -> impl Iterator<Item = &'a String> {
+        let links = self.links.get(sheet).into_iter().flatten();
+        let backlinks = self.backlinks.get(sheet).into_iter().flatten();
+        links.chain(backlinks)
+    }
+
+    /// Sheets within `depth` links of `center` in either direction, with their distance,
+    /// closest first. Stops after `limit` sheets.
+    pub fn neighborhood(&self, center: &str, depth: usize, limit: usize) -> Vec<(String, usize)> {
+        let mut seen = BTreeSet::from([center]);
+        let mut queue = VecDeque::from([(center, 0)]);
+        let mut ret = Vec::new();
+        while let Some((sheet, distance)) = queue.pop_front() {
+            if ret.len() == limit {
+                break;
+            }
+            ret.push((sheet.to_owned(), distance));
+            if distance == depth {
+                continue;
+            }
+            for neighbor in self.neighbors(sheet) {
+                if seen.insert(neighbor.as_str()) {
+                    queue.push_back((neighbor.as_str(), distance + 1));
+                }
+            }
+        }
+        ret
+    }
+
+    fn link_count(&self) -> usize {
+        self.links.values().map(BTreeSet::len).sum()
+    }
+}
+
+/// Positions of the sheets around a center sheet.
+struct GraphLayout {
+    center: String,
+    depth: usize,
+    /// Sheet and its position, the center sheet first
+    nodes: Vec<(String, Pos2)>,
+    /// Links between `nodes`, by index
+    edges: Vec<(usize, usize)>,
+    /// Whether sheets were left out past [`MAX_NODES`]
+    truncated: bool,
+}
+
+impl GraphLayout {
+    fn new(graph: &SheetGraph, center: &str, depth: usize) -> Self {
+        let neighborhood = graph.neighborhood(center, depth, MAX_NODES + 1);
+        let truncated = neighborhood.len() > MAX_NODES;
+        let neighborhood = &neighborhood[..neighborhood.len().min(MAX_NODES)];
+
+        let index: HashMap<&str, usize> = neighborhood
+            .iter()
+            .enumerate()
+            .map(|(i, (sheet, _))| (sheet.as_str(), i))
+            .collect();
+        let edges = neighborhood
+            .iter()
+            .enumerate()
+            .flat_map(|(from, (sheet, _))| {
+                let index = &index;
+                graph
+                    .links
+                    .get(sheet)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(move |target| Some((from, *index.get(target.as_str())?)))
+            })
+            .collect_vec();
+
+        // Start out on rings by distance, so the simulation only has to untangle
+        let mut positions = Vec::with_capacity(neighborhood.len());
+        for (distance, ring) in &neighborhood.iter().chunk_by(|(_, distance)| *distance) {
+            let count = ring.count();
+            for i in 0..count {
+                let angle = TAU * i as f32 / count as f32 + distance as f32 * 0.5;
+                let radius = SPRING_LENGTH * distance as f32;
+                positions.push(vec2(angle.cos(), angle.sin()) * radius);
+            }
+        }
+        settle(&mut positions, &edges);
+
+        Self {
+            center: center.to_owned(),
+            depth,
+            nodes: neighborhood
+                .iter()
+                .zip(positions)
+                .map(|((sheet, _), pos)| (sheet.clone(), pos.to_pos2()))
+                .collect(),
+            edges,
+            truncated,
+        }
+    }
+
+    fn bounds(&self) -> Rect {
+        Rect::from_points(&self.nodes.iter().map(|(_, pos)| *pos).collect_vec()).expand2(NODE_SIZE)
+    }
+}
+
+/// Force-directed layout: every sheet pushes the others away while links pull them together.
+/// The first sheet stays in the middle.
+fn settle(positions: &mut [Vec2], edges: &[(usize, usize)]) {
+    let k = SPRING_LENGTH;
+    let mut displacement = vec![Vec2::ZERO; positions.len()];
+    for iteration in 0..LAYOUT_ITERATIONS {
+        displacement.fill(Vec2::ZERO);
+        for a in 0..positions.len() {
+            for b in a + 1..positions.len() {
+                let delta = positions[a] - positions[b];
+                let distance = delta.length().max(1.0);
+                let force = delta / distance * (k * k / distance);
+                displacement[a] += force;
+                displacement[b] -= force;
+            }
+        }
+        for &(a, b) in edges {
+            if a == b {
+                continue;
+            }
+            let delta = positions[a] - positions[b];
+            let distance = delta.length().max(1.0);
+            let force = delta / distance * (distance * distance / k);
+            displacement[a] -= force;
+            displacement[b] += force;
+        }
+
+        let temperature = k * (1.0 - iteration as f32 / LAYOUT_ITERATIONS as f32) + 1.0;
+        for (pos, displacement) in positions.iter_mut().zip(&displacement).skip(1) {
+            let length = displacement.length();
+            if length > 0.0 {
+                *pos += *displacement / length * length.min(temperature);
+            }
+        }
+    }
+}
+
+/// Draws how sheets link to each other around the open sheet. Links come from the schemas, so
+/// every schema is read once when the window opens.
+pub struct SheetGraphWindow {
+    /// (read, total) schemas
+    progress: Rc<Cell<(usize, usize)>>,
+    promise: Option<TrackedPromise<SheetGraph>>,
+    graph: Option<SheetGraph>,
+    depth: usize,
+    layout: Option<GraphLayout>,
+    scene_rect: Rect,
+}
+
+impl SheetGraphWindow {
+    pub fn new(backend: Backend) -> Self {
+        let progress = Rc::new(Cell::new((0, 0)));
+        let promise = TrackedPromise::spawn_local(build(backend, progress.clone()));
+        Self {
+            progress,
+            promise: Some(promise),
+            graph: None,
+            depth: 1,
+            layout: None,
+            scene_rect: Rect::ZERO,
+        }
+    }
+
+    /// Returns the sheet the user clicked on. `open` is cleared when the window is closed.
+    pub fn draw(
+        &mut self,
+        ctx: &egui::Context,
+        open: &mut bool,
+        current_sheet: Option<&str>,
+    ) -> Option<String> {
+        if let Some(promise) = self.promise.take() {
+            match promise.try_take() {
+                Ok(graph) => self.graph = Some(graph),
+                Err(promise) => self.promise = Some(promise),
+            }
+        }
+
+        let mut ret = None;
+        egui::Window::new(tr(ctx, Text::SheetGraph))
+            .id(egui::Id::new("sheet-graph-window"))
+            .open(open)
+            .default_size([640.0, 480.0])
+            .show(ctx, |ui| {
+                let Some(graph) = &self.graph else {
+                    let (done, total) = self.progress.get();
+                    if total == 0 {
+                        ui.spinner();
+                    } else {
+                        ui.add(
+                            ProgressBar::new(done as f32 / total as f32)
+                                .text(format!("{done} / {total}")),
+                        );
+                    }
+                    return;
+                };
+
+                ui.horizontal(|ui| {
+                    ui.label(format!(
+                        "{} sheets, {} links",
+                        graph.links.len(),
+                        graph.link_count()
+                    ));
+                    ui.separator();
+                    ui.add(
+                        egui::Slider::new(&mut self.depth, 1..=MAX_DEPTH)
+                            .text("Depth")
+                            .integer(),
+                    );
+                });
+                let Some(center) = current_sheet else {
+                    ui.label("Open a sheet to see how it links to others.");
+                    return;
+                };
+
+                if self
+                    .layout
+                    .as_ref()
+                    .is_none_or(|l| l.center != center || l.depth != self.depth)
+                {
+                    let layout = GraphLayout::new(graph, center, self.depth);
+                    self.scene_rect = layout.bounds();
+                    self.layout = Some(layout);
+                }
+                let Some(layout) = &self.layout else {
+                    return;
+                };
+                if layout.truncated {
+                    ui.label(format!("Showing the {MAX_NODES} closest sheets."));
+                }
+                ui.separator();
+
+                egui::Frame::canvas(ui.style()).show(ui, |ui| {
+                    egui::Scene::new()
+                        .zoom_range(0.1..=2.0)
+                        .show(ui, &mut self.scene_rect, |ui| {
+                            if let Some(sheet) = draw_layout(ui, layout) {
+                                ret = Some(sheet);
+                            }
+                        });
+                });
+            });
+        ret
+    }
+}
+
+/// Returns the sheet that was clicked on.
+fn draw_layout(ui: &mut egui::Ui, layout: &GraphLayout) -> Option<String> {
+    // Links go under the sheets, but depend on which sheet is hovered
+    let edge_layer = ui.painter().add(Shape::Noop);
+
+    let mut clicked = None;
+    let mut hovered = None;
+    for (idx, (sheet, pos)) in layout.nodes.iter().enumerate() {
+        let rect = Rect::from_center_size(*pos, NODE_SIZE);
+        let resp = ui
+            .put(rect, egui::Button::new(sheet.as_str()).selected(idx == 0))
+            .on_hover_text(sheet.as_str());
+        if resp.hovered() {
+            hovered = Some(idx);
+        }
+        if resp.clicked() {
+            clicked = Some(sheet.clone());
+        }
+    }
+
+    let visuals = ui.visuals();
+    let stroke = Stroke::new(1.0, visuals.weak_text_color().gamma_multiply(0.6));
+    let highlight = Stroke::new(2.0, visuals.selection.bg_fill);
+    let mut shapes = Vec::new();
+    for &(from, to) in &layout.edges {
+        if from == to {
+            continue;
+        }
+        let stroke = if hovered.is_some_and(|h| h == from || h == to) {
+            highlight
+        } else {
+            stroke
+        };
+        arrow(
+            &mut shapes,
+            layout.nodes[from].1,
+            layout.nodes[to].1,
+            stroke,
+        );
+    }
+    ui.painter().set(edge_layer, Shape::Vec(shapes));
+    clicked
+}
+
+/// An arrow between the borders of two sheets.
+fn arrow(shapes: &mut Vec<Shape>, from: Pos2, to: Pos2, stroke: Stroke) {
+    let delta = to - from;
+    let Some(direction) = (delta.length() > 0.0).then(|| delta.normalized()) else {
+        return;
+    };
+    // Distance from a sheet's center to its border along the arrow
+    let half = NODE_SIZE / 2.0;
+    let inset = (half.x / direction.x.abs()).min(half.y / direction.y.abs());
+    let (start, end) = (from + direction * inset, to - direction * inset);
+    if (end - start).dot(direction) <= 0.0 {
+        return;
+    }
+
+    let head = direction * 8.0;
+    let side = direction.rot90() * 4.0;
+    shapes.push(Shape::line_segment([start, end], stroke));
+    shapes.push(Shape::convex_polygon(
+        vec![end, end - head + side, end - head - side],
+        stroke.color,
+        Stroke::NONE,
+    ));
+}
+
+async fn build(backend: Backend, progress: Rc<Cell<(usize, usize)>>) -> SheetGraph {
+    while backend.excel().entries_status() == EntriesStatus::Loading {
+        yield_to_ui().await;
+    }
+    // Miscellaneous sheets have no schemas
+    let names = backend
+        .excel()
+        .get_entries()
+        .iter()
+        .filter(|(_, id)| **id >= 0)
+        .map(|(name, _)| name.clone())
+        .sorted()
+        .collect_vec();
+    let total = names.len();
+    progress.set((0, total));
+
+    let links = futures_util::stream::iter(names)
+        .map(|name| {
+            let backend = backend.clone();
+            async move {
+                let targets = link_targets(&backend, &name).await;
+                (name, targets)
+            }
+        })
+        .buffer_unordered(CONCURRENT_READS)
+        .inspect(|_| progress.set((progress.get().0 + 1, total)))
+        .collect::<Vec<_>>()
+        .await;
+
+    let mut graph = SheetGraph::default();
+    for (name, targets) in links {
+        for target in targets {
+            graph.add_link(&name, &target);
+        }
+    }
+    graph
+}
+
+/// Sheets that the sheet's schema links to.
+async fn link_targets(backend: &Backend, name: &str) -> BTreeSet<String> {
+    let Ok(text) = backend.schema().get_schema_text(name).await else {
+        return BTreeSet::new();
+    };
+    let Ok(Ok(schema)) = Schema::from_str(&text) else {
+        return BTreeSet::new();
+    };
+    let Ok((columns, _)) = SchemaColumn::from_schema(&schema) else {
+        return BTreeSet::new();
+    };
+    columns
+        .iter()
+        .flat_map(|column| match column.meta() {
+            SchemaColumnMeta::Link(link) => link.targets().to_vec(),
+            SchemaColumnMeta::ConditionalLink { links, .. } => links
+                .values()
+                .flat_map(|link| link.targets().iter().cloned())
+                .collect(),
+            _ => Vec::new(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::SheetGraph;
+
+    #[test]
+    fn neighborhood() {
+        let mut graph = SheetGraph::default();
+        graph.add_link("Item", "ItemUICategory");
+        graph.add_link("Recipe", "Item");
+        graph.add_link("Recipe", "CraftType");
+        graph.add_link("ItemUICategory", "Item");
+
+        let names = |depth, limit| {
+            graph
+                .neighborhood("Item", depth, limit)
+                .into_iter()
+                .map(|(name, distance)| format!("{name}:{distance}"))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(1, 10), ["Item:0", "ItemUICategory:1", "Recipe:1"]);
+        assert_eq!(
+            names(2, 10),
+            ["Item:0", "ItemUICategory:1", "Recipe:1", "CraftType:2"]
+        );
+        assert_eq!(names(2, 2), ["Item:0", "ItemUICategory:1"]);
+    }
+}
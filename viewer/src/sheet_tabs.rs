@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+
+use egui::{Button, RichText, ScrollArea, Widget};
+
+use crate::{
+    i18n::{Text, tr},
+    settings::SHEET_TABS,
+    sheet::SheetViewState,
+};
+
+pub enum TabAction {
+    Select(String),
+    Close(String),
+}
+
+/// Sheets open side by side in tabs above the table. The active tab is always the selected
+/// sheet, so it's what the path points to; opening a sheet that isn't in a tab shows it in the
+/// active one instead, unless it was asked to open in a new one.
+#[derive(Default)]
+pub struct SheetTabs {
+    /// The view each tab was left on, by sheet name, to put back when it's switched to
+    views: HashMap<String, SheetViewState>,
+    /// Sheet of the active tab
+    active: Option<String>,
+    /// Sheet that gets a tab of its own once it's opened
+    opening: Option<String>,
+}
+
+impl SheetTabs {
+    /// Has the next opening of `sheet_name` add a tab instead of replacing the active one.
+    pub fn open_in_new_tab(&mut self, sheet_name: &str) {
+        self.opening = Some(sheet_name.to_owned());
+    }
+
+    /// Keeps the view of a tab being left.
+    pub fn keep_view(&mut self, view: SheetViewState) {
+        self.views.insert(view.sheet_name().to_owned(), view);
+    }
+
+    /// Puts `sheet_name` in a tab as it's opened. Returns the view the tab was left on when
+    /// it was already open in another one.
+    pub fn open(&mut self, ctx: &egui::Context, sheet_name: &str) -> Option<SheetViewState> {
+        let new_tab = self.opening.take().is_some_and(|s| s == sheet_name);
+        let previous = self.active.replace(sheet_name.to_owned());
+        let switched = SHEET_TABS.use_with(ctx, |tabs| {
+            place_tab(tabs, previous.as_deref(), sheet_name, new_tab)
+        });
+        if switched {
+            self.views.remove(sheet_name)
+        } else {
+            None
+        }
+    }
+
+    /// Removes the tab of `sheet_name`. Returns the tab next to it, if any are left.
+    pub fn close(&mut self, ctx: &egui::Context, sheet_name: &str) -> Option<String> {
+        self.views.remove(sheet_name);
+        if self.active.as_deref() == Some(sheet_name) {
+            self.active = None;
+        }
+        SHEET_TABS.use_with(ctx, |tabs| {
+            let idx = tabs.iter().position(|s| s == sheet_name)?;
+            tabs.remove(idx);
+            tabs.get(idx.min(tabs.len().saturating_sub(1))).cloned()
+        })
+    }
+
+    /// Draws the tab bar, which is only shown once more than one sheet is open.
+    pub fn draw(&self, ui: &mut egui::Ui) -> Option<TabAction> {
+        let ctx = ui.ctx().clone();
+        let tabs = SHEET_TABS.get(&ctx);
+        if tabs.len() < 2 {
+            return None;
+        }
+
+        let mut ret = None;
+        ScrollArea::horizontal()
+            .id_salt("sheet_tabs")
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    for tab in &tabs {
+                        let is_active = self.active.as_ref() == Some(tab);
+                        let resp = Button::selectable(is_active, tab.as_str())
+                            .ui(ui)
+                            .on_hover_text(tab);
+                        if resp.clicked() && !is_active {
+                            ret = Some(TabAction::Select(tab.clone()));
+                        }
+                        if resp.middle_clicked() {
+                            ret = Some(TabAction::Close(tab.clone()));
+                        }
+                        if ui
+                            .add(Button::new(RichText::new("×").small()).frame(false))
+                            .on_hover_text(tr(&ctx, Text::CloseTab))
+                            .clicked()
+                        {
+                            ret = Some(TabAction::Close(tab.clone()));
+                        }
+                        ui.separator();
+                    }
+                });
+            });
+        ret
+    }
+}
+
+/// Puts `sheet_name` in the tabs as it's opened from the `previous` one. Returns whether it
+/// already had a tab, which is then switched to.
+fn place_tab(
+    tabs: &mut Vec<String>,
+    previous: Option<&str>,
+    sheet_name: &str,
+    new_tab: bool,
+) -> bool {
+    if tabs.iter().any(|s| s == sheet_name) {
+        return previous != Some(sheet_name);
+    }
+    let previous_idx = previous.and_then(|previous| tabs.iter().position(|s| s == previous));
+    match previous_idx {
+        Some(idx) if !new_tab => tabs[idx] = sheet_name.to_owned(),
+        Some(idx) => tabs.insert(idx + 1, sheet_name.to_owned()),
+        None => tabs.push(sheet_name.to_owned()),
+    }
+    false
+}
+
+#[cfg(test)]
+mod test {
+    use super::place_tab;
+
+    #[test]
+    fn tabs_are_replaced_unless_opened_anew() {
+        let mut tabs = vec![];
+        assert!(!place_tab(&mut tabs, None, "Item", false));
+        assert!(!place_tab(&mut tabs, Some("Item"), "Action", true));
+        assert!(!place_tab(&mut tabs, Some("Item"), "Quest", true));
+        assert_eq!(tabs, ["Item", "Quest", "Action"]);
+
+        assert!(!place_tab(&mut tabs, Some("Quest"), "Mount", false));
+        assert_eq!(tabs, ["Item", "Mount", "Action"]);
+
+        assert!(place_tab(&mut tabs, Some("Mount"), "Action", false));
+        assert!(!place_tab(&mut tabs, Some("Action"), "Action", false));
+        assert!(place_tab(&mut tabs, Some("Action"), "Item", true));
+        assert_eq!(tabs, ["Item", "Mount", "Action"]);
+    }
+}
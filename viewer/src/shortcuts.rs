@@ -5,9 +5,28 @@ pub const SCHEMA_CLEAR: KeyboardShortcut = KeyboardShortcut::new(Modifiers::CTRL
 pub const SCHEMA_SAVE: KeyboardShortcut = KeyboardShortcut::new(Modifiers::CTRL, Key::S);
 pub const SCHEMA_SAVE_AS: KeyboardShortcut =
     KeyboardShortcut::new(Modifiers::CTRL.plus(Modifiers::SHIFT), Key::S);
+pub const SCHEMA_UNDO: KeyboardShortcut = KeyboardShortcut::new(Modifiers::CTRL, Key::Z);
+pub const SCHEMA_REDO: KeyboardShortcut =
+    KeyboardShortcut::new(Modifiers::CTRL.plus(Modifiers::SHIFT), Key::Z);
 
 pub const NAV_BACK: KeyboardShortcut = KeyboardShortcut::new(Modifiers::ALT, Key::ArrowLeft);
 pub const NAV_FORWARD: KeyboardShortcut = KeyboardShortcut::new(Modifiers::ALT, Key::ArrowRight);
 
 pub const GOTO_ROW: KeyboardShortcut = KeyboardShortcut::new(Modifiers::CTRL, Key::G);
 pub const GOTO_SHEET: KeyboardShortcut = KeyboardShortcut::new(Modifiers::CTRL, Key::P);
+// Also matches GOTO_SHEET, so it has to be consumed first
+pub const COMMAND_PALETTE: KeyboardShortcut =
+    KeyboardShortcut::new(Modifiers::CTRL.plus(Modifiers::SHIFT), Key::P);
+pub const FIND: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::F);
+pub const FIND_NEXT: KeyboardShortcut = KeyboardShortcut::new(Modifiers::NONE, Key::F3);
+pub const FIND_PREVIOUS: KeyboardShortcut = KeyboardShortcut::new(Modifiers::SHIFT, Key::F3);
+// Browsers reserve Ctrl+Tab for switching tabs
+pub const PREVIOUS_SHEET: KeyboardShortcut = KeyboardShortcut::new(Modifiers::CTRL, Key::E);
+
+// Only shown in menus; copying goes through the platform's copy event
+pub const COPY: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::C);
+// Browsers reserve Ctrl+Shift+C for their developer tools
+pub const COPY_RAW_BASE64: KeyboardShortcut =
+    KeyboardShortcut::new(Modifiers::CTRL.plus(Modifiers::ALT), Key::B);
+pub const COPY_RAW_HEX: KeyboardShortcut =
+    KeyboardShortcut::new(Modifiers::CTRL.plus(Modifiers::ALT), Key::H);
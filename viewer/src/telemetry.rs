@@ -0,0 +1,230 @@
+use std::{collections::BTreeMap, sync::LazyLock};
+
+use egui::{RichText, TextEdit, mutex::Mutex};
+use serde_json::{Value, json};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Duration, Instant};
+#[cfg(target_arch = "wasm32")]
+use web_time::{Duration, Instant};
+
+use crate::{
+    i18n::{Text, tr},
+    settings::{TELEMETRY_ENABLED, TELEMETRY_ENDPOINT},
+    utils::{TrackedPromise, request},
+};
+
+/// How often the collected statistics are sent while the viewer is open.
+const REPORT_INTERVAL: Duration = Duration::from_secs(15 * 60);
+/// When the first report is sent, so short sessions are reported too.
+const FIRST_REPORT_DELAY: Duration = Duration::from_secs(60);
+/// Samples kept per timing between reports; older ones are overwritten.
+const MAX_SAMPLES: usize = 2048;
+
+static SESSION: LazyLock<Mutex<Session>> = LazyLock::new(|| Mutex::new(Session::new()));
+
+/// Statistics collected since the last report. Only feature names and durations are kept:
+/// never sheet names, row data, filters, paths or anything identifying the user.
+struct Session {
+    counts: BTreeMap<&'static str, u64>,
+    timings: BTreeMap<&'static str, Samples>,
+    next_report: Instant,
+}
+
+#[derive(Default)]
+struct Samples {
+    /// Milliseconds
+    values: Vec<f32>,
+    recorded: usize,
+}
+
+impl Session {
+    fn new() -> Self {
+        Self {
+            counts: BTreeMap::new(),
+            timings: BTreeMap::new(),
+            next_report: Instant::now() + FIRST_REPORT_DELAY,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.counts.is_empty() && self.timings.is_empty()
+    }
+
+    fn clear(&mut self) {
+        self.counts.clear();
+        self.timings.clear();
+    }
+
+    fn report(&self) -> Value {
+        let platform = if cfg!(target_arch = "wasm32") {
+            "web"
+        } else {
+            std::env::consts::OS
+        };
+        json!({
+            "version": env!("CARGO_PKG_VERSION"),
+            "platform": platform,
+            "counts": self.counts,
+            "timings": self
+                .timings
+                .iter()
+                .map(|(name, samples)| (*name, percentiles(&samples.values)))
+                .collect::<BTreeMap<_, _>>(),
+        })
+    }
+}
+
+/// Counts a use of a feature, if the user opted in.
+pub fn count(ctx: &egui::Context, feature: &'static str) {
+    if TELEMETRY_ENABLED.get(ctx) {
+        *SESSION.lock().counts.entry(feature).or_default() += 1;
+    }
+}
+
+/// Records how long an operation took, if the user opted in.
+pub fn record_time(ctx: &egui::Context, metric: &'static str, duration: Duration) {
+    if !TELEMETRY_ENABLED.get(ctx) {
+        return;
+    }
+    let mut session = SESSION.lock();
+    let samples = session.timings.entry(metric).or_default();
+    let value = duration.as_secs_f32() * 1000.0;
+    if samples.values.len() < MAX_SAMPLES {
+        samples.values.push(value);
+    } else {
+        samples.values[samples.recorded % MAX_SAMPLES] = value;
+    }
+    samples.recorded += 1;
+}
+
+/// Records the time from [`Self::start`] until it's dropped.
+pub struct Timer<'a> {
+    ctx: &'a egui::Context,
+    metric: &'static str,
+    start: Instant,
+}
+
+impl<'a> Timer<'a> {
+    #[must_use]
+    pub fn start(ctx: &'a egui::Context, metric: &'static str) -> Self {
+        Self {
+            ctx,
+            metric,
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Drop for Timer<'_> {
+    fn drop(&mut self) {
+        record_time(self.ctx, self.metric, self.start.elapsed());
+    }
+}
+
+/// Sends the collected statistics to the configured endpoint once they're due.
+pub fn poll(ctx: &egui::Context) {
+    if !TELEMETRY_ENABLED.get(ctx) {
+        return;
+    }
+    let Some(endpoint) = TELEMETRY_ENDPOINT
+        .get(ctx)
+        .filter(|endpoint| !endpoint.trim().is_empty())
+    else {
+        return;
+    };
+
+    let report = {
+        let mut session = SESSION.lock();
+        if session.is_empty() || Instant::now() < session.next_report {
+            return;
+        }
+        session.next_report = Instant::now() + REPORT_INTERVAL;
+        let report = session.report();
+        session.clear();
+        report
+    };
+
+    // Runs detached; failures are only logged
+    let _ = TrackedPromise::spawn_local(async move {
+        let body = report.to_string().into_bytes();
+        let headers = [("Content-Type", "application/json")];
+        match request("POST", endpoint, &headers, Some(body)).await {
+            Ok(resp) if resp.ok => log::debug!("Sent usage statistics"),
+            Ok(resp) => log::warn!("Usage statistics endpoint answered {}", resp.status),
+            Err(e) => log::warn!("Failed to send usage statistics: {e}"),
+        }
+    });
+}
+
+/// The opt-in toggle, along with what exactly is collected and the report waiting to be sent.
+pub fn draw_settings(ui: &mut egui::Ui) {
+    let ctx = &ui.ctx().clone();
+    ui.set_max_width(320.0);
+    ui.label(tr(ctx, Text::TelemetryDisclosure));
+    ui.separator();
+
+    let mut enabled = TELEMETRY_ENABLED.get(ctx);
+    if ui.checkbox(&mut enabled, tr(ctx, Text::Enabled)).changed() {
+        TELEMETRY_ENABLED.set(ctx, enabled);
+        if !enabled {
+            SESSION.lock().clear();
+        }
+    }
+
+    ui.horizontal(|ui| {
+        ui.label(tr(ctx, Text::Endpoint));
+        let mut endpoint = TELEMETRY_ENDPOINT.get(ctx).unwrap_or_default();
+        if ui
+            .add(TextEdit::singleline(&mut endpoint).hint_text("https://…"))
+            .changed()
+        {
+            TELEMETRY_ENDPOINT.set(ctx, (!endpoint.trim().is_empty()).then_some(endpoint));
+        }
+    });
+
+    ui.collapsing(tr(ctx, Text::PendingReport), |ui| {
+        let report = SESSION.lock().report();
+        let text = serde_json::to_string_pretty(&report).unwrap_or_default();
+        egui::ScrollArea::vertical()
+            .max_height(240.0)
+            .show(ui, |ui| {
+                ui.label(RichText::new(text).monospace());
+            });
+    });
+}
+
+/// Nearest-rank percentiles of a set of samples.
+fn percentiles(samples: &[f32]) -> Value {
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(f32::total_cmp);
+    let at = |p: f32| {
+        ((sorted.len() as f32 * p).ceil() as usize)
+            .checked_sub(1)
+            .and_then(|rank| sorted.get(rank))
+            .copied()
+    };
+    json!({
+        "count": sorted.len(),
+        "p50": at(0.5),
+        "p90": at(0.9),
+        "p99": at(0.99),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::percentiles;
+
+    #[test]
+    fn percentile_ranks() {
+        let samples = (1..=100).map(|i| i as f32).collect::<Vec<_>>();
+        assert_eq!(
+            percentiles(&samples),
+            json!({ "count": 100, "p50": 50.0, "p90": 90.0, "p99": 99.0 })
+        );
+        assert_eq!(percentiles(&[3.0])["p99"], json!(3.0));
+        assert_eq!(percentiles(&[])["p50"], json!(null));
+    }
+}
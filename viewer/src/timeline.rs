@@ -0,0 +1,159 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    num::NonZeroUsize,
+    rc::Rc,
+    time::Duration,
+};
+
+use ironworks::excel::Language;
+
+use crate::{
+    data::web::WebFileProvider,
+    excel::{
+        base::CachedProvider,
+        provider::{EntriesStatus, ExcelHeader, ExcelProvider, ExcelSheet},
+    },
+    settings::{BackendConfig, InstallLocation},
+    utils::{GameVersion, sleep, yield_to_ui},
+};
+
+/// Each version's provider only loads a handful of sheets.
+const PROVIDER_CACHE_SIZE: usize = 8;
+/// How often to check whether a version's sheet list has loaded.
+const ENTRIES_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Finds the game version each row first appeared in, by binary searching over the row ids
+/// present in each version of a web backend. Assumes rows aren't removed and later re-added.
+#[derive(Clone)]
+pub struct RowTimeline(Rc<RowTimelineImpl>);
+
+struct RowTimelineImpl {
+    base_url: String,
+    slug: &'static str,
+    current: Option<GameVersion>,
+    // Oldest first, up to and including the current version
+    versions: RefCell<Option<Rc<Vec<GameVersion>>>>,
+    providers: RefCell<HashMap<GameVersion, CachedProvider>>,
+    row_sets: RefCell<HashMap<(String, GameVersion), Rc<HashSet<u32>>>>,
+}
+
+impl RowTimeline {
+    /// Returns `None` unless `config` is a web backend, the only kind with multiple versions.
+    pub fn new(config: &BackendConfig) -> Option<Self> {
+        let InstallLocation::Web(base_url, region, current) = &config.location else {
+            return None;
+        };
+        Some(Self(Rc::new(RowTimelineImpl {
+            base_url: base_url.clone(),
+            slug: region.slug()?,
+            current: current.clone(),
+            versions: RefCell::new(None),
+            providers: RefCell::new(HashMap::new()),
+            row_sets: RefCell::new(HashMap::new()),
+        })))
+    }
+
+    async fn versions(&self) -> anyhow::Result<Rc<Vec<GameVersion>>> {
+        if let Some(versions) = self.0.versions.borrow().clone() {
+            return Ok(versions);
+        }
+        let info = WebFileProvider::get_versions(&self.0.base_url, self.0.slug).await?;
+        let current = self.0.current.clone().unwrap_or(info.latest);
+        let mut versions = info
+            .versions
+            .into_iter()
+            .filter(|v| *v <= current)
+            .collect::<Vec<_>>();
+        versions.sort();
+        let versions = Rc::new(versions);
+        self.0.versions.replace(Some(versions.clone()));
+        Ok(versions)
+    }
+
+    async fn provider(&self, version: &GameVersion) -> anyhow::Result<CachedProvider> {
+        if let Some(provider) = self.0.providers.borrow().get(version) {
+            return Ok(provider.clone());
+        }
+        let files =
+            WebFileProvider::new(&self.0.base_url, self.0.slug, Some(version.clone())).await?;
+        let provider = CachedProvider::new(
+            Rc::new(files),
+            NonZeroUsize::new(PROVIDER_CACHE_SIZE).unwrap(),
+        )
+        .await?;
+        self.0
+            .providers
+            .borrow_mut()
+            .insert(version.clone(), provider.clone());
+        Ok(provider)
+    }
+
+    async fn row_set(
+        &self,
+        sheet: &str,
+        version: &GameVersion,
+    ) -> anyhow::Result<Rc<HashSet<u32>>> {
+        let key = (sheet.to_owned(), version.clone());
+        if let Some(set) = self.0.row_sets.borrow().get(&key) {
+            return Ok(set.clone());
+        }
+
+        let provider = self.provider(version).await?;
+        while provider.entries_status() == EntriesStatus::Loading {
+            sleep(ENTRIES_POLL_INTERVAL).await;
+        }
+        if let EntriesStatus::Failed(err) = provider.entries_status() {
+            anyhow::bail!("Failed to load the sheet list of {version}: {err}");
+        }
+
+        let set = if provider.get_entries().contains_key(sheet) {
+            let header = provider.get_header(sheet).await?;
+            // Row ids are the same in every language; pick any available one
+            let language = header
+                .languages()
+                .first()
+                .copied()
+                .unwrap_or(Language::None);
+            let data = provider.get_sheet(sheet, language).await?;
+            data.get_row_ids().collect()
+        } else {
+            HashSet::new()
+        };
+        let set = Rc::new(set);
+        self.0.row_sets.borrow_mut().insert(key, set.clone());
+        Ok(set)
+    }
+
+    /// Maps each of `row_ids` to the oldest version it exists in.
+    pub async fn first_appeared(
+        &self,
+        sheet: &str,
+        row_ids: impl IntoIterator<Item = u32>,
+    ) -> anyhow::Result<HashMap<u32, GameVersion>> {
+        let versions = self.versions().await?;
+        if versions.is_empty() {
+            anyhow::bail!("No versions available");
+        }
+
+        let mut ret = HashMap::new();
+        for row_id in row_ids {
+            if ret.contains_key(&row_id) {
+                continue;
+            }
+            // Invariant: the row exists in versions[hi]
+            let (mut lo, mut hi) = (0, versions.len() - 1);
+            while lo < hi {
+                let mid = lo + (hi - lo) / 2;
+                if self.row_set(sheet, &versions[mid]).await?.contains(&row_id) {
+                    hi = mid;
+                } else {
+                    lo = mid + 1;
+                }
+            }
+            ret.insert(row_id, versions[hi].clone());
+            yield_to_ui().await;
+        }
+        Ok(ret)
+    }
+}
@@ -0,0 +1,110 @@
+use egui::{Vec2, vec2};
+use serde::{Deserialize, Serialize};
+
+use crate::settings::LAYOUT_MODE;
+
+/// Screens narrower than this (in points) use the touch layout in [`LayoutMode::Auto`].
+const SMALL_SCREEN_WIDTH: f32 = 640.0;
+/// How close to the left edge a swipe has to start to open the sheet list.
+const EDGE_SWIPE_MARGIN: f32 = 32.0;
+/// How far a swipe has to travel horizontally to count.
+const SWIPE_DISTANCE: f32 = 80.0;
+/// How long a touch has to be held to open a context menu.
+const LONG_PRESS_SECONDS: f64 = 0.5;
+
+const TOUCH_INTERACT_SIZE: Vec2 = vec2(48.0, 36.0);
+const TOUCH_BUTTON_PADDING: Vec2 = vec2(10.0, 6.0);
+const TOUCH_ITEM_SPACING: Vec2 = vec2(10.0, 8.0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LayoutMode {
+    /// Touch layout on small screens.
+    #[default]
+    Auto,
+    Desktop,
+    Touch,
+}
+
+impl LayoutMode {
+    pub const ALL: &[Self] = &[Self::Auto, Self::Desktop, Self::Touch];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Auto => "Auto",
+            Self::Desktop => "Desktop",
+            Self::Touch => "Touch",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Swipe {
+    Left,
+    Right,
+}
+
+/// Whether the touch layout should be used this frame.
+pub fn is_touch(ctx: &egui::Context) -> bool {
+    match LAYOUT_MODE.get(ctx) {
+        LayoutMode::Auto => ctx.content_rect().width() < SMALL_SCREEN_WIDTH,
+        LayoutMode::Desktop => false,
+        LayoutMode::Touch => true,
+    }
+}
+
+/// Enlarges (or restores) widgets so they're comfortable to hit with a finger, and shortens
+/// the hold needed to open context menus with a long-press.
+pub fn apply_style(ctx: &egui::Context, touch: bool) {
+    let default_spacing = egui::style::Spacing::default();
+    ctx.all_styles_mut(|style| {
+        let spacing = &mut style.spacing;
+        if touch {
+            spacing.interact_size = TOUCH_INTERACT_SIZE;
+            spacing.button_padding = TOUCH_BUTTON_PADDING;
+            spacing.item_spacing = TOUCH_ITEM_SPACING;
+        } else {
+            spacing.interact_size = default_spacing.interact_size;
+            spacing.button_padding = default_spacing.button_padding;
+            spacing.item_spacing = default_spacing.item_spacing;
+        }
+    });
+    ctx.options_mut(|options| {
+        options.input_options.max_click_duration = if touch {
+            LONG_PRESS_SECONDS
+        } else {
+            egui::InputOptions::default().max_click_duration
+        };
+    });
+}
+
+/// A horizontal swipe released this frame. Swipes to the right only count when they start at
+/// the left edge of the screen, so they don't fight with scrolling the table.
+pub fn swipe(ctx: &egui::Context) -> Option<Swipe> {
+    let origin_id = egui::Id::new("touch-swipe-origin");
+    let (pressed, released, pos, left) = ctx.input(|i| {
+        (
+            i.pointer.primary_pressed(),
+            i.pointer.primary_released(),
+            i.pointer.interact_pos(),
+            i.content_rect().left(),
+        )
+    });
+    if pressed {
+        ctx.data_mut(|d| d.insert_temp(origin_id, pos));
+    }
+    if !released {
+        return None;
+    }
+
+    let origin = ctx.data_mut(|d| d.remove_temp::<Option<egui::Pos2>>(origin_id))??;
+    let delta = pos? - origin;
+    if delta.x.abs() < SWIPE_DISTANCE || delta.x.abs() < delta.y.abs() * 2.0 {
+        None
+    } else if delta.x < 0.0 {
+        Some(Swipe::Left)
+    } else if origin.x - left <= EDGE_SWIPE_MARGIN {
+        Some(Swipe::Right)
+    } else {
+        None
+    }
+}
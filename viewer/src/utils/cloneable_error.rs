@@ -1,5 +1,7 @@
 use std::{fmt::Display, ops::Deref};
 
+use super::ProviderError;
+
 /// Cloneable wrapper for `anyhow::Result`
 pub type CloneableResult<T> = Result<T, CloneableError>;
 
@@ -27,7 +29,11 @@ impl From<CloneableError> for anyhow::Error {
 
 impl Clone for CloneableError {
     fn clone(&self) -> Self {
-        Self(anyhow::anyhow!(self.0.to_string()))
+        // Keep provider errors intact, so copies can still be told apart by category
+        match ProviderError::of(&self.0) {
+            Some(error) => Self(error.clone().into()),
+            None => Self(anyhow::anyhow!(self.0.to_string())),
+        }
     }
 }
 
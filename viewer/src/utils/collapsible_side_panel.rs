@@ -75,6 +75,10 @@ impl CollapsibleSidePanel {
         })
     }
 
+    pub fn set_collapsed(ctx: &Context, id: impl Into<Id>, collapsed: bool) {
+        ctx.data_mut(|d| d.insert_temp(id.into().with("is_collapsed"), collapsed));
+    }
+
     fn openness(ctx: &Context, id: impl Into<Id>) -> f32 {
         let id = id.into();
         ctx.animate_bool_responsive(id.with("arrow_animation"), !Self::is_collapsed(ctx, id))
@@ -0,0 +1,73 @@
+use std::{
+    any::Any,
+    hash::Hash,
+    panic::{AssertUnwindSafe, catch_unwind},
+    sync::Arc,
+};
+
+use egui::{Frame, Id, RichText};
+
+use crate::i18n::{Text, tr};
+
+/// Draws `add_contents`, containing any failure to this part of the UI. A panic or a returned
+/// error replaces the contents with an error card (with a retry button) until retried, so the
+/// rest of the app stays usable.
+pub fn error_boundary<R>(
+    ui: &mut egui::Ui,
+    id_salt: impl Hash,
+    add_contents: impl FnOnce(&mut egui::Ui) -> anyhow::Result<R>,
+) -> Option<R> {
+    let id = Id::new(("error-boundary", id_salt));
+
+    if let Some(error) = ui.data(|d| d.get_temp::<Arc<str>>(id)) {
+        if draw_error_card(ui, &error) {
+            ui.data_mut(|d| d.remove::<Arc<str>>(id));
+            ui.ctx().request_repaint();
+        }
+        return None;
+    }
+
+    let error = match catch_unwind(AssertUnwindSafe(|| add_contents(ui))) {
+        Ok(Ok(ret)) => return Some(ret),
+        Ok(Err(e)) => format!("{e:?}"),
+        Err(panic) => panic_message(panic.as_ref()),
+    };
+    log::error!("Error while drawing UI: {error}");
+    ui.data_mut(|d| d.insert_temp(id, Arc::<str>::from(error)));
+    ui.ctx().request_repaint();
+    None
+}
+
+/// Returns true if retry was clicked.
+fn draw_error_card(ui: &mut egui::Ui, error: &str) -> bool {
+    let mut retry = false;
+    ui.vertical_centered(|ui| {
+        Frame::group(ui.style())
+            .fill(ui.visuals().error_fg_color.gamma_multiply(0.1))
+            .show(ui, |ui| {
+                ui.label(
+                    RichText::new(tr(ui.ctx(), Text::SomethingWentWrong))
+                        .heading()
+                        .color(ui.visuals().error_fg_color),
+                );
+                ui.label(RichText::new(error).monospace());
+                ui.horizontal(|ui| {
+                    retry = ui.button(tr(ui.ctx(), Text::Retry)).clicked();
+                    if ui.button(tr(ui.ctx(), Text::CopyError)).clicked() {
+                        ui.ctx().copy_text(error.to_owned());
+                    }
+                });
+            });
+    });
+    retry
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> String {
+    if let Some(s) = panic.downcast_ref::<&str>() {
+        format!("Panic: {s}")
+    } else if let Some(s) = panic.downcast_ref::<String>() {
+        format!("Panic: {s}")
+    } else {
+        "Panic: (unknown payload)".to_string()
+    }
+}
@@ -1,11 +1,19 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet, hash_map::Entry},
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::Arc,
+};
 
 use egui::{
     ColorImage, ImageSource, TextureHandle, TextureOptions, load::SizedTexture, mutex::Mutex,
 };
 use either::Either;
-use image::RgbaImage;
-use url::Url;
+use image::{RgbaImage, imageops::FilterType};
+
+use crate::{
+    data::{IconRevision, get_icon_path},
+    settings::{ICON_AUTO_DOWNSCALE, ICON_MAX_TEXTURE_SIZE},
+};
 
 use super::{
     CloneableResult, ConvertiblePromise, PromiseKind, TrackedPromise,
@@ -19,23 +27,51 @@ pub enum ManagedIcon {
     NotLoaded,
 }
 
-type IconEntry = (
-    u32,  // icon_id
+/// Largest side of hires textures on devices reporting little memory.
+const LOW_MEMORY_MAX_SIDE: u32 = 1024;
+
+/// Icons kept before the least recently drawn ones are dropped, along with their textures.
+const MAX_ICONS: usize = 4096;
+
+type ContentKey = (
+    u64,  // hash of the pixels
     bool, // hires
 );
 
-type IconPromise = TrackedPromise<anyhow::Result<Either<Url, RgbaImage>>>;
+type IconPromise = TrackedPromise<anyhow::Result<IconRevision>>;
 
-type ConvertibleIconPromise =
-    ConvertiblePromise<IconPromise, CloneableResult<ImageSource<'static>>>;
+type ConvertibleIconPromise = ConvertiblePromise<IconPromise, CloneableResult<LoadedIcon>>;
 
 #[derive(Clone, Default)]
 pub struct IconManager(Arc<Mutex<IconManagerImpl>>);
 
+#[derive(Clone)]
+struct LoadedIcon {
+    source: ImageSource<'static>,
+    /// The provider's tag of the icon's content, to check it against other versions with
+    etag: Option<String>,
+    /// Key of the texture in [`IconManagerImpl::textures`], if it was uploaded here
+    content: Option<ContentKey>,
+}
+
+struct IconSlot {
+    /// Generation of the backend the icon was loaded from
+    generation: u32,
+    promise: ConvertibleIconPromise,
+    /// The icon from a previous backend, shown until it's revalidated against the current one
+    stale: Option<LoadedIcon>,
+    /// When the icon was last drawn, in calls to [`IconManagerImpl::get_or_insert_icon_promise`]
+    last_used: u64,
+}
+
 #[derive(Default)]
 struct IconManagerImpl {
-    cache: HashMap<IconEntry, ConvertibleIconPromise>,
-    loaded_handles: Vec<TextureHandle>,
+    /// Keyed by the icon's path, which tells hires icons apart
+    cache: HashMap<String, IconSlot>,
+    /// Uploaded textures by their content, shared by icons that are identical across versions
+    textures: HashMap<ContentKey, TextureHandle>,
+    generation: u32,
+    uses: u64,
 }
 
 impl IconManager {
@@ -47,17 +83,21 @@ impl IconManager {
         self.0.lock().clear();
     }
 
-    // None = not loaded, Some(None) = loaded but failed/doesn't exist, Some(Some) = loaded successfully
-    // pub fn get_icon(&self, icon_id: u32, hires: bool, context: &egui::Context) -> ManagedIcon {
-    //     self.0.lock().get_icon(icon_id, hires, context)
-    // }
+    /// Keeps the loaded icons around after switching backends, but revalidates each one against
+    /// the new backend the next time it's drawn. Icons that didn't change keep their texture,
+    /// and aren't downloaded again if the backend can tell by their tag.
+    pub fn revalidate(&self) {
+        let mut inner = self.0.lock();
+        inner.generation = inner.generation.wrapping_add(1);
+    }
 
+    /// `promise_creator` is given the tag of the copy loaded from a previous backend, if any.
     pub fn get_or_insert_icon(
         &self,
         icon_id: u32,
         hires: bool,
         context: &egui::Context,
-        promise_creator: impl FnOnce() -> IconPromise,
+        promise_creator: impl FnOnce(Option<String>) -> IconPromise,
     ) -> ManagedIcon {
         self.0
             .lock()
@@ -67,20 +107,43 @@ impl IconManager {
 
 impl IconManagerImpl {
     pub fn clear(&mut self) {
-        self.loaded_handles.clear();
+        self.textures.clear();
         self.cache.clear();
     }
 
     fn convert_promise(
-        handles: &mut Vec<TextureHandle>,
+        textures: &mut HashMap<ContentKey, TextureHandle>,
+        stale: Option<&LoadedIcon>,
         icon_id: u32,
         hires: bool,
         ctx: &egui::Context,
         result: <IconPromise as PromiseKind>::Output,
-    ) -> CloneableResult<ImageSource<'static>> {
+    ) -> CloneableResult<LoadedIcon> {
         match result {
-            Ok(Either::Left(url)) => Ok(ImageSource::Uri(url.to_string().into())),
-            Ok(Either::Right(data)) => {
+            Ok(IconRevision::Unchanged) => stale.cloned().ok_or_else(|| {
+                anyhow::anyhow!("Icon {icon_id} was unchanged from a copy that isn't loaded").into()
+            }),
+            Ok(IconRevision::Changed(Either::Left(url), etag)) => Ok(LoadedIcon {
+                source: ImageSource::Uri(url.to_string().into()),
+                etag,
+                content: None,
+            }),
+            Ok(IconRevision::Changed(Either::Right(data), etag)) => {
+                let key = (content_hash(&data), hires);
+                if let Some(handle) = textures.get(&key) {
+                    return Ok(LoadedIcon {
+                        source: ImageSource::Texture(SizedTexture::from_handle(handle)),
+                        etag,
+                        content: Some(key),
+                    });
+                }
+                let data = match fit_texture(ctx, data, hires) {
+                    Ok(data) => data,
+                    Err(e) => {
+                        log::warn!("Failed to load icon {icon_id}: {e}");
+                        return Err(e.into());
+                    }
+                };
                 let handle = ctx.load_texture(
                     format!("Icon {icon_id}{}", if hires { " (hr1)" } else { "" }),
                     ColorImage::from_rgba_unmultiplied(
@@ -90,8 +153,12 @@ impl IconManagerImpl {
                     TextureOptions::LINEAR,
                 );
                 let ret = SizedTexture::from_handle(&handle);
-                handles.push(handle);
-                Ok(ImageSource::Texture(ret))
+                textures.insert(key, handle);
+                Ok(LoadedIcon {
+                    source: ImageSource::Texture(ret),
+                    etag,
+                    content: Some(key),
+                })
             }
             Err(e) => {
                 log::error!("Failed to load icon: {e:?}");
@@ -100,40 +167,155 @@ impl IconManagerImpl {
         }
     }
 
-    // pub fn get_icon(&mut self, icon_id: u32, hires: bool, context: &egui::Context) -> ManagedIcon {
-    //     let entry = match self.cache.get_mut(&(icon_id, hires)) {
-    //         Some(entry) => entry,
-    //         None => return ManagedIcon::NotLoaded,
-    //     };
-    //     let ret = entry
-    //         .get(|r| Self::convert_promise(&mut self.loaded_handles, icon_id, hires, context, r))
-    //         .cloned();
-    //     match ret {
-    //         Some(Ok(image)) => ManagedIcon::Loaded(image),
-    //         Some(Err(e)) => ManagedIcon::Failed(e),
-    //         None => ManagedIcon::Loading,
-    //     }
-    // }
-
     pub fn get_or_insert_icon_promise(
         &mut self,
         icon_id: u32,
         hires: bool,
         context: &egui::Context,
-        promise_creator: impl FnOnce() -> IconPromise,
+        promise_creator: impl FnOnce(Option<String>) -> IconPromise,
     ) -> ManagedIcon {
-        let ret = self
-            .cache
-            .entry((icon_id, hires))
-            .or_insert_with(|| ConvertiblePromise::new_promise(promise_creator()))
+        let generation = self.generation;
+        self.uses += 1;
+        let last_used = self.uses;
+        let path = get_icon_path(icon_id, hires);
+        if self.cache.len() >= MAX_ICONS && !self.cache.contains_key(&path) {
+            self.evict();
+        }
+
+        let slot = match self.cache.entry(path) {
+            Entry::Occupied(entry) => {
+                let slot = entry.into_mut();
+                if slot.generation != generation {
+                    slot.stale = match slot.promise.try_get() {
+                        Ok(Ok(icon)) => Some(icon.clone()),
+                        _ => slot.stale.take(),
+                    };
+                    let etag = slot.stale.as_ref().and_then(|icon| icon.etag.clone());
+                    slot.promise = ConvertiblePromise::new_promise(promise_creator(etag));
+                    slot.generation = generation;
+                }
+                slot.last_used = last_used;
+                slot
+            }
+            Entry::Vacant(entry) => entry.insert(IconSlot {
+                generation,
+                promise: ConvertiblePromise::new_promise(promise_creator(None)),
+                stale: None,
+                last_used,
+            }),
+        };
+        let stale = slot.stale.as_ref();
+        let ret = slot
+            .promise
             .get_mut(|r| {
-                Self::convert_promise(&mut self.loaded_handles, icon_id, hires, context, r)
+                Self::convert_promise(&mut self.textures, stale, icon_id, hires, context, r)
             })
             .cloned();
         match ret {
-            Some(Ok(image)) => ManagedIcon::Loaded(image),
-            Some(Err(e)) => ManagedIcon::Failed(e),
-            None => ManagedIcon::Loading,
+            Some(Ok(icon)) => {
+                slot.stale = None;
+                ManagedIcon::Loaded(icon.source)
+            }
+            Some(Err(e)) => {
+                slot.stale = None;
+                ManagedIcon::Failed(e)
+            }
+            None => slot.stale.as_ref().map_or(ManagedIcon::Loading, |icon| {
+                ManagedIcon::Loaded(icon.source.clone())
+            }),
         }
     }
+
+    /// Drops the least recently drawn quarter of the icons, and the textures no icon left uses.
+    fn evict(&mut self) {
+        let mut uses = self
+            .cache
+            .values()
+            .map(|slot| slot.last_used)
+            .collect::<Vec<_>>();
+        let cutoff = MAX_ICONS / 4;
+        let (_, &mut oldest_kept, _) = uses.select_nth_unstable(cutoff);
+        self.cache.retain(|_, slot| slot.last_used >= oldest_kept);
+
+        let used = self
+            .cache
+            .values()
+            .flat_map(|slot| {
+                let loaded = match slot.promise.try_get() {
+                    Ok(Ok(icon)) => icon.content,
+                    _ => None,
+                };
+                [loaded, slot.stale.as_ref().and_then(|icon| icon.content)]
+            })
+            .flatten()
+            .collect::<HashSet<_>>();
+        self.textures.retain(|key, _| used.contains(key));
+    }
+}
+
+fn content_hash(data: &RgbaImage) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    data.dimensions().hash(&mut hasher);
+    data.as_raw().hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Downscales an icon to the configured maximum size, and with automatic downscaling, to what
+/// the GPU and a low-memory device can hold. Fails if the icon is still too large for the GPU,
+/// since the allocation would otherwise leave a blank image behind.
+fn fit_texture(ctx: &egui::Context, data: RgbaImage, hires: bool) -> anyhow::Result<RgbaImage> {
+    let gpu_max = ctx.input(|i| i.max_texture_side) as u32;
+    let auto_downscale = ICON_AUTO_DOWNSCALE.get(ctx);
+
+    let mut max_side = ICON_MAX_TEXTURE_SIZE
+        .get(ctx)
+        .map_or(u32::MAX, |size| size.get().into());
+    if auto_downscale {
+        max_side = max_side.min(gpu_max);
+        if hires && is_low_memory_device() {
+            max_side = max_side.min(LOW_MEMORY_MAX_SIDE);
+        }
+    }
+
+    let (width, height) = data.dimensions();
+    let data = if width.max(height) > max_side {
+        let scale = f64::from(max_side) / f64::from(width.max(height));
+        let new_width = ((f64::from(width) * scale).round() as u32).max(1);
+        let new_height = ((f64::from(height) * scale).round() as u32).max(1);
+        log::info!("Downscaling {width}x{height} icon to {new_width}x{new_height}");
+        image::imageops::resize(&data, new_width, new_height, FilterType::Triangle)
+    } else {
+        data
+    };
+
+    if data.width().max(data.height()) > gpu_max {
+        anyhow::bail!(
+            "The icon is {}x{}, larger than the maximum texture size of {gpu_max}. Enable automatic downscaling to view it.",
+            data.width(),
+            data.height()
+        );
+    }
+    Ok(data)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn is_low_memory_device() -> bool {
+    use wasm_bindgen::JsValue;
+    use web_sys::js_sys::Reflect;
+
+    /// Devices with at most this much memory are considered low-memory
+    const LOW_MEMORY_GIB: f64 = 4.0;
+
+    // Only exposed by Chromium-based browsers, rounded to a power of two
+    web_sys::window()
+        .and_then(|window| {
+            Reflect::get(&window.navigator(), &JsValue::from_str("deviceMemory")).ok()
+        })
+        .and_then(|memory| memory.as_f64())
+        .is_some_and(|gib| gib <= LOW_MEMORY_GIB)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn is_low_memory_device() -> bool {
+    false
 }
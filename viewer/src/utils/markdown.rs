@@ -0,0 +1,201 @@
+use egui::RichText;
+
+/// An inline piece of a Markdown paragraph.
+#[derive(Debug, PartialEq, Eq)]
+enum Span<'a> {
+    Text(&'a str),
+    Code(&'a str),
+    Strong(&'a str),
+    Emphasis(&'a str),
+    Link { text: &'a str, url: &'a str },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Block {
+    Paragraph(String),
+    ListItem(String),
+}
+
+/// Renders a small subset of Markdown: paragraphs, `-`/`*` lists, code spans, bold, italics,
+/// `[links](url)` and bare URLs. Meant for short schema comments, not full documents.
+pub fn markdown(ui: &mut egui::Ui, text: &str) {
+    for block in parse_blocks(text) {
+        match block {
+            Block::Paragraph(text) => draw_inline(ui, &text),
+            Block::ListItem(text) => {
+                ui.horizontal_top(|ui| {
+                    ui.label("•");
+                    ui.vertical(|ui| draw_inline(ui, &text));
+                });
+            }
+        }
+    }
+}
+
+fn draw_inline(ui: &mut egui::Ui, text: &str) {
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 0.0;
+        for span in parse_inline(text) {
+            match span {
+                Span::Text(text) => ui.label(text),
+                Span::Code(code) => ui.code(code),
+                Span::Strong(text) => ui.label(RichText::new(text).strong()),
+                Span::Emphasis(text) => ui.label(RichText::new(text).italics()),
+                Span::Link { text, url } => ui.hyperlink_to(text, url).on_hover_text(url),
+            };
+        }
+    });
+}
+
+fn parse_blocks(text: &str) -> Vec<Block> {
+    fn flush(paragraph: &mut String, blocks: &mut Vec<Block>) {
+        if !paragraph.is_empty() {
+            blocks.push(Block::Paragraph(std::mem::take(paragraph)));
+        }
+    }
+
+    let mut blocks = Vec::new();
+    let mut paragraph = String::new();
+    for raw_line in text.lines() {
+        let line = raw_line.trim();
+        if line.is_empty() {
+            flush(&mut paragraph, &mut blocks);
+        } else if let Some(item) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(Block::ListItem(item.trim().to_owned()));
+        } else if raw_line.starts_with([' ', '\t'])
+            && paragraph.is_empty()
+            && let Some(Block::ListItem(item)) = blocks.last_mut()
+        {
+            // Indented continuation of a list item
+            item.push(' ');
+            item.push_str(line);
+        } else {
+            if !paragraph.is_empty() {
+                paragraph.push(' ');
+            }
+            paragraph.push_str(line);
+        }
+    }
+    flush(&mut paragraph, &mut blocks);
+    blocks
+}
+
+fn parse_inline(text: &str) -> Vec<Span<'_>> {
+    let mut spans = Vec::new();
+    let mut text_start = 0;
+    let mut i = 0;
+    while i < text.len() {
+        let rest = &text[i..];
+        let at_word_start = text[..i]
+            .chars()
+            .next_back()
+            .is_none_or(|c| c.is_whitespace() || c == '(');
+
+        let token = if let Some(inner) = rest.strip_prefix('`') {
+            inner
+                .find('`')
+                .map(|end| (Span::Code(&inner[..end]), end + 2))
+        } else if let Some(inner) = rest.strip_prefix("**") {
+            inner
+                .find("**")
+                .filter(|&end| end > 0)
+                .map(|end| (Span::Strong(&inner[..end]), end + 4))
+        } else if at_word_start && (rest.starts_with('*') || rest.starts_with('_')) {
+            let delimiter = &rest[..1];
+            let inner = &rest[1..];
+            inner
+                .find(delimiter)
+                .filter(|&end| end > 0)
+                .map(|end| (Span::Emphasis(&inner[..end]), end + 2))
+        } else if let Some(inner) = rest.strip_prefix('[') {
+            inner.find("](").and_then(|text_end| {
+                let url_part = &inner[text_end + 2..];
+                url_part.find(')').map(|url_end| {
+                    (
+                        Span::Link {
+                            text: &inner[..text_end],
+                            url: &url_part[..url_end],
+                        },
+                        1 + text_end + 2 + url_end + 1,
+                    )
+                })
+            })
+        } else if at_word_start && (rest.starts_with("https://") || rest.starts_with("http://")) {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            // Trailing punctuation is more likely to end the sentence than the URL
+            let url = rest[..end].trim_end_matches(['.', ',', ';', ':', ')']);
+            Some((Span::Link { text: url, url }, url.len()))
+        } else {
+            None
+        };
+
+        if let Some((span, len)) = token {
+            if text_start < i {
+                spans.push(Span::Text(&text[text_start..i]));
+            }
+            spans.push(span);
+            i += len;
+            text_start = i;
+        } else {
+            i += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    if text_start < text.len() {
+        spans.push(Span::Text(&text[text_start..]));
+    }
+    spans
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Block, Span, parse_blocks, parse_inline};
+
+    #[test]
+    fn inline() {
+        assert_eq!(
+            parse_inline("See `Item` for **all** _known_ values"),
+            vec![
+                Span::Text("See "),
+                Span::Code("Item"),
+                Span::Text(" for "),
+                Span::Strong("all"),
+                Span::Text(" "),
+                Span::Emphasis("known"),
+                Span::Text(" values"),
+            ]
+        );
+        assert_eq!(
+            parse_inline("[docs](https://example.com/a) or https://example.com."),
+            vec![
+                Span::Link {
+                    text: "docs",
+                    url: "https://example.com/a"
+                },
+                Span::Text(" or "),
+                Span::Link {
+                    text: "https://example.com",
+                    url: "https://example.com"
+                },
+                Span::Text("."),
+            ]
+        );
+        assert_eq!(
+            parse_inline("Unknown_0 * 2"),
+            vec![Span::Text("Unknown_0 * 2")]
+        );
+    }
+
+    #[test]
+    fn blocks() {
+        assert_eq!(
+            parse_blocks("First line\nsecond line\n\n- one\n- two\n  continued\nafter"),
+            vec![
+                Block::Paragraph("First line second line".to_owned()),
+                Block::ListItem("one".to_owned()),
+                Block::ListItem("two continued".to_owned()),
+                Block::Paragraph("after".to_owned()),
+            ]
+        );
+    }
+}
@@ -1,11 +1,25 @@
-use std::{cell::RefCell, num::NonZeroU32, rc::Rc};
+use std::{
+    cell::{Cell, RefCell},
+    num::{NonZeroU32, NonZeroUsize},
+    rc::Rc,
+};
 
 use itertools::Itertools;
+use lru::LruCache;
 use nucleo_matcher::{
     Matcher, Utf32Str,
     pattern::{CaseMatching, Normalization, Pattern},
 };
 
+use super::{PromiseKind, TrackedPromise, yield_to_ui};
+
+/// Lists with more candidates left to score than this are scored in the background.
+const BACKGROUND_THRESHOLD: usize = 2048;
+/// Candidates scored between yields to the UI when scoring in the background.
+const BACKGROUND_CHUNK_SIZE: usize = 1024;
+/// Recent patterns whose results are kept, so erasing a character doesn't rescore anything.
+const CACHED_PATTERNS: NonZeroUsize = NonZeroUsize::new(16).unwrap();
+
 pub struct FuzzyMatcher(Rc<RefCell<FuzzyMatcherImpl>>);
 
 struct FuzzyMatcherImpl {
@@ -63,6 +77,27 @@ impl FuzzyMatcher {
         }
     }
 
+    /// Scores the candidates at `indices`, appending the index and score of each match.
+    fn score_into<T>(
+        &self,
+        pattern: &Pattern,
+        candidates: &[T],
+        key: fn(&T) -> &str,
+        indices: &[u32],
+        scored: &mut Vec<(u32, u32)>,
+    ) {
+        let FuzzyMatcherImpl { matcher, utf_buf } = &mut *self.0.borrow_mut();
+
+        scored.extend(indices.iter().filter_map(|&idx| {
+            pattern
+                .score(
+                    Utf32Str::new(key(&candidates[idx as usize]), utf_buf),
+                    matcher,
+                )
+                .map(|score| (idx, score))
+        }));
+    }
+
     pub fn score_one(&self, pattern: &Pattern, haystack: &str) -> Option<NonZeroU32> {
         let FuzzyMatcherImpl { matcher, utf_buf } = &mut *self.0.borrow_mut();
 
@@ -83,3 +118,231 @@ impl FuzzyMatcher {
         Pattern::parse(pattern, case_matching, Normalization::Smart)
     }
 }
+
+/// Matches a pattern against a fixed list of candidates as it's typed, for filter boxes.
+/// Patterns extending a recent one only rescore that one's matches, and big lists are scored
+/// in the background so typing stays responsive.
+pub struct IncrementalMatcher<T> {
+    candidates: Rc<Vec<T>>,
+    key: fn(&T) -> &str,
+    matcher: FuzzyMatcher,
+    /// Ranked candidate indices of recent patterns
+    cache: LruCache<String, Rc<[u32]>>,
+    /// The last results handed out, and their pattern
+    current: Option<(String, Rc<Vec<T>>)>,
+    pending: Option<PendingMatch>,
+}
+
+struct PendingMatch {
+    pattern: String,
+    promise: TrackedPromise<Vec<u32>>,
+    /// Tells the background task to stop once its results are no longer wanted
+    cancelled: Rc<Cell<bool>>,
+}
+
+impl<T: Clone + 'static> IncrementalMatcher<T> {
+    /// `candidates` should already be in the order ties are broken in, like alphabetically.
+    pub fn new(candidates: Vec<T>, key: fn(&T) -> &str) -> Self {
+        Self {
+            candidates: Rc::new(candidates),
+            key,
+            matcher: FuzzyMatcher::new(),
+            cache: LruCache::new(CACHED_PATTERNS),
+            current: None,
+            pending: None,
+        }
+    }
+
+    /// Whether results are still being scored in the background.
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// The candidates matching `pattern`, best first. An empty pattern matches everything.
+    /// While a big list is being scored in the background, the previous results are returned
+    /// along with `true`; call this again on a later frame to pick up the new ones.
+    pub fn matches(&mut self, pattern: &str) -> (Rc<Vec<T>>, bool) {
+        if let Some((current, results)) = &self.current
+            && current == pattern
+        {
+            return (results.clone(), false);
+        }
+
+        self.poll();
+        if pattern.is_empty() {
+            self.cancel_pending();
+            let results = self.candidates.clone();
+            self.current = Some((String::new(), results.clone()));
+            return (results, false);
+        }
+        if let Some(ranked) = self.cache.get(pattern).cloned() {
+            self.cancel_pending();
+            return (self.set_current(pattern, &ranked), false);
+        }
+
+        let base = self.narrowest_base(pattern);
+        let base_len = base
+            .as_ref()
+            .map_or(self.candidates.len(), |base| base.len());
+        if base_len <= BACKGROUND_THRESHOLD {
+            self.cancel_pending();
+            let indices = base.unwrap_or_else(|| (0..base_len as u32).collect());
+            let mut scored = Vec::new();
+            self.matcher.score_into(
+                &FuzzyMatcher::parse_pattern(pattern),
+                &self.candidates,
+                self.key,
+                &indices,
+                &mut scored,
+            );
+            let ranked: Rc<[u32]> = rank(scored, &self.candidates, self.key).into();
+            self.cache.put(pattern.to_owned(), ranked.clone());
+            return (self.set_current(pattern, &ranked), false);
+        }
+
+        if self.pending.as_ref().is_none_or(|p| p.pattern != pattern) {
+            self.cancel_pending();
+            self.pending = Some(self.spawn(pattern, base));
+        }
+        let stale = self
+            .current
+            .as_ref()
+            .map_or_else(|| self.candidates.clone(), |(_, results)| results.clone());
+        (stale, true)
+    }
+
+    /// Caches the background results once they're ready.
+    fn poll(&mut self) {
+        let Some(pending) = self.pending.take() else {
+            return;
+        };
+        match pending.promise.try_take() {
+            Ok(ranked) => {
+                self.cache.put(pending.pattern, ranked.into());
+            }
+            Err(promise) => {
+                self.pending = Some(PendingMatch { promise, ..pending });
+            }
+        }
+    }
+
+    fn cancel_pending(&mut self) {
+        if let Some(pending) = self.pending.take() {
+            pending.cancelled.set(true);
+        }
+    }
+
+    /// The smallest set of cached matches that every match of `pattern` is guaranteed to be in.
+    fn narrowest_base(&self, pattern: &str) -> Option<Rc<[u32]>> {
+        self.cache
+            .iter()
+            .filter(|(previous, _)| narrows(previous, pattern))
+            .min_by_key(|(_, ranked)| ranked.len())
+            .map(|(_, ranked)| ranked.clone())
+    }
+
+    fn spawn(&self, pattern: &str, base: Option<Rc<[u32]>>) -> PendingMatch {
+        let cancelled = Rc::new(Cell::new(false));
+        let candidates = self.candidates.clone();
+        let key = self.key;
+        let indices = base.unwrap_or_else(|| (0..candidates.len() as u32).collect());
+        let parsed = FuzzyMatcher::parse_pattern(pattern);
+        let promise = TrackedPromise::spawn_local({
+            let cancelled = cancelled.clone();
+            async move {
+                let matcher = FuzzyMatcher::new();
+                let mut scored = Vec::new();
+                for chunk in indices.chunks(BACKGROUND_CHUNK_SIZE) {
+                    if cancelled.get() {
+                        return Vec::new();
+                    }
+                    matcher.score_into(&parsed, &candidates, key, chunk, &mut scored);
+                    yield_to_ui().await;
+                }
+                rank(scored, &candidates, key)
+            }
+        });
+        PendingMatch {
+            pattern: pattern.to_owned(),
+            promise,
+            cancelled,
+        }
+    }
+
+    fn set_current(&mut self, pattern: &str, ranked: &[u32]) -> Rc<Vec<T>> {
+        let results = Rc::new(
+            ranked
+                .iter()
+                .map(|&idx| self.candidates[idx as usize].clone())
+                .collect_vec(),
+        );
+        self.current = Some((pattern.to_owned(), results.clone()));
+        results
+    }
+}
+
+impl<T> Drop for IncrementalMatcher<T> {
+    fn drop(&mut self) {
+        if let Some(pending) = &self.pending {
+            pending.cancelled.set(true);
+        }
+    }
+}
+
+/// Sorts matches by score, then by length, then by candidate order.
+fn rank<T>(mut scored: Vec<(u32, u32)>, candidates: &[T], key: fn(&T) -> &str) -> Vec<u32> {
+    scored.sort_by_cached_key(|&(idx, score)| {
+        (
+            std::cmp::Reverse(score),
+            key(&candidates[idx as usize]).len(),
+            idx,
+        )
+    });
+    scored.into_iter().map(|(idx, _)| idx).collect()
+}
+
+/// Whether every match of `pattern` also matches `previous`. Typing more characters only
+/// narrows the results, unless they're part of a negated atom or an escape.
+fn narrows(previous: &str, pattern: &str) -> bool {
+    !previous.is_empty() && pattern.starts_with(previous) && !pattern.contains(['!', '\\'])
+}
+
+#[cfg(test)]
+mod test {
+    use super::{FuzzyMatcher, IncrementalMatcher, narrows};
+
+    #[test]
+    fn narrowing() {
+        assert!(narrows("qu", "que"));
+        assert!(narrows("qu", "qu st"));
+        assert!(!narrows("qu", "q"));
+        assert!(!narrows("", "q"));
+        assert!(!narrows("!qu", "!que"));
+    }
+
+    #[test]
+    fn incremental_results() {
+        let names = [
+            "Quest",
+            "QuestBattle",
+            "Item",
+            "ClassJob",
+            "QuestRedo",
+            "Aetheryte",
+        ];
+        let mut matcher =
+            IncrementalMatcher::new(names.iter().map(|s| s.to_string()).collect(), |s| {
+                s.as_str()
+            });
+        let fuzzy = FuzzyMatcher::new();
+        for pattern in ["q", "qu", "que", "ques t", "qu", "e"] {
+            let (results, pending) = matcher.matches(pattern);
+            assert!(!pending);
+            assert_eq!(
+                results.iter().map(String::as_str).collect::<Vec<_>>(),
+                fuzzy.match_list(Some(pattern), &names)
+            );
+        }
+        assert_eq!(matcher.matches("").0.len(), names.len());
+    }
+}
@@ -3,18 +3,20 @@ mod cloneable_error;
 mod collapsible_side_panel;
 mod color_theme;
 mod convertible_promise;
+mod error_boundary;
 mod icon_manager;
 #[cfg(target_arch = "wasm32")]
 mod jserror;
+mod markdown;
 mod matcher;
 mod opt_slider;
+mod provider_error;
+mod request_limiter;
 mod shared_future;
 pub mod shortcut;
 mod syntax_highlighting;
-pub mod tex_loader;
 mod tracked_promise;
 mod unsend_promise;
-mod version;
 mod webreq;
 mod yield_now;
 
@@ -22,16 +24,24 @@ pub use cache::KeyedCache;
 pub use cloneable_error::CloneableResult;
 pub use collapsible_side_panel::{CollapsibleSidePanel, Side};
 pub use color_theme::ColorTheme;
+pub use common::GameVersion;
+pub use common::tex_loader;
 pub use convertible_promise::{ConvertiblePromise, PromiseKind};
+pub use error_boundary::error_boundary;
 pub use icon_manager::{IconManager, ManagedIcon};
 #[cfg(target_arch = "wasm32")]
 pub use jserror::{JsErr, JsResult};
-pub use matcher::FuzzyMatcher;
+pub use markdown::markdown;
+pub use matcher::{FuzzyMatcher, IncrementalMatcher};
 pub use opt_slider::opt_slider;
+pub use provider_error::{ProviderError, ProviderResult};
+pub use request_limiter::RequestLimiter;
 pub use shared_future::SharedFuture;
 pub use syntax_highlighting::{CodeTheme, highlight};
 pub use tracked_promise::{TrackedPromise, tick_promises};
 pub use unsend_promise::UnsendPromise;
-pub use version::GameVersion;
-pub use webreq::{HttpResponse, fetch_url, fetch_url_str, request};
-pub use yield_now::yield_to_ui;
+pub use webreq::{
+    HttpResponse, fetch_url, fetch_url_limited, fetch_url_limited_with, fetch_url_str,
+    post_json_limited, request, set_request_limit, unreachable_origin,
+};
+pub use yield_now::{sleep, yield_to_ui};
@@ -0,0 +1,154 @@
+use std::{fmt, io};
+
+use crate::i18n::Text;
+
+pub type ProviderResult<T> = Result<T, ProviderError>;
+
+/// Why a game data or schema provider couldn't deliver, so the UI can suggest a fix. Each
+/// variant carries the underlying error message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProviderError {
+    /// The file, sheet or schema doesn't exist.
+    NotFound(String),
+    /// Access to the folder was refused, like when a browser drops a folder's permission.
+    PermissionDenied(String),
+    /// The server couldn't be reached or answered with an error.
+    Network(String),
+    /// The data was read but couldn't be parsed.
+    Corrupt(String),
+    /// The data isn't available for the selected game version.
+    VersionMismatch(String),
+}
+
+impl ProviderError {
+    /// Finds the provider error behind an [`anyhow::Error`], if there is one.
+    pub fn of(error: &anyhow::Error) -> Option<&Self> {
+        error.downcast_ref()
+    }
+
+    /// Classifies the status of an unsuccessful HTTP response.
+    pub fn from_status(status: u16, message: String) -> Self {
+        match status {
+            404 | 410 => Self::NotFound(message),
+            401 | 403 => Self::PermissionDenied(message),
+            _ => Self::Network(message),
+        }
+    }
+
+    /// Classifies an error message passed back from the web worker, which only sends strings.
+    /// Falls back to `default` for messages that aren't recognized.
+    #[cfg(target_arch = "wasm32")]
+    pub fn from_worker(message: String, default: fn(String) -> Self) -> Self {
+        if message.contains("NotAllowedError") || message.contains("permission denied") {
+            Self::PermissionDenied(message)
+        } else if message.contains("NotFoundError") {
+            Self::NotFound(message)
+        } else {
+            default(message)
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Self::NotFound(message)
+            | Self::PermissionDenied(message)
+            | Self::Network(message)
+            | Self::Corrupt(message)
+            | Self::VersionMismatch(message) => message,
+        }
+    }
+
+    /// What the user can do about it.
+    pub fn hint(&self) -> Text {
+        match self {
+            Self::NotFound(_) => Text::NotFoundHint,
+            Self::PermissionDenied(_) => Text::PermissionDeniedHint,
+            Self::Network(_) => Text::NetworkHint,
+            Self::Corrupt(_) => Text::CorruptHint,
+            Self::VersionMismatch(_) => Text::VersionMismatchHint,
+        }
+    }
+
+    /// Whether going back to setup fixes it, by re-granting folder access or picking another
+    /// version.
+    pub fn needs_setup(&self) -> bool {
+        matches!(self, Self::PermissionDenied(_) | Self::VersionMismatch(_))
+    }
+}
+
+impl fmt::Display for ProviderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for ProviderError {}
+
+impl From<io::Error> for ProviderError {
+    fn from(error: io::Error) -> Self {
+        let message = error.to_string();
+        match error.kind() {
+            io::ErrorKind::NotFound => Self::NotFound(message),
+            io::ErrorKind::PermissionDenied => Self::PermissionDenied(message),
+            _ => Self::Corrupt(message),
+        }
+    }
+}
+
+impl From<ironworks::Error> for ProviderError {
+    fn from(error: ironworks::Error) -> Self {
+        let message = error.to_string();
+        match error {
+            ironworks::Error::NotFound(_) => Self::NotFound(message),
+            _ => Self::Corrupt(message),
+        }
+    }
+}
+
+/// Recovers the category of errors that passed through `anyhow`. Anything else happened while
+/// parsing, so it's treated as corrupt data.
+impl From<anyhow::Error> for ProviderError {
+    fn from(error: anyhow::Error) -> Self {
+        let error = match error.downcast::<Self>() {
+            Ok(error) => return error,
+            Err(error) => error,
+        };
+        let error = match error.downcast::<io::Error>() {
+            Ok(error) => return error.into(),
+            Err(error) => error,
+        };
+        match error.downcast::<ironworks::Error>() {
+            Ok(error) => error.into(),
+            Err(error) => Self::Corrupt(format!("{error:#}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ProviderError;
+
+    #[test]
+    fn categories_survive_anyhow() {
+        let error = anyhow::Error::from(ProviderError::from_status(404, "gone".to_string()))
+            .context("Failed to read sheet");
+        assert!(matches!(
+            ProviderError::of(&error),
+            Some(ProviderError::NotFound(_))
+        ));
+        assert_eq!(
+            ProviderError::from(error),
+            ProviderError::NotFound("gone".to_string())
+        );
+
+        let error = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        assert!(matches!(
+            ProviderError::from(anyhow::Error::from(error)),
+            ProviderError::PermissionDenied(_)
+        ));
+        assert!(matches!(
+            ProviderError::from(anyhow::anyhow!("bad header")),
+            ProviderError::Corrupt(_)
+        ));
+    }
+}
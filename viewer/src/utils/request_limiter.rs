@@ -0,0 +1,171 @@
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    num::NonZero,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+/// Caps how many requests run at once. Requests over the limit wait their turn, first come
+/// first served.
+#[derive(Clone)]
+pub struct RequestLimiter(Rc<RefCell<LimiterState>>);
+
+struct LimiterState {
+    /// `None` for no limit
+    limit: Option<NonZero<usize>>,
+    active: usize,
+    next_id: u64,
+    /// Requests waiting for a slot, oldest first
+    queue: VecDeque<(u64, Option<Waker>)>,
+}
+
+impl LimiterState {
+    fn has_room(&self) -> bool {
+        self.limit.is_none_or(|limit| self.active < limit.get())
+    }
+
+    /// The waker of the next request in line, if it can start now.
+    fn next_waker(&mut self) -> Option<Waker> {
+        if !self.has_room() {
+            return None;
+        }
+        self.queue.front_mut().and_then(|(_, waker)| waker.take())
+    }
+}
+
+impl RequestLimiter {
+    pub fn new(limit: Option<NonZero<usize>>) -> Self {
+        Self(Rc::new(RefCell::new(LimiterState {
+            limit,
+            active: 0,
+            next_id: 0,
+            queue: VecDeque::new(),
+        })))
+    }
+
+    pub fn set_limit(&self, limit: Option<NonZero<usize>>) {
+        let waker = {
+            let mut state = self.0.borrow_mut();
+            state.limit = limit;
+            state.next_waker()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    /// Waits for a free slot. The slot is held until the returned permit is dropped.
+    pub fn acquire(&self) -> Acquire {
+        let mut state = self.0.borrow_mut();
+        let id = state.next_id;
+        state.next_id += 1;
+        state.queue.push_back((id, None));
+        Acquire {
+            limiter: self.clone(),
+            id,
+            acquired: false,
+        }
+    }
+
+    /// Wakes the next request in line, outside of the state's borrow since waking can poll.
+    fn wake_next(&self) {
+        let waker = self.0.borrow_mut().next_waker();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+pub struct Acquire {
+    limiter: RequestLimiter,
+    id: u64,
+    acquired: bool,
+}
+
+impl Future for Acquire {
+    type Output = RequestPermit;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        {
+            let mut state = this.limiter.0.borrow_mut();
+            let is_next = state.queue.front().is_some_and(|(id, _)| *id == this.id);
+            if !is_next || !state.has_room() {
+                if let Some((_, waker)) = state.queue.iter_mut().find(|(id, _)| *id == this.id) {
+                    *waker = Some(cx.waker().clone());
+                }
+                return Poll::Pending;
+            }
+            state.queue.pop_front();
+            state.active += 1;
+            this.acquired = true;
+        }
+        // The request after this one may fit as well
+        this.limiter.wake_next();
+        Poll::Ready(RequestPermit(this.limiter.clone()))
+    }
+}
+
+impl Drop for Acquire {
+    fn drop(&mut self) {
+        if self.acquired {
+            return;
+        }
+        self.limiter
+            .0
+            .borrow_mut()
+            .queue
+            .retain(|(id, _)| *id != self.id);
+        self.limiter.wake_next();
+    }
+}
+
+/// A slot of a [`RequestLimiter`], freed on drop.
+pub struct RequestPermit(RequestLimiter);
+
+impl Drop for RequestPermit {
+    fn drop(&mut self) {
+        self.0.0.borrow_mut().active -= 1;
+        self.0.wake_next();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        num::NonZero,
+        pin::pin,
+        task::{Context, Poll},
+    };
+
+    use futures_util::task::noop_waker_ref;
+
+    use super::RequestLimiter;
+
+    #[test]
+    fn queues_in_order() {
+        let mut cx = Context::from_waker(noop_waker_ref());
+        let limiter = RequestLimiter::new(NonZero::new(1));
+
+        let mut first = pin!(limiter.acquire());
+        let mut second = pin!(limiter.acquire());
+        let mut third = pin!(limiter.acquire());
+        let Poll::Ready(permit) = first.as_mut().poll(&mut cx) else {
+            panic!("the first request should start immediately");
+        };
+        assert!(third.as_mut().poll(&mut cx).is_pending());
+        assert!(second.as_mut().poll(&mut cx).is_pending());
+
+        drop(permit);
+        assert!(third.as_mut().poll(&mut cx).is_pending());
+        let Poll::Ready(permit) = second.as_mut().poll(&mut cx) else {
+            panic!("the second request should start once the first finishes");
+        };
+
+        limiter.set_limit(None);
+        assert!(third.as_mut().poll(&mut cx).is_ready());
+        drop(permit);
+    }
+}
@@ -12,6 +12,11 @@ impl<T: Clone + 'static> SharedFuture<T> {
         Self(future.boxed_local().shared())
     }
 
+    /// The output, if the future already finished.
+    pub fn peek(&self) -> Option<&T> {
+        self.0.peek()
+    }
+
     pub fn into_shared(self) -> Shared<LocalBoxFuture<'static, T>> {
         self.0
     }
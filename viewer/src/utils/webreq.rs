@@ -1,8 +1,45 @@
+use std::{cell::RefCell, num::NonZero, time::Duration};
+
 use ehttp::{Method, Request};
+use url::Url;
+
+use super::{ProviderError, ProviderResult, RequestLimiter, sleep};
+
+/// How long to wait before each retry of a request that couldn't reach its server.
+const RETRY_DELAYS: [Duration; 3] = [
+    Duration::from_millis(500),
+    Duration::from_secs(1),
+    Duration::from_secs(2),
+];
+
+thread_local! {
+    /// Shared by every web provider, since they usually talk to the same server.
+    static PROVIDER_REQUESTS: RequestLimiter = RequestLimiter::new(None);
+    /// The server requests last failed to reach, until one of its requests gets a response.
+    static UNREACHABLE: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Sets how many requests [`fetch_url_limited`] runs at once, so self-hosted servers that
+/// rate-limit aren't flooded. `None` for no limit.
+pub fn set_request_limit(limit: Option<NonZero<usize>>) {
+    PROVIDER_REQUESTS.with(|limiter| limiter.set_limit(limit));
+}
+
+/// The origin (like `https://exd.camora.dev`) of the server requests last failed to reach even
+/// after retrying, if it hasn't responded since.
+pub fn unreachable_origin() -> Option<String> {
+    UNREACHABLE.with_borrow(Clone::clone)
+}
+
+fn origin(url: &str) -> String {
+    Url::parse(url).map_or_else(|_| url.to_owned(), |url| url.origin().ascii_serialization())
+}
 
 pub struct HttpResponse {
     pub status: u16,
     pub ok: bool,
+    pub content_type: Option<String>,
+    pub etag: Option<String>,
     pub bytes: Vec<u8>,
 }
 
@@ -12,6 +49,18 @@ impl HttpResponse {
     }
 }
 
+impl From<ehttp::Response> for HttpResponse {
+    fn from(resp: ehttp::Response) -> Self {
+        Self {
+            status: resp.status,
+            ok: resp.ok,
+            content_type: resp.content_type().map(str::to_owned),
+            etag: resp.headers.get("etag").map(str::to_owned),
+            bytes: resp.bytes,
+        }
+    }
+}
+
 pub async fn request(
     method: &str,
     url: impl ToString,
@@ -31,32 +80,102 @@ pub async fn request(
         .await
         .map_err(|msg| anyhow::anyhow!(msg))?;
 
-    Ok(HttpResponse {
-        status: resp.status,
-        ok: resp.ok,
-        bytes: resp.bytes,
-    })
+    Ok(resp.into())
 }
 
-pub async fn fetch_url(url: impl ToString) -> anyhow::Result<Vec<u8>> {
-    let resp = ehttp::fetch_async(Request::get(url))
-        .await
-        .map_err(|msg| anyhow::anyhow!(msg))?;
+pub async fn fetch_url(url: impl ToString) -> ProviderResult<Vec<u8>> {
+    fetch_request(Request::get(url)).await
+}
 
+async fn fetch_request(req: Request) -> ProviderResult<Vec<u8>> {
+    let resp = send_request(req).await?;
     if !resp.ok {
-        anyhow::bail!(
-            "Response not OK ({}{}{}): {}",
+        return Err(ProviderError::from_status(
             resp.status,
-            if resp.status_text.is_empty() { "" } else { " " },
-            resp.status_text,
-            String::from_utf8_lossy(&resp.bytes)
-        );
+            format!(
+                "Response not OK ({}{}{}): {}",
+                resp.status,
+                if resp.status_text.is_empty() { "" } else { " " },
+                resp.status_text,
+                String::from_utf8_lossy(&resp.bytes)
+            ),
+        ));
     }
 
     Ok(resp.bytes)
 }
 
-pub async fn fetch_url_str(url: impl ToString) -> anyhow::Result<String> {
+/// Sends `req`, retrying while its server can't be reached. Responses are returned whatever
+/// their status.
+async fn send_request(req: Request) -> ProviderResult<ehttp::Response> {
+    let origin = origin(&req.url);
+    let mut delays = RETRY_DELAYS.into_iter();
+    let resp = loop {
+        let resp = ehttp::fetch_async(req.clone()).await;
+        let retry = match &resp {
+            Err(_) => true,
+            // Proxies in front of a server that's restarting
+            Ok(resp) => matches!(resp.status, 502..=504),
+        };
+        // Once a server is known to be down, fail fast instead of retrying every request
+        let known_down = UNREACHABLE.with_borrow(|o| o.as_ref() == Some(&origin));
+        match delays.next().filter(|_| retry && !known_down) {
+            Some(delay) => {
+                log::debug!("Retrying {} in {delay:?}", req.url);
+                sleep(delay).await;
+            }
+            None => break resp,
+        }
+    };
+    UNREACHABLE.with_borrow_mut(|unreachable| match &resp {
+        Ok(_) if unreachable.as_ref() == Some(&origin) => {
+            log::info!("{origin} is reachable again");
+            *unreachable = None;
+        }
+        Ok(_) => {}
+        Err(e) => {
+            if unreachable.as_ref() != Some(&origin) {
+                log::warn!("{origin} is unreachable: {e}");
+            }
+            *unreachable = Some(origin);
+        }
+    });
+    resp.map_err(ProviderError::Network)
+}
+
+/// [`fetch_url`] for web providers, waiting its turn if too many of their requests are running.
+pub async fn fetch_url_limited(url: impl ToString) -> ProviderResult<Vec<u8>> {
+    let limiter = PROVIDER_REQUESTS.with(RequestLimiter::clone);
+    let _permit = limiter.acquire().await;
+    fetch_url(url).await
+}
+
+/// [`fetch_url_limited`] with extra request headers, like `If-None-Match`. Unsuccessful
+/// responses are returned too, so callers can handle a `304 Not Modified`.
+pub async fn fetch_url_limited_with(
+    url: impl ToString,
+    headers: &[(&str, &str)],
+) -> ProviderResult<HttpResponse> {
+    let limiter = PROVIDER_REQUESTS.with(RequestLimiter::clone);
+    let _permit = limiter.acquire().await;
+    let mut req = Request::get(url);
+    for (key, value) in headers {
+        req.headers.insert(*key, *value);
+    }
+    Ok(send_request(req).await?.into())
+}
+
+/// [`fetch_url_limited`] for a POST of a JSON body. Unsuccessful responses are returned too,
+/// so callers can tell an endpoint the server doesn't have from one that failed.
+pub async fn post_json_limited(url: impl ToString, body: Vec<u8>) -> ProviderResult<HttpResponse> {
+    let limiter = PROVIDER_REQUESTS.with(RequestLimiter::clone);
+    let _permit = limiter.acquire().await;
+    let mut req = Request::post(url, body);
+    req.headers.insert("Content-Type", "application/json");
+    Ok(send_request(req).await?.into())
+}
+
+pub async fn fetch_url_str(url: impl ToString) -> ProviderResult<String> {
     let bytes = fetch_url(url).await?;
-    Ok(String::from_utf8(bytes)?)
+    String::from_utf8(bytes).map_err(|e| ProviderError::Corrupt(e.to_string()))
 }
@@ -3,6 +3,7 @@
 use std::{
     pin::Pin,
     task::{Context, Poll},
+    time::Duration,
 };
 
 /// A future that yields immediately, allowing the executor to process other tasks.
@@ -18,10 +19,46 @@ pub async fn yield_to_ui() {
 
 #[cfg(target_arch = "wasm32")]
 pub async fn yield_to_ui() {
+    sleep(Duration::ZERO).await;
+}
+
+/// Waits for `duration` without blocking the UI thread.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn sleep(duration: Duration) {
+    use std::{
+        sync::{Arc, Mutex},
+        task::Waker,
+    };
+
+    let state = Arc::new(Mutex::new((false, None::<Waker>)));
+    let timer_state = state.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(duration);
+        let mut state = timer_state.lock().unwrap();
+        state.0 = true;
+        if let Some(waker) = state.1.take() {
+            waker.wake();
+        }
+    });
+    std::future::poll_fn(|cx| {
+        let mut state = state.lock().unwrap();
+        if state.0 {
+            Poll::Ready(())
+        } else {
+            state.1 = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    })
+    .await;
+}
+
+#[cfg(target_arch = "wasm32")]
+pub async fn sleep(duration: Duration) {
     use eframe::wasm_bindgen::prelude::*;
     use wasm_bindgen_futures::JsFuture;
     use web_sys::window;
 
+    let timeout = i32::try_from(duration.as_millis()).unwrap_or(i32::MAX);
     let promise = web_sys::js_sys::Promise::new(&mut |resolve, _| {
         let closure = Closure::once_into_js(move || {
             resolve.call0(&JsValue::NULL).unwrap();
@@ -30,7 +67,7 @@ pub async fn yield_to_ui() {
             .unwrap()
             .set_timeout_with_callback_and_timeout_and_arguments_0(
                 closure.as_ref().unchecked_ref(),
-                0,
+                timeout,
             )
             .unwrap();
     });
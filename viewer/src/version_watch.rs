@@ -0,0 +1,305 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Duration, Instant};
+#[cfg(target_arch = "wasm32")]
+use web_time::{Duration, Instant};
+
+use egui::RichText;
+
+use crate::{
+    data::web::{VersionInfo, WebFileProvider},
+    i18n::{Text, tr},
+    settings::{BACKEND_CONFIG, BackendConfig, InstallLocation},
+    utils::{GameVersion, TrackedPromise, fetch_url, unreachable_origin},
+};
+
+/// How often the web backend's version list is fetched again.
+pub const REVALIDATE_INTERVAL: Duration = Duration::from_secs(10 * 60);
+/// How long a local install has to stay untouched after changing before it's offered to be
+/// reloaded, so it isn't reloaded halfway through a patch.
+#[cfg(not(target_arch = "wasm32"))]
+const SETTLE_TIME: Duration = Duration::from_secs(5);
+/// How long to wait before first checking whether an unreachable server is back. Doubles
+/// after every failed check, up to [`RECONNECT_MAX_DELAY`].
+const RECONNECT_MIN_DELAY: Duration = Duration::from_secs(5);
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(2 * 60);
+
+/// Periodically asks the web backend for its versions during a session, to tell the user when
+/// a newer game version than the one being viewed comes out.
+#[derive(Default)]
+pub struct VersionWatch {
+    promise: Option<TrackedPromise<anyhow::Result<VersionInfo>>>,
+    last_check: Option<Instant>,
+    /// The version being viewed. Taken from the first check if the config follows the latest.
+    current: Option<GameVersion>,
+    /// A newer latest version advertised since
+    available: Option<GameVersion>,
+    dismissed: bool,
+}
+
+impl VersionWatch {
+    /// Starts over after the backend was (re)loaded.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Revalidates the version list once it's stale. Does nothing unless the backend is the
+    /// web one.
+    pub fn poll(&mut self, ctx: &egui::Context) {
+        let Some(BackendConfig {
+            location: InstallLocation::Web(url, region, version),
+            ..
+        }) = BACKEND_CONFIG.get(ctx)
+        else {
+            return;
+        };
+        let Some(slug) = region.slug() else {
+            return;
+        };
+        if self.current.is_none() {
+            self.current = version;
+        }
+
+        if let Some(promise) = &self.promise {
+            let Some(result) = promise.try_get() else {
+                return;
+            };
+            match result {
+                Ok(info) => {
+                    let current = self.current.get_or_insert_with(|| info.latest.clone());
+                    if info.latest > *current && self.available.as_ref() != Some(&info.latest) {
+                        log::info!("New game version available: {}", info.latest);
+                        self.available = Some(info.latest.clone());
+                        self.dismissed = false;
+                    }
+                }
+                Err(e) => log::warn!("Failed to revalidate versions: {e}"),
+            }
+            self.promise = None;
+        }
+
+        let elapsed = self.last_check.map(|t| t.elapsed());
+        if elapsed.is_none_or(|e| e >= REVALIDATE_INTERVAL) {
+            self.last_check = Some(Instant::now());
+            self.promise = Some(TrackedPromise::spawn_local(async move {
+                WebFileProvider::get_versions(&url, slug).await
+            }));
+            ctx.request_repaint_after(REVALIDATE_INTERVAL);
+        } else if let Some(elapsed) = elapsed {
+            ctx.request_repaint_after(REVALIDATE_INTERVAL - elapsed);
+        }
+    }
+
+    /// Shows a notification while a newer version is available. Returns the version to switch
+    /// to once the user asks for it.
+    pub fn draw(&mut self, ctx: &egui::Context) -> Option<GameVersion> {
+        if self.dismissed {
+            return None;
+        }
+        let available = self.available.clone()?;
+        let mut ret = None;
+        egui::Window::new(tr(ctx, Text::NewGameVersion))
+            .id(egui::Id::new("version-watch"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, [-8.0, -8.0])
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(format!("Version {available} is now available."));
+                if let Some(current) = &self.current {
+                    ui.label(RichText::new(format!("Viewing {current}")).weak());
+                }
+                ui.horizontal(|ui| {
+                    if ui.button(tr(ctx, Text::SwitchToNewVersion)).clicked() {
+                        ret = Some(available.clone());
+                    }
+                    if ui.button("Dismiss").clicked() {
+                        self.dismissed = true;
+                    }
+                });
+            });
+        ret
+    }
+}
+
+/// Watches a local install during a session, to tell the user when the game was patched and
+/// what's shown may be out of date.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Default)]
+pub struct InstallWatch {
+    /// The watched install location, with no watcher if it couldn't be watched
+    watcher: Option<(String, Option<crate::data::sqpack::SqpackWatcher>)>,
+    /// When the notification was last dismissed
+    dismissed: Option<Instant>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl InstallWatch {
+    /// Starts over after the backend was (re)loaded.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Starts watching the install once it's loaded. Does nothing unless the backend is a
+    /// local install.
+    pub fn poll(&mut self, ctx: &egui::Context) {
+        let Some(BackendConfig {
+            location: InstallLocation::Sqpack(path),
+            ..
+        }) = BACKEND_CONFIG.get(ctx)
+        else {
+            self.watcher = None;
+            return;
+        };
+        if self
+            .watcher
+            .as_ref()
+            .is_some_and(|(watched, _)| *watched == path)
+        {
+            return;
+        }
+        let watcher = crate::data::sqpack::SqpackWatcher::new(&path, ctx.clone())
+            .inspect_err(|e| log::warn!("Failed to watch {path} for updates: {e}"))
+            .ok();
+        self.watcher = Some((path, watcher));
+    }
+
+    /// Shows a notification once the install changed and settled. Returns true once the user
+    /// asks to reload it.
+    pub fn draw(&mut self, ctx: &egui::Context) -> bool {
+        let Some(last_change) = self
+            .watcher
+            .as_ref()
+            .and_then(|(_, watcher)| watcher.as_ref()?.last_change())
+        else {
+            return false;
+        };
+        if self
+            .dismissed
+            .is_some_and(|dismissed| dismissed >= last_change)
+        {
+            return false;
+        }
+        let elapsed = last_change.elapsed();
+        if elapsed < SETTLE_TIME {
+            ctx.request_repaint_after(SETTLE_TIME - elapsed);
+            return false;
+        }
+
+        let mut ret = false;
+        egui::Window::new(tr(ctx, Text::GameFilesChanged))
+            .id(egui::Id::new("install-watch"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, [-8.0, -8.0])
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label("The game files changed while they were open, likely from a patch.");
+                ui.label(RichText::new("Sheets shown until reloading may be out of date.").weak());
+                ui.horizontal(|ui| {
+                    if ui.button(tr(ctx, Text::ReloadGameData)).clicked() {
+                        ret = true;
+                    }
+                    if ui.button("Dismiss").clicked() {
+                        self.dismissed = Some(Instant::now());
+                    }
+                });
+            });
+        ret
+    }
+}
+
+/// Checks whether a server that web requests failed to reach is back, waiting longer between
+/// every check. Sheets already downloaded can still be browsed meanwhile.
+#[derive(Default)]
+pub struct ConnectionWatch {
+    /// The unreachable server
+    origin: Option<String>,
+    /// Resolves once the server responds, with anything
+    promise: Option<TrackedPromise<()>>,
+    next_check: Option<Instant>,
+    delay: Duration,
+}
+
+impl ConnectionWatch {
+    /// Checks the server again once it's time. Returns true once it's reachable again, so
+    /// whatever failed to load meanwhile can be loaded again.
+    pub fn poll(&mut self, ctx: &egui::Context) -> bool {
+        let unreachable = unreachable_origin();
+        if unreachable.is_none() {
+            // Any request reaching the server clears it, not just ours
+            let reconnected = self.origin.is_some();
+            *self = Self::default();
+            return reconnected;
+        }
+        if self.origin != unreachable {
+            *self = Self {
+                origin: unreachable,
+                delay: RECONNECT_MIN_DELAY,
+                next_check: Some(Instant::now() + RECONNECT_MIN_DELAY),
+                promise: None,
+            };
+        }
+
+        if let Some(promise) = &self.promise {
+            if promise.try_get().is_none() {
+                return false;
+            }
+            // Still unreachable, or it would've been cleared
+            self.promise = None;
+            self.delay = (self.delay * 2).min(RECONNECT_MAX_DELAY);
+            self.next_check = Some(Instant::now() + self.delay);
+        }
+
+        let remaining = self.next_check.map_or(Duration::ZERO, |t| {
+            t.saturating_duration_since(Instant::now())
+        });
+        if remaining.is_zero() {
+            self.check_now();
+        } else {
+            ctx.request_repaint_after(remaining);
+        }
+        false
+    }
+
+    fn check_now(&mut self) {
+        let Some(origin) = self.origin.clone() else {
+            return;
+        };
+        self.next_check = None;
+        self.promise = Some(TrackedPromise::spawn_local(async move {
+            // Any response means the server is up, even an error page
+            let _ = fetch_url(origin).await;
+        }));
+    }
+
+    /// Shows a banner while a server is unreachable.
+    pub fn draw(&mut self, ui: &mut egui::Ui) {
+        let Some(origin) = self.origin.clone() else {
+            return;
+        };
+        let ctx = ui.ctx().clone();
+        egui::containers::panel::Panel::top("connection_watch").show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    format!("{} {origin}", tr(&ctx, Text::ServerUnreachable)),
+                );
+                ui.label(
+                    RichText::new("Sheets that were opened before can still be browsed.").weak(),
+                );
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if self.promise.is_some() {
+                        ui.spinner();
+                    } else if ui.button(tr(&ctx, Text::RetryNow)).clicked() {
+                        self.check_now();
+                    } else if let Some(next_check) = self.next_check {
+                        let remaining = next_check.saturating_duration_since(Instant::now());
+                        ui.label(
+                            RichText::new(format!("Retrying in {}s", remaining.as_secs() + 1))
+                                .weak(),
+                        );
+                        ctx.request_repaint_after(Duration::from_secs(1));
+                    }
+                });
+            });
+        });
+    }
+}
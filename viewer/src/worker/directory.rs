@@ -8,9 +8,10 @@ use futures_util::{FutureExt, StreamExt};
 use itertools::Itertools;
 use wasm_bindgen_futures::{JsFuture, stream::JsStream};
 use web_sys::{
-    File, FileSystemDirectoryHandle, FileSystemFileHandle, FileSystemHandle, FileSystemHandleKind,
-    FileSystemHandlePermissionDescriptor, FileSystemPermissionMode, FileSystemWritableFileStream,
-    PermissionState,
+    File, FileSystemDirectoryHandle, FileSystemFileHandle, FileSystemGetDirectoryOptions,
+    FileSystemGetFileOptions, FileSystemHandle, FileSystemHandleKind,
+    FileSystemHandlePermissionDescriptor, FileSystemPermissionMode, FileSystemRemoveOptions,
+    FileSystemWritableFileStream, PermissionState, WorkerGlobalScope, js_sys,
 };
 
 use crate::utils::{JsErr, JsResult};
@@ -175,6 +176,66 @@ impl DynamicDirectory {
     }
 }
 
+/// The root of the origin private file system. Only available inside the worker.
+pub async fn opfs_root() -> JsResult<FileSystemDirectoryHandle> {
+    let scope = js_sys::global()
+        .dyn_into::<WorkerGlobalScope>()
+        .map_err(|_| JsErr::msg("global scope is not a WorkerGlobalScope"))?;
+    JsFuture::from(scope.navigator().storage().get_directory())
+        .await?
+        .dyn_into::<FileSystemDirectoryHandle>()
+        .map_err(|_| JsErr::msg("entry is not a FileSystemDirectoryHandle"))
+}
+
+/// Gets the directory `name` inside `parent`, creating it if it doesn't exist.
+pub async fn create_directory_handle(
+    parent: &FileSystemDirectoryHandle,
+    name: &str,
+) -> JsResult<FileSystemDirectoryHandle> {
+    let options = FileSystemGetDirectoryOptions::new();
+    options.set_create(true);
+    JsFuture::from(parent.get_directory_handle_with_options(name, &options))
+        .await?
+        .dyn_into::<FileSystemDirectoryHandle>()
+        .map_err(|_| JsErr::msg("entry is not a FileSystemDirectoryHandle"))
+}
+
+/// Recursively removes the entry `name` inside `parent`, if it exists.
+pub async fn remove_entry(parent: &FileSystemDirectoryHandle, name: &str) -> JsResult<()> {
+    let options = FileSystemRemoveOptions::new();
+    options.set_recursive(true);
+    JsFuture::from(parent.remove_entry_with_options(name, &options)).await?;
+    Ok(())
+}
+
+/// Gets the file at `path` relative to `dir`, creating it and any missing parent directories.
+pub async fn create_file_handle(
+    dir: &FileSystemDirectoryHandle,
+    path: impl AsRef<Path>,
+) -> JsResult<FileSystemFileHandle> {
+    let components = path.as_ref().components().collect_vec();
+    let mut current_dir = dir.clone();
+    for component in &components[..components.len().saturating_sub(1)] {
+        match component {
+            std::path::Component::Normal(name) => {
+                current_dir =
+                    create_directory_handle(&current_dir, &name.to_string_lossy()).await?;
+            }
+            _ => return Err(JsErr::msg("invalid path component")),
+        }
+    }
+
+    let Some(std::path::Component::Normal(filename)) = components.last() else {
+        return Err(JsErr::msg("invalid file path"));
+    };
+    let options = FileSystemGetFileOptions::new();
+    options.set_create(true);
+    JsFuture::from(current_dir.get_file_handle_with_options(&filename.to_string_lossy(), &options))
+        .await?
+        .dyn_into::<FileSystemFileHandle>()
+        .map_err(|_| JsErr::msg("entry is not a FileSystemFileHandle"))
+}
+
 pub async fn get_file_blob(handle: FileSystemFileHandle) -> JsResult<File> {
     JsFuture::from(handle.get_file())
         .await?
@@ -22,6 +22,8 @@ pub enum WorkerRequest {
     SchemaSetup(WorkerDirectory),
     SchemaRequestGet(String),
     SchemaRequestStore((String, String)),
+    /// Folder name and `(path, text)` pairs to write into the origin private file system
+    SchemaSnapshot((String, Vec<(String, String)>)),
 
     VerifyFolder((WorkerDirectory, bool)),
 }
@@ -42,6 +44,7 @@ pub enum WorkerResponse {
     SchemaSetup(Result<(), String>),
     SchemaRequestGet(Result<String, String>),
     SchemaRequestStore(Result<(), String>),
+    SchemaSnapshot(Result<(), String>),
 
     VerifyFolder(Result<(), String>),
 }
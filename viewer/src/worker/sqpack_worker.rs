@@ -3,6 +3,7 @@ use std::{cell::RefCell, convert::Infallible, rc::Rc};
 use eframe::wasm_bindgen::JsCast;
 use futures_util::lock::Mutex;
 use gloo_worker::{HandlerId, Worker, WorkerScope};
+use image::DynamicImage;
 use indexed_db::Database;
 use ironworks::{
     Ironworks,
@@ -12,9 +13,13 @@ use wasm_bindgen_futures::spawn_local;
 use web_sys::{FileSystemDirectoryHandle, js_sys::JsString};
 
 use crate::{
+    data::loose::LooseFiles,
     stopwatch::Stopwatch,
     utils::tex_loader,
-    worker::directory::{DynamicDirectory, get_file_str, set_file_str},
+    worker::directory::{
+        DynamicDirectory, create_directory_handle, create_file_handle, get_file_str, opfs_root,
+        remove_entry, set_file_str,
+    },
 };
 
 use super::{
@@ -89,6 +94,29 @@ impl SqpackWorker {
             .await
             .map_err(|e| format!("Failed to add folder: {e}"))
     }
+
+    async fn write_schema_snapshot(
+        folder: String,
+        files: Vec<(String, String)>,
+    ) -> Result<(), String> {
+        let root = opfs_root()
+            .await
+            .map_err(|e| format!("Failed to open origin private file system: {e}"))?;
+        // Start from scratch so schemas deleted upstream don't linger
+        let _ = remove_entry(&root, &folder).await;
+        let dir = create_directory_handle(&root, &folder)
+            .await
+            .map_err(|e| format!("Failed to create folder {folder}: {e}"))?;
+        for (path, text) in files {
+            let handle = create_file_handle(&dir, &path)
+                .await
+                .map_err(|e| format!("Failed to create {path}: {e}"))?;
+            set_file_str(handle, &text)
+                .await
+                .map_err(|e| format!("Failed to write {path}: {e}"))?;
+        }
+        Self::add_db_folder_impl(STORE_SCHEMA, dir).await
+    }
 }
 
 impl Worker for SqpackWorker {
@@ -153,14 +181,15 @@ impl Worker for SqpackWorker {
             WorkerRequest::DataRequestFile(path) => {
                 let _stop = Stopwatch::new(format!("SqpackWorker::DataRequestFile({path:?})"));
                 if let Some(inst) = self.install_instance.borrow().as_ref() {
-                    let file = inst.0.file::<Vec<u8>>(&path).map_err(|e| e.to_string());
+                    let file = inst.file(&path);
                     scope.respond(id, WorkerResponse::DataRequestFile(file));
                 }
             }
             WorkerRequest::DataRequestTexture(path) => {
                 let _stop = Stopwatch::new(format!("SqpackWorker::DataRequestTexture({path:?})"));
                 if let Some(inst) = self.install_instance.borrow().as_ref() {
-                    let data = tex_loader::read(&inst.0, &path)
+                    let data = inst
+                        .texture(&path)
                         .map(|data| {
                             let data = data.to_rgba8();
                             (data.width(), data.height(), data.into_vec())
@@ -174,7 +203,7 @@ impl Worker for SqpackWorker {
                 if let Some(inst) = self.install_instance.borrow().as_ref() {
                     let mut result = Vec::with_capacity(paths.len());
                     for path in paths {
-                        result.push(inst.0.exists(&path).map_err(|e| e.to_string()));
+                        result.push(inst.exists(&path));
                     }
                     let result: Result<Vec<bool>, String> = result.into_iter().collect();
                     scope.respond(id, WorkerResponse::DataRequestExists(result));
@@ -255,6 +284,15 @@ impl Worker for SqpackWorker {
                     }
                 });
             }
+            WorkerRequest::SchemaSnapshot((folder, files)) => {
+                let _stop = Stopwatch::new("SqpackWorker::SchemaSnapshot");
+                let scope = scope.clone();
+                spawn_local(async move {
+                    let _stop = _stop;
+                    let ret = Self::write_schema_snapshot(folder, files).await;
+                    scope.respond(id, WorkerResponse::SchemaSnapshot(ret));
+                });
+            }
             WorkerRequest::VerifyFolder((handle, is_readwrite)) => {
                 let _stop = Stopwatch::new("SqpackWorker::VerifyFolder");
                 let scope = scope.clone();
@@ -277,16 +315,44 @@ impl Worker for SqpackWorker {
     }
 }
 
-struct InstallInstance(pub Ironworks<SqPack<VInstall<DirectoryVfs>>>);
+/// A sqpack install, or loose files extracted from one when the folder has them.
+enum InstallInstance {
+    Sqpack(Ironworks<SqPack<VInstall<DirectoryVfs>>>),
+    Loose(LooseFiles<DirectoryVfs>),
+}
 
 impl InstallInstance {
     async fn new(handle: FileSystemDirectoryHandle) -> std::io::Result<Self> {
-        let resource = VInstall::at_sqpack(
-            DirectoryVfs::new(handle)
-                .await
-                .map_err(std::io::Error::other)?,
-        );
-        let resource = SqPack::new(resource);
-        Ok(Self(Ironworks::new().with_resource(resource)))
+        let vfs = DirectoryVfs::new(handle)
+            .await
+            .map_err(std::io::Error::other)?;
+        Ok(match LooseFiles::detect(vfs) {
+            Ok(files) => Self::Loose(files),
+            Err(vfs) => {
+                let resource = SqPack::new(VInstall::at_sqpack(vfs));
+                Self::Sqpack(Ironworks::new().with_resource(resource))
+            }
+        })
+    }
+
+    fn file(&self, path: &str) -> Result<Vec<u8>, String> {
+        match self {
+            Self::Sqpack(ironworks) => ironworks.file::<Vec<u8>>(path).map_err(|e| e.to_string()),
+            Self::Loose(files) => files.read(path).map_err(|e| e.to_string()),
+        }
+    }
+
+    fn texture(&self, path: &str) -> anyhow::Result<DynamicImage> {
+        match self {
+            Self::Sqpack(ironworks) => tex_loader::read(ironworks, path),
+            Self::Loose(files) => files.texture(path),
+        }
+    }
+
+    fn exists(&self, path: &str) -> Result<bool, String> {
+        match self {
+            Self::Sqpack(ironworks) => ironworks.exists(path).map_err(|e| e.to_string()),
+            Self::Loose(files) => Ok(files.exists(path)),
+        }
     }
 }
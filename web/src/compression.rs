@@ -0,0 +1,101 @@
+use std::io::Write;
+
+use flate2::write::GzEncoder;
+
+use crate::config::Compression;
+
+/// Encodings sheet data is kept precompressed in, best first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    Brotli,
+    Gzip,
+}
+
+impl Encoding {
+    const ALL: [Self; 2] = [Self::Brotli, Self::Gzip];
+
+    /// The `Content-Encoding` value.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Brotli => "br",
+            Self::Gzip => "gzip",
+        }
+    }
+
+    /// The best encoding an `Accept-Encoding` header allows, if any.
+    pub fn preferred(accept_encoding: &str) -> Option<Self> {
+        let accepted = accept_encoding
+            .split(',')
+            .filter_map(|entry| {
+                let mut parts = entry.split(';').map(str::trim);
+                let name = parts.next()?;
+                let quality = parts
+                    .find_map(|param| param.strip_prefix("q="))
+                    .map_or(Some(1.0), |q| q.parse::<f32>().ok())?;
+                Some((name, quality))
+            })
+            .collect::<Vec<_>>();
+        let quality = |encoding: Self| {
+            accepted
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(encoding.name()))
+                .or_else(|| accepted.iter().find(|(name, _)| *name == "*"))
+                .map_or(0.0, |(_, q)| *q)
+        };
+        Self::ALL
+            .into_iter()
+            .map(|encoding| (encoding, quality(encoding)))
+            .filter(|(_, q)| *q > 0.0)
+            .fold(
+                None,
+                |best: Option<(Self, f32)>, (encoding, q)| match best {
+                    Some((_, best_q)) if best_q >= q => best,
+                    _ => Some((encoding, q)),
+                },
+            )
+            .map(|(encoding, _)| encoding)
+    }
+
+    pub fn compress(self, data: &[u8], config: &Compression) -> std::io::Result<Vec<u8>> {
+        match self {
+            Self::Brotli => {
+                let mut output = Vec::new();
+                let params = brotli::enc::BrotliEncoderParams {
+                    quality: config.brotli_quality.min(11) as i32,
+                    ..Default::default()
+                };
+                brotli::BrotliCompress(&mut &data[..], &mut output, &params)?;
+                Ok(output)
+            }
+            Self::Gzip => {
+                let mut encoder = GzEncoder::new(
+                    Vec::new(),
+                    flate2::Compression::new(config.gzip_level.min(9)),
+                );
+                encoder.write_all(data)?;
+                encoder.finish()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Encoding;
+
+    #[test]
+    fn picks_the_best_accepted_encoding() {
+        assert_eq!(
+            Encoding::preferred("gzip, deflate, br, zstd"),
+            Some(Encoding::Brotli)
+        );
+        assert_eq!(Encoding::preferred("gzip"), Some(Encoding::Gzip));
+        assert_eq!(
+            Encoding::preferred("br;q=0.5, gzip;q=0.8"),
+            Some(Encoding::Gzip)
+        );
+        assert_eq!(Encoding::preferred("br;q=0, *"), Some(Encoding::Gzip));
+        assert_eq!(Encoding::preferred("identity"), None);
+        assert_eq!(Encoding::preferred(""), None);
+    }
+}
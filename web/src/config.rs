@@ -10,6 +10,19 @@ pub struct AssetCache {
     pub file_ttl_minutes: u64,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Compression {
+    /// Compresses responses for clients that accept it.
+    pub enabled: bool,
+    /// Keeps EXD files compressed alongside the raw ones, so they're only compressed once.
+    pub precompress_exd: bool,
+    /// 0-9
+    pub gzip_level: u32,
+    /// 0-11
+    pub brotli_quality: u32,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(default)]
 pub struct Config {
@@ -19,6 +32,7 @@ pub struct Config {
     pub log_access_format: Option<String>,
     pub cache: ServerBuilder,
     pub assets: AssetCache,
+    pub compression: Compression,
     pub file_readahead: usize,
     pub api_workers: usize,
     pub github_client_id: String,
@@ -36,6 +50,17 @@ impl Default for AssetCache {
     }
 }
 
+impl Default for Compression {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            precompress_exd: true,
+            gzip_level: 6,
+            brotli_quality: 9,
+        }
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -47,6 +72,7 @@ impl Default for Config {
             log_access_format: None,
             cache: ServerBuilder::default(),
             assets: AssetCache::default(),
+            compression: Compression::default(),
             file_readahead: 0x800000, // 8 MiB
             api_workers: 1,
             github_client_id: String::new(),
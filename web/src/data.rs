@@ -5,12 +5,12 @@ use std::{
     time::Duration,
 };
 
+use common::api::{RepositoryInfo, VersionInfo};
 use ironworks::{
     Ironworks,
     sqpack::{SqPack, VInstall, Vfs},
 };
 use mini_moka::sync::{Cache, CacheBuilder};
-use serde::Serialize;
 use tokio::runtime::Handle;
 use xiv_cache::{
     builder::ServerBuilder,
@@ -20,38 +20,31 @@ use xiv_cache::{
 };
 use xiv_core::file::{slug::Slug, version::GameVersion};
 
-use crate::{blocking_stream::BlockingReader, config::AssetCache, smart_bufreader::SmartBufReader};
-
-#[derive(Debug, Clone, Serialize)]
-pub struct VersionInfo {
-    pub latest: GameVersion,
-    pub versions: Vec<GameVersion>,
-}
-
-impl From<SlugData> for VersionInfo {
-    fn from(value: SlugData) -> Self {
-        Self {
-            latest: value.latest_version,
-            versions: value.versions,
-        }
-    }
-}
+use crate::{
+    blocking_stream::BlockingReader,
+    compression::Encoding,
+    config::{AssetCache, Compression},
+    smart_bufreader::SmartBufReader,
+};
 
-#[derive(Debug, Clone, Serialize)]
-pub struct RepositoryInfo {
-    pub slug: Slug,
-    pub name: String,
-    pub latest: GameVersion,
+fn version_info(value: SlugData) -> anyhow::Result<VersionInfo> {
+    Ok(VersionInfo {
+        latest: (&value.latest_version).try_into()?,
+        versions: value
+            .versions
+            .iter()
+            .map(common::GameVersion::try_from)
+            .collect::<anyhow::Result<_>>()?,
+        icons: true,
+    })
 }
 
-impl RepositoryInfo {
-    fn from_slug_data(slug: Slug, value: SlugData) -> Self {
-        Self {
-            slug,
-            name: value.repository,
-            latest: value.latest_version,
-        }
-    }
+fn repository_info(slug: Slug, value: SlugData) -> anyhow::Result<RepositoryInfo> {
+    Ok(RepositoryInfo {
+        slug: slug.to_string(),
+        name: value.repository,
+        latest: (&value.latest_version).try_into()?,
+    })
 }
 
 type CacheIronworks = Ironworks<SqPack<VInstall<CacheVfs>>>;
@@ -60,14 +53,17 @@ type CacheIronworks = Ironworks<SqPack<VInstall<CacheVfs>>>;
 pub struct GameData {
     cache: Server,
     readahead_size: usize,
+    compression: Compression,
     ironworks_cache: Cache<(Slug, GameVersion), Arc<CacheIronworks>>,
     file_cache: Cache<(Slug, GameVersion, String), Arc<Vec<u8>>>,
+    compressed_cache: Cache<(Slug, GameVersion, String, Encoding), Arc<Vec<u8>>>,
 }
 
 impl GameData {
     pub async fn new(
         cache_config: ServerBuilder,
         asset_config: AssetCache,
+        compression: Compression,
         readahead_size: usize,
     ) -> anyhow::Result<Self> {
         let server = cache_config.build().await?;
@@ -75,17 +71,33 @@ impl GameData {
         Ok(Self {
             cache: server,
             readahead_size,
+            compression,
             ironworks_cache: CacheBuilder::new(asset_config.version_capacity)
                 .time_to_live(Duration::from_secs(60 * asset_config.version_ttl_minutes))
                 .build(),
             file_cache: CacheBuilder::new(asset_config.file_capacity)
                 .time_to_live(Duration::from_secs(60 * asset_config.file_ttl_minutes))
                 .build(),
+            compressed_cache: CacheBuilder::new(asset_config.file_capacity)
+                .time_to_live(Duration::from_secs(60 * asset_config.file_ttl_minutes))
+                .build(),
         })
     }
 
-    pub async fn versions(&self, slug: Slug) -> Option<VersionInfo> {
-        self.cache.get_slug(slug).await.map(VersionInfo::from).ok()
+    /// `None` if the slug isn't known.
+    pub async fn versions(&self, slug: Slug) -> anyhow::Result<Option<VersionInfo>> {
+        match self.cache.get_slug(slug).await {
+            Ok(slug_data) => version_info(slug_data).map(Some),
+            Err(_) => Ok(None),
+        }
+    }
+
+    pub async fn latest_version(&self, slug: Slug) -> Option<GameVersion> {
+        self.cache
+            .get_slug(slug)
+            .await
+            .ok()
+            .map(|slug_data| slug_data.latest_version)
     }
 
     pub async fn repositories(&self) -> anyhow::Result<Vec<RepositoryInfo>> {
@@ -93,7 +105,7 @@ impl GameData {
         let mut repositories = Vec::with_capacity(slugs.len());
         for slug in slugs {
             if let Ok(slug_data) = self.cache.get_slug(slug).await {
-                repositories.push(RepositoryInfo::from_slug_data(slug, slug_data));
+                repositories.push(repository_info(slug, slug_data)?);
             }
         }
         Ok(repositories)
@@ -153,6 +165,41 @@ impl GameData {
         Ok(data)
     }
 
+    /// The file compressed with `encoding`, if it's an EXD file and those are precompressed.
+    /// Other files are left to be compressed as they're sent.
+    pub async fn get_encoded(
+        &self,
+        slug: Slug,
+        version: GameVersion,
+        file: String,
+        encoding: Option<Encoding>,
+    ) -> Result<(Arc<Vec<u8>>, Option<Encoding>), ironworks::Error> {
+        let encoding = encoding.filter(|_| {
+            self.compression.enabled && self.compression.precompress_exd && file.ends_with(".exd")
+        });
+        let Some(encoding) = encoding else {
+            return Ok((self.get(slug, version, file).await?, None));
+        };
+
+        let key = (slug, version, file, encoding);
+        if let Some(ret) = self.compressed_cache.get(&key) {
+            return Ok((ret, Some(encoding)));
+        }
+        let (slug, version, file, encoding) = key;
+
+        let raw = self.get(slug, version.clone(), file.clone()).await?;
+        // Compressing sheets at high levels takes long enough to stall the runtime
+        let compression = self.compression.clone();
+        let data = tokio::task::spawn_blocking(move || encoding.compress(&raw, &compression))
+            .await
+            .map_err(|e| ironworks::Error::Resource(Box::new(e)))?
+            .map_err(|e| ironworks::Error::Resource(Box::new(e)))?;
+        let data = Arc::new(data);
+        self.compressed_cache
+            .insert((slug, version, file, encoding), data.clone());
+        Ok((data, Some(encoding)))
+    }
+
     pub async fn exists(
         &self,
         slug: Slug,
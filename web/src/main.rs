@@ -1,4 +1,5 @@
 mod blocking_stream;
+mod compression;
 mod config;
 mod data;
 mod queue;
@@ -9,7 +10,7 @@ use ::config::{Config, Environment, File, FileFormat};
 use actix_cors::Cors;
 use actix_web::{
     App, HttpServer,
-    middleware::{Condition, Logger, NormalizePath, TrailingSlash},
+    middleware::{Compress, Condition, Logger, NormalizePath, TrailingSlash},
     web::Data,
 };
 use actix_web_helmet::{Helmet, XContentTypeOptions};
@@ -71,6 +72,7 @@ async fn main() -> Result<(), ServerError> {
         GameData::new(
             config.cache.clone(),
             config.assets.clone(),
+            config.compression.clone(),
             config.file_readahead,
         )
         .await?,
@@ -102,9 +104,14 @@ async fn main() -> Result<(), ServerError> {
                         origin.to_str().is_ok_and(is_dev_origin)
                     })
                     .allowed_methods(vec!["GET", "POST"])
-                    .allowed_headers(vec!["Content-Type"]),
+                    .allowed_headers(vec!["Content-Type", "If-None-Match"])
+                    .expose_headers(vec!["ETag"]),
             )
             .wrap(NormalizePath::new(TrailingSlash::Always))
+            .wrap(Condition::new(
+                server_config.compression.enabled,
+                Compress::default(),
+            ))
             .wrap(Condition::new(
                 server_config.metrics_server_addr.is_some(),
                 server_prometheus.clone(),
@@ -1,25 +1,26 @@
 use std::sync::{Arc, OnceLock};
 
 use async_channel::Sender;
+use common::api::{RepositoryInfo, VersionInfo};
 use tokio::{
     runtime::Handle, select, sync::oneshot, task::JoinHandle
 };
 use tokio_util::sync::CancellationToken;
 use xiv_core::file::{slug::Slug, version::GameVersion};
 
-use crate::data::{GameData, RepositoryInfo, VersionInfo};
+use crate::{compression::Encoding, data::GameData};
 
 #[derive(Debug, Clone)]
 pub enum RequestData {
     Versions(Slug),
-    GetFile(Slug, Option<GameVersion>, String),
+    GetFile(Slug, Option<GameVersion>, String, Option<Encoding>),
     Exists(Slug, Option<GameVersion>, Vec<String>),
     Repositories,
 }
 
 pub enum Response {
-    Versions(Option<VersionInfo>),
-    GetFile(Result<Arc<Vec<u8>>, ironworks::Error>),
+    Versions(anyhow::Result<Option<VersionInfo>>),
+    GetFile(Result<(Arc<Vec<u8>>, Option<Encoding>), ironworks::Error>),
     Exists(Result<Vec<bool>, ironworks::Error>),
     Repositories(anyhow::Result<Vec<RepositoryInfo>>),
 }
@@ -83,16 +84,16 @@ impl MessageQueue {
                                         RequestData::Repositories => {
                                             Response::Repositories(this.data.repositories().await)
                                         }
-                                        RequestData::GetFile(slug, version, path) => {
+                                        RequestData::GetFile(slug, version, path, encoding) => {
                                             let version = match version {
                                                 Some(v) => Ok(v),
                                                 None => {
-                                                    this.data.versions(slug).await.map(|v| v.latest).ok_or_else(|| ironworks::Error::NotFound(ironworks::ErrorValue::Other("No version info available".to_string())))
+                                                    this.data.latest_version(slug).await.ok_or_else(|| ironworks::Error::NotFound(ironworks::ErrorValue::Other("No version info available".to_string())))
                                                 }
                                             };
                                             let result = match version {
                                                 Ok(version) => {
-                                                    this.data.get(slug, version, path).await
+                                                    this.data.get_encoded(slug, version, path, encoding).await
                                                 }
                                                 Err(e) => Err(e),
                                             };
@@ -103,7 +104,7 @@ impl MessageQueue {
                                             let version = match version {
                                                 Some(v) => Ok(v),
                                                 None => {
-                                                    this.data.versions(slug).await.map(|v| v.latest).ok_or_else(|| ironworks::Error::NotFound(ironworks::ErrorValue::Other("No version info available".to_string())))
+                                                    this.data.latest_version(slug).await.ok_or_else(|| ironworks::Error::NotFound(ironworks::ErrorValue::Other("No version info available".to_string())))
                                                 }
                                             };
                                             let result = match version {
@@ -139,7 +140,7 @@ impl MessageQueue {
         Ok(this)
     }
 
-    pub async fn versions(&self, slug: Slug) -> Option<VersionInfo> {
+    pub async fn versions(&self, slug: Slug) -> anyhow::Result<Option<VersionInfo>> {
         let (tx, rx) = oneshot::channel();
         self.0.tx.send(Request {
             data: RequestData::Versions(slug),
@@ -148,7 +149,7 @@ impl MessageQueue {
 
         match rx.await {
             Ok(Response::Versions(info)) => info,
-            _ => None,
+            _ => Err(anyhow::anyhow!("Failed to get versions")),
         }
     }
 
@@ -180,10 +181,11 @@ impl MessageQueue {
         }
     }
 
-    pub async fn get_file(&self, slug: Slug, version: Option<GameVersion>, path: String) -> Result<Arc<Vec<u8>>, ironworks::Error> {
+    /// The file, compressed with `encoding` if it was kept that way.
+    pub async fn get_file(&self, slug: Slug, version: Option<GameVersion>, path: String, encoding: Option<Encoding>) -> Result<(Arc<Vec<u8>>, Option<Encoding>), ironworks::Error> {
         let (tx, rx) = oneshot::channel();
         self.0.tx.send(Request {
-            data: RequestData::GetFile(slug, version, path),
+            data: RequestData::GetFile(slug, version, path, encoding),
             tx,
         }).await.expect("Failed to send request to message queue");
 
@@ -1,5 +1,7 @@
 use std::{
     fmt::Display,
+    hash::{DefaultHasher, Hash, Hasher},
+    io::Cursor,
     str::FromStr,
     sync::{Arc, LazyLock, Mutex},
     time::{Duration, Instant},
@@ -7,21 +9,30 @@ use std::{
 
 use actix_web::post;
 use actix_web::{
-    HttpResponse, Result,
+    HttpRequest, HttpResponse, Result,
     body::{EitherBody, MessageBody},
     dev::{HttpServiceFactory, ServiceResponse},
-    error::{ErrorBadRequest, ErrorInternalServerError},
+    error::{ErrorBadRequest, ErrorInternalServerError, ErrorNotFound},
     get,
-    http::header::ContentDisposition,
+    http::header::{self, ContentDisposition, ETag, EntityTag, IfNoneMatch},
     middleware::{ErrorHandlerResponse, ErrorHandlers},
     web::{self, Bytes},
 };
 use actix_web_lab::header::{CacheControl, CacheDirective};
+use common::{
+    api::{BatchRequest, ExistsResponse, IconFormat, IconQuery, RepositoriesResponse},
+    batch::{self, Part},
+    path::get_icon_path,
+    tex_loader,
+};
+use futures_util::{StreamExt, stream};
+use image::ImageFormat;
+use ironworks::file::{File, tex::Texture};
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 use xiv_core::file::{slug::Slug, version::GameVersion};
 
-use crate::{config::Config, data::RepositoryInfo, queue::MessageQueue};
+use crate::{compression::Encoding, config::Config, queue::MessageQueue};
 
 pub fn service() -> impl HttpServiceFactory {
     web::scope("/api")
@@ -30,6 +41,8 @@ pub fn service() -> impl HttpServiceFactory {
         .service(get_repositories)
         .service(get_versions_slug)
         .service(get_exists_slug)
+        .service(post_batch_slug)
+        .service(get_icon_slug)
         .service(get_file_slug)
         .service(get_songs)
         .wrap(
@@ -39,11 +52,6 @@ pub fn service() -> impl HttpServiceFactory {
         )
 }
 
-#[derive(Debug, Clone, Serialize)]
-struct RepositoriesInfo {
-    repositories: Vec<RepositoryInfo>,
-}
-
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
 pub enum QueryGameVersion {
     #[default]
@@ -80,11 +88,36 @@ impl Display for QueryGameVersion {
     }
 }
 
+/// Weak, since the same file can be sent compressed in different ways. `None` if the path
+/// can't be put in one.
+fn file_etag(version: &str, path: &str) -> Option<EntityTag> {
+    let tag = format!("{version}/{path}");
+    tag.bytes()
+        .all(|c| c == 0x21 || (0x23..=0x7e).contains(&c) || c >= 0x80)
+        .then(|| EntityTag::new_weak(tag))
+}
+
+fn is_unmodified(if_none_match: &IfNoneMatch, etag: &EntityTag) -> bool {
+    match if_none_match {
+        IfNoneMatch::Any => true,
+        IfNoneMatch::Items(tags) => tags.iter().any(|tag| tag.weak_eq(etag)),
+    }
+}
+
+fn not_modified(etag: EntityTag, directives: Vec<CacheDirective>) -> HttpResponse {
+    HttpResponse::NotModified()
+        .insert_header(ETag(etag))
+        .insert_header(CacheControl(directives))
+        .finish()
+}
+
 async fn serve_file(
     data: &MessageQueue,
     slug: Slug,
     version: QueryGameVersion,
     path: String,
+    accept_encoding: Option<Encoding>,
+    if_none_match: Option<IfNoneMatch>,
 ) -> Result<HttpResponse> {
     // Handle empty path case
     if path.is_empty() {
@@ -105,24 +138,79 @@ async fn serve_file(
         directives.push(CacheDirective::MaxAge(60 * 60 * 24));
     }
 
-    let data = data.get_file(slug, resolved_ver, path.clone()).await;
+    // A file never changes within a version, so the version it resolves to is enough to tell
+    // whether the client's copy is still current
+    let etag_version = match &version {
+        QueryGameVersion::Latest => data
+            .versions(slug)
+            .await
+            .ok()
+            .flatten()
+            .map(|v| v.latest.to_string()),
+        QueryGameVersion::Specific(version) => Some(version.to_string()),
+    };
+    let etag = etag_version.and_then(|version| file_etag(&version, &path));
+    // A tag we handed out means the file was there, but `*` only holds if it still is
+    if let Some(etag) = &etag
+        && let Some(if_none_match @ IfNoneMatch::Items(_)) = &if_none_match
+        && is_unmodified(if_none_match, etag)
+    {
+        return Ok(not_modified(etag.clone(), directives));
+    }
+
+    let data = data
+        .get_file(slug, resolved_ver, path.clone(), accept_encoding)
+        .await;
     match data {
-        Ok(data) => Ok(HttpResponse::Ok()
-            .insert_header(ContentDisposition::attachment(file_name))
-            .insert_header(CacheControl(directives))
-            .body(data.as_ref().clone())),
-        Err(err) if matches!(err, ironworks::Error::NotFound(_)) => Err(ErrorBadRequest(err)),
+        Ok((data, encoding)) => {
+            if let Some(etag) = &etag
+                && if_none_match.is_some_and(|if_none_match| is_unmodified(&if_none_match, etag))
+            {
+                return Ok(not_modified(etag.clone(), directives));
+            }
+
+            let mut response = HttpResponse::Ok();
+            response
+                .insert_header(ContentDisposition::attachment(file_name))
+                .insert_header(CacheControl(directives));
+            if let Some(etag) = etag {
+                response.insert_header(ETag(etag));
+            }
+            // Already compressed, which also keeps the Compress middleware from redoing it
+            if let Some(encoding) = encoding {
+                response
+                    .insert_header((header::CONTENT_ENCODING, encoding.name()))
+                    .insert_header((header::VARY, "Accept-Encoding"));
+            }
+            Ok(response.body(data.as_ref().clone()))
+        }
+        Err(err) if matches!(err, ironworks::Error::NotFound(_)) => Err(ErrorNotFound(err)),
         Err(err) => Err(ErrorInternalServerError(err)),
     }
 }
 
 #[get("/{slug}/{version}/{path:.*}/")]
 async fn get_file_slug(
+    req: HttpRequest,
     data: web::Data<MessageQueue>,
     path_info: web::Path<(Slug, QueryGameVersion, String)>,
+    if_none_match: Option<web::Header<IfNoneMatch>>,
 ) -> Result<HttpResponse> {
     let (slug, version, path) = path_info.into_inner();
-    serve_file(&data, slug, version, path).await
+    let accept_encoding = req
+        .headers()
+        .get(header::ACCEPT_ENCODING)
+        .and_then(|value| value.to_str().ok())
+        .and_then(Encoding::preferred);
+    serve_file(
+        &data,
+        slug,
+        version,
+        path,
+        accept_encoding,
+        if_none_match.map(web::Header::into_inner),
+    )
+    .await
 }
 
 #[derive(Debug, Deserialize)]
@@ -131,11 +219,6 @@ struct ExistsQuery {
     files: String,
 }
 
-#[derive(Debug, Serialize)]
-struct ExistsResponse {
-    exists: Vec<bool>,
-}
-
 async fn serve_exists(
     data: &MessageQueue,
     slug: Slug,
@@ -183,10 +266,186 @@ async fn get_exists_slug(
     serve_exists(&data, slug, version, &query.files).await
 }
 
+async fn serve_batch(
+    data: MessageQueue,
+    slug: Slug,
+    version: QueryGameVersion,
+    files: Vec<String>,
+) -> Result<HttpResponse> {
+    if files.is_empty() {
+        return Err(ErrorBadRequest("No files specified"));
+    }
+    if files.len() > batch::MAX_FILES {
+        return Err(ErrorBadRequest(format!(
+            "At most {} files can be requested at once",
+            batch::MAX_FILES
+        )));
+    }
+    if files
+        .iter()
+        .any(|path| path.is_empty() || path.contains(char::is_control))
+    {
+        return Err(ErrorBadRequest("Invalid file path"));
+    }
+
+    let resolved_ver = match &version {
+        QueryGameVersion::Latest => None,
+        QueryGameVersion::Specific(version) => Some(version.clone()),
+    };
+
+    // Each file is sent as soon as it's read, so the client isn't kept waiting on the slowest
+    let parts = stream::iter(files)
+        .then(move |path| {
+            let data = data.clone();
+            let version = resolved_ver.clone();
+            async move {
+                let part = match data.get_file(slug, version, path.clone(), None).await {
+                    Ok((file, _)) => Part {
+                        path,
+                        status: 200,
+                        data: file.as_ref().clone(),
+                    },
+                    Err(err) => Part {
+                        path,
+                        status: if matches!(err, ironworks::Error::NotFound(_)) {
+                            404
+                        } else {
+                            500
+                        },
+                        data: err.to_string().into_bytes(),
+                    },
+                };
+                Ok::<_, actix_web::Error>(Bytes::from(part.encode()))
+            }
+        })
+        .chain(stream::once(async { Ok(Bytes::from(Part::encode_end())) }));
+
+    Ok(HttpResponse::Ok()
+        .content_type(batch::content_type())
+        .streaming(parts))
+}
+
+#[post("/{slug}/{version}/batch/")]
+async fn post_batch_slug(
+    data: web::Data<MessageQueue>,
+    path_info: web::Path<(Slug, QueryGameVersion)>,
+    body: web::Json<BatchRequest>,
+) -> Result<HttpResponse> {
+    let (slug, version) = path_info.into_inner();
+    serve_batch(
+        data.get_ref().clone(),
+        slug,
+        version,
+        body.into_inner().files,
+    )
+    .await
+}
+
+/// Weak, like [`file_etag`], but taken from the icon's texture so it still matches in versions
+/// where the icon didn't change.
+fn icon_etag(path: &str, file: &[u8], format: IconFormat) -> EntityTag {
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    file.hash(&mut hasher);
+    let extension = match format {
+        IconFormat::Png => "png",
+        IconFormat::Webp => "webp",
+    };
+    EntityTag::new_weak(format!("{:016x}.{extension}", hasher.finish()))
+}
+
+async fn serve_icon(
+    data: &MessageQueue,
+    slug: Slug,
+    version: QueryGameVersion,
+    icon_id: u32,
+    query: IconQuery,
+    if_none_match: Option<IfNoneMatch>,
+) -> Result<HttpResponse> {
+    let resolved_ver = match &version {
+        QueryGameVersion::Latest => None,
+        QueryGameVersion::Specific(version) => Some(version.clone()),
+    };
+
+    let mut directives = vec![CacheDirective::Public];
+    if version != QueryGameVersion::Latest {
+        directives.push(CacheDirective::Immutable);
+        directives.push(CacheDirective::MaxAge(60 * 60 * 24 * 365));
+    } else {
+        directives.push(CacheDirective::MaxAge(60 * 60 * 24));
+    }
+
+    // Not every icon has a high resolution variant
+    let mut paths = vec![get_icon_path(icon_id, false)];
+    if query.hires {
+        paths.insert(0, get_icon_path(icon_id, true));
+    }
+    let mut texture = None;
+    for path in paths {
+        match data
+            .get_file(slug, resolved_ver.clone(), path.clone(), None)
+            .await
+        {
+            Ok((file, _)) => {
+                texture = Some((path, file));
+                break;
+            }
+            Err(ironworks::Error::NotFound(_)) => {}
+            Err(err) => return Err(ErrorInternalServerError(err)),
+        }
+    }
+    let Some((path, file)) = texture else {
+        return Err(ErrorNotFound(format!("Icon {icon_id} not found")));
+    };
+
+    // Checked before converting, which is the expensive part
+    let etag = icon_etag(&path, &file, query.format);
+    if if_none_match.is_some_and(|if_none_match| is_unmodified(&if_none_match, &etag)) {
+        return Ok(not_modified(etag, directives));
+    }
+
+    let (format, content_type) = match query.format {
+        IconFormat::Png => (ImageFormat::Png, "image/png"),
+        IconFormat::Webp => (ImageFormat::WebP, "image/webp"),
+    };
+    let image = web::block(move || {
+        let texture = Texture::read(Cursor::new(file.as_ref().clone()))?;
+        tex_loader::write(tex_loader::decode(texture, &path)?, format)
+    })
+    .await?
+    .map_err(ErrorInternalServerError)?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header(ETag(etag))
+        .insert_header(CacheControl(directives))
+        .content_type(content_type)
+        .body(image))
+}
+
+#[get("/{slug}/{version}/icon/{id}/")]
+async fn get_icon_slug(
+    data: web::Data<MessageQueue>,
+    path_info: web::Path<(Slug, QueryGameVersion, u32)>,
+    query: web::Query<IconQuery>,
+    if_none_match: Option<web::Header<IfNoneMatch>>,
+) -> Result<HttpResponse> {
+    let (slug, version, icon_id) = path_info.into_inner();
+    serve_icon(
+        &data,
+        slug,
+        version,
+        icon_id,
+        query.into_inner(),
+        if_none_match.map(web::Header::into_inner),
+    )
+    .await
+}
+
 async fn serve_versions(data: &MessageQueue, slug: Slug) -> Result<HttpResponse> {
     Ok(HttpResponse::Ok().json(
         data.versions(slug)
             .await
+            .map_err(ErrorInternalServerError)?
             .ok_or(ErrorBadRequest("No version info available"))?,
     ))
 }
@@ -205,7 +464,7 @@ async fn get_repositories(data: web::Data<MessageQueue>) -> Result<HttpResponse>
         .repositories()
         .await
         .map_err(ErrorInternalServerError)?;
-    Ok(HttpResponse::Ok().json(RepositoriesInfo { repositories }))
+    Ok(HttpResponse::Ok().json(RepositoriesResponse { repositories }))
 }
 
 #[derive(Debug, Serialize)]
@@ -398,3 +657,34 @@ async fn log_error2<B: MessageBody + 'static>(
 
     Ok(res)
 }
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::header::{EntityTag, IfNoneMatch};
+    use common::api::IconFormat;
+
+    use super::{file_etag, icon_etag, is_unmodified};
+
+    #[test]
+    fn etags_match_only_the_same_version_and_file() {
+        let etag = file_etag("2025.01.01.0000.0000", "exd/Item_0_en.exd").unwrap();
+        let sent = |tags: &[EntityTag]| IfNoneMatch::Items(tags.to_vec());
+        assert!(is_unmodified(&sent(std::slice::from_ref(&etag)), &etag));
+        assert!(is_unmodified(&IfNoneMatch::Any, &etag));
+
+        let newer = file_etag("2025.02.01.0000.0000", "exd/Item_0_en.exd").unwrap();
+        let other = file_etag("2025.01.01.0000.0000", "exd/Item_0_de.exd").unwrap();
+        assert!(!is_unmodified(&sent(&[newer, other]), &etag));
+
+        assert!(file_etag("2025.01.01.0000.0000", "exd/a \"b\".exd").is_none());
+    }
+
+    #[test]
+    fn icon_etags_follow_the_texture() {
+        let path = "ui/icon/000000/000001_hr1.tex";
+        let etag = icon_etag(path, b"texture", IconFormat::Png);
+        assert!(etag.weak_eq(&icon_etag(path, b"texture", IconFormat::Png)));
+        assert!(!etag.weak_eq(&icon_etag(path, b"changed", IconFormat::Png)));
+        assert!(!etag.weak_eq(&icon_etag(path, b"texture", IconFormat::Webp)));
+    }
+}